@@ -0,0 +1,109 @@
+//! How fast sRGB colors are classified, one at a time and in bulk, as for
+//! the pixels of images and video frames, and how fast Munsell colors are
+//! looked up.
+//!
+//! Run with `cargo bench`.
+
+#![allow(clippy::needless_return)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use palette::Srgb;
+
+use iscc_nbs_validator::classify::SrgbClassifier;
+use iscc_nbs_validator::spans::SpanTable;
+use iscc_nbs_validator::{Dataset, MunsellColor, MunsellHue};
+
+/// A 512×512 test image of gradients in red and green, with blue stepping
+/// across the diagonal, in steps coarse enough for many pixels to share a
+/// color, as in a photograph.
+fn gradient_image() -> Vec<Srgb<u8>> {
+    let mut pixels = Vec::new();
+    for y in 0..512u32 {
+        for x in 0..512u32 {
+            pixels.push(Srgb::new(
+                (x / 16 * 8) as u8,
+                (y / 16 * 8) as u8,
+                ((x + y) / 64 * 32) as u8,
+            ));
+        }
+    }
+    return pixels;
+}
+
+fn classify(c: &mut Criterion) {
+    let dataset = Dataset::bundled();
+    let pixels = gradient_image();
+
+    let mut group = c.benchmark_group("classify");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("classify_srgb", |b| {
+        b.iter(|| dataset.classify_srgb(black_box(Srgb::new(0.8, 0.2, 0.3))))
+    });
+
+    group.throughput(Throughput::Elements(pixels.len() as u64));
+    // every distinct color is classified anew
+    group.bench_function("classify_slice", |b| {
+        b.iter(|| SrgbClassifier::new(&dataset).classify_slice(black_box(&pixels)))
+    });
+    // as for the later frames of a video, all colors have been seen before
+    let mut classifier = SrgbClassifier::new(&dataset);
+    classifier.classify_slice(&pixels);
+    group.bench_function("classify_slice_seen", |b| {
+        b.iter(|| classifier.classify_slice(black_box(&pixels)))
+    });
+    group.finish();
+}
+
+/// Looking up Munsell colors by trying every block, against the lookup
+/// table and the [`SpanTable`].
+fn lookup(c: &mut Criterion) {
+    let dataset = Dataset::bundled();
+    let table = SpanTable::new(&dataset);
+    let colors = (0..1000)
+        .map(|i| {
+            MunsellColor::new(
+                MunsellHue::new((i * 37 % 1000) as f32 / 10.0),
+                (i % 97) as f32 / 10.0,
+                (i % 181) as f32 / 10.0,
+            )
+        })
+        .collect::<Vec<MunsellColor>>();
+
+    let mut group = c.benchmark_group("lookup");
+    group.throughput(Throughput::Elements(colors.len() as u64));
+    group.bench_function("blocks", |b| {
+        b.iter(|| {
+            black_box(&colors)
+                .iter()
+                .map(|x| {
+                    dataset
+                        .blocks()
+                        .iter()
+                        .find(|block| dataset.block_contains(block, x))
+                        .map(|block| block.color_id)
+                })
+                .collect::<Vec<Option<u32>>>()
+        })
+    });
+    group.bench_function("lookup_table", |b| {
+        b.iter(|| {
+            black_box(&colors)
+                .iter()
+                .map(|x| dataset.lookup_table().classify_munsell(x))
+                .collect::<Vec<Option<u32>>>()
+        })
+    });
+    group.bench_function("span_table", |b| {
+        b.iter(|| {
+            black_box(&colors)
+                .iter()
+                .map(|x| table.classify(x))
+                .collect::<Vec<Option<u32>>>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, classify, lookup);
+criterion_main!(benches);
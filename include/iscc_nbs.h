@@ -0,0 +1,44 @@
+/*
+ * C API of the iscc-nbs-validator crate, built with the `ffi` feature.
+ *
+ * Colors are level 3 color ids, from 1 to iscc_nbs_name_count(); 0 means no
+ * color. Strings are owned by the library and live as long as the program.
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+#ifndef ISCC_NBS_H
+#define ISCC_NBS_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The id of the level 3 color an 8-bit sRGB color falls into, or 0. */
+uint32_t iscc_nbs_lookup_rgb(uint8_t r, uint8_t g, uint8_t b);
+
+/* The id of the level 3 color of a Munsell specification like "7.5YR 6/8",
+ * or 0 if it isn't one or falls outside the solid. */
+uint32_t iscc_nbs_lookup_munsell(const char *spec);
+
+/* The number of level 3 colors; their ids run from 1 to this. */
+uint32_t iscc_nbs_name_count(void);
+
+/* The name of a level 3 color, like "Vivid pink", or NULL. */
+const char *iscc_nbs_name(uint32_t color_id);
+
+/* The abbreviation of a level 3 color, like "viv.Pk", or NULL. */
+const char *iscc_nbs_abbr(uint32_t color_id);
+
+/* Write the representative sRGB color of a level 3 color into rgb, as three
+ * bytes; returns false, writing nothing, for unknown ids. */
+bool iscc_nbs_srgb(uint32_t color_id, uint8_t *rgb);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* ISCC_NBS_H */
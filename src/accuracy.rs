@@ -0,0 +1,217 @@
+//! Comparison of the Munsell to sRGB conversion of this crate with a table
+//! of reference colors, like the Munsell to sRGB correspondence published
+//! by Paul Centore, to show how close the renotation interpolation and the
+//! chromatic adaptation come to it, and to catch regressions in them.
+//!
+//! The reference table is not included; it is read from a file with a
+//! Munsell specification and an sRGB color, from 0 to 255, per line, like
+//! `5R 4/14,197,33,56` or `5R 4/14 197 33 56`.
+
+use palette::{ColorDifference, IntoColor, Lab, Srgb};
+
+use crate::munsell::{MunsellColor, MunsellFormat, MunsellHue};
+
+/// A Munsell color and the sRGB color a reference conversion gives for it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReferenceColor {
+    pub munsell: MunsellColor,
+    pub srgb: Srgb<u8>,
+}
+
+/// A reference color and what this crate converts its Munsell color to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConversionError {
+    pub reference: ReferenceColor,
+    /// The color converted from the Munsell specification, with channels
+    /// that may be outside of `[0, 1]`.
+    pub computed: Srgb,
+    /// The CIEDE2000 color difference from the reference color.
+    pub delta_e: f32,
+}
+
+/// How far the conversion is from the reference for the colors of one hue
+/// page, in CIEDE2000 units.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HueAccuracy {
+    /// The hue, like `2.5R`, or `N` for the neutrals.
+    pub hue: String,
+    pub count: usize,
+    pub mean: f32,
+    pub max: f32,
+}
+
+/// Parse a table of reference colors, with a Munsell specification and the
+/// red, green and blue channels, from 0 to 255, per line, separated by
+/// commas or spaces. Blank lines, lines starting with `#` and a header line
+/// are skipped.
+pub fn parse_reference_table(text: &str) -> Result<Vec<ReferenceColor>, String> {
+    let mut table = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields = line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(|x| x.trim_matches('"'))
+            .filter(|x| !x.is_empty())
+            .collect::<Vec<&str>>();
+        if fields.len() < 4 {
+            // a header
+            if i == 0 {
+                continue;
+            }
+            return Err(format!(
+                "line {} is not a Munsell specification and three channels",
+                i + 1
+            ));
+        }
+        let (spec, channels) = fields.split_at(fields.len() - 3);
+        let channels = channels
+            .iter()
+            .map(|x| x.parse::<f32>().ok().filter(|x| (0.0..=255.0).contains(x)))
+            .collect::<Option<Vec<f32>>>();
+        let channels = match channels {
+            Some(v) => v,
+            None if i == 0 => continue,
+            None => {
+                return Err(format!(
+                    "the channels on line {} are not numbers from 0 to 255",
+                    i + 1
+                ))
+            }
+        };
+        let munsell = match MunsellColor::parse(&spec.join(" ")) {
+            Some(v) => v,
+            None if i == 0 => continue,
+            None => {
+                return Err(format!(
+                    "'{}' on line {} is not a Munsell specification",
+                    spec.join(" "),
+                    i + 1
+                ))
+            }
+        };
+        let [red, green, blue] = [0, 1, 2].map(|c| channels[c].round() as u8);
+        table.push(ReferenceColor {
+            munsell,
+            srgb: Srgb::new(red, green, blue),
+        });
+    }
+    return Ok(table);
+}
+
+/// Convert the Munsell color of every reference color with
+/// [`MunsellColor::to_display_lch`], and compare it with the reference, in
+/// the order of `reference`.
+pub fn conversion_errors(reference: &[ReferenceColor]) -> Vec<ConversionError> {
+    return reference
+        .iter()
+        .map(|x| {
+            let lab: Lab = x.munsell.to_display_lch().into_color();
+            let expected: Lab = x.srgb.into_format::<f32>().into_color();
+            return ConversionError {
+                reference: x.clone(),
+                computed: lab.into_color(),
+                delta_e: lab.get_color_difference(&expected),
+            };
+        })
+        .collect();
+}
+
+/// The errors of each hue page, with the hues rounded to the conventional
+/// steps of 2.5, in order of hue from 2.5R, and the neutrals last.
+pub fn hue_accuracy(errors: &[ConversionError]) -> Vec<HueAccuracy> {
+    let format = MunsellFormat {
+        hue_precision: 1,
+        precision: None,
+        snap_hue: true,
+        boundary_as_ten: true,
+    };
+
+    let first: MunsellHue = "2.5R".parse().unwrap();
+
+    // (hue, its place on the circle from the first, errors)
+    let mut hues: Vec<(String, f32, Vec<f32>)> = Vec::new();
+    for error in errors.iter() {
+        let munsell = &error.reference.munsell;
+        let (hue, order) = if munsell.is_neutral() {
+            ("N".to_string(), f32::INFINITY)
+        } else {
+            let hue = munsell.hue.format(&format);
+            let order = (hue.parse::<MunsellHue>().unwrap() - first).rem_euclid(100.0);
+            (hue, order)
+        };
+        match hues.iter_mut().find(|x| x.0 == hue) {
+            Some(x) => x.2.push(error.delta_e),
+            None => hues.push((hue, order, vec![error.delta_e])),
+        }
+    }
+    hues.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+    return hues
+        .into_iter()
+        .map(|(hue, _, delta_e)| HueAccuracy {
+            hue,
+            count: delta_e.len(),
+            mean: delta_e.iter().sum::<f32>() / delta_e.len() as f32,
+            max: delta_e.iter().copied().fold(0.0, f32::max),
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod test {
+    use palette::{IntoColor, Srgb};
+
+    use crate::accuracy::{conversion_errors, hue_accuracy, parse_reference_table};
+    use crate::MunsellColor;
+
+    #[test]
+    fn reference_comparison() {
+        // what this crate converts a few colors to, in both forms
+        let srgb = |spec: &str| {
+            let color: Srgb = MunsellColor::parse(spec)
+                .unwrap()
+                .to_display_lch()
+                .into_color();
+            let c: Srgb<u8> = color.into_format();
+            return format!("{} {} {}", c.red, c.green, c.blue);
+        };
+        let text = format!(
+            "munsell,r,g,b\n# comment\n5R 4/10,{}\n\"2.5YR 6/4\",{}\n10RP 5/6 {}\nN 5/ {}\n",
+            srgb("5R 4/10").replace(' ', ","),
+            srgb("2.5YR 6/4").replace(' ', ","),
+            srgb("10RP 5/6"),
+            srgb("N 5/")
+        );
+        let table = parse_reference_table(&text).unwrap();
+        assert_eq!(table.len(), 4);
+        assert_eq!(table[0].munsell, MunsellColor::parse("5R 4/10").unwrap());
+
+        // off only by rounding to 8 bits
+        let errors = conversion_errors(&table);
+        assert!(errors.iter().all(|x| x.delta_e < 0.5));
+
+        // hue pages in order around the circle from 2.5R, 10RP last
+        let hues = hue_accuracy(&errors);
+        assert_eq!(
+            hues.iter().map(|x| x.hue.as_str()).collect::<Vec<&str>>(),
+            vec!["5.0R", "2.5YR", "10.0RP", "N"]
+        );
+        assert!(hues.iter().all(|x| x.count == 1 && x.max == x.mean));
+
+        // a reference farther off
+        let shifted = parse_reference_table("5R 4/10 200 40 60\n5R 4/10 150 40 60").unwrap();
+        let errors = conversion_errors(&shifted);
+        let hues = hue_accuracy(&errors);
+        assert_eq!(hues.len(), 1);
+        assert_eq!(hues[0].count, 2);
+        assert!(hues[0].max > hues[0].mean && hues[0].mean > 1.0);
+
+        assert!(parse_reference_table("5R 4/10 1 2 3\n5R 4/10 1 2").is_err());
+        assert!(parse_reference_table("5R 4/10 1 2 3\n5R 4/10 1 2 300").is_err());
+        assert!(parse_reference_table("5R 4/10 1 2 3\nblue 1 2 3").is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! Chromatic adaptation from Illuminant C, under which the Munsell
+//! renotation is specified, to the white point that CIELAB and the other
+//! color spaces are taken relative to.
+//!
+//! sRGB, and so every chart and export, is relative to D65, which is the
+//! default target; D50 is what ICC profiles and many spectrophotometers
+//! report. The adaptation used everywhere is set once with
+//! [`set_adaptation`], like the plot configuration.
+
+use std::sync::OnceLock;
+
+use palette::white_point::{WhitePoint, C, D50, D65, E};
+use palette::{IntoColor, Lab, Xyz, Yxy};
+
+static ADAPTATION: OnceLock<Adaptation> = OnceLock::new();
+
+/// A white point to adapt to.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Illuminant {
+    /// Average daylight, as the Munsell renotation was measured under.
+    C,
+    /// Horizon daylight, the white of ICC profiles.
+    D50,
+    /// Noon daylight, the white of sRGB.
+    #[default]
+    D65,
+}
+
+impl Illuminant {
+    /// The tristimulus values of this white, with a luminance of 1.
+    pub fn white(self) -> [f32; 3] {
+        let xyz: Xyz<E> = match self {
+            Illuminant::C => C::get_xyz(),
+            Illuminant::D50 => D50::get_xyz(),
+            Illuminant::D65 => D65::get_xyz(),
+        };
+        return [xyz.x, xyz.y, xyz.z];
+    }
+}
+
+/// How to carry colors from one white point to another.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AdaptationMethod {
+    /// Compute L\*a\*b\* relative to the source white and use it as if it
+    /// were relative to the target, as this crate did before adaptation
+    /// could be chosen.
+    LabScaling,
+    /// Scale X, Y and Z by the ratio of the whites.
+    XyzScaling,
+    /// Scale the cone responses of Hunt-Pointer-Estevez by the ratio of the
+    /// whites.
+    VonKries,
+    /// Scale the sharpened cone responses of the Bradford transform, as ICC
+    /// profiles do.
+    #[default]
+    Bradford,
+    /// Scale the cone responses of CIECAM02.
+    Cat02,
+}
+
+type Matrix = [[f32; 3]; 3];
+
+const IDENTITY: Matrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+const VON_KRIES: Matrix = [
+    [0.40024, 0.70760, -0.08081],
+    [-0.22630, 1.16532, 0.04570],
+    [0.0, 0.0, 0.91822],
+];
+
+const BRADFORD: Matrix = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296],
+];
+
+const CAT02: Matrix = [
+    [0.7328, 0.4296, -0.1624],
+    [-0.7036, 1.6975, 0.0061],
+    [0.0030, 0.0136, 0.9834],
+];
+
+fn multiply(m: &Matrix, v: [f32; 3]) -> [f32; 3] {
+    return [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ];
+}
+
+fn invert(m: &Matrix) -> Matrix {
+    let cofactor = |r: usize, c: usize| {
+        let (r1, r2) = ((r + 1) % 3, (r + 2) % 3);
+        let (c1, c2) = ((c + 1) % 3, (c + 2) % 3);
+        return m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1];
+    };
+    let determinant = (0..3).map(|c| m[0][c] * cofactor(0, c)).sum::<f32>();
+    let mut inverse = [[0.0; 3]; 3];
+    for (r, row) in inverse.iter_mut().enumerate() {
+        for (c, x) in row.iter_mut().enumerate() {
+            *x = cofactor(c, r) / determinant;
+        }
+    }
+    return inverse;
+}
+
+/// CIELAB relative to `white`: relative to the equal-energy white, the
+/// tristimulus values divided by those of `white` give just that.
+fn xyz_to_lab(xyz: [f32; 3], white: [f32; 3]) -> Lab {
+    let lab: Lab<E> =
+        Xyz::<E>::with_wp(xyz[0] / white[0], xyz[1] / white[1], xyz[2] / white[2]).into_color();
+    return Lab::new(lab.l, lab.a, lab.b);
+}
+
+/// The inverse of [`xyz_to_lab`].
+fn lab_to_xyz(lab: Lab, white: [f32; 3]) -> [f32; 3] {
+    let xyz: Xyz<E> = Lab::<E>::with_wp(lab.l, lab.a, lab.b).into_color();
+    return [xyz.x * white[0], xyz.y * white[1], xyz.z * white[2]];
+}
+
+/// A method of adaptation and the white point to adapt to.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Adaptation {
+    pub method: AdaptationMethod,
+    pub target: Illuminant,
+}
+
+impl Adaptation {
+    pub fn new(method: AdaptationMethod, target: Illuminant) -> Self {
+        return Adaptation { method, target };
+    }
+
+    /// Adapt tristimulus values from one white point to another.
+    /// [`AdaptationMethod::LabScaling`] adapts like
+    /// [`AdaptationMethod::XyzScaling`] here.
+    pub fn adapt(&self, xyz: [f32; 3], source: Illuminant, target: Illuminant) -> [f32; 3] {
+        if source == target {
+            return xyz;
+        }
+        let cone = match self.method {
+            AdaptationMethod::LabScaling | AdaptationMethod::XyzScaling => IDENTITY,
+            AdaptationMethod::VonKries => VON_KRIES,
+            AdaptationMethod::Bradford => BRADFORD,
+            AdaptationMethod::Cat02 => CAT02,
+        };
+        let (from, to) = (
+            multiply(&cone, source.white()),
+            multiply(&cone, target.white()),
+        );
+        let response = multiply(&cone, xyz);
+        let scaled = [
+            response[0] * to[0] / from[0],
+            response[1] * to[1] / from[1],
+            response[2] * to[2] / from[2],
+        ];
+        return multiply(&invert(&cone), scaled);
+    }
+
+    /// Convert a color under Illuminant C to CIELAB relative to the target
+    /// white.
+    pub fn to_lab(&self, xyy: Yxy<C>) -> Lab {
+        let xyz: Xyz<C> = xyy.into_color();
+        let xyz = [xyz.x, xyz.y, xyz.z];
+        if self.method == AdaptationMethod::LabScaling {
+            return xyz_to_lab(xyz, Illuminant::C.white());
+        }
+        let adapted = self.adapt(xyz, Illuminant::C, self.target);
+        return xyz_to_lab(adapted, self.target.white());
+    }
+
+    /// Carry CIELAB relative to the target white over to D65, for
+    /// converting to sRGB.
+    pub fn to_display(&self, lab: Lab) -> Lab {
+        if self.target == Illuminant::D65 || self.method == AdaptationMethod::LabScaling {
+            return lab;
+        }
+        let xyz = self.adapt(
+            lab_to_xyz(lab, self.target.white()),
+            self.target,
+            Illuminant::D65,
+        );
+        return xyz_to_lab(xyz, Illuminant::D65.white());
+    }
+
+    /// Carry CIELAB relative to D65, as from sRGB, over to the target white,
+    /// inverting [`Adaptation::to_display`].
+    pub fn from_display(&self, lab: Lab) -> Lab {
+        if self.target == Illuminant::D65 || self.method == AdaptationMethod::LabScaling {
+            return lab;
+        }
+        let xyz = self.adapt(
+            lab_to_xyz(lab, Illuminant::D65.white()),
+            Illuminant::D65,
+            self.target,
+        );
+        return xyz_to_lab(xyz, self.target.white());
+    }
+}
+
+/// Use `adaptation` for all conversions from now on. Only the first call
+/// has an effect, and only if no color has been converted yet; otherwise
+/// the adaptation already in use is returned as the error.
+pub fn set_adaptation(adaptation: Adaptation) -> Result<(), Adaptation> {
+    return ADAPTATION.set(adaptation).map_err(|_| *self::adaptation());
+}
+
+/// The adaptation colors are converted with: the one given to
+/// [`set_adaptation`], or Bradford to D65.
+pub fn adaptation() -> &'static Adaptation {
+    return ADAPTATION.get_or_init(Adaptation::default);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::adaptation::{adaptation, set_adaptation, Adaptation, AdaptationMethod, Illuminant};
+    use palette::Lab;
+
+    #[test]
+    fn white_points() {
+        // every method takes the source white to the target white
+        for method in [
+            AdaptationMethod::XyzScaling,
+            AdaptationMethod::VonKries,
+            AdaptationMethod::Bradford,
+            AdaptationMethod::Cat02,
+        ] {
+            let adaptation = Adaptation::new(method, Illuminant::D50);
+            let white = adaptation.adapt(Illuminant::C.white(), Illuminant::C, Illuminant::D50);
+            for (a, b) in white.iter().zip(Illuminant::D50.white()) {
+                assert!((a - b).abs() < 1e-4, "{:?}", method);
+            }
+
+            let lab = Lab::new(50.0, 20.0, -30.0);
+            let back = adaptation.from_display(adaptation.to_display(lab));
+            assert!((back.l - lab.l).abs() < 1e-3, "{:?}", method);
+            assert!((back.a - lab.a).abs() < 1e-3, "{:?}", method);
+            assert!((back.b - lab.b).abs() < 1e-3, "{:?}", method);
+        }
+
+        // Bradford from D65 to D50, as in the ICC specification
+        let adaptation = Adaptation::default();
+        let red = adaptation.adapt([0.4124, 0.2126, 0.0193], Illuminant::D65, Illuminant::D50);
+        assert!((red[0] - 0.4361).abs() < 1e-3);
+        assert!((red[1] - 0.2225).abs() < 1e-3);
+        assert!((red[2] - 0.0139).abs() < 1e-3);
+    }
+
+    #[test]
+    fn set_after_converting() {
+        // converting a color settles the adaptation
+        let current = *adaptation();
+        let other = Adaptation::new(AdaptationMethod::VonKries, Illuminant::D50);
+        assert_eq!(set_adaptation(other), Err(current));
+    }
+}
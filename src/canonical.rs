@@ -0,0 +1,311 @@
+//! A canonical form of ISCC-NBS XML documents, so that diffs of the dataset
+//! show only what changed: attributes in a fixed order, the ranges of each
+//! hue range sorted, and one tab of indentation per level.
+//!
+//! Comments are kept where they are, and a blank line between two elements
+//! stays one blank line. Comments between ranges move with the range after
+//! them.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use roxmltree::{Document, Node};
+
+use crate::dataset::Dataset;
+
+/// The order of the attributes of each element; attributes not listed
+/// follow in the order they are given.
+const ATTRIBUTE_ORDER: &[(&str, &[&str])] = &[
+    (
+        "name",
+        &["color", "name", "abbr", "srgb", "description", "usage"],
+    ),
+    ("amount", &["id"]),
+    ("hue-range", &["begin", "end"]),
+    (
+        "range",
+        &[
+            "color",
+            "chroma-begin",
+            "chroma-end",
+            "value-begin",
+            "value-end",
+        ],
+    ),
+];
+
+/// Why a document could not be put in canonical form.
+#[derive(Debug)]
+pub enum CanonicalError {
+    Xml(roxmltree::Error),
+    /// The document has errors, and so no block table to check the canonical
+    /// form against; [`Dataset::check`] finds them.
+    Invalid,
+    /// The canonical form would have different names or blocks, which is a
+    /// bug.
+    Changed,
+}
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            CanonicalError::Xml(e) => write!(f, "{}", e),
+            CanonicalError::Invalid => write!(f, "the dataset has errors; see `validate`"),
+            CanonicalError::Changed => {
+                write!(f, "the canonical form would change the names or blocks")
+            }
+        };
+    }
+}
+
+impl std::error::Error for CanonicalError {}
+
+pub(crate) fn escape_attribute(text: &str) -> String {
+    return escape_text(text).replace('"', "&quot;");
+}
+
+pub(crate) fn escape_text(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+}
+
+fn write_attributes(out: &mut String, node: Node) {
+    let order = ATTRIBUTE_ORDER
+        .iter()
+        .find(|(tag, _)| *tag == node.tag_name().name())
+        .map(|(_, order)| *order)
+        .unwrap_or(&[]);
+    let rank = |name: &str| order.iter().position(|x| *x == name).unwrap_or(order.len());
+
+    let mut attributes = node.attributes().iter().collect::<Vec<_>>();
+    // a stable sort keeps unlisted attributes in their order
+    attributes.sort_by_key(|x| rank(x.name()));
+    for attribute in attributes {
+        out.push_str(&format!(
+            " {}=\"{}\"",
+            attribute.name(),
+            escape_attribute(attribute.value())
+        ));
+    }
+}
+
+/// Whether there is a blank line between `node` and the node before it.
+fn follows_blank_line(node: Node) -> bool {
+    return node
+        .prev_sibling()
+        .filter(|x| x.is_text())
+        .and_then(|x| x.text())
+        .map(|x| x.matches('\n').count() >= 2)
+        .unwrap_or(false);
+}
+
+/// Whether `node` is a comment on the same line as the start tag of its
+/// parent, like `<hue-range begin="1R" end="4R"><!-- page 16 -->`.
+fn is_trailing_comment(node: Node) -> bool {
+    if !node.is_comment() {
+        return false;
+    }
+    return match node.prev_sibling() {
+        None => true,
+        Some(x) if x.is_text() => {
+            !x.text().unwrap_or("").contains('\n') && x.prev_sibling().is_none()
+        }
+        Some(_) => false,
+    };
+}
+
+/// A boundary as a number, with `INF` past every other.
+fn amount(node: Node, attribute: &str) -> f32 {
+    return node
+        .attribute(attribute)
+        .and_then(|x| x.parse().ok())
+        .unwrap_or(f32::INFINITY);
+}
+
+/// Order ranges by color, then outward in chroma and downward in value, like
+/// the charts are read.
+fn compare_ranges(a: Node, b: Node) -> Ordering {
+    let key = |x: Node| {
+        (
+            x.attribute("color")
+                .and_then(|x| x.parse::<u32>().ok())
+                .unwrap_or(u32::MAX),
+            amount(x, "chroma-begin"),
+            amount(x, "value-begin"),
+        )
+    };
+    let (ka, kb) = (key(a), key(b));
+    return ka
+        .0
+        .cmp(&kb.0)
+        .then(ka.1.total_cmp(&kb.1))
+        .then(kb.2.total_cmp(&ka.2));
+}
+
+/// The children of an element to write on lines of their own, in order:
+/// its elements and comments, with the comments before each range moving
+/// with it in a hue range.
+fn child_lines<'a, 'input>(node: Node<'a, 'input>) -> Vec<Vec<Node<'a, 'input>>> {
+    let mut groups: Vec<Vec<Node>> = Vec::new();
+    let mut pending: Vec<Node> = Vec::new();
+    for child in node.children() {
+        if !(child.is_element() || child.is_comment()) || is_trailing_comment(child) {
+            continue;
+        }
+        pending.push(child);
+        if child.is_element() {
+            groups.push(std::mem::take(&mut pending));
+        }
+    }
+
+    if node.has_tag_name("hue-range") {
+        groups.sort_by(|a, b| compare_ranges(*a.last().unwrap(), *b.last().unwrap()));
+    }
+    if !pending.is_empty() {
+        groups.push(pending);
+    }
+    return groups;
+}
+
+fn write_element(out: &mut String, node: Node, depth: usize) {
+    let indent = "\t".repeat(depth);
+    let tag = node.tag_name().name();
+    out.push_str(&format!("{}<{}", indent, tag));
+    write_attributes(out, node);
+
+    let lines = child_lines(node);
+    let trailing = node
+        .first_child()
+        .filter(|x| is_trailing_comment(*x))
+        .or_else(|| {
+            node.first_child()
+                .and_then(|x| x.next_sibling())
+                .filter(|x| is_trailing_comment(*x))
+        });
+    if lines.is_empty() && trailing.is_none() {
+        let text = node
+            .children()
+            .filter(|x| x.is_text())
+            .filter_map(|x| x.text())
+            .collect::<String>();
+        let text = text.trim();
+        if text.is_empty() {
+            out.push_str(" />\n");
+        } else {
+            out.push_str(&format!(">{}</{}>\n", escape_text(text), tag));
+        }
+        return;
+    }
+
+    out.push('>');
+    if let Some(comment) = trailing {
+        out.push_str(&format!("<!--{}-->", comment.text().unwrap_or("")));
+    }
+    out.push('\n');
+    for (i, line) in lines.iter().flatten().enumerate() {
+        if i > 0 && follows_blank_line(*line) {
+            out.push('\n');
+        }
+        write_node(out, *line, depth + 1);
+    }
+    out.push_str(&format!("{}</{}>\n", indent, tag));
+}
+
+fn write_node(out: &mut String, node: Node, depth: usize) {
+    if node.is_comment() {
+        out.push_str(&format!(
+            "{}<!--{}-->\n",
+            "\t".repeat(depth),
+            node.text().unwrap_or("")
+        ));
+    } else if node.is_element() {
+        write_element(out, node, depth);
+    }
+}
+
+/// The blocks of a dataset as boundary names, in a fixed order.
+fn block_table(dataset: &Dataset) -> Vec<(u32, [&str; 6])> {
+    let (hues, chromas, values) = (dataset.hues(), dataset.chromas(), dataset.values());
+    let mut table = dataset
+        .blocks()
+        .iter()
+        .map(|x| {
+            (
+                x.color_id,
+                [
+                    hues[x.hues.start].as_str(),
+                    hues[x.hues.end].as_str(),
+                    chromas[x.chromas.start].as_str(),
+                    chromas[x.chromas.end].as_str(),
+                    values[x.values.start].as_str(),
+                    values[x.values.end].as_str(),
+                ],
+            )
+        })
+        .collect::<Vec<_>>();
+    table.sort();
+    return table;
+}
+
+/// Write an ISCC-NBS XML document in canonical form, checking that it has
+/// the same names and block table as the original.
+pub fn canonicalize(text: &str) -> Result<String, CanonicalError> {
+    let opt = roxmltree::ParsingOptions { allow_dtd: true };
+    let doc = Document::parse_with_options(text, opt).map_err(CanonicalError::Xml)?;
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    for (i, node) in doc.root().children().enumerate() {
+        if i > 0 && follows_blank_line(node) {
+            out.push('\n');
+        }
+        write_node(&mut out, node, 0);
+    }
+
+    let original = match Dataset::check_and_build(text) {
+        Ok((Some(v), _)) => v,
+        Ok((None, _)) => return Err(CanonicalError::Invalid),
+        Err(e) => return Err(CanonicalError::Xml(e)),
+    };
+    let canonical = match Dataset::check_and_build(&out) {
+        Ok((Some(v), _)) => v,
+        _ => return Err(CanonicalError::Changed),
+    };
+    if block_table(&original) != block_table(&canonical) || !original.diff(&canonical).is_empty() {
+        return Err(CanonicalError::Changed);
+    }
+    return Ok(out);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::canonical::canonicalize;
+    use crate::dataset::BUNDLED_XML;
+
+    #[test]
+    fn canonical_form() {
+        // the bundled dataset is already in canonical form
+        let canonical = canonicalize(BUNDLED_XML).unwrap();
+        assert_eq!(canonical, BUNDLED_XML);
+
+        let shuffled = BUNDLED_XML
+            .replacen(
+                "<name color=\"1\" name=\"Vivid pink\" abbr=\"viv.Pk\" />",
+                "<name abbr=\"viv.Pk\"   name=\"Vivid pink\" color=\"1\"/>",
+                1,
+            )
+            .replacen(
+                "\t\t\t<range color=\"1\" chroma-begin=\"11.0\" chroma-end=\"INF\" value-begin=\"6.5\" value-end=\"INF\" />\n\t\t\t<range color=\"2\"",
+                "\t\t\t<range color=\"2\"",
+                1,
+            )
+            .replacen(
+                "\t\t\t<range color=\"3\"",
+                "  <range value-end=\"INF\" value-begin=\"6.5\" chroma-end=\"INF\" chroma-begin=\"11.0\" color=\"1\" />\n\t\t\t<range color=\"3\"",
+                1,
+            );
+        assert_ne!(shuffled, BUNDLED_XML);
+        assert_eq!(canonicalize(&shuffled).unwrap(), BUNDLED_XML);
+    }
+}
@@ -0,0 +1,145 @@
+//! Comparison of the computed centroids of the level 3 colors with a table
+//! of published ones, like that of NBS Circular 553, to check both the
+//! blocks of the dataset and the Munsell conversions.
+//!
+//! The published table is not included; it is read from a CSV file with a
+//! color id and a Munsell specification per line, like `1,1.5R 7.5/10.2`.
+//! Neutrals are written like `N 9.5/`.
+
+use std::collections::HashMap;
+
+use palette::ColorDifference;
+
+use crate::colors::{get_mean_munsell_with, CentroidMethod};
+use crate::dataset::Dataset;
+use crate::munsell::MunsellColor;
+
+/// How far a computed centroid may be from the published one before it is
+/// flagged, in CIEDE2000 units.
+pub const DEFAULT_THRESHOLD: f32 = 5.0;
+
+/// A computed centroid and the published one for the same color.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CentroidDeviation {
+    pub color_id: u32,
+    pub computed: MunsellColor,
+    pub published: MunsellColor,
+    /// The CIEDE2000 color difference between the two.
+    pub delta_e: f32,
+}
+
+/// Parse a table of published centroids, with one `id,specification` per
+/// line. Blank lines, lines starting with `#` and a header line are
+/// skipped.
+pub fn parse_centroid_table(text: &str) -> Result<HashMap<u32, MunsellColor>, String> {
+    let mut table = HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (id, spec) = match line.split_once(',') {
+            Some(v) => v,
+            None => return Err(format!("line {} is not 'id,specification'", i + 1)),
+        };
+        let id = match id.trim().parse::<u32>() {
+            Ok(v) => v,
+            // a header
+            Err(_) if i == 0 => continue,
+            Err(_) => return Err(format!("invalid color id '{}' on line {}", id, i + 1)),
+        };
+        let color = match MunsellColor::parse(spec.trim().trim_matches('"')) {
+            Some(v) => v,
+            None => {
+                return Err(format!(
+                    "'{}' on line {} is not a Munsell specification",
+                    spec,
+                    i + 1
+                ))
+            }
+        };
+        table.insert(id, color);
+    }
+    return Ok(table);
+}
+
+impl Dataset {
+    /// Compare the centroid of every level 3 color in `published` with the
+    /// one computed by [`crate::colors::get_mean_munsell`], in order of
+    /// color id. Ids the dataset doesn't name are left out.
+    pub fn centroid_deviations(
+        &self,
+        published: &HashMap<u32, MunsellColor>,
+    ) -> Vec<CentroidDeviation> {
+        return self.centroid_deviations_with(published, CentroidMethod::default());
+    }
+
+    /// Like [`Dataset::centroid_deviations`], with the centroids computed
+    /// by `method`.
+    pub fn centroid_deviations_with(
+        &self,
+        published: &HashMap<u32, MunsellColor>,
+        method: CentroidMethod,
+    ) -> Vec<CentroidDeviation> {
+        let computed = get_mean_munsell_with(self, method);
+
+        let mut ids = published
+            .keys()
+            .copied()
+            .filter(|id| self.names().contains_key(id))
+            .collect::<Vec<u32>>();
+        ids.sort();
+
+        return ids
+            .into_iter()
+            .map(|id| {
+                let computed = computed[(id - 1) as usize].clone();
+                let published = published[&id].clone();
+                let delta_e = computed.to_lab().get_color_difference(&published.to_lab());
+                return CentroidDeviation {
+                    color_id: id,
+                    computed,
+                    published,
+                    delta_e,
+                };
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::centroids::parse_centroid_table;
+    use crate::colors::get_mean_munsell;
+    use crate::{Dataset, MunsellColor};
+
+    #[test]
+    fn parse_tables() {
+        let table =
+            parse_centroid_table("id,munsell\n# comment\n1,1.5R 7.5/10.2\n263,N 9.5/\n").unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table[&1], MunsellColor::parse("1.5R 7.5/10.2").unwrap());
+        assert_eq!(table[&263].value, 9.5);
+        assert_eq!(table[&263].chroma, 0.0);
+
+        assert!(parse_centroid_table("1,7.5YR 6/8\nx,7.5YR 6/8").is_err());
+        assert!(parse_centroid_table("1,blue").is_err());
+    }
+
+    #[test]
+    fn deviations() {
+        let dataset = Dataset::bundled();
+        let mut table = parse_centroid_table("1,2R 8/12\n267,N 0.5/\n999,N 5/").unwrap();
+        // a published centroid equal to the computed one
+        table.insert(2, get_mean_munsell(&dataset)[1].clone());
+
+        let deviations = dataset.centroid_deviations(&table);
+        assert_eq!(
+            deviations.iter().map(|x| x.color_id).collect::<Vec<u32>>(),
+            vec![1, 2, 267]
+        );
+        assert!(deviations[0].delta_e > 0.0);
+        assert!(deviations[1].delta_e < 0.001);
+    }
+}
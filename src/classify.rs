@@ -0,0 +1,471 @@
+//! Classification of sRGB colors into ISCC-NBS categories.
+
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use palette::{ColorDifference, IntoColor, Lab, Srgb};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::adaptation::adaptation;
+use crate::colors::get_mean_munsell;
+use crate::dataset::Dataset;
+use crate::munsell::MunsellColor;
+use crate::progress::{no_progress, report, Progress, Stage};
+use crate::random::{Rng, DEFAULT_SEED};
+
+/// How many colors [`Dataset::classify_with_uncertainty`] samples.
+const UNCERTAINTY_SAMPLES: usize = 2000;
+
+/// Settings for classifying colors into categories.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassifyOptions {
+    /// Colors with a chroma below this are treated as neutral (chroma 0),
+    /// ignoring their hue. Measured or converted colors near the neutral
+    /// axis tend to have a noisy hue, which otherwise decides between the
+    /// low-chroma categories of different hue families.
+    pub neutral_chroma: f32,
+}
+
+impl Default for ClassifyOptions {
+    fn default() -> Self {
+        ClassifyOptions {
+            neutral_chroma: 0.0,
+        }
+    }
+}
+
+/// The level 3 color whose centroid is nearest to a color, and how near it
+/// is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NearestCentroid {
+    pub color_id: u32,
+    /// The CIEDE2000 color difference between the color and the centroid.
+    pub delta_e: f32,
+}
+
+/// A formula for the difference between two CIELAB colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeltaE {
+    /// The Euclidean distance, which overstates differences of saturated
+    /// colors.
+    Cie76,
+    /// CIEDE2000, which is closer to how different colors look.
+    Ciede2000,
+}
+
+impl DeltaE {
+    /// The difference between two colors by this formula.
+    pub fn between(self, a: Lab, b: Lab) -> f32 {
+        return match self {
+            DeltaE::Cie76 => {
+                ((a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)).sqrt()
+            }
+            DeltaE::Ciede2000 => a.get_color_difference(&b),
+        };
+    }
+}
+
+/// Parse an sRGB color written as `#aabbcc` or `rgb(120, 30, 200)`.
+pub fn parse_srgb(text: &str) -> Option<Srgb<u8>> {
+    lazy_static! {
+        static ref HEX: Regex = Regex::new(r"^\s*#?([0-9a-fA-F]{6})\s*$").unwrap();
+        static ref FUNC: Regex =
+            Regex::new(r"^\s*rgb\(\s*(\d{1,3})\s*,\s*(\d{1,3})\s*,\s*(\d{1,3})\s*\)\s*$").unwrap();
+    }
+
+    if let Some(caps) = HEX.captures(text) {
+        return caps.get(1).unwrap().as_str().parse::<Srgb<u8>>().ok();
+    }
+
+    let caps = FUNC.captures(text)?;
+    let channel = |i: usize| caps.get(i).unwrap().as_str().parse::<u8>().ok();
+
+    return Some(Srgb::new(channel(1)?, channel(2)?, channel(3)?));
+}
+
+/// Convert an sRGB color into CIELAB relative to the target white point of
+/// [`adaptation`], like [`MunsellColor::to_lab`].
+pub fn srgb_to_lab(color: Srgb) -> Lab {
+    return adaptation().from_display(color.into_color());
+}
+
+/// Convert an sRGB color into Munsell notation.
+pub fn srgb_to_munsell(color: Srgb) -> MunsellColor {
+    return MunsellColor::from_lab(&srgb_to_lab(color));
+}
+
+/// Classifies many 8-bit sRGB colors quickly, by remembering the category
+/// of every color it has classified.
+///
+/// Converting a color to Munsell notation takes tens of microseconds, so
+/// every distinct color is classified only once, on every core with the
+/// `parallel` feature; colors seen before are a table lookup. Colors that
+/// fall into no block take the color whose centroid is nearest instead, as
+/// with [`Dataset::nearest_centroid`].
+pub struct SrgbClassifier<'a> {
+    dataset: &'a Dataset,
+    /// The level 3 color id of every color by its 24-bit RGB value, or 0
+    /// if it hasn't been classified yet. This takes 64 MiB, but the pages
+    /// of colors never seen are never touched.
+    table: Vec<u32>,
+}
+
+impl<'a> SrgbClassifier<'a> {
+    pub fn new(dataset: &'a Dataset) -> Self {
+        return SrgbClassifier {
+            dataset,
+            table: srgb_table(),
+        };
+    }
+
+    /// Give the id of the level 3 color of each of `colors`.
+    pub fn classify_slice(&mut self, colors: &[Srgb<u8>]) -> Vec<u32> {
+        return classify_with_table(self.dataset, &mut self.table, colors);
+    }
+}
+
+/// An empty table of the level 3 color ids of the 8-bit sRGB colors, for
+/// [`classify_with_table`].
+fn srgb_table() -> Vec<u32> {
+    return vec![0; 1 << 24];
+}
+
+fn classify_key(dataset: &Dataset, key: u32) -> u32 {
+    let [_, r, g, b] = key.to_be_bytes();
+    let color: Srgb = Srgb::new(r, g, b).into_format();
+    return match dataset.classify_srgb(color) {
+        Some(v) => v,
+        None => dataset.nearest_centroid(srgb_to_lab(color)).color_id,
+    };
+}
+
+/// Give the id of the level 3 color of each of `colors`, classifying the
+/// ones that aren't in `table` yet and adding them to it.
+fn classify_with_table(dataset: &Dataset, table: &mut [u32], colors: &[Srgb<u8>]) -> Vec<u32> {
+    let key = |c: &Srgb<u8>| u32::from_be_bytes([0, c.red, c.green, c.blue]);
+
+    let mut unseen = colors
+        .iter()
+        .map(key)
+        .filter(|x| table[*x as usize] == 0)
+        .collect::<Vec<u32>>();
+    unseen.sort_unstable();
+    unseen.dedup();
+
+    #[cfg(feature = "parallel")]
+    let ids = unseen
+        .par_iter()
+        .map(|x| classify_key(dataset, *x))
+        .collect::<Vec<u32>>();
+    #[cfg(not(feature = "parallel"))]
+    let ids = unseen
+        .iter()
+        .map(|x| classify_key(dataset, *x))
+        .collect::<Vec<u32>>();
+    for (x, id) in unseen.into_iter().zip(ids) {
+        table[x as usize] = id;
+    }
+
+    return colors.iter().map(|c| table[key(c) as usize]).collect();
+}
+
+impl Dataset {
+    /// Find the id of the level 3 color that an sRGB color falls into.
+    pub fn classify_srgb(&self, color: Srgb) -> Option<u32> {
+        return self.classify_srgb_with(color, &ClassifyOptions::default());
+    }
+
+    /// Like [`Dataset::classify_srgb`], with explicit settings.
+    pub fn classify_srgb_with(&self, color: Srgb, options: &ClassifyOptions) -> Option<u32> {
+        return self.classify_munsell_with(&srgb_to_munsell(color), options);
+    }
+
+    /// Classify many sRGB colors at once, like the pixels of an image,
+    /// giving the id of the level 3 color of each, as an [`SrgbClassifier`]
+    /// does. The dataset keeps the table of the classifier, so that colors
+    /// of earlier calls, as of the frames of a video, are a table lookup;
+    /// calls from several threads take turns.
+    pub fn classify_slice(&self, colors: &[Srgb<u8>]) -> Vec<u32> {
+        let mut table = self.srgb_table.lock().unwrap();
+        if table.is_empty() {
+            *table = srgb_table();
+        }
+        return classify_with_table(self, &mut table, colors);
+    }
+
+    /// Like [`Dataset::lookup_id`], with explicit settings.
+    pub fn classify_munsell_with(
+        &self,
+        color: &MunsellColor,
+        options: &ClassifyOptions,
+    ) -> Option<u32> {
+        if color.chroma >= 0.0 && color.chroma < options.neutral_chroma && color.value >= 0.0 {
+            return Some(self.classify_neutral(color.value));
+        }
+
+        return self.lookup_table().classify_munsell(color);
+    }
+
+    /// Find every level 3 color a Munsell color might be if it were off by
+    /// up to `epsilon` hue, value and chroma steps, as with measured colors
+    /// near a boundary. The color it falls into comes first, if any; see
+    /// [`crate::lookup::ColorLookupTable::classify_within`].
+    pub fn classify_with_tolerance(&self, color: &MunsellColor, epsilon: f32) -> Vec<u32> {
+        return self.lookup_table().classify_within(
+            color.hue.raw(),
+            color.value,
+            color.chroma,
+            epsilon,
+        );
+    }
+
+    /// Find the level 3 color whose centroid, as computed by
+    /// [`get_mean_munsell`], is nearest to a CIELAB color by CIEDE2000.
+    ///
+    /// This is a fallback for colors right on the boundaries of blocks, or
+    /// outside the color solid, where [`Dataset::lookup_id`] is of little
+    /// use: it always finds a color, and the distance tells how far from
+    /// typical of it the color is.
+    pub fn nearest_centroid(&self, lab: Lab) -> NearestCentroid {
+        let (color_id, delta_e) = self.rank_centroids(lab, DeltaE::Ciede2000)[0];
+        return NearestCentroid { color_id, delta_e };
+    }
+
+    /// The difference between a CIELAB color and the centroid of a level 3
+    /// color, as computed by [`get_mean_munsell`], or `None` if there is no
+    /// such color. Where [`Dataset::lookup_id`] only tells whether a color
+    /// is "Vivid red", this tells how typical of it the color is.
+    pub fn delta_e_to_centroid(&self, lab: Lab, color_id: u32, formula: DeltaE) -> Option<f32> {
+        if !self.names().contains_key(&color_id) {
+            return None;
+        }
+        let centroid = &get_mean_munsell(self)[(color_id - 1) as usize];
+        return Some(formula.between(lab, centroid.to_lab()));
+    }
+
+    /// Every level 3 color with the difference between its centroid and a
+    /// CIELAB color, nearest first.
+    pub fn rank_centroids(&self, lab: Lab, formula: DeltaE) -> Vec<(u32, f32)> {
+        let mut ranked = get_mean_munsell(self)
+            .iter()
+            .enumerate()
+            .map(|(i, centroid)| ((i + 1) as u32, formula.between(lab, centroid.to_lab())))
+            .collect::<Vec<(u32, f32)>>();
+        ranked.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        return ranked;
+    }
+
+    /// Classify a measured CIELAB color with a standard uncertainty of
+    /// `sigma` in each of L\*, a\* and b\*, giving the probability of each
+    /// level 3 color it could be, most likely first.
+    ///
+    /// The probabilities are estimated by classifying normally distributed
+    /// samples around `lab`. Samples that fall outside the color solid
+    /// count for no color, so the probabilities may add up to less than 1.
+    /// The samples are drawn from an [`Rng`] seeded with [`DEFAULT_SEED`],
+    /// so the results are the same on every call.
+    pub fn classify_with_uncertainty(&self, lab: Lab, sigma: f32) -> Vec<(u32, f32)> {
+        return self.classify_with_uncertainty_with_rng(lab, sigma, &mut Rng::new(DEFAULT_SEED));
+    }
+
+    /// Like [`Dataset::classify_with_uncertainty`], drawing the samples
+    /// from `rng`.
+    pub fn classify_with_uncertainty_with_rng(
+        &self,
+        lab: Lab,
+        sigma: f32,
+        rng: &mut Rng,
+    ) -> Vec<(u32, f32)> {
+        let mut counts: HashMap<u32, usize> = HashMap::new();
+        for _ in 0..UNCERTAINTY_SAMPLES {
+            let mut jitter = || (rng.next_normal() as f32) * sigma;
+            let sample = Lab::new(lab.l + jitter(), lab.a + jitter(), lab.b + jitter());
+            if let Some(id) = self.lookup_id(&MunsellColor::from_lab(&sample)) {
+                *counts.entry(id).or_insert(0) += 1;
+            }
+        }
+
+        let mut candidates = counts
+            .into_iter()
+            .map(|(id, count)| (id, count as f32 / UNCERTAINTY_SAMPLES as f32))
+            .collect::<Vec<(u32, f32)>>();
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        return candidates;
+    }
+
+    /// Classify an evenly spaced grid of `steps`×`steps`×`steps` colors
+    /// covering the sRGB cube, and count how many of them fall into each
+    /// level 3 color.
+    ///
+    /// Every level 3 color has an entry, even those that claim no samples.
+    pub fn srgb_coverage(&self, steps: u32) -> HashMap<u32, u32> {
+        return self.srgb_coverage_with_progress(steps, &mut no_progress);
+    }
+
+    /// Like [`Dataset::srgb_coverage`], reporting progress through the
+    /// classify stage, one unit per red channel step.
+    pub fn srgb_coverage_with_progress(&self, steps: u32, progress: Progress) -> HashMap<u32, u32> {
+        let mut counts: HashMap<u32, u32> = self.names().keys().map(|id| (*id, 0)).collect();
+        let step = |i: u32| (i as f32) / ((steps - 1) as f32);
+
+        report(progress, Stage::Classify, 0, steps as usize);
+        for r in 0..steps {
+            for g in 0..steps {
+                for b in 0..steps {
+                    if let Some(id) = self.classify_srgb(Srgb::new(step(r), step(g), step(b))) {
+                        *counts.entry(id).or_insert(0) += 1;
+                    }
+                }
+            }
+            report(progress, Stage::Classify, (r + 1) as usize, steps as usize);
+        }
+
+        return counts;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use palette::Lab;
+
+    use crate::classify::{parse_srgb, ClassifyOptions, DeltaE, SrgbClassifier};
+    use crate::colors::get_mean_munsell;
+    use crate::random::{Rng, DEFAULT_SEED};
+    use crate::{Dataset, MunsellColor};
+    use palette::Srgb;
+
+    #[test]
+    fn parse_colors() {
+        assert_eq!(parse_srgb("#aabbcc"), Some(Srgb::new(0xaa, 0xbb, 0xcc)));
+        assert_eq!(
+            parse_srgb("rgb(120, 30, 200)"),
+            Some(Srgb::new(120, 30, 200))
+        );
+        assert_eq!(parse_srgb("rgb(120, 30, 300)"), None);
+        assert_eq!(parse_srgb("#abc"), None);
+    }
+
+    #[test]
+    fn classify_neutrals() {
+        let dataset = Dataset::bundled();
+
+        assert_eq!(dataset.classify_srgb(Srgb::new(1.0, 1.0, 1.0)), Some(263));
+        assert_eq!(dataset.classify_srgb(Srgb::new(0.0, 0.0, 0.0)), Some(267));
+    }
+
+    #[test]
+    fn neutral_threshold() {
+        let dataset = Dataset::bundled();
+        let options = ClassifyOptions {
+            neutral_chroma: 0.7,
+        };
+        let classify = |spec: &str, options: &ClassifyOptions| {
+            dataset.classify_munsell_with(&MunsellColor::parse(spec).unwrap(), options)
+        };
+
+        // 10: pinkish gray, 191: bluish gray, 264: light gray, 265: medium gray
+        assert_eq!(classify("2R 7/0.6", &ClassifyOptions::default()), Some(10));
+        assert_eq!(classify("2R 7/0.4", &ClassifyOptions::default()), Some(264));
+        assert_eq!(classify("2R 7/0.6", &options), Some(264));
+        assert_eq!(classify("2R 7/0.7", &options), Some(10));
+        assert_eq!(
+            classify("7PB 5/0.5", &ClassifyOptions::default()),
+            Some(191)
+        );
+        assert_eq!(classify("7PB 5/0.5", &options), Some(265));
+    }
+
+    #[test]
+    fn nearest_centroids() {
+        let dataset = Dataset::bundled();
+        let centroids = get_mean_munsell(&dataset);
+
+        let nearest = dataset.nearest_centroid(centroids[41].to_lab());
+        assert_eq!(nearest.color_id, 42);
+        assert!(nearest.delta_e < 0.001);
+
+        // lighter than any white
+        let outside = Lab::new(104.0, 0.0, 0.0);
+        let nearest = dataset.nearest_centroid(outside);
+        assert_eq!(nearest.color_id, 263);
+        assert!(nearest.delta_e > 1.0);
+    }
+
+    #[test]
+    fn classify_many_colors() {
+        let dataset = Dataset::bundled();
+        let colors = (0..=255u8)
+            .step_by(15)
+            .flat_map(|x| [Srgb::new(x, 255 - x, 128), Srgb::new(x, x, x)])
+            .chain([Srgb::new(255, 0, 0), Srgb::new(255, 0, 0)])
+            .collect::<Vec<Srgb<u8>>>();
+
+        let ids = dataset.classify_slice(&colors);
+        assert_eq!(ids.len(), colors.len());
+        for (color, id) in colors.iter().zip(ids.iter()) {
+            assert_eq!(dataset.classify_srgb(color.into_format()), Some(*id));
+        }
+        assert!(dataset.classify_slice(&[]).is_empty());
+        // the dataset remembers them too
+        assert_eq!(dataset.classify_slice(&colors), ids);
+
+        // the classifier remembers the colors of earlier calls
+        let mut classifier = SrgbClassifier::new(&dataset);
+        assert_eq!(classifier.classify_slice(&colors[..10]), ids[..10]);
+        assert_eq!(classifier.classify_slice(&colors), ids);
+    }
+
+    #[test]
+    fn centroid_differences() {
+        let dataset = Dataset::bundled();
+        let lab = get_mean_munsell(&dataset)[10].to_lab();
+        let shifted = Lab::new(lab.l + 3.0, lab.a + 4.0, lab.b);
+
+        let cie76 = dataset.delta_e_to_centroid(shifted, 11, DeltaE::Cie76);
+        assert!((cie76.unwrap() - 5.0).abs() < 1e-3);
+        let ciede2000 = dataset.delta_e_to_centroid(shifted, 11, DeltaE::Ciede2000);
+        assert!(ciede2000.unwrap() > 0.0);
+        assert_eq!(dataset.delta_e_to_centroid(lab, 268, DeltaE::Cie76), None);
+
+        for formula in [DeltaE::Cie76, DeltaE::Ciede2000] {
+            let ranked = dataset.rank_centroids(lab, formula);
+            assert_eq!(ranked.len(), 267);
+            assert_eq!(ranked[0].0, 11);
+            assert!(ranked.windows(2).all(|x| x[0].1 <= x[1].1));
+        }
+    }
+
+    #[test]
+    fn classify_uncertain_colors() {
+        let dataset = Dataset::bundled();
+        let lab = MunsellColor::parse("2R 7/12").unwrap().to_lab();
+
+        // with no uncertainty, there is only one candidate
+        assert_eq!(dataset.classify_with_uncertainty(lab, 0.0), vec![(1, 1.0)]);
+
+        let candidates = dataset.classify_with_uncertainty(lab, 5.0);
+        assert!(candidates.len() > 1);
+        assert!(candidates.windows(2).all(|x| x[0].1 >= x[1].1));
+        assert!((candidates.iter().map(|x| x.1).sum::<f32>() - 1.0).abs() < 0.001);
+        assert_eq!(candidates, dataset.classify_with_uncertainty(lab, 5.0));
+
+        // other seeds give other samples, but much the same probabilities
+        let seeded =
+            |seed: u64| dataset.classify_with_uncertainty_with_rng(lab, 5.0, &mut Rng::new(seed));
+        assert_eq!(candidates, seeded(DEFAULT_SEED));
+        assert_eq!(seeded(1), seeded(1));
+        assert_ne!(seeded(1), seeded(2));
+        assert_eq!(seeded(1)[0].0, candidates[0].0);
+    }
+
+    #[test]
+    fn coverage_counts_every_sample() {
+        let dataset = Dataset::bundled();
+        let coverage = dataset.srgb_coverage(5);
+
+        assert_eq!(coverage.len(), dataset.names().len());
+        assert_eq!(coverage.values().sum::<u32>(), 5 * 5 * 5);
+    }
+}
@@ -0,0 +1,622 @@
+//! The `plot` command, and the exports and the site that draw charts, which
+//! are only built with the `charts` feature.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{Args, Subcommand, ValueEnum};
+use log::{error, info, warn};
+use palette::Srgb;
+
+use iscc_nbs_validator::colors::{
+    get_gamut_clipping_in, get_level1_mean_colors, get_level2_mean_colors,
+    get_representative_colors,
+};
+use iscc_nbs_validator::cvd::Deficiency;
+use iscc_nbs_validator::export;
+use iscc_nbs_validator::export::swatches::SwatchNaming;
+use iscc_nbs_validator::plot;
+use iscc_nbs_validator::plot::config::PlotConfig;
+use iscc_nbs_validator::plot::{ChartOverlays, GnuplotError};
+use iscc_nbs_validator::Dataset;
+
+use crate::cli::logging::log_progress;
+use crate::{
+    export_colors, load_dataset, read_centroid_table, representative, translate, translation_path,
+    try_load_dataset, try_translate, Cli,
+};
+
+/// The options of `plot`.
+#[derive(Args)]
+pub struct PlotArgs {
+    /// Which figures to render
+    #[arg(value_enum, default_value_t = PlotKind::Charts)]
+    pub kind: PlotKind,
+    /// How to render them
+    #[arg(long, value_enum, default_value_t = PlotFormat::Png)]
+    pub format: PlotFormat,
+    /// Only re-render the charts whose blocks, names or colors changed
+    /// since the last run
+    #[arg(long)]
+    pub only_changed: bool,
+    /// How the charts look
+    #[arg(long, value_enum, default_value_t = PlotStyle::Modern)]
+    pub style: PlotStyle,
+    /// Only write the gnuplot scripts, without rendering them
+    #[arg(long)]
+    pub no_render: bool,
+    /// Mark the published centroids of the colors on the charts, from a
+    /// CSV file of `id,munsell` lines like `centroids` compares with
+    #[arg(long)]
+    pub centroids: Option<PathBuf>,
+    /// Draw the edge of the sRGB gamut on the charts
+    #[arg(long)]
+    pub gamut_boundary: bool,
+    /// Keep running, validating the dataset and re-rendering the charts
+    /// that changed every time it is saved
+    #[arg(long)]
+    pub watch: bool,
+    /// Color the charts by the level 1 or level 2 names, merging the
+    /// regions of the level 3 names under each
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..=3))]
+    pub level: u32,
+}
+
+impl Default for PlotArgs {
+    /// What `plot` does without options, and running without a command.
+    fn default() -> Self {
+        return PlotArgs {
+            kind: PlotKind::Charts,
+            format: PlotFormat::Png,
+            only_changed: false,
+            style: PlotStyle::Modern,
+            no_render: false,
+            centroids: None,
+            gamut_boundary: false,
+            watch: false,
+            level: 3,
+        };
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ExportBundle {
+    /// Write every export, the solid and the charts into one directory, with
+    /// a manifest of their hashes
+    All {
+        /// The directory to write into
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Write a small PNG of the representative color of every level 3
+    /// name into one directory, with an index.json of them
+    Swatches {
+        /// The directory to write into
+        #[arg(long)]
+        out: PathBuf,
+        /// The width and height of each swatch, in pixels
+        #[arg(long, default_value_t = export::swatches::DEFAULT_SWATCH_SIZE, value_parser = clap::value_parser!(u32).range(1..=4096))]
+        size: u32,
+        /// What to name the files after
+        #[arg(long, value_enum, default_value_t = SwatchNamingArg::Id)]
+        naming: SwatchNamingArg,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum PlotKind {
+    /// The color name charts, one per hue leaf, in doc/
+    Charts,
+    /// Munsell book pages of color chips, one per hue step, in doc/book/
+    Book,
+    /// The hue circle, with level 1 and level 2 hue names, in doc/
+    Wheel,
+    /// A poster of every level 3 color as a labelled swatch, grouped by
+    /// level 1 name, in doc/
+    Poster,
+    /// Slices of the color solid at constant value, one through the middle
+    /// of each value span, in doc/slices/
+    Slices,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum PlotStyle {
+    /// Regions filled with their representative colors
+    Modern,
+    /// Black and white hatching after the 1955 publication, in doc/nbs-1955/
+    #[value(name = "nbs-1955")]
+    Nbs1955,
+    /// The modern charts as seen without red cones, in doc/protanopia/
+    Protanopia,
+    /// The modern charts as seen without green cones, in doc/deuteranopia/
+    Deuteranopia,
+    /// The modern charts as seen without blue cones, in doc/tritanopia/
+    Tritanopia,
+}
+
+impl PlotStyle {
+    /// The color vision deficiency the style simulates, if any.
+    fn deficiency(self) -> Option<Deficiency> {
+        return match self {
+            PlotStyle::Modern | PlotStyle::Nbs1955 => None,
+            PlotStyle::Protanopia => Some(Deficiency::Protanopia),
+            PlotStyle::Deuteranopia => Some(Deficiency::Deuteranopia),
+            PlotStyle::Tritanopia => Some(Deficiency::Tritanopia),
+        };
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum PlotFormat {
+    /// Draw PNG files directly
+    Png,
+    /// Write gnuplot scripts and render them to PNG with gnuplot
+    Gnuplot,
+    /// Write SVG files directly
+    Svg,
+    /// Write one HTML page with every chart, describing regions on hover
+    Html,
+    /// Write standalone TikZ figures for LaTeX documents
+    Tikz,
+    /// Write one printable PDF with two charts to a page, like the
+    /// publication
+    Pdf,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SwatchNamingArg {
+    /// The color id, like 1.png
+    Id,
+    /// The abbreviation, like viv.Pk.png
+    Abbr,
+}
+
+impl From<SwatchNamingArg> for SwatchNaming {
+    fn from(arg: SwatchNamingArg) -> Self {
+        return match arg {
+            SwatchNamingArg::Id => SwatchNaming::Id,
+            SwatchNamingArg::Abbr => SwatchNaming::Abbr,
+        };
+    }
+}
+
+/// The plot configuration used if there is no --plot-config.
+const PLOT_CONFIG: &str = "plot.toml";
+
+/// Draw the charts with the configuration at `path`, or in `plot.toml` if
+/// there is one, with the fonts given on the command line.
+pub fn load_plot_config(cli: &Cli) {
+    let path = match cli.plot_config.as_deref() {
+        Some(v) => Some(v),
+        None if Path::new(PLOT_CONFIG).exists() => Some(Path::new(PLOT_CONFIG)),
+        None => None,
+    };
+    let mut config = match path.map(PlotConfig::load) {
+        Some(Ok(v)) => v,
+        Some(Err(e)) => {
+            error!("{}: {}.", path.unwrap().display(), e);
+            std::process::exit(1);
+        }
+        None => PlotConfig::default(),
+    };
+
+    if let Some(name) = &cli.font_name {
+        config.font_name = name.clone();
+    }
+    if let Some(path) = &cli.font_path {
+        config.font_path = Some(path.clone());
+    }
+    if let Some(path) = &config.font_path {
+        let valid = std::fs::read(path)
+            .map(|x| ttf_parser::Face::from_slice(&x, 0).is_ok())
+            .unwrap_or(false);
+        if !valid {
+            error!("{} is not a TrueType font.", path.display());
+            std::process::exit(1);
+        }
+    }
+    if let Err(e) = plot::config::set_config(config) {
+        error!("{}.", e);
+        std::process::exit(1);
+    }
+}
+
+/// How often `plot --watch` looks at the dataset.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Validate the dataset and re-render the charts that changed, and again
+/// every time the dataset, or its name file, is saved, until interrupted.
+/// Problems with the dataset are logged, and the charts left as they are
+/// until the next save.
+fn watch(path: &Path, lang: Option<&str>, render: impl Fn(&Dataset)) {
+    let files = match lang {
+        Some(lang) => vec![path.to_path_buf(), translation_path(path, lang)],
+        None => vec![path.to_path_buf()],
+    };
+    let modified = || {
+        return files
+            .iter()
+            .map(|x| std::fs::metadata(x).and_then(|x| x.modified()).ok())
+            .collect::<Vec<_>>();
+    };
+
+    let mut last = None;
+    loop {
+        let now = modified();
+        if last.as_ref() != Some(&now) {
+            // let the editor finish writing
+            if last.is_some() {
+                std::thread::sleep(WATCH_INTERVAL);
+            }
+            last = Some(modified());
+            match try_load_dataset(path) {
+                Ok(mut dataset) => {
+                    if lang.is_none_or(|lang| try_translate(&mut dataset, path, lang)) {
+                        render(&dataset);
+                    }
+                }
+                Err(e) => error!("{}.", e),
+            }
+            info!("Watching {} for changes.", path.display());
+        }
+        std::thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+/// The share of its chroma a color may lose to fit into sRGB before its
+/// rendering is worth a warning.
+const HEAVY_CLIP: f32 = 0.25;
+
+/// The representative colors of the level 3 names for the charts, with
+/// chroma reduced in the perceptual space of the plot configuration.
+fn chart_colors(dataset: &Dataset) -> Vec<Srgb> {
+    return get_representative_colors(
+        dataset,
+        representative(),
+        plot::config::config().perceptual_space,
+    );
+}
+
+/// Render the charts colored by the names of `level` 1 or 2.
+fn plot_level(dataset: &Dataset, format: PlotFormat, level: u32) {
+    warn_about_clipping(dataset);
+
+    let colors = match level {
+        1 => get_level1_mean_colors(dataset),
+        _ => get_level2_mean_colors(dataset),
+    };
+    match format {
+        PlotFormat::Svg => {
+            plot::svg::generate_level_svg_with_progress(dataset, level, &colors, &mut log_progress)
+        }
+        _ => check_png(plot::png::generate_level_charts_with_progress(
+            dataset,
+            level,
+            &colors,
+            &mut log_progress,
+        )),
+    }
+}
+
+fn warn_about_clipping(dataset: &Dataset) {
+    let heavy = get_gamut_clipping_in(dataset, plot::config::config().perceptual_space)
+        .iter()
+        .filter(|x| x.loss_fraction() > HEAVY_CLIP)
+        .count();
+    if heavy > 0 {
+        warn!(
+            "{} colors lose more than {}% of their chroma to fit into sRGB, and are drawn duller than they are; see `stats`.",
+            heavy,
+            HEAVY_CLIP * 100.0
+        );
+    }
+}
+
+/// Exit with an error if gnuplot could not render the scripts.
+fn check_gnuplot(result: Result<(), GnuplotError>) {
+    match result {
+        Ok(()) => {}
+        Err(GnuplotError::NotFound) => {
+            error!("gnuplot is not installed; install it, or pass --no-render to only write the scripts.");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            error!("{}.", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Exit with an error if the PNG files could not be drawn or written.
+fn check_png<T>(result: std::io::Result<T>) -> T {
+    return match result {
+        Ok(x) => x,
+        Err(e) => {
+            error!("cannot write the PNG files: {}.", e);
+            std::process::exit(1);
+        }
+    };
+}
+
+fn plot(
+    dataset: &Dataset,
+    kind: PlotKind,
+    format: PlotFormat,
+    only_changed: bool,
+    style: PlotStyle,
+    no_render: bool,
+    overlays: &ChartOverlays,
+) {
+    // the book pages show chips of the renotation data, not the centroids
+    if !matches!(kind, PlotKind::Book) {
+        warn_about_clipping(dataset);
+    }
+
+    if no_render && format != PlotFormat::Gnuplot {
+        error!("--no-render is only available with --format gnuplot.");
+        std::process::exit(1);
+    }
+
+    if !overlays.centroids.is_empty() || overlays.gamut_boundary {
+        let modern_charts = matches!(kind, PlotKind::Charts)
+            && matches!(format, PlotFormat::Png | PlotFormat::Gnuplot)
+            && style == PlotStyle::Modern
+            && !only_changed;
+        if !modern_charts {
+            error!("--centroids and --gamut-boundary are only available for PNG and gnuplot charts in the modern style, without --only-changed.");
+            std::process::exit(1);
+        }
+    }
+
+    if only_changed {
+        if !matches!((kind, format), (PlotKind::Charts, PlotFormat::Png)) {
+            error!("--only-changed is only available for PNG charts.");
+            std::process::exit(1);
+        }
+        let colors = chart_colors(dataset);
+        let count = match style {
+            PlotStyle::Modern => check_png(plot::png::generate_changed_charts_with_progress(
+                dataset,
+                &colors,
+                &mut log_progress,
+            )),
+            PlotStyle::Nbs1955 => check_png(plot::png::generate_changed_nbs_1955_charts(
+                dataset, &colors,
+            )),
+            _ => {
+                error!("--only-changed is not available for simulated color vision deficiencies.");
+                std::process::exit(1);
+            }
+        };
+        info!("{} of {} charts changed.", count, dataset.hues().len());
+        return;
+    }
+
+    if style == PlotStyle::Nbs1955 {
+        if !matches!((kind, format), (PlotKind::Charts, PlotFormat::Png)) {
+            error!("the nbs-1955 style is only available for PNG charts.");
+            std::process::exit(1);
+        }
+        let colors = chart_colors(dataset);
+        check_png(plot::png::generate_nbs_1955_charts_with_progress(
+            dataset,
+            &colors,
+            &mut log_progress,
+        ));
+        return;
+    }
+
+    if let Some(deficiency) = style.deficiency() {
+        if !matches!((kind, format), (PlotKind::Charts, PlotFormat::Png)) {
+            error!(
+                "the {} style is only available for PNG charts.",
+                deficiency.name()
+            );
+            std::process::exit(1);
+        }
+        let colors = chart_colors(dataset);
+        check_png(plot::png::generate_cvd_charts_with_progress(
+            dataset,
+            &colors,
+            deficiency,
+            &mut log_progress,
+        ));
+        return;
+    }
+
+    match (kind, format) {
+        (PlotKind::Charts, PlotFormat::Png) => {
+            let colors = chart_colors(dataset);
+            check_png(plot::png::generate_charts_with_progress(
+                dataset,
+                &colors,
+                overlays,
+                &mut log_progress,
+            ));
+        }
+        (PlotKind::Charts, PlotFormat::Gnuplot) => {
+            let colors = chart_colors(dataset);
+            check_gnuplot(plot::charts::generate_gnuplot_with_progress(
+                dataset,
+                &colors,
+                overlays,
+                !no_render,
+                &mut log_progress,
+            ));
+        }
+        (PlotKind::Charts, PlotFormat::Svg) => {
+            let colors = chart_colors(dataset);
+            plot::svg::generate_svg_with_progress(dataset, &colors, &mut log_progress);
+        }
+        (PlotKind::Charts, PlotFormat::Html) => {
+            let colors = chart_colors(dataset);
+            plot::html::generate_html_with_progress(dataset, &colors, &mut log_progress);
+        }
+        (PlotKind::Charts, PlotFormat::Tikz) => {
+            let colors = chart_colors(dataset);
+            plot::tikz::generate_tikz_with_progress(dataset, &colors, &mut log_progress);
+        }
+        (PlotKind::Charts, PlotFormat::Pdf) => {
+            let colors = chart_colors(dataset);
+            plot::pdf::generate_pdf_with_progress(dataset, &colors, &mut log_progress);
+        }
+        (_, PlotFormat::Tikz) => {
+            error!("TikZ output is only available for the charts.");
+            std::process::exit(1);
+        }
+        (_, PlotFormat::Pdf) => {
+            error!("PDF output is only available for the charts.");
+            std::process::exit(1);
+        }
+        (PlotKind::Book, PlotFormat::Png) => check_png(plot::png::generate_book_with_progress(
+            dataset,
+            &mut log_progress,
+        )),
+        (PlotKind::Book, PlotFormat::Gnuplot) => check_gnuplot(
+            plot::book::generate_gnuplot_with_progress(dataset, !no_render, &mut log_progress),
+        ),
+        (PlotKind::Book, PlotFormat::Svg) => {
+            error!("SVG output is not available for the book pages.");
+            std::process::exit(1);
+        }
+        (PlotKind::Book, PlotFormat::Html)
+        | (PlotKind::Wheel, PlotFormat::Html)
+        | (PlotKind::Poster, PlotFormat::Html)
+        | (PlotKind::Slices, PlotFormat::Html) => {
+            error!("HTML output is only available for the charts.");
+            std::process::exit(1);
+        }
+        (PlotKind::Wheel, PlotFormat::Png) => {
+            let colors = chart_colors(dataset);
+            check_png(plot::png::generate_wheel(dataset, &colors));
+        }
+        (PlotKind::Wheel, PlotFormat::Svg) => {
+            let colors = chart_colors(dataset);
+            plot::svg::generate_wheel_svg(dataset, &colors);
+        }
+        (PlotKind::Wheel, PlotFormat::Gnuplot) => {
+            error!("gnuplot output is not available for the hue wheel.");
+            std::process::exit(1);
+        }
+        (PlotKind::Poster, PlotFormat::Png) => {
+            let colors = chart_colors(dataset);
+            check_png(plot::png::generate_poster(dataset, &colors));
+        }
+        (PlotKind::Poster, PlotFormat::Svg) => {
+            let colors = chart_colors(dataset);
+            plot::svg::generate_poster_svg(dataset, &colors);
+        }
+        (PlotKind::Poster, PlotFormat::Gnuplot) => {
+            error!("gnuplot output is not available for the poster.");
+            std::process::exit(1);
+        }
+        (PlotKind::Slices, PlotFormat::Png) => {
+            let colors = chart_colors(dataset);
+            check_png(plot::png::generate_slices(dataset, &colors));
+        }
+        (PlotKind::Slices, PlotFormat::Svg) => {
+            let colors = chart_colors(dataset);
+            plot::svg::generate_slices_svg(dataset, &colors);
+        }
+        (PlotKind::Slices, PlotFormat::Gnuplot) => {
+            error!("gnuplot output is not available for the slices.");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn export_all(dataset: &Dataset, out: &Path) {
+    let colors = export_colors(dataset);
+    if let Err(e) = export::bundle::write_bundle(dataset, &colors, out) {
+        error!("cannot write to {}: {}.", out.display(), e);
+        std::process::exit(1);
+    }
+    info!("Wrote {}.", out.join(export::bundle::MANIFEST).display());
+}
+
+fn export_swatches(dataset: &Dataset, out: &Path, size: u32, naming: SwatchNaming) {
+    let colors = export_colors(dataset);
+    if let Err(e) = export::swatches::write_swatches(dataset, &colors, out, size, naming) {
+        error!("cannot write to {}: {}.", out.display(), e);
+        std::process::exit(1);
+    }
+    info!("Wrote {}.", out.join(export::swatches::INDEX).display());
+}
+
+pub fn site(dataset: &Dataset, out: &Path) {
+    let colors = export_colors(dataset);
+    if let Err(e) = export::site::write_site(dataset, &colors, out) {
+        error!("cannot write to {}: {}.", out.display(), e);
+        std::process::exit(1);
+    }
+    info!("Wrote {}.", out.join("index.html").display());
+}
+
+/// Run `plot`: the charts of the names of `--level`, the charts that change
+/// every time the dataset is saved with `--watch`, or the figures once.
+pub fn plot_command(cli: &Cli, args: &PlotArgs) {
+    let overlays = ChartOverlays {
+        centroids: args
+            .centroids
+            .as_deref()
+            .map(read_centroid_table)
+            .unwrap_or_default(),
+        gamut_boundary: args.gamut_boundary,
+    };
+    let overlaid = args.centroids.is_some() || args.gamut_boundary;
+
+    if args.level != 3 {
+        let charts = matches!(args.kind, PlotKind::Charts)
+            && matches!(args.format, PlotFormat::Png | PlotFormat::Svg)
+            && args.style == PlotStyle::Modern;
+        let plain = !args.only_changed && !args.no_render && !overlaid && !args.watch;
+        if !charts || !plain {
+            error!("--level is only available for PNG and SVG charts in the modern style, without --only-changed, --watch or overlays.");
+            std::process::exit(1);
+        }
+    }
+
+    if args.watch {
+        let (kind, format, style) = (args.kind, args.format, args.style);
+        if !matches!((kind, format), (PlotKind::Charts, PlotFormat::Png)) {
+            error!("--watch is only available for PNG charts.");
+            std::process::exit(1);
+        }
+        if overlaid {
+            error!("--watch only re-renders the charts that changed, without --centroids or --gamut-boundary.");
+            std::process::exit(1);
+        }
+        watch(&cli.dataset, cli.lang.as_deref(), |dataset| {
+            plot(dataset, kind, format, true, style, false, &overlays)
+        });
+        return;
+    }
+
+    let mut dataset = load_dataset(&cli.dataset);
+    if let Some(lang) = &cli.lang {
+        translate(&mut dataset, &cli.dataset, lang);
+    }
+    if args.level != 3 {
+        plot_level(&dataset, args.format, args.level);
+        return;
+    }
+    plot(
+        &dataset,
+        args.kind,
+        args.format,
+        args.only_changed,
+        args.style,
+        args.no_render,
+        &overlays,
+    );
+}
+
+/// Write one of the exports into a directory of their own.
+pub fn export_bundle(dataset: &Dataset, bundle: ExportBundle) {
+    match bundle {
+        ExportBundle::All { out } => export_all(dataset, &out),
+        ExportBundle::Swatches { out, size, naming } => {
+            export_swatches(dataset, &out, size, naming.into())
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Logging to standard error, with a progress bar for long-running work on
+//! a terminal.
+
+use std::io::IsTerminal;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use log::{debug, info, Level, LevelFilter, Log, Metadata, Record};
+
+use iscc_nbs_validator::progress::{ProgressEvent, Stage};
+
+/// Writes log records to standard error, so that they don't end up in
+/// exports written to standard output.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        return metadata.level() <= log::max_level();
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // clear the progress bar; the next event draws it again below
+        if PROGRESS_BAR.lock().unwrap().is_some() {
+            eprint!("\r\x1b[K");
+        }
+        match record.level() {
+            Level::Error => eprintln!("Error: {}", record.args()),
+            Level::Warn => eprintln!("Warning: {}", record.args()),
+            Level::Info => eprintln!("{}", record.args()),
+            Level::Debug | Level::Trace => {
+                eprintln!("{}: {}", record.target(), record.args())
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Log at info level by default, more with each `-v` and less with each
+/// `-q`.
+pub fn init_logging(verbose: u8, quiet: u8) {
+    let level = match verbose as i32 - quiet as i32 {
+        ..=-2 => LevelFilter::Error,
+        -1 => LevelFilter::Warn,
+        0 => LevelFilter::Info,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(level);
+}
+
+/// When the stage on the progress bar started, while one is shown.
+static PROGRESS_BAR: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// The width of the progress bar, in characters.
+const PROGRESS_BAR_WIDTH: usize = 30;
+
+/// A duration as minutes and seconds, like "1:05".
+fn minutes(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    return format!("{}:{:02}", seconds / 60, seconds % 60);
+}
+
+/// Log how long-running work is going: when each stage starts and finishes
+/// at info level, and every unit of work at debug level.
+///
+/// At info level on a terminal, stages are shown as a progress bar with the
+/// time left instead, redrawn with every unit of work; `-q` hides it.
+pub fn log_progress(event: ProgressEvent) {
+    let stage = match event.stage {
+        Stage::Parse => "Parsing",
+        Stage::Validate => "Validating",
+        Stage::Render => "Rendering",
+        Stage::Classify => "Classifying",
+    };
+    if log::max_level() != LevelFilter::Info || !std::io::stderr().is_terminal() {
+        if event.done == 0 {
+            info!("{}: {} to do.", stage, event.total);
+        } else if event.done == event.total {
+            info!("{}: done.", stage);
+        } else {
+            debug!("{}: {} of {}.", stage, event.done, event.total);
+        }
+        return;
+    }
+
+    let mut bar = PROGRESS_BAR.lock().unwrap();
+    let started = *bar.get_or_insert_with(Instant::now);
+    if event.done == 0 {
+        *bar = Some(Instant::now());
+    }
+    let elapsed = started.elapsed();
+    if event.done >= event.total {
+        *bar = None;
+        eprintln!("\r\x1b[K{}: done in {}.", stage, minutes(elapsed));
+        return;
+    }
+
+    let filled = PROGRESS_BAR_WIDTH * event.done / event.total.max(1);
+    let left = match event.done {
+        0 => "--:--".to_string(),
+        done => minutes(elapsed.mul_f64((event.total - done) as f64 / done as f64)),
+    };
+    eprint!(
+        "\r\x1b[K{}: [{}{}] {} of {}, {} left",
+        stage,
+        "#".repeat(filled),
+        " ".repeat(PROGRESS_BAR_WIDTH - filled),
+        event.done,
+        event.total,
+        left
+    );
+}
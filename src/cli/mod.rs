@@ -0,0 +1,6 @@
+//! The parts of the command line tool that stand on their own: logging, and
+//! the commands that draw, which are only built with the `charts` feature.
+
+#[cfg(feature = "charts")]
+pub mod charts;
+pub mod logging;
@@ -0,0 +1,725 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use palette::{convert::FromColorUnclamped, Clamp, IntoColor, Lch, LinSrgb, Oklab, Oklch, Srgb};
+
+use crate::dataset::{deinfinite, ColorBlock, ColorName, Dataset};
+use crate::degree::weighted_degree_mean;
+use crate::gamut::{gamut_mapping, map_into};
+use crate::munsell::{MunsellColor, MunsellHue};
+use crate::naming::ColorNamingSystem;
+use crate::rgbspace::rgb_space;
+
+/// How to find the centroid of a name from the wedges of its blocks.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CentroidMethod {
+    /// Average the midpoints of the hue, chroma and value ranges of every
+    /// block, weighted by volume. This puts the centroid of a wedge at too
+    /// low a chroma, since more of its volume is at the outer edge.
+    Midpoint,
+    /// Average the exact centers of mass of every wedge in the plane of
+    /// hue and chroma, weighted by volume, as the center of mass of all of
+    /// them together.
+    #[default]
+    CenterOfMass,
+}
+
+#[derive(Clone)]
+struct ColorAccumulator {
+    v: f32,
+    c: f32,
+    /// The center hue of every block in degrees, weighted by its volume.
+    hues: Vec<(f32, f32)>,
+    /// The center of mass in the plane of hue and chroma, as Cartesian
+    /// coordinates weighted by volume.
+    x: f32,
+    y: f32,
+    volume: f32,
+}
+
+/// A block as a wedge of the Munsell solid, with blocks extending to `INF`
+/// cut off at chroma 16 and value 10.
+struct Wedge {
+    hue: (MunsellHue, MunsellHue),
+    chroma: (f32, f32),
+    value: (f32, f32),
+}
+
+impl Wedge {
+    fn of_block<S: ColorNamingSystem + ?Sized>(dataset: &S, block: &ColorBlock) -> Self {
+        let hues = dataset.hues();
+        let chromas = dataset.chromas();
+        let values = dataset.values();
+
+        let chroma_end = deinfinite(&chromas[block.chromas.end]);
+        let value_end = deinfinite(&values[block.values.end]);
+        return Wedge {
+            hue: (
+                hues[block.hues.start].parse().unwrap(),
+                hues[block.hues.end].parse().unwrap(),
+            ),
+            chroma: (
+                chromas[block.chromas.start].parse().unwrap(),
+                chroma_end.parse::<f32>().unwrap().min(16.0),
+            ),
+            value: (
+                values[block.values.start].parse().unwrap(),
+                value_end.parse::<f32>().unwrap().min(10.0),
+            ),
+        };
+    }
+
+    /// The volume, taking chroma as the radius and value as the height, in
+    /// Munsell steps.
+    fn volume(&self) -> f32 {
+        let hue_delta = self.hue.0.distance_to(self.hue.1);
+        let (inner, outer) = self.chroma;
+        let area = PI * (outer * outer - inner * inner) * hue_delta / 100.0;
+        return area * (self.value.1 - self.value.0);
+    }
+
+    /// How far the center of mass is from the neutral axis, in chroma steps;
+    /// it lies at the middle hue and value.
+    ///
+    /// The center of an annulus of radii `r` and `R` spanning an angle `α`
+    /// lies on its middle angle at `2/3 (R³ - r³) / (R² - r²)`, times
+    /// `sin(α/2) / (α/2)` for how much of it is off to the sides.
+    fn center_of_mass_chroma(&self) -> f32 {
+        let (inner, outer) = self.chroma;
+        let angle = self.hue.0.distance_to(self.hue.1) / 100.0 * 2.0 * PI;
+        let radius = if outer > inner {
+            2.0 / 3.0 * (outer.powi(3) - inner.powi(3)) / (outer * outer - inner * inner)
+        } else {
+            outer
+        };
+        let spread = if angle > 0.0 {
+            (angle / 2.0).sin() / (angle / 2.0)
+        } else {
+            1.0
+        };
+        return radius * spread;
+    }
+}
+
+/// Compute the centroid of each name of a level in Munsell space, indexed
+/// by id - 1, by accumulating every block into the bucket of its finest
+/// name's ancestor at that level.
+fn mean_munsell_of_level<S: ColorNamingSystem + ?Sized>(
+    dataset: &S,
+    level: u32,
+    method: CentroidMethod,
+) -> Vec<MunsellColor> {
+    let buckets = dataset.names_at(level).map_or(0, |names| names.len());
+    let bucket = |color_id: u32| -> Option<usize> {
+        let id = dataset.ancestor_id(dataset.levels(), color_id, level)?;
+        return Some((id - 1) as usize);
+    };
+
+    let mut acc: Vec<ColorAccumulator> = Vec::with_capacity(buckets);
+    acc.resize(
+        buckets,
+        ColorAccumulator {
+            v: 0.0,
+            c: 0.0,
+            hues: Vec::new(),
+            x: 0.0,
+            y: 0.0,
+            volume: 0.0,
+        },
+    );
+
+    for block in dataset.blocks() {
+        // a custom system may have no such level, or blocks of no name
+        let Some(a) = bucket(block.color_id).and_then(|i| acc.get_mut(i)) else {
+            continue;
+        };
+        let wedge = Wedge::of_block(dataset, block);
+        let volume = wedge.volume();
+
+        let center_chroma = (wedge.chroma.0 + wedge.chroma.1) / 2.0;
+        let center_value = (wedge.value.0 + wedge.value.1) / 2.0;
+        let center_hue = wedge.hue.0.midpoint(wedge.hue.1);
+        let radius = wedge.center_of_mass_chroma();
+
+        a.v += center_value * volume;
+        a.c += center_chroma * volume;
+        a.hues.push((center_hue.to_degrees(), volume));
+        a.x += radius * center_hue.to_radians().cos() * volume;
+        a.y += radius * center_hue.to_radians().sin() * volume;
+        a.volume += volume;
+    }
+
+    return acc
+        .into_iter()
+        .map(|a| match method {
+            CentroidMethod::Midpoint => {
+                let hue = MunsellHue::from_degrees(weighted_degree_mean(a.hues).unwrap_or(0.0));
+                MunsellColor::new(hue, a.v / a.volume, a.c / a.volume)
+            }
+            CentroidMethod::CenterOfMass => {
+                let chroma = a.x.hypot(a.y) / a.volume;
+                // wedges all around the neutral axis leave no hue
+                let hue = if chroma > 1e-4 {
+                    MunsellHue::from_radians(a.y.atan2(a.x))
+                } else {
+                    MunsellHue::new(0.0)
+                };
+                MunsellColor::new(hue, a.v / a.volume, chroma)
+            }
+        })
+        .collect::<Vec<MunsellColor>>();
+}
+
+/// Compute the centroid of each level 3 color in Munsell space, indexed by
+/// `color_id - 1`.
+///
+/// Each block is treated as a wedge of the Munsell solid, and the centroids
+/// are the centers of mass of all the wedges of a color together. Blocks
+/// extending to `INF` are cut off at chroma 16 and value 10. For another
+/// [`ColorNamingSystem`], these are the centroids of its finest names.
+pub fn get_mean_munsell<S: ColorNamingSystem + ?Sized>(dataset: &S) -> Vec<MunsellColor> {
+    return get_mean_munsell_with(dataset, CentroidMethod::default());
+}
+
+/// Like [`get_mean_munsell`], with the centroids found by `method`.
+pub fn get_mean_munsell_with<S: ColorNamingSystem + ?Sized>(
+    dataset: &S,
+    method: CentroidMethod,
+) -> Vec<MunsellColor> {
+    return mean_munsell_of_level(dataset, dataset.levels(), method);
+}
+
+/// Like [`get_mean_munsell`], for the level 2 names, indexed by id - 1.
+pub fn get_level2_mean_munsell<S: ColorNamingSystem + ?Sized>(dataset: &S) -> Vec<MunsellColor> {
+    return mean_munsell_of_level(dataset, 2, CentroidMethod::default());
+}
+
+/// Like [`get_mean_munsell`], for the level 1 names, indexed by id - 1.
+pub fn get_level1_mean_munsell<S: ColorNamingSystem + ?Sized>(dataset: &S) -> Vec<MunsellColor> {
+    return mean_munsell_of_level(dataset, 1, CentroidMethod::default());
+}
+
+/// How much of the Munsell solid a level 3 color covers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ColorVolume {
+    /// The volume of its blocks, taking chroma as the radius and value as
+    /// the height, in Munsell steps.
+    pub volume: f32,
+    pub blocks: usize,
+    /// The share of the whole solid, from 0 to 1.
+    pub fraction: f32,
+}
+
+/// Compute how much of the Munsell solid each level 3 color covers, indexed
+/// by `color_id - 1`, from the same wedges as [`get_mean_munsell`]: blocks
+/// extending to `INF` are cut off at chroma 16 and value 10.
+pub fn get_volumes<S: ColorNamingSystem + ?Sized>(dataset: &S) -> Vec<ColorVolume> {
+    let mut volumes = vec![ColorVolume::default(); dataset.finest_names().len()];
+    for block in dataset.blocks() {
+        let v = &mut volumes[(block.color_id - 1) as usize];
+        v.volume += Wedge::of_block(dataset, block).volume();
+        v.blocks += 1;
+    }
+
+    let total: f32 = volumes.iter().map(|x| x.volume).sum();
+    for v in volumes.iter_mut() {
+        v.fraction = v.volume / total;
+    }
+    return volumes;
+}
+
+/// The space in which the chroma of out-of-gamut colors is reduced, and in
+/// which colors are judged light or dark for the text on them.
+///
+/// Reducing the chroma in CIELAB LCh shifts the hue of some colors, most of
+/// all blues, which turn purple; Oklch keeps hues more nearly constant, and
+/// its lightness follows how light colors look more evenly across hues.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "charts", derive(serde::Deserialize))]
+#[cfg_attr(feature = "charts", serde(rename_all = "lowercase"))]
+pub enum PerceptualSpace {
+    #[default]
+    Cielab,
+    Oklab,
+}
+
+/// The Oklab lightness of the gray with CIELAB L* 40, past which colors are
+/// light.
+const OKLAB_LIGHT: f32 = 0.483;
+
+/// Whether text on `color`, in the channels of [`rgb_space`], should be
+/// dark rather than light.
+pub fn is_light(color: Srgb, space: PerceptualSpace) -> bool {
+    let linear = rgb_space().to_linear_srgb(color);
+    return match space {
+        PerceptualSpace::Cielab => {
+            let lch: Lch = linear.into_color();
+            lch.l > 40.0
+        }
+        PerceptualSpace::Oklab => {
+            let oklch: Oklch = linear.into_color();
+            oklch.l > OKLAB_LIGHT
+        }
+    };
+}
+
+/// How much the chroma of a centroid had to be reduced to bring it into the
+/// gamut of [`rgb_space`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GamutClip {
+    /// How many times the chroma was reduced, by 1% each time, with
+    /// [`crate::gamut::GamutMapping::ChromaClamp`]; 0 with the other
+    /// mappings.
+    pub steps: u32,
+    /// The chroma of the centroid, in CIELAB or Oklab, whichever it was
+    /// reduced in.
+    pub chroma: f32,
+    /// How much of `chroma` was lost.
+    pub chroma_loss: f32,
+    /// The color before it was clipped, in the channels of [`rgb_space`],
+    /// which may be outside of `[0, 1]`.
+    pub unclipped: Srgb,
+}
+
+impl GamutClip {
+    /// Whether the centroid was outside the gamut.
+    pub fn is_clipped(&self) -> bool {
+        return !self.unclipped.is_within_bounds();
+    }
+
+    /// The share of the chroma that was lost, from 0 to 1.
+    pub fn loss_fraction(&self) -> f32 {
+        if self.chroma == 0.0 {
+            return 0.0;
+        }
+        return self.chroma_loss / self.chroma;
+    }
+}
+
+/// Bring a color into the gamut of [`rgb_space`] with the mapping of
+/// [`gamut_mapping`], where `lc` gives its lightness and chroma.
+fn clip_chroma<C>(mut color: C, lc: impl Fn(&mut C) -> (&mut f32, &mut f32)) -> (Srgb, GamutClip)
+where
+    LinSrgb: FromColorUnclamped<C>,
+    C: Copy + FromColorUnclamped<LinSrgb>,
+{
+    let space = rgb_space();
+    let original = *lc(&mut color).1;
+    let unclipped = space.from_linear_srgb(LinSrgb::from_color_unclamped(color));
+    let mut mapped = map_into(color, &lc, gamut_mapping(), space);
+
+    let clip = GamutClip {
+        steps: mapped.steps,
+        chroma: original,
+        chroma_loss: original - *lc(&mut mapped.color).1,
+        unclipped,
+    };
+    return (mapped.rgb, clip);
+}
+
+/// Bring centroids into the sRGB gamut, recording how much each of them
+/// had to be clipped.
+fn to_srgb_with_clipping(
+    centroids: Vec<MunsellColor>,
+    space: PerceptualSpace,
+) -> Vec<(Srgb, GamutClip)> {
+    return centroids
+        .into_iter()
+        .map(|mun| match space {
+            PerceptualSpace::Cielab => {
+                clip_chroma(mun.to_display_lch(), |x| (&mut x.l, &mut x.chroma))
+            }
+            PerceptualSpace::Oklab => clip_chroma(mun.to_oklch(), |x| (&mut x.l, &mut x.chroma)),
+        })
+        .collect();
+}
+
+/// Bring centroids into the sRGB gamut.
+fn to_srgb(centroids: Vec<MunsellColor>, space: PerceptualSpace) -> Vec<Srgb> {
+    return to_srgb_with_clipping(centroids, space)
+        .into_iter()
+        .map(|x| x.0)
+        .collect();
+}
+
+/// Replace colors with the curated representative colors of `names`, where
+/// the dataset gives one, in [`rgb_space`].
+fn with_curated(mut colors: Vec<Srgb>, names: Option<&HashMap<u32, ColorName>>) -> Vec<Srgb> {
+    let space = rgb_space();
+    for (id, name) in names.into_iter().flatten() {
+        if let Some(srgb) = name.srgb {
+            colors[(id - 1) as usize] = space.from_srgb(srgb.into_format());
+        }
+    }
+    return colors;
+}
+
+/// Compute a representative sRGB color for each level 3 color, indexed by
+/// `color_id - 1`.
+///
+/// These are the centroids of [`get_mean_munsell`], with their chroma
+/// reduced as needed to bring them into the sRGB gamut, except for names
+/// with a curated representative color in the dataset. With another
+/// [`rgb_space`], they are brought into its gamut instead, and given in its
+/// channels.
+pub fn get_mean_colors<S: ColorNamingSystem + ?Sized>(dataset: &S) -> Vec<Srgb> {
+    return get_mean_colors_in(dataset, PerceptualSpace::Cielab);
+}
+
+/// Like [`get_mean_colors`], reducing the chroma in `space`.
+pub fn get_mean_colors_in<S: ColorNamingSystem + ?Sized>(
+    dataset: &S,
+    space: PerceptualSpace,
+) -> Vec<Srgb> {
+    let colors = to_srgb(get_mean_munsell(dataset), space);
+    return with_curated(colors, Some(dataset.finest_names()));
+}
+
+/// Which color stands for each level 3 name on the charts and in exports.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Representative {
+    /// The centroid of the name's blocks, or its curated color, like
+    /// [`get_mean_colors`].
+    #[default]
+    VolumeCentroid,
+    /// Published centroids, like those of NBS Circular 553, by color id, as
+    /// read by [`crate::centroids::parse_centroid_table`]. Names the table
+    /// leaves out get their volume centroid.
+    PublishedCentroid(HashMap<u32, MunsellColor>),
+    /// The most saturated color within the name's blocks that sRGB can show,
+    /// from [`Dataset::max_chroma_in_gamut`]. Names without one get their
+    /// volume centroid.
+    MaxChromaInGamut,
+}
+
+/// How many hues and values [`Representative::MaxChromaInGamut`] tries
+/// across each block.
+const MAX_CHROMA_SAMPLES: usize = 3;
+
+/// Compute the sRGB color of every level 3 name chosen by `representative`,
+/// indexed by `color_id - 1`, reducing the chroma in `space` as needed to
+/// bring them into the sRGB gamut.
+///
+/// Curated representative colors in the dataset only replace volume
+/// centroids.
+pub fn get_representative_colors(
+    dataset: &Dataset,
+    representative: &Representative,
+    space: PerceptualSpace,
+) -> Vec<Srgb> {
+    let mut colors = get_mean_munsell(dataset);
+    match representative {
+        Representative::VolumeCentroid => return get_mean_colors_in(dataset, space),
+        Representative::PublishedCentroid(published) => {
+            for (id, color) in published.iter() {
+                if let Some(x) = id.checked_sub(1).and_then(|i| colors.get_mut(i as usize)) {
+                    *x = color.clone();
+                }
+            }
+        }
+        Representative::MaxChromaInGamut => {
+            let saturated = dataset.max_chroma_in_gamut(MAX_CHROMA_SAMPLES);
+            for (x, color) in colors.iter_mut().zip(saturated) {
+                if let Some(color) = color {
+                    *x = color;
+                }
+            }
+        }
+    }
+    return to_srgb(colors, space);
+}
+
+/// How much the chroma of each color of [`get_mean_colors`] was reduced to
+/// bring it into the sRGB gamut, indexed by `color_id - 1`. Names with a
+/// curated representative color are not clipped.
+pub fn get_gamut_clipping(dataset: &Dataset) -> Vec<GamutClip> {
+    return get_gamut_clipping_in(dataset, PerceptualSpace::Cielab);
+}
+
+/// Like [`get_gamut_clipping`], reducing the chroma in `space`.
+pub fn get_gamut_clipping_in(dataset: &Dataset, space: PerceptualSpace) -> Vec<GamutClip> {
+    let mut clipping = to_srgb_with_clipping(get_mean_munsell(dataset), space)
+        .into_iter()
+        .map(|x| x.1)
+        .collect::<Vec<GamutClip>>();
+    for (id, name) in dataset.names().iter() {
+        if name.srgb.is_some() {
+            clipping[(id - 1) as usize] = GamutClip::default();
+        }
+    }
+    return clipping;
+}
+
+/// Like [`get_mean_colors`], for the level 2 names, indexed by id - 1.
+pub fn get_level2_mean_colors<S: ColorNamingSystem + ?Sized>(dataset: &S) -> Vec<Srgb> {
+    let colors = to_srgb(get_level2_mean_munsell(dataset), PerceptualSpace::Cielab);
+    return with_curated(colors, dataset.names_at(2));
+}
+
+/// Like [`get_mean_colors`], for the level 1 names, indexed by id - 1.
+pub fn get_level1_mean_colors<S: ColorNamingSystem + ?Sized>(dataset: &S) -> Vec<Srgb> {
+    let colors = to_srgb(get_level1_mean_munsell(dataset), PerceptualSpace::Cielab);
+    return with_curated(colors, dataset.names_at(1));
+}
+
+/// Order colors so that neighbors look alike, returning indices into
+/// `colors`.
+///
+/// This is a traveling salesman path through Oklab: it starts from the
+/// darkest color and always goes on to the nearest one not yet visited,
+/// then reverses stretches of the path for as long as that shortens it.
+pub fn perceptual_order(colors: &[Srgb]) -> Vec<usize> {
+    let n = colors.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let lab = colors
+        .iter()
+        .map(|x| {
+            let lab: Oklab = x.into_linear().into_color();
+            return lab;
+        })
+        .collect::<Vec<Oklab>>();
+    let distance = |i: usize, j: usize| {
+        let (a, b) = (lab[i], lab[j]);
+        return ((a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)).sqrt();
+    };
+
+    let darkest = (0..n)
+        .min_by(|i, j| lab[*i].l.partial_cmp(&lab[*j].l).unwrap())
+        .unwrap();
+    let mut path = vec![darkest];
+    let mut visited = vec![false; n];
+    visited[darkest] = true;
+    for _ in 1..n {
+        let last = *path.last().unwrap();
+        let next = (0..n)
+            .filter(|x| !visited[*x])
+            .min_by(|i, j| distance(last, *i).partial_cmp(&distance(last, *j)).unwrap())
+            .unwrap();
+        visited[next] = true;
+        path.push(next);
+    }
+
+    // 2-opt: replace the edges (i, i + 1) and (j, j + 1) with (i, j) and
+    // (i + 1, j + 1) by reversing what lies between; the path is open, so
+    // the last stretch can be reversed without a second edge
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..n.saturating_sub(2) {
+            for j in (i + 2)..n {
+                let before = distance(path[i], path[i + 1])
+                    + if j + 1 < n {
+                        distance(path[j], path[j + 1])
+                    } else {
+                        0.0
+                    };
+                let after = distance(path[i], path[j])
+                    + if j + 1 < n {
+                        distance(path[i + 1], path[j + 1])
+                    } else {
+                        0.0
+                    };
+                if after < before - 1e-6 {
+                    path[i + 1..=j].reverse();
+                    improved = true;
+                }
+            }
+        }
+    }
+
+    return path;
+}
+
+#[cfg(test)]
+mod test {
+    use palette::{IntoColor, Srgb};
+
+    use crate::colors::{
+        get_gamut_clipping, get_gamut_clipping_in, get_level1_mean_colors, get_level1_mean_munsell,
+        get_level2_mean_colors, get_level2_mean_munsell, get_mean_colors, get_mean_munsell,
+        get_mean_munsell_with, get_representative_colors, get_volumes, is_light, perceptual_order,
+        CentroidMethod, PerceptualSpace, Representative,
+    };
+    use crate::dataset::BUNDLED_XML;
+    use crate::Dataset;
+
+    #[test]
+    fn level_centroids() {
+        let dataset = Dataset::bundled();
+        let level3 = get_mean_munsell(&dataset);
+        let level2 = get_level2_mean_munsell(&dataset);
+        let level1 = get_level1_mean_munsell(&dataset);
+        assert_eq!(level2.len(), 29);
+        assert_eq!(level1.len(), 13);
+
+        // a level 2 name with a single level 3 name shares its centroid
+        for (id, name) in dataset.level2_names().iter() {
+            let children = dataset
+                .names()
+                .iter()
+                .filter(|(_, x)| x.parent == Some(*id))
+                .map(|(x, _)| *x)
+                .collect::<Vec<u32>>();
+            if children.len() == 1 {
+                let (a, b) = (
+                    &level2[(id - 1) as usize],
+                    &level3[(children[0] - 1) as usize],
+                );
+                assert!((a.value - b.value).abs() < 1e-4, "{}", name.name);
+                assert!((a.chroma - b.chroma).abs() < 1e-4, "{}", name.name);
+            }
+        }
+
+        // gray lies between white and black
+        let value = |name: &str| {
+            let (id, _) = dataset
+                .level1_names()
+                .iter()
+                .find(|(_, x)| x.name == name)
+                .unwrap();
+            return level1[(id - 1) as usize].value;
+        };
+        assert!(value("White") > value("Gray"));
+        assert!(value("Gray") > value("Black"));
+
+        // the center of mass of a wedge lies further out than the midpoint
+        // of its chroma range, at the same value; colors spread over many
+        // hues can still come out a little grayer
+        let midpoints = get_mean_munsell_with(&dataset, CentroidMethod::Midpoint);
+        assert_eq!(midpoints.len(), level3.len());
+        for (a, b) in level3.iter().zip(midpoints.iter()) {
+            assert!((a.value - b.value).abs() < 1e-4);
+        }
+        let total = |colors: &[crate::MunsellColor]| colors.iter().map(|x| x.chroma).sum::<f32>();
+        assert!(total(&level3) > total(&midpoints));
+        assert!(level3[0].chroma > midpoints[0].chroma);
+    }
+
+    #[test]
+    fn gamut_clipping() {
+        let dataset = Dataset::bundled();
+        let clipping = get_gamut_clipping(&dataset);
+        assert_eq!(clipping.len(), 267);
+
+        // white fits into sRGB as it is, and vivid pink doesn't
+        assert_eq!(clipping[262].steps, 0);
+        assert_eq!(clipping[262].loss_fraction(), 0.0);
+        assert!(clipping[0].steps > 0);
+        assert!(clipping[0].loss_fraction() > 0.5);
+        assert!(clipping[0].chroma_loss < clipping[0].chroma);
+
+        // the same in Oklch, where chroma is on another scale
+        let oklab = get_gamut_clipping_in(&dataset, PerceptualSpace::Oklab);
+        assert_eq!(oklab[262].steps, 0);
+        assert!(oklab[0].steps > 0);
+        assert!(oklab[0].chroma < 1.0);
+        for space in [PerceptualSpace::Cielab, PerceptualSpace::Oklab] {
+            assert!(is_light(Srgb::new(1.0, 1.0, 1.0), space));
+            assert!(!is_light(Srgb::new(0.0, 0.0, 0.2), space));
+        }
+
+        // curated colors are taken as they are
+        let text = BUNDLED_XML.replacen(
+            "name=\"Vivid pink\" abbr=\"viv.Pk\"",
+            "name=\"Vivid pink\" abbr=\"viv.Pk\" srgb=\"#ff7f8f\"",
+            1,
+        );
+        let curated = get_gamut_clipping(&Dataset::parse(&text).unwrap());
+        assert_eq!(curated[0].steps, 0);
+        assert_eq!(curated[1], clipping[1]);
+
+        // the most saturated displayable strong pink is more saturated than
+        // its clipped centroid, and published centroids stand in for the
+        // computed ones only where given
+        let space = PerceptualSpace::Cielab;
+        let centroids = get_representative_colors(&dataset, &Representative::default(), space);
+        assert_eq!(centroids, get_mean_colors(&dataset));
+        let saturated =
+            get_representative_colors(&dataset, &Representative::MaxChromaInGamut, space);
+        let chroma = |x: Srgb| {
+            let lch: palette::Lch = x.into_linear().into_color();
+            return lch.chroma;
+        };
+        assert!(chroma(saturated[1]) > chroma(centroids[1]) + 5.0);
+        let published = [(2, "5R 5/10".parse().unwrap())].into_iter().collect();
+        let published = get_representative_colors(
+            &dataset,
+            &Representative::PublishedCentroid(published),
+            space,
+        );
+        assert_ne!(published[1], centroids[1]);
+        assert_eq!(published[2], centroids[2]);
+    }
+
+    #[test]
+    fn color_volumes() {
+        let dataset = Dataset::bundled();
+        let volumes = get_volumes(&dataset);
+        assert_eq!(volumes.len(), 267);
+        assert_eq!(volumes.iter().map(|x| x.blocks).sum::<usize>(), 932);
+        assert!(volumes.iter().all(|x| x.volume > 0.0));
+        let total: f32 = volumes.iter().map(|x| x.fraction).sum();
+        assert!((total - 1.0).abs() < 1e-4);
+
+        // the solid is a cylinder of radius 16 and height 10
+        let volume: f32 = volumes.iter().map(|x| x.volume).sum();
+        assert!((volume - std::f32::consts::PI * 256.0 * 10.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn curated_colors() {
+        let computed = get_mean_colors(&Dataset::bundled());
+
+        // the centroid of vivid pink loses so much chroma to the gamut that
+        // it falls into light pink
+        let text = BUNDLED_XML
+            .replacen(
+                "name=\"Vivid pink\" abbr=\"viv.Pk\"",
+                "name=\"Vivid pink\" abbr=\"viv.Pk\" srgb=\"#ff7f8f\"",
+                1,
+            )
+            .replacen(
+                "name=\"Pink\" abbr=\"Pk\"",
+                "name=\"Pink\" abbr=\"Pk\" srgb=\"#ff7f8f\"",
+                1,
+            );
+        let dataset = Dataset::parse(&text).unwrap();
+
+        let colors = get_mean_colors(&dataset);
+        assert_eq!(colors[0].into_format::<u8>(), Srgb::new(0xff, 0x7f, 0x8f));
+        assert_eq!(colors[1], computed[1]);
+        assert_eq!(
+            get_level1_mean_colors(&dataset)[0].into_format::<u8>(),
+            Srgb::new(0xff, 0x7f, 0x8f)
+        );
+        assert_ne!(get_level2_mean_colors(&dataset)[0], colors[0]);
+    }
+
+    #[test]
+    fn perceptual_path() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let order = perceptual_order(&colors);
+
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..colors.len()).collect::<Vec<usize>>());
+        // the darkest first
+        assert_eq!(dataset.names()[&(order[0] as u32 + 1)].name, "Olive black");
+
+        let length = |order: &[usize]| -> f32 {
+            return order
+                .windows(2)
+                .map(|x| {
+                    let (a, b) = (colors[x[0]], colors[x[1]]);
+                    (a.red - b.red).abs() + (a.green - b.green).abs() + (a.blue - b.blue).abs()
+                })
+                .sum();
+        };
+        let by_id = (0..colors.len()).collect::<Vec<usize>>();
+        assert!(length(&order) < length(&by_id) / 2.0);
+    }
+}
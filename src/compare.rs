@@ -0,0 +1,112 @@
+//! Where two level 3 colors meet in the color solid, to help decide which
+//! of two similar names fits a color.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::dataset::{block_cells, Dataset};
+
+/// The direction across a face of the color solid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FaceAxis {
+    Hue,
+    Chroma,
+    Value,
+}
+
+/// The faces one block of a color shares with one block of another.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SharedFace {
+    /// The index in [`Dataset::blocks`] of the block of the first color.
+    pub block: usize,
+    /// The index in [`Dataset::blocks`] of the block of the second color.
+    pub neighbor: usize,
+    /// The direction from one block to the other.
+    pub axis: FaceAxis,
+    /// How many cells of the first block touch the second, each cell being
+    /// the space between neighboring hue, chroma and value boundaries.
+    pub cells: usize,
+}
+
+impl Dataset {
+    /// Find the faces that blocks of color `a` share with blocks of color
+    /// `b`, ordered by block. The hue circle wraps around, so blocks at 10RP
+    /// and 1R are neighbors.
+    pub fn shared_faces(&self, a: u32, b: u32) -> Vec<SharedFace> {
+        let hue_count = self.hues().len();
+        let cells_of = |id: u32| {
+            let mut cells = HashMap::new();
+            for (i, block) in self.blocks().iter().enumerate() {
+                if block.color_id == id {
+                    for cell in block_cells(block, hue_count) {
+                        cells.insert(cell, i);
+                    }
+                }
+            }
+            return cells;
+        };
+        let (cells_a, cells_b) = (cells_of(a), cells_of(b));
+
+        let mut faces: BTreeMap<(usize, usize, FaceAxis), usize> = BTreeMap::new();
+        for (&(h, c, v), &block) in cells_a.iter() {
+            let neighbors = [
+                (((h + 1) % hue_count, c, v), FaceAxis::Hue),
+                (((h + hue_count - 1) % hue_count, c, v), FaceAxis::Hue),
+                ((h, c + 1, v), FaceAxis::Chroma),
+                ((h, c.wrapping_sub(1), v), FaceAxis::Chroma),
+                ((h, c, v + 1), FaceAxis::Value),
+                ((h, c, v.wrapping_sub(1)), FaceAxis::Value),
+            ];
+            for (cell, axis) in neighbors {
+                if let Some(&neighbor) = cells_b.get(&cell) {
+                    *faces.entry((block, neighbor, axis)).or_default() += 1;
+                }
+            }
+        }
+
+        return faces
+            .into_iter()
+            .map(|((block, neighbor, axis), cells)| SharedFace {
+                block,
+                neighbor,
+                axis,
+                cells,
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::compare::FaceAxis;
+    use crate::Dataset;
+
+    #[test]
+    fn shared_faces() {
+        let dataset = Dataset::bundled();
+
+        // "vivid pink" lies beyond "strong pink" in chroma
+        let faces = dataset.shared_faces(1, 2);
+        assert!(!faces.is_empty());
+        assert!(faces.iter().any(|x| x.axis == FaceAxis::Chroma));
+        for face in faces.iter() {
+            assert_eq!(dataset.blocks()[face.block].color_id, 1);
+            assert_eq!(dataset.blocks()[face.neighbor].color_id, 2);
+            assert!(face.cells > 0);
+        }
+
+        // the same faces, seen from the other side
+        let reverse = dataset.shared_faces(2, 1);
+        assert_eq!(
+            faces.iter().map(|x| x.cells).sum::<usize>(),
+            reverse.iter().map(|x| x.cells).sum::<usize>()
+        );
+
+        // "vivid pink" and "black" are far apart
+        assert!(dataset.shared_faces(1, 267).is_empty());
+
+        // "light gray" and "medium gray" only meet in value
+        let grays = dataset.shared_faces(264, 265);
+        assert!(!grays.is_empty());
+        assert!(grays.iter().all(|x| x.axis == FaceAxis::Value));
+    }
+}
@@ -0,0 +1,431 @@
+//! A compact binary form of a validated dataset, which loads without
+//! parsing or validating the XML again.
+//!
+//! The file starts with [`MAGIC`] and a version, followed by the metadata,
+//! the names of all three levels, the boundaries and the blocks. Integers
+//! are big-endian; text is a `u32` length followed by UTF-8, and optional
+//! fields are preceded by a byte that is 1 if they are present. The lookup
+//! structures are derived again on loading, which takes microseconds rather
+//! than the milliseconds of validating the XML.
+//!
+//! Reading the fields needs only `alloc`, so that [`crate::lite`] can load
+//! compiled datasets without `std`.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+#[cfg(feature = "std")]
+use palette::Srgb;
+
+#[cfg(feature = "std")]
+use crate::dataset::{ColorBlock, ColorName, Dataset, Metadata};
+#[cfg(feature = "std")]
+use crate::munsell::MunsellHue;
+
+/// The first bytes of every compiled dataset.
+pub const MAGIC: &[u8; 8] = b"ISCC-NBS";
+const VERSION: u16 = 1;
+
+/// Why a compiled dataset could not be loaded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompiledError {
+    /// The data doesn't start with [`MAGIC`].
+    NotCompiled,
+    /// The data was compiled by a different version of this library.
+    UnsupportedVersion(u16),
+    /// The data ends in the middle of a field.
+    Truncated,
+    InvalidText,
+    /// A hue, chroma or value boundary that isn't a number or Munsell hue.
+    InvalidBoundary(String),
+    /// A name, by id, whose parent is missing from the level above.
+    InvalidParent(u32),
+    /// A block, by index, whose ranges run past the boundaries, or whose
+    /// color has no name.
+    InvalidBlock(usize),
+    /// Data is left over after the blocks.
+    TrailingData,
+}
+
+impl fmt::Display for CompiledError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            CompiledError::NotCompiled => write!(f, "not a compiled dataset"),
+            CompiledError::UnsupportedVersion(v) => {
+                write!(f, "compiled dataset version {} is not supported", v)
+            }
+            CompiledError::Truncated => write!(f, "compiled dataset is truncated"),
+            CompiledError::InvalidText => write!(f, "compiled dataset has invalid UTF-8 text"),
+            CompiledError::InvalidBoundary(x) => {
+                write!(f, "compiled dataset has an invalid boundary '{}'", x)
+            }
+            CompiledError::InvalidParent(id) => {
+                write!(f, "color {} of the compiled dataset has no parent", id)
+            }
+            CompiledError::InvalidBlock(i) => {
+                write!(
+                    f,
+                    "block {} of the compiled dataset is out of range or unnamed",
+                    i
+                )
+            }
+            CompiledError::TrailingData => {
+                write!(f, "compiled dataset has data after its blocks")
+            }
+        };
+    }
+}
+
+impl core::error::Error for CompiledError {}
+
+#[cfg(feature = "std")]
+fn push_u32(out: &mut Vec<u8>, x: u32) {
+    out.extend_from_slice(&x.to_be_bytes());
+}
+
+#[cfg(feature = "std")]
+fn push_str(out: &mut Vec<u8>, text: &str) {
+    push_u32(out, text.len() as u32);
+    out.extend_from_slice(text.as_bytes());
+}
+
+#[cfg(feature = "std")]
+fn push_option<T>(out: &mut Vec<u8>, x: &Option<T>, push: impl Fn(&mut Vec<u8>, &T)) {
+    match x {
+        Some(v) => {
+            out.push(1);
+            push(out, v);
+        }
+        None => out.push(0),
+    }
+}
+
+#[cfg(feature = "std")]
+fn push_names(out: &mut Vec<u8>, names: &HashMap<u32, ColorName>) {
+    let mut ids = names.keys().copied().collect::<Vec<u32>>();
+    ids.sort();
+
+    push_u32(out, ids.len() as u32);
+    for id in ids {
+        let name = &names[&id];
+        push_u32(out, id);
+        push_str(out, &name.name);
+        push_str(out, &name.abbr);
+        push_option(out, &name.parent, |out, x| push_u32(out, *x));
+        push_option(out, &name.srgb, |out, x| {
+            out.extend_from_slice(&[x.red, x.green, x.blue])
+        });
+        push_option(out, &name.description, |out, x| push_str(out, x));
+        push_option(out, &name.usage, |out, x| push_str(out, x));
+    }
+}
+
+#[cfg(feature = "std")]
+fn push_strings(out: &mut Vec<u8>, texts: &[String]) {
+    push_u32(out, texts.len() as u32);
+    for text in texts {
+        push_str(out, text);
+    }
+}
+
+/// Reads the fields of a compiled dataset in order.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    /// Check the magic and version of a compiled dataset, and read on from
+    /// after them.
+    pub(crate) fn after_header(bytes: &'a [u8]) -> Result<Self, CompiledError> {
+        if !is_compiled(bytes) {
+            return Err(CompiledError::NotCompiled);
+        }
+        let mut reader = Reader {
+            bytes: &bytes[MAGIC.len()..],
+        };
+        let version = reader.u16()?;
+        if version != VERSION {
+            return Err(CompiledError::UnsupportedVersion(version));
+        }
+        return Ok(reader);
+    }
+
+    /// Whether every field has been read.
+    pub(crate) fn is_done(&self) -> bool {
+        return self.bytes.is_empty();
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], CompiledError> {
+        if self.bytes.len() < n {
+            return Err(CompiledError::Truncated);
+        }
+        let (head, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        return Ok(head);
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, CompiledError> {
+        return Ok(self.take(1)?[0]);
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, CompiledError> {
+        return Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()));
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, CompiledError> {
+        return Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()));
+    }
+
+    pub(crate) fn string(&mut self) -> Result<String, CompiledError> {
+        let len = self.u32()? as usize;
+        let bytes = self.take(len)?;
+        return match core::str::from_utf8(bytes) {
+            Ok(v) => Ok(v.to_string()),
+            Err(_) => Err(CompiledError::InvalidText),
+        };
+    }
+
+    pub(crate) fn option<T>(
+        &mut self,
+        read: impl Fn(&mut Self) -> Result<T, CompiledError>,
+    ) -> Result<Option<T>, CompiledError> {
+        return match self.u8()? {
+            0 => Ok(None),
+            _ => Ok(Some(read(self)?)),
+        };
+    }
+
+    #[cfg(feature = "std")]
+    fn names(&mut self) -> Result<HashMap<u32, ColorName>, CompiledError> {
+        let count = self.u32()?;
+        let mut names = HashMap::new();
+        for _ in 0..count {
+            let id = self.u32()?;
+            let name = ColorName {
+                name: self.string()?,
+                abbr: self.string()?,
+                parent: self.option(|r| r.u32())?,
+                srgb: self.option(|r| {
+                    let c = r.take(3)?;
+                    Ok(Srgb::new(c[0], c[1], c[2]))
+                })?,
+                description: self.option(|r| r.string())?,
+                usage: self.option(|r| r.string())?,
+            };
+            names.insert(id, name);
+        }
+        return Ok(names);
+    }
+
+    pub(crate) fn strings(&mut self) -> Result<Vec<String>, CompiledError> {
+        let count = self.u32()?;
+        return (0..count).map(|_| self.string()).collect();
+    }
+
+    pub(crate) fn range(&mut self) -> Result<Range<usize>, CompiledError> {
+        return Ok((self.u32()? as usize)..(self.u32()? as usize));
+    }
+}
+
+/// Whether `bytes` look like a compiled dataset, rather than XML.
+pub fn is_compiled(bytes: &[u8]) -> bool {
+    return bytes.starts_with(MAGIC);
+}
+
+#[cfg(feature = "std")]
+impl Dataset {
+    /// Write this dataset in the compact binary form that
+    /// [`Dataset::from_compiled`] loads.
+    pub fn to_compiled(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_be_bytes());
+
+        let metadata = self.metadata();
+        for text in [
+            &metadata.version,
+            &metadata.source,
+            &metadata.license,
+            &metadata.publication_date,
+        ] {
+            push_str(&mut out, text);
+        }
+
+        push_names(&mut out, self.level1_names());
+        push_names(&mut out, self.level2_names());
+        push_names(&mut out, self.names());
+        push_strings(&mut out, self.hues());
+        push_strings(&mut out, self.chromas());
+        push_strings(&mut out, self.values());
+
+        push_u32(&mut out, self.blocks().len() as u32);
+        for block in self.blocks() {
+            push_u32(&mut out, block.color_id);
+            for range in [&block.hues, &block.chromas, &block.values] {
+                push_u32(&mut out, range.start as u32);
+                push_u32(&mut out, range.end as u32);
+            }
+        }
+        return out;
+    }
+
+    /// Load a dataset written by [`Dataset::to_compiled`].
+    ///
+    /// The dataset was validated before it was compiled, so only enough is
+    /// checked here for the lookups not to fail on damaged data: that the
+    /// blocks are within the boundaries, and that every parent and block
+    /// color is among the names.
+    pub fn from_compiled(bytes: &[u8]) -> Result<Self, CompiledError> {
+        let mut reader = Reader::after_header(bytes)?;
+        let metadata = Metadata {
+            version: reader.string()?,
+            source: reader.string()?,
+            license: reader.string()?,
+            publication_date: reader.string()?,
+        };
+        let level1_names = reader.names()?;
+        let level2_names = reader.names()?;
+        let names = reader.names()?;
+        for (level, parents) in [(&level2_names, &level1_names), (&names, &level2_names)] {
+            let orphan = level
+                .iter()
+                .filter(|(_, x)| !x.parent.is_some_and(|p| parents.contains_key(&p)))
+                .map(|(id, _)| *id)
+                .min();
+            if let Some(id) = orphan {
+                return Err(CompiledError::InvalidParent(id));
+            }
+        }
+        let hues = reader.strings()?;
+        let chromas = reader.strings()?;
+        let values = reader.strings()?;
+
+        if let Some(x) = hues.iter().find(|x| x.parse::<MunsellHue>().is_err()) {
+            return Err(CompiledError::InvalidBoundary(x.clone()));
+        }
+        if let Some(x) = chromas
+            .iter()
+            .chain(values.iter())
+            .find(|x| x.parse::<f32>().is_err())
+        {
+            return Err(CompiledError::InvalidBoundary(x.clone()));
+        }
+
+        let count = reader.u32()?;
+        let mut blocks = Vec::new();
+        for i in 0..count as usize {
+            let block = ColorBlock {
+                color_id: reader.u32()?,
+                hues: reader.range()?,
+                chromas: reader.range()?,
+                values: reader.range()?,
+            };
+            let in_range = |range: &Range<usize>, len: usize| range.start < len && range.end < len;
+            if !in_range(&block.hues, hues.len())
+                || !in_range(&block.chromas, chromas.len())
+                || !in_range(&block.values, values.len())
+                || !names.contains_key(&block.color_id)
+            {
+                return Err(CompiledError::InvalidBlock(i));
+            }
+            blocks.push(block);
+        }
+        if !reader.is_done() {
+            return Err(CompiledError::TrailingData);
+        }
+
+        return Ok(Dataset::from_parts(
+            metadata,
+            (level1_names, level2_names, names),
+            hues,
+            chromas,
+            values,
+            blocks,
+        ));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::compiled::CompiledError;
+    use crate::munsell::MunsellColor;
+    use crate::{ColorName, Dataset};
+
+    #[test]
+    fn compiled_round_trip() {
+        let dataset = Dataset::bundled();
+        let bytes = dataset.to_compiled();
+        let loaded = Dataset::from_compiled(&bytes).unwrap();
+
+        assert_eq!(loaded.metadata(), dataset.metadata());
+        for (id, name) in dataset.names().iter() {
+            let x = &loaded.names()[id];
+            assert_eq!(
+                (&x.name, &x.abbr, x.parent, x.srgb, &x.description, &x.usage),
+                (
+                    &name.name,
+                    &name.abbr,
+                    name.parent,
+                    name.srgb,
+                    &name.description,
+                    &name.usage
+                )
+            );
+        }
+        assert_eq!(loaded.level1_names().len(), dataset.level1_names().len());
+        assert_eq!(loaded.hues(), dataset.hues());
+        assert_eq!(loaded.blocks().len(), dataset.blocks().len());
+        assert_eq!(loaded.neutrals(), dataset.neutrals());
+        let color: MunsellColor = "2R 7/12".parse().unwrap();
+        assert_eq!(loaded.lookup_id(&color), Some(1));
+        assert_eq!(loaded.lookup_abbr("viv.pB"), Some(194));
+        assert_eq!(loaded.to_compiled(), bytes);
+
+        assert_eq!(
+            Dataset::from_compiled(b"<?xml").err(),
+            Some(CompiledError::NotCompiled)
+        );
+        assert_eq!(
+            Dataset::from_compiled(&bytes[..bytes.len() - 1]).err(),
+            Some(CompiledError::Truncated)
+        );
+        // a name whose parent is left out, and blocks of a color left out
+        let with_names = |level2: HashMap<u32, ColorName>, names: HashMap<u32, ColorName>| {
+            let damaged = Dataset::from_parts(
+                dataset.metadata().clone(),
+                (dataset.level1_names().clone(), level2, names),
+                dataset.hues().to_vec(),
+                dataset.chromas().to_vec(),
+                dataset.values().to_vec(),
+                dataset.blocks().to_vec(),
+            );
+            return Dataset::from_compiled(&damaged.to_compiled()).err();
+        };
+        let id = dataset.blocks()[0].color_id;
+        let mut level2 = dataset.level2_names().clone();
+        level2.remove(&dataset.names()[&id].parent.unwrap());
+        assert_eq!(
+            with_names(level2, dataset.names().clone()),
+            Some(CompiledError::InvalidParent(id))
+        );
+        let mut names = dataset.names().clone();
+        names.remove(&id);
+        assert_eq!(
+            with_names(dataset.level2_names().clone(), names),
+            Some(CompiledError::InvalidBlock(0))
+        );
+
+        let mut newer = bytes.clone();
+        newer[9] = 2;
+        assert_eq!(
+            Dataset::from_compiled(&newer).err(),
+            Some(CompiledError::UnsupportedVersion(2))
+        );
+    }
+}
@@ -0,0 +1,103 @@
+//! Simulation of color vision deficiencies, to show how colors that are
+//! told apart by name look alike to color-blind viewers.
+//!
+//! The simulation uses the matrices of Machado, Oliveira and Fernandes, "A
+//! Physiologically-based Model for Simulation of Color Vision Deficiency"
+//! (2009), for a complete lack of one kind of cone, applied in linear sRGB.
+
+use palette::{LinSrgb, Srgb};
+
+/// A kind of dichromacy, where one of the three kinds of cones is missing.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Deficiency {
+    /// No long-wavelength (red) cones.
+    Protanopia,
+    /// No medium-wavelength (green) cones.
+    Deuteranopia,
+    /// No short-wavelength (blue) cones.
+    Tritanopia,
+}
+
+impl Deficiency {
+    /// The name of the deficiency, in lowercase, like `protanopia`.
+    pub fn name(self) -> &'static str {
+        return match self {
+            Deficiency::Protanopia => "protanopia",
+            Deficiency::Deuteranopia => "deuteranopia",
+            Deficiency::Tritanopia => "tritanopia",
+        };
+    }
+
+    fn matrix(self) -> [[f32; 3]; 3] {
+        return match self {
+            Deficiency::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            Deficiency::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            Deficiency::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        };
+    }
+}
+
+/// How a color looks to a viewer with a deficiency, as an sRGB color that
+/// looks the same to a viewer without one.
+pub fn simulate(color: Srgb, deficiency: Deficiency) -> Srgb {
+    let linear: LinSrgb = color.into_linear();
+    let rgb = [linear.red, linear.green, linear.blue];
+    let m = deficiency.matrix();
+    let row = |i: usize| (m[i][0] * rgb[0] + m[i][1] * rgb[1] + m[i][2] * rgb[2]).clamp(0.0, 1.0);
+
+    return Srgb::from_linear(LinSrgb::new(row(0), row(1), row(2)));
+}
+
+/// Like [`simulate`], for every color of a list, like the representative
+/// colors of [`get_mean_colors`](crate::colors::get_mean_colors).
+pub fn simulate_all(colors: &[Srgb], deficiency: Deficiency) -> Vec<Srgb> {
+    return colors.iter().map(|x| simulate(*x, deficiency)).collect();
+}
+
+#[cfg(test)]
+mod test {
+    use palette::{ColorDifference, IntoColor, Lab, Srgb};
+
+    use crate::cvd::{simulate, Deficiency};
+
+    #[test]
+    fn simulated_colors() {
+        let difference = |a: Srgb, b: Srgb| {
+            let (a, b): (Lab, Lab) = (a.into_color(), b.into_color());
+            return a.get_color_difference(&b);
+        };
+
+        // neutrals look the same to everyone
+        for deficiency in [
+            Deficiency::Protanopia,
+            Deficiency::Deuteranopia,
+            Deficiency::Tritanopia,
+        ] {
+            let gray = Srgb::new(0.5, 0.5, 0.5);
+            assert!(difference(simulate(gray, deficiency), gray) < 1.0);
+        }
+
+        // red and green are hard to tell apart without red or green cones,
+        // but not without blue ones
+        let red = Srgb::new(0.8, 0.2, 0.2);
+        let green = Srgb::new(0.3, 0.6, 0.2);
+        let normal = difference(red, green);
+        let simulated =
+            |deficiency| difference(simulate(red, deficiency), simulate(green, deficiency));
+        assert!(simulated(Deficiency::Protanopia) < normal / 2.0);
+        assert!(simulated(Deficiency::Deuteranopia) < normal / 2.0);
+        assert!(simulated(Deficiency::Tritanopia) > normal / 2.0);
+    }
+}
@@ -0,0 +1,2099 @@
+use is_sorted::IsSorted;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use std::sync::Mutex;
+
+use palette::Srgb;
+
+use crate::classify::parse_srgb;
+use crate::diagnostic::{Diagnostic, Diagnostics, ValidationError};
+use crate::grammar::{hue_span, parse_name, parse_name_loosely};
+use crate::leaf::{arc_contains, arc_within, build_leaves, HueLeaf};
+use crate::lookup::ColorLookupTable;
+use crate::munsell::{MunsellColor, MunsellHue};
+use crate::neutral::{build_neutrals, NeutralCategory};
+use crate::progress::{no_progress, report, Progress, Stage};
+
+/// The ISCC-NBS dataset bundled with this crate.
+pub(crate) const BUNDLED_XML: &str = include_str!("../iscc-nbs.xml");
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorName {
+    pub name: String,
+    pub abbr: String,
+    /// The id of the name containing this one at the next level up, if any.
+    pub parent: Option<u32>,
+    /// A curated representative color, from the `srgb` attribute, to use
+    /// instead of the computed centroid.
+    pub srgb: Option<Srgb<u8>>,
+    /// A description of the color, from the `description` attribute.
+    pub description: Option<String>,
+    /// Notes on the use of the name, like typical objects of the color,
+    /// from the `usage` attribute.
+    pub usage: Option<String>,
+}
+
+/// The names of a color at all three levels, like "Yellow" / "Yellowish
+/// brown" / "Light yellowish brown".
+#[derive(Clone, Copy)]
+pub struct LevelNames<'a> {
+    pub level1: &'a ColorName,
+    pub level2: &'a ColorName,
+    pub level3: &'a ColorName,
+}
+
+/// Where a dataset comes from, from its `<metadata>` element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metadata {
+    pub version: String,
+    pub source: String,
+    pub license: String,
+    /// The publication date of the source, in ISO 8601 form (like
+    /// `1976-12`).
+    pub publication_date: String,
+}
+
+/// A single rectangular block of the color name charts, expressed as
+/// index ranges into the hue, chroma and value boundary lists of the
+/// [`Dataset`] it came from.
+///
+/// The hue range may wrap around; `hues.end` is then smaller than
+/// `hues.start`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorBlock {
+    pub color_id: u32,
+    pub hues: Range<usize>,
+    pub chromas: Range<usize>,
+    pub values: Range<usize>,
+}
+
+/// Why the text of an ISCC-NBS XML document could not be made into a
+/// dataset.
+#[derive(Debug)]
+pub enum DatasetError {
+    /// The text isn't well-formed XML.
+    Xml(roxmltree::Error),
+    /// The document is, but doesn't describe a valid dataset.
+    Invalid(ValidationError),
+}
+
+impl fmt::Display for DatasetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            DatasetError::Xml(e) => write!(f, "{}", e),
+            DatasetError::Invalid(e) => write!(f, "{}", e),
+        };
+    }
+}
+
+impl std::error::Error for DatasetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        return match self {
+            DatasetError::Xml(e) => Some(e),
+            DatasetError::Invalid(e) => Some(e),
+        };
+    }
+}
+
+/// The validated contents of an ISCC-NBS XML document.
+pub struct Dataset {
+    metadata: Metadata,
+    pub(crate) level1_names: HashMap<u32, ColorName>,
+    pub(crate) level2_names: HashMap<u32, ColorName>,
+    pub(crate) names: HashMap<u32, ColorName>,
+    hues: Vec<String>,
+    chromas: Vec<String>,
+    values: Vec<String>,
+    blocks: Vec<ColorBlock>,
+    leaves: Vec<HueLeaf>,
+    pub(crate) neutrals: Vec<NeutralCategory>,
+    /// The level 3 color ids by abbreviation, both as given and in the
+    /// standard form where that differs, normalized by [`normalize_abbr`].
+    abbrs: HashMap<String, u32>,
+
+    // numeric forms of the boundaries, for lookups
+    hue_points: Vec<f32>,
+    chroma_points: Vec<f32>,
+    value_points: Vec<f32>,
+    table: ColorLookupTable,
+    /// The table of [`Dataset::classify_slice`], empty until it is first
+    /// called.
+    pub(crate) srgb_table: Mutex<Vec<u32>>,
+}
+
+impl Dataset {
+    /// The dataset transcribed from NBS Special Publication 440, as shipped
+    /// with this crate.
+    ///
+    /// Unlike [`Dataset::parse`], this logs nothing: the bundled dataset
+    /// has no errors, and its few warnings are no concern of the programs
+    /// using it.
+    pub fn bundled() -> Self {
+        let opt = roxmltree::ParsingOptions { allow_dtd: true };
+        let doc = roxmltree::Document::parse_with_options(BUNDLED_XML, opt)
+            .expect("bundled dataset should be well-formed");
+        let mut diagnostics = Diagnostics::default();
+        let dataset = Self::validate(&doc, &mut no_progress, &mut diagnostics);
+        return dataset.expect("bundled dataset should be valid");
+    }
+
+    /// Parse and validate the text of an ISCC-NBS XML document.
+    pub fn parse(text: &str) -> Result<Self, DatasetError> {
+        return Self::parse_with_progress(text, &mut no_progress);
+    }
+
+    /// Like [`Dataset::parse`], reporting progress through the parse and
+    /// validate stages.
+    pub fn parse_with_progress(text: &str, progress: Progress) -> Result<Self, DatasetError> {
+        report(progress, Stage::Parse, 0, 1);
+        let opt = roxmltree::ParsingOptions { allow_dtd: true };
+        let doc = roxmltree::Document::parse_with_options(text, opt).map_err(DatasetError::Xml)?;
+        report(progress, Stage::Parse, 1, 1);
+
+        return Self::from_document_with_progress(&doc, progress).map_err(DatasetError::Invalid);
+    }
+
+    /// Run every check on the text of an ISCC-NBS XML document, returning
+    /// what they find instead of printing it.
+    pub fn check(text: &str) -> Result<Vec<Diagnostic>, roxmltree::Error> {
+        return Ok(Self::check_and_build(text)?.1);
+    }
+
+    /// Like [`Dataset::check`], also building the dataset if none of the
+    /// problems found is an error.
+    pub fn check_and_build(
+        text: &str,
+    ) -> Result<(Option<Self>, Vec<Diagnostic>), roxmltree::Error> {
+        let opt = roxmltree::ParsingOptions { allow_dtd: true };
+        let doc = roxmltree::Document::parse_with_options(text, opt)?;
+
+        let mut diagnostics = Diagnostics::default();
+        let dataset = Self::validate(&doc, &mut no_progress, &mut diagnostics);
+        return Ok((dataset, diagnostics.list));
+    }
+
+    /// Validate a parsed ISCC-NBS document and build a dataset from it.
+    pub fn from_document(doc: &roxmltree::Document) -> Result<Self, ValidationError> {
+        return Self::from_document_with_progress(doc, &mut no_progress);
+    }
+
+    /// Like [`Dataset::from_document`], reporting progress through the
+    /// validate stage.
+    ///
+    /// Every problem found is logged as a warning or an error through the
+    /// `log` crate; if any of them is an error, all of them are returned.
+    pub fn from_document_with_progress(
+        doc: &roxmltree::Document,
+        progress: Progress,
+    ) -> Result<Self, ValidationError> {
+        let mut diagnostics = Diagnostics::default();
+        let dataset = Self::validate(doc, progress, &mut diagnostics);
+        diagnostics.log_and_check()?;
+        return Ok(dataset.unwrap());
+    }
+
+    /// Run every check on a document, collecting what they find into
+    /// `diagnostics`, and build a dataset if none of them is an error.
+    pub(crate) fn validate(
+        doc: &roxmltree::Document,
+        progress: Progress,
+        diagnostics: &mut Diagnostics,
+    ) -> Option<Self> {
+        const VALIDATE_STEPS: usize = 4;
+
+        report(progress, Stage::Validate, 0, VALIDATE_STEPS);
+        if !validate_structure(doc, diagnostics) {
+            return None;
+        }
+        let metadata = get_metadata(doc, diagnostics);
+        let (level1_names, level2_names, names, name_positions) = validate_names(doc, diagnostics);
+        report(progress, Stage::Validate, 1, VALIDATE_STEPS);
+
+        let hues = get_hues(doc, diagnostics);
+        let chromas = get_chromas(doc, diagnostics);
+        let values = get_values(doc, diagnostics);
+        report(progress, Stage::Validate, 2, VALIDATE_STEPS);
+
+        let (blocks, block_positions) = validate_blocks(doc, &hues, &chromas, &values, diagnostics);
+        report(progress, Stage::Validate, 3, VALIDATE_STEPS);
+        validate_references(
+            &names,
+            &blocks,
+            &name_positions,
+            &block_positions,
+            diagnostics,
+        );
+        // overlaps and gaps already account for any stray blocks
+        if !diagnostics.has_errors() {
+            validate_contiguity(
+                &names,
+                &blocks,
+                &block_positions,
+                &hues,
+                &chromas,
+                &values,
+                diagnostics,
+            );
+        }
+        report(progress, Stage::Validate, 4, VALIDATE_STEPS);
+
+        if diagnostics.has_errors() {
+            return None;
+        }
+
+        let dataset = Self::from_parts(
+            metadata,
+            (level1_names, level2_names, names),
+            hues,
+            chromas,
+            values,
+            blocks,
+        );
+
+        // the representative colors and the hues of the parent names can
+        // only be checked against a whole dataset
+        validate_representative_colors(&dataset, &name_positions, diagnostics);
+        validate_hierarchy(&dataset, &block_positions, diagnostics);
+        if diagnostics.has_errors() {
+            return None;
+        }
+        return Some(dataset);
+    }
+
+    /// Assemble a dataset from its validated names, boundaries and blocks,
+    /// deriving the lookup structures from them.
+    pub(crate) fn from_parts(
+        metadata: Metadata,
+        (level1_names, level2_names, names): (
+            HashMap<u32, ColorName>,
+            HashMap<u32, ColorName>,
+            HashMap<u32, ColorName>,
+        ),
+        hues: Vec<String>,
+        chromas: Vec<String>,
+        values: Vec<String>,
+        blocks: Vec<ColorBlock>,
+    ) -> Self {
+        let leaves = build_leaves(&hues);
+        let hue_points: Vec<f32> = hues
+            .iter()
+            .map(|x| x.parse::<MunsellHue>().unwrap().raw())
+            .collect();
+        let chroma_points: Vec<f32> = chromas.iter().map(|x| x.parse().unwrap()).collect();
+        let value_points: Vec<f32> = values.iter().map(|x| x.parse().unwrap()).collect();
+        let neutrals = build_neutrals(&blocks, &chroma_points, &value_points);
+        let abbrs = build_abbrs(&names);
+        let table = ColorLookupTable::from_blocks(
+            &hue_points,
+            chroma_points.clone(),
+            value_points.clone(),
+            &blocks,
+        );
+
+        return Dataset {
+            metadata,
+            level1_names,
+            level2_names,
+            names,
+            hues,
+            chromas,
+            values,
+            blocks,
+            leaves,
+            neutrals,
+            abbrs,
+            hue_points,
+            chroma_points,
+            value_points,
+            table,
+            srgb_table: Mutex::new(Vec::new()),
+        };
+    }
+
+    /// Where this dataset comes from.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// The level 3 color names, keyed by color id.
+    pub fn names(&self) -> &HashMap<u32, ColorName> {
+        &self.names
+    }
+
+    /// The level 2 color names, keyed by their level 2 id.
+    pub fn level2_names(&self) -> &HashMap<u32, ColorName> {
+        &self.level2_names
+    }
+
+    /// The level 1 color names, keyed by their level 1 id.
+    pub fn level1_names(&self) -> &HashMap<u32, ColorName> {
+        &self.level1_names
+    }
+
+    /// The hue boundaries, in order around the hue circle.
+    pub fn hues(&self) -> &[String] {
+        &self.hues
+    }
+
+    /// The chroma boundaries, in ascending order. The last entry is `INF`.
+    pub fn chromas(&self) -> &[String] {
+        &self.chromas
+    }
+
+    /// The value boundaries, in ascending order. The last entry is `INF`.
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    pub fn blocks(&self) -> &[ColorBlock] {
+        &self.blocks
+    }
+
+    /// The hue leaves, one starting at each entry of [`Dataset::hues`].
+    pub fn leaves(&self) -> &[HueLeaf] {
+        &self.leaves
+    }
+
+    /// A URL- and filename-safe identifier for a level 3 color, made of its
+    /// id and name, like `34-vivid-reddish-orange`.
+    pub fn slug(&self, color_id: u32) -> Option<String> {
+        let name = self.names.get(&color_id)?;
+        return Some(format!("{}-{}", color_id, slugify(&name.name)));
+    }
+
+    /// Find the id of the level 3 color with an abbreviation like "v.l.pB",
+    /// or its standard form where the dataset's differs.
+    ///
+    /// Spaces may stand in for the periods, as in "v l pB". The case of the
+    /// letters matters, since "l.Gy" is Light gray but "l.gY" Light greenish
+    /// yellow; other cases are only accepted if they match a single color.
+    pub fn lookup_abbr(&self, abbr: &str) -> Option<u32> {
+        let abbr = normalize_abbr(abbr);
+        if let Some(id) = self.abbrs.get(&abbr) {
+            return Some(*id);
+        }
+
+        let lowercase = abbr.to_lowercase();
+        let mut ids = self
+            .abbrs
+            .iter()
+            .filter(|(x, _)| x.to_lowercase() == lowercase)
+            .map(|(_, id)| *id)
+            .collect::<Vec<u32>>();
+        ids.sort();
+        ids.dedup();
+        return match ids[..] {
+            [id] => Some(id),
+            _ => None,
+        };
+    }
+
+    /// Find the id of the level 3 color with a name like "Light yellowish
+    /// brown", written in any case, with any whitespace or hyphens between
+    /// the words and "grey" for "gray", as [`crate::grammar::parse_name_loosely`]
+    /// reads it.
+    ///
+    /// Names outside the grammar, like those of some translations, are only
+    /// found as they are written, ignoring case and surrounding whitespace.
+    pub fn lookup_name(&self, name: &str) -> Option<u32> {
+        let mut ids = match parse_name_loosely(name) {
+            Some(parsed) => self
+                .names
+                .iter()
+                .filter(|(_, x)| parse_name(&x.name).as_ref() == Some(&parsed))
+                .map(|(id, _)| *id)
+                .collect::<Vec<u32>>(),
+            None => Vec::new(),
+        };
+        if ids.is_empty() {
+            ids = self
+                .names
+                .iter()
+                .filter(|(_, x)| x.name.to_lowercase() == name.trim().to_lowercase())
+                .map(|(id, _)| *id)
+                .collect();
+        }
+        ids.sort();
+        return ids.first().copied();
+    }
+
+    /// The level 3 color of every cell between the boundaries, for
+    /// classifying many colors.
+    pub fn lookup_table(&self) -> &ColorLookupTable {
+        return &self.table;
+    }
+
+    /// Find the id of the level 3 color that a Munsell color falls into,
+    /// in the [`Dataset::lookup_table`].
+    ///
+    /// Blocks include their lower boundaries and exclude their upper ones,
+    /// as described in [`crate::lookup`], so a color exactly on a boundary
+    /// has one color; [`Dataset::classify_with_tolerance`] finds the others
+    /// it is near. Returns `None` for colors outside of the Munsell solid, such as those
+    /// with a negative value or chroma.
+    pub fn lookup_id(&self, color: &MunsellColor) -> Option<u32> {
+        return self.table.classify_munsell(color);
+    }
+
+    /// Find the level 3 color name that a Munsell color falls into.
+    pub fn lookup(&self, color: &MunsellColor) -> Option<&ColorName> {
+        return self.lookup_id(color).and_then(|id| self.names.get(&id));
+    }
+
+    /// The names of a level 3 color and of the level 2 and level 1 names
+    /// containing it, or `None` if there is no such color.
+    pub fn level_names(&self, color_id: u32) -> Option<LevelNames<'_>> {
+        let level3 = self.names.get(&color_id)?;
+        let level2 = self.level2_names.get(&level3.parent?)?;
+        let level1 = self.level1_names.get(&level2.parent?)?;
+        return Some(LevelNames {
+            level1,
+            level2,
+            level3,
+        });
+    }
+
+    /// The id of the name of level `target` containing the name `color_id`
+    /// of `level`, or `color_id` itself if `target` is `level`. Returns
+    /// `None` if there is no such name, or `target` is a finer level.
+    pub fn ancestor_id(&self, level: u32, color_id: u32, target: u32) -> Option<u32> {
+        let names = match level {
+            1 => &self.level1_names,
+            2 => &self.level2_names,
+            3 => &self.names,
+            _ => return None,
+        };
+        let name = names.get(&color_id)?;
+        if target == level {
+            return Some(color_id);
+        }
+        return self.ancestor_id(level - 1, name.parent?, target);
+    }
+
+    /// Find the names at all three levels that a Munsell color falls into.
+    pub fn lookup_all_levels(&self, color: &MunsellColor) -> Option<LevelNames<'_>> {
+        return self.lookup_id(color).and_then(|id| self.level_names(id));
+    }
+
+    /// Whether a color is in a block, including its lower boundaries and
+    /// excluding its upper ones, for checking the lookup table against the
+    /// blocks.
+    pub fn block_contains(&self, block: &ColorBlock, color: &MunsellColor) -> bool {
+        let in_hue = arc_contains(
+            self.hue_points[block.hues.start],
+            self.hue_points[block.hues.end],
+            color.hue.raw(),
+        );
+        let in_chroma = color.chroma >= self.chroma_points[block.chromas.start]
+            && color.chroma < self.chroma_points[block.chromas.end];
+        let in_value = color.value >= self.value_points[block.values.start]
+            && color.value < self.value_points[block.values.end];
+
+        return in_hue && in_chroma && in_value;
+    }
+}
+
+pub(crate) fn deinfinite(x: &str) -> &str {
+    if x == "INF" {
+        "9999"
+    } else {
+        x
+    }
+}
+
+fn get_metadata(doc: &roxmltree::Document, diagnostics: &mut Diagnostics) -> Metadata {
+    let metadata = match doc.descendants().find(|n| n.has_tag_name("metadata")) {
+        Some(v) => v,
+        // datasets from before it was added have none
+        None => {
+            diagnostics.warning(
+                "missing-metadata",
+                "Missing <metadata> element; the version, source, license and publication date are left empty.".to_string(),
+            );
+            return Metadata {
+                version: String::new(),
+                source: String::new(),
+                license: String::new(),
+                publication_date: String::new(),
+            };
+        }
+    };
+
+    let mut field = |tag_name: &str| -> String {
+        let node = metadata.children().find(|n| n.has_tag_name(tag_name));
+        let text = node.and_then(|n| n.text()).map(|x| x.trim()).unwrap_or("");
+        if text.is_empty() {
+            diagnostics
+                .error(
+                    "missing-metadata",
+                    format!("Missing <{}> in <metadata>.", tag_name),
+                )
+                .node(node.unwrap_or(metadata));
+        }
+        return text.to_string();
+    };
+
+    return Metadata {
+        version: field("version"),
+        source: field("source"),
+        license: field("license"),
+        publication_date: field("publication-date"),
+    };
+}
+
+/// Lowercase a name, and replace everything but ASCII letters and digits
+/// with single dashes.
+pub fn slugify(name: &str) -> String {
+    return name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_ascii_lowercase())
+        .collect::<Vec<String>>()
+        .join("-");
+}
+
+/// The value of an attribute an element needs, or an error at the element
+/// if it has none.
+fn required_attribute<'a>(
+    node: roxmltree::Node<'a, '_>,
+    attribute: &str,
+    diagnostics: &mut Diagnostics,
+) -> Option<&'a str> {
+    let value = node.attribute(attribute);
+    if value.is_none() {
+        diagnostics
+            .error(
+                "missing-attribute",
+                format!(
+                    "<{}> has no {} attribute.",
+                    node.tag_name().name(),
+                    attribute
+                ),
+            )
+            .node(node);
+    }
+    return value;
+}
+
+fn add_name_to_map(
+    map: &mut HashMap<u32, ColorName>,
+    positions: &mut NamePositions,
+    level: u32,
+    node: roxmltree::Node,
+    parent: Option<u32>,
+    diagnostics: &mut Diagnostics,
+) {
+    let attributes = [
+        required_attribute(node, "name", diagnostics),
+        required_attribute(node, "abbr", diagnostics),
+        required_attribute(node, "color", diagnostics),
+    ];
+    let (color_name, color_abbr, color_attr) = match attributes {
+        [Some(name), Some(abbr), Some(color)] => (name.to_string(), abbr.to_string(), color),
+        _ => return,
+    };
+    let color_id: u32 = match color_attr.parse::<u32>() {
+        Ok(v) => v,
+        Err(_) => {
+            diagnostics
+                .error(
+                    "invalid-id",
+                    format!(
+                        "Name '{}' has an invalid color id '{}'.",
+                        color_name, color_attr
+                    ),
+                )
+                .node(node);
+            return;
+        }
+    };
+
+    // keep the first, so that later checks see a consistent map
+    if map.contains_key(&color_id) {
+        diagnostics
+            .error(
+                "duplicate-id",
+                format!(
+                    "Conflicting color ids for {}: {} and {}.",
+                    color_id,
+                    map.get(&color_id).unwrap().name,
+                    color_name
+                ),
+            )
+            .ids(level, &[color_id])
+            .node(node);
+        return;
+    }
+
+    let srgb = match node.attribute("srgb") {
+        Some(text) => match parse_srgb(text) {
+            Some(v) => Some(v),
+            None => {
+                diagnostics
+                    .error(
+                        "invalid-color",
+                        format!(
+                            "Name '{}' has an invalid representative color '{}'.",
+                            color_name, text
+                        ),
+                    )
+                    .ids(level, &[color_id])
+                    .node(node);
+                None
+            }
+        },
+        None => None,
+    };
+
+    positions.insert(
+        (level, color_id),
+        node.document().text_pos_at(node.range().start),
+    );
+    map.insert(
+        color_id,
+        ColorName {
+            name: color_name,
+            abbr: color_abbr,
+            parent,
+            srgb,
+            description: node.attribute("description").map(|x| x.to_string()),
+            usage: node.attribute("usage").map(|x| x.to_string()),
+        },
+    );
+}
+
+/// Check that every name is a hue name, preceded by a modifier at level 3,
+/// which catches typos that the duplicate checks miss, and that its
+/// abbreviation is derived from it by the standard rules.
+/// Write an abbreviation with its parts separated by single periods, like
+/// "v.l.pB" for "v. l. pB" or "v l pB".
+fn normalize_abbr(abbr: &str) -> String {
+    return abbr
+        .split(|c: char| c == '.' || c.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<&str>>()
+        .join(".");
+}
+
+fn build_abbrs(names: &HashMap<u32, ColorName>) -> HashMap<String, u32> {
+    let mut abbrs = HashMap::new();
+    for (id, name) in names.iter() {
+        abbrs.insert(normalize_abbr(&name.abbr), *id);
+    }
+    // the abbreviations as given take precedence over the standard forms
+    for (id, name) in names.iter() {
+        if let Some(parsed) = parse_name(&name.name) {
+            abbrs.entry(normalize_abbr(&parsed.abbr())).or_insert(*id);
+        }
+    }
+    return abbrs;
+}
+
+fn validate_grammar(
+    map: &HashMap<u32, ColorName>,
+    level: u32,
+    positions: &NamePositions,
+    diagnostics: &mut Diagnostics,
+) {
+    let mut ids = map.keys().copied().collect::<Vec<u32>>();
+    ids.sort();
+
+    for id in ids {
+        let name = &map[&id];
+        let parsed = match parse_name(&name.name) {
+            Some(v) if level == 3 || v.modifier.is_none() => v,
+            _ => {
+                let expected = if level == 3 {
+                    "a modifier followed by a hue name"
+                } else {
+                    "a hue name"
+                };
+                diagnostics
+                    .error(
+                        "ungrammatical-name",
+                        format!(
+                            "Name '{}' of level {} id {} is not {}.",
+                            name.name, level, id, expected
+                        ),
+                    )
+                    .ids(level, &[id])
+                    .position(positions.get(&(level, id)).copied());
+                continue;
+            }
+        };
+
+        // the source isn't entirely consistent about these, so they are
+        // only worth a second look
+        let abbr = parsed.abbr();
+        if name.abbr != abbr {
+            diagnostics
+                .warning(
+                    "nonstandard-abbr",
+                    format!(
+                        "Abbreviation '{}' of '{}' (level {} id {}) should be '{}'.",
+                        name.abbr, name.name, level, id, abbr
+                    ),
+                )
+                .ids(level, &[id])
+                .position(positions.get(&(level, id)).copied());
+        }
+    }
+}
+
+fn validate_name_map(
+    map: &HashMap<u32, ColorName>,
+    level: u32,
+    positions: &NamePositions,
+    diagnostics: &mut Diagnostics,
+) {
+    let position = |id: u32| positions.get(&(level, id)).copied();
+
+    // go through the ids in order, so that problems are reported in order
+    let mut ids = map.keys().copied().collect::<Vec<u32>>();
+    ids.sort();
+    let max_color_id = ids.last().copied().unwrap_or(0);
+
+    // ensure that each name and abbr is unused elsewhere, reporting each
+    // pair of ids once
+    for (i, color_id) in ids.iter().enumerate() {
+        let name_entry = &map[color_id];
+        for color2_id in ids.iter().skip(i + 1) {
+            let name2_entry = &map[color2_id];
+            if name_entry.name == name2_entry.name {
+                diagnostics
+                    .error(
+                        "duplicate-name",
+                        format!(
+                            "Duplicate name '{}' used for both id {} and {}.",
+                            name_entry.name, color_id, color2_id
+                        ),
+                    )
+                    .ids(level, &[*color_id, *color2_id])
+                    .position(position(*color2_id));
+            }
+            if name_entry.abbr == name2_entry.abbr {
+                diagnostics
+                    .error(
+                        "duplicate-abbr",
+                        format!(
+                            "Duplicate abbr '{}' used for both id {} and {}.",
+                            name_entry.abbr, color_id, color2_id
+                        ),
+                    )
+                    .ids(level, &[*color_id, *color2_id])
+                    .position(position(*color2_id));
+            }
+        }
+    }
+
+    // and that names don't collide once they're turned into slugs
+    let mut slugs: HashMap<String, u32> = HashMap::new();
+    for color_id in ids.iter() {
+        let name_entry = &map[color_id];
+        let slug = slugify(&name_entry.name);
+        if slug.is_empty() {
+            diagnostics
+                .error(
+                    "empty-slug",
+                    format!(
+                        "Name '{}' of id {} has an empty slug.",
+                        name_entry.name, color_id
+                    ),
+                )
+                .ids(level, &[*color_id])
+                .position(position(*color_id));
+            continue;
+        }
+        // identical names are already reported above
+        match slugs.get(&slug) {
+            Some(color2_id) if map[color2_id].name != name_entry.name => {
+                diagnostics
+                    .error(
+                        "duplicate-slug",
+                        format!(
+                            "Names '{}' and '{}' of ids {} and {} have the same slug '{}'.",
+                            map[color2_id].name, name_entry.name, color2_id, color_id, slug
+                        ),
+                    )
+                    .ids(level, &[*color2_id, *color_id])
+                    .position(position(*color_id));
+            }
+            Some(_) => {}
+            None => {
+                slugs.insert(slug, *color_id);
+            }
+        }
+    }
+
+    // also ensure that all ids from 1..max_color_id are present
+    for id in 1..max_color_id {
+        if !map.contains_key(&id) {
+            diagnostics
+                .error(
+                    "missing-id",
+                    format!("missing color id {} in 1..{}.", id, max_color_id),
+                )
+                .ids(level, &[id]);
+        }
+    }
+}
+
+type NameMap = HashMap<u32, ColorName>;
+/// Where each name starts in the XML document, by level and color id.
+type NamePositions = HashMap<(u32, u32), roxmltree::TextPos>;
+
+/// Validate the name hierarchy, returning the level 1, 2 and 3 names.
+fn validate_names(
+    doc: &roxmltree::Document,
+    diagnostics: &mut Diagnostics,
+) -> (NameMap, NameMap, NameMap, NamePositions) {
+    let mut positions = NamePositions::new();
+
+    let mut level1_names = HashMap::new();
+    let mut level2_names = HashMap::new();
+    let mut level3_names = HashMap::new();
+
+    // missing, as validate_structure reports
+    let names = match doc.descendants().find(|n| n.has_tag_name("names")) {
+        Some(v) => v,
+        None => return (level1_names, level2_names, level3_names, positions),
+    };
+
+    // a missing or invalid id was reported with the parent's name
+    let color_id = |node: roxmltree::Node| node.attribute("color")?.parse::<u32>().ok();
+
+    for level1 in names.children().filter(|n| n.is_element()) {
+        add_name_to_map(
+            &mut level1_names,
+            &mut positions,
+            1,
+            level1,
+            None,
+            diagnostics,
+        );
+        for level2 in level1.children().filter(|n| n.is_element()) {
+            add_name_to_map(
+                &mut level2_names,
+                &mut positions,
+                2,
+                level2,
+                color_id(level1),
+                diagnostics,
+            );
+            for level3 in level2.children().filter(|n| n.is_element()) {
+                add_name_to_map(
+                    &mut level3_names,
+                    &mut positions,
+                    3,
+                    level3,
+                    color_id(level2),
+                    diagnostics,
+                );
+            }
+        }
+    }
+
+    validate_name_map(&level1_names, 1, &positions, diagnostics);
+    validate_name_map(&level2_names, 2, &positions, diagnostics);
+    validate_name_map(&level3_names, 3, &positions, diagnostics);
+    validate_grammar(&level1_names, 1, &positions, diagnostics);
+    validate_grammar(&level2_names, 2, &positions, diagnostics);
+    validate_grammar(&level3_names, 3, &positions, diagnostics);
+
+    return (level1_names, level2_names, level3_names, positions);
+}
+
+/// Check that the elements every dataset needs are there, with enough
+/// boundaries to make a block, since the other checks assume so.
+fn validate_structure(doc: &roxmltree::Document, diagnostics: &mut Diagnostics) -> bool {
+    let mut valid = true;
+    for (tag_name, minimum) in [
+        ("names", 0),
+        ("hues", 1),
+        ("chromas", 2),
+        ("values", 2),
+        ("ranges", 0),
+    ] {
+        let element = doc.descendants().find(|n| n.has_tag_name(tag_name));
+        let count = element.map(|n| n.children().filter(|x| x.is_element()).count());
+        match count {
+            None => {
+                diagnostics.error(
+                    "missing-element",
+                    format!("Missing <{}> element.", tag_name),
+                );
+                valid = false;
+            }
+            Some(v) if v < minimum => {
+                diagnostics
+                    .error(
+                        "missing-element",
+                        format!(
+                            "<{}> has {} boundaries, but needs at least {}.",
+                            tag_name, v, minimum
+                        ),
+                    )
+                    .node(element.unwrap());
+                valid = false;
+            }
+            Some(_) => {}
+        }
+    }
+    return valid;
+}
+
+fn get_hues(doc: &roxmltree::Document, diagnostics: &mut Diagnostics) -> Vec<String> {
+    let mut amounts: Vec<String> = Vec::new();
+
+    // missing, as validate_structure reports
+    let values = match doc.descendants().find(|n| n.has_tag_name("hues")) {
+        Some(v) => v,
+        None => return amounts,
+    };
+
+    for amount_elem in values.children().filter(|n| n.is_element()) {
+        let hue = amount_elem.attribute("id").unwrap_or("");
+        match MunsellHue::normalize(hue) {
+            Err(_) => {
+                diagnostics
+                    .error(
+                        "invalid-hue",
+                        format!("'{}' in hues is not a Munsell hue like '7.5YR'.", hue),
+                    )
+                    .node(amount_elem);
+            }
+            Ok(normal) if normal != hue => {
+                diagnostics
+                    .warning(
+                        "non-canonical-hue",
+                        format!("'{}' in hues is usually written '{}'.", hue, normal),
+                    )
+                    .node(amount_elem);
+            }
+            Ok(_) => {}
+        }
+        amounts.push(hue.to_string());
+    }
+
+    return amounts;
+}
+
+/// Read the boundaries in `<tag_name>`, checking that they are numbers in
+/// sorted order, and warning about finite ones outside of `usual`.
+fn get_amount_list(
+    tag_name: &str,
+    usual: RangeInclusive<f32>,
+    doc: &roxmltree::Document,
+    diagnostics: &mut Diagnostics,
+) -> Vec<String> {
+    let mut amounts: Vec<String> = Vec::new();
+
+    // missing, as validate_structure reports
+    let values = match doc.descendants().find(|n| n.has_tag_name(tag_name)) {
+        Some(v) => v,
+        None => return amounts,
+    };
+    let elements = values
+        .children()
+        .filter(|n| n.is_element())
+        .collect::<Vec<roxmltree::Node>>();
+
+    for amount_elem in elements.iter() {
+        // empty amounts are reported as not numbers below
+        amounts.push(amount_elem.text().unwrap_or("").to_string());
+    }
+
+    // We actually want to keep these values as strings for index lookup, but
+    // also we do want to verify that these are floating-point values in sorted
+    // order.
+
+    for (amount, node) in amounts.iter().zip(elements.iter()) {
+        if amount.parse::<f32>().is_err() {
+            diagnostics
+                .error(
+                    "not-a-number",
+                    format!("'{}' in {} is not a number.", amount, tag_name),
+                )
+                .node(*node);
+        }
+    }
+    let mut amounts_f32 = amounts.iter().filter_map(|x| x.parse::<f32>().ok());
+    if !IsSorted::is_sorted(&mut amounts_f32) {
+        // point at the first boundary smaller than the one before it
+        let numbers = amounts
+            .iter()
+            .zip(elements.iter())
+            .filter_map(|(x, node)| x.parse::<f32>().ok().map(|x| (x, *node)))
+            .collect::<Vec<(f32, roxmltree::Node)>>();
+        let first = numbers.windows(2).find(|x| x[1].0 < x[0].0).map(|x| x[1].1);
+        let diagnostic = diagnostics.error(
+            "unsorted-boundaries",
+            format!("{} array is not in sorted order.", tag_name),
+        );
+        if let Some(node) = first {
+            diagnostic.node(node);
+        }
+    }
+    for (amount, node) in amounts.iter().zip(elements.iter()) {
+        match amount.parse::<f32>() {
+            Ok(x) if x.is_finite() && !usual.contains(&x) => {
+                diagnostics
+                    .warning(
+                        "unusual-boundary",
+                        format!(
+                            "'{}' in {} is outside the usual range of {} to {}.",
+                            amount,
+                            tag_name,
+                            usual.start(),
+                            usual.end()
+                        ),
+                    )
+                    .node(*node);
+            }
+            _ => {}
+        }
+    }
+
+    return amounts;
+}
+
+fn get_chromas(doc: &roxmltree::Document, diagnostics: &mut Diagnostics) -> Vec<String> {
+    return get_amount_list("chromas", 0.0..=20.0, doc, diagnostics);
+}
+
+fn get_values(doc: &roxmltree::Document, diagnostics: &mut Diagnostics) -> Vec<String> {
+    return get_amount_list("values", 0.0..=10.0, doc, diagnostics);
+}
+
+/// Check that every curated representative color classifies into its own
+/// name, or a level 3 color under it.
+fn validate_representative_colors(
+    dataset: &Dataset,
+    positions: &NamePositions,
+    diagnostics: &mut Diagnostics,
+) {
+    let levels = [
+        (1, dataset.level1_names()),
+        (2, dataset.level2_names()),
+        (3, dataset.names()),
+    ];
+
+    for (level, names) in levels {
+        let mut ids = names.keys().copied().collect::<Vec<u32>>();
+        ids.sort();
+
+        for id in ids {
+            let srgb = match names[&id].srgb {
+                Some(v) => v,
+                None => continue,
+            };
+            let found = dataset.classify_srgb(srgb.into_format());
+            let found_names = found.and_then(|x| dataset.level_names(x));
+            let found_id = match (level, found_names) {
+                (1, Some(x)) => x.level2.parent,
+                (2, Some(x)) => x.level3.parent,
+                _ => found,
+            };
+            if found_id == Some(id) {
+                continue;
+            }
+
+            let hex = format!("#{:02x}{:02x}{:02x}", srgb.red, srgb.green, srgb.blue);
+            let found_text = match found_id {
+                Some(x) => format!("{} ('{}')", x, names[&x].name),
+                None => "no color".to_string(),
+            };
+            diagnostics
+                .error(
+                    "misclassified-color",
+                    format!(
+                        "Representative color {} of level {} color {} ('{}') classifies as {}.",
+                        hex, level, id, names[&id].name, found_text
+                    ),
+                )
+                .ids(level, &[id])
+                .position(positions.get(&(level, id)).copied());
+        }
+    }
+}
+
+/// The smallest chroma or value span of a block that isn't worth a warning.
+const MIN_BLOCK_SPAN: f32 = 0.5;
+
+fn validate_blocks(
+    doc: &roxmltree::Document,
+    hues: &[String],
+    chromas: &[String],
+    values: &[String],
+    diagnostics: &mut Diagnostics,
+) -> (Vec<ColorBlock>, Vec<roxmltree::TextPos>) {
+    // only the cells matter here, so boundaries that don't parse, which are
+    // reported elsewhere, are as good as any
+    let hue_points = hues
+        .iter()
+        .map(|x| MunsellHue::parse(x).map_or(f32::NAN, |x| x.raw()))
+        .collect::<Vec<f32>>();
+    let points = |list: &[String]| list.iter().map(|x| x.parse().unwrap_or(f32::NAN)).collect();
+    let mut lookup_table = ColorLookupTable::empty(&hue_points, points(chromas), points(values));
+    let mut blocks: Vec<ColorBlock> = Vec::new();
+    let mut positions: Vec<roxmltree::TextPos> = Vec::new();
+
+    // missing, as validate_structure reports
+    let ranges = match doc.descendants().find(|n| n.has_tag_name("ranges")) {
+        Some(v) => v,
+        None => return (blocks, positions),
+    };
+
+    // the index of a boundary, or an error if it's not in the list
+    let find = |list: &[String],
+                kind: &str,
+                node: roxmltree::Node,
+                attribute: &str,
+                diagnostics: &mut Diagnostics|
+     -> Option<usize> {
+        let text = required_attribute(node, attribute, diagnostics)?;
+        let position = list.iter().position(|x| x == text);
+        if position.is_none() {
+            diagnostics
+                .error(
+                    "unknown-boundary",
+                    format!(
+                        "Unknown {} '{}' in {} of <{}>.",
+                        kind,
+                        text,
+                        attribute,
+                        node.tag_name().name()
+                    ),
+                )
+                .node(node);
+        }
+        return position;
+    };
+
+    // ranges with unknown boundaries leave gaps that aren't worth reporting
+    let mut skipped_ranges = false;
+
+    for huerange in ranges.children().filter(|n| n.is_element()) {
+        let hue_begin_index = find(hues, "hue", huerange, "begin", diagnostics);
+        let hue_end_index = find(hues, "hue", huerange, "end", diagnostics);
+        let (hue_begin_index, hue_end_index) = match (hue_begin_index, hue_end_index) {
+            (Some(begin), Some(end)) => (begin, end),
+            _ => {
+                skipped_ranges = true;
+                continue;
+            }
+        };
+
+        // hues will wrap around; ensure that begin < logical_end, and then
+        // when using the hue index later we'll mod it by length
+        let hue_logical_end_index = if hue_end_index < hue_begin_index {
+            hue_end_index + hues.len()
+        } else {
+            hue_end_index
+        };
+
+        for range in huerange.children().filter(|n| n.is_element()) {
+            let color_attr = match required_attribute(range, "color", diagnostics) {
+                Some(v) => v,
+                None => {
+                    skipped_ranges = true;
+                    continue;
+                }
+            };
+            let color_id = match color_attr.parse::<u32>() {
+                Ok(v) => v,
+                Err(_) => {
+                    diagnostics
+                        .error(
+                            "invalid-id",
+                            format!("Invalid color id '{}' in <range>.", color_attr),
+                        )
+                        .node(range);
+                    skipped_ranges = true;
+                    continue;
+                }
+            };
+            let indices = [
+                find(chromas, "chroma", range, "chroma-begin", diagnostics),
+                find(chromas, "chroma", range, "chroma-end", diagnostics),
+                find(values, "value", range, "value-begin", diagnostics),
+                find(values, "value", range, "value-end", diagnostics),
+            ];
+            let [chroma_begin_index, chroma_end_index, value_begin_index, value_end_index] =
+                match indices {
+                    [Some(a), Some(b), Some(c), Some(d)] => [a, b, c, d],
+                    _ => {
+                        skipped_ranges = true;
+                        continue;
+                    }
+                };
+
+            log::debug!(
+                "Block of color {}: hues {} to {}, chroma {} to {}, value {} to {}.",
+                color_id,
+                hues[hue_begin_index],
+                hues[hue_end_index],
+                chromas[chroma_begin_index],
+                chromas[chroma_end_index],
+                values[value_begin_index],
+                values[value_end_index]
+            );
+
+            // legal, but more likely a typo than a boundary of the charts,
+            // none of whose blocks is narrower than half a step
+            let span = |list: &[String], begin: usize, end: usize| -> f32 {
+                let parse = |i: usize| list[i].parse::<f32>().unwrap_or(f32::NAN);
+                return parse(end) - parse(begin);
+            };
+            let width = span(chromas, chroma_begin_index, chroma_end_index);
+            let height = span(values, value_begin_index, value_end_index);
+            if width < MIN_BLOCK_SPAN || height < MIN_BLOCK_SPAN {
+                diagnostics
+                    .warning(
+                        "tiny-block",
+                        format!(
+                            "Block of color {} is only {} chroma wide and {} value high.",
+                            color_id, width, height
+                        ),
+                    )
+                    .ids(3, &[color_id])
+                    .at(
+                        &hues[hue_begin_index],
+                        &chromas[chroma_begin_index],
+                        &values[value_begin_index],
+                    )
+                    .node(range);
+            }
+
+            for h in hue_begin_index..hue_logical_end_index {
+                let h = h % hues.len();
+
+                let chroma_span = chroma_begin_index..chroma_end_index;
+                for (c, chroma) in chromas
+                    .iter()
+                    .enumerate()
+                    .take(chroma_span.end)
+                    .skip(chroma_span.start)
+                {
+                    let value_span = value_begin_index..value_end_index;
+                    for (v, value) in values
+                        .iter()
+                        .enumerate()
+                        .take(value_span.end)
+                        .skip(value_span.start)
+                    {
+                        let existing = lookup_table.get(h, c, v).unwrap();
+                        if existing != 0 {
+                            diagnostics
+                                .error(
+                                    "overlapping-block",
+                                    format!(
+                                        "Trying to place color {} over {} at h={} c={} v={}",
+                                        color_id, existing, hues[h], chroma, value
+                                    ),
+                                )
+                                .ids(3, &[color_id, existing])
+                                .at(&hues[h], chroma, value)
+                                .node(range);
+                            continue;
+                        }
+
+                        lookup_table.set(h, c, v, color_id);
+                    }
+                }
+            }
+
+            positions.push(doc.text_pos_at(range.range().start));
+            blocks.push(ColorBlock {
+                color_id,
+                hues: Range {
+                    start: hue_begin_index,
+                    end: hue_end_index,
+                },
+                chromas: Range {
+                    start: chroma_begin_index,
+                    end: chroma_end_index,
+                },
+                values: Range {
+                    start: value_begin_index,
+                    end: value_end_index,
+                },
+            })
+        }
+    }
+
+    // now validate that all slots have been filled
+    if skipped_ranges {
+        return (blocks, positions);
+    }
+    for (h, hue) in hues.iter().enumerate() {
+        for (c, chroma) in chromas.iter().enumerate().take(chromas.len() - 1) {
+            for (v, value) in values.iter().enumerate().take(values.len() - 1) {
+                if lookup_table.get(h, c, v) == Some(0) {
+                    diagnostics
+                        .error(
+                            "coverage-gap",
+                            format!("No color placed at h={} c={} v={}", hue, chroma, value),
+                        )
+                        .at(hue, chroma, value);
+                }
+            }
+        }
+    }
+
+    return (blocks, positions);
+}
+
+/// Find the ids used by blocks that have no level 3 name, in ascending order.
+fn unnamed_block_ids(names: &NameMap, blocks: &[ColorBlock]) -> Vec<u32> {
+    let mut ids = blocks
+        .iter()
+        .map(|x| x.color_id)
+        .filter(|id| !names.contains_key(id))
+        .collect::<Vec<u32>>();
+    ids.sort();
+    ids.dedup();
+    return ids;
+}
+
+/// Find the level 3 names that no block uses, in ascending order.
+fn unreferenced_name_ids(names: &NameMap, blocks: &[ColorBlock]) -> Vec<u32> {
+    let mut ids = names
+        .keys()
+        .copied()
+        .filter(|id| !blocks.iter().any(|x| x.color_id == *id))
+        .collect::<Vec<u32>>();
+    ids.sort();
+    return ids;
+}
+
+/// Check that every block refers to a named color, and that every named
+/// color has at least one block.
+fn validate_references(
+    names: &NameMap,
+    blocks: &[ColorBlock],
+    name_positions: &NamePositions,
+    block_positions: &[roxmltree::TextPos],
+    diagnostics: &mut Diagnostics,
+) {
+    for id in unreferenced_name_ids(names, blocks) {
+        diagnostics
+            .warning(
+                "unused-name",
+                format!(
+                    "Color {} ('{}') is named but has no ranges.",
+                    id, names[&id].name
+                ),
+            )
+            .ids(3, &[id])
+            .position(name_positions.get(&(3, id)).copied());
+    }
+
+    for id in unnamed_block_ids(names, blocks) {
+        diagnostics
+            .error(
+                "unnamed-color",
+                format!("Ranges use color {}, which has no name.", id),
+            )
+            .ids(3, &[id])
+            .position(
+                blocks
+                    .iter()
+                    .position(|x| x.color_id == id)
+                    .map(|i| block_positions[i]),
+            );
+    }
+}
+
+/// The cells of the color solid covered by a block, as hue, chroma and value
+/// indices, following the hue range around the circle.
+pub(crate) fn block_cells(block: &ColorBlock, hue_count: usize) -> Vec<(usize, usize, usize)> {
+    let hue_end = if block.hues.end < block.hues.start {
+        block.hues.end + hue_count
+    } else {
+        block.hues.end
+    };
+    let mut cells = Vec::new();
+    for h in block.hues.start..hue_end {
+        for c in block.chromas.clone() {
+            for v in block.values.clone() {
+                cells.push((h % hue_count, c, v));
+            }
+        }
+    }
+    return cells;
+}
+
+/// Check that the blocks of each color form a single region, any two of
+/// them connected through blocks of the same color sharing a face. The hue
+/// circle wraps around, so 10RP and 1R are neighbors.
+fn validate_contiguity(
+    names: &NameMap,
+    blocks: &[ColorBlock],
+    block_positions: &[roxmltree::TextPos],
+    hues: &[String],
+    chromas: &[String],
+    values: &[String],
+    diagnostics: &mut Diagnostics,
+) {
+    let mut cells_by_id: BTreeMap<u32, HashSet<(usize, usize, usize)>> = BTreeMap::new();
+    for block in blocks.iter() {
+        cells_by_id
+            .entry(block.color_id)
+            .or_default()
+            .extend(block_cells(block, hues.len()));
+    }
+
+    for (id, cells) in cells_by_id.iter() {
+        let mut unvisited = cells.clone();
+        let mut regions: Vec<(usize, usize, usize)> = Vec::new();
+        while let Some(start) = unvisited.iter().min().copied() {
+            // flood fill one region, remembering its first cell
+            regions.push(start);
+            unvisited.remove(&start);
+            let mut stack = vec![start];
+            while let Some((h, c, v)) = stack.pop() {
+                let neighbors = [
+                    ((h + 1) % hues.len(), c, v),
+                    ((h + hues.len() - 1) % hues.len(), c, v),
+                    (h, c + 1, v),
+                    (h, c.wrapping_sub(1), v),
+                    (h, c, v + 1),
+                    (h, c, v.wrapping_sub(1)),
+                ];
+                for neighbor in neighbors {
+                    if unvisited.remove(&neighbor) {
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        if regions.len() < 2 {
+            continue;
+        }
+        let (h, c, v) = regions[1];
+        // the first block of the separate region
+        let block = blocks
+            .iter()
+            .position(|x| x.color_id == *id && block_cells(x, hues.len()).contains(&(h, c, v)));
+        diagnostics
+            .error(
+                "disconnected-color",
+                format!(
+                    "Blocks of color {} ('{}') form {} separate regions; one starts at h={} c={} v={}.",
+                    id,
+                    names[id].name,
+                    regions.len(),
+                    hues[h],
+                    chromas[c],
+                    values[v]
+                ),
+            )
+            .ids(3, &[*id])
+            .at(&hues[h], &chromas[c], &values[v])
+            .position(block.map(|i| block_positions[i]));
+    }
+}
+
+/// Check that the hues of every block fall within the hues its level 2 and
+/// level 1 names can cover, like "purplish blue" and "blue".
+fn validate_hierarchy(
+    dataset: &Dataset,
+    block_positions: &[roxmltree::TextPos],
+    diagnostics: &mut Diagnostics,
+) {
+    let (names, level2_names, level1_names) = (
+        dataset.names(),
+        dataset.level2_names(),
+        dataset.level1_names(),
+    );
+    let (hues, chromas, values) = (dataset.hues(), dataset.chromas(), dataset.values());
+    for (block, position) in dataset.blocks().iter().zip(block_positions.iter()) {
+        let id = block.color_id;
+        let level2_id = match names[&id].parent {
+            Some(x) => x,
+            None => continue,
+        };
+        let level1_id = level2_names[&level2_id].parent;
+        let parents = [
+            (2, Some(level2_id), level2_names),
+            (1, level1_id, level1_names),
+        ];
+
+        let (hue_begin, hue_end) = (&hues[block.hues.start], &hues[block.hues.end]);
+        for (level, parent_id, parent_names) in parents {
+            let parent = match parent_id {
+                Some(x) => &parent_names[&x],
+                None => continue,
+            };
+            let span = parse_name(&parent.name).and_then(|x| hue_span(&x.hue));
+            let (span_begin, span_end) = match span {
+                Some(x) => x,
+                None => continue,
+            };
+            if arc_within(
+                hue_begin.parse::<MunsellHue>().unwrap().raw(),
+                hue_end.parse::<MunsellHue>().unwrap().raw(),
+                span_begin.raw(),
+                span_end.raw(),
+            ) {
+                continue;
+            }
+
+            diagnostics
+                .warning(
+                    "hue-outside-parent",
+                    format!(
+                        "Block of color {} ('{}') from {} to {} is outside the hues of its level {} name '{}'.",
+                        id, names[&id].name, hue_begin, hue_end, level, parent.name
+                    ),
+                )
+                .ids(3, &[id])
+                .at(
+                    hue_begin,
+                    &chromas[block.chromas.start],
+                    &values[block.values.start],
+                )
+                .position(Some(*position));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+    use std::error::Error;
+
+    use crate::dataset::{slugify, unnamed_block_ids, unreferenced_name_ids, BUNDLED_XML};
+    use crate::diagnostic::{report_to_json, Diagnostic, Diagnostics};
+    use crate::progress::no_progress;
+    use crate::{ColorBlock, ColorName, Dataset, DatasetError, MunsellColor, MunsellHue, Severity};
+
+    /// Validate a broken copy of the bundled dataset, returning the error
+    /// messages.
+    fn errors_after(edits: &[(&str, &str)]) -> Vec<String> {
+        let mut text = BUNDLED_XML.to_string();
+        for (from, to) in edits {
+            assert!(text.contains(from));
+            text = text.replacen(from, to, 1);
+        }
+
+        let doc = roxmltree::Document::parse(&text).unwrap();
+        let mut diagnostics = Diagnostics::default();
+        let dataset = Dataset::validate(&doc, &mut no_progress, &mut diagnostics);
+        assert_eq!(dataset.is_none(), diagnostics.has_errors());
+
+        return diagnostics
+            .list
+            .into_iter()
+            .filter(|x| x.severity == Severity::Error)
+            .map(|x| x.message)
+            .collect();
+    }
+
+    #[test]
+    fn collects_every_error() {
+        assert!(errors_after(&[]).is_empty());
+
+        let errors = errors_after(&[
+            ("name=\"Strong pink\"", "name=\"Vivid pink\""),
+            (
+                "<amount>0.5</amount>\n\t\t<amount>0.7</amount>",
+                "<amount>0.7</amount>\n\t\t<amount>0.5</amount>",
+            ),
+            (
+                "chroma-begin=\"7.0\" chroma-end=\"11.0\"",
+                "chroma-begin=\"7.5\" chroma-end=\"11.0\"",
+            ),
+            ("<license>CC0-1.0</license>", "<license></license>"),
+        ]);
+        assert_eq!(
+            errors,
+            vec![
+                "Missing <license> in <metadata>.",
+                "Duplicate name 'Vivid pink' used for both id 1 and 2.",
+                "chromas array is not in sorted order.",
+                "Unknown chroma '7.5' in chroma-begin of <range>.",
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_attributes() {
+        // each missing attribute is reported at the line of its element,
+        // which is skipped, rather than stopping validation
+        let first_error = |from: &str, to: &str| {
+            let diagnostics = Dataset::check(&BUNDLED_XML.replacen(from, to, 1)).unwrap();
+            let error = diagnostics
+                .into_iter()
+                .find(|x| x.severity == Severity::Error)
+                .unwrap();
+            return (error.line.unwrap(), error.message);
+        };
+        let error = |line: u32, message: &str| (line, message.to_string());
+
+        assert_eq!(
+            first_error("name=\"Vivid pink\" ", ""),
+            error(42, "<name> has no name attribute.")
+        );
+        assert_eq!(
+            first_error("<name color=\"1\" name=\"Pink\" abbr=\"Pk\">", "<name>"),
+            error(40, "<name> has no name attribute.")
+        );
+        assert_eq!(
+            first_error("<range color=\"1\" ", "<range "),
+            error(495, "<range> has no color attribute.")
+        );
+        assert_eq!(
+            first_error("chroma-begin=\"11.0\" ", ""),
+            error(495, "<range> has no chroma-begin attribute.")
+        );
+        assert_eq!(
+            first_error("<hue-range begin=\"1R\" ", "<hue-range "),
+            error(494, "<hue-range> has no begin attribute.")
+        );
+        assert_eq!(
+            first_error("<amount>0.5</amount>", "<amount></amount>"),
+            error(444, "'' in chromas is not a number.")
+        );
+
+        // the errors of later elements are still found
+        let errors = errors_after(&[
+            ("name=\"Vivid pink\" ", ""),
+            ("<range color=\"1\" ", "<range "),
+        ]);
+        assert_eq!(errors[0], "<name> has no name attribute.");
+        assert!(errors.contains(&"<range> has no color attribute.".to_string()));
+
+        let errors = errors_after(&[("<names>", "<colors>"), ("</names>", "</colors>")]);
+        assert_eq!(errors, vec!["Missing <names> element."]);
+        let errors = errors_after(&[("<hues>", "<leaves>"), ("</hues>", "</leaves>")]);
+        assert_eq!(errors, vec!["Missing <hues> element."]);
+    }
+
+    #[test]
+    fn returns_errors() {
+        let result =
+            Dataset::parse(&BUNDLED_XML.replacen("name=\"Strong pink\"", "name=\"Vivid pink\"", 1));
+        let source = result.as_ref().err().and_then(|e| e.source()).unwrap();
+        assert_eq!(source.to_string(), "1 error found");
+        let error = match result {
+            Err(DatasetError::Invalid(e)) => e,
+            _ => panic!("a duplicate name should be an error"),
+        };
+        assert_eq!(error.to_string(), "1 error found");
+        assert_eq!(error.errors().next().unwrap().code, "duplicate-name");
+
+        let result = Dataset::parse("<iscc-nbs></colors>");
+        assert!(matches!(result, Err(DatasetError::Xml(_))));
+        assert!(result
+            .err()
+            .unwrap()
+            .source()
+            .unwrap()
+            .is::<roxmltree::Error>());
+    }
+
+    #[test]
+    fn custom_structure() {
+        let errors = errors_after(&[("<ranges>", "<blocks>"), ("</ranges>", "</blocks>")]);
+        assert_eq!(errors, vec!["Missing <ranges> element."]);
+
+        let errors = errors_after(&[("<amount id=\"4R\">", "<amount id=\"4Q\">")]);
+        assert_eq!(errors[0], "'4Q' in hues is not a Munsell hue like '7.5YR'.");
+
+        // hues written differently are only warned about, wherever they are
+        let text = BUNDLED_XML.replace("\"4R\"", "\"4 r\"");
+        let doc = roxmltree::Document::parse(&text).unwrap();
+        let mut diagnostics = Diagnostics::default();
+        assert!(Dataset::validate(&doc, &mut no_progress, &mut diagnostics).is_some());
+        let warning = diagnostics
+            .list
+            .iter()
+            .find(|x| x.code == "non-canonical-hue")
+            .unwrap();
+        assert_eq!(warning.message, "'4 r' in hues is usually written '4R'.");
+    }
+
+    #[test]
+    fn ungrammatical_names() {
+        let errors = errors_after(&[
+            ("name=\"Deep pink\"", "name=\"Deeep pink\""),
+            ("name=\"Light pink\"", "name=\"Light Pink\""),
+        ]);
+        assert_eq!(
+            errors,
+            vec![
+                "Name 'Deeep pink' of level 3 id 3 is not a modifier followed by a hue name.",
+                "Name 'Light Pink' of level 3 id 4 is not a modifier followed by a hue name.",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_every_overlap_and_gap() {
+        // color 2 moves onto color 1, leaving its own place empty
+        let errors = errors_after(&[(
+            "<range color=\"2\" chroma-begin=\"7.0\" chroma-end=\"11.0\"",
+            "<range color=\"2\" chroma-begin=\"11.0\" chroma-end=\"15.0\"",
+        )]);
+        assert!(errors.len() > 2);
+        assert!(errors[0].starts_with("Trying to place color 2 over 1 at h=1R"));
+        assert!(errors.last().unwrap().starts_with("No color placed at h="));
+    }
+
+    #[test]
+    fn disconnected_colors() {
+        // a block of moderate blue turns into vivid pink, far from the rest
+        // of it
+        let errors = Dataset::check(&BUNDLED_XML.replacen(
+            "<range color=\"182\" chroma-begin=\"5.0\" chroma-end=\"9.0\"",
+            "<range color=\"1\" chroma-begin=\"5.0\" chroma-end=\"9.0\"",
+            1,
+        ))
+        .unwrap()
+        .into_iter()
+        .filter(|x| x.code == "disconnected-color")
+        .collect::<Vec<Diagnostic>>();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].ids, vec![1]);
+        assert!(errors[0]
+            .message
+            .starts_with("Blocks of color 1 ('Vivid pink') form 2 separate regions"));
+
+        // the blocks of neutrals run all the way around the hue circle
+        assert!(!Dataset::check(BUNDLED_XML)
+            .unwrap()
+            .iter()
+            .any(|x| x.code == "disconnected-color"));
+    }
+
+    #[test]
+    fn hues_outside_parents() {
+        // moderate blue moves from blue into pink
+        let moved = "<name color=\"182\" name=\"Moderate blue\" abbr=\"m.B\" />";
+        let text = BUNDLED_XML.replacen(moved, "", 1).replacen(
+            "<name color=\"1\" name=\"Vivid pink\" abbr=\"viv.Pk\" />",
+            &format!(
+                "<name color=\"1\" name=\"Vivid pink\" abbr=\"viv.Pk\" />{}",
+                moved
+            ),
+            1,
+        );
+        let warnings = Dataset::check(&text)
+            .unwrap()
+            .into_iter()
+            .filter(|x| x.code == "hue-outside-parent")
+            .collect::<Vec<Diagnostic>>();
+
+        assert!(!warnings.is_empty());
+        assert!(warnings.iter().all(|x| x.ids == vec![182]));
+        assert!(warnings[0]
+            .message
+            .ends_with("outside the hues of its level 2 name 'Pink'."));
+        assert!(warnings[1]
+            .message
+            .ends_with("outside the hues of its level 1 name 'Pink'."));
+    }
+
+    #[test]
+    fn structured_diagnostics() {
+        let text = BUNDLED_XML
+            .replacen("name=\"Strong pink\"", "name=\"Vivid pink\"", 1)
+            .replacen(
+                "<range color=\"2\" chroma-begin=\"7.0\" chroma-end=\"11.0\"",
+                "<range color=\"2\" chroma-begin=\"11.0\" chroma-end=\"15.0\"",
+                1,
+            );
+        let all = Dataset::check(&text).unwrap();
+        let diagnostics = all
+            .iter()
+            .filter(|x| x.severity == Severity::Error)
+            .collect::<Vec<&Diagnostic>>();
+
+        // the second of the two names
+        assert_eq!(diagnostics[0].code, "duplicate-name");
+        assert_eq!(diagnostics[0].level, Some(3));
+        assert_eq!(diagnostics[0].ids, vec![1, 2]);
+        assert_eq!(
+            (diagnostics[0].line, diagnostics[0].column),
+            (Some(43), Some(5))
+        );
+        assert!(diagnostics[0]
+            .to_string()
+            .starts_with("Error: line 43, column 5: Duplicate name"));
+
+        let overlap = diagnostics[1];
+        assert_eq!(overlap.code, "overlapping-block");
+        assert_eq!(overlap.ids, vec![2, 1]);
+        assert_eq!(overlap.coordinates.as_ref().unwrap().hue, "1R");
+        assert_eq!((overlap.line, overlap.column), (Some(496), Some(4)));
+
+        let json = report_to_json(&all);
+        assert_eq!(json["errors"], diagnostics.len());
+        assert_eq!(json["warnings"], all.len() - diagnostics.len());
+        let gap = json["diagnostics"].as_array().unwrap().last().unwrap();
+        assert_eq!(gap["code"], "coverage-gap");
+        assert_eq!(gap["ids"].as_array().unwrap().len(), 0);
+        assert!(gap["coordinates"]["chroma"].is_string());
+        assert!(gap["line"].is_null());
+        assert!(gap["column"].is_null());
+    }
+
+    #[test]
+    fn warnings() {
+        let warnings = |text: &str| {
+            return Dataset::check(text)
+                .unwrap()
+                .into_iter()
+                .filter(|x| x.severity == Severity::Warning)
+                .collect::<Vec<Diagnostic>>();
+        };
+        // abbreviations that differ from the rules, as transcribed
+        let bundled = warnings(BUNDLED_XML);
+        assert!(bundled.iter().all(|x| x.code == "nonstandard-abbr"));
+        assert_eq!(
+            bundled.iter().map(|x| x.ids[0]).collect::<Vec<u32>>(),
+            vec![21, 118, 188]
+        );
+
+        // a value boundary past white, and a sliver of a block
+        let text = BUNDLED_XML
+            .replacen(
+                "<amount>8.5</amount>\n\t\t<amount>INF",
+                "<amount>8.5</amount>\n\t\t<amount>10.2</amount>\n\t\t<amount>INF",
+                1,
+            )
+            .replacen(
+                "color=\"263\" chroma-begin=\"0.0\" chroma-end=\"0.5\"",
+                "color=\"263\" chroma-begin=\"0.5\" chroma-end=\"0.7\"",
+                1,
+            );
+        let found = warnings(&text);
+        let unusual = found.iter().find(|x| x.code == "unusual-boundary").unwrap();
+        assert_eq!(
+            unusual.message,
+            "'10.2' in values is outside the usual range of 0 to 10."
+        );
+        assert!(found
+            .iter()
+            .any(|x| x.code == "tiny-block" && x.ids == vec![263]));
+    }
+
+    #[test]
+    fn misclassified_colors() {
+        let errors = errors_after(&[(
+            "name=\"Strong pink\" abbr=\"s.Pk\"",
+            "name=\"Strong pink\" abbr=\"s.Pk\" srgb=\"pink\"",
+        )]);
+        assert_eq!(
+            errors,
+            vec!["Name 'Strong pink' has an invalid representative color 'pink'."]
+        );
+
+        // only checked once everything else is valid
+        let errors = errors_after(&[(
+            "name=\"Vivid pink\" abbr=\"viv.Pk\"",
+            "name=\"Vivid pink\" abbr=\"viv.Pk\" srgb=\"#000000\"",
+        )]);
+        assert_eq!(
+            errors,
+            vec![
+                "Representative color #000000 of level 3 color 1 ('Vivid pink') classifies as 267 ('Black')."
+            ]
+        );
+    }
+
+    #[test]
+    fn cross_references() {
+        let dataset = Dataset::bundled();
+        assert!(unnamed_block_ids(dataset.names(), dataset.blocks()).is_empty());
+        assert!(unreferenced_name_ids(dataset.names(), dataset.blocks()).is_empty());
+
+        let name = |name: &str| ColorName {
+            name: name.to_string(),
+            abbr: name.to_string(),
+            parent: None,
+            srgb: None,
+            description: None,
+            usage: None,
+        };
+        let block = |color_id: u32| ColorBlock {
+            color_id,
+            hues: 0..1,
+            chromas: 0..1,
+            values: 0..1,
+        };
+        let names = HashMap::from([(1, name("one")), (2, name("two"))]);
+        let blocks = vec![block(1), block(3), block(3)];
+
+        assert_eq!(unnamed_block_ids(&names, &blocks), vec![3]);
+        assert_eq!(unreferenced_name_ids(&names, &blocks), vec![2]);
+    }
+
+    #[test]
+    fn metadata() {
+        let dataset = Dataset::bundled();
+
+        assert_eq!(dataset.metadata().license, "CC0-1.0");
+        assert_eq!(dataset.metadata().publication_date, "1976-12");
+        assert!(dataset
+            .metadata()
+            .source
+            .contains("Special Publication 440"));
+    }
+
+    #[test]
+    fn without_metadata() {
+        let start = BUNDLED_XML.find("<metadata>").unwrap();
+        let end = BUNDLED_XML.find("</metadata>").unwrap() + "</metadata>".len();
+        let text = [&BUNDLED_XML[..start], &BUNDLED_XML[end..]].concat();
+
+        let (dataset, diagnostics) = Dataset::check_and_build(&text).unwrap();
+        let dataset = dataset.unwrap();
+        assert_eq!(dataset.metadata().version, "");
+        assert_eq!(dataset.metadata().license, "");
+        assert_eq!(dataset.names().len(), 267);
+        let warning = diagnostics
+            .iter()
+            .find(|x| x.code == "missing-metadata")
+            .unwrap();
+        assert_eq!(warning.severity, Severity::Warning);
+    }
+
+    #[test]
+    fn slugs() {
+        assert_eq!(slugify("Vivid reddish orange"), "vivid-reddish-orange");
+        assert_eq!(slugify("  Grayish  (olive) green! "), "grayish-olive-green");
+
+        let dataset = Dataset::bundled();
+        assert_eq!(dataset.slug(34).unwrap(), "34-vivid-reddish-orange");
+        assert_eq!(dataset.slug(0), None);
+    }
+
+    #[test]
+    fn lookup_names() {
+        let dataset = Dataset::bundled();
+        let color = |hue: &str, value: f32, chroma: f32| {
+            MunsellColor::new(hue.parse::<MunsellHue>().unwrap(), value, chroma)
+        };
+
+        assert_eq!(dataset.lookup_id(&color("2R", 7.0, 12.0)), Some(1));
+        assert_eq!(
+            dataset.lookup(&color("2R", 7.0, 12.0)).unwrap().name,
+            "Vivid pink"
+        );
+        assert_eq!(dataset.lookup_id(&color("5PB", 9.0, 0.2)), Some(263));
+        // hue ranges wrap around from 9RP to 1R
+        assert_eq!(dataset.lookup_id(&color("10RP", 1.0, 0.2)), Some(267));
+        // lower boundaries are inclusive, upper ones exclusive
+        assert_eq!(dataset.lookup_id(&color("2R", 6.5, 11.0)), Some(1));
+        assert_eq!(dataset.lookup_id(&color("2R", 6.4, 11.0)), Some(3));
+    }
+
+    #[test]
+    fn lookup_levels() {
+        let dataset = Dataset::bundled();
+
+        let names = dataset
+            .lookup_all_levels(&MunsellColor::new(
+                "8YR".parse::<MunsellHue>().unwrap(),
+                6.0,
+                5.0,
+            ))
+            .unwrap();
+        assert_eq!(names.level1.name, "Brown");
+        assert_eq!(names.level2.name, "Yellowish brown");
+        assert_eq!(names.level3.name, "Light yellowish brown");
+
+        assert!(dataset.level_names(0).is_none());
+        assert!(dataset
+            .lookup_all_levels(&MunsellColor::new(MunsellHue::new(0.0), -1.0, 2.0))
+            .is_none());
+    }
+
+    #[test]
+    fn lookup_neutrals() {
+        let dataset = Dataset::bundled();
+
+        for (spec, name) in [
+            ("N 9.5/", "White"),
+            ("N 5/", "Medium gray"),
+            ("N 0.5/", "Black"),
+        ] {
+            let color: MunsellColor = spec.parse().unwrap();
+            assert_eq!(dataset.lookup(&color).unwrap().name, name, "{}", spec);
+        }
+    }
+
+    #[test]
+    fn lookup_abbreviations() {
+        let dataset = Dataset::bundled();
+
+        assert_eq!(dataset.lookup_abbr("viv.pB"), Some(194));
+        assert_eq!(dataset.lookup_abbr("viv. pB"), Some(194));
+        assert_eq!(dataset.lookup_abbr(" VIV PB "), Some(194));
+        assert_eq!(dataset.lookup_abbr("v.l.pB"), Some(198));
+        // "bkR" in the dataset, "bk.R" in the standard form
+        assert_eq!(dataset.lookup_abbr("bkR"), Some(21));
+        assert_eq!(dataset.lookup_abbr("bk.R"), Some(21));
+        // Light gray and Light greenish yellow differ only in case
+        assert_eq!(dataset.lookup_abbr("l.Gy"), Some(264));
+        assert_eq!(dataset.lookup_abbr("l.gY"), Some(101));
+        assert_eq!(dataset.lookup_abbr("l.gy"), None);
+        assert_eq!(dataset.lookup_abbr("v.p.B"), Some(184));
+        assert_eq!(dataset.lookup_abbr("v.p.b"), Some(184));
+
+        assert_eq!(dataset.lookup_name("Light yellowish brown"), Some(76));
+        assert_eq!(dataset.lookup_name(" light  YELLOWISH-brown "), Some(76));
+        assert_eq!(dataset.lookup_name("light greyish red"), Some(18));
+        assert_eq!(dataset.lookup_name("light yellowish"), None);
+        assert_eq!(dataset.lookup_name("Vivid bluish gray"), None);
+        assert_eq!(dataset.lookup_abbr("x.Q"), None);
+    }
+
+    #[test]
+    fn lookup_outside_solid() {
+        let dataset = Dataset::bundled();
+
+        assert_eq!(
+            dataset.lookup_id(&MunsellColor::new(MunsellHue::new(0.0), -1.0, 2.0)),
+            None
+        );
+        assert_eq!(
+            dataset.lookup_id(&MunsellColor::new(MunsellHue::new(0.0), 5.0, -2.0)),
+            None
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let dataset = Dataset::bundled();
+
+        let name = &dataset.names()[&1];
+        let json = serde_json::to_string(name).unwrap();
+        let parsed: ColorName = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.name, "Vivid pink");
+        assert_eq!(parsed.abbr, "viv.Pk");
+        assert_eq!(parsed.parent, name.parent);
+
+        let block = &dataset.blocks()[0];
+        let parsed: ColorBlock =
+            serde_json::from_str(&serde_json::to_string(block).unwrap()).unwrap();
+        assert_eq!(parsed.color_id, block.color_id);
+        assert_eq!(parsed.hues, block.hues);
+        assert_eq!(parsed.chromas, block.chromas);
+        assert_eq!(parsed.values, block.values);
+
+        let color: MunsellColor = "7.5YR 6/8".parse().unwrap();
+        let json = serde_json::to_string(&color).unwrap();
+        assert_eq!(json, r#"{"hue":12.5,"value":6.0,"chroma":8.0}"#);
+        assert_eq!(serde_json::from_str::<MunsellColor>(&json).unwrap(), color);
+    }
+}
@@ -16,8 +16,77 @@ pub fn degree_diff(f1: f32, f2: f32) -> f32 {
     return s.atan2(c).to_degrees().abs();
 }
 
+/// The sum of the unit vectors of `(angle, weight)` pairs in degrees, each
+/// scaled by its weight, and the sum of the weights.
+fn resultant(angles: impl IntoIterator<Item = (f32, f32)>) -> (f32, f32, f32) {
+    let mut x = 0.0;
+    let mut y = 0.0;
+    let mut total = 0.0;
+    for (angle, weight) in angles {
+        x += angle.to_radians().cos() * weight;
+        y += angle.to_radians().sin() * weight;
+        total += weight;
+    }
+
+    return (x, y, total);
+}
+
+/// The weighted circular mean of `(angle, weight)` pairs in degrees, in
+/// `(-180, 180]`, or `None` if the weights sum to zero or the angles cancel
+/// out.
+pub fn weighted_degree_mean(angles: impl IntoIterator<Item = (f32, f32)>) -> Option<f32> {
+    let (x, y, total) = resultant(angles);
+    if total <= 0.0 || x.hypot(y) < total * 1e-6 {
+        return None;
+    }
+
+    return Some(y.atan2(x).to_degrees());
+}
+
+/// The weighted circular variance of `(angle, weight)` pairs in degrees:
+/// one minus the length of their mean resultant vector, from `0` when all
+/// the angles are the same to `1` when they are spread evenly around the
+/// circle. Returns `0` if the weights sum to zero.
+pub fn degree_variance(angles: impl IntoIterator<Item = (f32, f32)>) -> f32 {
+    let (x, y, total) = resultant(angles);
+    if total <= 0.0 {
+        return 0.0;
+    }
+
+    return (1.0 - x.hypot(y) / total).max(0.0);
+}
+
+/// The shortest arc containing every angle, as its start in `[0, 360)` and
+/// its width in degrees, going counterclockwise from the start; `None` if
+/// there are no angles.
+///
+/// The arc is the complement of the largest gap between neighboring angles.
+pub fn degree_span(angles: impl IntoIterator<Item = f32>) -> Option<(f32, f32)> {
+    let mut angles = angles
+        .into_iter()
+        .map(|x| x.rem_euclid(360.0))
+        .collect::<Vec<f32>>();
+    if angles.is_empty() {
+        return None;
+    }
+    angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    // the gap from the last angle around to the first
+    let mut start = angles[0];
+    let mut gap = angles[0] + 360.0 - angles[angles.len() - 1];
+    for pair in angles.windows(2) {
+        if pair[1] - pair[0] > gap {
+            gap = pair[1] - pair[0];
+            start = pair[1];
+        }
+    }
+
+    return Some((start, 360.0 - gap));
+}
+
 #[cfg(test)]
 mod test {
+    use crate::degree::{degree_span, degree_variance, weighted_degree_mean};
     use crate::degree_average;
     use crate::degree_diff;
 
@@ -32,4 +101,21 @@ mod test {
         assert!(degree_diff(20.0, 40.0) - 20.0 < 0.0001);
         assert!(degree_diff(350.0, 20.0) - 30.0 < 0.0001);
     }
+
+    #[test]
+    fn test_statistics() {
+        let mean = weighted_degree_mean([(350.0, 1.0), (20.0, 2.0)]).unwrap();
+        assert!((mean - 10.1).abs() < 0.01);
+        assert_eq!(weighted_degree_mean([(0.0, 1.0), (180.0, 1.0)]), None);
+        assert_eq!(weighted_degree_mean([]), None);
+
+        assert!(degree_variance([(30.0, 1.0), (30.0, 5.0)]) < 0.0001);
+        assert!(degree_variance([(0.0, 1.0), (180.0, 1.0)]) > 0.9999);
+
+        let (start, width) = degree_span([350.0, 20.0, 5.0]).unwrap();
+        assert!((start - 350.0).abs() < 0.0001);
+        assert!((width - 30.0).abs() < 0.0001);
+        assert_eq!(degree_span([90.0]), Some((90.0, 0.0)));
+        assert_eq!(degree_span([]), None);
+    }
 }
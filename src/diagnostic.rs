@@ -0,0 +1,238 @@
+//! Problems found while validating a dataset, collected so that all of them
+//! can be reported at once instead of stopping at the first.
+
+use std::fmt;
+
+use roxmltree::{Node, TextPos};
+use serde_json::{json, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// The dataset is unusable until this is fixed.
+    Error,
+    /// The dataset is usable, but probably not as intended.
+    Warning,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        return match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+    }
+}
+
+/// A cell of the color solid, as the hue, chroma and value boundaries at its
+/// lower corner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Coordinates {
+    pub hue: String,
+    pub chroma: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// What kind of problem this is, like `duplicate-name` or
+    /// `coverage-gap`; stable across releases, unlike `message`.
+    pub code: &'static str,
+    pub message: String,
+    /// The name level that `ids` belong to.
+    pub level: Option<u32>,
+    /// The color ids involved, if any.
+    pub ids: Vec<u32>,
+    /// The cell of the color solid involved, if any.
+    pub coordinates: Option<Coordinates>,
+    /// The line of the XML document involved, if known, counting from 1.
+    pub line: Option<u32>,
+    /// The column on `line` where the element involved starts, if known,
+    /// counting from 1.
+    pub column: Option<u32>,
+}
+
+impl Diagnostic {
+    /// Name the color ids involved, and the level they belong to.
+    pub(crate) fn ids(&mut self, level: u32, ids: &[u32]) -> &mut Self {
+        self.level = Some(level);
+        self.ids = ids.to_vec();
+        return self;
+    }
+
+    pub(crate) fn at(&mut self, hue: &str, chroma: &str, value: &str) -> &mut Self {
+        self.coordinates = Some(Coordinates {
+            hue: hue.to_string(),
+            chroma: chroma.to_string(),
+            value: value.to_string(),
+        });
+        return self;
+    }
+
+    /// Point at a place in the XML document, if known.
+    pub(crate) fn position(&mut self, position: Option<TextPos>) -> &mut Self {
+        if let Some(position) = position {
+            self.line = Some(position.row);
+            self.column = Some(position.col);
+        }
+        return self;
+    }
+
+    /// Point at the start of an element of the XML document.
+    pub(crate) fn node(&mut self, node: Node) -> &mut Self {
+        return self.position(Some(node.document().text_pos_at(node.range().start)));
+    }
+
+    /// The message, after the line and column if they are known.
+    fn located_message(&self) -> String {
+        return match (self.line, self.column) {
+            (Some(line), Some(column)) => {
+                format!("line {}, column {}: {}", line, column, self.message)
+            }
+            (Some(line), None) => format!("line {}: {}", line, self.message),
+            _ => self.message.clone(),
+        };
+    }
+
+    /// Log this diagnostic through the `log` crate, at the level of its
+    /// severity.
+    ///
+    /// `nonstandard-abbr` warnings are logged at debug level: the bundled
+    /// dataset has a few, as transcribed, and [`crate::Dataset::check`]
+    /// still reports them.
+    pub fn log(&self) {
+        match self.severity {
+            Severity::Error => log::error!("{}", self.located_message()),
+            Severity::Warning if self.code == "nonstandard-abbr" => {
+                log::debug!("{}", self.located_message())
+            }
+            Severity::Warning => log::warn!("{}", self.located_message()),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        return json!({
+            "severity": self.severity.as_str(),
+            "code": self.code,
+            "message": self.message,
+            "level": self.level,
+            "ids": self.ids,
+            "coordinates": self.coordinates.as_ref().map(|x| json!({
+                "hue": x.hue,
+                "chroma": x.chroma,
+                "value": x.value,
+            })),
+            "line": self.line,
+            "column": self.column,
+        });
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "Error",
+            Severity::Warning => "Warning",
+        };
+        write!(f, "{}: {}", severity, self.located_message())
+    }
+}
+
+/// Why a dataset could not be built: every problem validation found, at
+/// least one of them an error, in the order they were found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationError {
+    /// The diagnostics that are errors, leaving out the warnings.
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        return self
+            .diagnostics
+            .iter()
+            .filter(|x| x.severity == Severity::Error);
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors = self.errors().count();
+        return write!(
+            f,
+            "{} error{} found",
+            errors,
+            if errors == 1 { "" } else { "s" }
+        );
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Build a report of a validation run, with counts of errors and warnings
+/// and every diagnostic in the order they were found.
+pub fn report_to_json(diagnostics: &[Diagnostic]) -> Value {
+    let count = |severity: Severity| {
+        diagnostics
+            .iter()
+            .filter(|x| x.severity == severity)
+            .count()
+    };
+
+    return json!({
+        "errors": count(Severity::Error),
+        "warnings": count(Severity::Warning),
+        "diagnostics": diagnostics.iter().map(|x| x.to_json()).collect::<Vec<Value>>(),
+    });
+}
+
+/// The diagnostics of one validation run, in the order they were found.
+#[derive(Default)]
+pub(crate) struct Diagnostics {
+    pub list: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    fn push(&mut self, severity: Severity, code: &'static str, message: String) -> &mut Diagnostic {
+        self.list.push(Diagnostic {
+            severity,
+            code,
+            message,
+            level: None,
+            ids: Vec::new(),
+            coordinates: None,
+            line: None,
+            column: None,
+        });
+        return self.list.last_mut().unwrap();
+    }
+
+    pub fn error(&mut self, code: &'static str, message: String) -> &mut Diagnostic {
+        return self.push(Severity::Error, code, message);
+    }
+
+    pub fn warning(&mut self, code: &'static str, message: String) -> &mut Diagnostic {
+        return self.push(Severity::Warning, code, message);
+    }
+
+    pub fn has_errors(&self) -> bool {
+        return self.list.iter().any(|x| x.severity == Severity::Error);
+    }
+
+    /// Log every diagnostic, and return them all as an error if any of
+    /// them is one.
+    ///
+    /// Logging keeps warnings out of exports written to standard output,
+    /// and lets frontends silence them.
+    pub fn log_and_check(self) -> Result<(), ValidationError> {
+        for diagnostic in self.list.iter() {
+            diagnostic.log();
+        }
+        if self.has_errors() {
+            return Err(ValidationError {
+                diagnostics: self.list,
+            });
+        }
+        return Ok(());
+    }
+}
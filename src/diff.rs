@@ -0,0 +1,251 @@
+//! The differences between two versions of a dataset, for reviewing edits
+//! to the names and blocks before merging them.
+//!
+//! Blocks are compared cell by cell, on a grid with the hue, chroma and
+//! value boundaries of both versions, so that a moved boundary shows up as
+//! the cells that changed color rather than as blocks removed and added.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::dataset::{ColorName, Dataset};
+use crate::munsell::{MunsellColor, MunsellHue};
+
+/// How a name changed between two versions of a dataset.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NameChange {
+    Added {
+        name: String,
+        abbr: String,
+    },
+    Removed {
+        name: String,
+        abbr: String,
+    },
+    /// The name or the abbreviation changed, or both.
+    Renamed {
+        old_name: String,
+        old_abbr: String,
+        name: String,
+        abbr: String,
+    },
+}
+
+/// A change to one name of a dataset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NameDiff {
+    /// 1, 2 or 3.
+    pub level: u32,
+    pub color_id: u32,
+    pub change: NameChange,
+}
+
+/// A cell of the color solid that changed color, between neighboring
+/// boundaries of either version of the dataset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellDiff {
+    pub hues: (String, String),
+    pub chromas: (String, String),
+    pub values: (String, String),
+    /// The level 3 color of the cell in the old version, if any.
+    pub old: Option<u32>,
+    /// The level 3 color of the cell in the new version, if any.
+    pub new: Option<u32>,
+}
+
+/// Everything that changed between two versions of a dataset.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DatasetDiff {
+    /// Ordered by level and then by color id.
+    pub names: Vec<NameDiff>,
+    /// Ordered by hue, chroma and value.
+    pub cells: Vec<CellDiff>,
+}
+
+impl DatasetDiff {
+    pub fn is_empty(&self) -> bool {
+        return self.names.is_empty() && self.cells.is_empty();
+    }
+}
+
+fn diff_names(
+    level: u32,
+    old: &HashMap<u32, ColorName>,
+    new: &HashMap<u32, ColorName>,
+) -> Vec<NameDiff> {
+    let mut ids = old.keys().chain(new.keys()).copied().collect::<Vec<u32>>();
+    ids.sort();
+    ids.dedup();
+
+    return ids
+        .into_iter()
+        .filter_map(|id| {
+            let change = match (old.get(&id), new.get(&id)) {
+                (None, Some(x)) => NameChange::Added {
+                    name: x.name.clone(),
+                    abbr: x.abbr.clone(),
+                },
+                (Some(x), None) => NameChange::Removed {
+                    name: x.name.clone(),
+                    abbr: x.abbr.clone(),
+                },
+                (Some(a), Some(b)) if a.name != b.name || a.abbr != b.abbr => NameChange::Renamed {
+                    old_name: a.name.clone(),
+                    old_abbr: a.abbr.clone(),
+                    name: b.name.clone(),
+                    abbr: b.abbr.clone(),
+                },
+                _ => return None,
+            };
+            return Some(NameDiff {
+                level,
+                color_id: id,
+                change,
+            });
+        })
+        .collect();
+}
+
+/// The boundaries of both lists, in order, with their numeric values.
+fn merge_amounts(a: &[String], b: &[String]) -> Vec<(String, f32)> {
+    let mut amounts = a
+        .iter()
+        .chain(b.iter())
+        .map(|x| (x.clone(), x.parse::<f32>().unwrap_or(f32::INFINITY)))
+        .collect::<Vec<(String, f32)>>();
+    amounts.sort_by(|x, y| x.1.total_cmp(&y.1));
+    amounts.dedup_by(|x, y| x.1 == y.1);
+    return amounts;
+}
+
+/// The point halfway between two boundaries, or a step past the last finite
+/// one.
+fn midpoint(begin: f32, end: f32) -> f32 {
+    if end.is_infinite() {
+        return begin + 1.0;
+    }
+    return (begin + end) / 2.0;
+}
+
+impl Dataset {
+    /// Compare this dataset with a newer version of it: which names were
+    /// added, removed or renamed at every level, and which cells of the
+    /// color solid belong to another color.
+    pub fn diff(&self, new: &Dataset) -> DatasetDiff {
+        let names = [
+            diff_names(1, self.level1_names(), new.level1_names()),
+            diff_names(2, self.level2_names(), new.level2_names()),
+            diff_names(3, self.names(), new.names()),
+        ]
+        .concat();
+
+        let mut hues = self
+            .hues()
+            .iter()
+            .chain(new.hues().iter())
+            .map(|x| (x.clone(), x.parse::<MunsellHue>().unwrap().raw()))
+            .collect::<Vec<(String, f32)>>();
+        hues.sort_by(|x, y| x.1.total_cmp(&y.1));
+        hues.dedup_by(|x, y| x.1 == y.1);
+        let chromas = merge_amounts(self.chromas(), new.chromas());
+        let values = merge_amounts(self.values(), new.values());
+
+        let mut cells = Vec::new();
+        for (h, (hue_begin, hue_point)) in hues.iter().enumerate() {
+            // the last cell wraps around the hue circle to the first boundary
+            let (hue_end, hue_end_point) = &hues[(h + 1) % hues.len()];
+            let hue = MunsellHue::new(*hue_point).midpoint(MunsellHue::new(*hue_end_point));
+            for c in chromas.windows(2) {
+                for v in values.windows(2) {
+                    let color =
+                        MunsellColor::new(hue, midpoint(v[0].1, v[1].1), midpoint(c[0].1, c[1].1));
+                    let (old, new) = (self.lookup_id(&color), new.lookup_id(&color));
+                    if old != new {
+                        cells.push(CellDiff {
+                            hues: (hue_begin.clone(), hue_end.clone()),
+                            chromas: (c[0].0.clone(), c[1].0.clone()),
+                            values: (v[0].0.clone(), v[1].0.clone()),
+                            old,
+                            new,
+                        });
+                    }
+                }
+            }
+        }
+
+        return DatasetDiff { names, cells };
+    }
+}
+
+/// The old and the new color of a changed cell.
+pub type ColorChange = (Option<u32>, Option<u32>);
+
+/// Group the changed cells by the colors they changed between, in the order
+/// the first cell of each group appears.
+pub fn group_cells(cells: &[CellDiff]) -> Vec<(ColorChange, Vec<&CellDiff>)> {
+    let mut order = Vec::new();
+    let mut groups: BTreeMap<ColorChange, Vec<&CellDiff>> = BTreeMap::new();
+    for cell in cells {
+        let key = (cell.old, cell.new);
+        if !groups.contains_key(&key) {
+            order.push(key);
+        }
+        groups.entry(key).or_default().push(cell);
+    }
+    return order
+        .into_iter()
+        .map(|key| (key, groups.remove(&key).unwrap()))
+        .collect();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dataset::BUNDLED_XML;
+    use crate::diff::{group_cells, NameChange};
+    use crate::Dataset;
+
+    #[test]
+    fn dataset_diff() {
+        let old = Dataset::bundled();
+        assert!(old.diff(&old).is_empty());
+
+        let text = BUNDLED_XML
+            .replacen(
+                "name=\"Vivid pink\" abbr=\"viv.Pk\"",
+                "name=\"Vivid pink\" abbr=\"v.Pk\"",
+                1,
+            )
+            .replacen(
+                "<range color=\"9\" chroma-begin=\"0.5\" chroma-end=\"1.5\" value-begin=\"8.5\"",
+                "<range color=\"9\" chroma-begin=\"0.5\" chroma-end=\"1.5\" value-begin=\"8.25\"",
+                1,
+            )
+            .replacen(
+                "value-begin=\"6.5\" value-end=\"8.5\" />",
+                "value-begin=\"6.5\" value-end=\"8.25\" />",
+                1,
+            )
+            .replacen(
+                "<amount>8.5</amount>",
+                "<amount>8.25</amount>\n\t\t<amount>8.5</amount>",
+                1,
+            );
+        let new = Dataset::parse(&text).unwrap();
+        let diff = old.diff(&new);
+
+        assert_eq!(diff.names.len(), 1);
+        assert_eq!((diff.names[0].level, diff.names[0].color_id), (3, 1));
+        assert!(matches!(
+            &diff.names[0].change,
+            NameChange::Renamed { old_abbr, abbr, .. } if old_abbr == "viv.Pk" && abbr == "v.Pk"
+        ));
+
+        // pinkish white grew into the cells of pinkish gray from 1R to 4R
+        let groups = group_cells(&diff.cells);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].0, (Some(10), Some(9)));
+        for cell in groups[0].1.iter() {
+            assert_eq!(cell.hues, ("1R".to_string(), "4R".to_string()));
+            assert_eq!(cell.values, ("8.25".to_string(), "8.5".to_string()));
+        }
+    }
+}
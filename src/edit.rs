@@ -0,0 +1,366 @@
+//! Editing a dataset in code rather than in its XML: renaming colors, and
+//! splitting and merging blocks. Every edit is checked like a document
+//! would be, and only made if the result has no errors; [`to_xml`] then
+//! writes the edited dataset back out.
+//!
+//! Blocks are addressed by their index in [`Dataset::blocks`], which any
+//! edit may change.
+//!
+//! The lines and columns of the diagnostics of an edit are those of the
+//! document [`to_xml`] writes for the edited dataset.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::compare::FaceAxis;
+use crate::dataset::{ColorBlock, Dataset};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::export::xml::to_xml;
+use crate::leaf::arc_contains;
+use crate::munsell::MunsellHue;
+
+/// Why an edit was not made.
+#[derive(Debug)]
+pub enum EditError {
+    NoSuchBlock(usize),
+    NoSuchName {
+        level: u32,
+        color_id: u32,
+    },
+    /// The boundary to split at is not a boundary of that axis, or not
+    /// inside the block.
+    InvalidSplit(String),
+    /// The blocks are of different colors, or do not share a whole face.
+    NotMergeable(usize, usize),
+    /// The edited dataset has errors, listed with its warnings.
+    Invalid(Vec<Diagnostic>),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            EditError::NoSuchBlock(i) => write!(f, "there is no block {}", i),
+            EditError::NoSuchName { level, color_id } => {
+                write!(f, "there is no level {} color {}", level, color_id)
+            }
+            EditError::InvalidSplit(at) => write!(f, "cannot split the block at '{}'", at),
+            EditError::NotMergeable(a, b) => write!(
+                f,
+                "blocks {} and {} are not of one color sharing a whole face",
+                a, b
+            ),
+            EditError::Invalid(diagnostics) => {
+                let errors = diagnostics
+                    .iter()
+                    .filter(|x| x.severity == Severity::Error)
+                    .collect::<Vec<_>>();
+                write!(f, "the edit would leave {} error(s)", errors.len())?;
+                if let Some(first) = errors.first() {
+                    write!(f, ", the first: {}", first)?;
+                }
+                Ok(())
+            }
+        };
+    }
+}
+
+impl std::error::Error for EditError {}
+
+/// The boundaries of a dataset and its blocks, taken apart to edit.
+struct Parts {
+    hues: Vec<String>,
+    chromas: Vec<String>,
+    values: Vec<String>,
+    blocks: Vec<ColorBlock>,
+}
+
+fn range_mut(block: &mut ColorBlock, axis: FaceAxis) -> &mut Range<usize> {
+    return match axis {
+        FaceAxis::Hue => &mut block.hues,
+        FaceAxis::Chroma => &mut block.chromas,
+        FaceAxis::Value => &mut block.values,
+    };
+}
+
+fn range(block: &ColorBlock, axis: FaceAxis) -> &Range<usize> {
+    return match axis {
+        FaceAxis::Hue => &block.hues,
+        FaceAxis::Chroma => &block.chromas,
+        FaceAxis::Value => &block.values,
+    };
+}
+
+impl Parts {
+    fn boundaries(&mut self, axis: FaceAxis) -> &mut Vec<String> {
+        return match axis {
+            FaceAxis::Hue => &mut self.hues,
+            FaceAxis::Chroma => &mut self.chromas,
+            FaceAxis::Value => &mut self.values,
+        };
+    }
+
+    /// The index of the boundary `at` of an axis, adding it where it goes
+    /// if it is a new one.
+    fn find_or_insert(&mut self, axis: FaceAxis, at: &str) -> Option<usize> {
+        let point = |x: &str| match axis {
+            FaceAxis::Hue => x.parse::<MunsellHue>().ok().map(|x| x.raw()),
+            _ => x.parse::<f32>().ok(),
+        };
+        let new = point(at).filter(|x| x.is_finite() && *x >= 0.0)?;
+        let points = self
+            .boundaries(axis)
+            .iter()
+            .map(|x| point(x).unwrap())
+            .collect::<Vec<f32>>();
+        if let Some(i) = points.iter().position(|x| *x == new) {
+            return Some(i);
+        }
+
+        let index = match axis {
+            // the hues go around the circle once, starting anywhere
+            FaceAxis::Hue => {
+                let n = points.len();
+                (0..n).find(|i| arc_contains(points[*i], points[(i + 1) % n], new))? + 1
+            }
+            _ => points.iter().position(|x| *x > new).unwrap_or(points.len()),
+        };
+        self.boundaries(axis).insert(index, at.to_string());
+        for block in self.blocks.iter_mut() {
+            let range = range_mut(block, axis);
+            if range.start >= index {
+                range.start += 1;
+            }
+            if range.end >= index {
+                range.end += 1;
+            }
+        }
+        return Some(index);
+    }
+}
+
+impl Dataset {
+    fn parts(&self) -> Parts {
+        return Parts {
+            hues: self.hues().to_vec(),
+            chromas: self.chromas().to_vec(),
+            values: self.values().to_vec(),
+            blocks: self.blocks().to_vec(),
+        };
+    }
+
+    /// Check a dataset with the edited parts as if it were read from a
+    /// document, and become it if there are no errors.
+    fn rebuild(&mut self, dataset: Dataset) -> Result<Vec<Diagnostic>, EditError> {
+        let text = to_xml(&dataset);
+        return match Dataset::check_and_build(&text) {
+            Ok((Some(dataset), diagnostics)) => {
+                *self = dataset;
+                Ok(diagnostics)
+            }
+            Ok((None, diagnostics)) => Err(EditError::Invalid(diagnostics)),
+            Err(e) => panic!("the XML written for a dataset should be well-formed: {}", e),
+        };
+    }
+
+    fn rebuild_with(&mut self, parts: Parts) -> Result<Vec<Diagnostic>, EditError> {
+        let dataset = Dataset::from_parts(
+            self.metadata().clone(),
+            (
+                self.level1_names.clone(),
+                self.level2_names.clone(),
+                self.names.clone(),
+            ),
+            parts.hues,
+            parts.chromas,
+            parts.values,
+            parts.blocks,
+        );
+        return self.rebuild(dataset);
+    }
+
+    /// Change the name and abbreviation of a color of level 1, 2 or 3,
+    /// returning the warnings about the edited dataset.
+    pub fn rename(
+        &mut self,
+        level: u32,
+        color_id: u32,
+        name: &str,
+        abbr: &str,
+    ) -> Result<Vec<Diagnostic>, EditError> {
+        let mut names = (
+            self.level1_names.clone(),
+            self.level2_names.clone(),
+            self.names.clone(),
+        );
+        let map = match level {
+            1 => &mut names.0,
+            2 => &mut names.1,
+            _ => &mut names.2,
+        };
+        let color = match map.get_mut(&color_id) {
+            Some(v) if (1..=3).contains(&level) => v,
+            _ => return Err(EditError::NoSuchName { level, color_id }),
+        };
+        color.name = name.to_string();
+        color.abbr = abbr.to_string();
+
+        let dataset = Dataset::from_parts(
+            self.metadata().clone(),
+            names,
+            self.hues().to_vec(),
+            self.chromas().to_vec(),
+            self.values().to_vec(),
+            self.blocks().to_vec(),
+        );
+        return self.rebuild(dataset);
+    }
+
+    /// Split a block in two of the same color at a boundary of `axis`, like
+    /// `7.0` for chroma, adding the boundary if it is a new one. The lower
+    /// part keeps the index of the block, and the upper part is added last.
+    pub fn split_block(
+        &mut self,
+        block: usize,
+        axis: FaceAxis,
+        at: &str,
+    ) -> Result<Vec<Diagnostic>, EditError> {
+        if block >= self.blocks().len() {
+            return Err(EditError::NoSuchBlock(block));
+        }
+        let mut parts = self.parts();
+        let index = parts
+            .find_or_insert(axis, at)
+            .ok_or_else(|| EditError::InvalidSplit(at.to_string()))?;
+
+        let split = range(&parts.blocks[block], axis).clone();
+        let inside = match axis {
+            FaceAxis::Hue => {
+                let n = parts.hues.len();
+                let offset = (index + n - split.start) % n;
+                offset > 0 && offset < (split.end + n - split.start) % n
+            }
+            _ => split.start < index && index < split.end,
+        };
+        if !inside {
+            return Err(EditError::InvalidSplit(at.to_string()));
+        }
+
+        let mut upper = parts.blocks[block].clone();
+        range_mut(&mut parts.blocks[block], axis).end = index;
+        range_mut(&mut upper, axis).start = index;
+        parts.blocks.push(upper);
+        return self.rebuild_with(parts);
+    }
+
+    /// Merge two blocks of the same color that share a whole face into one,
+    /// which takes the index of the lower of the two.
+    pub fn merge_blocks(&mut self, a: usize, b: usize) -> Result<Vec<Diagnostic>, EditError> {
+        let count = self.blocks().len();
+        if let Some(i) = [a, b].into_iter().find(|x| *x >= count) {
+            return Err(EditError::NoSuchBlock(i));
+        }
+        let (first, second) = (&self.blocks()[a], &self.blocks()[b]);
+        if a == b || first.color_id != second.color_id {
+            return Err(EditError::NotMergeable(a, b));
+        }
+
+        let axes = [FaceAxis::Hue, FaceAxis::Chroma, FaceAxis::Value];
+        let differ = axes
+            .into_iter()
+            .filter(|x| range(first, *x) != range(second, *x))
+            .collect::<Vec<FaceAxis>>();
+        let merged = match differ[..] {
+            [axis] => {
+                let (x, y) = (range(first, axis), range(second, axis));
+                if x.end == y.start {
+                    Some((axis, x.start..y.end))
+                } else if y.end == x.start {
+                    Some((axis, y.start..x.end))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        let (axis, merged) = merged.ok_or(EditError::NotMergeable(a, b))?;
+
+        let mut parts = self.parts();
+        let (low, high) = (a.min(b), a.max(b));
+        *range_mut(&mut parts.blocks[low], axis) = merged;
+        parts.blocks.remove(high);
+        return self.rebuild_with(parts);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::compare::FaceAxis;
+    use crate::edit::EditError;
+    use crate::Dataset;
+
+    #[test]
+    fn edits() {
+        let mut dataset = Dataset::bundled();
+        let original = Dataset::bundled();
+
+        dataset.rename(3, 1, "Brilliant pink", "brill.Pk").unwrap();
+        assert_eq!(dataset.names()[&1].name, "Brilliant pink");
+        assert_eq!(original.diff(&dataset).names.len(), 1);
+        assert!(matches!(
+            dataset.rename(3, 999, "Vivid pink", "viv.Pk"),
+            Err(EditError::NoSuchName { .. })
+        ));
+        // a name out of the grammar is an error, and changes nothing
+        assert!(matches!(
+            dataset.rename(3, 1, "Vivid rose", "viv.Ro"),
+            Err(EditError::Invalid(_))
+        ));
+        assert_eq!(dataset.names()[&1].name, "Brilliant pink");
+
+        // splitting at a new boundary and merging back changes no cell
+        let block = dataset
+            .blocks()
+            .iter()
+            .position(|x| x.color_id == 1)
+            .unwrap();
+        let count = dataset.blocks().len();
+        dataset
+            .split_block(block, FaceAxis::Chroma, "12.0")
+            .unwrap();
+        assert_eq!(dataset.blocks().len(), count + 1);
+        assert!(dataset.chromas().contains(&"12.0".to_string()));
+        assert!(original.diff(&dataset).cells.is_empty());
+        let parts = dataset
+            .blocks()
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| x.color_id == 1)
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>();
+        assert!(matches!(
+            dataset.split_block(parts[0], FaceAxis::Value, "2.0"),
+            Err(EditError::InvalidSplit(_))
+        ));
+        dataset.merge_blocks(parts[0], parts[1]).unwrap();
+        assert_eq!(dataset.blocks().len(), count);
+        assert!(original.diff(&dataset).cells.is_empty());
+
+        // blocks of different colors do not merge
+        let other = dataset
+            .blocks()
+            .iter()
+            .position(|x| x.color_id == 2)
+            .unwrap();
+        assert!(matches!(
+            dataset.merge_blocks(parts[0], other),
+            Err(EditError::NotMergeable(..))
+        ));
+
+        // splitting across a new hue
+        let count = dataset.blocks().len();
+        dataset.split_block(0, FaceAxis::Hue, "2R").unwrap();
+        assert_eq!(dataset.hues().len(), original.hues().len() + 1);
+        assert_eq!(dataset.blocks().len(), count + 1);
+        assert!(original.diff(&dataset).cells.is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+//! Adobe swatch files: Adobe Color (`.aco`) for Photoshop, and Adobe Swatch
+//! Exchange (`.ase`) for Illustrator and InDesign.
+
+use palette::Srgb;
+
+use crate::export::{level_names, ordered_names, swatch_label, SwatchOrder};
+use crate::naming::ColorNamingSystem;
+
+/// The name as UTF-16 code units, with the terminating null both formats
+/// expect.
+fn utf16_with_null(text: &str) -> Vec<u16> {
+    let mut units = text.encode_utf16().collect::<Vec<u16>>();
+    units.push(0);
+    return units;
+}
+
+fn push_utf16(out: &mut Vec<u8>, units: &[u16]) {
+    for unit in units {
+        out.extend_from_slice(&unit.to_be_bytes());
+    }
+}
+
+/// Build an Adobe Color file of the names of level 1, 2 or 3, with `colors`
+/// their representative colors, indexed by `color_id - 1`.
+///
+/// The file has both sections of the format: version 1 with only the
+/// colors, for old readers, then version 2 with the colors and their names.
+pub fn to_aco<S: ColorNamingSystem + ?Sized>(
+    dataset: &S,
+    level: u32,
+    colors: &[Srgb],
+    order: SwatchOrder,
+) -> Vec<u8> {
+    let names = ordered_names(level_names(dataset, level), colors, order);
+
+    let mut out = Vec::new();
+    for version in [1u16, 2] {
+        out.extend_from_slice(&version.to_be_bytes());
+        out.extend_from_slice(&(names.len() as u16).to_be_bytes());
+        for (id, name) in names.iter() {
+            let c: Srgb<u8> = colors[(id - 1) as usize].into_format();
+            // color space 0 is RGB, with each channel scaled to 16 bits
+            out.extend_from_slice(&0u16.to_be_bytes());
+            for channel in [c.red, c.green, c.blue] {
+                out.extend_from_slice(&(channel as u16 * 257).to_be_bytes());
+            }
+            out.extend_from_slice(&0u16.to_be_bytes());
+
+            if version == 2 {
+                let label = utf16_with_null(&swatch_label(name));
+                out.extend_from_slice(&(label.len() as u32).to_be_bytes());
+                push_utf16(&mut out, &label);
+            }
+        }
+    }
+    return out;
+}
+
+/// Append an Adobe Swatch Exchange block of `kind` with a name and `body`.
+fn push_ase_block(out: &mut Vec<u8>, kind: u16, name: &str, body: &[u8]) {
+    let name = utf16_with_null(name);
+    out.extend_from_slice(&kind.to_be_bytes());
+    out.extend_from_slice(&((2 + 2 * name.len() + body.len()) as u32).to_be_bytes());
+    out.extend_from_slice(&(name.len() as u16).to_be_bytes());
+    push_utf16(out, &name);
+    out.extend_from_slice(body);
+}
+
+/// Build an Adobe Swatch Exchange file of the names of level 1, 2 or 3, with
+/// `colors` their representative colors, indexed by `color_id - 1`, in a
+/// group named after the level.
+pub fn to_ase<S: ColorNamingSystem + ?Sized>(
+    dataset: &S,
+    level: u32,
+    colors: &[Srgb],
+    order: SwatchOrder,
+) -> Vec<u8> {
+    const GROUP_START: u16 = 0xc001;
+    const GROUP_END: u16 = 0xc002;
+    const COLOR: u16 = 0x0001;
+    // a global color, as opposed to a spot or process color
+    const GLOBAL: u16 = 0;
+
+    let names = ordered_names(level_names(dataset, level), colors, order);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"ASEF");
+    out.extend_from_slice(&1u16.to_be_bytes());
+    out.extend_from_slice(&0u16.to_be_bytes());
+    out.extend_from_slice(&((names.len() + 2) as u32).to_be_bytes());
+
+    push_ase_block(
+        &mut out,
+        GROUP_START,
+        &format!("ISCC-NBS level {}", level),
+        &[],
+    );
+    for (id, name) in names.iter() {
+        let c = colors[(id - 1) as usize];
+        let mut body = Vec::new();
+        body.extend_from_slice(b"RGB ");
+        for channel in [c.red, c.green, c.blue] {
+            body.extend_from_slice(&channel.to_be_bytes());
+        }
+        body.extend_from_slice(&GLOBAL.to_be_bytes());
+        push_ase_block(&mut out, COLOR, &swatch_label(name), &body);
+    }
+    out.extend_from_slice(&GROUP_END.to_be_bytes());
+    out.extend_from_slice(&0u32.to_be_bytes());
+
+    return out;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::export::adobe::{to_aco, to_ase};
+    use crate::export::SwatchOrder;
+    use crate::Dataset;
+
+    fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+        return u16::from_be_bytes([bytes[offset], bytes[offset + 1]]);
+    }
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        return u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap());
+    }
+
+    #[test]
+    fn aco_files() {
+        let dataset = Dataset::bundled();
+        let aco = to_aco(&dataset, 3, &get_mean_colors(&dataset), SwatchOrder::Id);
+
+        // version 1: 267 colors of 10 bytes
+        assert_eq!(u16_at(&aco, 0), 1);
+        assert_eq!(u16_at(&aco, 2), 267);
+        // "Vivid pink" is #ffc2c5
+        assert_eq!(u16_at(&aco, 4), 0);
+        assert_eq!(u16_at(&aco, 6), 0xffff);
+        assert_eq!(u16_at(&aco, 8), 0xc2c2);
+
+        // version 2, with "Vivid pink (viv.Pk)" and a null
+        let v2 = 4 + 267 * 10;
+        assert_eq!(u16_at(&aco, v2), 2);
+        assert_eq!(u16_at(&aco, v2 + 2), 267);
+        assert_eq!(u32_at(&aco, v2 + 14), 20);
+        let name = (0..19)
+            .map(|i| u16_at(&aco, v2 + 18 + 2 * i))
+            .collect::<Vec<u16>>();
+        assert_eq!(String::from_utf16(&name).unwrap(), "Vivid pink (viv.Pk)");
+        assert_eq!(u16_at(&aco, v2 + 18 + 38), 0);
+    }
+
+    #[test]
+    fn ase_files() {
+        let dataset = Dataset::bundled();
+        let ase = to_ase(&dataset, 3, &get_mean_colors(&dataset), SwatchOrder::Id);
+
+        assert_eq!(&ase[0..4], b"ASEF");
+        assert_eq!(u32_at(&ase, 8), 267 + 2);
+
+        // walk the blocks: a group start, the colors, and a group end
+        let mut offset = 12;
+        let mut kinds = Vec::new();
+        while offset < ase.len() {
+            kinds.push(u16_at(&ase, offset));
+            offset += 6 + u32_at(&ase, offset + 2) as usize;
+        }
+        assert_eq!(offset, ase.len());
+        assert_eq!(kinds.len(), 267 + 2);
+        assert_eq!(kinds[0], 0xc001);
+        assert!(kinds[1..268].iter().all(|x| *x == 1));
+        assert_eq!(kinds[268], 0xc002);
+    }
+}
@@ -0,0 +1,166 @@
+//! Every file derived from the dataset, written in one run into a single
+//! directory for publishing a release, with a manifest of their hashes.
+//!
+//! The files only depend on the dataset, so two runs on the same dataset
+//! write the same bytes, and the manifest tells which files changed between
+//! releases.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use palette::Srgb;
+use ttf_word_wrap::TTFParserMeasure;
+
+use crate::colors::{get_level1_mean_colors, get_level2_mean_colors};
+use crate::dataset::Dataset;
+use crate::export::{adobe, css, csv, gltf, gpl, json, obj, rust, SwatchOrder};
+use crate::plot::html::charts_to_html;
+use crate::plot::incremental::fnv1a;
+use crate::plot::load_font_data;
+use crate::plot::svg::{plate_to_svg, wheel_to_svg};
+use crate::progress::no_progress;
+
+/// The name of the manifest, in the bundle's directory.
+pub const MANIFEST: &str = "MANIFEST";
+
+fn add(files: &mut BTreeMap<String, Vec<u8>>, path: &str, contents: impl Into<Vec<u8>>) {
+    files.insert(path.to_string(), contents.into());
+}
+
+/// Build every file of the bundle, by path relative to the bundle's
+/// directory.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn bundle_files(dataset: &Dataset, colors: &[Srgb]) -> BTreeMap<String, Vec<u8>> {
+    let mut files = BTreeMap::new();
+
+    let json = json::to_json(dataset, colors);
+    add(
+        &mut files,
+        "iscc-nbs.json",
+        serde_json::to_string_pretty(&json).unwrap() + "\n",
+    );
+    add(&mut files, "iscc-nbs.csv", csv::to_csv(dataset, colors));
+    add(&mut files, "iscc-nbs.rs", rust::to_rust(dataset, colors));
+    add(&mut files, "iscc-nbs.css", css::to_css(dataset, colors));
+    add(&mut files, "_iscc-nbs.scss", css::to_scss(dataset, colors));
+
+    let level_colors = [
+        get_level1_mean_colors(dataset),
+        get_level2_mean_colors(dataset),
+        colors.to_vec(),
+    ];
+    for (i, colors) in level_colors.iter().enumerate() {
+        let level = (i + 1) as u32;
+        let path = format!("palettes/iscc-nbs-level{}", level);
+        add(
+            &mut files,
+            &format!("{}.gpl", path),
+            gpl::to_gpl(dataset, level, colors, SwatchOrder::Id),
+        );
+        add(
+            &mut files,
+            &format!("{}.aco", path),
+            adobe::to_aco(dataset, level, colors, SwatchOrder::Id),
+        );
+        add(
+            &mut files,
+            &format!("{}.ase", path),
+            adobe::to_ase(dataset, level, colors, SwatchOrder::Id),
+        );
+    }
+
+    let solid = dataset.color_solid(colors);
+    add(
+        &mut files,
+        "solid/iscc-nbs.obj",
+        obj::to_obj(dataset, &solid),
+    );
+    add(
+        &mut files,
+        "solid/iscc-nbs.gltf",
+        serde_json::to_string(&gltf::to_gltf(dataset, &solid)).unwrap() + "\n",
+    );
+
+    let font_data = load_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+    for h in 0..dataset.hues().len() {
+        let plate = dataset.plate(h).unwrap();
+        add(
+            &mut files,
+            &format!(
+                "charts/page{}-{}_hues_{}-{}.svg",
+                plate.page, plate.side, plate.hue_begin, plate.hue_end
+            ),
+            plate_to_svg(dataset, &plate, colors, &measure),
+        );
+    }
+    add(
+        &mut files,
+        "charts/hue_wheel.svg",
+        wheel_to_svg(dataset, colors),
+    );
+    add(
+        &mut files,
+        "index.html",
+        charts_to_html(dataset, colors, &mut no_progress),
+    );
+
+    return files;
+}
+
+/// List the FNV-1a hash and path of every file, one per line, after a line
+/// with the version of the dataset.
+pub fn manifest(dataset: &Dataset, files: &BTreeMap<String, Vec<u8>>) -> String {
+    let mut text = format!("# iscc-nbs {}\n", dataset.metadata().version);
+    for (path, contents) in files.iter() {
+        writeln!(text, "{:016x}  {}", fnv1a(contents), path).unwrap();
+    }
+    return text;
+}
+
+/// Write every file of the bundle, and its manifest, into `out`.
+pub fn write_bundle(dataset: &Dataset, colors: &[Srgb], out: &Path) -> std::io::Result<()> {
+    let files = bundle_files(dataset, colors);
+    for (path, contents) in files.iter() {
+        let path = out.join(path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, contents)?;
+    }
+    std::fs::write(out.join(MANIFEST), manifest(dataset, &files))?;
+    return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::export::bundle::{bundle_files, manifest};
+    use crate::Dataset;
+
+    #[test]
+    fn deterministic_bundles() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let files = bundle_files(&dataset, &colors);
+
+        assert!(files.contains_key("iscc-nbs.json"));
+        assert!(files.contains_key("index.html"));
+        assert_eq!(
+            files
+                .keys()
+                .filter(|x| x.starts_with("charts/page"))
+                .count(),
+            dataset.hues().len()
+        );
+
+        let text = manifest(&dataset, &files);
+        assert_eq!(text.lines().count(), files.len() + 1);
+        assert!(text.lines().any(|x| x.ends_with("  iscc-nbs.csv")));
+
+        // the same dataset gives the same files
+        assert_eq!(manifest(&dataset, &bundle_files(&dataset, &colors)), text);
+    }
+}
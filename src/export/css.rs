@@ -0,0 +1,86 @@
+//! Stylesheets with a variable for the representative color of every level 3
+//! name, named after it, like `--iscc-vivid-pink`.
+
+use palette::Srgb;
+
+use crate::dataset::slugify;
+use crate::export::sorted_names;
+use crate::naming::ColorNamingSystem;
+use crate::rgbspace::rgb_space;
+
+/// The name of the variable of a color, without the `--` or `$` of CSS or
+/// SCSS.
+fn variable_name(name: &str) -> String {
+    return format!("iscc-{}", slugify(name));
+}
+
+/// Build a stylesheet declaring a CSS custom property on `:root` for every
+/// level 3 name, with `colors` their representative colors, indexed by
+/// `color_id - 1`, in [`rgb_space`].
+pub fn to_css<S: ColorNamingSystem + ?Sized>(dataset: &S, colors: &[Srgb]) -> String {
+    let mut out = format!(
+        "/* ISCC-NBS level 3 colors, iscc-nbs {} */\n:root {{\n",
+        dataset.metadata().version
+    );
+    for (id, name) in sorted_names(dataset.finest_names()) {
+        out.push_str(&format!(
+            "  --{}: {};\n",
+            variable_name(&name.name),
+            rgb_space().css_color(colors[(id - 1) as usize])
+        ));
+    }
+    out.push_str("}\n");
+    return out;
+}
+
+/// Like [`to_css`], as SCSS variables, followed by a map of all of them by
+/// name for iterating over.
+pub fn to_scss<S: ColorNamingSystem + ?Sized>(dataset: &S, colors: &[Srgb]) -> String {
+    let names = sorted_names(dataset.finest_names());
+
+    let mut out = format!(
+        "// ISCC-NBS level 3 colors, iscc-nbs {}\n",
+        dataset.metadata().version
+    );
+    for (id, name) in names.iter() {
+        out.push_str(&format!(
+            "${}: {};\n",
+            variable_name(&name.name),
+            rgb_space().css_color(colors[(id - 1) as usize])
+        ));
+    }
+
+    out.push_str("\n$iscc-colors: (\n");
+    for (_, name) in names.iter() {
+        out.push_str(&format!(
+            "  \"{}\": ${},\n",
+            slugify(&name.name),
+            variable_name(&name.name)
+        ));
+    }
+    out.push_str(");\n");
+    return out;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::export::css::{to_css, to_scss};
+    use crate::Dataset;
+
+    #[test]
+    fn stylesheets() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+
+        let css = to_css(&dataset, &colors);
+        assert!(css.contains(":root {\n  --iscc-vivid-pink: #ffc2c5;\n"));
+        assert!(css.contains("  --iscc-vivid-purplish-blue: #"));
+        assert_eq!(css.matches("--iscc-").count(), 267);
+        assert!(css.ends_with("}\n"));
+
+        let scss = to_scss(&dataset, &colors);
+        assert!(scss.contains("\n$iscc-vivid-pink: #ffc2c5;\n"));
+        assert!(scss.contains("  \"black\": $iscc-black,\n);\n"));
+    }
+}
@@ -0,0 +1,75 @@
+use palette::Srgb;
+
+use crate::dataset::Dataset;
+use crate::export::{ordered_names, srgb_hex, SwatchOrder};
+
+/// Quote a CSV field if it contains a separator, quote or line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        return format!("\"{}\"", text.replace('"', "\"\""));
+    }
+    return text.to_string();
+}
+
+/// Build a CSV table with one row per level 3 color: its id, name and
+/// abbreviation, the ids of the level 2 and level 1 names containing it,
+/// and its representative sRGB color (`colors`, indexed by
+/// `color_id - 1`) as a hex string.
+pub fn to_csv(dataset: &Dataset, colors: &[Srgb]) -> String {
+    return to_csv_in_order(dataset, colors, SwatchOrder::Id);
+}
+
+/// Like [`to_csv`], with the rows in the given order.
+pub fn to_csv_in_order(dataset: &Dataset, colors: &[Srgb], order: SwatchOrder) -> String {
+    let mut out = String::from("id,name,abbr,level2,level1,srgb\n");
+
+    for (id, name) in ordered_names(dataset.names(), colors, order) {
+        let level2 = name.parent.unwrap();
+        let level1 = dataset.level2_names()[&level2].parent.unwrap();
+
+        out.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            id,
+            csv_field(&name.name),
+            csv_field(&name.abbr),
+            level2,
+            level1,
+            srgb_hex(colors[(id - 1) as usize])
+        ));
+    }
+
+    return out;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::export::csv::{csv_field, to_csv, to_csv_in_order};
+    use crate::export::SwatchOrder;
+    use crate::Dataset;
+
+    #[test]
+    fn quoting() {
+        assert_eq!(csv_field("Vivid pink"), "Vivid pink");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn csv_rows() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let csv = to_csv(&dataset, &colors);
+        let lines = csv.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines.len(), 268);
+        assert_eq!(lines[0], "id,name,abbr,level2,level1,srgb");
+        assert!(lines[1].starts_with("1,Vivid pink,viv.Pk,1,1,#"));
+        assert!(lines[267].starts_with("267,Black,Bk,"));
+
+        let csv = to_csv_in_order(&dataset, &colors, SwatchOrder::Perceptual);
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 268);
+        assert!(lines[1].starts_with("114,Olive black,OlBk,"));
+    }
+}
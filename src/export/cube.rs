@@ -0,0 +1,107 @@
+//! 3D lookup tables (`.cube`) mapping every sRGB color to the
+//! representative color of the category it falls into: a "posterize to
+//! ISCC-NBS" filter for DaVinci Resolve, OBS and photo editors.
+
+use std::fmt::Write as _;
+
+use palette::Srgb;
+
+use crate::classify::srgb_to_lab;
+use crate::dataset::Dataset;
+use crate::progress::{no_progress, report, Progress, Stage};
+
+/// How many samples along each axis a LUT has by default, as is usual for
+/// `.cube` files.
+pub const DEFAULT_LUT_SIZE: u32 = 33;
+
+/// The id of the name of level 1, 2 or 3 containing a level 3 color.
+fn level_id(dataset: &Dataset, color_id: u32, level: u32) -> u32 {
+    let level2 = dataset.names()[&color_id].parent.unwrap();
+    return match level {
+        1 => dataset.level2_names()[&level2].parent.unwrap(),
+        2 => level2,
+        _ => color_id,
+    };
+}
+
+/// Build a `.cube` LUT of `size`×`size`×`size` samples of the sRGB cube,
+/// each mapped to the representative color (`colors`, indexed by id - 1) of
+/// the name of level 1, 2 or 3 it falls into.
+///
+/// Samples that fall into no block, right on the edge of the solid, take the
+/// color whose centroid is nearest instead.
+pub fn to_cube(dataset: &Dataset, level: u32, colors: &[Srgb], size: u32) -> String {
+    return to_cube_with_progress(dataset, level, colors, size, &mut no_progress);
+}
+
+/// Like [`to_cube`], reporting progress through the classify stage, one
+/// unit per blue channel step.
+pub fn to_cube_with_progress(
+    dataset: &Dataset,
+    level: u32,
+    colors: &[Srgb],
+    size: u32,
+    progress: Progress,
+) -> String {
+    let mut out = String::new();
+    writeln!(out, "TITLE \"ISCC-NBS level {}\"", level).unwrap();
+    writeln!(out, "# iscc-nbs {}", dataset.metadata().version).unwrap();
+    writeln!(out, "LUT_3D_SIZE {}", size).unwrap();
+    writeln!(out, "DOMAIN_MIN 0.0 0.0 0.0").unwrap();
+    writeln!(out, "DOMAIN_MAX 1.0 1.0 1.0").unwrap();
+
+    let step = |i: u32| (i as f32) / ((size - 1) as f32);
+
+    // red changes fastest, then green, then blue
+    report(progress, Stage::Classify, 0, size as usize);
+    for b in 0..size {
+        for g in 0..size {
+            for r in 0..size {
+                let sample = Srgb::new(step(r), step(g), step(b));
+                let color_id = match dataset.classify_srgb(sample) {
+                    Some(v) => v,
+                    None => dataset.nearest_centroid(srgb_to_lab(sample)).color_id,
+                };
+                let c = colors[(level_id(dataset, color_id, level) - 1) as usize];
+                writeln!(out, "{:.6} {:.6} {:.6}", c.red, c.green, c.blue).unwrap();
+            }
+        }
+        report(progress, Stage::Classify, (b + 1) as usize, size as usize);
+    }
+    return out;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::{get_level1_mean_colors, get_mean_colors};
+    use crate::export::cube::to_cube;
+    use crate::Dataset;
+
+    #[test]
+    fn lookup_table() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let cube = to_cube(&dataset, 3, &colors, 3);
+        let lines = cube.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], "TITLE \"ISCC-NBS level 3\"");
+        assert_eq!(lines[2], "LUT_3D_SIZE 3");
+        assert_eq!(lines.len(), 5 + 27);
+
+        // black and white map to the representative colors of Black and
+        // White
+        let entry = |color_id: usize| {
+            let c = colors[color_id - 1];
+            return format!("{:.6} {:.6} {:.6}", c.red, c.green, c.blue);
+        };
+        assert_eq!(lines[5], entry(267));
+        assert_eq!(lines[5 + 26], entry(263));
+
+        let level1_colors = get_level1_mean_colors(&dataset);
+        let cube = to_cube(&dataset, 1, &level1_colors, 2);
+        let entries = cube.lines().skip(5).collect::<Vec<&str>>();
+        assert_eq!(entries.len(), 8);
+        assert!(entries.iter().all(|x| level1_colors
+            .iter()
+            .any(|c| *x == format!("{:.6} {:.6} {:.6}", c.red, c.green, c.blue))));
+    }
+}
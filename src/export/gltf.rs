@@ -0,0 +1,175 @@
+use base64::Engine;
+use palette::LinSrgb;
+use serde_json::{json, Value};
+
+use crate::dataset::Dataset;
+use crate::solid::SolidPart;
+
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Collects the binary data of a glTF file, along with the buffer views and
+/// accessors describing it.
+struct Buffer {
+    data: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl Buffer {
+    /// Append a view of `bytes`, and an accessor for it; returns the index
+    /// of the accessor.
+    fn push(&mut self, bytes: Vec<u8>, target: u32, mut accessor: Value) -> usize {
+        self.views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.data.len(),
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        self.data.extend(bytes);
+
+        accessor["bufferView"] = json!(self.views.len() - 1);
+        self.accessors.push(accessor);
+        return self.accessors.len() - 1;
+    }
+
+    fn push_vec3(&mut self, values: &[[f32; 3]], bounds: bool) -> usize {
+        let bytes = values
+            .iter()
+            .flatten()
+            .flat_map(|x| x.to_le_bytes())
+            .collect::<Vec<u8>>();
+        let mut accessor = json!({
+            "componentType": FLOAT,
+            "count": values.len(),
+            "type": "VEC3",
+        });
+        // required for positions
+        if bounds {
+            let bound = |f: fn(f32, f32) -> f32, init: f32| {
+                (0..3)
+                    .map(|i| values.iter().map(|v| v[i]).fold(init, f))
+                    .collect::<Vec<f32>>()
+            };
+            accessor["min"] = json!(bound(f32::min, f32::INFINITY));
+            accessor["max"] = json!(bound(f32::max, f32::NEG_INFINITY));
+        }
+        return self.push(bytes, ARRAY_BUFFER, accessor);
+    }
+
+    fn push_indices(&mut self, triangles: &[[u32; 3]]) -> usize {
+        let bytes = triangles
+            .iter()
+            .flatten()
+            .flat_map(|x| x.to_le_bytes())
+            .collect::<Vec<u8>>();
+        let accessor = json!({
+            "componentType": UNSIGNED_INT,
+            "count": triangles.len() * 3,
+            "type": "SCALAR",
+        });
+        return self.push(bytes, ELEMENT_ARRAY_BUFFER, accessor);
+    }
+}
+
+/// Build a glTF 2.0 document of the color solid, with one node per level 3
+/// color named by its slug, and the binary data embedded as a base64 data
+/// URI.
+///
+/// Vertex colors (`COLOR_0`) are in linear RGB, as glTF requires.
+pub fn to_gltf(dataset: &Dataset, solid: &[SolidPart]) -> Value {
+    let metadata = dataset.metadata();
+    let mut buffer = Buffer {
+        data: Vec::new(),
+        views: Vec::new(),
+        accessors: Vec::new(),
+    };
+    let mut nodes: Vec<Value> = Vec::new();
+    let mut meshes: Vec<Value> = Vec::new();
+
+    for part in solid.iter() {
+        let slug = dataset.slug(part.color_id).unwrap();
+        let linear: LinSrgb = part.color.into_linear();
+        let colors = vec![[linear.red, linear.green, linear.blue]; part.positions.len()];
+
+        let position = buffer.push_vec3(&part.positions, true);
+        let color = buffer.push_vec3(&colors, false);
+        let indices = buffer.push_indices(&part.triangles);
+
+        meshes.push(json!({
+            "name": slug,
+            "primitives": [{
+                "attributes": { "POSITION": position, "COLOR_0": color },
+                "indices": indices,
+                "material": 0,
+            }],
+        }));
+        nodes.push(json!({
+            "name": slug,
+            "mesh": meshes.len() - 1,
+            "extras": { "color_id": part.color_id },
+        }));
+    }
+
+    let uri = format!(
+        "data:application/octet-stream;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(&buffer.data)
+    );
+
+    return json!({
+        "asset": {
+            "version": "2.0",
+            "generator": "iscc-nbs-validator",
+            "copyright": metadata.license,
+            "extras": {
+                "version": metadata.version,
+                "source": metadata.source,
+                "publication_date": metadata.publication_date,
+            },
+        },
+        "scene": 0,
+        "scenes": [{ "name": "ISCC-NBS color solid", "nodes": (0..nodes.len()).collect::<Vec<usize>>() }],
+        "nodes": nodes,
+        "meshes": meshes,
+        "materials": [{
+            "name": "vertex color",
+            "pbrMetallicRoughness": { "metallicFactor": 0.0, "roughnessFactor": 1.0 },
+        }],
+        "buffers": [{ "byteLength": buffer.data.len(), "uri": uri }],
+        "bufferViews": buffer.views,
+        "accessors": buffer.accessors,
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use base64::Engine;
+
+    use crate::colors::get_mean_colors;
+    use crate::export::gltf::to_gltf;
+    use crate::Dataset;
+
+    #[test]
+    fn gltf_buffers() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let solid = dataset.color_solid(&colors);
+        let gltf = to_gltf(&dataset, &solid);
+
+        assert_eq!(gltf["nodes"].as_array().unwrap().len(), 267);
+        assert_eq!(gltf["nodes"][0]["name"], "1-vivid-pink");
+        assert_eq!(gltf["accessors"].as_array().unwrap().len(), 267 * 3);
+
+        let uri = gltf["buffers"][0]["uri"].as_str().unwrap();
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(uri.split_once(',').unwrap().1)
+            .unwrap();
+        assert_eq!(gltf["buffers"][0]["byteLength"], data.len());
+
+        let last = gltf["bufferViews"].as_array().unwrap().last().unwrap();
+        let end = last["byteOffset"].as_u64().unwrap() + last["byteLength"].as_u64().unwrap();
+        assert_eq!(end as usize, data.len());
+    }
+}
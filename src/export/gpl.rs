@@ -0,0 +1,66 @@
+//! GIMP palettes (`.gpl`), which Inkscape and Krita read as well.
+
+use palette::Srgb;
+
+use crate::export::{level_names, ordered_names, swatch_label, SwatchOrder};
+use crate::naming::ColorNamingSystem;
+
+/// How many columns GIMP shows the palette in.
+const COLUMNS: u32 = 16;
+
+/// Build a GIMP palette of the names of level 1, 2 or 3, with `colors`
+/// their representative colors, indexed by `color_id - 1`. Each color is
+/// named like "Vivid pink (viv.Pk)".
+pub fn to_gpl<S: ColorNamingSystem + ?Sized>(
+    dataset: &S,
+    level: u32,
+    colors: &[Srgb],
+    order: SwatchOrder,
+) -> String {
+    let mut out = format!(
+        "GIMP Palette\nName: ISCC-NBS level {}\nColumns: {}\n# iscc-nbs {}\n",
+        level,
+        COLUMNS,
+        dataset.metadata().version
+    );
+    for (id, name) in ordered_names(level_names(dataset, level), colors, order) {
+        let c: Srgb<u8> = colors[(id - 1) as usize].into_format();
+        out.push_str(&format!(
+            "{:3} {:3} {:3}\t{}\n",
+            c.red,
+            c.green,
+            c.blue,
+            swatch_label(name)
+        ));
+    }
+    return out;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::{get_level1_mean_colors, get_mean_colors};
+    use crate::export::gpl::to_gpl;
+    use crate::export::SwatchOrder;
+    use crate::Dataset;
+
+    #[test]
+    fn palettes() {
+        let dataset = Dataset::bundled();
+        let gpl = to_gpl(&dataset, 3, &get_mean_colors(&dataset), SwatchOrder::Id);
+        let lines = gpl.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], "GIMP Palette");
+        assert_eq!(lines[1], "Name: ISCC-NBS level 3");
+        assert_eq!(lines.len(), 4 + 267);
+        assert_eq!(lines[4], "255 194 197\tVivid pink (viv.Pk)");
+        assert!(lines[4 + 266].ends_with("\tBlack (Bk)"));
+
+        let gpl = to_gpl(
+            &dataset,
+            1,
+            &get_level1_mean_colors(&dataset),
+            SwatchOrder::Id,
+        );
+        assert_eq!(gpl.lines().count(), 4 + 13);
+        assert!(gpl.lines().nth(4).unwrap().ends_with("\tPink (Pk)"));
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use palette::Srgb;
+use serde_json::{json, Value};
+
+use crate::colors::{get_level1_mean_colors, get_level2_mean_colors};
+use crate::dataset::{ColorName, Dataset};
+use crate::export::{sorted_names, srgb_hex};
+use crate::rgbspace::{rgb_space, RgbSpace};
+
+fn name_to_json(id: u32, name: &ColorName) -> Value {
+    let mut value = json!({
+        "id": id,
+        "name": name.name,
+        "abbr": name.abbr,
+        "parent": name.parent,
+    });
+    // only where the dataset has them, since most names don't
+    if let Some(description) = &name.description {
+        value["description"] = json!(description);
+    }
+    if let Some(usage) = &name.usage {
+        value["usage"] = json!(usage);
+    }
+    return value;
+}
+
+/// Set the representative color of a name: a hex code under `srgb`, or in
+/// the other spaces, CSS `color()` under `color`.
+fn set_color(value: &mut Value, color: Srgb) {
+    let space = rgb_space();
+    if space == RgbSpace::Srgb {
+        value["srgb"] = json!(srgb_hex(color));
+    } else {
+        value["color"] = json!(space.css_color(color));
+    }
+}
+
+/// Build a JSON document with the dataset's metadata, the three name levels, the boundary lists,
+/// every color block, and the representative color of each name: the
+/// given `colors` (indexed by `color_id - 1`) at level 3, and the
+/// centroids of [`get_level1_mean_colors`] and [`get_level2_mean_colors`]
+/// at levels 1 and 2, in [`rgb_space`], which is named by `color_space`.
+pub fn to_json(dataset: &Dataset, colors: &[Srgb]) -> Value {
+    let hues = dataset.hues();
+    let chromas = dataset.chromas();
+    let values = dataset.values();
+
+    let with_srgb = |names: &HashMap<u32, ColorName>, colors: &[Srgb]| {
+        return sorted_names(names)
+            .into_iter()
+            .map(|(id, name)| {
+                let mut value = name_to_json(id, name);
+                set_color(&mut value, colors[(id - 1) as usize]);
+                return value;
+            })
+            .collect::<Vec<Value>>();
+    };
+    let level1 = with_srgb(dataset.level1_names(), &get_level1_mean_colors(dataset));
+    let level2 = with_srgb(dataset.level2_names(), &get_level2_mean_colors(dataset));
+    let level3 = sorted_names(dataset.names())
+        .into_iter()
+        .map(|(id, name)| {
+            let mut value = name_to_json(id, name);
+            value["slug"] = json!(dataset.slug(id).unwrap());
+            set_color(&mut value, colors[(id - 1) as usize]);
+            return value;
+        })
+        .collect::<Vec<Value>>();
+
+    let blocks = dataset
+        .blocks()
+        .iter()
+        .map(|block| {
+            return json!({
+                "color_id": block.color_id,
+                "hue": [hues[block.hues.start], hues[block.hues.end]],
+                "chroma": [chromas[block.chromas.start], chromas[block.chromas.end]],
+                "value": [values[block.values.start], values[block.values.end]],
+            });
+        })
+        .collect::<Vec<Value>>();
+
+    let metadata = dataset.metadata();
+
+    return json!({
+        "metadata": {
+            "version": metadata.version,
+            "source": metadata.source,
+            "license": metadata.license,
+            "publication_date": metadata.publication_date,
+        },
+        "color_space": rgb_space().css_name(),
+        "level1": level1,
+        "level2": level2,
+        "level3": level3,
+        "hues": hues,
+        "chromas": chromas,
+        "values": values,
+        "blocks": blocks,
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::dataset::BUNDLED_XML;
+    use crate::export::json::to_json;
+    use crate::Dataset;
+
+    #[test]
+    fn json_contents() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let json = to_json(&dataset, &colors);
+
+        assert_eq!(json["metadata"]["license"], "CC0-1.0");
+        assert_eq!(json["level3"].as_array().unwrap().len(), 267);
+        assert_eq!(json["level3"][0]["id"], 1);
+        assert_eq!(json["level3"][0]["name"], "Vivid pink");
+        assert_eq!(json["level3"][0]["slug"], "1-vivid-pink");
+        assert!(json["level3"][0]["srgb"].as_str().unwrap().starts_with('#'));
+        assert!(json["level1"][0]["srgb"].as_str().unwrap().starts_with('#'));
+        assert_eq!(json["level2"][28]["name"], "Black");
+        assert!(json["level2"][28]["srgb"].is_string());
+        assert_eq!(json["color_space"], "srgb");
+        assert_eq!(
+            json["blocks"].as_array().unwrap().len(),
+            dataset.blocks().len()
+        );
+        assert_eq!(json["chromas"].as_array().unwrap().last().unwrap(), "INF");
+    }
+
+    #[test]
+    fn descriptions() {
+        let text = BUNDLED_XML.replacen(
+            "name=\"Vivid pink\" abbr=\"viv.Pk\"",
+            "name=\"Vivid pink\" abbr=\"viv.Pk\" description=\"A bright pink.\" usage=\"Flamingos\"",
+            1,
+        );
+        let dataset = Dataset::parse(&text).unwrap();
+        let json = to_json(&dataset, &get_mean_colors(&dataset));
+
+        assert_eq!(json["level3"][0]["description"], "A bright pink.");
+        assert_eq!(json["level3"][0]["usage"], "Flamingos");
+        assert!(json["level3"][1].get("description").is_none());
+        assert!(json["level1"][0].get("usage").is_none());
+    }
+}
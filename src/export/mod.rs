@@ -0,0 +1,90 @@
+// Exporters that write the dataset out in formats other tools can consume
+// without reimplementing the XML parsing and block math.
+
+use std::collections::HashMap;
+
+use palette::Srgb;
+
+use crate::colors::perceptual_order;
+use crate::dataset::ColorName;
+use crate::naming::ColorNamingSystem;
+
+pub mod adobe;
+#[cfg(feature = "charts")]
+pub mod bundle;
+pub mod css;
+pub mod csv;
+pub mod cube;
+pub mod gltf;
+pub mod gpl;
+pub mod json;
+pub mod npy;
+pub mod obj;
+pub mod rgbtxt;
+pub mod rust;
+#[cfg(feature = "charts")]
+pub mod site;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "charts")]
+pub mod swatches;
+pub mod xml;
+pub mod xterm;
+
+/// Format an sRGB color as a `#rrggbb` hex string.
+pub fn srgb_hex(color: Srgb) -> String {
+    let c: Srgb<u8> = color.into_format();
+    return format!("#{:02x}{:02x}{:02x}", c.red, c.green, c.blue);
+}
+
+/// The names of one level, ordered by id.
+pub(crate) fn sorted_names(names: &HashMap<u32, ColorName>) -> Vec<(u32, &ColorName)> {
+    let mut sorted = names
+        .iter()
+        .map(|(id, name)| (*id, name))
+        .collect::<Vec<_>>();
+    sorted.sort_by_key(|(id, _)| *id);
+    return sorted;
+}
+
+/// The names of a level, or of the finest level for levels outside of the
+/// system's, like level 3 of a [`crate::Dataset`].
+pub(crate) fn level_names<S: ColorNamingSystem + ?Sized>(
+    dataset: &S,
+    level: u32,
+) -> &HashMap<u32, ColorName> {
+    return dataset
+        .names_at(level)
+        .unwrap_or_else(|| dataset.finest_names());
+}
+
+/// The name of a color in a palette, like "Vivid pink (viv.Pk)".
+pub(crate) fn swatch_label(name: &ColorName) -> String {
+    return format!("{} ({})", name.name, name.abbr);
+}
+
+/// The order in which exports list colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SwatchOrder {
+    /// By id, as in the dataset.
+    Id,
+    /// So that neighbors look alike; see [`perceptual_order`].
+    Perceptual,
+}
+
+/// The names of one level in the given order, with `colors` their
+/// representative colors, indexed by id - 1.
+pub(crate) fn ordered_names<'a>(
+    names: &'a HashMap<u32, ColorName>,
+    colors: &[Srgb],
+    order: SwatchOrder,
+) -> Vec<(u32, &'a ColorName)> {
+    let sorted = sorted_names(names);
+    return match order {
+        SwatchOrder::Id => sorted,
+        SwatchOrder::Perceptual => perceptual_order(colors)
+            .into_iter()
+            .map(|i| sorted[i])
+            .collect(),
+    };
+}
@@ -0,0 +1,239 @@
+//! NumPy arrays (`.npy`) of the lookup table, the color id of every cell
+//! between the hue, chroma and value boundaries, for analyzing or drawing
+//! the partition in Python; and NumPy archives (`.npz`) of it together with
+//! the boundaries along each axis.
+//!
+//! The table is indexed `[hue, chroma, value]`, in the order of the
+//! dataset's hue, chroma and value boundaries, like
+//! [`crate::lookup::ColorLookupTable::get`]: cell `[h, c, v]` spans hue leaf
+//! `h`, from hue boundary `h` to the next, and the chroma and value spans
+//! from boundary `c` and `v` to the next. Cells no block covers are 0.
+//!
+//! An archive has these arrays, read with `numpy.load`:
+//!
+//! - `ids`, the table.
+//! - `hue`, the first hue of every leaf on the scale of
+//!   [`crate::MunsellHue::raw`].
+//! - `hue_names`, the same hues as written in the dataset, like `"2.5R"`.
+//! - `chroma` and `value`, every boundary, one more than there are spans,
+//!   ending in infinity.
+
+use crate::dataset::Dataset;
+use crate::munsell::MunsellHue;
+
+/// The header of an array of version 1.0 of the format, padded so that the
+/// data starts at a multiple of 64 bytes.
+fn npy_header(descr: &str, shape: &[usize]) -> Vec<u8> {
+    let shape = match shape {
+        [n] => format!("({},)", n),
+        _ => format!(
+            "({})",
+            shape
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    };
+    let mut dict = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': {}, }}",
+        descr, shape
+    );
+    // the magic string, the version and the length take 10 bytes, and the
+    // dictionary ends in a newline
+    while (10 + dict.len() + 1) % 64 != 0 {
+        dict.push(' ');
+    }
+    dict.push('\n');
+
+    let mut out = b"\x93NUMPY\x01\x00".to_vec();
+    out.extend_from_slice(&(dict.len() as u16).to_le_bytes());
+    out.extend_from_slice(dict.as_bytes());
+    return out;
+}
+
+fn f32_npy(values: &[f32]) -> Vec<u8> {
+    let mut out = npy_header("<f4", &[values.len()]);
+    for x in values {
+        out.extend_from_slice(&x.to_le_bytes());
+    }
+    return out;
+}
+
+/// An array of strings, as NumPy's fixed-width UTF-32 ones.
+fn str_npy(values: &[String]) -> Vec<u8> {
+    let width = values.iter().map(|x| x.chars().count()).max().unwrap_or(1);
+    let mut out = npy_header(&format!("<U{}", width), &[values.len()]);
+    for x in values {
+        let mut chars = x.chars().map(|c| c as u32).collect::<Vec<u32>>();
+        chars.resize(width, 0);
+        for c in chars {
+            out.extend_from_slice(&c.to_le_bytes());
+        }
+    }
+    return out;
+}
+
+/// Build a `.npy` array of the lookup table, with the id of the name of
+/// level 1, 2 or 3 containing each cell's color, as unsigned 16-bit
+/// integers.
+pub fn to_npy(dataset: &Dataset, level: u32) -> Vec<u8> {
+    let table = dataset.lookup_table();
+    let (hues, chromas, values) = table.shape();
+
+    let mut out = npy_header("<u2", &[hues, chromas, values]);
+    for h in 0..hues {
+        for c in 0..chromas {
+            for v in 0..values {
+                let id = match table.get(h, c, v).unwrap() {
+                    0 => 0,
+                    x => dataset.ancestor_id(3, x, level).unwrap(),
+                };
+                out.extend_from_slice(&(id as u16).to_le_bytes());
+            }
+        }
+    }
+    return out;
+}
+
+/// The CRC-32 of a ZIP entry.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    return !crc;
+}
+
+/// Build a ZIP archive of files stored without compression, as NumPy writes
+/// with `numpy.savez`.
+fn zip_stored(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    // 1980-01-01, the earliest date the format has
+    const DATE: u16 = (1 << 5) | 1;
+
+    let mut out = Vec::new();
+    let mut directory = Vec::new();
+    for (name, data) in files {
+        let offset = out.len() as u32;
+        // version needed, flags, method and time, then the date, the CRC
+        // and the compressed and uncompressed sizes
+        let mut fields = Vec::new();
+        fields.extend_from_slice(&20u16.to_le_bytes());
+        fields.extend_from_slice(&[0; 6]);
+        fields.extend_from_slice(&DATE.to_le_bytes());
+        fields.extend_from_slice(&crc32(data).to_le_bytes());
+        fields.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        fields.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        // no extra field
+        fields.extend_from_slice(&[0; 2]);
+
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        out.extend_from_slice(&fields);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        // version made by
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        directory.extend_from_slice(&fields);
+        // no comment, disk 0, no attributes
+        directory.extend_from_slice(&[0; 10]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let directory_offset = out.len() as u32;
+    out.extend_from_slice(&directory);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    for _ in 0..2 {
+        out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    }
+    out.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    out.extend_from_slice(&directory_offset.to_le_bytes());
+    out.extend_from_slice(&[0; 2]);
+    return out;
+}
+
+/// Build a `.npz` archive of the array of [`to_npy`], as `ids`, with the
+/// boundaries along each axis.
+pub fn to_npz(dataset: &Dataset, level: u32) -> Vec<u8> {
+    let hues = dataset
+        .hues()
+        .iter()
+        .map(|x| x.parse::<MunsellHue>().unwrap().raw())
+        .collect::<Vec<f32>>();
+    let points = |x: &[String]| {
+        x.iter()
+            .map(|x| x.parse::<f32>().unwrap())
+            .collect::<Vec<f32>>()
+    };
+
+    return zip_stored(&[
+        ("ids.npy", to_npy(dataset, level)),
+        ("hue.npy", f32_npy(&hues)),
+        ("hue_names.npy", str_npy(dataset.hues())),
+        ("chroma.npy", f32_npy(&points(dataset.chromas()))),
+        ("value.npy", f32_npy(&points(dataset.values()))),
+    ]);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::export::npy::{crc32, to_npy, to_npz};
+    use crate::Dataset;
+
+    #[test]
+    fn npy_arrays() {
+        let dataset = Dataset::bundled();
+        let table = dataset.lookup_table();
+        let (hues, chromas, values) = table.shape();
+
+        let npy = to_npy(&dataset, 3);
+        assert_eq!(&npy[..8], b"\x93NUMPY\x01\x00");
+        let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = std::str::from_utf8(&npy[10..10 + header_len]).unwrap();
+        assert!(header.contains(&format!("'shape': ({}, {}, {})", hues, chromas, values)));
+        assert!(header.ends_with('\n'));
+
+        let data = &npy[10 + header_len..];
+        assert_eq!(data.len(), 2 * hues * chromas * values);
+        let cell = |h: usize, c: usize, v: usize| {
+            let i = 2 * ((h * chromas + c) * values + v);
+            return u16::from_le_bytes([data[i], data[i + 1]]) as u32;
+        };
+        assert_eq!(cell(3, 5, 7), table.get(3, 5, 7).unwrap());
+        assert_eq!(cell(0, 0, 0), table.get(0, 0, 0).unwrap());
+
+        // level 1 ids in place of level 3 ones
+        let level1 = to_npy(&dataset, 1);
+        let id = u16::from_le_bytes([level1[npy.len() - 2], level1[npy.len() - 1]]) as u32;
+        let last = table.get(hues - 1, chromas - 1, values - 1).unwrap();
+        assert_eq!(Some(id), dataset.ancestor_id(3, last, 1));
+
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        let npz = to_npz(&dataset, 3);
+        assert_eq!(&npz[..4], b"PK\x03\x04");
+        for name in [
+            "ids.npy",
+            "hue.npy",
+            "hue_names.npy",
+            "chroma.npy",
+            "value.npy",
+        ] {
+            let found = npz.windows(name.len()).filter(|x| *x == name.as_bytes());
+            // in the local header and in the central directory
+            assert_eq!(found.count(), 2, "{}", name);
+        }
+        assert!(npz.windows(npy.len()).any(|x| x == &npy[..]));
+    }
+}
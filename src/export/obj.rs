@@ -0,0 +1,72 @@
+use std::fmt::Write as _;
+
+use crate::dataset::Dataset;
+use crate::solid::SolidPart;
+
+/// Build a Wavefront OBJ file of the color solid, with one object per level
+/// 3 color named by its slug.
+///
+/// Vertex colors are written as the widely supported `v x y z r g b`
+/// extension, in sRGB.
+pub fn to_obj(dataset: &Dataset, solid: &[SolidPart]) -> String {
+    let metadata = dataset.metadata();
+    let mut out = String::new();
+
+    writeln!(out, "# ISCC-NBS color solid, version {}", metadata.version).unwrap();
+    writeln!(out, "# {}", metadata.source).unwrap();
+    writeln!(out, "# License: {}", metadata.license).unwrap();
+
+    // OBJ indices are global and start at 1
+    let mut base = 1;
+    for part in solid.iter() {
+        writeln!(out, "o {}", dataset.slug(part.color_id).unwrap()).unwrap();
+        let c = part.color;
+        for [x, y, z] in part.positions.iter() {
+            writeln!(
+                out,
+                "v {:.4} {:.4} {:.4} {:.4} {:.4} {:.4}",
+                x, y, z, c.red, c.green, c.blue
+            )
+            .unwrap();
+        }
+        for [a, b, c] in part.triangles.iter() {
+            writeln!(out, "f {} {} {}", a + base, b + base, c + base).unwrap();
+        }
+        base += part.positions.len() as u32;
+    }
+
+    return out;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::export::obj::to_obj;
+    use crate::Dataset;
+
+    #[test]
+    fn obj_objects() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let solid = dataset.color_solid(&colors);
+        let obj = to_obj(&dataset, &solid);
+
+        let vertices: usize = solid.iter().map(|x| x.positions.len()).sum();
+        let faces: usize = solid.iter().map(|x| x.triangles.len()).sum();
+        assert_eq!(obj.lines().filter(|x| x.starts_with("o ")).count(), 267);
+        assert_eq!(
+            obj.lines().filter(|x| x.starts_with("v ")).count(),
+            vertices
+        );
+        assert_eq!(obj.lines().filter(|x| x.starts_with("f ")).count(), faces);
+        assert!(obj.contains("\no 1-vivid-pink\n"));
+
+        let last = obj.lines().last().unwrap();
+        let max = last[2..]
+            .split(' ')
+            .map(|x| x.parse::<usize>().unwrap())
+            .max()
+            .unwrap();
+        assert_eq!(max, vertices);
+    }
+}
@@ -0,0 +1,111 @@
+//! X11 `rgb.txt` color databases, for older tools that look colors up by
+//! name in one, like `xcolors` and Tk.
+
+use std::collections::HashSet;
+
+use palette::Srgb;
+
+use crate::colors::{get_level1_mean_colors, get_level2_mean_colors};
+use crate::dataset::{ColorName, Dataset};
+use crate::export::sorted_names;
+
+/// A name without spaces, like "VividPink", the way `rgb.txt` gives every
+/// name of more than one word a second time.
+fn joined_name(name: &str) -> String {
+    return name
+        .split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            return match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            };
+        })
+        .collect();
+}
+
+fn write_names(
+    out: &mut String,
+    seen: &mut HashSet<String>,
+    names: &[(u32, &ColorName)],
+    colors: &[Srgb],
+) {
+    for (id, name) in names {
+        let c: Srgb<u8> = colors[(id - 1) as usize].into_format();
+        let mut variants = vec![name.name.clone()];
+        if name.name.contains(' ') {
+            variants.push(joined_name(&name.name));
+        }
+        for variant in variants {
+            // names are looked up ignoring case, and the first one wins
+            if !seen.insert(variant.to_lowercase()) {
+                continue;
+            }
+            out.push_str(&format!(
+                "{:3} {:3} {:3}\t\t{}\n",
+                c.red, c.green, c.blue, variant
+            ));
+        }
+    }
+}
+
+/// Build an `rgb.txt` of the level 3 names, with `colors` their
+/// representative colors, indexed by `color_id - 1`. Names of more than one
+/// word are also given without spaces, like "VividPink".
+///
+/// With `all_levels`, the level 2 and level 1 names follow with the
+/// centroids of [`get_level2_mean_colors`] and [`get_level1_mean_colors`],
+/// leaving out those that a finer level already has, like "Pink".
+pub fn to_rgb_txt(dataset: &Dataset, colors: &[Srgb], all_levels: bool) -> String {
+    let mut out = format!(
+        "! ISCC-NBS colors, iscc-nbs {}\n",
+        dataset.metadata().version
+    );
+    let mut seen = HashSet::new();
+    write_names(&mut out, &mut seen, &sorted_names(dataset.names()), colors);
+    if all_levels {
+        write_names(
+            &mut out,
+            &mut seen,
+            &sorted_names(dataset.level2_names()),
+            &get_level2_mean_colors(dataset),
+        );
+        write_names(
+            &mut out,
+            &mut seen,
+            &sorted_names(dataset.level1_names()),
+            &get_level1_mean_colors(dataset),
+        );
+    }
+    return out;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::export::rgbtxt::to_rgb_txt;
+    use crate::Dataset;
+
+    #[test]
+    fn rgb_txt() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let text = to_rgb_txt(&dataset, &colors, false);
+        let lines = text.lines().collect::<Vec<&str>>();
+        assert!(lines[0].starts_with("! ISCC-NBS colors"));
+        assert!(lines[1].ends_with("\t\tVivid pink"));
+        assert!(lines[2].ends_with("\t\tVividPink"));
+        assert_eq!(lines[1].split('\t').next(), lines[2].split('\t').next());
+        // "Black" and "White" have no second form
+        assert!(text.contains("\t\tWhite\n"));
+        assert!(!text.contains("\t\tPink\n"));
+
+        // the coarser levels add the names the finer ones don't have, once
+        let all = to_rgb_txt(&dataset, &colors, true);
+        assert!(all.starts_with(&text));
+        assert_eq!(all.matches("\t\tPink\n").count(), 1);
+        assert_eq!(all.matches("\t\tWhite\n").count(), 1);
+        assert!(all.contains("\t\tOlive green\n"));
+        assert!(all.contains("\t\tOliveGreen\n"));
+    }
+}
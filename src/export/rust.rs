@@ -0,0 +1,250 @@
+use std::fmt::Write;
+
+use palette::Srgb;
+
+use crate::colors::{get_level1_mean_colors, get_level2_mean_colors};
+use crate::dataset::{ColorName, Dataset};
+use crate::export::sorted_names;
+use crate::munsell::MunsellHue;
+
+const BLOCK_TYPE: &str = "\
+/// A block of the Munsell solid belonging to one level 3 color, as index
+/// ranges into `HUES`, `CHROMAS` and `VALUES`. Blocks include their lower
+/// boundaries and exclude their upper ones; the hue range wraps around when
+/// `hues.1 < hues.0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Block {
+    pub color_id: u16,
+    pub hues: (u8, u8),
+    pub chromas: (u8, u8),
+    pub values: (u8, u8),
+}
+";
+
+fn float_literal(x: f32) -> String {
+    if x.is_infinite() {
+        return "f32::INFINITY".to_string();
+    }
+    return format!("{:?}", x);
+}
+
+fn write_array(out: &mut String, doc: &str, name: &str, ty: &str, items: &[String]) {
+    writeln!(out, "/// {}", doc).unwrap();
+    writeln!(out, "pub const {}: [{}; {}] = [", name, ty, items.len()).unwrap();
+    for item in items {
+        writeln!(out, "    {},", item).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    writeln!(out).unwrap();
+}
+
+fn write_names(
+    out: &mut String,
+    level: u32,
+    prefix: &str,
+    names: &[(u32, &ColorName)],
+    colors: &[Srgb],
+) {
+    let quoted = |x: &str| format!("{:?}", x);
+
+    write_array(
+        out,
+        &format!("Level {} names, indexed by id - 1.", level),
+        &format!("{}NAMES", prefix),
+        "&str",
+        &names
+            .iter()
+            .map(|(_, x)| quoted(&x.name))
+            .collect::<Vec<_>>(),
+    );
+    write_array(
+        out,
+        &format!("Level {} abbreviations, indexed by id - 1.", level),
+        &format!("{}ABBRS", prefix),
+        "&str",
+        &names
+            .iter()
+            .map(|(_, x)| quoted(&x.abbr))
+            .collect::<Vec<_>>(),
+    );
+    if level > 1 {
+        write_array(
+            out,
+            &format!(
+                "The level {} id containing each level {} name, indexed by id - 1.",
+                level - 1,
+                level
+            ),
+            &format!("{}PARENTS", prefix),
+            "u8",
+            &names
+                .iter()
+                .map(|(_, x)| x.parent.unwrap().to_string())
+                .collect::<Vec<_>>(),
+        );
+    }
+    write_array(
+        out,
+        &format!(
+            "Representative sRGB colors of the level {} names, indexed by id - 1.",
+            level
+        ),
+        &format!("{}SRGB", prefix),
+        "[u8; 3]",
+        &names
+            .iter()
+            .map(|(id, _)| {
+                let c: Srgb<u8> = colors[(id - 1) as usize].into_format();
+                return format!("[{}, {}, {}]", c.red, c.green, c.blue);
+            })
+            .collect::<Vec<_>>(),
+    );
+}
+
+/// Generate Rust source code with the dataset as `const` arrays, for
+/// projects that want to include it without depending on this crate.
+///
+/// Names are indexed by `id - 1` at each level; `colors` are the
+/// representative sRGB colors of the level 3 names, also indexed by
+/// `color_id - 1`. Levels 1 and 2 get the centroids of
+/// [`get_level1_mean_colors`] and [`get_level2_mean_colors`].
+pub fn to_rust(dataset: &Dataset, colors: &[Srgb]) -> String {
+    let metadata = dataset.metadata();
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "// ISCC-NBS System of Color Designation, version {}.",
+        metadata.version
+    )
+    .unwrap();
+    writeln!(out, "//").unwrap();
+    writeln!(
+        out,
+        "// Source: {}, {}.",
+        metadata.source, metadata.publication_date
+    )
+    .unwrap();
+    writeln!(out, "//").unwrap();
+    writeln!(
+        out,
+        "// Generated from iscc-nbs.xml by iscc-nbs-validator; do not edit."
+    )
+    .unwrap();
+    writeln!(out, "//").unwrap();
+    writeln!(out, "// SPDX-License-Identifier: {}", metadata.license).unwrap();
+    writeln!(out).unwrap();
+
+    write_names(
+        &mut out,
+        1,
+        "LEVEL1_",
+        &sorted_names(dataset.level1_names()),
+        &get_level1_mean_colors(dataset),
+    );
+    write_names(
+        &mut out,
+        2,
+        "LEVEL2_",
+        &sorted_names(dataset.level2_names()),
+        &get_level2_mean_colors(dataset),
+    );
+    write_names(&mut out, 3, "", &sorted_names(dataset.names()), colors);
+
+    let hues = dataset.hues();
+    write_array(
+        &mut out,
+        "Hue boundaries, in order around the hue circle.",
+        "HUES",
+        "&str",
+        &hues.iter().map(|x| format!("{:?}", x)).collect::<Vec<_>>(),
+    );
+    write_array(
+        &mut out,
+        "Hue boundaries on a 0-100 scale, with 0 at 5R and 10 per hue family.",
+        "HUE_POINTS",
+        "f32",
+        &hues
+            .iter()
+            .map(|x| float_literal(x.parse::<MunsellHue>().unwrap().raw()))
+            .collect::<Vec<_>>(),
+    );
+    write_array(
+        &mut out,
+        "Chroma boundaries, in ascending order.",
+        "CHROMAS",
+        "f32",
+        &dataset
+            .chromas()
+            .iter()
+            .map(|x| float_literal(x.parse().unwrap()))
+            .collect::<Vec<_>>(),
+    );
+    write_array(
+        &mut out,
+        "Value boundaries, in ascending order.",
+        "VALUES",
+        "f32",
+        &dataset
+            .values()
+            .iter()
+            .map(|x| float_literal(x.parse().unwrap()))
+            .collect::<Vec<_>>(),
+    );
+
+    out.push_str(BLOCK_TYPE);
+    writeln!(out).unwrap();
+    write_array(
+        &mut out,
+        "The blocks of every level 3 color.",
+        "BLOCKS",
+        "Block",
+        &dataset
+            .blocks()
+            .iter()
+            .map(|x| {
+                format!(
+                    "Block {{ color_id: {}, hues: ({}, {}), chromas: ({}, {}), values: ({}, {}) }}",
+                    x.color_id,
+                    x.hues.start,
+                    x.hues.end,
+                    x.chromas.start,
+                    x.chromas.end,
+                    x.values.start,
+                    x.values.end
+                )
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    // drop the blank line after the last array
+    out.pop();
+    return out;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::export::rust::to_rust;
+    use crate::Dataset;
+
+    #[test]
+    fn generated_arrays() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let code = to_rust(&dataset, &colors);
+
+        assert!(code.contains("pub const LEVEL1_NAMES: [&str; 13] = [\n    \"Pink\",\n"));
+        assert!(code.contains("pub const NAMES: [&str; 267] = [\n    \"Vivid pink\",\n"));
+        assert!(code.contains("pub const PARENTS: [u8; 267] = ["));
+        assert!(code.contains("pub const SRGB: [[u8; 3]; 267] = ["));
+        assert!(code.contains("pub const LEVEL1_SRGB: [[u8; 3]; 13] = ["));
+        assert!(code.contains("pub const LEVEL2_SRGB: [[u8; 3]; 29] = ["));
+        assert!(code.contains("    f32::INFINITY,\n];"));
+        assert!(code.contains(&format!(
+            "pub const BLOCKS: [Block; {}] = [",
+            dataset.blocks().len()
+        )));
+        assert!(code.contains("    Block { color_id: 1, hues: (0, 1), chromas: ("));
+    }
+}
@@ -0,0 +1,314 @@
+//! A static website documenting the dataset: an index of every level 3
+//! color, a page for each color with its blocks and the parts of the charts
+//! it is on, and a page for each hue leaf with its chart.
+//!
+//! The pages link to each other by relative paths, so the site can be
+//! browsed from the disk as well as served from anywhere.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use palette::Srgb;
+use ttf_word_wrap::TTFParserMeasure;
+
+use crate::colors::get_mean_munsell;
+use crate::dataset::Dataset;
+use crate::export::{sorted_names, srgb_hex};
+use crate::munsell::MunsellColor;
+use crate::plate::Plate;
+use crate::plot::load_font_data;
+use crate::plot::svg::{plate_excerpt_to_svg, plate_to_svg};
+
+const STYLE: &str = "body { font-family: 'DejaVu Sans', sans-serif; margin: 1em auto; max-width: 60em; }
+nav { margin-bottom: 1em; }
+table { border-collapse: collapse; }
+td, th { padding: 0.2em 0.6em; text-align: left; }
+.swatch { display: inline-block; width: 3em; height: 1.2em; border: 1px solid #000; vertical-align: middle; }
+.swatch.large { width: 100%; height: 5em; }
+.leaves { columns: 4; list-style: none; padding: 0; }
+.excerpts { display: flex; flex-wrap: wrap; gap: 1em; }
+.excerpts figure { margin: 0; }
+";
+
+fn escape(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+/// The path of the page of a level 3 color, from the root of the site.
+fn color_path(dataset: &Dataset, color_id: u32) -> String {
+    return format!("colors/{}.html", dataset.slug(color_id).unwrap());
+}
+
+/// The path of the page of a hue leaf, from the root of the site.
+fn leaf_path(plate: &Plate) -> String {
+    return format!("leaves/{}-{}.html", plate.hue_begin, plate.hue_end);
+}
+
+/// A whole page, with `root` the relative path from it to the root of the
+/// site.
+fn page(title: &str, root: &str, body: &str) -> String {
+    return format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{}</title>
+<link rel=\"stylesheet\" href=\"{}style.css\">
+</head>
+<body>
+<nav><a href=\"{}index.html\">ISCC-NBS colors</a></nav>
+{}</body>
+</html>
+",
+        escape(title),
+        root,
+        root,
+        body
+    );
+}
+
+fn swatch(color: Srgb, class: &str) -> String {
+    return format!(
+        "<span class=\"{}\" style=\"background: {}\"></span>",
+        class,
+        srgb_hex(color)
+    );
+}
+
+fn index_page(dataset: &Dataset, plates: &[Plate], colors: &[Srgb]) -> String {
+    let mut body = format!(
+        "<h1>ISCC-NBS colors</h1>\n<p>{} level 3 colors, from {}; version {}.</p>\n",
+        dataset.names().len(),
+        escape(&dataset.metadata().source),
+        escape(&dataset.metadata().version)
+    );
+
+    body.push_str("<h2>Hue leaves</h2>\n<ul class=\"leaves\">\n");
+    for plate in plates {
+        body.push_str(&format!(
+            "<li><a href=\"{}\">{}-{}</a></li>\n",
+            leaf_path(plate),
+            plate.hue_begin,
+            plate.hue_end
+        ));
+    }
+    body.push_str("</ul>\n");
+
+    body.push_str("<h2>Colors</h2>\n<table>\n");
+    body.push_str("<tr><th></th><th>Id</th><th>Name</th><th>Abbr.</th><th>Level 2</th></tr>\n");
+    for (id, name) in sorted_names(dataset.names()) {
+        let names = dataset.level_names(id).unwrap();
+        body.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>\n",
+            swatch(colors[(id - 1) as usize], "swatch"),
+            id,
+            color_path(dataset, id),
+            escape(&name.name),
+            escape(&name.abbr),
+            escape(&names.level2.name)
+        ));
+    }
+    body.push_str("</table>\n");
+
+    return page("ISCC-NBS colors", "", &body);
+}
+
+fn color_page(
+    dataset: &Dataset,
+    plates: &[Plate],
+    colors: &[Srgb],
+    centroids: &[MunsellColor],
+    measure: &TTFParserMeasure,
+    color_id: u32,
+) -> String {
+    let names = dataset.level_names(color_id).unwrap();
+    let name = names.level3;
+    let color = colors[(color_id - 1) as usize];
+    let centroid = &centroids[(color_id - 1) as usize];
+
+    let mut body = format!(
+        "<h1>{}: {} ({})</h1>\n{}\n<p>{} / {} / {}</p>\n",
+        color_id,
+        escape(&name.name),
+        escape(&name.abbr),
+        swatch(color, "swatch large"),
+        escape(&names.level1.name),
+        escape(&names.level2.name),
+        escape(&name.name)
+    );
+    if let Some(description) = &name.description {
+        body.push_str(&format!("<p>{}</p>\n", escape(description)));
+    }
+    if let Some(usage) = &name.usage {
+        body.push_str(&format!("<p><i>{}</i></p>\n", escape(usage)));
+    }
+    body.push_str(&format!(
+        "<p>Centroid {} {:.1}/{:.1}; representative color {}.</p>\n",
+        centroid.hue,
+        centroid.value,
+        centroid.chroma,
+        srgb_hex(color)
+    ));
+
+    let (hues, chromas, values) = (dataset.hues(), dataset.chromas(), dataset.values());
+    body.push_str("<h2>Blocks</h2>\n<table>\n<tr><th>Hue</th><th>Chroma</th><th>Value</th></tr>\n");
+    for block in dataset.blocks().iter().filter(|x| x.color_id == color_id) {
+        body.push_str(&format!(
+            "<tr><td>{} to {}</td><td>{} to {}</td><td>{} to {}</td></tr>\n",
+            hues[block.hues.start],
+            hues[block.hues.end],
+            chromas[block.chromas.start],
+            chromas[block.chromas.end],
+            values[block.values.start],
+            values[block.values.end]
+        ));
+    }
+    body.push_str("</table>\n");
+
+    body.push_str("<h2>On the charts</h2>\n<div class=\"excerpts\">\n");
+    for plate in plates {
+        if let Some(svg) = plate_excerpt_to_svg(dataset, plate, colors, measure, color_id) {
+            body.push_str(&format!(
+                "<figure>\n{}<figcaption><a href=\"../{}\">{}-{}</a>, page {}</figcaption>\n</figure>\n",
+                svg,
+                leaf_path(plate),
+                plate.hue_begin,
+                plate.hue_end,
+                plate.page
+            ));
+        }
+    }
+    body.push_str("</div>\n");
+
+    return page(&name.name, "../", &body);
+}
+
+fn leaf_page(
+    dataset: &Dataset,
+    plates: &[Plate],
+    colors: &[Srgb],
+    measure: &TTFParserMeasure,
+    h: usize,
+) -> String {
+    let plate = &plates[h];
+    let (previous, next) = (
+        &plates[(h + plates.len() - 1) % plates.len()],
+        &plates[(h + 1) % plates.len()],
+    );
+
+    let mut body = format!(
+        "<h1>Hues {}-{}</h1>\n<p>Page {} of NBS Special Publication 440. \
+         <a href=\"../{}\">Previous</a> | <a href=\"../{}\">Next</a></p>\n{}",
+        plate.hue_begin,
+        plate.hue_end,
+        plate.page,
+        leaf_path(previous),
+        leaf_path(next),
+        plate_to_svg(dataset, plate, colors, measure)
+    );
+
+    body.push_str("<h2>Colors</h2>\n<table>\n");
+    for region in plate.regions.iter() {
+        let name = &dataset.names()[&region.color_id];
+        body.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td><a href=\"../{}\">{}</a></td><td>{}</td></tr>\n",
+            swatch(colors[(region.color_id - 1) as usize], "swatch"),
+            region.color_id,
+            color_path(dataset, region.color_id),
+            escape(&name.name),
+            escape(&name.abbr)
+        ));
+    }
+    body.push_str("</table>\n");
+
+    return page(
+        &format!("Hues {}-{}", plate.hue_begin, plate.hue_end),
+        "../",
+        &body,
+    );
+}
+
+/// Build every page of the site, by path relative to its root.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn site_files(dataset: &Dataset, colors: &[Srgb]) -> BTreeMap<String, String> {
+    let font_data = load_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+    let plates = (0..dataset.hues().len())
+        .map(|h| dataset.plate(h).unwrap())
+        .collect::<Vec<Plate>>();
+    let centroids = get_mean_munsell(dataset);
+
+    let mut files = BTreeMap::new();
+    files.insert("style.css".to_string(), STYLE.to_string());
+    files.insert(
+        "index.html".to_string(),
+        index_page(dataset, &plates, colors),
+    );
+    for (id, _) in sorted_names(dataset.names()) {
+        files.insert(
+            color_path(dataset, id),
+            color_page(dataset, &plates, colors, &centroids, &measure, id),
+        );
+    }
+    for (h, plate) in plates.iter().enumerate() {
+        files.insert(
+            leaf_path(plate),
+            leaf_page(dataset, &plates, colors, &measure, h),
+        );
+    }
+    return files;
+}
+
+/// Write every page of the site into `out`.
+pub fn write_site(dataset: &Dataset, colors: &[Srgb], out: &Path) -> std::io::Result<()> {
+    for (path, contents) in site_files(dataset, colors).iter() {
+        let path = out.join(path);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, contents)?;
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::export::site::site_files;
+    use crate::Dataset;
+
+    #[test]
+    fn site_pages() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let files = site_files(&dataset, &colors);
+
+        let colors_pages = files.keys().filter(|x| x.starts_with("colors/")).count();
+        let leaf_pages = files.keys().filter(|x| x.starts_with("leaves/")).count();
+        assert_eq!(colors_pages, dataset.names().len());
+        assert_eq!(leaf_pages, dataset.hues().len());
+
+        let index = &files["index.html"];
+        assert!(index.contains("<a href=\"colors/1-vivid-pink.html\">Vivid pink</a>"));
+        assert!(index.contains("<a href=\"leaves/1R-4R.html\">1R-4R</a>"));
+
+        let page = &files["colors/1-vivid-pink.html"];
+        assert!(page.contains("<a href=\"../leaves/1R-4R.html\">1R-4R</a>"));
+        assert!(page.contains("<svg "));
+
+        // every link is to a page of the site
+        for (path, contents) in files.iter() {
+            let dir = if path.contains('/') { "../" } else { "" };
+            for link in contents.split("href=\"").skip(1) {
+                let link = link.split('"').next().unwrap();
+                let target = link.strip_prefix(dir).unwrap_or(link);
+                assert!(files.contains_key(target), "{} links to {}", path, link);
+            }
+        }
+    }
+}
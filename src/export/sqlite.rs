@@ -0,0 +1,194 @@
+use std::path::Path;
+
+use palette::Srgb;
+use rusqlite::{params, Connection};
+
+use crate::colors::{get_level1_mean_colors, get_level2_mean_colors};
+use crate::dataset::Dataset;
+use crate::export::{sorted_names, srgb_hex};
+use crate::munsell::MunsellHue;
+
+const SCHEMA: &str = "
+CREATE TABLE metadata (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE names (
+    level INTEGER NOT NULL,
+    id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    abbr TEXT NOT NULL,
+    parent INTEGER,
+    PRIMARY KEY (level, id)
+);
+CREATE TABLE blocks (
+    id INTEGER PRIMARY KEY,
+    color_id INTEGER NOT NULL,
+    hue_begin TEXT NOT NULL,
+    hue_end TEXT NOT NULL,
+    hue_begin_raw REAL NOT NULL,
+    hue_end_raw REAL NOT NULL,
+    chroma_begin REAL NOT NULL,
+    chroma_end REAL NOT NULL,
+    value_begin REAL NOT NULL,
+    value_end REAL NOT NULL
+);
+CREATE INDEX blocks_color_id ON blocks (color_id);
+CREATE TABLE centroids (
+    level INTEGER NOT NULL,
+    color_id INTEGER NOT NULL,
+    srgb TEXT NOT NULL,
+    red REAL NOT NULL,
+    green REAL NOT NULL,
+    blue REAL NOT NULL,
+    PRIMARY KEY (level, color_id)
+);
+";
+
+/// Create the `metadata`, `names`, `blocks` and `centroids` tables in `conn` and fill
+/// them from the dataset and the representative colors (`colors`, indexed
+/// by `color_id - 1`). Centroids of levels 1 and 2 are computed with
+/// [`get_level1_mean_colors`] and [`get_level2_mean_colors`].
+///
+/// Block boundaries are stored both as the hue names of the charts and as
+/// numbers: hues as [`MunsellHue::raw`] (a block wraps around when
+/// `hue_end_raw < hue_begin_raw`), chromas and values as floats, with `INF`
+/// stored as infinity. Blocks include their lower boundaries and exclude
+/// their upper ones.
+pub fn write_tables(
+    conn: &mut Connection,
+    dataset: &Dataset,
+    colors: &[Srgb],
+) -> rusqlite::Result<()> {
+    let hues = dataset.hues();
+    let chromas = dataset.chromas();
+    let values = dataset.values();
+
+    let tx = conn.transaction()?;
+    tx.execute_batch(SCHEMA)?;
+
+    let metadata = dataset.metadata();
+    let fields = [
+        ("version", &metadata.version),
+        ("source", &metadata.source),
+        ("license", &metadata.license),
+        ("publication_date", &metadata.publication_date),
+    ];
+    for (key, value) in fields {
+        tx.execute(
+            "INSERT INTO metadata (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+    }
+
+    let levels = [
+        (1, dataset.level1_names()),
+        (2, dataset.level2_names()),
+        (3, dataset.names()),
+    ];
+    for (level, names) in levels {
+        for (id, name) in sorted_names(names) {
+            tx.execute(
+                "INSERT INTO names (level, id, name, abbr, parent) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![level, id, name.name, name.abbr, name.parent],
+            )?;
+        }
+    }
+
+    let number = |x: &str| x.parse::<f64>().unwrap();
+    for block in dataset.blocks() {
+        let hue_begin = &hues[block.hues.start];
+        let hue_end = &hues[block.hues.end];
+        tx.execute(
+            "INSERT INTO blocks (color_id, hue_begin, hue_end, hue_begin_raw, hue_end_raw, \
+             chroma_begin, chroma_end, value_begin, value_end) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                block.color_id,
+                hue_begin,
+                hue_end,
+                hue_begin.parse::<MunsellHue>().unwrap().raw(),
+                hue_end.parse::<MunsellHue>().unwrap().raw(),
+                number(&chromas[block.chromas.start]),
+                number(&chromas[block.chromas.end]),
+                number(&values[block.values.start]),
+                number(&values[block.values.end]),
+            ],
+        )?;
+    }
+
+    let centroids = [
+        (1, dataset.level1_names(), get_level1_mean_colors(dataset)),
+        (2, dataset.level2_names(), get_level2_mean_colors(dataset)),
+        (3, dataset.names(), colors.to_vec()),
+    ];
+    for (level, names, colors) in centroids {
+        for (id, _) in sorted_names(names) {
+            let color = colors[(id - 1) as usize];
+            tx.execute(
+                "INSERT INTO centroids (level, color_id, srgb, red, green, blue) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    level,
+                    id,
+                    srgb_hex(color),
+                    color.red,
+                    color.green,
+                    color.blue
+                ],
+            )?;
+        }
+    }
+
+    return tx.commit();
+}
+
+/// Write a new SQLite database at `path` with the tables of
+/// [`write_tables`], replacing any existing file.
+pub fn write_sqlite(path: &Path, dataset: &Dataset, colors: &[Srgb]) -> rusqlite::Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path).unwrap();
+    }
+
+    let mut conn = Connection::open(path)?;
+    return write_tables(&mut conn, dataset, colors);
+}
+
+#[cfg(test)]
+mod test {
+    use rusqlite::Connection;
+
+    use crate::colors::get_mean_colors;
+    use crate::export::sqlite::write_tables;
+    use crate::Dataset;
+
+    #[test]
+    fn range_query() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let mut conn = Connection::open_in_memory().unwrap();
+        write_tables(&mut conn, &dataset, &colors).unwrap();
+
+        let count = |table: &str| -> u32 {
+            let query = format!("SELECT COUNT(*) FROM {}", table);
+            return conn.query_row(&query, [], |row| row.get(0)).unwrap();
+        };
+        assert_eq!(count("metadata"), 4);
+        assert_eq!(count("names"), 13 + 29 + 267);
+        assert_eq!(count("blocks"), dataset.blocks().len() as u32);
+        assert_eq!(count("centroids"), 13 + 29 + 267);
+
+        // 2R 7/12, which is vivid pink
+        let name: String = conn
+            .query_row(
+                "SELECT names.name FROM blocks JOIN names ON names.level = 3 AND names.id = blocks.color_id \
+                 WHERE hue_begin_raw <= 97.0 AND hue_end_raw > 97.0 \
+                 AND chroma_begin <= 12.0 AND chroma_end > 12.0 \
+                 AND value_begin <= 7.0 AND value_end > 7.0",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(name, "Vivid pink");
+    }
+}
@@ -0,0 +1,117 @@
+//! One small PNG filled with the representative color of each level 3 name,
+//! for use as icons in other applications, with an index of them.
+//!
+//! The index, `index.json`, lists every color with its id, name,
+//! abbreviation, color and the file of its swatch, in order of id.
+
+use std::path::Path;
+
+use palette::Srgb;
+use plotters::prelude::*;
+use serde_json::{json, Value};
+
+use crate::dataset::Dataset;
+use crate::export::{sorted_names, srgb_hex};
+
+/// The width and height of a swatch, in pixels.
+pub const DEFAULT_SWATCH_SIZE: u32 = 32;
+
+/// The name of the index, in the directory of the swatches.
+pub const INDEX: &str = "index.json";
+
+/// What the file of each swatch is named after.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SwatchNaming {
+    /// The color id, like `1.png`.
+    #[default]
+    Id,
+    /// The abbreviation, like `viv.Pk.png`. Some abbreviations differ only in
+    /// case, like `l.Gy` and `l.gY`, and overwrite each other on file
+    /// systems that ignore it.
+    Abbr,
+}
+
+/// The file name of the swatch of a level 3 color.
+pub fn swatch_file_name(dataset: &Dataset, color_id: u32, naming: SwatchNaming) -> String {
+    return match naming {
+        SwatchNaming::Id => format!("{}.png", color_id),
+        SwatchNaming::Abbr => format!("{}.png", dataset.names()[&color_id].abbr),
+    };
+}
+
+/// Build the index of the swatches of every level 3 name, with `colors`
+/// their representative colors, indexed by `color_id - 1`.
+pub fn swatch_index(dataset: &Dataset, colors: &[Srgb], naming: SwatchNaming) -> Value {
+    return sorted_names(dataset.names())
+        .into_iter()
+        .map(|(id, name)| {
+            return json!({
+                "id": id,
+                "name": name.name,
+                "abbr": name.abbr,
+                "srgb": srgb_hex(colors[(id - 1) as usize]),
+                "file": swatch_file_name(dataset, id, naming),
+            });
+        })
+        .collect::<Value>();
+}
+
+/// Write a `size` by `size` swatch of every level 3 name into `out`, and
+/// the index of them.
+pub fn write_swatches(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    out: &Path,
+    size: u32,
+    naming: SwatchNaming,
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(out)?;
+    for (id, _) in sorted_names(dataset.names()) {
+        let path = out.join(swatch_file_name(dataset, id, naming));
+        let c: Srgb<u8> = colors[(id - 1) as usize].into_format();
+        let root = BitMapBackend::new(&path, (size, size)).into_drawing_area();
+        root.fill(&RGBColor(c.red, c.green, c.blue))
+            .and_then(|_| root.present())
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+    }
+
+    let index = swatch_index(dataset, colors, naming);
+    std::fs::write(
+        out.join(INDEX),
+        serde_json::to_string_pretty(&index).unwrap() + "\n",
+    )?;
+    return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::export::srgb_hex;
+    use crate::export::swatches::{swatch_index, write_swatches, SwatchNaming, INDEX};
+    use crate::Dataset;
+
+    #[test]
+    fn swatch_files() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+
+        let index = swatch_index(&dataset, &colors, SwatchNaming::Abbr);
+        let index = index.as_array().unwrap();
+        assert_eq!(index.len(), dataset.names().len());
+        assert_eq!(index[0]["file"], "viv.Pk.png");
+        assert_eq!(index[0]["srgb"], srgb_hex(colors[0]));
+
+        let out = std::env::temp_dir().join("iscc-nbs-swatches-test");
+        write_swatches(&dataset, &colors, &out, 8, SwatchNaming::Id).unwrap();
+        let png = std::fs::read(out.join("1.png")).unwrap();
+        let index = std::fs::read_to_string(out.join(INDEX)).unwrap();
+        let files = std::fs::read_dir(&out).unwrap().count();
+        std::fs::remove_dir_all(&out).unwrap();
+
+        assert_eq!(&png[1..4], b"PNG");
+        // the width and height in the header
+        assert_eq!(&png[16..24], &[0, 0, 0, 8, 0, 0, 0, 8]);
+        assert!(index.contains("\"file\": \"267.png\""));
+        assert_eq!(files, dataset.names().len() + 1);
+    }
+}
@@ -0,0 +1,192 @@
+//! The ISCC-NBS XML format itself, for writing back a dataset built or
+//! edited in code. The output is in the canonical form of
+//! [`crate::canonical`], without the comments of the bundled document.
+
+use crate::canonical::{escape_attribute, escape_text};
+use crate::dataset::{ColorBlock, ColorName, Dataset};
+use crate::export::sorted_names;
+use crate::munsell::MunsellHue;
+
+fn write_name(out: &mut String, id: u32, name: &ColorName, depth: usize, children: bool) {
+    out.push_str(&format!(
+        "{}<name color=\"{}\" name=\"{}\" abbr=\"{}\"",
+        "\t".repeat(depth),
+        id,
+        escape_attribute(&name.name),
+        escape_attribute(&name.abbr)
+    ));
+    if let Some(c) = name.srgb {
+        out.push_str(&format!(
+            " srgb=\"#{:02x}{:02x}{:02x}\"",
+            c.red, c.green, c.blue
+        ));
+    }
+    if let Some(description) = &name.description {
+        out.push_str(&format!(
+            " description=\"{}\"",
+            escape_attribute(description)
+        ));
+    }
+    if let Some(usage) = &name.usage {
+        out.push_str(&format!(" usage=\"{}\"", escape_attribute(usage)));
+    }
+    out.push_str(if children { ">\n" } else { " />\n" });
+}
+
+fn write_names(out: &mut String, dataset: &Dataset) {
+    let children = |level: u32, parent: u32| {
+        let names = match level {
+            2 => dataset.level2_names(),
+            _ => dataset.names(),
+        };
+        return sorted_names(names)
+            .into_iter()
+            .filter(|(_, x)| x.parent == Some(parent))
+            .collect::<Vec<_>>();
+    };
+
+    out.push_str("\t<names>\n");
+    for (id1, level1) in sorted_names(dataset.level1_names()) {
+        let level2_names = children(2, id1);
+        write_name(out, id1, level1, 2, !level2_names.is_empty());
+        for (id2, level2) in level2_names.iter() {
+            let level3_names = children(3, *id2);
+            write_name(out, *id2, level2, 3, !level3_names.is_empty());
+            for (id3, level3) in level3_names.iter() {
+                write_name(out, *id3, level3, 4, false);
+            }
+            if !level3_names.is_empty() {
+                out.push_str("\t\t\t</name>\n");
+            }
+        }
+        if !level2_names.is_empty() {
+            out.push_str("\t\t</name>\n");
+        }
+    }
+    out.push_str("\t</names>\n");
+}
+
+/// The position of a hue on the circle from 0 to 1, with 5R at 0, as the
+/// text of its `<amount>`: two decimals, or more where needed.
+fn hue_amount(hue: &str) -> String {
+    let point = hue.parse::<MunsellHue>().unwrap().raw() / 100.0;
+    let mut text = format!("{:.4}", point);
+    while text.ends_with('0') && text.len() > "0.00".len() {
+        text.pop();
+    }
+    return text;
+}
+
+fn write_amounts(out: &mut String, tag: &str, amounts: &[String]) {
+    out.push_str(&format!("\t<{}>\n", tag));
+    for amount in amounts {
+        out.push_str(&format!("\t\t<amount>{}</amount>\n", escape_text(amount)));
+    }
+    out.push_str(&format!("\t</{}>\n", tag));
+}
+
+fn write_ranges(out: &mut String, dataset: &Dataset) {
+    let (hues, chromas, values) = (dataset.hues(), dataset.chromas(), dataset.values());
+
+    let mut blocks = dataset.blocks().iter().collect::<Vec<&ColorBlock>>();
+    // grouped by hue range, and like the canonical form within each
+    blocks.sort_by_key(|x| {
+        (
+            x.hues.start,
+            x.hues.end,
+            x.color_id,
+            x.chromas.start,
+            usize::MAX - x.values.start,
+        )
+    });
+
+    out.push_str("\t<ranges>\n");
+    for (i, block) in blocks.iter().enumerate() {
+        let first = i == 0 || blocks[i - 1].hues != block.hues;
+        if first {
+            if i > 0 {
+                out.push_str("\t\t</hue-range>\n");
+            }
+            out.push_str(&format!(
+                "\t\t<hue-range begin=\"{}\" end=\"{}\">\n",
+                hues[block.hues.start], hues[block.hues.end]
+            ));
+        }
+        out.push_str(&format!(
+            "\t\t\t<range color=\"{}\" chroma-begin=\"{}\" chroma-end=\"{}\" value-begin=\"{}\" value-end=\"{}\" />\n",
+            block.color_id,
+            chromas[block.chromas.start],
+            chromas[block.chromas.end],
+            values[block.values.start],
+            values[block.values.end]
+        ));
+    }
+    if !blocks.is_empty() {
+        out.push_str("\t\t</hue-range>\n");
+    }
+    out.push_str("\t</ranges>\n");
+}
+
+/// Write a dataset as an ISCC-NBS XML document, which parses back into the
+/// same names and blocks.
+pub fn to_xml(dataset: &Dataset) -> String {
+    let metadata = dataset.metadata();
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<system>\n");
+    out.push_str("\t<metadata>\n");
+    for (tag, text) in [
+        ("version", &metadata.version),
+        ("source", &metadata.source),
+        ("license", &metadata.license),
+        ("publication-date", &metadata.publication_date),
+    ] {
+        out.push_str(&format!("\t\t<{}>{}</{}>\n", tag, escape_text(text), tag));
+    }
+    out.push_str("\t</metadata>\n");
+
+    write_names(&mut out, dataset);
+    out.push_str("\t<hues>\n");
+    for hue in dataset.hues() {
+        out.push_str(&format!(
+            "\t\t<amount id=\"{}\">{}</amount>\n",
+            escape_attribute(hue),
+            hue_amount(hue)
+        ));
+    }
+    out.push_str("\t</hues>\n");
+    write_amounts(&mut out, "chromas", dataset.chromas());
+    write_amounts(&mut out, "values", dataset.values());
+    write_ranges(&mut out, dataset);
+    out.push_str("</system>\n");
+    return out;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::canonical::canonicalize;
+    use crate::export::xml::to_xml;
+    use crate::Dataset;
+
+    #[test]
+    fn xml_round_trip() {
+        let dataset = Dataset::bundled();
+        let xml = to_xml(&dataset);
+        assert!(xml.contains("\t\t<amount id=\"1R\">0.96</amount>\n"));
+        assert!(xml.contains("\t\t\t\t<name color=\"1\" name=\"Vivid pink\" abbr=\"viv.Pk\" />\n"));
+
+        // already canonical, and the same dataset
+        assert_eq!(canonicalize(&xml).unwrap(), xml);
+        let (parsed, diagnostics) = Dataset::check_and_build(&xml).unwrap();
+        assert!(dataset.diff(&parsed.unwrap()).is_empty());
+        assert_eq!(
+            diagnostics.len(),
+            Dataset::check(crate::dataset::BUNDLED_XML).unwrap().len()
+        );
+
+        // the bundled document is the same apart from its comments
+        let stripped = regex::Regex::new(r"(?s)\n?\s*<!--.*?-->")
+            .unwrap()
+            .replace_all(crate::dataset::BUNDLED_XML, "")
+            .replace("\n\n", "\n");
+        assert_eq!(xml, stripped);
+    }
+}
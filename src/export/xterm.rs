@@ -0,0 +1,159 @@
+//! The nearest colors of the xterm 256-color palette, for terminal programs
+//! that want to print a color by its ISCC-NBS name.
+//!
+//! Only indices 16 to 255 are matched: the 6 × 6 × 6 color cube and the
+//! gray ramp are the same in every terminal, while the first 16 colors are
+//! usually themed.
+
+use std::fmt::Write;
+
+use palette::{ColorDifference, IntoColor, Lab, Srgb};
+
+use crate::dataset::Dataset;
+use crate::export::sorted_names;
+
+/// The first palette index with a fixed color.
+pub const FIRST_FIXED_INDEX: u8 = 16;
+
+/// The levels of each channel of the color cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The color of an xterm palette index from [`FIRST_FIXED_INDEX`] up, or
+/// `None` for the themed ones below it.
+pub fn xterm_color(index: u8) -> Option<Srgb<u8>> {
+    if index < FIRST_FIXED_INDEX {
+        return None;
+    }
+    if index >= 232 {
+        let gray = 8 + 10 * (index - 232);
+        return Some(Srgb::new(gray, gray, gray));
+    }
+    let i = (index - FIRST_FIXED_INDEX) as usize;
+    return Some(Srgb::new(
+        CUBE_LEVELS[i / 36],
+        CUBE_LEVELS[i / 6 % 6],
+        CUBE_LEVELS[i % 6],
+    ));
+}
+
+/// The palette index nearest to the representative color of a level 3
+/// name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct XtermMatch {
+    pub color_id: u32,
+    pub index: u8,
+    /// The CIEDE2000 color difference between the two.
+    pub delta_e: f32,
+}
+
+fn to_lab(color: Srgb) -> Lab {
+    return color.into_linear().into_color();
+}
+
+/// Find the nearest xterm palette index to every level 3 name, by CIEDE2000
+/// from `colors`, their representative colors indexed by `color_id - 1`, in
+/// order of id.
+pub fn xterm_matches(dataset: &Dataset, colors: &[Srgb]) -> Vec<XtermMatch> {
+    let palette = (FIRST_FIXED_INDEX..=255)
+        .map(|i| (i, to_lab(xterm_color(i).unwrap().into_format())))
+        .collect::<Vec<(u8, Lab)>>();
+    return sorted_names(dataset.names())
+        .into_iter()
+        .map(|(id, _)| {
+            let lab = to_lab(colors[(id - 1) as usize]);
+            let (index, delta_e) = palette
+                .iter()
+                .map(|(i, x)| (*i, lab.get_color_difference(x)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            return XtermMatch {
+                color_id: id,
+                index,
+                delta_e,
+            };
+        })
+        .collect();
+}
+
+/// Build a table with one line per level 3 name: its id, abbreviation,
+/// name, nearest palette index and the CIEDE2000 difference to it.
+pub fn to_xterm_table(dataset: &Dataset, colors: &[Srgb]) -> String {
+    let mut out = format!(
+        "{:>3} {:<10} {:<36} {:>5} {:>6}\n",
+        "id", "abbr", "name", "xterm", "dE2000"
+    );
+    for m in xterm_matches(dataset, colors) {
+        let name = &dataset.names()[&m.color_id];
+        writeln!(
+            out,
+            "{:>3} {:<10} {:<36} {:>5} {:>6.2}",
+            m.color_id, name.abbr, name.name, m.index, m.delta_e
+        )
+        .unwrap();
+    }
+    return out;
+}
+
+/// Generate Rust source code with the nearest palette index of every level
+/// 3 name as a `const` array, indexed by `color_id - 1` like the arrays of
+/// [`crate::export::rust::to_rust`].
+pub fn to_xterm_rust(dataset: &Dataset, colors: &[Srgb]) -> String {
+    let matches = xterm_matches(dataset, colors);
+    let mut out = format!(
+        "// The nearest xterm 256-color palette index to every ISCC-NBS level 3\n\
+         // color, version {}.\n\
+         //\n\
+         // Generated from iscc-nbs.xml by iscc-nbs-validator; do not edit.\n\n",
+        dataset.metadata().version
+    );
+    writeln!(
+        out,
+        "/// xterm palette indices of the level 3 names, indexed by id - 1."
+    )
+    .unwrap();
+    writeln!(out, "pub const XTERM_256: [u8; {}] = [", matches.len()).unwrap();
+    for m in matches.iter() {
+        let name = &dataset.names()[&m.color_id];
+        writeln!(
+            out,
+            "    {}, // {} {}, dE2000 {:.2}",
+            m.index, m.color_id, name.name, m.delta_e
+        )
+        .unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    return out;
+}
+
+#[cfg(test)]
+mod test {
+    use palette::Srgb;
+
+    use crate::colors::get_mean_colors;
+    use crate::export::xterm::{to_xterm_rust, to_xterm_table, xterm_color, xterm_matches};
+    use crate::Dataset;
+
+    #[test]
+    fn xterm_palette() {
+        assert_eq!(xterm_color(15), None);
+        assert_eq!(xterm_color(16), Some(Srgb::new(0, 0, 0)));
+        assert_eq!(xterm_color(160), Some(Srgb::new(215, 0, 0)));
+        assert_eq!(xterm_color(231), Some(Srgb::new(255, 255, 255)));
+        assert_eq!(xterm_color(232), Some(Srgb::new(8, 8, 8)));
+        assert_eq!(xterm_color(255), Some(Srgb::new(238, 238, 238)));
+
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let matches = xterm_matches(&dataset, &colors);
+        assert_eq!(matches.len(), 267);
+        assert!(matches.iter().all(|x| x.index >= 16 && x.delta_e < 20.0));
+        // black is on the gray ramp, near its dark end
+        assert!((232..240).contains(&matches[266].index));
+
+        let table = to_xterm_table(&dataset, &colors);
+        assert_eq!(table.lines().count(), 268);
+        let code = to_xterm_rust(&dataset, &colors);
+        assert!(code.contains("pub const XTERM_256: [u8; 267] = [\n"));
+        assert!(code.contains(" // 1 Vivid pink, dE2000 "));
+    }
+}
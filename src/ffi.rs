@@ -0,0 +1,144 @@
+//! A C API over the bundled dataset, for imaging applications in C and C++.
+//! `include/iscc_nbs.h` declares it.
+//!
+//! Colors are level 3 color ids; 0 means no color. Strings are owned by the
+//! library and live as long as the program.
+
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr, CString};
+
+use lazy_static::lazy_static;
+use palette::Srgb;
+
+use crate::colors::get_mean_colors;
+use crate::dataset::Dataset;
+use crate::munsell::MunsellColor;
+
+struct Names {
+    names: HashMap<u32, CString>,
+    abbrs: HashMap<u32, CString>,
+    colors: Vec<Srgb<u8>>,
+}
+
+lazy_static! {
+    static ref DATASET: Dataset = Dataset::bundled();
+    static ref NAMES: Names = Names {
+        names: DATASET
+            .names()
+            .iter()
+            .map(|(id, x)| (*id, CString::new(x.name.as_str()).unwrap()))
+            .collect(),
+        abbrs: DATASET
+            .names()
+            .iter()
+            .map(|(id, x)| (*id, CString::new(x.abbr.as_str()).unwrap()))
+            .collect(),
+        colors: get_mean_colors(&*DATASET)
+            .into_iter()
+            .map(|x| x.into_format())
+            .collect(),
+    };
+}
+
+/// The id of the level 3 color an 8-bit sRGB color falls into, or 0.
+#[no_mangle]
+pub extern "C" fn iscc_nbs_lookup_rgb(r: u8, g: u8, b: u8) -> u32 {
+    let color: Srgb = Srgb::new(r, g, b).into_format();
+    return DATASET.classify_srgb(color).unwrap_or(0);
+}
+
+/// The id of the level 3 color of a Munsell specification like "7.5YR 6/8",
+/// or 0 if it isn't one or falls outside the solid.
+///
+/// # Safety
+///
+/// `spec` must be null or point to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn iscc_nbs_lookup_munsell(spec: *const c_char) -> u32 {
+    if spec.is_null() {
+        return 0;
+    }
+    let spec = match CStr::from_ptr(spec).to_str() {
+        Ok(v) => v,
+        Err(_) => return 0,
+    };
+    return MunsellColor::parse(spec)
+        .and_then(|x| DATASET.lookup_id(&x))
+        .unwrap_or(0);
+}
+
+/// The number of level 3 colors; their ids run from 1 to this.
+#[no_mangle]
+pub extern "C" fn iscc_nbs_name_count() -> u32 {
+    return DATASET.names().len() as u32;
+}
+
+/// The name of a level 3 color, like "Vivid pink", or null.
+#[no_mangle]
+pub extern "C" fn iscc_nbs_name(color_id: u32) -> *const c_char {
+    return NAMES
+        .names
+        .get(&color_id)
+        .map_or(std::ptr::null(), |x| x.as_ptr());
+}
+
+/// The abbreviation of a level 3 color, like "viv.Pk", or null.
+#[no_mangle]
+pub extern "C" fn iscc_nbs_abbr(color_id: u32) -> *const c_char {
+    return NAMES
+        .abbrs
+        .get(&color_id)
+        .map_or(std::ptr::null(), |x| x.as_ptr());
+}
+
+/// Write the representative sRGB color of a level 3 color into `rgb`, as
+/// three bytes; returns false, writing nothing, for unknown ids.
+///
+/// # Safety
+///
+/// `rgb` must point to at least three writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn iscc_nbs_srgb(color_id: u32, rgb: *mut u8) -> bool {
+    if rgb.is_null() || !DATASET.names().contains_key(&color_id) {
+        return false;
+    }
+    let c = NAMES.colors[(color_id - 1) as usize];
+    let out = std::slice::from_raw_parts_mut(rgb, 3);
+    out.copy_from_slice(&[c.red, c.green, c.blue]);
+    return true;
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::{CStr, CString};
+
+    use crate::ffi::{
+        iscc_nbs_abbr, iscc_nbs_lookup_munsell, iscc_nbs_lookup_rgb, iscc_nbs_name,
+        iscc_nbs_name_count, iscc_nbs_srgb,
+    };
+
+    #[test]
+    fn c_api() {
+        assert_eq!(iscc_nbs_name_count(), 267);
+        assert_eq!(iscc_nbs_lookup_rgb(0, 0, 0), 267);
+
+        let spec = CString::new("2R 7/12").unwrap();
+        let id = unsafe { iscc_nbs_lookup_munsell(spec.as_ptr()) };
+        assert_eq!(id, 1);
+        assert_eq!(unsafe { iscc_nbs_lookup_munsell(std::ptr::null()) }, 0);
+        let spec = CString::new("blue").unwrap();
+        assert_eq!(unsafe { iscc_nbs_lookup_munsell(spec.as_ptr()) }, 0);
+
+        let name = unsafe { CStr::from_ptr(iscc_nbs_name(1)) };
+        assert_eq!(name.to_str().unwrap(), "Vivid pink");
+        let abbr = unsafe { CStr::from_ptr(iscc_nbs_abbr(1)) };
+        assert_eq!(abbr.to_str().unwrap(), "viv.Pk");
+        assert!(iscc_nbs_name(0).is_null());
+        assert!(iscc_nbs_abbr(268).is_null());
+
+        let mut rgb = [0u8; 3];
+        assert!(unsafe { iscc_nbs_srgb(1, rgb.as_mut_ptr()) });
+        assert_eq!(rgb, [0xff, 0xc2, 0xc5]);
+        assert!(!unsafe { iscc_nbs_srgb(0, rgb.as_mut_ptr()) });
+    }
+}
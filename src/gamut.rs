@@ -0,0 +1,497 @@
+//! Which blocks of the color name charts lie outside the sRGB gamut, found
+//! by sampling points throughout each block, and where the edge of the
+//! gamut is on each hue leaf; and how colors outside of it are brought into
+//! it.
+//!
+//! The mapping used for every representative color is set once with
+//! [`set_gamut_mapping`], like the adaptation. Representative colors can
+//! also be mapped into the wider gamuts of [`crate::rgbspace`].
+
+use std::sync::OnceLock;
+
+use palette::convert::FromColorUnclamped;
+use palette::{Clamp, Lch, LinSrgb, Srgb};
+
+use crate::dataset::{ColorBlock, Dataset};
+use crate::munsell::{MunsellColor, MunsellHue};
+use crate::rgbspace::RgbSpace;
+
+static GAMUT_MAPPING: OnceLock<GamutMapping> = OnceLock::new();
+
+/// How a color outside the sRGB gamut is brought into it, in the polar form
+/// of a perceptual space: CIELAB LCh or Oklch.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GamutMapping {
+    /// Reduce the chroma by 1% at a time, keeping the lightness and hue,
+    /// until the color fits.
+    #[default]
+    ChromaClamp,
+    /// Move toward the gray with the lightness of the cusp, the most
+    /// saturated color of the hue that fits, reducing the chroma and
+    /// bringing the lightness closer to that of the cusp, keeping the hue.
+    /// Colors too light or dark for their chroma keep more of it.
+    TowardCusp,
+    /// Clamp each sRGB channel into `[0, 1]`, which keeps more chroma but
+    /// shifts the hue and lightness.
+    Clip,
+}
+
+/// Use `mapping` for all representative colors from now on. Only the first
+/// call has an effect, and only if no color has been mapped yet; otherwise
+/// the mapping already in use is returned as the error.
+pub fn set_gamut_mapping(mapping: GamutMapping) -> Result<(), GamutMapping> {
+    return GAMUT_MAPPING.set(mapping).map_err(|_| gamut_mapping());
+}
+
+/// The mapping representative colors are brought into sRGB with: the one
+/// given to [`set_gamut_mapping`], or [`GamutMapping::ChromaClamp`].
+pub fn gamut_mapping() -> GamutMapping {
+    return *GAMUT_MAPPING.get_or_init(GamutMapping::default);
+}
+
+/// A color of a polar perceptual space that fits into an RGB space, as what
+/// it was mapped to and how.
+pub(crate) struct MappedColor<C> {
+    pub color: C,
+    /// The color in the channels of the space it was mapped into.
+    pub rgb: Srgb,
+    /// How many times the chroma was reduced by 1%, with
+    /// [`GamutMapping::ChromaClamp`]; 0 with the other mappings.
+    pub steps: u32,
+}
+
+/// The largest share `t` from 0 to 1 for which `fits(t)` holds, taking it
+/// to hold at 0 and to hold up to some share and not past it.
+fn largest_fitting(fits: impl Fn(f32) -> bool) -> f32 {
+    const ITERATIONS: usize = 24;
+
+    if fits(1.0) {
+        return 1.0;
+    }
+    let mut low = 0.0;
+    let mut high = 1.0;
+    for _ in 0..ITERATIONS {
+        let middle = (low + high) / 2.0;
+        if fits(middle) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    return low;
+}
+
+/// The lightness of the cusp of the hue of `color`: the lightness at which
+/// the most chroma of that hue fits into `space`, to a hundredth of the
+/// range from black to white.
+fn cusp_lightness<C>(color: C, lc: &impl Fn(&mut C) -> (&mut f32, &mut f32), space: RgbSpace) -> f32
+where
+    LinSrgb: FromColorUnclamped<C>,
+    C: Copy + FromColorUnclamped<LinSrgb>,
+{
+    const STEPS: usize = 100;
+
+    let mut white = C::from_color_unclamped(LinSrgb::new(1.0, 1.0, 1.0));
+    let white = *lc(&mut white).0;
+    // past the chroma of any color of Rec. 2020, in either space
+    let reach = 2.0 * white;
+    let at = |l: f32, chroma: f32| {
+        let mut x = color;
+        let (x_l, x_chroma) = lc(&mut x);
+        (*x_l, *x_chroma) = (l, chroma);
+        return fits(x, space);
+    };
+    return (1..STEPS)
+        .map(|i| white * (i as f32) / (STEPS as f32))
+        .map(|l| (l, largest_fitting(|t| at(l, t * reach))))
+        .fold(
+            (white / 2.0, 0.0),
+            |best, x| if x.1 > best.1 { x } else { best },
+        )
+        .0;
+}
+
+/// The channels of `color` in `space`, which may be outside of `[0, 1]`.
+fn to_rgb<C>(color: C, space: RgbSpace) -> Srgb
+where
+    LinSrgb: FromColorUnclamped<C>,
+{
+    return space.from_linear_srgb(LinSrgb::from_color_unclamped(color));
+}
+
+fn fits<C>(color: C, space: RgbSpace) -> bool
+where
+    LinSrgb: FromColorUnclamped<C>,
+{
+    return to_rgb(color, space).is_within_bounds();
+}
+
+/// Bring a color of a polar perceptual space into `space` with `mapping`,
+/// where `lc` gives its lightness and chroma.
+pub(crate) fn map_into<C>(
+    mut color: C,
+    lc: impl Fn(&mut C) -> (&mut f32, &mut f32),
+    mapping: GamutMapping,
+    space: RgbSpace,
+) -> MappedColor<C>
+where
+    LinSrgb: FromColorUnclamped<C>,
+    C: Copy + FromColorUnclamped<LinSrgb>,
+{
+    let mut rgb = to_rgb(color, space);
+    let mut steps = 0;
+    if rgb.is_within_bounds() {
+        return MappedColor { color, rgb, steps };
+    }
+
+    match mapping {
+        GamutMapping::ChromaClamp => {
+            while !rgb.is_within_bounds() {
+                *lc(&mut color).1 *= 0.99;
+                steps += 1;
+                rgb = to_rgb(color, space);
+            }
+        }
+        GamutMapping::TowardCusp => {
+            let cusp = cusp_lightness(color, &lc, space);
+            let (l, chroma) = {
+                let (l, chroma) = lc(&mut color);
+                (*l, *chroma)
+            };
+            let toward = |t: f32| {
+                let mut x = color;
+                let (x_l, x_chroma) = lc(&mut x);
+                (*x_l, *x_chroma) = (cusp + t * (l - cusp), t * chroma);
+                return x;
+            };
+            let t = largest_fitting(|t| fits(toward(t), space));
+            color = toward(t);
+            // the last step of the search can still be a hair outside
+            rgb = to_rgb(color, space).clamp();
+        }
+        GamutMapping::Clip => {
+            rgb = rgb.clamp();
+            color = C::from_color_unclamped(space.to_linear_srgb(rgb));
+        }
+    }
+    return MappedColor { color, rgb, steps };
+}
+
+/// How much of a block lies within the sRGB gamut.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GamutCoverage {
+    Inside,
+    Partial,
+    Outside,
+}
+
+/// How much of a block lies outside the sRGB gamut.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockGamut {
+    /// The index of the block in [`Dataset::blocks`].
+    pub block: usize,
+    pub color_id: u32,
+    /// The share of the samples outside sRGB, from 0 to 1.
+    pub outside: f32,
+    /// The largest share of its CIELAB chroma any sample had to lose to fit
+    /// into sRGB, keeping its lightness and hue.
+    pub max_chroma_loss: f32,
+}
+
+impl BlockGamut {
+    pub fn coverage(&self) -> GamutCoverage {
+        if self.outside == 0.0 {
+            return GamutCoverage::Inside;
+        }
+        if self.outside == 1.0 {
+            return GamutCoverage::Outside;
+        }
+        return GamutCoverage::Partial;
+    }
+}
+
+/// Sample `samples` × `samples` × `samples` points throughout a block, at
+/// the centers of a grid of cells.
+///
+/// Boundaries at `INF` are cut off at chroma 16 and value 10.
+fn block_samples(dataset: &Dataset, block: &ColorBlock, samples: usize) -> Vec<MunsellColor> {
+    let hues = dataset.hues();
+    let chromas = dataset.chromas();
+    let values = dataset.values();
+
+    let hue_begin = hues[block.hues.start].parse::<MunsellHue>().unwrap();
+    let hue_end = hues[block.hues.end].parse::<MunsellHue>().unwrap();
+    let mut hue_width = (hue_end.raw() - hue_begin.raw()).rem_euclid(100.0);
+    if hue_width == 0.0 {
+        hue_width = 100.0;
+    }
+    let chroma_begin: f32 = chromas[block.chromas.start].parse().unwrap();
+    let chroma_end = chromas[block.chromas.end].parse::<f32>().unwrap().min(16.0);
+    let value_begin: f32 = values[block.values.start].parse().unwrap();
+    let value_end = values[block.values.end].parse::<f32>().unwrap().min(10.0);
+
+    let step = |i: usize| ((i as f32) + 0.5) / (samples as f32);
+    let mut points = Vec::with_capacity(samples * samples * samples);
+    for h in 0..samples {
+        for c in 0..samples {
+            for v in 0..samples {
+                points.push(MunsellColor::new(
+                    hue_begin + step(h) * hue_width,
+                    value_begin + step(v) * (value_end - value_begin),
+                    chroma_begin + step(c) * (chroma_end - chroma_begin),
+                ));
+            }
+        }
+    }
+    return points;
+}
+
+/// The share of its chroma a color has to lose to fit into sRGB, keeping
+/// its lightness and hue; `1` if even the gray of its lightness doesn't.
+fn chroma_loss(lch: Lch) -> f32 {
+    const ITERATIONS: usize = 24;
+
+    if Srgb::from_color_unclamped(lch).is_within_bounds() {
+        return 0.0;
+    }
+
+    // the largest share of the chroma known to fit, and the smallest not to
+    let mut low = 0.0;
+    let mut high = 1.0;
+    for _ in 0..ITERATIONS {
+        let middle = (low + high) / 2.0;
+        let reduced = Lch::new(lch.l, lch.chroma * middle, lch.hue);
+        if Srgb::from_color_unclamped(reduced).is_within_bounds() {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    return 1.0 - low;
+}
+
+/// The highest chroma [`max_srgb_chroma`] looks for, past the most
+/// saturated colors of sRGB.
+pub const MAX_CHROMA: f32 = 32.0;
+
+/// The largest Munsell chroma of `hue` at `value` that is within the sRGB
+/// gamut, to a hundredth of a step; `0` if even the gray of `value` isn't,
+/// and for black and white.
+///
+/// Chromas past [`MAX_CHROMA`] aren't searched.
+pub fn max_srgb_chroma(hue: MunsellHue, value: f32) -> f32 {
+    const ITERATIONS: usize = 12;
+
+    if value <= 0.0 || value >= 10.0 {
+        return 0.0;
+    }
+
+    let inside = |chroma: f32| {
+        let lch = MunsellColor::new(hue, value, chroma).to_display_lch();
+        return Srgb::from_color_unclamped(lch).is_within_bounds();
+    };
+    if !inside(0.0) {
+        return 0.0;
+    }
+    if inside(MAX_CHROMA) {
+        return MAX_CHROMA;
+    }
+
+    // the largest chroma known to fit, and the smallest not to
+    let mut low = 0.0;
+    let mut high = MAX_CHROMA;
+    for _ in 0..ITERATIONS {
+        let middle = (low + high) / 2.0;
+        if inside(middle) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    return low;
+}
+
+impl Dataset {
+    /// The edge of the sRGB gamut on the hue leaf starting at hue index `h`,
+    /// at the middle hue of the leaf: the largest chroma in the gamut at
+    /// every tenth of a value step, as (chroma, value) pairs from black to
+    /// white.
+    pub fn leaf_gamut_boundary(&self, h: usize) -> Vec<(f32, f32)> {
+        let leaf = &self.leaves()[h];
+        let hue = leaf.begin.midpoint(leaf.end);
+        return (0..=100)
+            .map(|i| {
+                let value = (i as f32) / 10.0;
+                return (max_srgb_chroma(hue, value), value);
+            })
+            .collect();
+    }
+
+    /// Find how much of every block lies outside the sRGB gamut, by
+    /// converting `samples` × `samples` × `samples` points throughout each
+    /// block, in the order of [`Dataset::blocks`].
+    pub fn block_gamut(&self, samples: usize) -> Vec<BlockGamut> {
+        return self
+            .blocks()
+            .iter()
+            .enumerate()
+            .map(|(i, block)| {
+                let losses = block_samples(self, block, samples.max(1))
+                    .iter()
+                    .map(|x| chroma_loss(x.to_display_lch()))
+                    .collect::<Vec<f32>>();
+                let outside = losses.iter().filter(|x| **x > 0.0).count();
+
+                return BlockGamut {
+                    block: i,
+                    color_id: block.color_id,
+                    outside: (outside as f32) / (losses.len() as f32),
+                    max_chroma_loss: losses.iter().copied().fold(0.0, f32::max),
+                };
+            })
+            .collect();
+    }
+
+    /// Find the most saturated color of every level 3 name that sRGB can
+    /// show, indexed by `color_id - 1`: the highest Munsell chroma within
+    /// its blocks and the gamut at `samples` × `samples` hues and values
+    /// throughout each block. `None` for names without any.
+    pub fn max_chroma_in_gamut(&self, samples: usize) -> Vec<Option<MunsellColor>> {
+        let samples = samples.max(1);
+        let step = |i: usize| ((i as f32) + 0.5) / (samples as f32);
+        let mut colors: Vec<Option<MunsellColor>> = vec![None; self.names().len()];
+        for block in self.blocks() {
+            let parse = |x: &[String], i: usize| x[i].parse::<f32>().unwrap();
+            let hue_begin = self.hues()[block.hues.start].parse::<MunsellHue>().unwrap();
+            let hue_end = self.hues()[block.hues.end].parse::<MunsellHue>().unwrap();
+            let mut hue_width = (hue_end.raw() - hue_begin.raw()).rem_euclid(100.0);
+            if hue_width == 0.0 {
+                hue_width = 100.0;
+            }
+            let chroma_begin = parse(self.chromas(), block.chromas.start);
+            // blocks end just below their upper boundaries
+            let chroma_end = parse(self.chromas(), block.chromas.end) - 0.01;
+            let value_begin = parse(self.values(), block.values.start);
+            let value_end = parse(self.values(), block.values.end).min(10.0);
+
+            let best = &mut colors[(block.color_id - 1) as usize];
+            for h in 0..samples {
+                let hue = hue_begin + step(h) * hue_width;
+                for v in 0..samples {
+                    let value = value_begin + step(v) * (value_end - value_begin);
+                    let chroma = max_srgb_chroma(hue, value).min(chroma_end);
+                    if chroma < chroma_begin || best.as_ref().is_some_and(|x| x.chroma >= chroma) {
+                        continue;
+                    }
+                    *best = Some(MunsellColor::new(hue, value, chroma));
+                }
+            }
+        }
+        return colors;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use palette::{Clamp, Lch};
+
+    use crate::colors::get_mean_munsell;
+    use crate::gamut::{
+        cusp_lightness, gamut_mapping, map_into, max_srgb_chroma, set_gamut_mapping, GamutCoverage,
+        GamutMapping,
+    };
+    use crate::rgbspace::RgbSpace;
+    use crate::{Dataset, MunsellHue};
+
+    #[test]
+    fn gamut_mappings() {
+        fn lc(x: &mut Lch) -> (&mut f32, &mut f32) {
+            return (&mut x.l, &mut x.chroma);
+        }
+        // yellow is lightest at its most saturated, and blue darkest
+        let yellow: MunsellHue = "5Y".parse().unwrap();
+        let blue: MunsellHue = "10PB".parse().unwrap();
+        let cusp = |hue: MunsellHue| {
+            let lch = crate::MunsellColor::new(hue, 5.0, 10.0).to_display_lch();
+            return cusp_lightness(lch, &lc, RgbSpace::Srgb);
+        };
+        assert!(cusp(yellow) > 80.0);
+        assert!(cusp(blue) < 45.0);
+
+        // vivid pink is outside, and every mapping brings it in
+        let pink = get_mean_munsell(&Dataset::bundled())[0].to_display_lch();
+        let clamped = map_into(pink, lc, GamutMapping::ChromaClamp, RgbSpace::Srgb);
+        let cusped = map_into(pink, lc, GamutMapping::TowardCusp, RgbSpace::Srgb);
+        let clipped = map_into(pink, lc, GamutMapping::Clip, RgbSpace::Srgb);
+        for mapped in [&clamped, &cusped, &clipped] {
+            assert!(mapped.rgb.is_within_bounds());
+            assert!(mapped.color.chroma < pink.chroma);
+        }
+        assert!(clamped.steps > 0 && cusped.steps == 0);
+        assert_eq!(clamped.color.l, pink.l);
+        assert_eq!(cusped.color.hue, pink.hue);
+        assert_ne!(cusped.color.l, pink.l);
+        // clamping the channels keeps more chroma, for another hue
+        assert!(clipped.color.chroma > clamped.color.chroma);
+        assert_ne!(clipped.color.hue, pink.hue);
+        // the wider gamuts keep more of it
+        let p3 = map_into(pink, lc, GamutMapping::ChromaClamp, RgbSpace::DisplayP3);
+        let rec2020 = map_into(pink, lc, GamutMapping::ChromaClamp, RgbSpace::Rec2020);
+        assert!(p3.rgb.is_within_bounds() && rec2020.rgb.is_within_bounds());
+        assert!(clamped.color.chroma < p3.color.chroma);
+        assert!(p3.color.chroma <= rec2020.color.chroma);
+
+        // colors inside are left as they are
+        let gray = Lch::new(50.0, 0.0, 0.0);
+        assert_eq!(
+            map_into(gray, lc, GamutMapping::TowardCusp, RgbSpace::Srgb).color,
+            gray
+        );
+    }
+
+    #[test]
+    fn block_coverage() {
+        let dataset = Dataset::bundled();
+        let gamut = dataset.block_gamut(2);
+        assert_eq!(gamut.len(), dataset.blocks().len());
+
+        // the grays are inside, and some of the vivid colors are not
+        let neutrals = dataset.neutral_ids();
+        assert!(gamut
+            .iter()
+            .filter(|x| neutrals.contains(&x.color_id))
+            .all(|x| x.coverage() == GamutCoverage::Inside));
+        assert!(gamut
+            .iter()
+            .any(|x| x.coverage() == GamutCoverage::Outside && x.color_id == 1));
+        assert!(gamut
+            .iter()
+            .all(|x| (x.max_chroma_loss > 0.0) == (x.outside > 0.0)));
+    }
+
+    #[test]
+    fn leaf_boundary() {
+        // yellows reach their highest chroma in the light values, and
+        // purple blues in the dark ones
+        let yellow = "5Y".parse::<MunsellHue>().unwrap();
+        let blue = "7.5PB".parse::<MunsellHue>().unwrap();
+        assert!(max_srgb_chroma(yellow, 8.5) > max_srgb_chroma(yellow, 3.0));
+        assert!(max_srgb_chroma(blue, 3.0) > max_srgb_chroma(blue, 8.5));
+
+        let dataset = Dataset::bundled();
+        let boundary = dataset.leaf_gamut_boundary(0);
+        assert_eq!(boundary.len(), 101);
+        assert_eq!(boundary[50].1, 5.0);
+        assert!(boundary[50].0 > 12.0 && boundary[50].0 < 24.0);
+        // black and white are at the ends
+        assert_eq!((boundary[0].0, boundary[100].0), (0.0, 0.0));
+        assert!(boundary[1].0 < 2.0 && boundary[99].0 < 2.0);
+    }
+
+    #[test]
+    fn set_after_mapping() {
+        // mapping a color settles the gamut mapping
+        let current = gamut_mapping();
+        assert_eq!(set_gamut_mapping(GamutMapping::Clip), Err(current));
+    }
+}
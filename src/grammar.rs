@@ -0,0 +1,324 @@
+//! The grammar of the names: an optional modifier of lightness and
+//! saturation, like "very light" or "dark grayish", followed by a hue name,
+//! like "purplish pink" or "bluish gray". Only level 3 names have
+//! modifiers.
+
+use crate::munsell::MunsellHue;
+
+/// The hues each hue name of the level 1 and level 2 names can cover, from
+/// the first hue up around the circle to the second. These are generous,
+/// reaching past the charts to the next multiple of 5; a level 1 name
+/// covers the hues of all of its level 2 names. The neutrals can be any hue.
+const HUE_SPANS: &[(&str, &str, &str)] = &[
+    ("pink", "5P", "10YR"),
+    ("red", "1RP", "5YR"),
+    ("yellowish pink", "1R", "10YR"),
+    ("reddish orange", "5R", "5YR"),
+    ("reddish brown", "5R", "10YR"),
+    ("orange", "5R", "10YR"),
+    ("brown", "5R", "5Y"),
+    ("orange yellow", "5YR", "5Y"),
+    ("yellowish brown", "5YR", "5Y"),
+    ("yellow", "5YR", "5GY"),
+    ("olive brown", "10YR", "5Y"),
+    ("greenish yellow", "5Y", "5GY"),
+    ("olive", "10YR", "10GY"),
+    ("yellow green", "5Y", "10GY"),
+    ("olive green", "10Y", "10GY"),
+    ("yellowish green", "5GY", "5G"),
+    ("green", "10Y", "5B"),
+    ("bluish green", "5G", "5B"),
+    ("greenish blue", "5BG", "10B"),
+    ("blue", "5BG", "10PB"),
+    ("purplish blue", "1PB", "10PB"),
+    ("violet", "5PB", "5P"),
+    ("purple", "5PB", "5R"),
+    ("reddish purple", "5P", "5RP"),
+    ("purplish pink", "5P", "10RP"),
+    ("purplish red", "1RP", "5R"),
+];
+
+/// The modifiers of the level 3 names.
+const MODIFIERS: &[&str] = &[
+    "vivid",
+    "brilliant",
+    "strong",
+    "deep",
+    "very deep",
+    "very light",
+    "light",
+    "moderate",
+    "medium",
+    "dark",
+    "very dark",
+    "pale",
+    "very pale",
+    "light grayish",
+    "grayish",
+    "dark grayish",
+    "blackish",
+];
+
+/// The hue names of the level 2 names.
+const HUES: &[&str] = &[
+    "pink",
+    "red",
+    "yellowish pink",
+    "reddish orange",
+    "reddish brown",
+    "orange",
+    "brown",
+    "orange yellow",
+    "yellowish brown",
+    "yellow",
+    "olive brown",
+    "greenish yellow",
+    "olive",
+    "yellow green",
+    "olive green",
+    "yellowish green",
+    "green",
+    "bluish green",
+    "greenish blue",
+    "blue",
+    "purplish blue",
+    "violet",
+    "purple",
+    "reddish purple",
+    "purplish pink",
+    "purplish red",
+    "white",
+    "gray",
+    "black",
+];
+
+/// Hue names that only appear at level 3: pink and orange tinged with
+/// brown, and the tinted neutrals, like "pinkish white" or "olive black".
+const TINTED_HUES: &[&str] = &["brownish pink", "brownish orange"];
+const TINTS: &[&str] = &[
+    "pinkish",
+    "reddish",
+    "brownish",
+    "yellowish",
+    "olive",
+    "greenish",
+    "bluish",
+    "purplish",
+];
+const NEUTRALS: &[&str] = &["white", "gray", "black"];
+
+/// The abbreviations of the modifiers, each ending in a period.
+const MODIFIER_ABBRS: &[(&str, &str)] = &[
+    ("vivid", "viv."),
+    ("brilliant", "brill."),
+    ("strong", "s."),
+    ("deep", "dp."),
+    ("very deep", "v.dp."),
+    ("very light", "v.l."),
+    ("light", "l."),
+    ("moderate", "m."),
+    ("medium", "m."),
+    ("dark", "d."),
+    ("very dark", "v.d."),
+    ("pale", "p."),
+    ("very pale", "v.p."),
+    ("light grayish", "l.gy."),
+    ("grayish", "gy."),
+    ("dark grayish", "d.gy."),
+    ("blackish", "bk."),
+];
+
+/// The abbreviations of the hue nouns; the "-ish" forms are abbreviated
+/// like their nouns, in lowercase.
+const HUE_ABBRS: &[(&str, &str)] = &[
+    ("pink", "Pk"),
+    ("red", "R"),
+    ("orange", "O"),
+    ("brown", "Br"),
+    ("yellow", "Y"),
+    ("olive", "Ol"),
+    ("green", "G"),
+    ("blue", "B"),
+    ("violet", "V"),
+    ("purple", "P"),
+    ("white", "Wh"),
+    ("gray", "Gy"),
+    ("black", "Bk"),
+];
+
+/// The nouns of the "-ish" forms of the hues.
+const ISH_NOUNS: &[(&str, &str)] = &[
+    ("pinkish", "pink"),
+    ("reddish", "red"),
+    ("brownish", "brown"),
+    ("yellowish", "yellow"),
+    ("greenish", "green"),
+    ("bluish", "blue"),
+    ("purplish", "purple"),
+];
+
+fn lookup(table: &[(&str, &'static str)], key: &str) -> Option<&'static str> {
+    return table.iter().find(|x| x.0 == key).map(|x| x.1);
+}
+
+/// A name, split into its parts.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParsedName {
+    pub modifier: Option<&'static str>,
+    pub hue: String,
+}
+
+impl ParsedName {
+    /// The standard abbreviation of this name, like "v.l.pB" for "Very
+    /// light purplish blue": the abbreviation of the modifier, then those
+    /// of the words of the hue name.
+    pub fn abbr(&self) -> String {
+        let mut abbr = match self.modifier {
+            Some(x) => lookup(MODIFIER_ABBRS, x).unwrap().to_string(),
+            None => String::new(),
+        };
+        for word in self.hue.split(' ') {
+            match lookup(ISH_NOUNS, word) {
+                Some(noun) => abbr += &lookup(HUE_ABBRS, noun).unwrap().to_lowercase(),
+                None => abbr += lookup(HUE_ABBRS, word).unwrap(),
+            }
+        }
+        return abbr;
+    }
+}
+
+fn is_hue(text: &str) -> bool {
+    if HUES.contains(&text) || TINTED_HUES.contains(&text) {
+        return true;
+    }
+    return match text.split_once(' ') {
+        Some((tint, neutral)) => TINTS.contains(&tint) && NEUTRALS.contains(&neutral),
+        None => false,
+    };
+}
+
+/// Split a name like "Very light purplish blue" into its modifier
+/// and hue name, if it follows the grammar.
+///
+/// Names are in sentence case; anything else, like "Vivid Pink", does not
+/// parse.
+pub fn parse_name(name: &str) -> Option<ParsedName> {
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    if !first.is_uppercase() || chars.any(|x| x.is_uppercase()) {
+        return None;
+    }
+
+    return parse_words(&name.to_lowercase());
+}
+
+/// Parse a name as people write it, like "LIGHT  yellowish-brown" or "dark
+/// greyish blue": in any case, with any whitespace or hyphens between the
+/// words, and "grey" for "gray".
+pub fn parse_name_loosely(text: &str) -> Option<ParsedName> {
+    let words = text
+        .split(|x: char| x.is_whitespace() || x == '-')
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_lowercase().replacen("grey", "gray", 1))
+        .collect::<Vec<String>>();
+    return parse_words(&words.join(" "));
+}
+
+/// Split a lowercase name with single spaces between its words into its
+/// modifier and hue name.
+fn parse_words(name: &str) -> Option<ParsedName> {
+    if is_hue(name) {
+        return Some(ParsedName {
+            modifier: None,
+            hue: name.to_string(),
+        });
+    }
+
+    // "light grayish red" only parses with the longer modifier
+    return MODIFIERS.iter().find_map(|modifier| {
+        let hue = name.strip_prefix(modifier)?.strip_prefix(' ')?;
+        if !is_hue(hue) {
+            return None;
+        }
+        return Some(ParsedName {
+            modifier: Some(modifier),
+            hue: hue.to_string(),
+        });
+    });
+}
+
+/// The hues a level 1 or level 2 hue name, like "purplish blue", can
+/// cover, or `None` for the neutrals and names outside the grammar.
+pub fn hue_span(hue: &str) -> Option<(MunsellHue, MunsellHue)> {
+    let (_, begin, end) = HUE_SPANS.iter().find(|x| x.0 == hue)?;
+    return Some((
+        begin.parse::<MunsellHue>().unwrap(),
+        end.parse::<MunsellHue>().unwrap(),
+    ));
+}
+
+#[cfg(test)]
+mod test {
+    use crate::grammar::{hue_span, parse_name, parse_name_loosely, ParsedName, HUES, NEUTRALS};
+
+    #[test]
+    fn parse_names() {
+        let parsed = |modifier: Option<&'static str>, hue: &str| ParsedName {
+            modifier,
+            hue: hue.to_string(),
+        };
+
+        assert_eq!(
+            parse_name("Very light purplish blue"),
+            Some(parsed(Some("very light"), "purplish blue"))
+        );
+        assert_eq!(
+            parse_name("Light grayish red"),
+            Some(parsed(Some("light grayish"), "red"))
+        );
+        assert_eq!(
+            parse_name("Dark bluish gray"),
+            Some(parsed(Some("dark"), "bluish gray"))
+        );
+        assert_eq!(parse_name("Black"), Some(parsed(None, "black")));
+
+        assert_eq!(parse_name("Vivd pink"), None);
+        assert_eq!(parse_name("Vivid Pink"), None);
+        assert_eq!(parse_name("Vivid pinkish"), None);
+        assert_eq!(parse_name("Vivid  pink"), None);
+        assert_eq!(parse_name(""), None);
+
+        // as people write them
+        assert_eq!(
+            parse_name_loosely("  LIGHT yellowish-brown\t"),
+            parse_name("Light yellowish brown")
+        );
+        assert_eq!(
+            parse_name_loosely("dark greyish blue"),
+            parse_name("Dark grayish blue")
+        );
+        assert_eq!(parse_name_loosely("Vivid Pink"), parse_name("Vivid pink"));
+        assert_eq!(parse_name_loosely("vivid pinkish"), None);
+        assert_eq!(parse_name_loosely(" "), None);
+    }
+
+    #[test]
+    fn abbreviations() {
+        let abbr = |name: &str| parse_name(name).unwrap().abbr();
+
+        assert_eq!(abbr("Very light purplish blue"), "v.l.pB");
+        assert_eq!(abbr("Light grayish reddish brown"), "l.gy.rBr");
+        assert_eq!(abbr("Pinkish white"), "pkWh");
+        assert_eq!(abbr("Olive gray"), "OlGy");
+        assert_eq!(abbr("Brilliant orange yellow"), "brill.OY");
+        assert_eq!(abbr("Blackish purple"), "bk.P");
+    }
+
+    #[test]
+    fn hue_spans() {
+        for hue in HUES {
+            assert_eq!(hue_span(hue).is_none(), NEUTRALS.contains(hue), "{}", hue);
+        }
+        assert_eq!(hue_span("very light blue"), None);
+    }
+}
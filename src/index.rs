@@ -0,0 +1,169 @@
+//! A k-d tree over the centroids of the level 3 colors in CIELAB, for
+//! finding the nearest one to many colors, like every pixel of an image,
+//! without comparing each color with all 267 centroids.
+
+use palette::Lab;
+
+use crate::colors::get_mean_munsell;
+use crate::dataset::Dataset;
+
+struct Node {
+    point: [f32; 3],
+    color_id: u32,
+    /// The coordinate this node splits its children by: L*, a* or b*.
+    axis: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+/// The centroids of [`get_mean_munsell`], indexed for nearest-neighbor
+/// queries by CIE76 color difference, the Euclidean distance in CIELAB.
+pub struct CentroidIndex {
+    nodes: Vec<Node>,
+    root: Option<usize>,
+}
+
+fn lab_to_point(lab: Lab) -> [f32; 3] {
+    return [lab.l, lab.a, lab.b];
+}
+
+fn distance_squared(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    return (0..3).map(|i| (a[i] - b[i]).powi(2)).sum();
+}
+
+impl CentroidIndex {
+    /// Index the centroids of a dataset.
+    pub fn new(dataset: &Dataset) -> Self {
+        let points = get_mean_munsell(dataset)
+            .iter()
+            .enumerate()
+            .map(|(i, x)| ((i + 1) as u32, lab_to_point(x.to_lab())))
+            .collect::<Vec<(u32, [f32; 3])>>();
+        return Self::from_points(points);
+    }
+
+    fn from_points(mut points: Vec<(u32, [f32; 3])>) -> Self {
+        let mut index = CentroidIndex {
+            nodes: Vec::with_capacity(points.len()),
+            root: None,
+        };
+        index.root = index.build(&mut points, 0);
+        return index;
+    }
+
+    /// Add the points to the tree, split at the median of `axis`, returning
+    /// the index of the node at the top.
+    fn build(&mut self, points: &mut [(u32, [f32; 3])], axis: usize) -> Option<usize> {
+        if points.is_empty() {
+            return None;
+        }
+
+        points.sort_by(|a, b| a.1[axis].total_cmp(&b.1[axis]));
+        let median = points.len() / 2;
+        let (color_id, point) = points[median];
+
+        let (below, above) = points.split_at_mut(median);
+        let left = self.build(below, (axis + 1) % 3);
+        let right = self.build(&mut above[1..], (axis + 1) % 3);
+
+        self.nodes.push(Node {
+            point,
+            color_id,
+            axis,
+            left,
+            right,
+        });
+        return Some(self.nodes.len() - 1);
+    }
+
+    fn search(&self, node: Option<usize>, target: &[f32; 3], best: &mut Option<(u32, f32)>) {
+        let node = match node {
+            Some(x) => &self.nodes[x],
+            None => return,
+        };
+
+        let distance = distance_squared(&node.point, target);
+        if best.is_none_or(|x| distance < x.1) {
+            *best = Some((node.color_id, distance));
+        }
+
+        // look on the side of the split the target is on first, and only
+        // on the other side if it could hold anything nearer
+        let offset = target[node.axis] - node.point[node.axis];
+        let (near, far) = if offset < 0.0 {
+            (node.left, node.right)
+        } else {
+            (node.right, node.left)
+        };
+        self.search(near, target, best);
+        if best.is_none_or(|x| offset * offset < x.1) {
+            self.search(far, target, best);
+        }
+    }
+
+    /// Find the level 3 color whose centroid is nearest to `lab`, and the
+    /// CIE76 color difference between them.
+    pub fn nearest(&self, lab: Lab) -> (u32, f32) {
+        let mut best = None;
+        self.search(self.root, &lab_to_point(lab), &mut best);
+        let (color_id, distance) = best.expect("index should not be empty");
+        return (color_id, distance.sqrt());
+    }
+
+    /// Like [`CentroidIndex::nearest`], for every color of `colors`.
+    pub fn nearest_all(&self, colors: &[Lab]) -> Vec<(u32, f32)> {
+        return colors.iter().map(|x| self.nearest(*x)).collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use palette::Lab;
+
+    use crate::colors::get_mean_munsell;
+    use crate::index::CentroidIndex;
+    use crate::random::Rng;
+    use crate::Dataset;
+
+    #[test]
+    fn matches_linear_search() {
+        let dataset = Dataset::bundled();
+        let index = CentroidIndex::new(&dataset);
+        let centroids = get_mean_munsell(&dataset)
+            .iter()
+            .map(|x| x.to_lab())
+            .collect::<Vec<Lab>>();
+
+        let mut rng = Rng::new(1);
+        let mut uniform = |min: f64, max: f64| (min + (max - min) * rng.next_f64()) as f32;
+        let colors = (0..500)
+            .map(|_| {
+                Lab::new(
+                    uniform(0.0, 100.0),
+                    uniform(-100.0, 100.0),
+                    uniform(-100.0, 100.0),
+                )
+            })
+            .collect::<Vec<Lab>>();
+
+        for (color, (id, distance)) in colors.iter().zip(index.nearest_all(&colors)) {
+            let linear = centroids
+                .iter()
+                .map(|x| {
+                    ((x.l - color.l).powi(2) + (x.a - color.a).powi(2) + (x.b - color.b).powi(2))
+                        .sqrt()
+                })
+                .fold(f32::INFINITY, f32::min);
+            assert!((distance - linear).abs() < 1e-3);
+            let centroid = centroids[(id - 1) as usize];
+            let own = ((centroid.l - color.l).powi(2)
+                + (centroid.a - color.a).powi(2)
+                + (centroid.b - color.b).powi(2))
+            .sqrt();
+            assert!((own - distance).abs() < 1e-3);
+        }
+
+        // a centroid is its own nearest
+        assert_eq!(index.nearest(centroids[41]), (42, 0.0));
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+
+use crate::dataset::Dataset;
+use crate::munsell::MunsellHue;
+
+/// One hue leaf of the color name charts: the span of hues between two
+/// adjacent entries of [`Dataset::hues`]. A leaf includes its first hue but
+/// not its last.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HueLeaf {
+    /// Index of the first hue of this leaf in [`Dataset::hues`].
+    pub index: usize,
+    pub begin: MunsellHue,
+    pub end: MunsellHue,
+    /// The name of `begin` as written in the dataset, like `9RP`.
+    pub begin_name: String,
+    /// The name of `end` as written in the dataset, like `1R`.
+    pub end_name: String,
+}
+
+impl HueLeaf {
+    /// Whether `hue` falls within this leaf, accounting for the leaf that
+    /// wraps around from RP to R.
+    pub fn contains(&self, hue: MunsellHue) -> bool {
+        return arc_contains(self.begin.raw(), self.end.raw(), hue.raw());
+    }
+
+    /// The width of this leaf, in Munsell hue steps.
+    pub fn width(&self) -> f32 {
+        return (self.end.raw() - self.begin.raw() + 100.0) % 100.0;
+    }
+}
+
+impl fmt::Display for HueLeaf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.begin_name, self.end_name)
+    }
+}
+
+/// Whether `point` lies on the arc from `begin` (inclusive) to `end`
+/// (exclusive) going up around the hue circle.
+pub(crate) fn arc_contains(begin: f32, end: f32, point: f32) -> bool {
+    if begin < end {
+        return point >= begin && point < end;
+    } else {
+        return point >= begin || point < end;
+    }
+}
+
+/// Whether the arc from `begin` to `end` lies within the arc from
+/// `outer_begin` to `outer_end`, both going up around the hue circle. Arcs
+/// that begin where they end are the whole circle.
+pub(crate) fn arc_within(begin: f32, end: f32, outer_begin: f32, outer_end: f32) -> bool {
+    let length = |from: f32, to: f32| {
+        let x = (to - from + 100.0) % 100.0;
+        return if x == 0.0 { 100.0 } else { x };
+    };
+    let outer = length(outer_begin, outer_end);
+    if outer == 100.0 {
+        return true;
+    }
+    let offset = (begin - outer_begin + 100.0) % 100.0;
+    return offset + length(begin, end) <= outer;
+}
+
+pub(crate) fn build_leaves(hues: &[String]) -> Vec<HueLeaf> {
+    return (0..hues.len())
+        .map(|h| {
+            let next = (h + 1) % hues.len();
+
+            return HueLeaf {
+                index: h,
+                begin: hues[h].parse::<MunsellHue>().unwrap(),
+                end: hues[next].parse::<MunsellHue>().unwrap(),
+                begin_name: hues[h].clone(),
+                end_name: hues[next].clone(),
+            };
+        })
+        .collect();
+}
+
+impl Dataset {
+    /// Find the leaf that `hue` falls into.
+    pub fn leaf_containing(&self, hue: MunsellHue) -> &HueLeaf {
+        return self
+            .leaves()
+            .iter()
+            .find(|leaf| leaf.contains(hue))
+            .expect("hue leaves should cover the whole hue circle");
+    }
+
+    /// Find the leaf that starts at the hue given by `huespec`.
+    ///
+    /// Equivalent notations of the same hue are accepted, so `0B` finds the
+    /// leaf starting at `10BG`.
+    pub fn leaf_starting_at(&self, huespec: &str) -> Option<&HueLeaf> {
+        let hue = MunsellHue::parse(huespec)?;
+
+        return self
+            .leaves()
+            .iter()
+            .find(|leaf| (leaf.begin.raw() - hue.raw()).abs() < 0.0001);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::leaf::arc_within;
+    use crate::{Dataset, MunsellHue};
+
+    #[test]
+    fn leaf_contains() {
+        let dataset = Dataset::bundled();
+        let leaf = dataset.leaf_starting_at("9RP").unwrap();
+
+        assert_eq!(leaf.to_string(), "9RP-1R");
+        assert!(leaf.contains("9RP".parse::<MunsellHue>().unwrap()));
+        assert!(leaf.contains("10RP".parse::<MunsellHue>().unwrap()));
+        assert!(leaf.contains("0.5R".parse::<MunsellHue>().unwrap()));
+        assert!(!leaf.contains("1R".parse::<MunsellHue>().unwrap()));
+        assert!((leaf.width() - 2.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn arcs_within_arcs() {
+        let arc = |begin: &str, end: &str| {
+            (
+                begin.parse::<MunsellHue>().unwrap().raw(),
+                end.parse::<MunsellHue>().unwrap().raw(),
+            )
+        };
+        let within =
+            |inner: (f32, f32), outer: (f32, f32)| arc_within(inner.0, inner.1, outer.0, outer.1);
+
+        assert!(within(arc("1R", "4R"), arc("1R", "5YR")));
+        assert!(within(arc("9RP", "1R"), arc("1RP", "5R")));
+        assert!(!within(arc("9RP", "1R"), arc("1R", "5YR")));
+        assert!(!within(arc("1R", "7YR"), arc("1R", "5YR")));
+        // the whole circle is only within itself
+        assert!(within(arc("1R", "1R"), arc("5P", "5P")));
+        assert!(!within(arc("1R", "1R"), arc("1R", "9RP")));
+    }
+
+    #[test]
+    fn leaf_aliases() {
+        let dataset = Dataset::bundled();
+
+        assert_eq!(dataset.leaf_starting_at("0B").unwrap().begin_name, "10BG");
+        assert_eq!(dataset.leaf_starting_at("10BG").unwrap().index, 21);
+        assert_eq!(
+            dataset
+                .leaf_containing("0R".parse::<MunsellHue>().unwrap())
+                .begin_name,
+            "9RP"
+        );
+        assert!(dataset.leaf_starting_at("2R").is_none());
+        assert!(dataset.leaf_starting_at("bogus").is_none());
+    }
+}
@@ -0,0 +1,121 @@
+// Library for working with the ISCC-NBS System of Color Designation, as
+// described by iscc-nbs.xml.
+//
+// SPDX-License-Identifier: MIT
+
+#![allow(clippy::needless_return)]
+// Without `std`, only the lookup core of `lite` is built, for embedded
+// devices.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate is_sorted;
+
+#[cfg(feature = "std")]
+pub mod accuracy;
+#[cfg(feature = "std")]
+pub mod adaptation;
+#[cfg(feature = "std")]
+pub mod canonical;
+#[cfg(feature = "std")]
+pub mod centroids;
+#[cfg(feature = "std")]
+pub mod classify;
+#[cfg(feature = "std")]
+pub mod colors;
+#[cfg(feature = "std")]
+pub mod compare;
+pub mod compiled;
+#[cfg(feature = "std")]
+pub mod cvd;
+#[cfg(feature = "std")]
+pub mod dataset;
+#[cfg(feature = "std")]
+pub mod degree;
+#[cfg(feature = "std")]
+pub mod diagnostic;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod edit;
+#[cfg(feature = "std")]
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod gamut;
+#[cfg(feature = "std")]
+pub mod grammar;
+#[cfg(feature = "std")]
+pub mod index;
+#[cfg(feature = "std")]
+pub mod leaf;
+pub mod lite;
+#[cfg(feature = "std")]
+pub mod lookup;
+#[cfg(feature = "std")]
+pub mod munsell;
+#[cfg(feature = "std")]
+pub mod naming;
+#[cfg(feature = "std")]
+pub mod neutral;
+#[cfg(feature = "charts")]
+pub mod plate;
+#[cfg(feature = "charts")]
+pub mod plot;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "std")]
+pub mod random;
+#[cfg(feature = "std")]
+pub mod renotation;
+#[cfg(feature = "std")]
+pub mod repl;
+#[cfg(feature = "std")]
+pub mod rgbspace;
+#[cfg(feature = "std")]
+pub mod search;
+#[cfg(feature = "std")]
+pub mod solid;
+pub mod spans;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod synonyms;
+#[cfg(feature = "std")]
+pub mod tolerance;
+#[cfg(feature = "std")]
+pub mod translation;
+#[cfg(feature = "std")]
+pub mod verify;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "std")]
+pub use dataset::{ColorBlock, ColorName, Dataset, DatasetError, LevelNames, Metadata};
+#[cfg(feature = "std")]
+pub use degree::{degree_average, degree_diff, degree_span, degree_variance, weighted_degree_mean};
+#[cfg(feature = "std")]
+pub use diagnostic::{Coordinates, Diagnostic, Severity, ValidationError};
+#[cfg(feature = "std")]
+pub use leaf::HueLeaf;
+#[cfg(feature = "std")]
+pub use munsell::{
+    luminance_to_value, value_to_luminance, MunsellColor, MunsellFormat, MunsellHue, ParseError,
+};
+#[cfg(feature = "std")]
+pub use naming::ColorNamingSystem;
+#[cfg(feature = "std")]
+pub use neutral::NeutralCategory;
+#[cfg(feature = "charts")]
+pub use plate::{Plate, PlateAxes, PlateRegion};
+#[cfg(feature = "charts")]
+pub use plot::labels::LabelPlacement;
+#[cfg(feature = "charts")]
+pub use plot::wheel::HueSector;
+#[cfg(feature = "std")]
+pub use solid::SolidPart;
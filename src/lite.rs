@@ -0,0 +1,255 @@
+//! The lookup core of the naming system, which needs only `alloc`: parsing
+//! Munsell specifications, finding the block a color falls into, and the
+//! names of all three levels.
+//!
+//! Without the `std` feature this is all the library has, for embedded
+//! devices like color sensors that report Munsell colors. A [`LiteDataset`]
+//! loads a dataset written by the `compile` command, which such a device can
+//! take in with `include_bytes!`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::compiled::{CompiledError, Reader};
+use crate::spans::{BlockRanges, SpanTable};
+
+/// The hue letter codes, in order around the hue circle from R.
+pub(crate) const LETTER_CODES: &[&str] = &["R", "YR", "Y", "GY", "G", "BG", "B", "PB", "P", "RP"];
+
+/// Parse a value or chroma, which are plain decimal numbers like `6`, `0.5`
+/// or `.5`.
+pub fn parse_number(text: &str) -> Option<f32> {
+    // digits with at most one dot among them, ending in a digit
+    let head = text.strip_suffix(|x: char| x.is_ascii_digit())?;
+    if !head.chars().all(|x| x.is_ascii_digit() || x == '.') || head.matches('.').count() > 1 {
+        return None;
+    }
+    return text.parse().ok();
+}
+
+/// Parse a hue like `7.5YR` into a number from 0 to 100, like
+/// [`crate::MunsellHue::raw`]: `5R` is 0 and every letter code 10 more.
+///
+/// The letter code may be in any case, and whitespace may surround the hue
+/// or come between its number and letter code, so ` 7.5 yr ` is read as
+/// `7.5YR`. The number runs from 0 to 10, and `10R` and `0YR` are the same
+/// hue.
+pub fn parse_hue(huespec: &str) -> Option<f32> {
+    let huespec = huespec.trim();
+    let letters = huespec.find(|x: char| x.is_ascii_alphabetic())?;
+    let (number, code) = huespec.split_at(letters);
+    let hue_code = LETTER_CODES
+        .iter()
+        .position(|x| x.eq_ignore_ascii_case(code))?;
+    let hue_number = parse_number(number.trim_end()).filter(|x| *x <= 10.0)?;
+    return Some((((hue_code * 10) as f32) + (hue_number - 5.0) + 100.0) % 100.0);
+}
+
+/// Parse a Munsell specification like `7.5YR 6/8`, or a neutral like `N 5/`,
+/// into its hue, as from [`parse_hue`], value and chroma. Neutrals have hue
+/// 0 and chroma 0.
+///
+/// This accepts the same specifications as [`crate::MunsellColor`], but
+/// tells nothing of why others are rejected.
+pub fn parse_munsell(spec: &str) -> Option<(f32, f32, f32)> {
+    let spec = spec.trim();
+    // the hue ends at its letters, so "7.5YR6/8" is accepted too
+    let letters = spec.find(|x: char| x.is_ascii_alphabetic())?;
+    let hue_end = spec[letters..]
+        .find(|x: char| !x.is_ascii_alphabetic())
+        .map_or(spec.len(), |x| letters + x);
+    let (hue, rest) = spec.split_at(hue_end);
+    let (value, chroma) = match rest.split_once('/') {
+        Some((value, chroma)) => (value.trim(), Some(chroma.trim())),
+        None => (rest.trim(), None),
+    };
+
+    let value = parse_number(value).filter(|x| *x <= 10.0)?;
+    if hue.eq_ignore_ascii_case("N") {
+        // the chroma of a neutral is empty, or zero
+        return match chroma {
+            None | Some("") => Some((0.0, value, 0.0)),
+            Some(x) if parse_number(x) == Some(0.0) => Some((0.0, value, 0.0)),
+            Some(_) => None,
+        };
+    }
+    return Some((parse_hue(hue)?, value, parse_number(chroma?)?));
+}
+
+/// A name of any level, without the description and usage of
+/// [`crate::ColorName`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiteName {
+    pub id: u32,
+    pub name: String,
+    pub abbr: String,
+    /// The id of the name of the level above, if any.
+    pub parent: Option<u32>,
+}
+
+/// The names and blocks of a dataset, for looking up colors without `std`.
+pub struct LiteDataset {
+    /// The names of every level, in order of id.
+    levels: [Vec<LiteName>; 3],
+    table: SpanTable,
+}
+
+fn read_names(reader: &mut Reader) -> Result<Vec<LiteName>, CompiledError> {
+    let count = reader.u32()?;
+    let mut names = Vec::new();
+    for _ in 0..count {
+        names.push(LiteName {
+            id: reader.u32()?,
+            name: reader.string()?,
+            abbr: reader.string()?,
+            parent: reader.option(|r| r.u32())?,
+        });
+        // the representative color, description and usage
+        reader.option(|r| r.take(3).map(|_| ()))?;
+        reader.option(|r| r.string())?;
+        reader.option(|r| r.string())?;
+    }
+    names.sort_by_key(|x| x.id);
+    return Ok(names);
+}
+
+fn parse_boundaries(
+    boundaries: &[String],
+    parse: impl Fn(&str) -> Option<f32>,
+) -> Result<Vec<f32>, CompiledError> {
+    return boundaries
+        .iter()
+        .map(|x| parse(x).ok_or_else(|| CompiledError::InvalidBoundary(x.clone())))
+        .collect();
+}
+
+impl LiteDataset {
+    /// Load a dataset written by [`crate::Dataset::to_compiled`], checking
+    /// as much as it does.
+    pub fn from_compiled(bytes: &[u8]) -> Result<Self, CompiledError> {
+        let mut reader = Reader::after_header(bytes)?;
+        // the version, source, license and publication date
+        for _ in 0..4 {
+            reader.string()?;
+        }
+        let levels = [
+            read_names(&mut reader)?,
+            read_names(&mut reader)?,
+            read_names(&mut reader)?,
+        ];
+        let hues = parse_boundaries(&reader.strings()?, parse_hue)?;
+        let chromas = parse_boundaries(&reader.strings()?, |x| x.parse().ok())?;
+        let values = parse_boundaries(&reader.strings()?, |x| x.parse().ok())?;
+
+        let count = reader.u32()?;
+        let mut blocks: Vec<BlockRanges> = Vec::new();
+        for i in 0..count as usize {
+            let block = (
+                reader.u32()?,
+                reader.range()?,
+                reader.range()?,
+                reader.range()?,
+            );
+            let in_range =
+                |range: &core::ops::Range<usize>, len: usize| range.start < len && range.end < len;
+            if !in_range(&block.1, hues.len())
+                || !in_range(&block.2, chromas.len())
+                || !in_range(&block.3, values.len())
+            {
+                return Err(CompiledError::InvalidBlock(i));
+            }
+            blocks.push(block);
+        }
+        if !reader.is_done() {
+            return Err(CompiledError::TrailingData);
+        }
+
+        return Ok(LiteDataset {
+            levels,
+            table: SpanTable::from_blocks(&hues, chromas, values, &blocks),
+        });
+    }
+
+    /// The level 3 names, in order of id.
+    pub fn names(&self) -> &[LiteName] {
+        return &self.levels[2];
+    }
+
+    /// The name of a level, from 1 to 3, by id.
+    pub fn name(&self, level: usize, id: u32) -> Option<&LiteName> {
+        let names = self.levels.get(level.checked_sub(1)?)?;
+        return names
+            .binary_search_by_key(&id, |x| x.id)
+            .ok()
+            .map(|i| &names[i]);
+    }
+
+    /// The names of a level 3 color and of the level 2 and level 1 names
+    /// containing it, in order of level.
+    pub fn level_names(&self, color_id: u32) -> Option<[&LiteName; 3]> {
+        let level3 = self.name(3, color_id)?;
+        let level2 = self.name(2, level3.parent?)?;
+        let level1 = self.name(1, level2.parent?)?;
+        return Some([level1, level2, level3]);
+    }
+
+    /// Find the id of the level 3 color at a hue, as from [`parse_hue`],
+    /// value and chroma, like [`crate::Dataset::lookup_id`].
+    pub fn lookup_id(&self, hue: f32, value: f32, chroma: f32) -> Option<u32> {
+        return self.table.classify_point(hue, value, chroma);
+    }
+
+    /// Find the level 3 name of a Munsell specification like `7.5YR 6/8`.
+    pub fn lookup(&self, spec: &str) -> Option<&LiteName> {
+        let (hue, value, chroma) = parse_munsell(spec)?;
+        return self.name(3, self.lookup_id(hue, value, chroma)?);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::lite::{parse_hue, parse_munsell, parse_number, LiteDataset};
+    use crate::{Dataset, MunsellColor};
+
+    #[test]
+    fn lite_lookup() {
+        assert_eq!(parse_number(".5"), Some(0.5));
+        assert_eq!(parse_number("5."), None);
+        assert_eq!(parse_number("1.2.3"), None);
+        assert_eq!(parse_number("12.25"), Some(12.25));
+        assert_eq!(parse_hue("9RP"), Some(94.0));
+        assert_eq!(parse_hue("9R P"), None);
+        assert_eq!(parse_hue("10R"), parse_hue("0YR"));
+        assert_eq!(parse_hue("15R"), None);
+        assert_eq!(parse_munsell(" 7.5YR6/8 "), Some((12.5, 6.0, 8.0)));
+        assert_eq!(parse_munsell("N5"), Some((0.0, 5.0, 0.0)));
+        assert_eq!(parse_munsell("N 5/2"), None);
+        assert_eq!(parse_munsell("5R 11/2"), None);
+
+        let dataset = Dataset::bundled();
+        let lite = LiteDataset::from_compiled(&dataset.to_compiled()).unwrap();
+        assert_eq!(lite.names().len(), dataset.names().len());
+        for spec in [
+            "2R 7/12",
+            "5Y 8/10",
+            "N 9.5/",
+            "2.5PB 3/4",
+            "9RP 2/1",
+            "5G 11/",
+        ] {
+            let color = spec.parse::<MunsellColor>().ok();
+            assert_eq!(
+                lite.lookup(spec).map(|x| x.id),
+                color.and_then(|x| dataset.lookup_id(&x)),
+                "{}",
+                spec
+            );
+        }
+
+        let names = lite.level_names(194).unwrap();
+        assert_eq!(names[2].abbr, "viv.pB");
+        assert_eq!(names[1].name, dataset.level_names(194).unwrap().level2.name);
+        assert_eq!(names[0].name, "Blue");
+        assert!(lite.name(4, 1).is_none());
+    }
+}
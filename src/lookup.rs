@@ -0,0 +1,309 @@
+//! A dense table of the level 3 color of every cell between the hue, chroma
+//! and value boundaries, for classifying colors without trying every block.
+//!
+//! Validation fills one in block by block to find overlaps and gaps, and
+//! every [`Dataset`] keeps one for [`Dataset::classify_munsell_with`]. Where
+//! memory matters more than speed, [`crate::spans::SpanTable`] holds the
+//! same cells run-length encoded.
+//!
+//! # Boundaries
+//!
+//! Every cell, and so every block, includes its lower boundaries and
+//! excludes its upper ones: a color of chroma exactly 7.0 belongs to the
+//! cell whose chroma span begins at 7.0, not the one ending there, and the
+//! same goes for value, and for hue going up around the circle from 5R. The
+//! last chroma and value spans reach to infinity. Colors within some
+//! distance of a boundary can still be asked for every color they might
+//! be, with [`ColorLookupTable::classify_within`].
+
+use std::ops::RangeInclusive;
+
+use crate::dataset::{ColorBlock, Dataset};
+use crate::munsell::MunsellColor;
+
+/// The level 3 color of every cell of a dataset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorLookupTable {
+    /// The first hue of every leaf, normalized to `[0, 100)`, with the index
+    /// of the leaf, in increasing order of hue.
+    hue_starts: Vec<(f32, usize)>,
+    /// The chroma boundaries, ending in infinity.
+    chroma_points: Vec<f32>,
+    /// The value boundaries, ending in infinity.
+    value_points: Vec<f32>,
+    /// The color of every cell by [`ColorLookupTable::index`], or 0 where no
+    /// block covers it.
+    cells: Vec<u32>,
+}
+
+impl ColorLookupTable {
+    /// A table with no cell filled in, between boundaries given as numbers,
+    /// with the hues like [`crate::MunsellHue::raw`].
+    pub(crate) fn empty(
+        hue_points: &[f32],
+        chroma_points: Vec<f32>,
+        value_points: Vec<f32>,
+    ) -> Self {
+        let mut hue_starts = hue_points
+            .iter()
+            .enumerate()
+            .map(|(h, x)| (x.rem_euclid(100.0), h))
+            .collect::<Vec<(f32, usize)>>();
+        hue_starts.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // one fewer chroma and value span than boundaries, for the INF at
+        // the end
+        let size = hue_points.len()
+            * chroma_points.len().saturating_sub(1)
+            * value_points.len().saturating_sub(1);
+        return ColorLookupTable {
+            hue_starts,
+            chroma_points,
+            value_points,
+            cells: vec![0; size],
+        };
+    }
+
+    /// The table of a validated dataset, like [`Dataset::lookup_table`], to
+    /// keep apart from it.
+    pub fn new(dataset: &Dataset) -> Self {
+        return dataset.lookup_table().clone();
+    }
+
+    /// Fill in the cells of blocks between boundaries given as numbers, like
+    /// [`ColorLookupTable::empty`].
+    pub(crate) fn from_blocks(
+        hue_points: &[f32],
+        chroma_points: Vec<f32>,
+        value_points: Vec<f32>,
+        blocks: &[ColorBlock],
+    ) -> Self {
+        let mut table = Self::empty(hue_points, chroma_points, value_points);
+        let leaves = hue_points.len();
+        for block in blocks {
+            let mut h = block.hues.start;
+            loop {
+                for c in block.chromas.clone() {
+                    for v in block.values.clone() {
+                        // the first block wins, as when trying them in order
+                        if table.get(h, c, v) == Some(0) {
+                            table.set(h, c, v, block.color_id);
+                        }
+                    }
+                }
+                h = (h + 1) % leaves;
+                if h == block.hues.end {
+                    break;
+                }
+            }
+        }
+        return table;
+    }
+
+    /// The number of hue leaves, chroma spans and value spans.
+    pub fn shape(&self) -> (usize, usize, usize) {
+        return (
+            self.hue_starts.len(),
+            self.chroma_points.len().saturating_sub(1),
+            self.value_points.len().saturating_sub(1),
+        );
+    }
+
+    /// Where the cell of hue leaf `h`, chroma span `c` and value span `v` is
+    /// in `cells`, or `None` if there is no such cell.
+    pub(crate) fn index(&self, h: usize, c: usize, v: usize) -> Option<usize> {
+        let (hues, chromas, values) = self.shape();
+        if h >= hues || c >= chromas || v >= values {
+            return None;
+        }
+        return Some((h * chromas + c) * values + v);
+    }
+
+    /// The color of a cell, 0 if no block covers it, or `None` if there is
+    /// no such cell.
+    pub fn get(&self, h: usize, c: usize, v: usize) -> Option<u32> {
+        return self.index(h, c, v).map(|i| self.cells[i]);
+    }
+
+    pub(crate) fn set(&mut self, h: usize, c: usize, v: usize, color_id: u32) {
+        let i = self.index(h, c, v).unwrap();
+        self.cells[i] = color_id;
+    }
+
+    /// Find the id of the level 3 color at a hue, like
+    /// [`crate::MunsellHue::raw`], value and chroma.
+    ///
+    /// Hues wrap around, so `-5.0` is `95.0` and the leaf before the first
+    /// boundary is the one from the last. Cells include their lower
+    /// boundaries and exclude their upper ones, and the last chroma and
+    /// value spans reach to infinity. Returns `None` for negative and
+    /// missing chromas and values, and where no block covers the cell.
+    pub fn classify(&self, hue: f32, value: f32, chroma: f32) -> Option<u32> {
+        if !hue.is_finite() || self.hue_starts.is_empty() {
+            return None;
+        }
+        let hue = hue.rem_euclid(100.0);
+        let h = match self.hue_starts.partition_point(|x| x.0 <= hue) {
+            0 => self.hue_starts[self.hue_starts.len() - 1].1,
+            i => self.hue_starts[i - 1].1,
+        };
+
+        let span = |points: &[f32], x: f32| -> Option<usize> {
+            if x.is_nan() {
+                return None;
+            }
+            return match points.partition_point(|p| *p <= x) {
+                0 => None,
+                i if i == points.len() => None,
+                i => Some(i - 1),
+            };
+        };
+        let c = span(&self.chroma_points, chroma)?;
+        let v = span(&self.value_points, value)?;
+        return match self.get(h, c, v)? {
+            0 => None,
+            id => Some(id),
+        };
+    }
+
+    /// The spans of `points` that come within `epsilon` of `x`, following
+    /// the boundary rules of [`ColorLookupTable::classify`].
+    fn spans_within(points: &[f32], x: f32, epsilon: f32) -> Option<RangeInclusive<usize>> {
+        let (low, high) = (x - epsilon, x + epsilon);
+        if x.is_nan() || high < points[0] {
+            return None;
+        }
+        let first = points.partition_point(|p| *p <= low.max(points[0])) - 1;
+        let last = points.partition_point(|p| *p <= high).min(points.len() - 1) - 1;
+        if first > last {
+            return None;
+        }
+        return Some(first..=last);
+    }
+
+    /// The hue leaves that come within `epsilon` of `hue`, as indices into
+    /// `hue_starts`.
+    fn leaves_within(&self, hue: f32, epsilon: f32) -> Vec<usize> {
+        let n = self.hue_starts.len();
+        let low = (hue - epsilon).rem_euclid(100.0);
+        let mut k = match self.hue_starts.partition_point(|x| x.0 <= low) {
+            0 => n - 1,
+            i => i - 1,
+        };
+        let mut leaves = vec![self.hue_starts[k].1];
+        while leaves.len() < n {
+            k = (k + 1) % n;
+            if (self.hue_starts[k].0 - low).rem_euclid(100.0) > 2.0 * epsilon {
+                break;
+            }
+            leaves.push(self.hue_starts[k].1);
+        }
+        return leaves;
+    }
+
+    /// Find every level 3 color within `epsilon` of a hue, value and
+    /// chroma, in hue, value and chroma steps, like [`ColorLookupTable::classify`].
+    ///
+    /// The color the point itself falls into comes first, if any, and the
+    /// others follow by id. Away from every boundary this is just that
+    /// color; outside of the solid by more than `epsilon`, nothing.
+    pub fn classify_within(&self, hue: f32, value: f32, chroma: f32, epsilon: f32) -> Vec<u32> {
+        let mut ids = Vec::new();
+        if !hue.is_finite() || self.hue_starts.is_empty() || epsilon.is_nan() {
+            return ids;
+        }
+        let epsilon = epsilon.max(0.0);
+        let (chromas, values) = match (
+            Self::spans_within(&self.chroma_points, chroma, epsilon),
+            Self::spans_within(&self.value_points, value, epsilon),
+        ) {
+            (Some(c), Some(v)) => (c, v),
+            _ => return ids,
+        };
+        for h in self.leaves_within(hue, epsilon) {
+            for c in chromas.clone() {
+                for v in values.clone() {
+                    match self.get(h, c, v) {
+                        Some(0) | None => {}
+                        Some(id) => ids.push(id),
+                    }
+                }
+            }
+        }
+        ids.sort();
+        ids.dedup();
+
+        if let Some(id) = self.classify(hue, value, chroma) {
+            ids.retain(|x| *x != id);
+            ids.insert(0, id);
+        }
+        return ids;
+    }
+
+    /// Find the id of the level 3 color that a Munsell color falls into,
+    /// like [`Dataset::lookup_id`].
+    pub fn classify_munsell(&self, color: &MunsellColor) -> Option<u32> {
+        return self.classify(color.hue.raw(), color.value, color.chroma);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lookup::ColorLookupTable;
+    use crate::{Dataset, MunsellColor, MunsellHue};
+
+    #[test]
+    fn lookup_table() {
+        let dataset = Dataset::bundled();
+        let table = ColorLookupTable::new(&dataset);
+        assert_eq!(
+            table.shape(),
+            (
+                dataset.hues().len(),
+                dataset.chromas().len() - 1,
+                dataset.values().len() - 1
+            )
+        );
+        assert_eq!(table.get(dataset.hues().len(), 0, 0), None);
+        assert!(table.get(0, 0, 0).unwrap() > 0);
+
+        for hue in (0..200).map(|x| x as f32 / 2.0) {
+            for chroma in [-1.0, 0.0, 0.3, 1.5, 3.0, 7.0, 11.0, 14.9, 15.0, 40.0] {
+                for value in [-0.5, 0.0, 0.9, 2.5, 4.5, 6.5, 8.5, 9.9, 10.0, 12.0] {
+                    let color = MunsellColor::new(MunsellHue::new(hue), value, chroma);
+                    let block = dataset
+                        .blocks()
+                        .iter()
+                        .find(|x| dataset.block_contains(x, &color));
+                    assert_eq!(
+                        table.classify_munsell(&color),
+                        block.map(|x| x.color_id),
+                        "{}",
+                        color
+                    );
+                }
+            }
+        }
+
+        // the hue wraps around, so -3 is 2R, and what isn't a number is
+        // outside
+        assert_eq!(table.classify(97.0, 7.0, 12.0), Some(1));
+        assert_eq!(table.classify(-3.0, 7.0, 12.0), Some(1));
+        assert_eq!(table.classify(f32::NAN, 7.0, 12.0), None);
+        assert_eq!(table.classify(2.0, f32::NAN, 12.0), None);
+        assert_eq!(table.classify(2.0, 7.0, f32::INFINITY), None);
+
+        // 2R 6.5/11 is on the lower boundary of a block of vivid pink, just
+        // above strong pink
+        assert_eq!(table.classify(97.0, 6.5, 11.0), Some(1));
+        assert_eq!(table.classify_within(97.0, 6.5, 11.0, 0.0), vec![1]);
+        let near = table.classify_within(97.0, 6.5, 11.0, 0.1);
+        assert_eq!(near[0], 1);
+        assert!(near.contains(&3));
+        assert_eq!(table.classify_within(97.0, 7.5, 13.0, 0.1), vec![1]);
+        assert!(table.classify_within(97.0, 7.5, -0.5, 0.1).is_empty());
+        // the leaves wrap around too
+        let wrapped = table.classify_within(99.95, 7.5, 13.0, 0.1);
+        assert_eq!(wrapped, table.classify_within(-0.05, 7.5, 13.0, 0.1));
+    }
+}
@@ -2,670 +2,2099 @@
 //
 // SPDX-License-Identifier: MIT
 
-extern crate is_sorted;
-mod degree;
-mod munsell;
+#![allow(clippy::needless_return)]
 
-use is_sorted::IsSorted;
+mod cli;
 
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::Write;
-use std::ops::Range;
-use std::process::Command;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use clap::{ArgAction, Args, Parser, Subcommand, ValueEnum};
+use log::{error, info, warn};
+use palette::{Clamp, ColorDifference, IntoColor, Lab, Srgb};
+
+#[cfg(feature = "charts")]
+use cli::charts::{
+    export_bundle, load_plot_config, plot_command, site, ExportBundle, PlotArgs, PlotFormat,
+};
+use cli::logging::{init_logging, log_progress};
+use iscc_nbs_validator::accuracy;
+use iscc_nbs_validator::adaptation::{set_adaptation, Adaptation, AdaptationMethod, Illuminant};
+use iscc_nbs_validator::canonical;
+use iscc_nbs_validator::centroids;
+use iscc_nbs_validator::classify::{
+    parse_srgb, srgb_to_lab, srgb_to_munsell, ClassifyOptions, DeltaE,
+};
+use iscc_nbs_validator::colors::{
+    get_gamut_clipping, get_level1_mean_colors, get_level2_mean_colors, get_mean_colors,
+    get_mean_munsell, get_mean_munsell_with, get_representative_colors, get_volumes,
+    CentroidMethod, PerceptualSpace, Representative,
+};
+use iscc_nbs_validator::compare::FaceAxis;
+use iscc_nbs_validator::compiled;
+use iscc_nbs_validator::diagnostic;
+use iscc_nbs_validator::diff::{group_cells, NameChange};
+use iscc_nbs_validator::export;
+use iscc_nbs_validator::export::cube::DEFAULT_LUT_SIZE;
+use iscc_nbs_validator::export::SwatchOrder;
+use iscc_nbs_validator::gamut::{set_gamut_mapping, BlockGamut, GamutCoverage, GamutMapping};
+use iscc_nbs_validator::munsell::MunsellFormat;
+use iscc_nbs_validator::random::{Rng, DEFAULT_SEED};
+use iscc_nbs_validator::repl::{self, read_key, Edit, LineEditor};
+use iscc_nbs_validator::rgbspace::{set_rgb_space, RgbSpace};
+use iscc_nbs_validator::synonyms::Synonyms;
+use iscc_nbs_validator::translation::Translation;
+use iscc_nbs_validator::verify::SampleFault;
+use iscc_nbs_validator::{ColorBlock, ColorName, Dataset, MunsellColor, Severity};
+
+#[derive(Parser)]
+#[command(about = "Validate an ISCC-NBS dataset and generate charts from it")]
+struct Cli {
+    /// The dataset to use, following the schema of iscc-nbs.xml
+    #[arg(long, global = true, default_value = "iscc-nbs.xml")]
+    dataset: PathBuf,
+    /// The parameters to draw the charts with, like their size and axis
+    /// ranges; plot.toml, if there is one, by default
+    #[cfg(feature = "charts")]
+    #[arg(long, global = true)]
+    plot_config: Option<PathBuf>,
+    /// A TrueType font file to draw the charts with, instead of finding one
+    /// with fontconfig
+    #[cfg(feature = "charts")]
+    #[arg(long, global = true)]
+    font_path: Option<PathBuf>,
+    /// The typeface to find with fontconfig and to name in SVG and gnuplot
+    /// output, instead of DejaVu Sans
+    #[cfg(feature = "charts")]
+    #[arg(long, global = true)]
+    font_name: Option<String>,
+    /// The language to name colors in, like "de", from names.de.xml or
+    /// names.de.json next to the dataset
+    #[arg(long, global = true)]
+    lang: Option<String>,
+    /// How to adapt the renotation from Illuminant C to the white point
+    /// colors are converted relative to
+    #[arg(long, global = true, value_enum, default_value_t = AdaptationArg::Bradford)]
+    adaptation: AdaptationArg,
+    /// The white point that CIELAB input and output is relative to; sRGB is
+    /// always relative to D65
+    #[arg(long, global = true, value_enum, default_value_t = IlluminantArg::D65)]
+    illuminant: IlluminantArg,
+    /// How representative colors outside sRGB are brought into it
+    #[arg(long, global = true, value_enum, default_value_t = GamutMappingArg::ChromaClamp)]
+    gamut_mapping: GamutMappingArg,
+    /// The RGB space representative colors are given in, for CSS, SCSS and
+    /// JSON exports and SVG and HTML charts
+    #[arg(long, global = true, value_enum, default_value_t = ColorSpaceArg::Srgb)]
+    color_space: ColorSpaceArg,
+    /// Which color of each name to show on the charts and in exports
+    #[arg(long, global = true, value_enum, default_value_t = RepresentativeArg::VolumeCentroid)]
+    representative: RepresentativeArg,
+    /// The published centroids for `--representative published-centroid`,
+    /// from a CSV file of `id,munsell` lines like `centroids` compares with
+    #[arg(long, global = true)]
+    published_centroids: Option<PathBuf>,
+    /// Log more: each unit of work, and every block read; twice for
+    /// everything
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    verbose: u8,
+    /// Log less: only warnings and errors; twice for only errors
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    quiet: u8,
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
 
-use fontconfig::Fontconfig;
-use geo::extremes::Extremes;
-use geo::Centroid;
-use geo_clipper::Clipper;
-use geo_types::{Coordinate, LineString, Polygon};
-use palette::{convert::FromColorUnclamped, Clamp, IntoColor, Lch, Srgb};
-use ttf_word_wrap::{TTFParserMeasure, WhiteSpaceWordWrap, Wrap};
+static REPRESENTATIVE: OnceLock<Representative> = OnceLock::new();
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Validate the dataset and render figures from it (the default)
+    #[cfg(feature = "charts")]
+    Plot {
+        #[command(flatten)]
+        args: PlotArgs,
+    },
+    /// Look up the ISCC-NBS name of a Munsell color, like "7.5YR 6/8", or
+    /// of an abbreviation, like "v.l.pB"
+    Lookup {
+        /// The Munsell specification, abbreviation or name to look up
+        #[arg(required_unless_present = "stdin")]
+        spec: Option<String>,
+        /// Look up every line of standard input instead, each a Munsell
+        /// specification, abbreviation, name or sRGB color, and write a line of
+        /// tab-separated input, id, abbreviation and name for each, followed
+        /// by the level 2 and level 1 names with --all-levels
+        #[arg(long, conflicts_with = "spec")]
+        stdin: bool,
+        #[command(flatten)]
+        args: ClassifyArgs,
+    },
+    /// Find level 3 colors by a half-remembered name or abbreviation, like
+    /// "viv prplsh blue"
+    Search {
+        /// Whole or partial words of the name
+        query: String,
+        /// The most colors to list
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Classify an sRGB color, like "#aabbcc" or "rgb(120, 30, 200)"
+    Classify {
+        /// The sRGB color to classify
+        color: String,
+        #[command(flatten)]
+        args: ClassifyArgs,
+    },
+    /// Report how much of the sRGB cube each category claims
+    Coverage {
+        /// Number of samples along each channel of the sRGB cube
+        #[arg(long, default_value_t = 32, value_parser = clap::value_parser!(u32).range(2..))]
+        steps: u32,
+    },
+    /// Check that random points within the sRGB gamut each fall into exactly
+    /// one block and classify into its color, reporting any that don't
+    Verify {
+        /// Number of points to check
+        #[arg(long, default_value_t = 10000)]
+        samples: usize,
+        /// The seed of the random points
+        #[arg(long, default_value_t = DEFAULT_SEED)]
+        seed: u64,
+    },
+    /// Report which blocks lie partly or wholly outside the sRGB gamut, and
+    /// how the representative colors were clipped into it
+    Gamut {
+        /// Number of samples along each axis of a block
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+        samples: u32,
+    },
+    /// Report statistics about the dataset, like how many blocks each color
+    /// and hue leaf has and how much each color is clipped to fit into sRGB
+    Stats {
+        /// How to write the report
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        report: ReportFormat,
+    },
+    /// Print the computed centroid and representative color of every level 3
+    /// color, or compare the centroids with a table of published ones
+    Centroids {
+        /// A CSV file with a color id and a Munsell specification per line
+        table: Option<PathBuf>,
+        /// Flag centroids farther than this from the published ones, in
+        /// CIEDE2000 units
+        #[arg(long, default_value_t = centroids::DEFAULT_THRESHOLD)]
+        threshold: f32,
+        /// The order to print the colors in, without a table
+        #[arg(long, value_enum, default_value_t = CentroidOrder::Id, conflicts_with = "table")]
+        sort: CentroidOrder,
+        /// How to compute the centroids from the blocks
+        #[arg(long, value_enum, default_value_t = CentroidMethodArg::CenterOfMass)]
+        method: CentroidMethodArg,
+    },
+    /// Compare the Munsell to sRGB conversion with a table of reference
+    /// colors, like Paul Centore's, with the error of each hue page
+    Accuracy {
+        /// A file with a Munsell specification and red, green and blue from
+        /// 0 to 255 per line
+        table: PathBuf,
+        /// How many of the colors farthest from the reference to list
+        #[arg(long, default_value_t = 10)]
+        worst: usize,
+    },
+    /// Compare two level 3 colors: their blocks, centroids, and where they
+    /// meet
+    Compare {
+        /// The color id of the first color
+        a: u32,
+        /// The color id of the second color
+        b: u32,
+    },
+    /// Look up the trade and common names of the Dictionary of Color Names,
+    /// like "maroon", in a synonym file, after checking that every color it
+    /// gives exists
+    Synonyms {
+        /// A file with a name and its color ids per line, like
+        /// 'Peacock blue,173 174'
+        file: PathBuf,
+        /// A synonym to list the level 3 colors of, or a color id, name or
+        /// abbreviation to list the synonyms of
+        query: Option<String>,
+    },
+    /// Show a level 3 color in the terminal in 24-bit color, with its names
+    /// at all three levels, or every color at once as a grid
+    Show {
+        /// A color id, name, abbreviation or Munsell specification
+        #[arg(required_unless_present = "all")]
+        color: Option<String>,
+        /// Show every level 3 color
+        #[arg(long, conflicts_with = "color")]
+        all: bool,
+    },
+    /// Look up colors at a prompt, each an sRGB color, a Munsell
+    /// specification, a name or an abbreviation, with a history of the
+    /// lines entered and completion of names with the tab key
+    Repl,
+    /// Report the names and blocks that changed between two versions of a
+    /// dataset, instead of the dataset of --dataset
+    Diff {
+        /// The old version
+        old: PathBuf,
+        /// The new version
+        new: PathBuf,
+    },
+    /// Write the dataset out in another format
+    #[command(args_conflicts_with_subcommands = true)]
+    Export {
+        #[cfg(feature = "charts")]
+        #[command(subcommand)]
+        bundle: Option<ExportBundle>,
+        /// The format to write
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        /// The order of the colors, for CSV and palette exports
+        #[arg(long, value_enum, default_value_t = ExportOrder::Id)]
+        order: ExportOrder,
+        /// Which level of names to include, for palette, LUT and NumPy exports
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..=3))]
+        level: u32,
+        /// How many samples of each channel a LUT has
+        #[arg(long, default_value_t = DEFAULT_LUT_SIZE, value_parser = clap::value_parser!(u32).range(2..=256))]
+        lut_size: u32,
+        /// Add the level 2 and level 1 names, for rgb.txt exports
+        #[arg(long)]
+        all_levels: bool,
+        /// Write to this file instead of standard output
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Write the dataset XML in canonical form: attributes in a fixed order,
+    /// the ranges of each hue range sorted, and consistent indentation
+    Fmt {
+        /// Write to this file instead of standard output; it may be the
+        /// dataset itself
+        #[arg(long, short, conflicts_with = "check")]
+        output: Option<PathBuf>,
+        /// Only check that the dataset is in canonical form, failing if not
+        #[arg(long)]
+        check: bool,
+    },
+    /// Write the dataset in a compact binary form, which --dataset loads
+    /// without parsing or validating the XML again
+    Compile {
+        /// Write to this file instead of standard output
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Check the dataset and report every problem found
+    Validate {
+        /// How to write the report
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        report: ReportFormat,
+        /// Fail on warnings too, like unusual boundaries or tiny blocks
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+    /// Write a static website documenting the dataset: an index of every
+    /// color, a page per color and a page per hue leaf
+    #[cfg(feature = "charts")]
+    Site {
+        /// The directory to write into
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Write the color solid as a 3D mesh, one wedge per color block
+    Solid {
+        /// The format to write
+        #[arg(long, value_enum, default_value_t = SolidFormat::Obj)]
+        format: SolidFormat,
+        /// Write to this file instead of standard output
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
 
-use degree::{degree_average, degree_diff};
-use munsell::{MunsellColor, MunsellHue};
+#[derive(Args)]
+struct ClassifyArgs {
+    /// Also print the level 1 and level 2 names
+    #[arg(long)]
+    all_levels: bool,
+    /// Treat colors with a chroma below this as neutral, ignoring their hue
+    #[arg(long, default_value_t = 0.0)]
+    neutral_chroma: f32,
+    /// Also list the other level 3 colors within this many hue, value and
+    /// chroma steps, for colors near a boundary
+    #[arg(long)]
+    tolerance: Option<f32>,
+}
 
-struct ColorName {
-    name: String,
-    abbr: String,
+impl ClassifyArgs {
+    fn options(&self) -> ClassifyOptions {
+        return ClassifyOptions {
+            neutral_chroma: self.neutral_chroma,
+        };
+    }
 }
 
-struct ColorBlock {
-    color_id: u32,
-    hues: Range<usize>,
-    chromas: Range<usize>,
-    values: Range<usize>,
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum RepresentativeArg {
+    /// The centroid of each name's blocks, or its curated color
+    VolumeCentroid,
+    /// The published centroids of `--published-centroids`
+    PublishedCentroid,
+    /// The most saturated color of each name that sRGB can show
+    MaxChromaInGamut,
 }
 
-fn add_name_to_map(map: &mut HashMap<u32, ColorName>, node: roxmltree::Node) {
-    let color_id: u32 = node.attribute("color").unwrap().parse::<u32>().unwrap();
-    let color_name = node.attribute("name").unwrap().to_string();
-    let color_abbr = node.attribute("abbr").unwrap().to_string();
+#[derive(Clone, Copy, ValueEnum)]
+enum CentroidMethodArg {
+    /// The volume-weighted average of the midpoints of the blocks
+    Midpoint,
+    /// The exact center of mass of the blocks
+    CenterOfMass,
+}
 
-    if map.contains_key(&color_id) {
-        println!(
-            "Error: Conflicting color ids for {}: {} and {}.",
-            color_id,
-            map.get(&color_id).unwrap().name,
-            color_name
-        );
+impl From<CentroidMethodArg> for CentroidMethod {
+    fn from(arg: CentroidMethodArg) -> Self {
+        return match arg {
+            CentroidMethodArg::Midpoint => CentroidMethod::Midpoint,
+            CentroidMethodArg::CenterOfMass => CentroidMethod::CenterOfMass,
+        };
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AdaptationArg {
+    /// Take L*a*b* relative to Illuminant C as relative to the target, as
+    /// before adaptation could be chosen
+    LabScaling,
+    /// Scale X, Y and Z
+    XyzScaling,
+    /// Scale the Hunt-Pointer-Estevez cone responses
+    VonKries,
+    /// The Bradford transform, as in ICC profiles
+    Bradford,
+    /// The transform of CIECAM02
+    Cat02,
+}
+
+impl From<AdaptationArg> for AdaptationMethod {
+    fn from(arg: AdaptationArg) -> Self {
+        return match arg {
+            AdaptationArg::LabScaling => AdaptationMethod::LabScaling,
+            AdaptationArg::XyzScaling => AdaptationMethod::XyzScaling,
+            AdaptationArg::VonKries => AdaptationMethod::VonKries,
+            AdaptationArg::Bradford => AdaptationMethod::Bradford,
+            AdaptationArg::Cat02 => AdaptationMethod::Cat02,
+        };
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GamutMappingArg {
+    /// Reduce the chroma, keeping the lightness and hue
+    ChromaClamp,
+    /// Move toward the gray at the lightness of the most saturated color of
+    /// the hue, keeping the hue
+    TowardCusp,
+    /// Clamp each sRGB channel, shifting the hue and lightness
+    Clip,
+}
+
+impl From<GamutMappingArg> for GamutMapping {
+    fn from(arg: GamutMappingArg) -> Self {
+        return match arg {
+            GamutMappingArg::ChromaClamp => GamutMapping::ChromaClamp,
+            GamutMappingArg::TowardCusp => GamutMapping::TowardCusp,
+            GamutMappingArg::Clip => GamutMapping::Clip,
+        };
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum IlluminantArg {
+    /// Illuminant C, as the renotation is specified under
+    C,
+    /// D50, as in ICC profiles
+    D50,
+    /// D65, as in sRGB
+    D65,
+}
+
+impl From<IlluminantArg> for Illuminant {
+    fn from(arg: IlluminantArg) -> Self {
+        return match arg {
+            IlluminantArg::C => Illuminant::C,
+            IlluminantArg::D50 => Illuminant::D50,
+            IlluminantArg::D65 => Illuminant::D65,
+        };
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum ColorSpaceArg {
+    /// sRGB, as hex codes
+    Srgb,
+    /// Display P3, as CSS color(display-p3 ...)
+    DisplayP3,
+    /// Rec. 2020, as CSS color(rec2020 ...)
+    Rec2020,
+}
+
+impl From<ColorSpaceArg> for RgbSpace {
+    fn from(arg: ColorSpaceArg) -> Self {
+        return match arg {
+            ColorSpaceArg::Srgb => RgbSpace::Srgb,
+            ColorSpaceArg::DisplayP3 => RgbSpace::DisplayP3,
+            ColorSpaceArg::Rec2020 => RgbSpace::Rec2020,
+        };
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    /// Names, boundaries, blocks and representative colors as JSON
+    Json,
+    /// One row per level 3 color, with its parents and representative color
+    Csv,
+    /// Rust source code with the dataset as const arrays
+    Rust,
+    /// CSS custom properties on :root, one per level 3 color
+    Css,
+    /// SCSS variables, one per level 3 color, and a map of them
+    Scss,
+    /// A GIMP palette of the names of one level, also read by Inkscape
+    Gpl,
+    /// Adobe Color swatches of the names of one level, for Photoshop
+    Aco,
+    /// Adobe Swatch Exchange swatches of the names of one level, for
+    /// Illustrator
+    Ase,
+    /// A 3D LUT mapping every sRGB color to the representative color of
+    /// its name at one level, for video and photo editors
+    Cube,
+    /// The nearest xterm 256-color palette index of every level 3 color,
+    /// as a table
+    Xterm,
+    /// The same as Rust source code with a const array
+    XtermRust,
+    /// An X11 rgb.txt color database of the level 3 names
+    RgbTxt,
+    /// A NumPy array of the lookup table, the id of the name at one level
+    /// of every cell between the hue, chroma and value boundaries
+    Npy,
+    /// A NumPy archive of the same array with the boundaries along each
+    /// axis
+    Npz,
+    /// An SQLite database with names, blocks and centroids tables
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    /// Tables and lines of text to read
+    Text,
+    /// A JSON object for other tools, like dashboards
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum ExportOrder {
+    /// By id
+    Id,
+    /// So that neighboring colors look alike
+    Perceptual,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum CentroidOrder {
+    /// By id
+    Id,
+    /// Around the hue circle by the hue of the centroid, with the neutrals
+    /// last
+    Hue,
+    /// By level 1 name, under a heading for each
+    Group,
+}
+
+fn read_dataset(path: &Path) -> String {
+    return match std::fs::read_to_string(path) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("cannot read {}: {}.", path.display(), e);
+            std::process::exit(1);
+        }
+    };
+}
+
+/// Read a dataset, in XML or compiled; or say why it can't be.
+fn try_load_dataset(path: &Path) -> Result<Dataset, String> {
+    let bytes = match std::fs::read(path) {
+        Ok(v) => v,
+        Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+    };
+    if compiled::is_compiled(&bytes) {
+        return Dataset::from_compiled(&bytes).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+    let text = match String::from_utf8(bytes) {
+        Ok(v) => v,
+        Err(_) => {
+            return Err(format!(
+                "{} is neither XML nor a compiled dataset",
+                path.display()
+            ))
+        }
+    };
+    return Dataset::parse(&text).map_err(|e| e.to_string());
+}
+
+fn load_dataset(path: &Path) -> Dataset {
+    return match try_load_dataset(path) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}.", e);
+            std::process::exit(1);
+        }
+    };
+}
+
+/// Find the name file of a language next to the dataset at `dataset`.
+fn translation_path(dataset: &Path, lang: &str) -> PathBuf {
+    let dir = dataset.parent().unwrap_or(Path::new(""));
+    let xml = dir.join(format!("names.{}.xml", lang));
+    let json = dir.join(format!("names.{}.json", lang));
+    if !xml.exists() && json.exists() {
+        return json;
+    }
+    return xml;
+}
+
+fn load_translation(dataset: &Path, lang: &str) -> Translation {
+    let path = translation_path(dataset, lang);
+    return match Translation::load(&path) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}: {}.", path.display(), e);
+            std::process::exit(1);
+        }
+    };
+}
+
+/// Name the colors of `dataset` in `lang`, printing the problems found with
+/// its name file; returns whether none of them is an error, leaving the
+/// dataset as it was if any is.
+fn try_translate(dataset: &mut Dataset, path: &Path, lang: &str) -> bool {
+    let file = translation_path(path, lang);
+    let translation = match Translation::load(&file) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}: {}.", file.display(), e);
+            return false;
+        }
+    };
+    let diagnostics = translation.check(dataset);
+    for diagnostic in diagnostics.iter() {
+        diagnostic.log();
+    }
+    if diagnostics.iter().any(|x| x.severity == Severity::Error) {
+        return false;
+    }
+    dataset.translate(&translation);
+    return true;
+}
+
+/// Like [`try_translate`], exiting if the name file has errors.
+fn translate(dataset: &mut Dataset, path: &Path, lang: &str) {
+    if !try_translate(dataset, path, lang) {
         std::process::exit(1);
     }
+}
 
-    map.insert(
-        color_id,
-        ColorName {
-            name: color_name,
-            abbr: color_abbr,
+fn validate(path: &Path, lang: Option<&str>, report: ReportFormat, deny_warnings: bool) {
+    let text = read_dataset(path);
+    let diagnostics = match Dataset::check_and_build(&text) {
+        Ok((Some(dataset), diagnostics)) => match lang {
+            Some(lang) => {
+                let translation = load_translation(path, lang);
+                [diagnostics, translation.check(&dataset)].concat()
+            }
+            None => diagnostics,
         },
-    );
+        Ok((None, diagnostics)) => diagnostics,
+        Err(e) => {
+            error!("{}.", e);
+            std::process::exit(1);
+        }
+    };
+
+    match report {
+        ReportFormat::Text => {
+            for diagnostic in diagnostics.iter() {
+                println!("{}", diagnostic);
+            }
+        }
+        ReportFormat::Json => {
+            let json = diagnostic::report_to_json(&diagnostics);
+            println!("{}", serde_json::to_string_pretty(&json).unwrap());
+        }
+    }
+
+    if diagnostics
+        .iter()
+        .any(|x| x.severity == Severity::Error || deny_warnings)
+    {
+        std::process::exit(1);
+    }
 }
 
-fn validate_name_map(map: &HashMap<u32, ColorName>) {
-    let mut max_color_id: u32 = 0;
+fn fmt(path: &Path, output: Option<&Path>, check: bool) {
+    let text = read_dataset(path);
+    let canonical = match canonical::canonicalize(&text) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}: {}.", path.display(), e);
+            std::process::exit(1);
+        }
+    };
+
+    if check {
+        if canonical != text {
+            error!(
+                "{} is not in canonical form; run `fmt -o {}`.",
+                path.display(),
+                path.display()
+            );
+            std::process::exit(1);
+        }
+        return;
+    }
+    write_output(output, &canonical);
+}
 
-    for (color_id, name_entry) in map.iter() {
-        if color_id > &max_color_id {
-            max_color_id = *color_id
+/// Choose the representative colors of `--representative`, reading the
+/// published centroids if it asks for them.
+fn set_representative(cli: &Cli) {
+    let representative = match (cli.representative, &cli.published_centroids) {
+        (RepresentativeArg::VolumeCentroid, _) => Representative::VolumeCentroid,
+        (RepresentativeArg::PublishedCentroid, Some(table)) => {
+            Representative::PublishedCentroid(read_centroid_table(table))
         }
+        (RepresentativeArg::PublishedCentroid, None) => {
+            error!("--representative published-centroid needs --published-centroids.");
+            std::process::exit(1);
+        }
+        (RepresentativeArg::MaxChromaInGamut, _) => Representative::MaxChromaInGamut,
+    };
+    REPRESENTATIVE.set(representative).unwrap();
+}
+
+fn representative() -> &'static Representative {
+    return REPRESENTATIVE.get_or_init(Representative::default);
+}
+
+/// The representative colors of the level 3 names for exports.
+fn export_colors(dataset: &Dataset) -> Vec<Srgb> {
+    return get_representative_colors(dataset, representative(), PerceptualSpace::Cielab);
+}
+
+fn print_name(level: u32, id: u32, name: &ColorName) {
+    println!("level {}: {:>3} {:<10} {}", level, id, name.abbr, name.name);
+}
 
-        // ensure that this name and abbr are unused elsewhere
-        for (color2_id, name2_entry) in map.iter() {
-            if color_id == color2_id {
-                continue; // but don't match ourselves!
+/// Print the other colors near `color`, if asked to with --tolerance.
+fn print_nearby(dataset: &Dataset, color: &MunsellColor, id: u32, args: &ClassifyArgs) {
+    let epsilon = match args.tolerance {
+        Some(v) => v,
+        None => return,
+    };
+    let nearby = dataset
+        .classify_with_tolerance(color, epsilon)
+        .into_iter()
+        .filter(|x| *x != id)
+        .collect::<Vec<u32>>();
+    if nearby.is_empty() {
+        println!("no other color within {}", epsilon);
+        return;
+    }
+    println!("within {} of:", epsilon);
+    for other in nearby {
+        print_name(3, other, &dataset.names()[&other]);
+    }
+}
+
+fn lookup(dataset: &Dataset, spec: &str, args: &ClassifyArgs) {
+    let color = match spec.parse::<MunsellColor>() {
+        Ok(v) => v,
+        Err(e) => match dataset
+            .lookup_abbr(spec)
+            .or_else(|| dataset.lookup_name(spec))
+        {
+            Some(id) => {
+                print_names(dataset, id, args.all_levels);
+                return;
             }
-            if name_entry.name == name2_entry.name {
-                println!(
-                    "Error: Duplicate name '{}' used for both id {} and {}.",
-                    name_entry.name, color_id, color2_id
+            None => {
+                error!(
+                    "{}, nor an abbreviation like 'v.l.pB' or a name like 'light yellowish brown'.",
+                    e
                 );
                 std::process::exit(1);
             }
-            if name_entry.abbr == name2_entry.abbr {
-                println!(
-                    "Error: Duplicate abbr '{}' used for both id {} and {}.",
-                    name_entry.abbr, color_id, color2_id
-                );
+        },
+    };
+
+    let id = match dataset.classify_munsell_with(&color, &args.options()) {
+        Some(v) => v,
+        None => {
+            error!("{} is outside of the Munsell color solid.", color);
+            std::process::exit(1);
+        }
+    };
+
+    print_names(dataset, id, args.all_levels);
+    print_nearby(dataset, &color, id, args);
+}
+
+/// The level 3 color of a Munsell specification, an abbreviation, a name or
+/// an sRGB color.
+fn lookup_any(dataset: &Dataset, text: &str, options: &ClassifyOptions) -> Option<u32> {
+    if let Ok(color) = text.parse::<MunsellColor>() {
+        return dataset.classify_munsell_with(&color, options);
+    }
+    if let Some(id) = dataset.lookup_abbr(text) {
+        return Some(id);
+    }
+    if let Some(id) = dataset.lookup_name(text) {
+        return Some(id);
+    }
+    let color = parse_srgb(text)?;
+    return dataset.classify_munsell_with(&srgb_to_munsell(color.into_format()), options);
+}
+
+fn lookup_stdin(dataset: &Dataset, args: &ClassifyArgs) {
+    let options = args.options();
+    let mut out = std::io::stdout().lock();
+    for (i, line) in std::io::stdin().lines().enumerate() {
+        let line = match line {
+            Ok(v) => v,
+            Err(e) => {
+                error!("standard input: {}.", e);
                 std::process::exit(1);
             }
+        };
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
         }
+
+        let mut fields = vec![text.to_string()];
+        match lookup_any(dataset, text, &options) {
+            Some(id) => {
+                let names = dataset.level_names(id).unwrap();
+                fields.extend([
+                    id.to_string(),
+                    names.level3.abbr.clone(),
+                    names.level3.name.clone(),
+                ]);
+                if args.all_levels {
+                    fields.extend([names.level2.name.clone(), names.level1.name.clone()]);
+                }
+            }
+            None => {
+                warn!(
+                    "line {}: '{}' is no Munsell color, abbreviation, name or sRGB color in the Munsell color solid.",
+                    i + 1,
+                    text
+                );
+                fields.extend(["", "", ""].map(String::from));
+                if args.all_levels {
+                    fields.extend(["", ""].map(String::from));
+                }
+            }
+        }
+        // a closed pipe, like `| head`, ends the lookups
+        if writeln!(out, "{}", fields.join("\t")).is_err() {
+            return;
+        }
+    }
+}
+
+fn search(dataset: &Dataset, query: &str, limit: usize) {
+    let matches = dataset.search(query, limit);
+    if matches.is_empty() {
+        error!("no color name matches '{}'.", query);
+        std::process::exit(1);
     }
 
-    // also ensure that all ids from 1..max_color_id are present
-    for id in 1..max_color_id {
-        if !map.contains_key(&id) {
-            println!("Error: missing color id {} in 1..{}.", id, max_color_id);
+    let colors = get_mean_colors(dataset);
+    println!(
+        "{:>3} {:<10} {:<36} {:<5} srgb",
+        "id", "abbr", "name", "score"
+    );
+    for m in matches {
+        let name = &dataset.names()[&m.color_id];
+        println!(
+            "{:>3} {:<10} {:<36} {:<5.2} {}",
+            m.color_id,
+            name.abbr,
+            name.name,
+            m.score,
+            export::srgb_hex(colors[(m.color_id - 1) as usize])
+        );
+    }
+}
+
+fn classify(dataset: &Dataset, text: &str, args: &ClassifyArgs) {
+    let color = match parse_srgb(text) {
+        Some(v) => v,
+        None => {
+            error!(
+                "'{}' is not a color like '#aabbcc' or 'rgb(120, 30, 200)'.",
+                text
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let munsell = srgb_to_munsell(color.into_format());
+    let format = MunsellFormat {
+        precision: Some(1),
+        ..MunsellFormat::default()
+    };
+    println!("munsell: {}", munsell.format(&format));
+
+    match dataset.classify_munsell_with(&munsell, &args.options()) {
+        Some(id) => {
+            print_names(dataset, id, args.all_levels);
+            let lab = srgb_to_lab(color.into_format());
+            println!(
+                "delta E to its centroid: {:.1} (CIEDE2000), {:.1} (CIE76)",
+                dataset
+                    .delta_e_to_centroid(lab, id, DeltaE::Ciede2000)
+                    .unwrap(),
+                dataset.delta_e_to_centroid(lab, id, DeltaE::Cie76).unwrap()
+            );
+            print_nearby(dataset, &munsell, id, args);
+        }
+        None => {
+            error!("{} is outside of the Munsell color solid.", text);
             std::process::exit(1);
         }
     }
 }
 
-fn validate_names(doc: &roxmltree::Document) -> HashMap<u32, ColorName> {
-    let names = doc.descendants().find(|n| n.has_tag_name("names")).unwrap();
+fn coverage(dataset: &Dataset, steps: u32) {
+    let counts = dataset.srgb_coverage_with_progress(steps, &mut log_progress);
+    let total = steps * steps * steps;
 
-    let mut level1_names = HashMap::new();
-    let mut level2_names = HashMap::new();
-    let mut level3_names = HashMap::new();
+    let mut ids = counts.keys().copied().collect::<Vec<u32>>();
+    ids.sort_by_key(|id| (std::cmp::Reverse(counts[id]), *id));
 
-    for level1 in names.children().filter(|n| n.is_element()) {
-        add_name_to_map(&mut level1_names, level1);
-        for level2 in level1.children().filter(|n| n.is_element()) {
-            add_name_to_map(&mut level2_names, level2);
-            for level3 in level2.children().filter(|n| n.is_element()) {
-                add_name_to_map(&mut level3_names, level3);
-            }
+    println!("Share of {} sRGB samples claimed by each category:", total);
+    for id in ids.iter().filter(|id| counts[id] > 0) {
+        let name = &dataset.names()[id];
+        println!(
+            "{:>7.3}% {:>3} {:<10} {}",
+            100.0 * (counts[id] as f64) / (total as f64),
+            id,
+            name.abbr,
+            name.name
+        );
+    }
+
+    let unclaimed = ids.iter().filter(|id| counts[id] == 0).collect::<Vec<_>>();
+    println!();
+    println!("{} categories claim no samples:", unclaimed.len());
+    for id in unclaimed {
+        let name = &dataset.names()[id];
+        println!("{:>3} {:<10} {}", id, name.abbr, name.name);
+    }
+}
+
+fn verify(dataset: &Dataset, samples: usize, seed: u64) {
+    let coverage =
+        dataset.sample_coverage_with_progress(samples, &mut Rng::new(seed), &mut log_progress);
+    let abbr = |id: &u32| dataset.names()[id].abbr.clone();
+    for x in coverage.faults.iter() {
+        let spec = format!("{} {:.3}/{:.3}", x.color.hue, x.color.value, x.color.chroma);
+        match &x.fault {
+            SampleFault::Gap => error!("{} is in no block.", spec),
+            SampleFault::Overlap(ids) => error!(
+                "{} is in blocks of {}.",
+                spec,
+                ids.iter().map(abbr).collect::<Vec<String>>().join(", ")
+            ),
+            SampleFault::Mismatch {
+                expected,
+                classified,
+            } => error!(
+                "{} is in a block of {}, but classifies as {}.",
+                spec,
+                abbr(expected),
+                classified.as_ref().map_or("nothing".to_string(), abbr)
+            ),
         }
     }
 
-    validate_name_map(&level1_names);
-    validate_name_map(&level2_names);
-    validate_name_map(&level3_names);
+    if !coverage.faults.is_empty() {
+        error!(
+            "{} of {} points don't have exactly one color.",
+            coverage.faults.len(),
+            coverage.samples
+        );
+        std::process::exit(1);
+    }
+    info!("All {} points have exactly one color.", coverage.samples);
+}
+
+fn gamut(dataset: &Dataset, samples: usize) {
+    let blocks = dataset.block_gamut(samples);
+    let mut outside = blocks
+        .iter()
+        .filter(|x| x.coverage() != GamutCoverage::Inside)
+        .collect::<Vec<&BlockGamut>>();
+    outside.sort_by(|a, b| {
+        b.outside
+            .total_cmp(&a.outside)
+            .then(b.max_chroma_loss.total_cmp(&a.max_chroma_loss))
+            .then(a.block.cmp(&b.block))
+    });
+
+    let count =
+        |coverage: GamutCoverage| outside.iter().filter(|x| x.coverage() == coverage).count();
+    println!(
+        "{} of {} blocks lie wholly and {} partly outside sRGB, by share of {} samples outside:",
+        count(GamutCoverage::Outside),
+        blocks.len(),
+        count(GamutCoverage::Partial),
+        samples * samples * samples
+    );
+    println!(
+        "{:>7} {:>10} {:>3} {:<10} block",
+        "outside", "max loss", "id", "abbr"
+    );
+    for x in outside {
+        println!(
+            "{:>6.1}% {:>9.1}% {:>3} {:<10} {}",
+            100.0 * x.outside,
+            100.0 * x.max_chroma_loss,
+            x.color_id,
+            dataset.names()[&x.color_id].abbr,
+            describe_block(dataset, &dataset.blocks()[x.block])
+        );
+    }
+
+    let clipping = get_gamut_clipping(dataset);
+    let colors = get_mean_colors(dataset);
+    let ids = (1..=clipping.len() as u32)
+        .filter(|id| clipping[(id - 1) as usize].is_clipped())
+        .collect::<Vec<u32>>();
+
+    println!();
+    println!(
+        "{} representative colors were clipped into sRGB:",
+        ids.len()
+    );
+    println!(
+        "{:>3} {:<10} {:>6} {:>6}  {:<24} clipped",
+        "id", "abbr", "chroma", "cut to", "unclipped"
+    );
+    for id in ids {
+        let clip = &clipping[(id - 1) as usize];
+        let c = clip.unclipped;
+        println!(
+            "{:>3} {:<10} {:>6.1} {:>6.1}  {:<24} {}",
+            id,
+            dataset.names()[&id].abbr,
+            clip.chroma,
+            clip.chroma - clip.chroma_loss,
+            format!("rgb({:.3}, {:.3}, {:.3})", c.red, c.green, c.blue),
+            export::srgb_hex(colors[(id - 1) as usize])
+        );
+    }
+}
 
-    return level3_names;
+/// List how much of the Munsell solid each color covers, largest first.
+fn print_volumes(dataset: &Dataset) {
+    let volumes = get_volumes(dataset);
+    let mut ids = (1..=volumes.len() as u32).collect::<Vec<u32>>();
+    ids.sort_by(|a, b| {
+        let volume = |id: &u32| volumes[(id - 1) as usize].volume;
+        volume(b).total_cmp(&volume(a)).then(a.cmp(b))
+    });
+
+    let (largest, smallest) = (ids[0], ids[ids.len() - 1]);
+
+    println!();
+    println!(
+        "Share of the Munsell solid, up to chroma 16 and value 10, of every category; the largest is {:.0} times the smallest:",
+        volumes[(largest - 1) as usize].volume / volumes[(smallest - 1) as usize].volume
+    );
+    println!(
+        "{:>7} {:>7} {:>6} {:>3} {:<10} name",
+        "share", "volume", "blocks", "id", "abbr"
+    );
+    for id in ids {
+        let v = &volumes[(id - 1) as usize];
+        let name = &dataset.names()[&id];
+        let mark = if id == largest {
+            " (largest)"
+        } else if id == smallest {
+            " (smallest)"
+        } else {
+            ""
+        };
+        println!(
+            "{:>6.3}% {:>7.1} {:>6} {:>3} {:<10} {}{}",
+            100.0 * v.fraction,
+            v.volume,
+            v.blocks,
+            id,
+            name.abbr,
+            name.name,
+            mark
+        );
+    }
 }
 
-fn get_hues(doc: &roxmltree::Document) -> Vec<String> {
-    let mut amounts: Vec<String> = Vec::new();
+fn stats(dataset: &Dataset, report: ReportFormat) {
+    let summary = dataset.stats();
+    if let ReportFormat::Json = report {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&summary.to_json()).unwrap()
+        );
+        return;
+    }
+
+    println!(
+        "{} level 1, {} level 2 and {} level 3 names",
+        dataset.level1_names().len(),
+        dataset.level2_names().len(),
+        dataset.names().len()
+    );
+    println!(
+        "{} blocks, between {} hue, {} chroma and {} value boundaries",
+        dataset.blocks().len(),
+        dataset.hues().len(),
+        dataset.chromas().len(),
+        dataset.values().len()
+    );
+    println!(
+        "Blocks use chroma {} to {} and value {} to {}, besides INF",
+        summary.chroma_range.0,
+        summary.chroma_range.1,
+        summary.value_range.0,
+        summary.value_range.1
+    );
 
-    let values = doc.descendants().find(|n| n.has_tag_name("hues")).unwrap();
+    print_volumes(dataset);
+
+    println!();
+    println!("Share of the Munsell solid of every level 1 name:");
+    println!(
+        "{:>7} {:>7} {:>6} {:>3} name",
+        "share", "volume", "colors", "id"
+    );
+    for family in summary.families.iter() {
+        println!(
+            "{:>6.3}% {:>7.1} {:>6} {:>3} {}",
+            100.0 * family.fraction,
+            family.volume,
+            family.colors,
+            family.level1_id,
+            dataset.level1_names()[&family.level1_id].name
+        );
+    }
 
-    for amount_elem in values.children().filter(|n| n.is_element()) {
-        amounts.push(amount_elem.attribute("id").unwrap().to_string());
+    println!();
+    println!("Blocks on every hue leaf, and blocks beginning and ending at its first hue:");
+    println!("{:<10} {:>6} {:>6} {:>6}", "leaf", "blocks", "begin", "end");
+    for (leaf, boundary) in summary.leaves.iter().zip(summary.hue_boundaries.iter()) {
+        println!(
+            "{:<10} {:>6} {:>6} {:>6}",
+            leaf.leaf, leaf.blocks, boundary.begins, boundary.ends
+        );
     }
 
-    return amounts;
+    let clipping = get_gamut_clipping(dataset);
+    let mut ids = (1..=clipping.len() as u32)
+        .filter(|id| clipping[(id - 1) as usize].is_clipped())
+        .collect::<Vec<u32>>();
+    ids.sort_by(|a, b| {
+        let loss = |id: &u32| clipping[(id - 1) as usize].loss_fraction();
+        loss(b).total_cmp(&loss(a)).then(a.cmp(b))
+    });
+
+    println!();
+    println!(
+        "{} categories lose chroma to fit into sRGB, by share of chroma lost:",
+        ids.len()
+    );
+    for id in ids {
+        let clip = &clipping[(id - 1) as usize];
+        let name = &dataset.names()[&id];
+        let steps = match clip.steps {
+            0 => String::new(),
+            n => format!(", {} steps", n),
+        };
+        println!(
+            "{:>5.1}% {:>3} {:<10} {} (chroma {:.1} to {:.1}{})",
+            100.0 * clip.loss_fraction(),
+            id,
+            name.abbr,
+            name.name,
+            clip.chroma,
+            clip.chroma - clip.chroma_loss,
+            steps
+        );
+    }
 }
 
-fn get_amount_list(tag_name: &str, doc: &roxmltree::Document) -> Vec<String> {
-    let mut amounts: Vec<String> = Vec::new();
+fn print_centroids(dataset: &Dataset, order: CentroidOrder, method: CentroidMethod) {
+    let centroids = get_mean_munsell_with(dataset, method);
+    let colors = get_mean_colors(dataset);
+    let neutrals = dataset.neutral_ids();
+    let level1_id = |id: u32| -> u32 {
+        let level2 = dataset.names()[&id].parent.unwrap();
+        return dataset.level2_names()[&level2].parent.unwrap();
+    };
 
-    let values = doc
-        .descendants()
-        .find(|n| n.has_tag_name(tag_name))
-        .unwrap();
+    let mut ids = dataset.names().keys().copied().collect::<Vec<u32>>();
+    ids.sort();
+    match order {
+        CentroidOrder::Id => {}
+        CentroidOrder::Hue => ids.sort_by(|a, b| {
+            let (ca, cb) = (&centroids[(a - 1) as usize], &centroids[(b - 1) as usize]);
+            // the hues of the neutrals are meaningless; order them by value
+            let by_hue = if neutrals.contains(a) {
+                Ordering::Equal
+            } else {
+                ca.hue.raw().partial_cmp(&cb.hue.raw()).unwrap()
+            };
+            return neutrals
+                .contains(a)
+                .cmp(&neutrals.contains(b))
+                .then(by_hue)
+                .then(cb.value.partial_cmp(&ca.value).unwrap());
+        }),
+        CentroidOrder::Group => ids.sort_by_key(|x| (level1_id(*x), *x)),
+    }
+
+    println!(
+        "{:>3} {:<10} {:<36} {:<18} srgb",
+        "id", "abbr", "name", "centroid"
+    );
+    let mut group = None;
+    for id in ids {
+        if order == CentroidOrder::Group && group != Some(level1_id(id)) {
+            group = Some(level1_id(id));
+            println!("\n{}", dataset.level1_names()[&level1_id(id)].name);
+        }
 
-    for amount_elem in values.children().filter(|n| n.is_element()) {
-        amounts.push(amount_elem.text().unwrap().to_string());
+        let name = &dataset.names()[&id];
+        let centroid = &centroids[(id - 1) as usize];
+        println!(
+            "{:>3} {:<10} {:<36} {:<18} {}",
+            id,
+            name.abbr,
+            name.name,
+            format!(
+                "{} {:.1}/{:.1}",
+                centroid.hue, centroid.value, centroid.chroma
+            ),
+            export::srgb_hex(colors[(id - 1) as usize])
+        );
     }
+}
 
-    // We actually want to keep these values as strings for index lookup, but
-    // also we do want to verify that these are floating-point values in sorted
-    // order.
+/// Read a table of published centroids, with one `id,munsell` pair per
+/// line.
+fn read_centroid_table(table: &Path) -> HashMap<u32, MunsellColor> {
+    let text = match std::fs::read_to_string(table) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("cannot read {}: {}.", table.display(), e);
+            std::process::exit(1);
+        }
+    };
+    return match centroids::parse_centroid_table(&text) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}.", e);
+            std::process::exit(1);
+        }
+    };
+}
 
-    let mut amounts_f32 = amounts
-        .clone()
-        .into_iter()
-        .map(|x| x.parse::<f32>().unwrap());
-    if !IsSorted::is_sorted(&mut amounts_f32) {
-        println!("Error: {} array is not in sorted order.", tag_name);
+fn compare_centroids(dataset: &Dataset, table: &Path, threshold: f32, method: CentroidMethod) {
+    let published = read_centroid_table(table);
+
+    let deviations = dataset.centroid_deviations_with(&published, method);
+    println!(
+        "{:>3} {:<10} {:>7}  {:<18} {:<18}",
+        "id", "abbr", "dE2000", "computed", "published"
+    );
+    for deviation in deviations.iter() {
+        let name = &dataset.names()[&deviation.color_id];
+        let format = |x: &MunsellColor| format!("{} {:.1}/{:.1}", x.hue, x.value, x.chroma);
+        println!(
+            "{:>3} {:<10} {:>7.2}  {:<18} {:<18}{}",
+            deviation.color_id,
+            name.abbr,
+            deviation.delta_e,
+            format(&deviation.computed),
+            format(&deviation.published),
+            if deviation.delta_e > threshold {
+                "  !"
+            } else {
+                ""
+            }
+        );
+    }
+
+    let flagged = deviations.iter().filter(|x| x.delta_e > threshold).count();
+    println!();
+    println!(
+        "{} of {} centroids differ by more than {}.",
+        flagged,
+        deviations.len(),
+        threshold
+    );
+}
+
+fn accuracy(table: &Path, worst: usize) {
+    let text = match std::fs::read_to_string(table) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("cannot read {}: {}.", table.display(), e);
+            std::process::exit(1);
+        }
+    };
+    let reference = match accuracy::parse_reference_table(&text) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}.", e);
+            std::process::exit(1);
+        }
+    };
+    if reference.is_empty() {
+        error!("{} has no reference colors.", table.display());
         std::process::exit(1);
     }
 
-    return amounts;
+    let errors = accuracy::conversion_errors(&reference);
+    println!("{:<7} {:>6} {:>7} {:>7}", "hue", "colors", "mean", "max");
+    for hue in accuracy::hue_accuracy(&errors) {
+        println!(
+            "{:<7} {:>6} {:>7.2} {:>7.2}",
+            hue.hue, hue.count, hue.mean, hue.max
+        );
+    }
+
+    let mut sorted = errors.iter().collect::<Vec<_>>();
+    sorted.sort_by(|a, b| b.delta_e.partial_cmp(&a.delta_e).unwrap());
+    if worst > 0 {
+        println!();
+        println!(
+            "{:<18} {:>7}  {:<8} reference",
+            "munsell", "dE2000", "computed"
+        );
+        for error in sorted.iter().take(worst) {
+            let computed: Srgb = error.computed.clamp();
+            println!(
+                "{:<18} {:>7.2}  {:<8} {}",
+                error
+                    .reference
+                    .munsell
+                    .format(&MunsellFormat::conventional()),
+                error.delta_e,
+                export::srgb_hex(computed),
+                export::srgb_hex(error.reference.srgb.into_format())
+            );
+        }
+    }
+
+    let mean = errors.iter().map(|x| x.delta_e).sum::<f32>() / errors.len() as f32;
+    println!();
+    println!(
+        "{} colors, mean CIEDE2000 {:.2}, max {:.2}.",
+        errors.len(),
+        mean,
+        sorted[0].delta_e
+    );
 }
 
-fn get_chromas(doc: &roxmltree::Document) -> Vec<String> {
-    return get_amount_list("chromas", &doc);
+fn describe_block(dataset: &Dataset, block: &ColorBlock) -> String {
+    let (hues, chromas, values) = (dataset.hues(), dataset.chromas(), dataset.values());
+    return format!(
+        "hue {} to {}, chroma {} to {}, value {} to {}",
+        hues[block.hues.start],
+        hues[block.hues.end],
+        chromas[block.chromas.start],
+        chromas[block.chromas.end],
+        values[block.values.start],
+        values[block.values.end]
+    );
 }
 
-fn get_values(doc: &roxmltree::Document) -> Vec<String> {
-    return get_amount_list("values", &doc);
+/// A row of a swatch, as spaces on a 24-bit background color.
+fn swatch(color: Srgb) -> String {
+    let c: Srgb<u8> = color.into_format();
+    return format!(
+        "\x1b[48;2;{};{};{}m{:16}\x1b[0m",
+        c.red, c.green, c.blue, ""
+    );
 }
 
-fn validate_blocks(
-    doc: &roxmltree::Document,
-    hues: &Vec<String>,
-    chromas: &Vec<String>,
-    values: &Vec<String>,
-) -> Vec<ColorBlock> {
-    // The lookup table is logically a three-dimensional array, but initializing a
-    // vector of vectors of vectors is Actually Kind Of A Pain?
-    //
-    // We remove one from chroma and values length because of the INF at the end.
-    let mut lookup_table: Vec<u32> =
-        Vec::with_capacity(hues.len() * (chromas.len() - 1) * (values.len() - 1));
-    let mut blocks: Vec<ColorBlock> = Vec::new();
+/// `text` in a 24-bit background color, in black or white to stand out
+/// from it.
+fn paint(color: Srgb, text: &str) -> String {
+    let c: Srgb<u8> = color.into_format();
+    let lab: Lab = color.into_color();
+    let foreground = if lab.l > 40.0 { 0 } else { 255 };
+    return format!(
+        "\x1b[48;2;{};{};{}m\x1b[38;2;{f};{f};{f}m{}\x1b[0m",
+        c.red,
+        c.green,
+        c.blue,
+        text,
+        f = foreground
+    );
+}
 
-    lookup_table.resize(hues.len() * (chromas.len() - 1) * (values.len() - 1), 0);
-    let index = |h: usize, c: usize, v: usize| -> Option<usize> {
-        if h > hues.len() {
-            return None;
+/// Check a synonym file, then print the colors of the synonym `query`, or
+/// the synonyms of the color it names; without a query, how many colors
+/// have synonyms.
+fn synonyms(dataset: &Dataset, file: &Path, query: Option<&str>) {
+    let synonyms = match Synonyms::load(file) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("{}: {}.", file.display(), e);
+            std::process::exit(1);
         }
-        if c > (chromas.len() - 1) {
-            return None;
+    };
+    let diagnostics = synonyms.check(dataset);
+    for diagnostic in diagnostics.iter() {
+        diagnostic.log();
+    }
+    if diagnostics.iter().any(|x| x.severity == Severity::Error) {
+        std::process::exit(1);
+    }
+
+    let query = match query {
+        Some(v) => v,
+        None => {
+            let mut ids = synonyms
+                .entries()
+                .iter()
+                .flat_map(|x| x.ids.iter().copied())
+                .collect::<Vec<u32>>();
+            ids.sort();
+            ids.dedup();
+            println!(
+                "{} synonyms, for {} of the {} colors.",
+                synonyms.entries().len(),
+                ids.len(),
+                dataset.names().len()
+            );
+            return;
         }
-        if v > (values.len() - 1) {
-            return None;
+    };
+
+    if let Some(synonym) = synonyms.lookup(query) {
+        for id in synonym.ids.iter() {
+            print_name(3, *id, &dataset.names()[id]);
         }
-        return Some((h * (chromas.len() - 1) * (values.len() - 1)) + (c * (values.len() - 1)) + v);
+        return;
+    }
+    let id = match query.trim().parse::<u32>() {
+        Ok(v) if dataset.names().contains_key(&v) => Some(v),
+        Ok(_) => None,
+        Err(_) => dataset
+            .lookup_name(query)
+            .or_else(|| dataset.lookup_abbr(query)),
     };
+    match id {
+        Some(id) => {
+            print_name(3, id, &dataset.names()[&id]);
+            for synonym in synonyms.of_color(id) {
+                println!("  {}", synonym.name);
+            }
+        }
+        None => {
+            error!("'{}' is neither a synonym nor a color.", query);
+            std::process::exit(1);
+        }
+    }
+}
 
-    let ranges = doc
-        .descendants()
-        .find(|n| n.has_tag_name("ranges"))
-        .unwrap();
+/// The level 3 color of an id, a name, an abbreviation or a Munsell
+/// specification, in that order.
+fn find_color(dataset: &Dataset, text: &str) -> Option<u32> {
+    if let Ok(id) = text.trim().parse::<u32>() {
+        return dataset.names().contains_key(&id).then_some(id);
+    }
+    return dataset
+        .lookup_name(text)
+        .or_else(|| dataset.lookup_abbr(text))
+        .or_else(|| {
+            text.parse::<MunsellColor>()
+                .ok()
+                .and_then(|x| dataset.lookup_id(&x))
+        });
+}
 
-    for huerange in ranges.children().filter(|n| n.is_element()) {
-        let hue_begin_index = hues
-            .iter()
-            .position(|x| x == huerange.attribute("begin").unwrap())
-            .unwrap();
-        let hue_end_index = hues
-            .iter()
-            .position(|x| x == huerange.attribute("end").unwrap())
-            .unwrap();
-
-        // hues will wrap around; ensure that begin < logical_end, and then
-        // when using the hue index later we'll mod it by length
-        let hue_logical_end_index;
-        if hue_end_index < hue_begin_index {
-            hue_logical_end_index = hue_end_index + hues.len();
-        } else {
-            hue_logical_end_index = hue_end_index;
+fn show(dataset: &Dataset, text: &str) {
+    let id = match find_color(dataset, text) {
+        Some(v) => v,
+        None => {
+            error!(
+                "'{}' is not a level 3 color id, name or abbreviation, nor a Munsell color in one.",
+                text
+            );
+            std::process::exit(1);
         }
+    };
 
-        for range in huerange.children().filter(|n| n.is_element()) {
-            let color_id = range.attribute("color").unwrap().parse::<u32>().unwrap();
-            let chroma_begin_index = chromas
-                .iter()
-                .position(|x| x == range.attribute("chroma-begin").unwrap())
-                .unwrap();
-            let chroma_end_index = chromas
-                .iter()
-                .position(|x| x == range.attribute("chroma-end").unwrap())
-                .unwrap();
-            let value_begin_index = values
-                .iter()
-                .position(|x| x == range.attribute("value-begin").unwrap())
-                .unwrap();
-            let value_end_index = values
-                .iter()
-                .position(|x| x == range.attribute("value-end").unwrap())
-                .unwrap();
+    print_color(
+        dataset,
+        id,
+        &get_mean_colors(dataset),
+        &get_mean_munsell(dataset),
+    );
+}
+
+/// Print the names of a level 3 color, its representative color and its
+/// centroid next to a swatch of it, from the `colors` and `centroids` of
+/// every color, indexed by `id - 1`.
+fn print_color(dataset: &Dataset, id: u32, colors: &[Srgb], centroids: &[MunsellColor]) {
+    let color = colors[(id - 1) as usize];
+    let centroid = &centroids[(id - 1) as usize];
+    let names = dataset.level_names(id).unwrap();
+    let lines = [
+        format!("{:>3} {:<10} {}", id, names.level3.abbr, names.level3.name),
+        format!("level 2: {}", names.level2.name),
+        format!("level 1: {}", names.level1.name),
+        format!(
+            "{}, centroid {} {:.1}/{:.1}",
+            export::srgb_hex(color),
+            centroid.hue,
+            centroid.value,
+            centroid.chroma
+        ),
+    ];
+    for line in lines.iter() {
+        println!("{}  {}", swatch(color), line);
+    }
+}
+
+/// Answer a line of `repl`: print the color it gives, if it is a Munsell
+/// specification or an sRGB color, and the level 3 color it falls into or
+/// names.
+fn answer(dataset: &Dataset, colors: &[Srgb], centroids: &[MunsellColor], text: &str) {
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+
+    let given = match text.parse::<MunsellColor>() {
+        Ok(munsell) => {
+            let color: Srgb = munsell.to_display_lch().into_color();
+            Some((color.clamp(), munsell))
+        }
+        Err(_) if find_color(dataset, text).is_some() => None,
+        Err(_) => parse_srgb(text).map(|x| (x.into_format(), srgb_to_munsell(x.into_format()))),
+    };
+    let id = match &given {
+        Some((_, munsell)) => dataset.lookup_id(munsell),
+        None => find_color(dataset, text),
+    };
 
-            for h in hue_begin_index..hue_logical_end_index {
-                let h = h % hues.len();
+    if let Some((color, munsell)) = &given {
+        println!(
+            "{}  {}, munsell {} {:.1}/{:.1}",
+            swatch(*color),
+            export::srgb_hex(*color),
+            munsell.hue,
+            munsell.value,
+            munsell.chroma
+        );
+    }
+    match id {
+        Some(id) => print_color(dataset, id, colors, centroids),
+        None if given.is_some() => println!("outside of the Munsell color solid"),
+        None => println!(
+            "'{}' is no sRGB color, Munsell specification, color id, name or abbreviation",
+            text
+        ),
+    }
+}
 
-                for c in chroma_begin_index..chroma_end_index {
-                    for v in value_begin_index..value_end_index {
-                        let idx = index(h, c, v).unwrap();
+/// Switch the terminal of standard input to sending every key as it is
+/// pressed, without echoing it, and Ctrl-C as a key; returns the settings
+/// to restore, or `None` if standard input is no terminal or `stty` failed.
+fn raw_terminal() -> Option<String> {
+    if !std::io::stdin().is_terminal() {
+        return None;
+    }
+    let saved = std::process::Command::new("stty")
+        .arg("-g")
+        .stdin(std::process::Stdio::inherit())
+        .output()
+        .ok()
+        .filter(|x| x.status.success())?;
+    let saved = String::from_utf8(saved.stdout).ok()?.trim().to_string();
+    let status = std::process::Command::new("stty")
+        .args(["-icanon", "-echo", "-isig", "min", "1", "time", "0"])
+        .status()
+        .ok()?;
+    return status.success().then_some(saved);
+}
 
-                        if lookup_table[idx] != 0 {
-                            println!(
-                                "Error: Trying to place color {} over {} at h={} c={} v={}",
-                                color_id, lookup_table[idx], hues[h], chromas[c], values[v]
-                            );
-                            std::process::exit(1);
-                        }
+fn restore_terminal(saved: &str) {
+    let _ = std::process::Command::new("stty").arg(saved).status();
+}
 
-                        lookup_table[idx] = color_id;
+fn repl(dataset: &Dataset) {
+    const PROMPT: &str = "iscc-nbs> ";
+
+    let colors = get_mean_colors(dataset);
+    let centroids = get_mean_munsell(dataset);
+
+    // without a terminal, like from a pipe, answer line by line
+    let saved = match raw_terminal() {
+        Some(v) => v,
+        None => {
+            for line in std::io::stdin().lines() {
+                match line {
+                    Ok(v) => answer(dataset, &colors, &centroids, &v),
+                    Err(e) => {
+                        error!("standard input: {}.", e);
+                        std::process::exit(1);
                     }
                 }
             }
-
-            blocks.push(ColorBlock {
-                color_id: color_id,
-                hues: Range {
-                    start: hue_begin_index,
-                    end: hue_end_index,
-                },
-                chromas: Range {
-                    start: chroma_begin_index,
-                    end: chroma_end_index,
-                },
-                values: Range {
-                    start: value_begin_index,
-                    end: value_end_index,
-                },
-            })
+            return;
         }
-    }
+    };
 
-    // now validate that all slots have been filled
-    for h in 0..hues.len() {
-        for c in 0..chromas.len() - 1 {
-            for v in 0..values.len() - 1 {
-                let idx = index(h, c, v).unwrap();
+    let draw = |editor: &LineEditor| {
+        let line = editor.line();
+        print!("\r\x1b[K{}{}", PROMPT, line);
+        let back = line.chars().count() - editor.cursor();
+        if back > 0 {
+            print!("\x1b[{}D", back);
+        }
+        let _ = std::io::stdout().flush();
+    };
 
-                if lookup_table[idx] == 0 {
-                    println!(
-                        "Error: No color placed at h={} c={} v={}",
-                        hues[h], chromas[c], values[v]
-                    );
-                    std::process::exit(1);
-                }
+    let mut editor = LineEditor::new();
+    let mut input = std::io::stdin().lock();
+    draw(&editor);
+    loop {
+        let key = match read_key(&mut input) {
+            Ok(Some(v)) => v,
+            Ok(None) => break,
+            Err(e) => {
+                restore_terminal(&saved);
+                println!();
+                error!("standard input: {}.", e);
+                std::process::exit(1);
+            }
+        };
+        match editor.key(key, |x| repl::completions(dataset, x)) {
+            Edit::Redraw => {}
+            Edit::Candidates(candidates) => {
+                println!();
+                println!("{}", candidates.join("  "));
             }
+            Edit::Submit(line) => {
+                println!();
+                answer(dataset, &colors, &centroids, &line);
+            }
+            Edit::Exit => break,
         }
+        draw(&editor);
     }
+    println!();
+    restore_terminal(&saved);
+}
 
-    return blocks;
+/// Every level 3 color as a grid of swatches, in order of id.
+fn show_all(dataset: &Dataset) {
+    const COLUMNS: usize = 5;
+
+    let colors = get_mean_colors(dataset);
+    let mut ids = dataset.names().keys().copied().collect::<Vec<u32>>();
+    ids.sort();
+    for row in ids.chunks(COLUMNS) {
+        let cells = row
+            .iter()
+            .map(|id| {
+                let cell = format!(" {:>3} {:<10}", id, dataset.names()[id].abbr);
+                return paint(colors[(id - 1) as usize], &cell);
+            })
+            .collect::<Vec<String>>();
+        println!("{}", cells.join(""));
+    }
 }
 
-fn deinfinite(x: String) -> String {
-    if x == "INF" {
-        "9999".to_string()
-    } else {
-        x
-    }
-}
-
-#[derive(Clone)]
-struct ColorAccumulator {
-    v: f32,
-    c: f32,
-    hx: f32,
-    hy: f32,
-    volume: f32,
-}
-
-fn get_mean_colors(
-    blocks: &Vec<ColorBlock>,
-    hues: &Vec<String>,
-    chromas: &Vec<String>,
-    values: &Vec<String>,
-) -> Vec<Srgb> {
-    // make a bucket for each level3
-    let mut acc: Vec<ColorAccumulator> = Vec::with_capacity(267);
-    acc.resize(
-        267,
-        ColorAccumulator {
-            v: 0.0,
-            c: 0.0,
-            hx: 0.0,
-            hy: 0.0,
-            volume: 0.0,
-        },
-    );
+fn compare(dataset: &Dataset, a: u32, b: u32) {
+    for id in [a, b] {
+        if !dataset.names().contains_key(&id) {
+            error!("there is no level 3 color {}.", id);
+            std::process::exit(1);
+        }
+    }
 
-    for block in blocks {
-        let hue_start = hues[block.hues.start].clone();
-        let hue_end = hues[block.hues.end].clone();
-        let chroma_start = chromas[block.chromas.start].clone();
-        let chroma_end = deinfinite(chromas[block.chromas.end].clone());
-        let value_start = values[block.values.start].clone();
-        let value_end = deinfinite(values[block.values.end].clone());
-
-        let hue_start = MunsellHue::from_str(&hue_start);
-        let hue_end = MunsellHue::from_str(&hue_end);
-        let hue_delta = degree_diff(hue_start.to_degrees(), hue_end.to_degrees());
-
-        let chroma_start_f: f32 = chroma_start.parse().unwrap();
-        let chroma_end_f: f32 = chroma_end.parse::<f32>().unwrap().min(16.0);
-        let value_start_f: f32 = value_start.parse().unwrap();
-        let value_end_f: f32 = value_end.parse::<f32>().unwrap().min(10.0);
-
-        let area_outer = chroma_end_f * chroma_end_f * hue_delta.to_degrees() / 360.0;
-        let area_inner = chroma_start_f * chroma_start_f * hue_delta.to_degrees() / 360.0;
-        let area = area_outer - area_inner;
-        let volume = area * (value_end_f - value_start_f);
-
-        let center_chroma = (chroma_start_f + chroma_end_f) / 2.0;
-        let center_value = (value_start_f + value_end_f) / 2.0;
-        let center_hue = degree_average(hue_start.to_degrees(), hue_end.to_degrees());
-        let center_huex = center_hue.to_radians().cos();
-        let center_huey = center_hue.to_radians().sin();
-
-        let a = &mut acc[(block.color_id - 1) as usize];
-        a.v += center_value * volume;
-        a.c += center_chroma * volume;
-        a.hx += center_huex * volume;
-        a.hy += center_huey * volume;
-        a.volume += volume;
-    }
-
-    let rgbout = acc
-        .into_iter()
-        .map(|a| {
-            let angle_degrees = ((a.hy / a.volume).atan2(a.hx / a.volume)).to_degrees();
-            let munsell_hue = MunsellHue::new(((angle_degrees * 100.0 / 360.0) + 100.0) % 100.0);
-            let mun = MunsellColor::new(munsell_hue, a.v / a.volume, a.c / a.volume);
-
-            // Convert average Munsell color to Lch, then to RGB. If the resulting RGB
-            // is out-of-range, reduce chroma until we're back in-range.
-            let mut lch = mun.to_approximate_lch();
-            let mut rgb = Srgb::from_color_unclamped(lch);
-            loop {
-                if rgb.is_within_bounds() {
-                    break;
-                }
+    let centroids = get_mean_munsell(dataset);
+    let colors = get_mean_colors(dataset);
+    for id in [a, b] {
+        let name = &dataset.names()[&id];
+        let centroid = &centroids[(id - 1) as usize];
+        println!("{:>3} {:<10} {}", id, name.abbr, name.name);
+        println!(
+            "    centroid {} {:.1}/{:.1}, {}",
+            centroid.hue,
+            centroid.value,
+            centroid.chroma,
+            export::srgb_hex(colors[(id - 1) as usize])
+        );
+        for block in dataset.blocks().iter().filter(|x| x.color_id == id) {
+            println!("    {}", describe_block(dataset, block));
+        }
+        println!();
+    }
 
-                lch.chroma *= 0.99;
-                rgb = Srgb::from_color_unclamped(lch);
-            }
+    let delta_e = centroids[(a - 1) as usize]
+        .to_lab()
+        .get_color_difference(&centroids[(b - 1) as usize].to_lab());
+    println!("CIEDE2000 between centroids: {:.2}", delta_e);
 
-            return rgb;
-        })
-        .collect::<Vec<Srgb>>();
+    let faces = dataset.shared_faces(a, b);
+    if faces.is_empty() {
+        println!("{} and {} share no faces.", a, b);
+    } else {
+        println!("Shared faces:");
+        for face in faces.iter() {
+            let blocks = dataset.blocks();
+            println!(
+                "    {} ({}) meets {} ({}) across {}, {} cell{}",
+                a,
+                describe_block(dataset, &blocks[face.block]),
+                b,
+                describe_block(dataset, &blocks[face.neighbor]),
+                match face.axis {
+                    FaceAxis::Hue => "hue",
+                    FaceAxis::Chroma => "chroma",
+                    FaceAxis::Value => "value",
+                },
+                face.cells,
+                if face.cells == 1 { "" } else { "s" }
+            );
+        }
+    }
 
-    return rgbout;
+    // only draw swatches where they can be seen
+    if std::io::stdout().is_terminal() {
+        println!();
+        println!("{:<17}{}", a, b);
+        for _ in 0..4 {
+            println!(
+                "{} {}",
+                swatch(colors[(a - 1) as usize]),
+                swatch(colors[(b - 1) as usize])
+            );
+        }
+    }
 }
 
-fn generate_gnuplot(
-    blocks: &Vec<ColorBlock>,
-    hues: &Vec<String>,
-    chromas: &Vec<String>,
-    values: &Vec<String>,
-    names: &HashMap<u32, ColorName>,
-    colors: &Vec<Srgb>,
-) {
-    const FONT_FACE: &'static str = "DejaVu Sans";
-    let fc = Fontconfig::new().unwrap();
-    let font = fc.find(FONT_FACE, None).unwrap();
-    let font_data = std::fs::read(font.path).expect("font does not exist");
-    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
-    let measure = TTFParserMeasure::new(&font_face);
-
-    for h in 0..hues.len() {
-        let hue_blocks = blocks.iter().filter(|x| h == x.hues.start);
-
-        let basename = format!(
-            "doc/page{}-{}_hues_{}-{}",
-            16 + (h / 2),
-            h % 2,
-            hues[h],
-            hues[(h + 1) % hues.len()]
-        );
-        let mut file = File::create(format!("{}.gnu", basename)).unwrap();
-
-        writeln!(&mut file, "set encoding utf8").unwrap();
-        writeln!(&mut file, "set xrange [ 0.0 : 16.9 ]").unwrap();
-        writeln!(&mut file, "set yrange [ 0.0 : 10.4 ]").unwrap();
-        writeln!(&mut file, "set grid xtics ytics").unwrap();
-        writeln!(&mut file, "unset key").unwrap();
-        writeln!(&mut file, "set border 3").unwrap();
-        writeln!(&mut file, "set xlabel \"Munsell Chroma\"").unwrap();
-        writeln!(&mut file, "set ylabel \"Munsell Value\"").unwrap();
-        writeln!(
-            &mut file,
-            "set title \"{}-{}\" offset graph 0.45,0",
-            hues[h],
-            hues[(h + 1) % hues.len()]
-        )
-        .unwrap();
-
-        writeln!(&mut file, "set style fill empty").unwrap();
-        writeln!(&mut file, "set style line 1 default").unwrap();
-
-        let mut has_0p7 = false;
-        let mut has_1p2 = false;
-
-        let mut regions: HashMap<u32, Polygon> = HashMap::new();
-
-        for block in hue_blocks {
-            let x1 = chromas[block.chromas.start].clone();
-            let x2 = deinfinite(chromas[block.chromas.end].clone());
-            let y1 = values[block.values.start].clone();
-            let y2 = deinfinite(values[block.values.end].clone());
-
-            let x1f: f64 = x1.parse().unwrap();
-            let x2f: f64 = x2.parse::<f64>().unwrap().min(17.0);
-            let y1f: f64 = y1.parse().unwrap();
-            let y2f: f64 = y2.parse::<f64>().unwrap().min(10.5);
-
-            if x1 == "0.7" || x2 == "0.7" {
-                has_0p7 = true;
-            }
+fn describe_name_change(change: &NameChange) -> String {
+    return match change {
+        NameChange::Added { name, abbr } => format!("added {} ({})", name, abbr),
+        NameChange::Removed { name, abbr } => format!("removed {} ({})", name, abbr),
+        NameChange::Renamed {
+            old_name,
+            old_abbr,
+            name,
+            abbr,
+        } => format!("renamed {} ({}) to {} ({})", old_name, old_abbr, name, abbr),
+    };
+}
 
-            if x1 == "1.2" || x2 == "1.2" {
-                has_1p2 = true;
-            }
+fn diff(old_path: &Path, new_path: &Path) {
+    let old = load_dataset(old_path);
+    let new = load_dataset(new_path);
+    let diff = old.diff(&new);
+    if diff.is_empty() {
+        println!("The datasets have the same names and blocks.");
+        return;
+    }
 
-            let area = Polygon::new(
-                LineString(vec![
-                    Coordinate { x: x1f, y: y1f },
-                    Coordinate { x: x1f, y: y2f },
-                    Coordinate { x: x2f, y: y2f },
-                    Coordinate { x: x2f, y: y1f },
-                ]),
-                vec![],
+    if !diff.names.is_empty() {
+        println!(
+            "{} name{} changed:",
+            diff.names.len(),
+            if diff.names.len() == 1 { "" } else { "s" }
+        );
+        for name in diff.names.iter() {
+            println!(
+                "level {}: {:>3} {}",
+                name.level,
+                name.color_id,
+                describe_name_change(&name.change)
             );
-            if regions.contains_key(&block.color_id) {
-                let union = regions.get(&block.color_id).unwrap().union(&area, 10.0);
-                regions.insert(block.color_id, union.into_iter().next().unwrap());
-            } else {
-                regions.insert(block.color_id, area);
+        }
+    }
+
+    if !diff.cells.is_empty() {
+        if !diff.names.is_empty() {
+            println!();
+        }
+        let describe = |id: Option<u32>, dataset: &Dataset| match id {
+            Some(id) => format!("{} ({})", id, dataset.names()[&id].abbr),
+            None => "nothing".to_string(),
+        };
+        println!(
+            "{} cell{} changed color:",
+            diff.cells.len(),
+            if diff.cells.len() == 1 { "" } else { "s" }
+        );
+        for ((from, to), cells) in group_cells(&diff.cells) {
+            println!("{} to {}:", describe(from, &old), describe(to, &new));
+            for cell in cells {
+                println!(
+                    "    hue {} to {}, chroma {} to {}, value {} to {}",
+                    cell.hues.0,
+                    cell.hues.1,
+                    cell.chromas.0,
+                    cell.chromas.1,
+                    cell.values.0,
+                    cell.values.1
+                );
             }
         }
+    }
+}
 
-        for (id, region) in regions.iter() {
-            writeln!(&mut file, "").unwrap();
-            let color = colors[(id - 1) as usize];
-            let color_u8: Srgb<u8> = color.into_format();
-            writeln!(
-                &mut file,
-                "set object {} polygon from {} fc rgbcolor \"#{:x}\" fs solid 1.0 border lc \"#000000\"",
-                id + 1,
-                region
-                    .exterior()
-                    .points()
-                    .map(|v| format!("{},{}", v.x(), v.y()))
-                    .collect::<Vec<String>>()
-                    .join(" to "),
-                color_u8
-            )
-            .unwrap();
-
-            let extremes = region.extremes().unwrap();
-            let poly_min = Coordinate {
-                x: extremes.x_min.coord.x,
-                y: extremes.y_min.coord.y,
-            };
-            let poly_max = Coordinate {
-                x: extremes.x_max.coord.x,
-                y: extremes.y_max.coord.y,
-            };
+#[derive(Clone, Copy, ValueEnum)]
+enum SolidFormat {
+    /// Wavefront OBJ, with vertex colors
+    Obj,
+    /// glTF 2.0, with the binary data embedded
+    Gltf,
+}
 
-            let label_pos = region.centroid().unwrap();
-            let (label_x, label_y) = (label_pos.x(), label_pos.y());
+fn write_output(output: Option<&Path>, text: &str) {
+    match output {
+        Some(path) => std::fs::write(path, text).unwrap(),
+        None => print!("{}", text),
+    }
+}
 
-            // Should probably be computed from the graph view somehow but:
-            const HORIZ_SCALE_FACTOR: f64 = 6000.0;
-            const VERT_SCALE_FACTOR: f64 = 14000.0;
+fn write_binary_output(output: Option<&Path>, bytes: &[u8]) {
+    match output {
+        Some(path) => std::fs::write(path, bytes).unwrap(),
+        None => std::io::stdout().write_all(bytes).unwrap(),
+    }
+}
 
-            let label_text: String = format!("{}: {}", id, names[&id].name);
+fn export(
+    dataset: &Dataset,
+    format: ExportFormat,
+    order: ExportOrder,
+    level: u32,
+    lut_size: u32,
+    all_levels: bool,
+    output: Option<&Path>,
+) {
+    let colors = export_colors(dataset);
+    let order = match order {
+        ExportOrder::Id => SwatchOrder::Id,
+        ExportOrder::Perceptual => SwatchOrder::Perceptual,
+    };
+    let palette = matches!(
+        format,
+        ExportFormat::Gpl | ExportFormat::Aco | ExportFormat::Ase
+    );
+    if order != SwatchOrder::Id && !(palette || matches!(format, ExportFormat::Csv)) {
+        error!("--order is only available for CSV and palette exports.");
+        std::process::exit(1);
+    }
+    let lut = matches!(format, ExportFormat::Cube);
+    let table = matches!(format, ExportFormat::Npy | ExportFormat::Npz);
+    if level != 3 && !(palette || lut || table) {
+        error!("--level is only available for palette, LUT and NumPy exports.");
+        std::process::exit(1);
+    }
+    if lut_size != DEFAULT_LUT_SIZE && !lut {
+        error!("--lut-size is only available for LUT exports.");
+        std::process::exit(1);
+    }
+    if all_levels && !matches!(format, ExportFormat::RgbTxt) {
+        error!("--all-levels is only available for rgb.txt exports.");
+        std::process::exit(1);
+    }
+    if level != 3 && !table && *representative() != Representative::VolumeCentroid {
+        error!("--representative is only available for level 3 exports.");
+        std::process::exit(1);
+    }
+    let level_colors = match level {
+        1 => get_level1_mean_colors(dataset),
+        2 => get_level2_mean_colors(dataset),
+        _ => colors.clone(),
+    };
 
-            // try a word wrap horizontally
-            let h_word_wrap = WhiteSpaceWordWrap::new(
-                (HORIZ_SCALE_FACTOR * (poly_max.x - poly_min.x)) as u32,
-                &measure,
-            );
-            let h_lines = label_text
-                .as_str()
-                .wrap(&h_word_wrap)
-                .collect::<Vec<&str>>();
-
-            // try a word wrap vertically
-            let v_word_wrap = WhiteSpaceWordWrap::new(
-                (VERT_SCALE_FACTOR * (poly_max.y - poly_min.y)) as u32,
-                &measure,
-            );
-            let v_lines = label_text
-                .as_str()
-                .wrap(&v_word_wrap)
-                .collect::<Vec<&str>>();
-
-            // Base the winner on line count.
-            let is_horiz = h_lines.len() <= v_lines.len();
-
-            let linebreaked_label = (if is_horiz { &h_lines } else { &v_lines }).join("\\n");
-            let rotate = if is_horiz { "norotate" } else { "rotate by 90" };
-            let offset_x = if is_horiz {
-                0.0
-            } else {
-                -((v_lines.len() - 1) as f32) / 2.0
-            };
-            let offset_y = if is_horiz {
-                ((h_lines.len() - 1) as f32) / 2.0
-            } else {
-                0.0
+    match format {
+        ExportFormat::Json => {
+            let json = export::json::to_json(dataset, &colors);
+            let text = serde_json::to_string_pretty(&json).unwrap() + "\n";
+            write_output(output, &text);
+        }
+        ExportFormat::Csv => {
+            let csv = export::csv::to_csv_in_order(dataset, &colors, order);
+            write_output(output, &csv);
+        }
+        ExportFormat::Rust => write_output(output, &export::rust::to_rust(dataset, &colors)),
+        ExportFormat::RgbTxt => write_output(
+            output,
+            &export::rgbtxt::to_rgb_txt(dataset, &colors, all_levels),
+        ),
+        ExportFormat::Xterm => {
+            write_output(output, &export::xterm::to_xterm_table(dataset, &colors))
+        }
+        ExportFormat::XtermRust => {
+            write_output(output, &export::xterm::to_xterm_rust(dataset, &colors))
+        }
+        ExportFormat::Css => write_output(output, &export::css::to_css(dataset, &colors)),
+        ExportFormat::Scss => write_output(output, &export::css::to_scss(dataset, &colors)),
+        ExportFormat::Gpl => write_output(
+            output,
+            &export::gpl::to_gpl(dataset, level, &level_colors, order),
+        ),
+        ExportFormat::Aco => write_binary_output(
+            output,
+            &export::adobe::to_aco(dataset, level, &level_colors, order),
+        ),
+        ExportFormat::Ase => write_binary_output(
+            output,
+            &export::adobe::to_ase(dataset, level, &level_colors, order),
+        ),
+        ExportFormat::Cube => write_output(
+            output,
+            &export::cube::to_cube_with_progress(
+                dataset,
+                level,
+                &level_colors,
+                lut_size,
+                &mut log_progress,
+            ),
+        ),
+        ExportFormat::Npy => write_binary_output(output, &export::npy::to_npy(dataset, level)),
+        ExportFormat::Npz => write_binary_output(output, &export::npy::to_npz(dataset, level)),
+        #[cfg(feature = "sqlite")]
+        ExportFormat::Sqlite => {
+            let path = match output {
+                Some(v) => v,
+                None => {
+                    error!("--output is required for SQLite exports.");
+                    std::process::exit(1);
+                }
             };
+            if let Err(e) = export::sqlite::write_sqlite(path, dataset, &colors) {
+                error!("{}.", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
 
-            // yank off the ID then add it back in boldface (hopefully this doesn't
-            // change the width too much...)
-            let (prefix, suffix) = linebreaked_label.split_once(':').unwrap();
-            let linebreaked_label = format!("{{/:Bold {}}}:{}", prefix, suffix);
-
-            let color_lch: Lch = color.into_color();
-            let textcolor = if color_lch.l > 40.0 {
-                "000000"
-            } else {
-                "FFFFFF"
-            };
+fn solid(dataset: &Dataset, format: SolidFormat, output: Option<&Path>) {
+    let colors = export_colors(dataset);
+    let solid = dataset.color_solid(&colors);
 
-            writeln!(
-                &mut file,
-                "set label {} \"{}\" at first {},{} center {} textcolor \"#{}\" offset character {},{}",
-                id + 1,
-                linebreaked_label,
-                label_x,
-                label_y,
-                rotate,
-                textcolor,
-                offset_x,
-                offset_y
-            )
-            .unwrap();
-        }
-
-        writeln!(
-            &mut file,
-            "set xtics border nomirror out scale 2.0 font '{},8'",
-            FONT_FACE
-        )
-        .unwrap();
-        writeln!(&mut file, "set xtics 0, 2.0").unwrap();
-        writeln!(&mut file, "set xtics add (1.0)").unwrap();
-        if has_0p7 {
-            writeln!(&mut file, "set xtics add (\"0.7\" 0.7 1)").unwrap();
-            writeln!(
-                &mut file,
-                "set label 1000 \"0.7\" at first 0.65,-0.25 center font \"{},6\"",
-                FONT_FACE
-            )
-            .unwrap();
-        }
-        if has_1p2 {
-            writeln!(&mut file, "set xtics add (\"1.2\" 1.2 1)").unwrap();
-            writeln!(
-                &mut file,
-                "set label 1001 \"1.2\" at first 1.25,-0.25 center font \"{},6\"",
-                FONT_FACE
-            )
-            .unwrap();
-        }
-
-        writeln!(&mut file, "set mxtics 2").unwrap();
-        writeln!(
-            &mut file,
-            "set ytics border nomirror out scale 2.0 font '{},8'",
-            FONT_FACE
-        )
-        .unwrap();
-        writeln!(&mut file, "set ytics 0, 1.0").unwrap();
-        writeln!(&mut file, "set mytics 2").unwrap();
-
-        writeln!(
-            &mut file,
-            "set terminal pngcairo size 600,800 enhanced font '{},7'",
-            FONT_FACE
-        )
-        .unwrap();
-        writeln!(&mut file, "set output '{}.png'", basename).unwrap();
-
-        // we need to plot _something_; can't just have polygons
-        writeln!(&mut file, "plot x+9999").unwrap();
-
-        // close and flush the file
-        drop(file);
-
-        Command::new("gnuplot")
-            .arg(format!("{}.gnu", basename))
-            .status()
-            .expect("failed to execute gnuplot");
+    match format {
+        SolidFormat::Obj => write_output(output, &export::obj::to_obj(dataset, &solid)),
+        SolidFormat::Gltf => {
+            let gltf = export::gltf::to_gltf(dataset, &solid);
+            write_output(output, &(serde_json::to_string(&gltf).unwrap() + "\n"));
+        }
     }
 }
 
-fn main() {
-    let text = std::fs::read_to_string("iscc-nbs.xml").unwrap();
+fn print_names(dataset: &Dataset, id: u32, all_levels: bool) {
+    let names = dataset.level_names(id).unwrap();
+    if all_levels {
+        let level2_id = names.level3.parent.unwrap();
+        let level1_id = names.level2.parent.unwrap();
 
-    let opt = roxmltree::ParsingOptions { allow_dtd: true };
+        print_name(1, level1_id, names.level1);
+        print_name(2, level2_id, names.level2);
+    }
+    print_name(3, id, names.level3);
+}
 
-    let doc = match roxmltree::Document::parse_with_options(&text, opt) {
-        Ok(v) => v,
-        Err(e) => {
-            println!("Error: {}.", e);
-            std::process::exit(1);
-        }
+/// What to do without a command: render the charts, or validate the dataset
+/// when they are not built.
+fn default_command() -> Commands {
+    #[cfg(feature = "charts")]
+    return Commands::Plot {
+        args: PlotArgs::default(),
+    };
+    #[cfg(not(feature = "charts"))]
+    return Commands::Validate {
+        report: ReportFormat::Text,
+        deny_warnings: false,
     };
+}
 
-    let level3_names = validate_names(&doc);
+fn main() {
+    let mut cli = Cli::parse();
+    init_logging(cli.verbose, cli.quiet);
+    let adaptation = Adaptation::new(cli.adaptation.into(), cli.illuminant.into());
+    if set_adaptation(adaptation).is_err() {
+        error!("the adaptation cannot change once a color is converted.");
+        std::process::exit(1);
+    }
+    if set_gamut_mapping(cli.gamut_mapping.into()).is_err() {
+        error!("the gamut mapping cannot change once a color is mapped.");
+        std::process::exit(1);
+    }
+    let command = cli.command.take().unwrap_or_else(default_command);
+    // every other output is read as sRGB
+    let tagged = match &command {
+        #[cfg(feature = "charts")]
+        Commands::Export {
+            bundle: Some(_), ..
+        } => false,
+        Commands::Export {
+            format: ExportFormat::Css | ExportFormat::Scss | ExportFormat::Json,
+            ..
+        } => true,
+        #[cfg(feature = "charts")]
+        Commands::Plot {
+            args:
+                PlotArgs {
+                    format: PlotFormat::Svg | PlotFormat::Html,
+                    ..
+                },
+        } => true,
+        _ => false,
+    };
+    if cli.color_space != ColorSpaceArg::Srgb && !tagged {
+        error!("--color-space is only available for CSS, SCSS and JSON exports and SVG and HTML charts.");
+        std::process::exit(1);
+    }
+    if set_rgb_space(cli.color_space.into()).is_err() {
+        error!("the color space cannot change once a color is computed.");
+        std::process::exit(1);
+    }
 
-    let hues = get_hues(&doc);
-    let chromas = get_chromas(&doc);
-    let values = get_values(&doc);
+    // validating reports problems instead of exiting on them
+    if let Commands::Validate {
+        report,
+        deny_warnings,
+    } = command
+    {
+        validate(&cli.dataset, cli.lang.as_deref(), report, deny_warnings);
+        return;
+    }
+    if let Commands::Fmt { output, check } = &command {
+        fmt(&cli.dataset, output.as_deref(), *check);
+        return;
+    }
+    if let Commands::Diff { old, new } = &command {
+        diff(old, new);
+        return;
+    }
+    if let Commands::Accuracy { table, worst } = &command {
+        accuracy(table, *worst);
+        return;
+    }
 
-    let blocks = validate_blocks(&doc, &hues, &chromas, &values);
-    let colors = get_mean_colors(&blocks, &hues, &chromas, &values);
+    #[cfg(feature = "charts")]
+    load_plot_config(&cli);
+    set_representative(&cli);
+    #[cfg(feature = "charts")]
+    if let Commands::Plot { args } = &command {
+        plot_command(&cli, args);
+        return;
+    }
+    let mut dataset = load_dataset(&cli.dataset);
+    if let Some(lang) = &cli.lang {
+        translate(&mut dataset, &cli.dataset, lang);
+    }
 
-    generate_gnuplot(&blocks, &hues, &chromas, &values, &level3_names, &colors);
+    match command {
+        Commands::Lookup {
+            stdin: true, args, ..
+        } => lookup_stdin(&dataset, &args),
+        Commands::Lookup { spec, args, .. } => lookup(&dataset, &spec.unwrap(), &args),
+        Commands::Search { query, limit } => search(&dataset, &query, limit),
+        Commands::Classify { color, args } => classify(&dataset, &color, &args),
+        Commands::Coverage { steps } => coverage(&dataset, steps),
+        Commands::Verify { samples, seed } => verify(&dataset, samples, seed),
+        Commands::Gamut { samples } => gamut(&dataset, samples as usize),
+        Commands::Stats { report } => stats(&dataset, report),
+        Commands::Centroids {
+            table: Some(table),
+            threshold,
+            method,
+            ..
+        } => compare_centroids(&dataset, &table, threshold, method.into()),
+        Commands::Centroids {
+            table: None,
+            sort,
+            method,
+            ..
+        } => print_centroids(&dataset, sort, method.into()),
+        Commands::Compare { a, b } => compare(&dataset, a, b),
+        Commands::Synonyms { file, query } => synonyms(&dataset, &file, query.as_deref()),
+        Commands::Show { all: true, .. } => show_all(&dataset),
+        Commands::Show { color, .. } => show(&dataset, &color.unwrap()),
+        Commands::Repl => repl(&dataset),
+        #[cfg(feature = "charts")]
+        Commands::Export {
+            bundle: Some(bundle),
+            ..
+        } => export_bundle(&dataset, bundle),
+        Commands::Export {
+            format,
+            order,
+            level,
+            lut_size,
+            all_levels,
+            output,
+            ..
+        } => export(
+            &dataset,
+            format,
+            order,
+            level,
+            lut_size,
+            all_levels,
+            output.as_deref(),
+        ),
+        #[cfg(feature = "charts")]
+        Commands::Site { out } => site(&dataset, &out),
+        Commands::Solid { format, output } => solid(&dataset, format, output.as_deref()),
+        Commands::Compile { output } => {
+            write_binary_output(output.as_deref(), &dataset.to_compiled())
+        }
+        Commands::Validate { .. }
+        | Commands::Fmt { .. }
+        | Commands::Diff { .. }
+        | Commands::Accuracy { .. } => {
+            unreachable!()
+        }
+        #[cfg(feature = "charts")]
+        Commands::Plot { .. } => {
+            unreachable!()
+        }
+    }
 }
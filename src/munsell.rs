@@ -1,16 +1,61 @@
 use lazy_static::lazy_static;
-use palette::{LabHue, Lch};
+use palette::white_point::C;
+use palette::{IntoColor, Lab, LabHue, Lch, Oklab, Oklch, Yxy};
 use regex::Regex;
 use std::fmt;
+use std::ops::{Add, Sub};
+use std::str::FromStr;
 
-const LETTER_CODES: &[&str] = &["R", "YR", "Y", "GY", "G", "BG", "B", "PB", "P", "RP"];
+use crate::adaptation::{adaptation, Adaptation};
+use crate::lite::{parse_hue, parse_number};
+use crate::renotation::munsell_to_xyy;
+
+pub(crate) use crate::lite::LETTER_CODES;
+
+/// Why a Munsell specification could not be parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseError {
+    /// The hue is not a number followed by a hue letter code, like `7.5YR`.
+    InvalidHue(String),
+    /// The value is not a number.
+    InvalidValue(String),
+    /// The value is a number, but not one from 0 (black) to 10 (white).
+    ValueOutOfRange(String),
+    /// The chroma is not a number.
+    InvalidChroma(String),
+    /// The color is not of the form `hue value/chroma`, like `7.5YR 6/8`.
+    InvalidColor(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidHue(x) => write!(f, "'{}' is not a Munsell hue like '7.5YR'", x),
+            ParseError::InvalidValue(x) => write!(f, "'{}' is not a Munsell value", x),
+            ParseError::ValueOutOfRange(x) => {
+                write!(f, "Munsell value '{}' is not between 0 and 10", x)
+            }
+            ParseError::InvalidChroma(x) => write!(f, "'{}' is not a Munsell chroma", x),
+            ParseError::InvalidColor(x) => {
+                write!(f, "'{}' is not a Munsell specification like '7.5YR 6/8'", x)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
 
 /// The hue is a circular type, where `0` and `100` is the same, and
 /// it's normalized to `[0, 100)` when it's converted to a linear
 /// number (like `f32`). This makes many calculations easier, but may
 /// also have some surprising effects if it's expected to act as a
 /// linear number.
+///
+/// With the `serde` feature, it is serialized as that number, so it
+/// round-trips exactly.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 #[repr(C)]
 pub struct MunsellHue(f32);
 
@@ -26,19 +71,17 @@ impl MunsellHue {
         self.0
     }
 
-    #[inline]
-    pub fn from_str(huespec: &str) -> Self {
-        Self::new(huespec_to_point(huespec))
+    /// Parse a hue like `7.5YR`, returning `None` if `huespec` is not one.
+    pub fn parse(huespec: &str) -> Option<Self> {
+        return huespec.parse().ok();
     }
 
     #[inline]
-    #[allow(dead_code)]
     pub fn from_degrees(degrees: f32) -> Self {
         Self::new(normalize_angle_positive(degrees * (100.0 / 360.0)))
     }
 
     #[inline]
-    #[allow(dead_code)]
     pub fn from_radians(radians: f32) -> Self {
         Self::new(normalize_angle_positive(
             radians.to_degrees() * (100.0 / 360.0),
@@ -46,59 +89,247 @@ impl MunsellHue {
     }
 
     #[inline]
-    pub fn to_degrees(&self) -> f32 {
+    pub fn to_degrees(self) -> f32 {
         self.0 * (360.0 / 100.0)
     }
 
     #[inline]
-    #[allow(dead_code)]
-    pub fn to_radians(&self) -> f32 {
+    pub fn to_radians(self) -> f32 {
         self.to_degrees().to_radians()
     }
+
+    /// The number of hue steps between two hues, the short way around the
+    /// circle, from `0` to `50`.
+    #[inline]
+    pub fn distance_to(self, other: Self) -> f32 {
+        (other - self).abs()
+    }
+
+    /// Interpolate from this hue to `other` the short way around the circle,
+    /// where `t` of `0` is this hue and `1` is `other`.
+    #[inline]
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    /// The hue halfway between this hue and `other`, the short way around
+    /// the circle.
+    #[inline]
+    pub fn midpoint(self, other: Self) -> Self {
+        self.lerp(other, 0.5)
+    }
+}
+
+/// Turn a hue by a number of hue steps, wrapping around at `100`.
+impl Add<f32> for MunsellHue {
+    type Output = MunsellHue;
+
+    fn add(self, steps: f32) -> MunsellHue {
+        MunsellHue::new(normalize_angle_positive(self.0 + steps))
+    }
+}
+
+/// Turn a hue back by a number of hue steps, wrapping around at `0`.
+impl Sub<f32> for MunsellHue {
+    type Output = MunsellHue;
+
+    fn sub(self, steps: f32) -> MunsellHue {
+        MunsellHue::new(normalize_angle_positive(self.0 - steps))
+    }
+}
+
+/// The signed number of hue steps from `other` to this hue the short way
+/// around the circle, in `[-50, 50)`.
+impl Sub for MunsellHue {
+    type Output = f32;
+
+    fn sub(self, other: MunsellHue) -> f32 {
+        normalize_angle_positive(self.0 - other.0 + 50.0) - 50.0
+    }
+}
+
+/// How to write Munsell hues and colors, for output meant for people rather
+/// than for parsing back exactly.
+///
+/// The default is the form of `Display`, like `0.00YR 6.5/8`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MunsellFormat {
+    /// How many decimals the number of a hue has.
+    pub hue_precision: usize,
+    /// How many decimals the value and chroma have, or `None` for as many as
+    /// they need.
+    pub precision: Option<usize>,
+    /// Round hues to the nearest of the conventional steps 2.5, 5, 7.5 and
+    /// 10, as on the pages of the Munsell Book of Color.
+    pub snap_hue: bool,
+    /// Write a hue on the boundary between two hue families as 10 of the
+    /// first, like `10R`, rather than 0 of the second, like `0YR`.
+    pub boundary_as_ten: bool,
+}
+
+impl Default for MunsellFormat {
+    fn default() -> Self {
+        return MunsellFormat {
+            hue_precision: 2,
+            precision: None,
+            snap_hue: false,
+            boundary_as_ten: false,
+        };
+    }
+}
+
+impl MunsellFormat {
+    /// The conventional form of published specifications, like
+    /// `10.0R 6.5/8.0`.
+    pub fn conventional() -> Self {
+        return MunsellFormat {
+            hue_precision: 1,
+            precision: Some(1),
+            snap_hue: false,
+            boundary_as_ten: true,
+        };
+    }
+}
+
+impl MunsellHue {
+    /// Write this hue like `7.5YR`, as `format` says.
+    pub fn format(&self, format: &MunsellFormat) -> String {
+        // on the scale where 0R is 0, rounded first, so that 9.999R doesn't
+        // come out as 10.00R
+        let scale = 10f32.powi(format.hue_precision as i32);
+        let mut hp = normalize_angle_positive(self.0 + 5.0);
+        if format.snap_hue {
+            hp = (hp / 2.5).round() * 2.5;
+        }
+        hp = normalize_angle_positive((hp * scale).round() / scale);
+
+        let mut index = (hp / 10.0).floor() as usize % LETTER_CODES.len();
+        let mut hn = hp - (index * 10) as f32;
+        if format.boundary_as_ten && hn == 0.0 {
+            index = (index + LETTER_CODES.len() - 1) % LETTER_CODES.len();
+            hn = 10.0;
+        }
+        return format!(
+            "{:.*}{}",
+            format.hue_precision,
+            hn.max(0.0),
+            LETTER_CODES[index]
+        );
+    }
 }
 
+/// Writes hues like `7.50YR`; a precision, like `{:.1}`, sets the number of
+/// decimals, and [`MunsellHue::format`] has more options.
 impl fmt::Display for MunsellHue {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let hp = (self.0 + 5.0) % 100.0;
-        let hn = hp % 10.0;
-        let index = ((hp - hn) / 10.0) as usize;
+        let format = MunsellFormat {
+            hue_precision: f.precision().unwrap_or(2),
+            ..MunsellFormat::default()
+        };
+        write!(f, "{}", self.format(&format))
+    }
+}
 
-        write!(f, "{:1.2}{}", hn, LETTER_CODES[index])
+impl MunsellHue {
+    /// Parse a hue as leniently as [`str::parse`], and write it in the
+    /// canonical form of the hue boundaries of the dataset: the number with
+    /// at most two decimals and no trailing zeros, the letter code in upper
+    /// case, and hues between two letter codes as 10 of the first, like
+    /// `7.5YR` for ` 07.50 yr` or `10R` for `0YR`.
+    pub fn normalize(huespec: &str) -> Result<String, ParseError> {
+        let format = MunsellFormat {
+            hue_precision: 2,
+            boundary_as_ten: true,
+            ..MunsellFormat::default()
+        };
+        let hue = huespec.parse::<MunsellHue>()?.format(&format);
+        let letters = hue.find(|x: char| x.is_ascii_alphabetic()).unwrap();
+        let (number, code) = hue.split_at(letters);
+        let number = number.trim_end_matches('0').trim_end_matches('.');
+        return Ok(format!("{}{}", number, code));
     }
 }
 
-#[inline]
-fn normalize_angle_positive(point: f32) -> f32 {
-    point - ((point / 100.0).floor() * 100.0)
+impl FromStr for MunsellHue {
+    type Err = ParseError;
+
+    fn from_str(huespec: &str) -> Result<Self, ParseError> {
+        return parse_hue(huespec)
+            .map(Self::new)
+            .ok_or_else(|| ParseError::InvalidHue(huespec.to_string()));
+    }
+}
+
+/// The CIELAB hue angles corresponding to Munsell 5R, 5Y, 5G, 5B, 5P, and
+/// 5R again, for [`MunsellColor::to_approximate_lch`].
+///
+/// LCh has four primaries; we need to sneak Purple in to match.
+const LABHUE_HUES: [f32; 6] = [
+    24.00,          // Red
+    90.00,          // Yellow
+    145.00,         // Green
+    245.00,         // Blue
+    310.00,         // Purple
+    360.00 + 24.00, // Red (again)
+];
+
+/// The luminance factor Y of a Munsell value, in percent relative to the
+/// perfect diffuser, by the quintic polynomial of ASTM D1535; value 10 is
+/// Y = 100.
+///
+/// The renotation data gives Y relative to magnesium oxide instead, which is
+/// about 2.6% higher.
+pub fn value_to_luminance(value: f32) -> f32 {
+    let v = value as f64;
+    let y = 1.1914 * v - 0.22533 * v.powi(2) + 0.23352 * v.powi(3) - 0.020484 * v.powi(4)
+        + 0.00081939 * v.powi(5);
+
+    return y as f32;
+}
+
+/// The Munsell value of a luminance factor Y in percent, inverting
+/// [`value_to_luminance`] by bisection, clamped to `[0, 10]`.
+pub fn luminance_to_value(luminance: f32) -> f32 {
+    const ITERATIONS: usize = 48;
+
+    let mut low = 0.0;
+    let mut high = 10.0;
+    for _ in 0..ITERATIONS {
+        let middle = (low + high) / 2.0;
+        if value_to_luminance(middle) < luminance {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    return (low + high) / 2.0;
 }
 
-fn huespec_to_point(huespec: &str) -> f32 {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r"^(\d*\.?\d+)(R|YR|Y|GY|G|BG|B|PB|P|RP)").unwrap();
+/// CIE L\* of a luminance factor Y in percent.
+fn luminance_to_lightness(luminance: f32) -> f32 {
+    let y = luminance / 100.0;
+    if y > 216.0 / 24389.0 {
+        return 116.0 * y.cbrt() - 16.0;
     }
+    return y * (24389.0 / 27.0);
+}
 
-    let caps = RE.captures(huespec).unwrap();
-    let hue_number = caps.get(1).unwrap().as_str().parse::<f32>().unwrap();
-    let hue_code = (match caps.get(2).unwrap().as_str() {
-        "R" => Ok(0),
-        "YR" => Ok(1),
-        "Y" => Ok(2),
-        "GY" => Ok(3),
-        "G" => Ok(4),
-        "BG" => Ok(5),
-        "B" => Ok(6),
-        "PB" => Ok(7),
-        "P" => Ok(8),
-        "RP" => Ok(9),
-        _ => Err("Invalid hue code"),
-    })
-    .unwrap();
-    let hue_value: f32 = (((hue_code * 10) as f32) + (hue_number - 5.0) + 100.0) % 100.0;
+/// The luminance factor Y in percent of CIE L\*.
+fn lightness_to_luminance(lightness: f32) -> f32 {
+    if lightness > 8.0 {
+        return ((lightness + 16.0) / 116.0).powi(3) * 100.0;
+    }
+    return lightness * (27.0 / 24389.0) * 100.0;
+}
 
-    return hue_value;
+#[inline]
+fn normalize_angle_positive(point: f32) -> f32 {
+    point - ((point / 100.0).floor() * 100.0)
 }
 
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MunsellColor {
     pub hue: MunsellHue,
     pub value: f32,
@@ -115,14 +346,187 @@ impl MunsellColor {
         MunsellColor { hue, value, chroma }
     }
 
+    /// Create a neutral gray, like `N 5/`. Its hue is meaningless, and set to
+    /// `5R`.
+    pub const fn neutral(value: f32) -> Self {
+        Self::new_const(MunsellHue::new(0.0), value, 0.0)
+    }
+
+    /// Whether this color is a neutral, with no chroma.
+    #[inline]
+    pub fn is_neutral(&self) -> bool {
+        self.chroma == 0.0
+    }
+
+    /// Parse a full Munsell specification, like `7.5YR 6/8`, or a neutral,
+    /// like `N 5/`. The value must be from 0 to 10, and the chroma, which
+    /// has no sign, is never negative.
+    ///
+    /// Returns `None` if `spec` is not of that form; use [`str::parse`] to
+    /// find out why.
+    pub fn parse(spec: &str) -> Option<Self> {
+        return spec.parse().ok();
+    }
+
+    /// Convert this color to CIE xyY under Illuminant C, by interpolating
+    /// the Munsell renotation data.
+    pub fn to_xyy(&self) -> Yxy<C> {
+        let (x, y, luminance) = munsell_to_xyy(self.hue, self.value, self.chroma);
+
+        return Yxy::with_wp(x as f32, y as f32, (luminance / 100.0) as f32);
+    }
+
+    /// Convert this color to CIELAB, by interpolating the Munsell
+    /// renotation data.
+    ///
+    /// The renotation is specified under Illuminant C. It is adapted to the
+    /// white point of [`adaptation`], D65 by the Bradford transform unless
+    /// set otherwise, and L\*a\*b\* is relative to that white point, so
+    /// that Munsell neutrals stay neutral.
+    pub fn to_lab(&self) -> Lab {
+        return self.to_lab_with(adaptation());
+    }
+
+    /// Convert this color to CIELAB with the given adaptation; see
+    /// [`MunsellColor::to_lab`].
+    pub fn to_lab_with(&self, adaptation: &Adaptation) -> Lab {
+        return adaptation.to_lab(self.to_xyy());
+    }
+
+    /// Convert this color to CIELAB LCh; see [`MunsellColor::to_lab`].
+    pub fn to_lch(&self) -> Lch {
+        return self.to_lab().into_color();
+    }
+
+    /// Convert this color to CIELAB LCh relative to D65, whatever the
+    /// target white point, for converting to sRGB.
+    pub fn to_display_lch(&self) -> Lch {
+        return adaptation().to_display(self.to_lab()).into_color();
+    }
+
+    /// Convert a CIELAB color to Munsell, inverting [`MunsellColor::to_lab`].
+    pub fn from_lab(lab: &Lab) -> Self {
+        return Self::from_lch(&(*lab).into_color());
+    }
+
+    /// Convert a CIELAB color to Munsell, inverting
+    /// [`MunsellColor::to_lab_with`].
+    pub fn from_lab_with(lab: &Lab, adaptation: &Adaptation) -> Self {
+        return Self::from_lch_with(&(*lab).into_color(), adaptation);
+    }
+
+    /// Convert this color to Oklab, which is relative to D65 whatever the
+    /// target white point of [`adaptation`].
+    pub fn to_oklab(&self) -> Oklab {
+        return adaptation().to_display(self.to_lab()).into_color();
+    }
+
+    /// Convert this color to Oklch; see [`MunsellColor::to_oklab`].
+    pub fn to_oklch(&self) -> Oklch {
+        return self.to_oklab().into_color();
+    }
+
+    /// Convert an Oklab color to Munsell, inverting
+    /// [`MunsellColor::to_oklab`].
+    pub fn from_oklab(oklab: &Oklab) -> Self {
+        return Self::from_lab(&adaptation().from_display((*oklab).into_color()));
+    }
+
+    /// Convert an Oklch color to Munsell, inverting
+    /// [`MunsellColor::to_oklch`].
+    pub fn from_oklch(oklch: &Oklch) -> Self {
+        let oklab: Oklab = (*oklch).into_color();
+        return Self::from_oklab(&oklab);
+    }
+
+    /// Convert a CIELAB LCh color to Munsell, inverting
+    /// [`MunsellColor::to_lch`].
+    ///
+    /// The value is first found by bisection on the L\* of the neutrals. Hue
+    /// and chroma are then refined iteratively, starting from
+    /// [`MunsellColor::from_approximate_lch`], by converting the current
+    /// estimate forward and correcting it by the remaining hue angle and
+    /// chroma ratio, and the value by the remaining difference in L\*, which
+    /// adaptation leaves for chromatic colors.
+    pub fn from_lch(lch: &Lch) -> Self {
+        return Self::from_lch_with(lch, adaptation());
+    }
+
+    /// Convert a CIELAB LCh color to Munsell with the given adaptation; see
+    /// [`MunsellColor::from_lch`].
+    pub fn from_lch_with(lch: &Lch, adaptation: &Adaptation) -> Self {
+        const MAX_ITERATIONS: usize = 64;
+        const TOLERANCE: f32 = 0.0001;
+
+        let mut low = 0.0;
+        let mut high = 10.0;
+        for _ in 0..MAX_ITERATIONS {
+            let middle = (low + high) / 2.0;
+            // the bounds are adjacent floats; bisecting further changes
+            // nothing
+            if middle == low || middle == high {
+                break;
+            }
+            if MunsellColor::new(MunsellHue::new(0.0), middle, 0.0)
+                .to_lab_with(adaptation)
+                .l
+                < lch.l
+            {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        let mut value = (low + high) / 2.0;
+
+        let estimate = Self::from_approximate_lch(lch);
+        if lch.chroma < TOLERANCE {
+            return Self::new(estimate.hue, value, 0.0);
+        }
+
+        let mut hue = estimate.hue.raw();
+        let mut chroma = estimate.chroma;
+        for _ in 0..MAX_ITERATIONS {
+            let current: Lch = Self::new(MunsellHue::new(hue), value, chroma)
+                .to_lab_with(adaptation)
+                .into_color();
+
+            let hue_error = (lch.hue - current.hue).to_degrees();
+            let chroma_ratio = if current.chroma > TOLERANCE {
+                lch.chroma / current.chroma
+            } else {
+                2.0
+            };
+
+            // adapting a chromatic color can move its L* a little away
+            // from that of the neutral of its value
+            let lightness_value = |l: f32| luminance_to_value(lightness_to_luminance(l));
+            let value_error = lightness_value(lch.l) - lightness_value(current.l);
+
+            hue = normalize_angle_positive(hue + hue_error * (100.0 / 360.0));
+            chroma *= chroma_ratio;
+            value = (value + value_error).clamp(0.0, 10.0);
+
+            if hue_error.abs() < TOLERANCE
+                && (chroma_ratio - 1.0).abs() < TOLERANCE
+                && value_error.abs() < TOLERANCE
+            {
+                break;
+            }
+        }
+
+        return Self::new(MunsellHue::new(hue), value, chroma);
+    }
+
     /// Return an approximation of CIELAB Lch from this Munsell color.
     ///
     /// This uses a method similar to Paul Centore's [CIELABtoApproxMunsellSpec](https://github.com/colour-science/MunsellAndKubelkaMunkToolbox/blob/master/GeneralRoutines/CIELABtoApproxMunsellSpec.m),
-    /// where the Munsell value is Lch_L / 10, and Munsell chroma
-    /// is Lch_C / 5. I use a slightly different mechanism for computing
-    /// the resulting hue.
+    /// where Munsell chroma is Lch_C / 5. Instead of taking the Munsell
+    /// value as Lch_L / 10, Lch_L is computed from the luminance of the value
+    /// by [`value_to_luminance`]. I use a slightly different mechanism for
+    /// computing the resulting hue.
     pub fn to_approximate_lch(&self) -> Lch {
-        let l: f32 = self.value * 10.0;
+        let l: f32 = luminance_to_lightness(value_to_luminance(self.value));
         let c: f32 = self.chroma * 5.0;
         let hue: f32 = self.hue.raw();
 
@@ -130,16 +534,6 @@ impl MunsellColor {
         let index = index_float as usize;
         let index_remainder = index_float - (index as f32);
 
-        // LCh has four primaries; we need to sneak Purple in to match
-        const LABHUE_HUES: [f32; 6] = [
-            24.00,          // Red
-            90.00,          // Yellow
-            145.00,         // Green
-            245.00,         // Blue
-            310.00,         // Purple
-            360.00 + 24.00, // Red (again)
-        ];
-
         let h = interpolation::lerp(
             &LABHUE_HUES[index],
             &LABHUE_HUES[index + 1],
@@ -149,23 +543,161 @@ impl MunsellColor {
 
         return Lch::with_wp(l, c, lch_hue);
     }
+
+    /// Like [`MunsellColor::to_approximate_lch`], in Oklch.
+    pub fn to_approximate_oklch(&self) -> Oklch {
+        return self.to_approximate_lch().into_color();
+    }
+
+    /// The inverse of [`MunsellColor::to_approximate_oklch`].
+    pub fn from_approximate_oklch(oklch: &Oklch) -> Self {
+        return Self::from_approximate_lch(&(*oklch).into_color());
+    }
+
+    /// The inverse of [`MunsellColor::to_approximate_lch`].
+    pub fn from_approximate_lch(lch: &Lch) -> Self {
+        let value = luminance_to_value(lightness_to_luminance(lch.l));
+        let chroma = lch.chroma / 5.0;
+
+        // bring the angle into the range covered by LABHUE_HUES
+        let mut h = lch.hue.to_positive_degrees();
+        if h < LABHUE_HUES[0] {
+            h += 360.0;
+        }
+
+        let index = LABHUE_HUES
+            .windows(2)
+            .position(|w| h >= w[0] && h < w[1])
+            .unwrap_or(LABHUE_HUES.len() - 2);
+        let index_remainder =
+            (h - LABHUE_HUES[index]) / (LABHUE_HUES[index + 1] - LABHUE_HUES[index]);
+        let hue = MunsellHue::new(((index as f32) + index_remainder) * 20.0 % 100.0);
+
+        return Self::new(hue, value, chroma);
+    }
+}
+
+impl MunsellColor {
+    /// Write this color like `7.5YR 6/8`, or `N 5/` if it is a neutral, as
+    /// `format` says.
+    pub fn format(&self, format: &MunsellFormat) -> String {
+        let number = |x: f32| match format.precision {
+            Some(precision) => format!("{:.*}", precision, x),
+            None => x.to_string(),
+        };
+        if self.is_neutral() {
+            return format!("N {}/", number(self.value));
+        }
+        return format!(
+            "{} {}/{}",
+            self.hue.format(format),
+            number(self.value),
+            number(self.chroma)
+        );
+    }
 }
 
+/// Writes colors like `7.50YR 6/8`; a precision, like `{:.1}`, sets the
+/// number of decimals of all three parts, and [`MunsellColor::format`] has
+/// more options.
 impl fmt::Display for MunsellColor {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} {}/{}", self.hue, self.value, self.chroma)
+        let format = match f.precision() {
+            Some(precision) => MunsellFormat {
+                hue_precision: precision,
+                precision: Some(precision),
+                ..MunsellFormat::default()
+            },
+            None => MunsellFormat::default(),
+        };
+        write!(f, "{}", self.format(&format))
+    }
+}
+
+impl FromStr for MunsellColor {
+    type Err = ParseError;
+
+    fn from_str(spec: &str) -> Result<Self, ParseError> {
+        lazy_static! {
+            // the hue ends at its letters, so "7.5YR6/8" is accepted too,
+            // and may have a space before them, like "7.5 YR 6/8"; neutrals
+            // may leave out the slash, like "N5"
+            static ref RE: Regex = Regex::new(
+                r"^\s*([^\s/]*?\s*[A-Za-z]+)\s*([^\s/]*)\s*(?:/\s*([^\s/]*))?\s*$"
+            )
+            .unwrap();
+        }
+
+        let caps = match RE.captures(spec) {
+            Some(v) => v,
+            None => return Err(ParseError::InvalidColor(spec.to_string())),
+        };
+        // a neutral has no hue
+        let hue = match caps.get(1).unwrap().as_str() {
+            "N" | "n" => None,
+            hue => Some(hue.parse::<MunsellHue>()?),
+        };
+        let value_text = caps.get(2).unwrap().as_str();
+        let value = parse_number(value_text)
+            .ok_or_else(|| ParseError::InvalidValue(value_text.to_string()))?;
+        if value > 10.0 {
+            return Err(ParseError::ValueOutOfRange(value_text.to_string()));
+        }
+
+        let hue = match hue {
+            Some(v) => v,
+            // the chroma of a neutral is empty, or zero
+            None => {
+                return match caps.get(3).map_or("", |x| x.as_str()) {
+                    "" => Ok(Self::neutral(value)),
+                    chroma if parse_number(chroma) == Some(0.0) => Ok(Self::neutral(value)),
+                    chroma => Err(ParseError::InvalidChroma(chroma.to_string())),
+                };
+            }
+        };
+        let chroma = match caps.get(3) {
+            Some(v) => v.as_str(),
+            None => return Err(ParseError::InvalidColor(spec.to_string())),
+        };
+        let chroma =
+            parse_number(chroma).ok_or_else(|| ParseError::InvalidChroma(chroma.to_string()))?;
+
+        return Ok(Self::new(hue, value, chroma));
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::MunsellHue;
+    use crate::munsell::{luminance_to_value, value_to_luminance, MunsellFormat};
+    use crate::{MunsellColor, MunsellHue, ParseError};
 
     #[test]
     fn hue_from_string() {
-        assert_eq!(MunsellHue::from_str("5R"), MunsellHue::new(0.0));
-        assert_eq!(MunsellHue::from_str("5Y"), MunsellHue::new(20.0));
-        assert_eq!(MunsellHue::from_str("5.5Y"), MunsellHue::new(20.5));
+        assert_eq!("5R".parse::<MunsellHue>().unwrap(), MunsellHue::new(0.0));
+        assert_eq!("5Y".parse::<MunsellHue>().unwrap(), MunsellHue::new(20.0));
+        assert_eq!("5.5Y".parse::<MunsellHue>().unwrap(), MunsellHue::new(20.5));
+        assert_eq!("9RP".parse::<MunsellHue>().unwrap(), MunsellHue::new(94.0));
+    }
+
+    #[test]
+    fn lenient_hues() {
+        let hue = "7.5YR".parse::<MunsellHue>().unwrap();
+        for spec in ["7.5yr", " 7.5YR ", "07.5YR", "7.5 Yr", "7.50YR"] {
+            assert_eq!(spec.parse::<MunsellHue>(), Ok(hue), "{}", spec);
+        }
+        assert_eq!("10R".parse::<MunsellHue>(), "0YR".parse::<MunsellHue>());
+        assert_eq!("7.5 yr 6/8".parse(), Ok(MunsellColor::new(hue, 6.0, 8.0)));
+        assert_eq!("n 5/".parse(), Ok(MunsellColor::neutral(5.0)));
+        assert!("7.5Q".parse::<MunsellHue>().is_err());
+        assert!("7.5 Y R".parse::<MunsellHue>().is_err());
+
+        let normalize = |x: &str| MunsellHue::normalize(x).unwrap();
+        assert_eq!(normalize(" 07.50 yr"), "7.5YR");
+        assert_eq!(normalize("0YR"), "10R");
+        assert_eq!(normalize("10bg"), "10BG");
+        assert_eq!(normalize("0.25p"), "0.25P");
+        assert_eq!(normalize("1R"), "1R");
+        assert!(MunsellHue::normalize("Q").is_err());
     }
 
     #[test]
@@ -174,4 +706,209 @@ mod test {
         assert_eq!(format!("{}", MunsellHue::new(20.0)), "5.00Y");
         assert_eq!(format!("{}", MunsellHue::new(20.5)), "5.50Y");
     }
+
+    #[test]
+    fn custom_formats() {
+        let hue = MunsellHue::new(5.0);
+        assert_eq!(hue.to_string(), "0.00YR");
+        assert_eq!(format!("{:.1}", hue), "0.0YR");
+        let conventional = MunsellFormat::conventional();
+        assert_eq!(hue.format(&conventional), "10.0R");
+        assert_eq!(MunsellHue::new(4.9999).format(&conventional), "10.0R");
+        assert_eq!(MunsellHue::new(94.0).format(&conventional), "9.0RP");
+
+        let snapped = MunsellFormat {
+            snap_hue: true,
+            hue_precision: 1,
+            ..MunsellFormat::default()
+        };
+        assert_eq!(MunsellHue::new(13.1).format(&snapped), "7.5YR");
+        assert_eq!(MunsellHue::new(94.0).format(&snapped), "0.0R");
+        let snapped = MunsellFormat {
+            boundary_as_ten: true,
+            ..snapped
+        };
+        assert_eq!(MunsellHue::new(94.0).format(&snapped), "10.0RP");
+
+        let color = MunsellColor::new(MunsellHue::new(5.0), 6.5, 8.0);
+        assert_eq!(color.to_string(), "0.00YR 6.5/8");
+        assert_eq!(format!("{:.1}", color), "0.0YR 6.5/8.0");
+        assert_eq!(color.format(&conventional), "10.0R 6.5/8.0");
+        assert_eq!(MunsellColor::neutral(5.0).format(&conventional), "N 5.0/");
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!("9RP".parse(), Ok(MunsellHue::new(94.0)));
+        assert_eq!(
+            "9Q".parse::<MunsellHue>(),
+            Err(ParseError::InvalidHue("9Q".to_string()))
+        );
+        // hue numbers run from 0 to 10, and don't wrap into the next letter
+        for huespec in ["10.5R", "15R", "-1R"] {
+            assert_eq!(
+                huespec.parse::<MunsellHue>(),
+                Err(ParseError::InvalidHue(huespec.to_string()))
+            );
+        }
+        assert_eq!(
+            "15R 5/5".parse::<MunsellColor>(),
+            Err(ParseError::InvalidHue("15R".to_string()))
+        );
+        assert_eq!(
+            "7.5YR6/8".parse(),
+            Ok(MunsellColor::new(MunsellHue::new(12.5), 6.0, 8.0))
+        );
+        assert_eq!(
+            "7.5XY 6/8".parse::<MunsellColor>(),
+            Err(ParseError::InvalidHue("7.5XY".to_string()))
+        );
+        assert_eq!(
+            "7.5YR six/8".parse::<MunsellColor>(),
+            Err(ParseError::InvalidValue("six".to_string()))
+        );
+        assert_eq!(
+            "7.5YR 10.5/2".parse::<MunsellColor>(),
+            Err(ParseError::ValueOutOfRange("10.5".to_string()))
+        );
+        assert_eq!(
+            "N 11/".parse::<MunsellColor>(),
+            Err(ParseError::ValueOutOfRange("11".to_string()))
+        );
+        // chromas have no sign, so are never negative
+        assert_eq!(
+            "7.5YR 6/-2".parse::<MunsellColor>(),
+            Err(ParseError::InvalidChroma("-2".to_string()))
+        );
+        assert_eq!(
+            "7.5YR 6/".parse::<MunsellColor>(),
+            Err(ParseError::InvalidChroma("".to_string()))
+        );
+        assert_eq!(
+            "7.5YR 6".parse::<MunsellColor>().unwrap_err().to_string(),
+            "'7.5YR 6' is not a Munsell specification like '7.5YR 6/8'"
+        );
+    }
+
+    #[test]
+    fn neutrals() {
+        assert_eq!("N 5/".parse(), Ok(MunsellColor::neutral(5.0)));
+        assert_eq!("N5".parse(), Ok(MunsellColor::neutral(5.0)));
+        assert_eq!(" N 9.5/0 ".parse(), Ok(MunsellColor::neutral(9.5)));
+        assert_eq!(
+            "N 5/2".parse::<MunsellColor>(),
+            Err(ParseError::InvalidChroma("2".to_string()))
+        );
+        assert_eq!(MunsellColor::neutral(5.0).to_string(), "N 5/");
+        assert!(MunsellColor::neutral(5.0).is_neutral());
+
+        let lab = MunsellColor::neutral(5.0).to_lab();
+        assert!(lab.a.abs() < 0.01 && lab.b.abs() < 0.01);
+    }
+
+    #[test]
+    fn hue_arithmetic() {
+        let hue = MunsellHue::new(95.0);
+        assert_eq!(hue + 10.0, MunsellHue::new(5.0));
+        assert_eq!(MunsellHue::new(5.0) - 10.0, hue);
+        assert_eq!(MunsellHue::new(5.0) - hue, 10.0);
+        assert_eq!(hue - MunsellHue::new(5.0), -10.0);
+        assert_eq!(hue.distance_to(MunsellHue::new(5.0)), 10.0);
+        assert_eq!(
+            MunsellHue::new(10.0).distance_to(MunsellHue::new(80.0)),
+            30.0
+        );
+        assert_eq!(hue.midpoint(MunsellHue::new(5.0)), MunsellHue::new(0.0));
+        assert_eq!(hue.lerp(MunsellHue::new(5.0), 0.75), MunsellHue::new(2.5));
+        assert_eq!(
+            MunsellHue::new(20.0).midpoint(MunsellHue::new(40.0)),
+            MunsellHue::new(30.0)
+        );
+    }
+
+    #[test]
+    fn color_from_string() {
+        assert_eq!(
+            MunsellColor::parse("7.5YR 6/8"),
+            Some(MunsellColor::new(MunsellHue::new(12.5), 6.0, 8.0))
+        );
+        assert_eq!(
+            MunsellColor::parse(" 5R 2.5 / 0.7 "),
+            Some(MunsellColor::new(MunsellHue::new(0.0), 2.5, 0.7))
+        );
+        assert_eq!(MunsellColor::parse("7.5YR"), None);
+        assert_eq!(MunsellColor::parse("7.5XY 6/8"), None);
+    }
+
+    #[test]
+    fn lab_neutrals() {
+        let gray = MunsellColor::new("5Y".parse::<MunsellHue>().unwrap(), 5.0, 0.0).to_lab();
+        assert!(gray.a.abs() < 0.01 && gray.b.abs() < 0.01);
+        assert!((gray.l - 51.6).abs() < 0.5);
+
+        let red = MunsellColor::parse("5R 4/14").unwrap().to_lch();
+        assert!(red.chroma > 50.0);
+        let hue = red.hue.to_positive_degrees();
+        assert!(hue > 10.0 && hue < 40.0);
+    }
+
+    #[test]
+    fn lch_round_trip() {
+        for spec in [
+            "5R 5/10",
+            "2.5YR 7/4",
+            "10GY 3/6",
+            "7.5PB 2/12",
+            "1RP 8/2",
+            "9RP 4.5/0.7",
+        ] {
+            let color = MunsellColor::parse(spec).unwrap();
+            let back = MunsellColor::from_lch(&color.to_lch());
+
+            assert!((back.hue.raw() - color.hue.raw()).abs() < 0.01, "{}", spec);
+            assert!((back.value - color.value).abs() < 0.01, "{}", spec);
+            assert!((back.chroma - color.chroma).abs() < 0.01, "{}", spec);
+        }
+    }
+
+    #[test]
+    fn oklab_round_trip() {
+        // neutrals stay neutral, as in CIELAB
+        let gray = MunsellColor::neutral(5.0).to_oklab();
+        assert!(gray.a.abs() < 0.001 && gray.b.abs() < 0.001);
+
+        for spec in ["5R 5/10", "10GY 3/6", "7.5PB 2/12"] {
+            let color = MunsellColor::parse(spec).unwrap();
+            let back = MunsellColor::from_oklch(&color.to_oklch());
+            assert!((back.hue.raw() - color.hue.raw()).abs() < 0.01, "{}", spec);
+            assert!((back.value - color.value).abs() < 0.01, "{}", spec);
+            assert!((back.chroma - color.chroma).abs() < 0.01, "{}", spec);
+
+            let back = MunsellColor::from_approximate_oklch(&color.to_approximate_oklch());
+            assert!((back.hue.raw() - color.hue.raw()).abs() < 0.01, "{}", spec);
+            assert!((back.chroma - color.chroma).abs() < 0.01, "{}", spec);
+        }
+    }
+
+    #[test]
+    fn value_luminance() {
+        assert_eq!(value_to_luminance(0.0), 0.0);
+        assert!((value_to_luminance(5.0) - 19.27).abs() < 0.01);
+        assert!((value_to_luminance(10.0) - 100.0).abs() < 0.01);
+        for value in [0.5, 2.0, 5.0, 8.5, 10.0] {
+            assert!((luminance_to_value(value_to_luminance(value)) - value).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn approximate_lch_round_trip() {
+        for spec in ["5R 5/10", "2.5YR 7/4", "10GY 3/6", "7.5PB 2/12", "1RP 8/2"] {
+            let color = MunsellColor::parse(spec).unwrap();
+            let back = MunsellColor::from_approximate_lch(&color.to_approximate_lch());
+
+            assert!((back.hue.raw() - color.hue.raw()).abs() < 0.001, "{}", spec);
+            assert!((back.value - color.value).abs() < 0.001, "{}", spec);
+            assert!((back.chroma - color.chroma).abs() < 0.001, "{}", spec);
+        }
+    }
 }
@@ -0,0 +1,287 @@
+//! A system of color names over the Munsell solid: a hierarchy of names,
+//! from the coarsest level to the finest, the blocks of the solid each name
+//! of the finest level covers, and a way to classify a color into them.
+//!
+//! [`Dataset`] is the ISCC-NBS system, read from its XML. Other partitions
+//! of the solid, like research variants of it or simplified systems of a
+//! hundred names, can implement [`ColorNamingSystem`] to reuse what is
+//! written against it rather than against `Dataset`: checking the blocks by
+//! sampling with [`crate::verify::check_samples`], the centroids and
+//! representative colors of [`crate::colors`], the palette and stylesheet
+//! exports, and the poster of every color.
+
+use std::collections::HashMap;
+
+use crate::dataset::{ColorBlock, ColorName, Dataset, Metadata};
+use crate::munsell::MunsellColor;
+
+/// A hierarchy of color names and the blocks of the Munsell solid they
+/// cover.
+///
+/// Names are identified by ids from 1 up at each level, and every name but
+/// those of level 1 has a parent one level up. Blocks are index ranges into
+/// the hue, chroma and value boundaries, as in a [`ColorBlock`] of a
+/// dataset.
+pub trait ColorNamingSystem {
+    /// Where the system comes from.
+    fn metadata(&self) -> &Metadata;
+
+    /// The number of levels of names; level 1 is the coarsest, and the names
+    /// of the last level have the blocks.
+    fn levels(&self) -> u32;
+
+    /// The names of a level from 1 to [`ColorNamingSystem::levels`], by id,
+    /// or `None` for other levels.
+    fn names_at(&self, level: u32) -> Option<&HashMap<u32, ColorName>>;
+
+    /// The hue boundaries, like `"2.5R"`.
+    fn hues(&self) -> &[String];
+
+    /// The chroma boundaries, the last one possibly `"INF"`.
+    fn chromas(&self) -> &[String];
+
+    /// The value boundaries, the last one possibly `"INF"`.
+    fn values(&self) -> &[String];
+
+    /// The blocks of the names of the finest level.
+    fn blocks(&self) -> &[ColorBlock];
+
+    /// Whether a color is in a block, including its lower boundaries and
+    /// excluding its upper ones.
+    fn block_contains(&self, block: &ColorBlock, color: &MunsellColor) -> bool;
+
+    /// The name of the finest level a color falls into, or `None` outside
+    /// of the Munsell solid.
+    fn classify(&self, color: &MunsellColor) -> Option<u32> {
+        return self
+            .blocks()
+            .iter()
+            .find(|block| self.block_contains(block, color))
+            .map(|block| block.color_id);
+    }
+
+    /// The names of the finest level.
+    ///
+    /// # Panics
+    ///
+    /// If [`ColorNamingSystem::names_at`] has no names for
+    /// [`ColorNamingSystem::levels`].
+    fn finest_names(&self) -> &HashMap<u32, ColorName> {
+        return self
+            .names_at(self.levels())
+            .expect("no names at the finest level");
+    }
+
+    /// The id of the name of level `target` containing the name `color_id`
+    /// of `level`, or `color_id` itself if `target` is `level`. Returns
+    /// `None` if there is no such name, or `target` is a finer level.
+    fn ancestor_id(&self, level: u32, color_id: u32, target: u32) -> Option<u32> {
+        if level == 0 || level > self.levels() {
+            return None;
+        }
+        let name = self.names_at(level)?.get(&color_id)?;
+        if target == level {
+            return Some(color_id);
+        }
+        return self.ancestor_id(level - 1, name.parent?, target);
+    }
+}
+
+impl ColorNamingSystem for Dataset {
+    fn metadata(&self) -> &Metadata {
+        return Dataset::metadata(self);
+    }
+
+    fn levels(&self) -> u32 {
+        return 3;
+    }
+
+    fn names_at(&self, level: u32) -> Option<&HashMap<u32, ColorName>> {
+        return match level {
+            1 => Some(&self.level1_names),
+            2 => Some(&self.level2_names),
+            3 => Some(&self.names),
+            _ => None,
+        };
+    }
+
+    fn hues(&self) -> &[String] {
+        return Dataset::hues(self);
+    }
+
+    fn chromas(&self) -> &[String] {
+        return Dataset::chromas(self);
+    }
+
+    fn values(&self) -> &[String] {
+        return Dataset::values(self);
+    }
+
+    fn blocks(&self) -> &[ColorBlock] {
+        return Dataset::blocks(self);
+    }
+
+    fn block_contains(&self, block: &ColorBlock, color: &MunsellColor) -> bool {
+        return Dataset::block_contains(self, block, color);
+    }
+
+    /// Classified with the lookup table, instead of searching the blocks.
+    fn classify(&self, color: &MunsellColor) -> Option<u32> {
+        return self.lookup_table().classify_munsell(color);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::colors::{get_level1_mean_munsell, get_mean_colors, get_mean_munsell};
+    use crate::dataset::{deinfinite, ColorBlock, ColorName, Metadata};
+    use crate::export::css::to_css;
+    use crate::leaf::arc_contains;
+    use crate::munsell::{MunsellColor, MunsellHue};
+    use crate::naming::ColorNamingSystem;
+    use crate::progress::no_progress;
+    use crate::random::{Rng, DEFAULT_SEED};
+    use crate::verify::check_samples;
+    use crate::Dataset;
+
+    /// A system of one color, split into dark and light at value 5, with a
+    /// block for each half of the hue circle.
+    struct Lightness {
+        metadata: Metadata,
+        level1_names: HashMap<u32, ColorName>,
+        names: HashMap<u32, ColorName>,
+        hues: Vec<String>,
+        chromas: Vec<String>,
+        values: Vec<String>,
+        blocks: Vec<ColorBlock>,
+    }
+
+    fn color_name(name: &str, abbr: &str, parent: Option<u32>) -> ColorName {
+        return ColorName {
+            name: name.to_string(),
+            abbr: abbr.to_string(),
+            parent,
+            srgb: None,
+            description: None,
+            usage: None,
+        };
+    }
+
+    impl Lightness {
+        fn new() -> Lightness {
+            let strings = |x: &[&str]| x.iter().map(|x| x.to_string()).collect();
+            let mut blocks = Vec::new();
+            for hues in [0..1, 1..2] {
+                for (color_id, values) in [(1, 0..1), (2, 1..2)] {
+                    blocks.push(ColorBlock {
+                        color_id,
+                        hues: hues.clone(),
+                        chromas: 0..1,
+                        values,
+                    });
+                }
+            }
+            return Lightness {
+                metadata: Metadata {
+                    version: "test".to_string(),
+                    source: String::new(),
+                    license: String::new(),
+                    publication_date: String::new(),
+                },
+                level1_names: HashMap::from([(1, color_name("Color", "C", None))]),
+                names: HashMap::from([
+                    (1, color_name("Dark color", "d.C", Some(1))),
+                    (2, color_name("Light color", "l.C", Some(1))),
+                ]),
+                hues: strings(&["5R", "5BG", "5R"]),
+                chromas: strings(&["0", "INF"]),
+                values: strings(&["0", "5", "INF"]),
+                blocks,
+            };
+        }
+    }
+
+    impl ColorNamingSystem for Lightness {
+        fn metadata(&self) -> &Metadata {
+            return &self.metadata;
+        }
+
+        fn levels(&self) -> u32 {
+            return 2;
+        }
+
+        fn names_at(&self, level: u32) -> Option<&HashMap<u32, ColorName>> {
+            return match level {
+                1 => Some(&self.level1_names),
+                2 => Some(&self.names),
+                _ => None,
+            };
+        }
+
+        fn hues(&self) -> &[String] {
+            return &self.hues;
+        }
+
+        fn chromas(&self) -> &[String] {
+            return &self.chromas;
+        }
+
+        fn values(&self) -> &[String] {
+            return &self.values;
+        }
+
+        fn blocks(&self) -> &[ColorBlock] {
+            return &self.blocks;
+        }
+
+        fn block_contains(&self, block: &ColorBlock, color: &MunsellColor) -> bool {
+            let hue = |i: usize| self.hues[i].parse::<MunsellHue>().unwrap().raw();
+            let point = |x: &[String], i: usize| deinfinite(&x[i]).parse::<f32>().unwrap();
+            return arc_contains(hue(block.hues.start), hue(block.hues.end), color.hue.raw())
+                && color.chroma >= point(&self.chromas, block.chromas.start)
+                && color.chroma < point(&self.chromas, block.chromas.end)
+                && color.value >= point(&self.values, block.values.start)
+                && color.value < point(&self.values, block.values.end);
+        }
+    }
+
+    #[test]
+    fn alternative_system() {
+        let system = Lightness::new();
+        let color: MunsellColor = "5PB 7/4".parse().unwrap();
+        assert_eq!(system.classify(&color), Some(2));
+        assert_eq!(system.ancestor_id(2, 2, 1), Some(1));
+        assert_eq!(system.ancestor_id(3, 2, 1), None);
+        assert!(system.names_at(3).is_none());
+
+        let coverage = check_samples(&system, 300, &mut Rng::new(DEFAULT_SEED), &mut no_progress);
+        assert!(coverage.faults.is_empty(), "{:?}", coverage.faults);
+
+        // wedges all around the neutral axis, halfway up each half
+        let centroids = get_mean_munsell(&system);
+        assert_eq!(centroids.len(), 2);
+        assert!((centroids[0].value - 2.5).abs() < 1e-4);
+        assert!((centroids[1].value - 7.5).abs() < 1e-4);
+        assert!(centroids[1].chroma < 1e-3);
+        assert!((get_level1_mean_munsell(&system)[0].value - 5.0).abs() < 1e-4);
+
+        let css = to_css(&system, &get_mean_colors(&system));
+        assert!(css.contains("iscc-nbs test"));
+        assert!(css.contains("--iscc-dark-color: "));
+        assert!(css.contains("--iscc-light-color: "));
+
+        // the dataset classifies through its lookup table the same way
+        let dataset = Dataset::bundled();
+        assert_eq!(
+            ColorNamingSystem::classify(&dataset, &color),
+            dataset.lookup_id(&color)
+        );
+        assert_eq!(
+            ColorNamingSystem::ancestor_id(&dataset, 3, 34, 1),
+            dataset.ancestor_id(3, 34, 1)
+        );
+        assert!(ColorNamingSystem::names_at(&dataset, 4).is_none());
+    }
+}
@@ -0,0 +1,127 @@
+//! The achromatic categories (white, the grays, and black), which surround
+//! the neutral axis the same way at every hue.
+
+use crate::dataset::{ColorBlock, Dataset};
+
+/// An achromatic level 3 category, and the range of Munsell values it
+/// covers on the neutral axis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NeutralCategory {
+    pub color_id: u32,
+    pub value_begin: f32,
+    /// The upper value boundary, excluded from the category. Infinite for
+    /// white.
+    pub value_end: f32,
+    /// The chroma below which colors of any hue fall into this category.
+    pub chroma_end: f32,
+}
+
+impl NeutralCategory {
+    pub fn contains_value(&self, value: f32) -> bool {
+        return value >= self.value_begin && value < self.value_end;
+    }
+}
+
+/// Find the categories whose blocks start at chroma 0, ordered from the
+/// lightest to the darkest.
+pub(crate) fn build_neutrals(
+    blocks: &[ColorBlock],
+    chroma_points: &[f32],
+    value_points: &[f32],
+) -> Vec<NeutralCategory> {
+    let mut neutrals: Vec<NeutralCategory> = Vec::new();
+
+    for block in blocks
+        .iter()
+        .filter(|x| chroma_points[x.chromas.start] == 0.0)
+    {
+        if neutrals.iter().any(|x| x.color_id == block.color_id) {
+            continue;
+        }
+
+        neutrals.push(NeutralCategory {
+            color_id: block.color_id,
+            value_begin: value_points[block.values.start],
+            value_end: value_points[block.values.end],
+            chroma_end: chroma_points[block.chromas.end],
+        });
+    }
+
+    neutrals.sort_by(|a, b| b.value_begin.partial_cmp(&a.value_begin).unwrap());
+    return neutrals;
+}
+
+impl Dataset {
+    /// The achromatic categories, from white to black.
+    pub fn neutrals(&self) -> &[NeutralCategory] {
+        &self.neutrals
+    }
+
+    /// The ids of the achromatic categories, from white to black.
+    pub fn neutral_ids(&self) -> Vec<u32> {
+        return self.neutrals.iter().map(|x| x.color_id).collect();
+    }
+
+    /// The values separating the achromatic categories, in ascending order.
+    pub fn neutral_value_breakpoints(&self) -> Vec<f32> {
+        return self
+            .neutrals
+            .iter()
+            .rev()
+            .skip(1)
+            .map(|x| x.value_begin)
+            .collect();
+    }
+
+    /// Whether a level 3 color is one of the achromatic categories.
+    pub fn is_neutral(&self, color_id: u32) -> bool {
+        return self.neutrals.iter().any(|x| x.color_id == color_id);
+    }
+
+    /// Find the id of the achromatic category of a neutral color with the
+    /// given Munsell value, without searching the color blocks.
+    ///
+    /// Values below 0 are classified as black.
+    pub fn classify_neutral(&self, value: f32) -> u32 {
+        return self
+            .neutrals
+            .iter()
+            .find(|x| x.contains_value(value))
+            .unwrap_or_else(|| self.neutrals.last().unwrap())
+            .color_id;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Dataset, MunsellColor, MunsellHue};
+
+    #[test]
+    fn neutral_categories() {
+        let dataset = Dataset::bundled();
+
+        assert_eq!(dataset.neutral_ids(), vec![263, 264, 265, 266, 267]);
+        assert_eq!(
+            dataset.neutral_value_breakpoints(),
+            vec![2.5, 4.5, 6.5, 8.5]
+        );
+        assert!(dataset.is_neutral(265));
+        assert!(!dataset.is_neutral(10));
+    }
+
+    #[test]
+    fn neutral_fast_path() {
+        let dataset = Dataset::bundled();
+
+        for tenths in -5..=105 {
+            let value = tenths as f32 / 10.0;
+            let color = MunsellColor::new(MunsellHue::new(0.0), value.max(0.0), 0.0);
+            assert_eq!(
+                dataset.classify_neutral(value),
+                dataset.lookup_id(&color).unwrap(),
+                "{}",
+                value
+            );
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+
+use geo::extremes::Extremes;
+use geo::Centroid;
+use geo_clipper::Clipper;
+use geo_types::{Coord, LineString, MultiPolygon, Point, Polygon, Rect};
+
+use crate::dataset::{deinfinite, Dataset};
+use crate::plot::config::config;
+
+/// Chroma boundaries that fall between the regular grid lines of a chart,
+/// and get their own tick mark when a plate uses them.
+const FINE_CHROMA_TICKS: &[&str] = &["0.7", "1.2"];
+
+/// The geometry of a single hue leaf of the color name charts: a
+/// chroma (x) by value (y) plane, divided into one region per color.
+pub struct Plate {
+    /// Index of the first hue of this leaf in [`Dataset::hues`].
+    pub hue_index: usize,
+    pub hue_begin: String,
+    pub hue_end: String,
+    /// Page number of this leaf in NBS Special Publication 440.
+    pub page: usize,
+    /// Which of the two leaves on `page` this is (0 or 1).
+    pub side: usize,
+    /// The level of the names the regions are colored by: 3, or 1 or 2 for
+    /// [`Dataset::plate_at_level`].
+    pub level: u32,
+    /// The regions of this leaf, ordered by color id.
+    pub regions: Vec<PlateRegion>,
+    pub axes: PlateAxes,
+}
+
+/// The union of all blocks of one color on a plate. At levels 1 and 2, the
+/// blocks of a name may fall into several regions that don't touch, each
+/// with its own label.
+pub struct PlateRegion {
+    /// The id of the name, of the level of the plate.
+    pub color_id: u32,
+    pub polygon: Polygon<f64>,
+    /// Where the name of the color should be placed.
+    pub label_anchor: Point<f64>,
+    /// The bounding box of `polygon`, the space available to the label.
+    pub bounds: Rect<f64>,
+}
+
+pub struct PlateAxes {
+    /// The visible chroma range.
+    pub chroma_range: (f64, f64),
+    /// The visible value range.
+    pub value_range: (f64, f64),
+    /// Chroma at which `INF` boundaries are cut off, just outside the
+    /// visible range.
+    pub chroma_limit: f64,
+    /// Value at which `INF` boundaries are cut off, just outside the
+    /// visible range.
+    pub value_limit: f64,
+    /// Chroma boundaries used on this plate that need a tick mark in
+    /// addition to the regular grid.
+    pub fine_chroma_ticks: Vec<f64>,
+}
+
+impl Dataset {
+    /// The region of every color on the hue leaf starting at hue index `h`,
+    /// as (color id, polygon) pairs ordered by color id: the union of the
+    /// color's blocks, in chroma (x) and value (y), with `INF` boundaries
+    /// cut off at [`PlateAxes::chroma_limit`] and [`PlateAxes::value_limit`].
+    ///
+    /// This is the geometry the charts are drawn from, for drawing them some
+    /// other way; [`Dataset::plate`] has the axes and label positions too.
+    /// Returns `None` if there is no hue index `h`.
+    pub fn regions_for_hue_leaf(&self, h: usize) -> Option<Vec<(u32, Polygon<f64>)>> {
+        let regions = self.plate(h)?.regions;
+        return Some(
+            regions
+                .into_iter()
+                .map(|x| (x.color_id, x.polygon))
+                .collect(),
+        );
+    }
+
+    /// Compute the geometry of the hue leaf starting at hue index `h`, or
+    /// `None` if there is no such hue index.
+    pub fn plate(&self, h: usize) -> Option<Plate> {
+        return self.plate_at_level(h, 3);
+    }
+
+    /// Like [`Dataset::plate`], with one region for all the blocks of the
+    /// level 3 names under each name of `level`, 1 or 2, where they touch.
+    /// Returns `None` for a `level` other than 1, 2 or 3, too.
+    pub fn plate_at_level(&self, h: usize, level: u32) -> Option<Plate> {
+        let hues = self.hues();
+        if h >= hues.len() {
+            return None;
+        }
+        let chromas = self.chromas();
+        let values = self.values();
+
+        let axes_chroma_limit = config().chroma_limit;
+        let axes_value_limit = config().value_limit;
+
+        let mut fine_chroma_ticks: Vec<f64> = Vec::new();
+        let mut regions: BTreeMap<u32, MultiPolygon> = BTreeMap::new();
+
+        for block in self.blocks().iter().filter(|x| h == x.hues.start) {
+            let x1 = chromas[block.chromas.start].as_str();
+            let x2 = deinfinite(&chromas[block.chromas.end]);
+            let y1 = values[block.values.start].as_str();
+            let y2 = deinfinite(&values[block.values.end]);
+
+            let x1f: f64 = x1.parse().unwrap();
+            let x2f: f64 = x2.parse::<f64>().unwrap().min(axes_chroma_limit);
+            let y1f: f64 = y1.parse().unwrap();
+            let y2f: f64 = y2.parse::<f64>().unwrap().min(axes_value_limit);
+
+            for x in [x1, x2] {
+                if FINE_CHROMA_TICKS.contains(&x) {
+                    let tick: f64 = x.parse().unwrap();
+                    if !fine_chroma_ticks.contains(&tick) {
+                        fine_chroma_ticks.push(tick);
+                    }
+                }
+            }
+
+            let area = Polygon::new(
+                LineString(vec![
+                    Coord { x: x1f, y: y1f },
+                    Coord { x: x1f, y: y2f },
+                    Coord { x: x2f, y: y2f },
+                    Coord { x: x2f, y: y1f },
+                ]),
+                vec![],
+            );
+            let id = self.ancestor_id(3, block.color_id, level)?;
+            let region = match regions.get(&id) {
+                Some(existing) => existing.union(&area, 10.0),
+                None => MultiPolygon(vec![area]),
+            };
+            regions.insert(id, region);
+        }
+
+        fine_chroma_ticks.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let regions = regions
+            .into_iter()
+            .flat_map(|(color_id, parts)| parts.into_iter().map(move |x| (color_id, x)))
+            .map(|(color_id, polygon)| {
+                let extremes = polygon.extremes().unwrap();
+                let bounds = Rect::new(
+                    Coord {
+                        x: extremes.x_min.coord.x,
+                        y: extremes.y_min.coord.y,
+                    },
+                    Coord {
+                        x: extremes.x_max.coord.x,
+                        y: extremes.y_max.coord.y,
+                    },
+                );
+                let label_anchor = polygon.centroid().unwrap();
+
+                return PlateRegion {
+                    color_id,
+                    polygon,
+                    label_anchor,
+                    bounds,
+                };
+            })
+            .collect();
+
+        return Some(Plate {
+            hue_index: h,
+            hue_begin: hues[h].clone(),
+            hue_end: hues[(h + 1) % hues.len()].clone(),
+            page: config().first_page + (h / 2),
+            side: h % 2,
+            level,
+            regions,
+            axes: PlateAxes {
+                chroma_range: config().chroma_range,
+                value_range: config().value_range,
+                chroma_limit: axes_chroma_limit,
+                value_limit: axes_value_limit,
+                fine_chroma_ticks,
+            },
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use geo::Area;
+
+    use crate::Dataset;
+
+    #[test]
+    fn leaf_regions() {
+        let dataset = Dataset::bundled();
+        let regions = dataset.regions_for_hue_leaf(0).unwrap();
+        assert!(regions.windows(2).all(|x| x[0].0 < x[1].0));
+        assert!(regions.iter().any(|x| x.0 == 1));
+
+        // the regions tile the leaf, out to the cutoffs
+        let area: f64 = regions.iter().map(|x| x.1.unsigned_area()).sum();
+        let axes = dataset.plate(0).unwrap().axes;
+        assert!((area - axes.chroma_limit * axes.value_limit).abs() < 1e-6);
+    }
+
+    #[test]
+    fn merged_regions() {
+        let dataset = Dataset::bundled();
+        let plate = dataset.plate(0).unwrap();
+        // "Vivid pink" is under "Pink" at both levels
+        assert_eq!(dataset.ancestor_id(3, 1, 2), Some(1));
+        assert_eq!(dataset.ancestor_id(3, 1, 1), Some(1));
+        assert_eq!(dataset.ancestor_id(3, 1, 3), Some(1));
+        assert_eq!(dataset.ancestor_id(3, 1000, 1), None);
+
+        for level in [1, 2] {
+            let merged = dataset.plate_at_level(0, level).unwrap();
+            assert_eq!(merged.level, level);
+            assert!(merged.regions.len() < plate.regions.len());
+
+            // every level 3 region is in the region of its ancestor
+            for region in plate.regions.iter() {
+                let id = dataset.ancestor_id(3, region.color_id, level).unwrap();
+                assert!(merged.regions.iter().any(|x| x.color_id == id));
+            }
+
+            // the merged regions cover the same area, without overlapping
+            let area = |plate: &crate::plate::Plate| -> f64 {
+                return plate
+                    .regions
+                    .iter()
+                    .map(|x| x.polygon.unsigned_area())
+                    .sum();
+            };
+            assert!((area(&merged) - area(&plate)).abs() < 1e-3 * area(&plate));
+        }
+    }
+
+    #[test]
+    fn missing_plates() {
+        let dataset = Dataset::bundled();
+        let h = dataset.hues().len();
+        assert!(dataset.plate(h - 1).is_some());
+        assert!(dataset.plate(h).is_none());
+        assert!(dataset.regions_for_hue_leaf(h).is_none());
+        assert!(dataset.plate_at_level(0, 4).is_none());
+    }
+}
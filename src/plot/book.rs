@@ -0,0 +1,121 @@
+//! Munsell book pages: constant-hue charts of individual color chips, with
+//! the boundaries of the ISCC-NBS regions drawn over them.
+
+use std::fs::File;
+use std::io::Write;
+
+use palette::{convert::FromColorUnclamped, Clamp, Srgb};
+
+use crate::dataset::Dataset;
+use crate::munsell::{MunsellColor, MunsellHue, LETTER_CODES};
+use crate::plot::{
+    polygon_vertices, run_gnuplot, write_plate_footer, write_plate_header, GnuplotError,
+};
+use crate::progress::{no_progress, report, Progress, Stage};
+
+/// The hue steps of each hue family that get their own page.
+const HUE_STEPS: &[&str] = &["2.5", "5", "7.5", "10"];
+
+/// Chips are placed at every even chroma up to this one.
+pub(crate) const MAX_CHIP_CHROMA: u32 = 16;
+
+/// Chips are placed at every whole value between 1 and 9.
+pub(crate) const CHIP_VALUES: std::ops::RangeInclusive<u32> = 1..=9;
+
+/// The hue of each page, from 2.5R through 10RP.
+pub(crate) fn book_page_hues() -> Vec<String> {
+    return LETTER_CODES
+        .iter()
+        .flat_map(|code| {
+            HUE_STEPS
+                .iter()
+                .map(move |step| format!("{}{}", step, code))
+        })
+        .collect();
+}
+
+/// Write `doc/book/page*.gnu` for every page of a Munsell book, from 2.5R
+/// through 10RP, and render them with gnuplot if `render` is set.
+///
+/// Chips are colored from the Munsell renotation data, and only chips that
+/// can be displayed in sRGB are drawn.
+pub fn generate_gnuplot(dataset: &Dataset, render: bool) -> Result<(), GnuplotError> {
+    return generate_gnuplot_with_progress(dataset, render, &mut no_progress);
+}
+
+/// Like [`generate_gnuplot`], reporting progress through the render stage,
+/// one unit per page.
+pub fn generate_gnuplot_with_progress(
+    dataset: &Dataset,
+    render: bool,
+    progress: Progress,
+) -> Result<(), GnuplotError> {
+    std::fs::create_dir_all("doc/book").unwrap();
+
+    let page_hues = book_page_hues();
+    let total = page_hues.len();
+    report(progress, Stage::Render, 0, total);
+    for (page, hue_name) in page_hues.iter().enumerate() {
+        let hue = hue_name.parse::<MunsellHue>().unwrap();
+        let plate = dataset.plate(dataset.leaf_containing(hue).index).unwrap();
+
+        let basename = format!("doc/book/page{:02}_{}", page + 1, hue_name);
+        let mut file = File::create(format!("{}.gnu", basename)).unwrap();
+
+        write_plate_header(
+            &mut file,
+            &plate,
+            &format!("{} ({}-{})", hue_name, plate.hue_begin, plate.hue_end),
+        );
+
+        let mut object_id = 1000;
+        for value in CHIP_VALUES {
+            for chroma in (2..=MAX_CHIP_CHROMA).step_by(2) {
+                let chip = MunsellColor::new(hue, value as f32, chroma as f32);
+                let rgb = Srgb::from_color_unclamped(chip.to_display_lch());
+                if !rgb.is_within_bounds() {
+                    continue;
+                }
+
+                let rgb_u8: Srgb<u8> = rgb.into_format();
+                writeln!(
+                    &mut file,
+                    "set object {} rect center {},{} size 1.6,0.8 fc rgbcolor \"#{:x}\" fs solid 1.0 noborder",
+                    object_id, chroma, value, rgb_u8
+                )
+                .unwrap();
+                object_id += 1;
+            }
+        }
+
+        for region in plate.regions.iter() {
+            writeln!(
+                &mut file,
+                "set object {} polygon from {} front fs empty border lc \"#000000\" lw 1.5",
+                region.color_id + 1,
+                polygon_vertices(&region.polygon)
+            )
+            .unwrap();
+            writeln!(
+                &mut file,
+                "set label {} \"{}\" at first {},{} center front font \",6\"",
+                region.color_id + 1,
+                region.color_id,
+                region.label_anchor.x(),
+                region.label_anchor.y()
+            )
+            .unwrap();
+        }
+
+        write_plate_footer(&mut file, &plate, &basename);
+
+        // close and flush the file
+        drop(file);
+
+        if render {
+            run_gnuplot(&basename)?;
+        }
+        report(progress, Stage::Render, page + 1, total);
+    }
+    return Ok(());
+}
@@ -0,0 +1,190 @@
+//! The color name charts: one figure per hue leaf, with each region filled
+//! with its representative color and labeled with its name.
+
+use std::fs::File;
+use std::io::Write;
+use std::sync::mpsc;
+
+use palette::Srgb;
+use rayon::prelude::*;
+use ttf_word_wrap::TTFParserMeasure;
+
+use crate::dataset::Dataset;
+use crate::plot::labels::place_labels;
+use crate::plot::{
+    centroid_markers, gamut_boundary_lines, label_text_color, load_font_data, polygon_vertices,
+    run_gnuplot, write_plate_footer, write_plate_header, ChartOverlays, GnuplotError,
+};
+use crate::progress::{no_progress, report, Progress, Stage};
+
+/// Write `doc/page*.gnu` for every hue leaf, and render them with gnuplot
+/// if `render` is set.
+///
+/// `overlays` are drawn over the regions.
+pub fn generate_gnuplot(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    overlays: &ChartOverlays,
+    render: bool,
+) -> Result<(), GnuplotError> {
+    return generate_gnuplot_with_progress(dataset, colors, overlays, render, &mut no_progress);
+}
+
+/// Like [`generate_gnuplot`], reporting progress through the render stage,
+/// one unit per hue leaf.
+///
+/// The charts are written and rendered in parallel, so they may complete in
+/// any order; progress is still reported on the calling thread. Rendering
+/// stops at the first chart gnuplot fails on.
+pub fn generate_gnuplot_with_progress(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    overlays: &ChartOverlays,
+    render: bool,
+    progress: Progress,
+) -> Result<(), GnuplotError> {
+    let font_data = load_font_data();
+
+    let total = dataset.hues().len();
+    report(progress, Stage::Render, 0, total);
+    let (sender, receiver) = mpsc::channel();
+    return std::thread::scope(|scope| {
+        let result = scope.spawn(|| {
+            return (0..total)
+                .into_par_iter()
+                .try_for_each_with(sender, |sender, h| {
+                    write_chart_gnuplot(dataset, colors, overlays, &font_data, h, render)?;
+                    sender.send(()).unwrap();
+                    return Ok(());
+                });
+        });
+        for (done, _) in receiver.iter().enumerate() {
+            report(progress, Stage::Render, done + 1, total);
+        }
+        return result.join().unwrap();
+    });
+}
+
+/// Write the chart of hue leaf `h`, and render it if `render` is set.
+fn write_chart_gnuplot(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    overlays: &ChartOverlays,
+    font_data: &[u8],
+    h: usize,
+    render: bool,
+) -> Result<(), GnuplotError> {
+    let font_face = ttf_parser::Face::from_slice(font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+
+    let plate = dataset.plate(h).unwrap();
+
+    let basename = format!(
+        "doc/page{}-{}_hues_{}-{}",
+        plate.page, plate.side, plate.hue_begin, plate.hue_end
+    );
+    let mut file = File::create(format!("{}.gnu", basename)).unwrap();
+
+    write_plate_header(
+        &mut file,
+        &plate,
+        &format!("{}-{}", plate.hue_begin, plate.hue_end),
+    );
+
+    let placements = place_labels(dataset, &plate, colors, &measure);
+    for (region, placement) in plate.regions.iter().zip(placements) {
+        let id = region.color_id;
+
+        writeln!(&mut file).unwrap();
+        let color = colors[(id - 1) as usize];
+        let color_u8: Srgb<u8> = color.into_format();
+        writeln!(
+            &mut file,
+            "set object {} polygon from {} fc rgbcolor \"#{:x}\" fs solid 1.0 border lc \"#000000\"",
+            id + 1,
+            polygon_vertices(&region.polygon),
+            color_u8
+        )
+        .unwrap();
+
+        let (label_x, label_y) = placement.position;
+        let rotated = placement.rotation != 0.0;
+
+        let linebreaked_label = placement.lines.join("\\n");
+        let rotate = if rotated { "rotate by 90" } else { "norotate" };
+        let offset_x = if rotated {
+            -((placement.lines.len() - 1) as f32) / 2.0
+        } else {
+            0.0
+        };
+        let offset_y = if rotated {
+            0.0
+        } else {
+            ((placement.lines.len() - 1) as f32) / 2.0
+        };
+
+        // yank off the ID then add it back in boldface (hopefully this doesn't
+        // change the width too much...)
+        let (prefix, suffix) = linebreaked_label.split_once(':').unwrap();
+        let linebreaked_label = format!("{{/:Bold {}}}:{}", prefix, suffix);
+
+        let textcolor = format!("{:x}", placement.text_color);
+
+        writeln!(
+            &mut file,
+            "set label {} \"{}\" at first {},{} center {} textcolor \"#{}\" offset character {},{}",
+            id + 1,
+            linebreaked_label,
+            label_x,
+            label_y,
+            rotate,
+            textcolor,
+            offset_x,
+            offset_y
+        )
+        .unwrap();
+    }
+
+    if overlays.gamut_boundary {
+        for line in gamut_boundary_lines(dataset, &plate) {
+            for segment in line.windows(2) {
+                writeln!(
+                    &mut file,
+                    "set arrow from first {},{} to first {},{} nohead front lw 2 dt 2 lc rgbcolor \"#404040\"",
+                    segment[0].0, segment[0].1, segment[1].0, segment[1].1
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    for marker in centroid_markers(dataset, &plate, &overlays.centroids) {
+        let color = label_text_color(colors[(marker.color_id - 1) as usize]);
+        let fill = if marker.on_leaf {
+            "fs solid 1.0"
+        } else {
+            "fs empty"
+        };
+        writeln!(
+            &mut file,
+            "set object {} circle at first {},{} size screen 0.006 front fc rgbcolor \"#{}\" {} border lc rgbcolor \"#{}\"",
+            2000 + marker.color_id,
+            marker.position.0,
+            marker.position.1,
+            color,
+            fill,
+            color
+        )
+        .unwrap();
+    }
+
+    write_plate_footer(&mut file, &plate, &basename);
+
+    // close and flush the file
+    drop(file);
+
+    if render {
+        run_gnuplot(&basename)?;
+    }
+    return Ok(());
+}
@@ -0,0 +1,213 @@
+//! The parameters the charts are drawn with, read from a TOML file like
+//! `plot.toml` so that they can be changed without patching the source.
+//!
+//! Every key is optional and defaults to the values the charts in `doc/`
+//! are drawn with:
+//!
+//! ```toml
+//! # size of the PNG and gnuplot charts, and of the SVG charts on screen
+//! width = 600
+//! height = 800
+//! # the visible part of the chroma and value axes
+//! chroma_range = [0.0, 16.9]
+//! value_range = [0.0, 10.4]
+//! # where INF boundaries are cut off, just outside the visible range
+//! chroma_limit = 17.0
+//! value_limit = 10.5
+//! # size of the region labels, in pixels of a 600×800 chart
+//! label_font_size = 11.0
+//! # how wide a label may be per unit of chroma, and how tall per unit of
+//! # value when it is rotated, in font units
+//! label_wrap_horizontal = 6000.0
+//! label_wrap_vertical = 14000.0
+//! # page number of the first leaf in NBS Special Publication 440
+//! first_page = 16
+//! # the typeface of the labels, found with fontconfig, or else the
+//! # embedded DejaVu Sans
+//! font_name = "DejaVu Sans"
+//! # a TrueType font file to use instead, for every typeface and style
+//! # font_path = "fonts/DejaVuSans.ttf"
+//! # where colors outside of sRGB lose chroma, and are judged light or dark
+//! # for their labels: "cielab" or "oklab"
+//! perceptual_space = "cielab"
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::colors::PerceptualSpace;
+use crate::plot::labels::LABEL_FONT_SIZE;
+use crate::plot::FONT_FACE;
+
+static CONFIG: OnceLock<PlotConfig> = OnceLock::new();
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlotConfig {
+    pub width: u32,
+    pub height: u32,
+    pub chroma_range: (f64, f64),
+    pub value_range: (f64, f64),
+    pub chroma_limit: f64,
+    pub value_limit: f64,
+    pub label_font_size: f64,
+    pub label_wrap_horizontal: f64,
+    pub label_wrap_vertical: f64,
+    pub first_page: usize,
+    pub font_name: String,
+    pub font_path: Option<PathBuf>,
+    pub perceptual_space: PerceptualSpace,
+}
+
+impl Default for PlotConfig {
+    fn default() -> Self {
+        return PlotConfig {
+            width: 600,
+            height: 800,
+            chroma_range: (0.0, 16.9),
+            value_range: (0.0, 10.4),
+            chroma_limit: 17.0,
+            value_limit: 10.5,
+            label_font_size: LABEL_FONT_SIZE,
+            label_wrap_horizontal: 6000.0,
+            label_wrap_vertical: 14000.0,
+            first_page: 16,
+            font_name: FONT_FACE.to_string(),
+            font_path: None,
+            perceptual_space: PerceptualSpace::Cielab,
+        };
+    }
+}
+
+/// Why a plot configuration could not be used.
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(std::io::Error),
+    Toml(toml::de::Error),
+    /// A value that parses but makes no sense, like an empty axis range.
+    Invalid(String),
+    /// A configuration given after the first chart was drawn.
+    AlreadySet,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            ConfigError::Read(e) => write!(f, "{}", e),
+            ConfigError::Toml(e) => write!(f, "{}", e),
+            ConfigError::Invalid(e) => write!(f, "{}", e),
+            ConfigError::AlreadySet => {
+                write!(f, "the charts are drawn with another configuration already")
+            }
+        };
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl PlotConfig {
+    /// Parse a configuration, with the defaults for every key it leaves
+    /// out.
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let config: PlotConfig = toml::from_str(text).map_err(ConfigError::Toml)?;
+        config.check()?;
+        return Ok(config);
+    }
+
+    /// Read and parse a configuration file.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = std::fs::read_to_string(path).map_err(ConfigError::Read)?;
+        return Self::parse(&text);
+    }
+
+    fn check(&self) -> Result<(), ConfigError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(ConfigError::Invalid("the charts must not be empty".into()));
+        }
+        for (name, (min, max)) in [
+            ("chroma_range", self.chroma_range),
+            ("value_range", self.value_range),
+        ] {
+            if min >= max {
+                return Err(ConfigError::Invalid(format!(
+                    "{} must run from a lower to a higher number",
+                    name
+                )));
+            }
+        }
+        if self.chroma_limit < self.chroma_range.1 || self.value_limit < self.value_range.1 {
+            return Err(ConfigError::Invalid(
+                "the limits must not be inside the visible ranges".into(),
+            ));
+        }
+        if self.label_font_size <= 0.0
+            || self.label_wrap_horizontal <= 0.0
+            || self.label_wrap_vertical <= 0.0
+        {
+            return Err(ConfigError::Invalid(
+                "the label font size and wrap widths must be positive".into(),
+            ));
+        }
+        return Ok(());
+    }
+}
+
+/// Use `config` for all charts drawn from now on. Only the first call has
+/// an effect, and only if no chart has been drawn yet.
+pub fn set_config(config: PlotConfig) -> Result<(), ConfigError> {
+    return CONFIG.set(config).map_err(|_| ConfigError::AlreadySet);
+}
+
+/// The configuration the charts are drawn with: the one given to
+/// [`set_config`], or the defaults.
+pub fn config() -> &'static PlotConfig {
+    return CONFIG.get_or_init(PlotConfig::default);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::PerceptualSpace;
+    use crate::plot::config::{config, set_config, ConfigError, PlotConfig};
+
+    #[test]
+    fn parse_config() {
+        assert_eq!(PlotConfig::parse("").unwrap(), PlotConfig::default());
+
+        let config = PlotConfig::parse("width = 1200\nvalue_range = [1.0, 9.0]\n").unwrap();
+        assert_eq!(config.width, 1200);
+        assert_eq!(config.height, 800);
+        assert_eq!(config.value_range, (1.0, 9.0));
+        assert_eq!(
+            PlotConfig::parse("perceptual_space = \"oklab\"")
+                .unwrap()
+                .perceptual_space,
+            PerceptualSpace::Oklab
+        );
+
+        assert!(matches!(
+            PlotConfig::parse("wdith = 1200"),
+            Err(ConfigError::Toml(_))
+        ));
+        assert!(matches!(
+            PlotConfig::parse("chroma_range = [10.0, 2.0]"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            PlotConfig::parse("value_limit = 5.0"),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn set_after_drawing() {
+        // drawing a chart settles the configuration
+        config();
+        assert!(matches!(
+            set_config(PlotConfig::default()),
+            Err(ConfigError::AlreadySet)
+        ));
+    }
+}
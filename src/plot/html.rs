@@ -0,0 +1,229 @@
+//! A self-contained HTML page with every hue leaf of the color name charts,
+//! where hovering a region shows what is known about its color.
+
+use palette::Srgb;
+use serde_json::{json, Map, Value};
+use ttf_word_wrap::TTFParserMeasure;
+
+use crate::dataset::Dataset;
+use crate::export::sorted_names;
+use crate::plot::load_font_data;
+use crate::plot::svg::plate_to_svg;
+use crate::progress::{no_progress, report, Progress, Stage};
+use crate::rgbspace::rgb_space;
+
+const STYLE: &str = "
+body { font-family: 'DejaVu Sans', sans-serif; margin: 1em; }
+#layout { display: flex; gap: 1em; align-items: flex-start; }
+.plate { display: none; }
+.plate.selected { display: block; }
+.plate text { pointer-events: none; }
+.plate polygon:hover { stroke-width: 3; }
+#info { min-width: 18em; padding: 0.5em; border: 1px solid #888; }
+#swatch { width: 100%; height: 4em; border: 1px solid #000; margin-bottom: 0.5em; }
+#info ul { padding-left: 1.2em; margin: 0.2em 0; }
+";
+
+const SCRIPT: &str = "
+const select = document.getElementById('hue');
+const info = document.getElementById('info');
+
+function showPlate() {
+    for (const plate of document.querySelectorAll('.plate')) {
+        plate.classList.toggle('selected', plate.dataset.leaf === select.value);
+    }
+}
+
+function showColor(leaf, id) {
+    const color = COLORS[id];
+    const blocks = BLOCKS[leaf][id].map((b) => `<li>chroma ${b.chroma}, value ${b.value}</li>`);
+    info.innerHTML = `<div id=\"swatch\" style=\"background: ${color.color}\"></div>`
+        + `<b>${id}: ${color.name}</b> (${color.abbr})<br>`
+        + (color.description ? `<p>${color.description}</p>` : '')
+        + (color.usage ? `<p><i>${color.usage}</i></p>` : '')
+        + `hues ${HUES[leaf]}<ul>${blocks.join('')}</ul>`
+        + `representative color ${color.color}`;
+}
+
+for (const plate of document.querySelectorAll('.plate')) {
+    for (const polygon of plate.querySelectorAll('polygon')) {
+        polygon.addEventListener('mouseenter', () => showColor(plate.dataset.leaf, polygon.dataset.color));
+    }
+}
+select.addEventListener('change', showPlate);
+showPlate();
+";
+
+fn escape(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+}
+
+/// Build the data shown when hovering regions: the name, abbreviation,
+/// representative color, description and usage notes of every level 3
+/// color, and for every hue leaf,
+/// the chroma and value boundaries of the blocks of each color in it.
+fn hover_data(dataset: &Dataset, colors: &[Srgb]) -> (Value, Value, Value) {
+    let hues = dataset.hues();
+    let chromas = dataset.chromas();
+    let values = dataset.values();
+
+    // in order of id, so that the page is the same from run to run
+    let mut names = Map::new();
+    for (id, name) in sorted_names(dataset.names()) {
+        names.insert(
+            id.to_string(),
+            json!({
+                "name": name.name,
+                "abbr": name.abbr,
+                "color": rgb_space().css_color(colors[(id - 1) as usize]),
+                "description": name.description.as_deref().map(escape),
+                "usage": name.usage.as_deref().map(escape),
+            }),
+        );
+    }
+
+    let mut leaves = Map::new();
+    let mut leaf_hues = Map::new();
+    for h in 0..hues.len() {
+        let mut blocks = Map::new();
+        for block in dataset.blocks().iter().filter(|x| x.hues.start == h) {
+            let entry = blocks
+                .entry(block.color_id.to_string())
+                .or_insert_with(|| json!([]));
+            entry.as_array_mut().unwrap().push(json!({
+                "chroma": format!("{}-{}", chromas[block.chromas.start], chromas[block.chromas.end]),
+                "value": format!("{}-{}", values[block.values.start], values[block.values.end]),
+            }));
+        }
+        leaves.insert(h.to_string(), Value::Object(blocks));
+        leaf_hues.insert(
+            h.to_string(),
+            json!(format!("{}-{}", hues[h], hues[(h + 1) % hues.len()])),
+        );
+    }
+
+    return (
+        Value::Object(names),
+        Value::Object(leaves),
+        Value::Object(leaf_hues),
+    );
+}
+
+/// Build an HTML page with an SVG chart of every hue leaf, a selector to
+/// switch between them, and a panel describing the hovered region.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn charts_to_html(dataset: &Dataset, colors: &[Srgb], progress: Progress) -> String {
+    let font_data = load_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+
+    let (names, blocks, hues) = hover_data(dataset, colors);
+
+    let mut options = String::new();
+    let mut plates = String::new();
+    let total = dataset.hues().len();
+    report(progress, Stage::Render, 0, total);
+    for h in 0..total {
+        let plate = dataset.plate(h).unwrap();
+        options.push_str(&format!(
+            "<option value=\"{}\">{}-{} (page {})</option>\n",
+            h,
+            escape(&plate.hue_begin),
+            escape(&plate.hue_end),
+            plate.page
+        ));
+        plates.push_str(&format!(
+            "<div class=\"plate\" data-leaf=\"{}\">\n{}</div>\n",
+            h,
+            plate_to_svg(dataset, &plate, colors, &measure)
+        ));
+        report(progress, Stage::Render, h + 1, total);
+    }
+
+    return format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>ISCC-NBS color name charts</title>
+<style>{}</style>
+</head>
+<body>
+<label>Hues <select id=\"hue\">
+{}</select></label>
+<div id=\"layout\">
+<div>
+{}</div>
+<div id=\"info\">Hover a region to see its color.</div>
+</div>
+<script>
+const COLORS = {};
+const BLOCKS = {};
+const HUES = {};
+{}</script>
+</body>
+</html>
+",
+        STYLE, options, plates, names, blocks, hues, SCRIPT
+    );
+}
+
+/// Write `doc/charts.html`.
+pub fn generate_html(dataset: &Dataset, colors: &[Srgb]) {
+    generate_html_with_progress(dataset, colors, &mut no_progress);
+}
+
+/// Like [`generate_html`], reporting progress through the render stage, one
+/// unit per hue leaf.
+pub fn generate_html_with_progress(dataset: &Dataset, colors: &[Srgb], progress: Progress) {
+    std::fs::write("doc/charts.html", charts_to_html(dataset, colors, progress)).unwrap();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::dataset::BUNDLED_XML;
+    use crate::plot::html::{charts_to_html, hover_data};
+    use crate::progress::no_progress;
+    use crate::Dataset;
+
+    #[test]
+    fn hover_contents() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let (names, blocks, hues) = hover_data(&dataset, &colors);
+
+        assert_eq!(names["1"]["name"], "Vivid pink");
+        assert_eq!(blocks["0"]["1"][0]["chroma"], "11.0-INF");
+        assert_eq!(blocks["0"]["1"][0]["value"], "6.5-INF");
+        assert_eq!(hues["0"], "1R-4R");
+        assert!(names["1"]["description"].is_null());
+
+        let text = BUNDLED_XML.replacen(
+            "name=\"Vivid pink\" abbr=\"viv.Pk\"",
+            "name=\"Vivid pink\" abbr=\"viv.Pk\" description=\"Pink &lt;bright&gt;\"",
+            1,
+        );
+        let dataset = Dataset::parse(&text).unwrap();
+        let (names, _, _) = hover_data(&dataset, &colors);
+        assert_eq!(names["1"]["description"], "Pink &lt;bright&gt;");
+    }
+
+    #[test]
+    fn page_has_every_leaf() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let html = charts_to_html(&dataset, &colors, &mut no_progress);
+
+        assert_eq!(
+            html.matches("<div class=\"plate\"").count(),
+            dataset.hues().len()
+        );
+        assert_eq!(html.matches("<option ").count(), dataset.hues().len());
+    }
+}
@@ -0,0 +1,131 @@
+//! Content hashes of the color name charts, so that a preview run only
+//! re-renders the hue leaves whose blocks, names or colors, or the settings
+//! they are drawn with, changed since the last run.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use palette::Srgb;
+
+use crate::dataset::Dataset;
+use crate::plate::Plate;
+
+/// Where the hashes of the last rendered charts are kept, next to them.
+pub const HASHES_PATH: &str = "doc/.chart-hashes";
+
+/// FNV-1a, which unlike the hasher in std is stable across Rust releases.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    return hash;
+}
+
+/// Hash everything a chart is drawn from: the hues, the outline of every
+/// region, and the name and color of every region.
+pub fn plate_hash(dataset: &Dataset, plate: &Plate, colors: &[Srgb]) -> u64 {
+    return plate_hash_with(dataset, plate, colors, "");
+}
+
+/// Like [`plate_hash`], also hashing `parameters`, a description of the
+/// settings the chart is drawn with, like its style, size and font.
+pub fn plate_hash_with(dataset: &Dataset, plate: &Plate, colors: &[Srgb], parameters: &str) -> u64 {
+    let mut text = format!("{}\n{}-{}\n", parameters, plate.hue_begin, plate.hue_end);
+    for region in plate.regions.iter() {
+        let id = region.color_id;
+        let color = colors[(id - 1) as usize];
+        writeln!(
+            text,
+            "{} {} {:?}",
+            id,
+            dataset.names()[&id].name,
+            (color.red, color.green, color.blue)
+        )
+        .unwrap();
+        for point in region.polygon.exterior().points() {
+            write!(text, " {},{}", point.x(), point.y()).unwrap();
+        }
+        text.push('\n');
+    }
+    return fnv1a(text.as_bytes());
+}
+
+/// Read the hashes of the last run, by file name; missing or unreadable
+/// files just mean that everything is rendered again.
+pub fn read_hashes(path: &str) -> BTreeMap<String, u64> {
+    let text = std::fs::read_to_string(path).unwrap_or_default();
+    return text
+        .lines()
+        .filter_map(|line| {
+            let (name, hash) = line.split_once(' ')?;
+            return Some((name.to_string(), u64::from_str_radix(hash, 16).ok()?));
+        })
+        .collect();
+}
+
+pub fn write_hashes(path: &str, hashes: &BTreeMap<String, u64>) {
+    let mut text = String::new();
+    for (name, hash) in hashes.iter() {
+        writeln!(text, "{} {:016x}", name, hash).unwrap();
+    }
+    std::fs::write(path, text).unwrap();
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use crate::colors::get_mean_colors;
+    use crate::dataset::BUNDLED_XML;
+    use crate::plot::incremental::{plate_hash, plate_hash_with, read_hashes, write_hashes};
+    use crate::Dataset;
+
+    #[test]
+    fn only_touched_leaves_change() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let hash =
+            |dataset: &Dataset, h: usize| plate_hash(dataset, &dataset.plate(h).unwrap(), &colors);
+
+        // color 2 only appears on the leaves from 1R to 7R
+        let edited = Dataset::parse(&BUNDLED_XML.replacen(
+            "name=\"Strong pink\"",
+            "name=\"Brilliant pink\"",
+            1,
+        ))
+        .unwrap();
+        let changed = (0..dataset.hues().len())
+            .filter(|h| hash(&dataset, *h) != hash(&edited, *h))
+            .collect::<Vec<usize>>();
+        assert!(!changed.is_empty());
+        assert!(!changed.contains(&12));
+        assert_eq!(hash(&dataset, 0), hash(&Dataset::bundled(), 0));
+    }
+
+    #[test]
+    fn parameters_change_every_leaf() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        for h in 0..dataset.hues().len() {
+            let plate = dataset.plate(h).unwrap();
+            assert_ne!(
+                plate_hash_with(&dataset, &plate, &colors, "Modern 600x800"),
+                plate_hash_with(&dataset, &plate, &colors, "Nbs1955 600x800")
+            );
+        }
+    }
+
+    #[test]
+    fn hashes_round_trip() {
+        let path = std::env::temp_dir().join("iscc-nbs-chart-hashes-test");
+        let path = path.to_str().unwrap();
+        let hashes = BTreeMap::from([("a.png".to_string(), 1), ("b.png".to_string(), u64::MAX)]);
+
+        write_hashes(path, &hashes);
+        assert_eq!(read_hashes(path), hashes);
+        std::fs::remove_file(path).unwrap();
+        assert!(read_hashes(path).is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+//! The final placement of the region labels of a chart, as data, so that
+//! other frontends can draw the labels exactly where the static renders do.
+
+use palette::Srgb;
+use ttf_word_wrap::TTFParserMeasure;
+
+use crate::dataset::Dataset;
+use crate::export::level_names;
+use crate::plate::Plate;
+use crate::plot::config::config;
+use crate::plot::{label_text_color, layout_label, load_font_data};
+
+/// The default font size of the region labels, in pixels of a 600×800
+/// chart; see [`PlotConfig::label_font_size`](crate::plot::config::PlotConfig).
+pub const LABEL_FONT_SIZE: f64 = 11.0;
+
+/// Where and how the label of one region is drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LabelPlacement {
+    pub color_id: u32,
+    /// The center of the label, in chroma and value coordinates.
+    pub position: (f64, f64),
+    /// Counterclockwise rotation in degrees: 0, or 90 for labels that run
+    /// along the value axis.
+    pub rotation: f64,
+    /// The word-wrapped text, starting with "<id>: ".
+    pub lines: Vec<String>,
+    pub font_size: f64,
+    /// Black on light colors, white on dark ones.
+    pub text_color: Srgb<u8>,
+}
+
+/// Place the label of every region of a plate, in the order of the regions.
+///
+/// `colors` are the representative colors of the names of the level of the
+/// plate, indexed by `color_id - 1`.
+pub fn label_placements(dataset: &Dataset, plate: &Plate, colors: &[Srgb]) -> Vec<LabelPlacement> {
+    let font_data = load_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+    return place_labels(dataset, plate, colors, &measure);
+}
+
+/// Like [`label_placements`], measuring with an already loaded font.
+pub(crate) fn place_labels(
+    dataset: &Dataset,
+    plate: &Plate,
+    colors: &[Srgb],
+    measure: &TTFParserMeasure,
+) -> Vec<LabelPlacement> {
+    let names = level_names(dataset, plate.level);
+    return plate
+        .regions
+        .iter()
+        .map(|region| {
+            let id = region.color_id;
+            let color = colors[(id - 1) as usize];
+            let layout = layout_label(
+                &format!("{}: {}", id, names[&id].name),
+                &region.bounds,
+                measure,
+            );
+            return LabelPlacement {
+                color_id: id,
+                position: (region.label_anchor.x(), region.label_anchor.y()),
+                rotation: if layout.rotated { 90.0 } else { 0.0 },
+                lines: layout.lines,
+                font_size: config().label_font_size,
+                text_color: label_text_color(color).parse().unwrap(),
+            };
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::plot::labels::label_placements;
+    use crate::Dataset;
+
+    #[test]
+    fn placements() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let plate = dataset.plate(0).unwrap();
+        let placements = label_placements(&dataset, &plate, &colors);
+
+        assert_eq!(placements.len(), plate.regions.len());
+        for (placement, region) in placements.iter().zip(plate.regions.iter()) {
+            assert_eq!(placement.color_id, region.color_id);
+            assert!(region.bounds.min().x <= placement.position.0);
+            assert!(placement.position.0 <= region.bounds.max().x);
+            assert!(placement.rotation == 0.0 || placement.rotation == 90.0);
+            assert!(placement.lines[0].starts_with(&format!("{}:", region.color_id)));
+            assert_eq!(
+                placement.lines.join(" "),
+                format!(
+                    "{}: {}",
+                    region.color_id,
+                    dataset.names()[&region.color_id].name
+                )
+            );
+        }
+
+        // "Black" is labeled in white
+        let black = placements.iter().find(|x| x.color_id == 267).unwrap();
+        assert_eq!(black.text_color, palette::Srgb::new(255, 255, 255));
+    }
+}
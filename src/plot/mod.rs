@@ -0,0 +1,384 @@
+//! Rendering of the color name charts and related figures, directly to PNG,
+//! SVG or PDF, or through gnuplot.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use std::process::Command;
+
+use fontconfig::Fontconfig;
+use geo_types::Rect;
+use palette::Srgb;
+use ttf_word_wrap::{TTFParserMeasure, WhiteSpaceWordWrap, Wrap};
+
+use crate::colors::is_light;
+use crate::dataset::Dataset;
+use crate::munsell::MunsellColor;
+use crate::plate::Plate;
+use crate::plot::config::config;
+
+pub mod book;
+pub mod charts;
+pub mod config;
+pub mod html;
+pub mod incremental;
+pub mod labels;
+pub mod pdf;
+pub mod png;
+pub mod poster;
+pub mod slice;
+pub mod svg;
+pub mod tikz;
+pub mod wheel;
+
+/// The default typeface of the charts; [`PlotConfig`](config::PlotConfig)
+/// can name another.
+pub(crate) const FONT_FACE: &str = "DejaVu Sans";
+/// The typeface of the charts in the style of the 1955 publication.
+pub(crate) const SERIF_FONT_FACE: &str = "DejaVu Serif";
+
+/// DejaVu Sans, for when fontconfig isn't available or finds nothing, as on
+/// Windows or in minimal containers; see `fonts/LICENSE`.
+const EMBEDDED_FONT: &[u8] = include_bytes!("../../fonts/DejaVuSans.ttf");
+
+/// Find a font with fontconfig, which is loaded when first needed, if it is
+/// installed at all.
+fn find_system_font(face: &str, style: Option<&str>) -> Option<Vec<u8>> {
+    let fc = Fontconfig::new()?;
+    let font = fc.find(face, style)?;
+    return std::fs::read(font.path).ok();
+}
+
+/// Read the font file of the plot configuration, if it has one, for every
+/// typeface and style; or else find the font with fontconfig, falling back
+/// to the embedded one.
+fn find_font_data(face: &str, style: Option<&str>) -> Vec<u8> {
+    if let Some(path) = &config().font_path {
+        return std::fs::read(path).expect("font file does not exist");
+    }
+    return find_system_font(face, style).unwrap_or_else(|| EMBEDDED_FONT.to_vec());
+}
+
+/// Read the font used for labels, so that they can be measured for
+/// word wrapping.
+pub(crate) fn load_font_data() -> Vec<u8> {
+    return find_font_data(&config().font_name, None);
+}
+
+/// Read the bold variant of the label font.
+pub(crate) fn load_bold_font_data() -> Vec<u8> {
+    return find_font_data(&config().font_name, Some("Bold"));
+}
+
+/// Read the label font of the 1955 style.
+pub(crate) fn load_serif_font_data() -> Vec<u8> {
+    return find_font_data(SERIF_FONT_FACE, None);
+}
+
+/// Read the bold variant of the label font of the 1955 style.
+pub(crate) fn load_bold_serif_font_data() -> Vec<u8> {
+    return find_font_data(SERIF_FONT_FACE, Some("Bold"));
+}
+
+/// A region label, broken into lines.
+pub(crate) struct LabelLayout {
+    pub lines: Vec<String>,
+    /// Whether the label runs along the value axis (rotated 90 degrees
+    /// counterclockwise) to fit a tall, narrow region.
+    pub rotated: bool,
+}
+
+/// Word-wrap a label to fit the bounds of its region, either horizontally
+/// or rotated, whichever needs fewer lines.
+pub(crate) fn layout_label(
+    text: &str,
+    bounds: &Rect<f64>,
+    measure: &TTFParserMeasure,
+) -> LabelLayout {
+    // Should probably be computed from the graph view somehow but:
+    let horiz_scale_factor = config().label_wrap_horizontal;
+    let vert_scale_factor = config().label_wrap_vertical;
+
+    // try a word wrap horizontally
+    let h_word_wrap =
+        WhiteSpaceWordWrap::new((horiz_scale_factor * bounds.width()) as u32, measure);
+    let h_lines = text.wrap(&h_word_wrap).collect::<Vec<&str>>();
+
+    // try a word wrap vertically
+    let v_word_wrap =
+        WhiteSpaceWordWrap::new((vert_scale_factor * bounds.height()) as u32, measure);
+    let v_lines = text.wrap(&v_word_wrap).collect::<Vec<&str>>();
+
+    // Base the winner on line count.
+    let rotated = h_lines.len() > v_lines.len();
+    let lines = if rotated { v_lines } else { h_lines };
+
+    return LabelLayout {
+        lines: lines.into_iter().map(|x| x.to_string()).collect(),
+        rotated,
+    };
+}
+
+/// The color of label text drawn over a region of the given color: black
+/// on light colors, white on dark ones, as judged in the perceptual space of
+/// the configuration.
+pub(crate) fn label_text_color(color: Srgb) -> &'static str {
+    if is_light(color, config().perceptual_space) {
+        return "000000";
+    }
+    return "FFFFFF";
+}
+
+/// What to draw over the regions of the color name charts, besides their
+/// labels.
+#[derive(Clone, Debug, Default)]
+pub struct ChartOverlays {
+    /// Published centroids of the level 3 colors, by color id, like
+    /// [`parse_centroid_table`] reads, to mark each region at: with a dot
+    /// on the leaf of the centroid's hue, and a ring on the other leaves of
+    /// its color.
+    ///
+    /// [`parse_centroid_table`]: crate::centroids::parse_centroid_table
+    pub centroids: HashMap<u32, MunsellColor>,
+    /// Whether to draw the edge of the sRGB gamut at the middle hue of each
+    /// leaf, from [`Dataset::leaf_gamut_boundary`]; the colors past it can't
+    /// be shown on screen.
+    pub gamut_boundary: bool,
+}
+
+/// The edge of the sRGB gamut on a plate, as the runs of points of
+/// [`Dataset::leaf_gamut_boundary`] within the visible chroma range.
+pub(crate) fn gamut_boundary_lines(dataset: &Dataset, plate: &Plate) -> Vec<Vec<(f64, f64)>> {
+    let mut lines: Vec<Vec<(f64, f64)>> = vec![Vec::new()];
+    for (chroma, value) in dataset.leaf_gamut_boundary(plate.hue_index) {
+        if (chroma as f64) <= plate.axes.chroma_range.1 {
+            lines
+                .last_mut()
+                .unwrap()
+                .push((chroma as f64, value as f64));
+        } else if !lines.last().unwrap().is_empty() {
+            lines.push(Vec::new());
+        }
+    }
+    lines.retain(|x| x.len() > 1);
+    return lines;
+}
+
+/// A published centroid of the color of a region, marked on a plate.
+pub(crate) struct CentroidMarker {
+    pub color_id: u32,
+    /// The chroma (x) and value (y) of the centroid.
+    pub position: (f64, f64),
+    /// Whether the hue of the centroid falls on this leaf; other leaves
+    /// show where the centroid is in chroma and value only. Neutral
+    /// centroids are on every leaf.
+    pub on_leaf: bool,
+}
+
+/// The markers of the published centroids of the colors of the regions of
+/// a plate, from [`ChartOverlays::centroids`], in order of color id.
+/// Centroids outside the visible range are left out.
+pub(crate) fn centroid_markers(
+    dataset: &Dataset,
+    plate: &Plate,
+    centroids: &HashMap<u32, MunsellColor>,
+) -> Vec<CentroidMarker> {
+    let (chroma_range, value_range) = (plate.axes.chroma_range, plate.axes.value_range);
+    return plate
+        .regions
+        .iter()
+        .filter_map(|region| {
+            let centroid = centroids.get(&region.color_id)?;
+            let position = (centroid.chroma as f64, centroid.value as f64);
+            if position.0 > chroma_range.1 || position.1 > value_range.1 {
+                return None;
+            }
+            let on_leaf = centroid.is_neutral()
+                || dataset.leaf_containing(centroid.hue).index == plate.hue_index;
+            return Some(CentroidMarker {
+                color_id: region.color_id,
+                position,
+                on_leaf,
+            });
+        })
+        .collect();
+}
+
+/// Write the preamble shared by all figures drawn on a plate's axes.
+pub(crate) fn write_plate_header(file: &mut File, plate: &Plate, title: &str) {
+    writeln!(file, "set encoding utf8").unwrap();
+    writeln!(
+        file,
+        "set xrange [ {:.1} : {:.1} ]",
+        plate.axes.chroma_range.0, plate.axes.chroma_range.1
+    )
+    .unwrap();
+    writeln!(
+        file,
+        "set yrange [ {:.1} : {:.1} ]",
+        plate.axes.value_range.0, plate.axes.value_range.1
+    )
+    .unwrap();
+    writeln!(file, "set grid xtics ytics").unwrap();
+    writeln!(file, "unset key").unwrap();
+    writeln!(file, "set border 3").unwrap();
+    writeln!(file, "set xlabel \"Munsell Chroma\"").unwrap();
+    writeln!(file, "set ylabel \"Munsell Value\"").unwrap();
+    writeln!(file, "set title \"{}\" offset graph 0.45,0", title).unwrap();
+
+    writeln!(file, "set style fill empty").unwrap();
+    writeln!(file, "set style line 1 default").unwrap();
+}
+
+/// Write the tick marks for a plate's axes, the output settings, and the
+/// final plot command.
+pub(crate) fn write_plate_footer(file: &mut File, plate: &Plate, basename: &str) {
+    writeln!(
+        file,
+        "set xtics border nomirror out scale 2.0 font '{},8'",
+        config().font_name
+    )
+    .unwrap();
+    writeln!(file, "set xtics 0, 2.0").unwrap();
+    writeln!(file, "set xtics add (1.0)").unwrap();
+    for (i, tick) in plate.axes.fine_chroma_ticks.iter().enumerate() {
+        // nudge the tick label away from the 1.0 tick
+        let label_x = if *tick < 1.0 {
+            tick - 0.05
+        } else {
+            tick + 0.05
+        };
+
+        writeln!(file, "set xtics add (\"{}\" {} 1)", tick, tick).unwrap();
+        writeln!(
+            file,
+            "set label {} \"{}\" at first {:.2},-0.25 center font \"{},6\"",
+            1000 + i,
+            tick,
+            label_x,
+            config().font_name
+        )
+        .unwrap();
+    }
+
+    writeln!(file, "set mxtics 2").unwrap();
+    writeln!(
+        file,
+        "set ytics border nomirror out scale 2.0 font '{},8'",
+        config().font_name
+    )
+    .unwrap();
+    writeln!(file, "set ytics 0, 1.0").unwrap();
+    writeln!(file, "set mytics 2").unwrap();
+
+    writeln!(
+        file,
+        "set terminal pngcairo size {},{} enhanced font '{},7'",
+        config().width,
+        config().height,
+        config().font_name
+    )
+    .unwrap();
+    writeln!(file, "set output '{}.png'", basename).unwrap();
+
+    // we need to plot _something_; can't just have polygons
+    writeln!(file, "plot x+9999").unwrap();
+}
+
+/// Format the outline of a polygon as a gnuplot vertex list.
+pub(crate) fn polygon_vertices(polygon: &geo_types::Polygon<f64>) -> String {
+    return polygon
+        .exterior()
+        .points()
+        .map(|v| format!("{},{}", v.x(), v.y()))
+        .collect::<Vec<String>>()
+        .join(" to ");
+}
+
+/// Why gnuplot could not render a script.
+#[derive(Debug)]
+pub enum GnuplotError {
+    /// gnuplot isn't installed, or isn't on the `PATH`.
+    NotFound,
+    Io(std::io::Error),
+    /// gnuplot ran, but failed on the script.
+    Failed(String),
+}
+
+impl fmt::Display for GnuplotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            GnuplotError::NotFound => write!(f, "gnuplot is not installed"),
+            GnuplotError::Io(e) => write!(f, "cannot run gnuplot: {}", e),
+            GnuplotError::Failed(script) => write!(f, "gnuplot failed on {}", script),
+        };
+    }
+}
+
+impl std::error::Error for GnuplotError {}
+
+/// Render `<basename>.gnu` into `<basename>.png`.
+pub(crate) fn run_gnuplot(basename: &str) -> Result<(), GnuplotError> {
+    let script = format!("{}.gnu", basename);
+    let status = match Command::new("gnuplot").arg(&script).status() {
+        Ok(v) => v,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Err(GnuplotError::NotFound),
+        Err(e) => return Err(GnuplotError::Io(e)),
+    };
+    if !status.success() {
+        return Err(GnuplotError::Failed(script));
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+    use crate::centroids::parse_centroid_table;
+    use crate::plot::{centroid_markers, gamut_boundary_lines, EMBEDDED_FONT};
+    use crate::Dataset;
+
+    #[test]
+    fn embedded_font() {
+        let face = ttf_parser::Face::from_slice(EMBEDDED_FONT, 0).unwrap();
+        assert!(face.glyph_index('a').is_some());
+        assert!(face.units_per_em().is_some());
+    }
+
+    #[test]
+    fn centroid_marker_leaves() {
+        let dataset = Dataset::bundled();
+        let centroids =
+            parse_centroid_table("1,2.5R 7.5/12.0\n2,2.5R 7.0/18.0\n263,N 9.5/\n").unwrap();
+
+        let markers = centroid_markers(&dataset, &dataset.plate(0).unwrap(), &centroids);
+        // strong pink is out of the visible chroma range
+        assert_eq!(
+            markers.iter().map(|x| x.color_id).collect::<Vec<u32>>(),
+            vec![1, 263]
+        );
+        assert_eq!(markers[0].position, (12.0, 7.5));
+        assert!(markers.iter().all(|x| x.on_leaf));
+
+        // vivid pink is on the leaf before too, where its centroid isn't
+        let last = dataset.hues().len() - 1;
+        let markers = centroid_markers(&dataset, &dataset.plate(last).unwrap(), &centroids);
+        assert!(markers.iter().any(|x| x.color_id == 1 && !x.on_leaf));
+        assert!(markers.iter().any(|x| x.color_id == 263 && x.on_leaf));
+    }
+
+    #[test]
+    fn gamut_boundary_runs() {
+        // the reds run past the visible chroma range in the middle values
+        let dataset = Dataset::bundled();
+        let plate = dataset.plate(0).unwrap();
+        let lines = gamut_boundary_lines(&dataset, &plate);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0][0], (0.0, 0.0));
+        assert_eq!(lines[1].last(), Some(&(0.0, 10.0)));
+        assert!(lines
+            .iter()
+            .flatten()
+            .all(|x| x.0 <= plate.axes.chroma_range.1));
+    }
+}
@@ -0,0 +1,664 @@
+//! The color name charts as one printable PDF, laid out like NBS Special
+//! Publication 440: the two hue leaves of each page side by side, under the
+//! page number they have in the publication.
+//!
+//! The PDF is written directly, with the regions and axes as vector paths
+//! and the label font embedded, so that it prints the same everywhere.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+use geo_types::Polygon;
+use palette::Srgb;
+use ttf_parser::Face;
+use ttf_word_wrap::TTFParserMeasure;
+
+use crate::dataset::Dataset;
+use crate::plate::Plate;
+use crate::plot::labels::place_labels;
+use crate::plot::{load_bold_font_data, load_font_data};
+use crate::progress::{no_progress, report, Progress, Stage};
+
+/// The size of a page, US Letter in landscape, in points.
+const PAGE_WIDTH: f64 = 792.0;
+const PAGE_HEIGHT: f64 = 612.0;
+/// The space around and between the leaves of a page, in points.
+const MARGIN: f64 = 36.0;
+
+/// The size of a leaf in the pixels of the PNG and SVG charts, which the
+/// leaves are drawn in and then scaled to fit the page.
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 800.0;
+
+/// The edges of the plotting area, in pixels from the bottom left.
+const LEFT: f64 = 60.0;
+const RIGHT: f64 = 580.0;
+const TOP: f64 = 760.0;
+const BOTTOM: f64 = 60.0;
+
+/// Font size in pixels for tick marks.
+const TICK_FONT_SIZE: f64 = 11.0;
+/// Font size in points for page numbers.
+const PAGE_NUMBER_FONT_SIZE: f64 = 10.0;
+
+/// How far below the middle of a line of text its baseline is, as a share
+/// of the font size, to center text vertically like SVG's
+/// `dominant-baseline="middle"`.
+const BASELINE_SHIFT: f64 = 0.35;
+
+/// The resource names of the label fonts in the content streams.
+const REGULAR: &str = "F1";
+const BOLD: &str = "F2";
+
+/// A TrueType font to embed, with the widths of the characters it is used
+/// for.
+struct PdfFont {
+    data: Vec<u8>,
+    name: String,
+    /// Advance widths in thousandths of the font size of the characters
+    /// from U+0020 through U+00FF, the printable range of
+    /// `WinAnsiEncoding` that agrees with Latin-1.
+    widths: Vec<u32>,
+    bbox: [i32; 4],
+    ascent: i32,
+    descent: i32,
+    cap_height: i32,
+}
+
+impl PdfFont {
+    fn new(data: Vec<u8>) -> Self {
+        let face = Face::from_slice(&data, 0).expect("TTF should be valid");
+        let units = face.units_per_em().unwrap_or(1000) as f64;
+        let scale = |x: f64| (x * 1000.0 / units).round() as i32;
+
+        let widths = (0x20..=0xffu32)
+            .map(|code| {
+                let advance = char::from_u32(code)
+                    .and_then(|c| face.glyph_index(c))
+                    .and_then(|glyph| face.glyph_hor_advance(glyph))
+                    .unwrap_or(0);
+                return scale(advance as f64) as u32;
+            })
+            .collect();
+        // PDF names may not contain spaces or delimiters
+        let name = face
+            .names()
+            .find(|x| x.name_id() == ttf_parser::name_id::POST_SCRIPT_NAME)
+            .and_then(|x| x.to_string())
+            .unwrap_or_else(|| "ChartFont".to_string())
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect();
+        let bbox = face.global_bounding_box();
+
+        return PdfFont {
+            name,
+            widths,
+            bbox: [
+                scale(bbox.x_min as f64),
+                scale(bbox.y_min as f64),
+                scale(bbox.x_max as f64),
+                scale(bbox.y_max as f64),
+            ],
+            ascent: scale(face.ascender() as f64),
+            descent: scale(face.descender() as f64),
+            cap_height: scale(face.capital_height().unwrap_or(face.ascender()) as f64),
+            data,
+        };
+    }
+
+    /// The width of `text` at a font size of 1.
+    fn text_width(&self, text: &str) -> f64 {
+        return encode(text)
+            .iter()
+            .map(|x| self.widths[(x - 0x20) as usize] as f64 / 1000.0)
+            .sum();
+    }
+}
+
+/// Encode text in `WinAnsiEncoding`, replacing characters outside of
+/// Latin-1 and control characters with question marks.
+fn encode(text: &str) -> Vec<u8> {
+    return text
+        .chars()
+        .map(|c| match c as u32 {
+            x @ (0x20..=0x7e | 0xa0..=0xff) => x as u8,
+            _ => b'?',
+        })
+        .collect();
+}
+
+/// Encode text as a PDF string literal.
+fn string_literal(text: &str) -> String {
+    let mut out = String::from("(");
+    for byte in encode(text) {
+        match byte {
+            b'(' | b')' | b'\\' => {
+                out.push('\\');
+                out.push(byte as char);
+            }
+            0x20..=0x7e => out.push(byte as char),
+            _ => write!(out, "\\{:03o}", byte).unwrap(),
+        }
+    }
+    out.push(')');
+    return out;
+}
+
+fn rgb(color: Srgb<u8>) -> String {
+    let c: Srgb<f32> = color.into_format();
+    return format!("{:.3} {:.3} {:.3}", c.red, c.green, c.blue);
+}
+
+/// Maps chroma/value coordinates of a plate to the pixels of a leaf.
+struct View {
+    chroma_range: (f64, f64),
+    value_range: (f64, f64),
+}
+
+impl View {
+    fn new(plate: &Plate) -> Self {
+        return View {
+            chroma_range: plate.axes.chroma_range,
+            value_range: plate.axes.value_range,
+        };
+    }
+
+    fn x(&self, chroma: f64) -> f64 {
+        let (min, max) = self.chroma_range;
+        return LEFT + (chroma - min) / (max - min) * (RIGHT - LEFT);
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        let (min, max) = self.value_range;
+        return BOTTOM + (value - min) / (max - min) * (TOP - BOTTOM);
+    }
+}
+
+/// Draws the operators of a content stream.
+struct Content<'a> {
+    out: String,
+    regular: &'a PdfFont,
+    bold: &'a PdfFont,
+}
+
+/// How text is aligned on the point it is drawn at.
+#[derive(Clone, Copy)]
+enum Align {
+    Center,
+    Right,
+}
+
+impl Content<'_> {
+    fn line(&mut self, from: (f64, f64), to: (f64, f64)) {
+        writeln!(
+            self.out,
+            "{:.2} {:.2} m {:.2} {:.2} l S",
+            from.0, from.1, to.0, to.1
+        )
+        .unwrap();
+    }
+
+    fn polygon(&mut self, view: &View, polygon: &Polygon<f64>) {
+        for (i, v) in polygon.exterior().points().enumerate() {
+            let operator = if i == 0 { "m" } else { "l" };
+            write!(
+                self.out,
+                "{:.2} {:.2} {} ",
+                view.x(v.x()),
+                view.y(v.y()),
+                operator
+            )
+            .unwrap();
+        }
+        // close, fill and stroke
+        writeln!(self.out, "b").unwrap();
+    }
+
+    /// Draw spans of text in the given fonts as one line, with its baseline
+    /// at `at` rotated counterclockwise by `rotation` degrees.
+    fn text(
+        &mut self,
+        spans: &[(&str, bool)],
+        size: f64,
+        at: (f64, f64),
+        rotation: f64,
+        align: Align,
+    ) {
+        let font = |bold: bool| if bold { self.bold } else { self.regular };
+        let width: f64 = spans
+            .iter()
+            .map(|(text, bold)| font(*bold).text_width(text) * size)
+            .sum();
+        let dx = match align {
+            Align::Center => -width / 2.0,
+            Align::Right => -width,
+        };
+        let (sin, cos) = rotation.to_radians().sin_cos();
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            "BT {:.4} {:.4} {:.4} {:.4} {:.2} {:.2} Tm",
+            cos,
+            sin,
+            -sin,
+            cos,
+            at.0 + cos * dx,
+            at.1 + sin * dx
+        )
+        .unwrap();
+        for (text, bold) in spans {
+            writeln!(
+                out,
+                "/{} {:.2} Tf {} Tj",
+                if *bold { BOLD } else { REGULAR },
+                size,
+                string_literal(text)
+            )
+            .unwrap();
+        }
+        writeln!(out, "ET").unwrap();
+        self.out.push_str(&out);
+    }
+}
+
+fn draw_axes(content: &mut Content, view: &View, plate: &Plate) {
+    let mut chroma_ticks: Vec<(f64, f64)> = (0..=16)
+        .step_by(2)
+        .map(|x| (x as f64, TICK_FONT_SIZE))
+        .collect();
+    chroma_ticks.push((1.0, TICK_FONT_SIZE));
+    for tick in plate.axes.fine_chroma_ticks.iter() {
+        chroma_ticks.push((*tick, TICK_FONT_SIZE * 0.75));
+    }
+
+    writeln!(content.out, "q 0.8 G [2 3] 0 d").unwrap();
+    for (tick, _) in chroma_ticks.iter() {
+        let x = view.x(*tick);
+        content.line((x, BOTTOM), (x, TOP));
+    }
+    for tick in 0..=10 {
+        let y = view.y(tick as f64);
+        content.line((LEFT, y), (RIGHT, y));
+    }
+    writeln!(content.out, "Q").unwrap();
+
+    writeln!(
+        content.out,
+        "{} {} m {} {} l {} {} l S",
+        LEFT, TOP, LEFT, BOTTOM, RIGHT, BOTTOM
+    )
+    .unwrap();
+
+    for (tick, size) in chroma_ticks.iter() {
+        let x = view.x(*tick);
+        content.line((x, BOTTOM), (x, BOTTOM - 6.0));
+        content.text(
+            &[(&tick.to_string(), false)],
+            *size,
+            (x, BOTTOM - 8.0 - size),
+            0.0,
+            Align::Center,
+        );
+    }
+    content.text(
+        &[("Munsell Chroma", false)],
+        TICK_FONT_SIZE,
+        ((LEFT + RIGHT) / 2.0, 20.0),
+        0.0,
+        Align::Center,
+    );
+
+    for tick in 0..=10 {
+        let y = view.y(tick as f64);
+        content.line((LEFT - 6.0, y), (LEFT, y));
+        content.text(
+            &[(&tick.to_string(), false)],
+            TICK_FONT_SIZE,
+            (LEFT - 9.0, y - BASELINE_SHIFT * TICK_FONT_SIZE),
+            0.0,
+            Align::Right,
+        );
+    }
+    content.text(
+        &[("Munsell Value", false)],
+        TICK_FONT_SIZE,
+        (22.0, (TOP + BOTTOM) / 2.0),
+        90.0,
+        Align::Center,
+    );
+}
+
+/// Draw one leaf, in pixels from the bottom left of the leaf.
+fn draw_leaf(
+    content: &mut Content,
+    dataset: &Dataset,
+    plate: &Plate,
+    colors: &[Srgb],
+    measure: &TTFParserMeasure,
+) {
+    let view = View::new(plate);
+
+    content.text(
+        &[(&format!("{}-{}", plate.hue_begin, plate.hue_end), false)],
+        TICK_FONT_SIZE,
+        (RIGHT, TOP + 12.0),
+        0.0,
+        Align::Right,
+    );
+
+    // regions extend past the visible range, out to the INF cutoffs
+    writeln!(
+        content.out,
+        "q {} {} {} {} re W n 0 G",
+        LEFT,
+        BOTTOM,
+        RIGHT - LEFT,
+        TOP - BOTTOM
+    )
+    .unwrap();
+    for region in plate.regions.iter() {
+        let color: Srgb<u8> = colors[(region.color_id - 1) as usize].into_format();
+        writeln!(content.out, "{} rg", rgb(color)).unwrap();
+        content.polygon(&view, &region.polygon);
+    }
+
+    for placement in place_labels(dataset, plate, colors, measure) {
+        let (x, y) = (view.x(placement.position.0), view.y(placement.position.1));
+        let size = placement.font_size;
+        let line_height = size * 1.15;
+        let (sin, cos) = placement.rotation.to_radians().sin_cos();
+        writeln!(content.out, "{} rg", rgb(placement.text_color)).unwrap();
+
+        let first = (placement.lines.len() - 1) as f64 * line_height / 2.0;
+        for (i, line) in placement.lines.iter().enumerate() {
+            // the offset of the baseline from the center of the label,
+            // perpendicular to the text
+            let offset = first - i as f64 * line_height - BASELINE_SHIFT * size;
+            let at = (x - sin * offset, y + cos * offset);
+            // the id is in boldface
+            match line.split_once(':').filter(|_| i == 0) {
+                Some((prefix, suffix)) => content.text(
+                    &[(prefix, true), (&format!(":{}", suffix), false)],
+                    size,
+                    at,
+                    placement.rotation,
+                    Align::Center,
+                ),
+                None => content.text(
+                    &[(line, false)],
+                    size,
+                    at,
+                    placement.rotation,
+                    Align::Center,
+                ),
+            }
+        }
+    }
+    writeln!(content.out, "Q 0 g").unwrap();
+
+    draw_axes(content, &view, plate);
+}
+
+/// Collects the objects of a PDF file, numbered from 1 in the order they
+/// are added.
+struct PdfWriter {
+    objects: Vec<Vec<u8>>,
+}
+
+impl PdfWriter {
+    /// Reserve the number of an object to add later.
+    fn reserve(&mut self) -> usize {
+        self.objects.push(Vec::new());
+        return self.objects.len();
+    }
+
+    fn set(&mut self, id: usize, object: Vec<u8>) {
+        self.objects[id - 1] = object;
+    }
+
+    fn add(&mut self, object: &str) -> usize {
+        let id = self.reserve();
+        self.set(id, object.as_bytes().to_vec());
+        return id;
+    }
+
+    fn add_stream(&mut self, dict: &str, data: &[u8]) -> usize {
+        let entries = if dict.is_empty() {
+            String::new()
+        } else {
+            format!("{} ", dict)
+        };
+        let mut object = format!("<< {}/Length {} >>\nstream\n", entries, data.len()).into_bytes();
+        object.extend_from_slice(data);
+        object.extend_from_slice(b"\nendstream");
+        let id = self.reserve();
+        self.set(id, object);
+        return id;
+    }
+
+    /// Embed a font, returning the number of its font dictionary.
+    fn add_font(&mut self, font: &PdfFont) -> usize {
+        let file = self.add_stream(&format!("/Length1 {}", font.data.len()), &font.data);
+        let descriptor = self.add(&format!(
+            "<< /Type /FontDescriptor /FontName /{} /Flags 32 /FontBBox [{} {} {} {}] /ItalicAngle 0 /Ascent {} /Descent {} /CapHeight {} /StemV 80 /FontFile2 {} 0 R >>",
+            font.name,
+            font.bbox[0],
+            font.bbox[1],
+            font.bbox[2],
+            font.bbox[3],
+            font.ascent,
+            font.descent,
+            font.cap_height,
+            file
+        ));
+        let widths = font
+            .widths
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        return self.add(&format!(
+            "<< /Type /Font /Subtype /TrueType /BaseFont /{} /FirstChar 32 /LastChar 255 /Widths [{}] /Encoding /WinAnsiEncoding /FontDescriptor {} 0 R >>",
+            font.name, widths, descriptor
+        ));
+    }
+
+    /// Write out the file, with `root` as its catalog.
+    fn finish(self, root: usize) -> Vec<u8> {
+        let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in self.objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend_from_slice(object);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref = out.len();
+        let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+        for offset in offsets {
+            writeln!(table, "{:010} 00000 n ", offset).unwrap();
+        }
+        write!(
+            table,
+            "trailer\n<< /Size {} /Root {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            self.objects.len() + 1,
+            root,
+            xref
+        )
+        .unwrap();
+        out.extend_from_slice(table.as_bytes());
+        return out;
+    }
+}
+
+/// Render every hue leaf of the color name charts into one PDF document,
+/// two leaves to a page.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn charts_to_pdf(dataset: &Dataset, colors: &[Srgb]) -> Vec<u8> {
+    return charts_to_pdf_with_progress(dataset, colors, &mut no_progress);
+}
+
+/// Like [`charts_to_pdf`], reporting progress through the render stage, one
+/// unit per hue leaf.
+pub fn charts_to_pdf_with_progress(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    progress: Progress,
+) -> Vec<u8> {
+    let font_data = load_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+    let regular = PdfFont::new(font_data.clone());
+    let bold = PdfFont::new(load_bold_font_data());
+
+    let mut pdf = PdfWriter {
+        objects: Vec::new(),
+    };
+    let catalog = pdf.reserve();
+    let pages = pdf.reserve();
+    let regular_id = pdf.add_font(&regular);
+    let bold_id = pdf.add_font(&bold);
+
+    // the leaves of each page of the publication, in order
+    let total = dataset.hues().len();
+    let mut leaves: BTreeMap<usize, Vec<Plate>> = BTreeMap::new();
+    for h in 0..total {
+        let plate = dataset.plate(h).unwrap();
+        leaves.entry(plate.page).or_default().push(plate);
+    }
+
+    // scale the leaves to fit side by side between the margins
+    let scale = f64::min(
+        (PAGE_WIDTH - 3.0 * MARGIN) / 2.0 / WIDTH,
+        (PAGE_HEIGHT - 2.0 * MARGIN) / HEIGHT,
+    );
+    let top = (PAGE_HEIGHT + HEIGHT * scale) / 2.0;
+
+    report(progress, Stage::Render, 0, total);
+    let mut done = 0;
+    let mut kids = Vec::new();
+    for (page, plates) in leaves.iter() {
+        let mut content = Content {
+            out: String::new(),
+            regular: &regular,
+            bold: &bold,
+        };
+        for plate in plates.iter() {
+            let left = MARGIN + plate.side as f64 * (WIDTH * scale + MARGIN);
+            writeln!(
+                content.out,
+                "q {:.4} 0 0 {:.4} {:.2} {:.2} cm",
+                scale,
+                scale,
+                left,
+                top - HEIGHT * scale
+            )
+            .unwrap();
+            draw_leaf(&mut content, dataset, plate, colors, &measure);
+            writeln!(content.out, "Q").unwrap();
+
+            done += 1;
+            report(progress, Stage::Render, done, total);
+        }
+        content.text(
+            &[(&page.to_string(), false)],
+            PAGE_NUMBER_FONT_SIZE,
+            (PAGE_WIDTH / 2.0, MARGIN / 2.0),
+            0.0,
+            Align::Center,
+        );
+
+        let stream = pdf.add_stream("", content.out.as_bytes());
+        kids.push(pdf.add(&format!(
+            "<< /Type /Page /Parent {} 0 R /Contents {} 0 R >>",
+            pages, stream
+        )));
+    }
+
+    pdf.set(
+        pages,
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} /MediaBox [0 0 {} {}] /Resources << /Font << /{} {} 0 R /{} {} 0 R >> >> >>",
+            kids.iter()
+                .map(|x| format!("{} 0 R", x))
+                .collect::<Vec<String>>()
+                .join(" "),
+            kids.len(),
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            REGULAR,
+            regular_id,
+            BOLD,
+            bold_id
+        )
+        .into_bytes(),
+    );
+    // number the pages like the publication in viewers
+    let first_page = leaves.keys().next().copied().unwrap_or(1);
+    pdf.set(
+        catalog,
+        format!(
+            "<< /Type /Catalog /Pages {} 0 R /PageLabels << /Nums [0 << /S /D /St {} >>] >> >>",
+            pages, first_page
+        )
+        .into_bytes(),
+    );
+    return pdf.finish(catalog);
+}
+
+/// Write `doc/charts.pdf`, with every hue leaf.
+pub fn generate_pdf(dataset: &Dataset, colors: &[Srgb]) {
+    generate_pdf_with_progress(dataset, colors, &mut no_progress);
+}
+
+/// Like [`generate_pdf`], reporting progress through the render stage, one
+/// unit per hue leaf.
+pub fn generate_pdf_with_progress(dataset: &Dataset, colors: &[Srgb], progress: Progress) {
+    let pdf = charts_to_pdf_with_progress(dataset, colors, progress);
+    std::fs::write("doc/charts.pdf", pdf).unwrap();
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::plot::pdf::{charts_to_pdf, string_literal};
+    use crate::Dataset;
+
+    #[test]
+    fn charts_pdf() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let pdf = charts_to_pdf(&dataset, &colors);
+        let contains = |x: &str| pdf.windows(x.len()).any(|w| w == x.as_bytes());
+
+        assert!(pdf.starts_with(b"%PDF-1.4\n"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        // two leaves to a page, numbered like the publication
+        let page = b"/Type /Page ";
+        let pages = pdf.windows(page.len()).filter(|w| w == page).count();
+        assert_eq!(pages, dataset.hues().len().div_ceil(2));
+        assert!(contains("/S /D /St 16"));
+        assert!(contains("(16) Tj"));
+        assert!(contains("(1R-4R) Tj"));
+
+        // the cross-reference table points at every object
+        let tail = std::str::from_utf8(&pdf[pdf.len() - 32..]).unwrap();
+        let xref: usize = tail.lines().rev().nth(1).unwrap().parse().unwrap();
+        let table = std::str::from_utf8(&pdf[xref..]).unwrap();
+        assert!(table.starts_with("xref\n"));
+        for (i, line) in table.lines().skip(3).enumerate() {
+            if line == "trailer" {
+                break;
+            }
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj\n", i + 1).as_bytes()));
+        }
+
+        assert_eq!(string_literal("a (b) \\ é"), "(a \\(b\\) \\\\ \\351)");
+    }
+}
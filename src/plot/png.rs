@@ -0,0 +1,1021 @@
+//! The color name charts, Munsell book pages and the hue circle drawn
+//! straight to PNG with plotters, so that no gnuplot installation is needed.
+//!
+//! The drawing functions create the directory of the file they write, and
+//! return the errors of drawing it and writing it out.
+
+use std::path::Path;
+use std::sync::Once;
+
+use geo_clipper::Clipper;
+use geo_types::{Coord, LineString, Polygon};
+use palette::{convert::FromColorUnclamped, Clamp, IntoColor, Lch, Srgb};
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::element::DashedPathElement;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{register_font, FontStyle, FontTransform};
+use ttf_word_wrap::TTFParserMeasure;
+
+use crate::cvd::{simulate_all, Deficiency};
+use crate::dataset::Dataset;
+use crate::munsell::{MunsellColor, MunsellHue};
+use crate::naming::ColorNamingSystem;
+use crate::plate::Plate;
+use crate::plot::book::{book_page_hues, CHIP_VALUES, MAX_CHIP_CHROMA};
+use crate::plot::config::config;
+use crate::plot::incremental::{fnv1a, plate_hash_with, read_hashes, write_hashes, HASHES_PATH};
+use crate::plot::labels::place_labels;
+use crate::plot::poster::{legend_drawing, poster_drawing, PosterDrawing};
+use crate::plot::slice::{slice_drawing, slice_values};
+use crate::plot::wheel::{wheel_drawing, Anchor, WheelDrawing, SIZE};
+use crate::plot::{
+    centroid_markers, gamut_boundary_lines, label_text_color, load_bold_font_data,
+    load_bold_serif_font_data, load_font_data, load_serif_font_data, ChartOverlays, LabelLayout,
+};
+use crate::progress::{no_progress, report, Progress, Stage};
+
+/// The names the label fonts are registered under with plotters.
+const FONT: &str = "iscc-nbs-sans";
+const SERIF_FONT: &str = "iscc-nbs-serif";
+
+const TICK_FONT_SIZE: f64 = 13.0;
+
+type Chart<'a, 'b> =
+    ChartContext<'a, BitMapBackend<'b>, Cartesian2d<RangedCoordf64, RangedCoordf64>>;
+
+static REGISTER_FONTS: Once = Once::new();
+
+/// Hand the label fonts to plotters, which needs them for the lifetime of
+/// the program.
+fn register_fonts() {
+    REGISTER_FONTS.call_once(|| {
+        let fonts = [
+            (FONT, FontStyle::Normal, load_font_data()),
+            (FONT, FontStyle::Bold, load_bold_font_data()),
+            (SERIF_FONT, FontStyle::Normal, load_serif_font_data()),
+            (SERIF_FONT, FontStyle::Bold, load_bold_serif_font_data()),
+        ];
+        for (name, style, data) in fonts {
+            let data: &'static [u8] = Box::leak(data.into_boxed_slice());
+            register_font(name, style, data)
+                .ok()
+                .expect("TTF should be valid");
+        }
+    });
+}
+
+fn srgb_to_rgbcolor(color: Srgb) -> RGBColor {
+    let c: Srgb<u8> = color.into_format();
+    return RGBColor(c.red, c.green, c.blue);
+}
+
+/// Cut a region down to the visible part of the plate; regions run out to
+/// the `INF` cutoffs, just past the edges of the chart.
+fn visible_parts(plate: &Plate, polygon: &Polygon<f64>) -> Vec<Vec<(f64, f64)>> {
+    let (x0, x1) = plate.axes.chroma_range;
+    let (y0, y1) = plate.axes.value_range;
+    let view = Polygon::new(
+        LineString(vec![
+            Coord { x: x0, y: y0 },
+            Coord { x: x0, y: y1 },
+            Coord { x: x1, y: y1 },
+            Coord { x: x1, y: y0 },
+        ]),
+        vec![],
+    );
+
+    return polygon
+        .intersection(&view, 1000.0)
+        .into_iter()
+        .map(|part| part.exterior().points().map(|p| (p.x(), p.y())).collect())
+        .collect();
+}
+
+/// A plotters error as an IO error, so that drawing and saving a PNG fail
+/// the same way.
+fn drawing_error(e: impl std::fmt::Display) -> std::io::Error {
+    return std::io::Error::other(e.to_string());
+}
+
+/// Create the directory of `path`, if it is missing.
+fn create_parent_dir(path: &str) -> std::io::Result<()> {
+    if let Some(parent) = Path::new(path).parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    return Ok(());
+}
+
+/// Draw the axes and grid of a plate, returning the chart to draw into.
+fn draw_frame<'a, 'b>(
+    root: &'a DrawingArea<BitMapBackend<'b>, Shift>,
+    plate: &Plate,
+    title: &str,
+    font: &str,
+) -> std::io::Result<Chart<'a, 'b>> {
+    root.fill(&WHITE).map_err(drawing_error)?;
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(title, (font, TICK_FONT_SIZE))
+        .margin(12)
+        .x_label_area_size(44)
+        .y_label_area_size(36)
+        .build_cartesian_2d(
+            plate.axes.chroma_range.0..plate.axes.chroma_range.1,
+            plate.axes.value_range.0..plate.axes.value_range.1,
+        )
+        .map_err(drawing_error)?;
+
+    chart
+        .configure_mesh()
+        .x_labels(9)
+        .y_labels(11)
+        .x_label_formatter(&|x| format!("{}", x))
+        .y_label_formatter(&|y| format!("{}", y))
+        .disable_x_mesh()
+        .disable_y_mesh()
+        .x_desc("Munsell Chroma")
+        .y_desc("Munsell Value")
+        .label_style((font, TICK_FONT_SIZE))
+        .axis_desc_style((font, TICK_FONT_SIZE))
+        .draw()
+        .map_err(drawing_error)?;
+
+    // the boundaries between the regular ticks
+    let small = TextStyle::from((font, TICK_FONT_SIZE * 0.7).into_font())
+        .pos(Pos::new(HPos::Center, VPos::Top));
+    for tick in plate.axes.fine_chroma_ticks.iter() {
+        let (x, y) = chart
+            .plotting_area()
+            .map_coordinate(&(*tick, plate.axes.value_range.0));
+        root.draw(&PathElement::new(vec![(x, y), (x, y + 4)], BLACK))
+            .map_err(drawing_error)?;
+        root.draw_text(&tick.to_string(), &small, (x, y + 18))
+            .map_err(drawing_error)?;
+    }
+
+    return Ok(chart);
+}
+
+/// Draw a label centered on `center`, with the id before the first colon
+/// in boldface, over a box of `background` if given.
+fn draw_label(
+    root: &DrawingArea<BitMapBackend, Shift>,
+    center: (i32, i32),
+    layout: &LabelLayout,
+    color: RGBColor,
+    size: f64,
+    font: &str,
+    background: Option<RGBColor>,
+) -> std::io::Result<()> {
+    let transform = if layout.rotated {
+        FontTransform::Rotate270
+    } else {
+        FontTransform::None
+    };
+    let style = |font_style: FontStyle| {
+        TextStyle::from((font, size, font_style).into_font())
+            .color(&color)
+            .transform(transform.clone())
+            .pos(Pos::new(HPos::Left, VPos::Center))
+    };
+    let regular = style(FontStyle::Normal);
+    let bold = style(FontStyle::Bold);
+
+    // along is the direction the text runs in, across the direction lines
+    // are stacked in
+    let place = |along: i32, across: i32| {
+        if layout.rotated {
+            (center.0 + across, center.1 - along)
+        } else {
+            (center.0 + along, center.1 + across)
+        }
+    };
+
+    let line_height = (size * 1.15) as i32;
+    let first = -((layout.lines.len() as i32 - 1) * line_height) / 2;
+
+    for (i, line) in layout.lines.iter().enumerate() {
+        let across = first + (i as i32) * line_height;
+        let (prefix, suffix) = match line.split_once(':').filter(|_| i == 0) {
+            Some((prefix, suffix)) => (prefix, format!(":{}", suffix)),
+            None => ("", line.clone()),
+        };
+
+        let prefix_width = root
+            .estimate_text_size(prefix, &bold)
+            .map_err(drawing_error)?
+            .0 as i32;
+        let suffix_width = root
+            .estimate_text_size(&suffix, &regular)
+            .map_err(drawing_error)?
+            .0 as i32;
+        let start = -(prefix_width + suffix_width) / 2;
+
+        if let Some(background) = background {
+            let (x0, y0) = place(start - 1, across - line_height / 2);
+            let (x1, y1) = place(-start + 1, across + line_height / 2);
+            root.draw(&Rectangle::new(
+                [(x0.min(x1), y0.min(y1)), (x0.max(x1), y0.max(y1))],
+                background.filled(),
+            ))
+            .map_err(drawing_error)?;
+        }
+        if !prefix.is_empty() {
+            root.draw_text(prefix, &bold, place(start, across))
+                .map_err(drawing_error)?;
+        }
+        root.draw_text(&suffix, &regular, place(start + prefix_width, across))
+            .map_err(drawing_error)?;
+    }
+    return Ok(());
+}
+
+/// The look of the color name charts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChartStyle {
+    /// Regions filled with their representative colors.
+    Modern,
+    /// After the charts of the 1955 publication: black and white, with
+    /// regions hatched instead of filled, heavy boundaries and serif type.
+    Nbs1955,
+}
+
+/// Hatch lines across a polygon in pixels, `spacing` pixels apart at
+/// `degrees`. Lines lie on a grid shared by all polygons, so that hatching
+/// continues across neighboring regions with the same pattern.
+fn hatch_lines(points: &[(i32, i32)], degrees: f64, spacing: f64) -> Vec<Vec<(i32, i32)>> {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    // coordinates along the hatch lines, and across them
+    let along = |p: (i32, i32)| p.0 as f64 * cos + p.1 as f64 * sin;
+    let across = |p: (i32, i32)| -p.0 as f64 * sin + p.1 as f64 * cos;
+    let pixel = |s: f64, t: f64| {
+        (
+            (s * cos - t * sin).round() as i32,
+            (s * sin + t * cos).round() as i32,
+        )
+    };
+
+    let lo = points
+        .iter()
+        .map(|p| across(*p))
+        .fold(f64::INFINITY, f64::min);
+    let hi = points
+        .iter()
+        .map(|p| across(*p))
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    let mut lines = Vec::new();
+    let mut k = (lo / spacing).ceil();
+    while k * spacing < hi {
+        let t = k * spacing;
+        let mut hits = points
+            .windows(2)
+            .filter_map(|edge| {
+                let (a, b) = (across(edge[0]), across(edge[1]));
+                if (a <= t) == (b <= t) {
+                    return None;
+                }
+                let f = (t - a) / (b - a);
+                return Some(along(edge[0]) + (along(edge[1]) - along(edge[0])) * f);
+            })
+            .collect::<Vec<f64>>();
+        hits.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        // inside between every other crossing
+        for pair in hits.chunks_exact(2) {
+            lines.push(vec![pixel(pair[0], t), pixel(pair[1], t)]);
+        }
+        k += 1.0;
+    }
+    return lines;
+}
+
+/// Render one hue leaf of the color name charts to a PNG file.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn draw_chart(
+    dataset: &Dataset,
+    plate: &Plate,
+    colors: &[Srgb],
+    measure: &TTFParserMeasure,
+    path: &str,
+) -> std::io::Result<()> {
+    return draw_chart_with_style(
+        dataset,
+        plate,
+        colors,
+        measure,
+        ChartStyle::Modern,
+        &ChartOverlays::default(),
+        path,
+    );
+}
+
+/// Like [`draw_chart`], in the given style, with `overlays` drawn over the
+/// regions.
+///
+/// In the 1955 style, the hatching of a region runs at an angle set by its
+/// level 1 category, and its density follows the lightness of its
+/// representative color, denser for darker colors.
+pub fn draw_chart_with_style(
+    dataset: &Dataset,
+    plate: &Plate,
+    colors: &[Srgb],
+    measure: &TTFParserMeasure,
+    style: ChartStyle,
+    overlays: &ChartOverlays,
+    path: &str,
+) -> std::io::Result<()> {
+    register_fonts();
+    create_parent_dir(path)?;
+
+    let root = BitMapBackend::new(path, (config().width, config().height)).into_drawing_area();
+    let (title, font, boundary) = match style {
+        ChartStyle::Modern => (
+            format!("{}-{}", plate.hue_begin, plate.hue_end),
+            FONT,
+            BLACK.stroke_width(1),
+        ),
+        ChartStyle::Nbs1955 => (
+            format!("HUES {} TO {}", plate.hue_begin, plate.hue_end),
+            SERIF_FONT,
+            BLACK.stroke_width(2),
+        ),
+    };
+    let mut chart = draw_frame(&root, plate, &title, font)?;
+
+    for region in plate.regions.iter() {
+        let color = colors[(region.color_id - 1) as usize];
+        for part in visible_parts(plate, &region.polygon) {
+            match style {
+                ChartStyle::Modern => {
+                    chart
+                        .draw_series(std::iter::once(plotters::element::Polygon::new(
+                            part.clone(),
+                            srgb_to_rgbcolor(color).filled(),
+                        )))
+                        .map_err(drawing_error)?;
+                }
+                ChartStyle::Nbs1955 => {
+                    let level1_id = dataset
+                        .ancestor_id(plate.level, region.color_id, 1)
+                        .unwrap();
+                    let degrees = [45.0, 135.0, 0.0, 90.0][(level1_id % 4) as usize];
+                    let lch: Lch = color.into_color();
+                    let spacing = 3.0 + 9.0 * (lch.l as f64) / 100.0;
+
+                    let pixels = part
+                        .iter()
+                        .map(|p| chart.plotting_area().map_coordinate(p))
+                        .collect::<Vec<_>>();
+                    for line in hatch_lines(&pixels, degrees, spacing) {
+                        root.draw(&PathElement::new(line, RGBColor(64, 64, 64)))
+                            .map_err(drawing_error)?;
+                    }
+                }
+            }
+            chart
+                .draw_series(std::iter::once(PathElement::new(part, boundary)))
+                .map_err(drawing_error)?;
+        }
+    }
+
+    if overlays.gamut_boundary {
+        let color = match style {
+            ChartStyle::Modern => RGBColor(64, 64, 64),
+            ChartStyle::Nbs1955 => BLACK,
+        };
+        for line in gamut_boundary_lines(dataset, plate) {
+            chart
+                .draw_series(std::iter::once(DashedPathElement::new(
+                    line,
+                    6,
+                    4,
+                    color.stroke_width(2),
+                )))
+                .map_err(drawing_error)?;
+        }
+    }
+
+    for marker in centroid_markers(dataset, plate, &overlays.centroids) {
+        let center = chart.plotting_area().map_coordinate(&marker.position);
+        let color = match style {
+            ChartStyle::Modern => {
+                let color: Srgb<u8> = label_text_color(colors[(marker.color_id - 1) as usize])
+                    .parse()
+                    .unwrap();
+                RGBColor(color.red, color.green, color.blue)
+            }
+            ChartStyle::Nbs1955 => BLACK,
+        };
+        let shape = if marker.on_leaf {
+            color.filled()
+        } else {
+            color.stroke_width(2)
+        };
+        root.draw(&Circle::new(center, 4, shape))
+            .map_err(drawing_error)?;
+    }
+
+    for placement in place_labels(dataset, plate, colors, measure) {
+        let center = chart.plotting_area().map_coordinate(&placement.position);
+        let layout = LabelLayout {
+            lines: placement.lines,
+            rotated: placement.rotation != 0.0,
+        };
+
+        match style {
+            ChartStyle::Modern => draw_label(
+                &root,
+                center,
+                &layout,
+                RGBColor(
+                    placement.text_color.red,
+                    placement.text_color.green,
+                    placement.text_color.blue,
+                ),
+                placement.font_size,
+                FONT,
+                None,
+            ),
+            ChartStyle::Nbs1955 => draw_label(
+                &root,
+                center,
+                &layout,
+                BLACK,
+                placement.font_size,
+                SERIF_FONT,
+                Some(WHITE),
+            ),
+        }?;
+    }
+
+    root.present().map_err(drawing_error)?;
+    return Ok(());
+}
+
+/// Render one Munsell book page to a PNG file.
+pub fn draw_book_page(dataset: &Dataset, hue_name: &str, path: &str) -> std::io::Result<()> {
+    register_fonts();
+    create_parent_dir(path)?;
+
+    let hue = hue_name.parse::<MunsellHue>().unwrap();
+    let plate = dataset.plate(dataset.leaf_containing(hue).index).unwrap();
+    let root = BitMapBackend::new(path, (config().width, config().height)).into_drawing_area();
+    let mut chart = draw_frame(
+        &root,
+        &plate,
+        &format!("{} ({}-{})", hue_name, plate.hue_begin, plate.hue_end),
+        FONT,
+    )?;
+
+    for value in CHIP_VALUES {
+        for chroma in (2..=MAX_CHIP_CHROMA).step_by(2) {
+            let chip = MunsellColor::new(hue, value as f32, chroma as f32);
+            let rgb = Srgb::from_color_unclamped(chip.to_display_lch());
+            if !rgb.is_within_bounds() {
+                continue;
+            }
+
+            let (x, y) = (chroma as f64, value as f64);
+            chart
+                .draw_series(std::iter::once(Rectangle::new(
+                    [(x - 0.8, y - 0.4), (x + 0.8, y + 0.4)],
+                    srgb_to_rgbcolor(rgb).filled(),
+                )))
+                .map_err(drawing_error)?;
+        }
+    }
+
+    for region in plate.regions.iter() {
+        for part in visible_parts(&plate, &region.polygon) {
+            chart
+                .draw_series(std::iter::once(PathElement::new(
+                    part,
+                    BLACK.stroke_width(2),
+                )))
+                .map_err(drawing_error)?;
+        }
+
+        let center = chart
+            .plotting_area()
+            .map_coordinate(&(region.label_anchor.x(), region.label_anchor.y()));
+        let layout = LabelLayout {
+            lines: vec![region.color_id.to_string()],
+            rotated: false,
+        };
+        draw_label(
+            &root,
+            center,
+            &layout,
+            BLACK,
+            config().label_font_size * 0.8,
+            FONT,
+            None,
+        )?;
+    }
+
+    root.present().map_err(drawing_error)?;
+    return Ok(());
+}
+
+/// Render the hue circle to a PNG file.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn draw_wheel(dataset: &Dataset, colors: &[Srgb], path: &str) -> std::io::Result<()> {
+    return draw_wheel_drawing(&wheel_drawing(dataset, colors), path);
+}
+
+/// Render the layout of the hue circle, or of a slice of the solid, to a
+/// PNG file.
+fn draw_wheel_drawing(drawing: &WheelDrawing, path: &str) -> std::io::Result<()> {
+    register_fonts();
+    create_parent_dir(path)?;
+
+    let root = BitMapBackend::new(path, (SIZE as u32, SIZE as u32)).into_drawing_area();
+    root.fill(&WHITE).map_err(drawing_error)?;
+
+    let pixel = |(x, y): (f64, f64)| (x.round() as i32, y.round() as i32);
+
+    for polygon in drawing.polygons.iter() {
+        let points = polygon.points.iter().map(|p| pixel(*p)).collect::<Vec<_>>();
+        root.draw(&plotters::element::Polygon::new(
+            points.clone(),
+            srgb_to_rgbcolor(polygon.fill).filled(),
+        ))
+        .map_err(drawing_error)?;
+        root.draw(&PathElement::new(
+            points
+                .into_iter()
+                .chain(std::iter::once(pixel(polygon.points[0])))
+                .collect::<Vec<_>>(),
+            BLACK,
+        ))
+        .map_err(drawing_error)?;
+    }
+
+    for line in drawing.lines.iter() {
+        root.draw(&PathElement::new(
+            vec![pixel(line.from), pixel(line.to)],
+            BLACK.stroke_width(line.width as u32),
+        ))
+        .map_err(drawing_error)?;
+    }
+
+    for text in drawing.texts.iter() {
+        let hpos = match text.anchor {
+            Anchor::Start => HPos::Left,
+            Anchor::Middle => HPos::Center,
+            Anchor::End => HPos::Right,
+        };
+        let style =
+            TextStyle::from((FONT, text.size).into_font()).pos(Pos::new(hpos, VPos::Center));
+
+        let line_height = text.size * 1.15;
+        let first = -((text.lines.len() - 1) as f64) * line_height / 2.0;
+        for (i, line) in text.lines.iter().enumerate() {
+            let at = (text.at.0, text.at.1 + first + (i as f64) * line_height);
+            root.draw_text(line, &style, pixel(at))
+                .map_err(drawing_error)?;
+        }
+    }
+
+    root.present().map_err(drawing_error)?;
+    return Ok(());
+}
+
+/// Render `doc/hue_wheel.png`.
+pub fn generate_wheel(dataset: &Dataset, colors: &[Srgb]) -> std::io::Result<()> {
+    return draw_wheel(dataset, colors, "doc/hue_wheel.png");
+}
+
+/// Render the slice of the color solid at `value` to a PNG file.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn draw_slice(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    value: f32,
+    path: &str,
+) -> std::io::Result<()> {
+    return draw_wheel_drawing(&slice_drawing(dataset, colors, value), path);
+}
+
+/// Render a slice through the middle of every value span of the dataset
+/// into `doc/slices/`, named after the value the span begins at.
+pub fn generate_slices(dataset: &Dataset, colors: &[Srgb]) -> std::io::Result<()> {
+    for (begin, end) in slice_values(dataset) {
+        let path = format!("doc/slices/value_{}.png", begin);
+        draw_slice(dataset, colors, (begin + end) / 2.0, &path)?;
+    }
+    return Ok(());
+}
+
+/// Render the poster of every level 3 color to a PNG file.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn draw_poster<S: ColorNamingSystem + ?Sized>(
+    dataset: &S,
+    colors: &[Srgb],
+    path: &str,
+) -> std::io::Result<()> {
+    return draw_swatch_drawing(&poster_drawing(dataset, colors), path);
+}
+
+/// Render the legend of every level 3 color to a PNG file.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn draw_legend<S: ColorNamingSystem + ?Sized>(
+    dataset: &S,
+    colors: &[Srgb],
+    path: &str,
+) -> std::io::Result<()> {
+    return draw_swatch_drawing(&legend_drawing(dataset, colors), path);
+}
+
+fn draw_swatch_drawing(drawing: &PosterDrawing, path: &str) -> std::io::Result<()> {
+    register_fonts();
+    create_parent_dir(path)?;
+
+    let size = (drawing.width.ceil() as u32, drawing.height.ceil() as u32);
+    let root = BitMapBackend::new(path, size).into_drawing_area();
+    root.fill(&WHITE).map_err(drawing_error)?;
+
+    let pixel = |(x, y): (f64, f64)| (x.round() as i32, y.round() as i32);
+
+    for swatch in drawing.swatches.iter() {
+        let corners = [
+            pixel(swatch.at),
+            pixel((swatch.at.0 + swatch.size.0, swatch.at.1 + swatch.size.1)),
+        ];
+        root.draw(&Rectangle::new(
+            corners,
+            srgb_to_rgbcolor(swatch.fill).filled(),
+        ))
+        .map_err(drawing_error)?;
+        root.draw(&Rectangle::new(corners, BLACK))
+            .map_err(drawing_error)?;
+    }
+
+    for text in drawing.texts.iter() {
+        let hpos = match text.anchor {
+            Anchor::Start => HPos::Left,
+            Anchor::Middle => HPos::Center,
+            Anchor::End => HPos::Right,
+        };
+        let style = if text.bold {
+            FontStyle::Bold
+        } else {
+            FontStyle::Normal
+        };
+        let color = RGBColor(text.color.red, text.color.green, text.color.blue);
+        let style = TextStyle::from((FONT, text.size, style).into_font())
+            .color(&color)
+            .pos(Pos::new(hpos, VPos::Center));
+        root.draw_text(&text.text, &style, pixel(text.at))
+            .map_err(drawing_error)?;
+    }
+
+    root.present().map_err(drawing_error)?;
+    return Ok(());
+}
+
+/// Render `doc/poster.png`.
+pub fn generate_poster(dataset: &Dataset, colors: &[Srgb]) -> std::io::Result<()> {
+    return draw_poster(dataset, colors, "doc/poster.png");
+}
+
+/// Render `doc/page*.png` for every hue leaf, with `overlays` drawn over
+/// the regions, and `doc/legend.png`.
+pub fn generate_charts(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    overlays: &ChartOverlays,
+) -> std::io::Result<()> {
+    return generate_charts_with_progress(dataset, colors, overlays, &mut no_progress);
+}
+
+/// Like [`generate_charts`], reporting progress through the render stage,
+/// one unit per hue leaf.
+pub fn generate_charts_with_progress(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    overlays: &ChartOverlays,
+    progress: Progress,
+) -> std::io::Result<()> {
+    let font_data = load_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+
+    let total = dataset.hues().len();
+    report(progress, Stage::Render, 0, total);
+    for h in 0..total {
+        let plate = dataset.plate(h).unwrap();
+        draw_chart_with_style(
+            dataset,
+            &plate,
+            colors,
+            &measure,
+            ChartStyle::Modern,
+            overlays,
+            &chart_path("doc", &plate),
+        )?;
+        report(progress, Stage::Render, h + 1, total);
+    }
+    return draw_legend(dataset, colors, "doc/legend.png");
+}
+
+fn chart_path(dir: &str, plate: &Plate) -> String {
+    return format!(
+        "{}/page{}-{}_hues_{}-{}.png",
+        dir, plate.page, plate.side, plate.hue_begin, plate.hue_end
+    );
+}
+
+/// Render `doc/nbs-1955/page*.png` for every hue leaf, in the style of the
+/// 1955 publication.
+pub fn generate_nbs_1955_charts(dataset: &Dataset, colors: &[Srgb]) -> std::io::Result<()> {
+    return generate_nbs_1955_charts_with_progress(dataset, colors, &mut no_progress);
+}
+
+/// Like [`generate_nbs_1955_charts`], reporting progress through the render
+/// stage, one unit per hue leaf.
+pub fn generate_nbs_1955_charts_with_progress(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    progress: Progress,
+) -> std::io::Result<()> {
+    let font_data = load_serif_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+
+    let total = dataset.hues().len();
+    report(progress, Stage::Render, 0, total);
+    for h in 0..total {
+        let plate = dataset.plate(h).unwrap();
+        let path = chart_path("doc/nbs-1955", &plate);
+        draw_chart_with_style(
+            dataset,
+            &plate,
+            colors,
+            &measure,
+            ChartStyle::Nbs1955,
+            &ChartOverlays::default(),
+            &path,
+        )?;
+        report(progress, Stage::Render, h + 1, total);
+    }
+    return Ok(());
+}
+
+/// Render `doc/<deficiency>/page*.png` for every hue leaf, like
+/// `doc/protanopia/`, with the regions filled as a viewer with `deficiency`
+/// sees them, to show which names become hard to tell apart.
+pub fn generate_cvd_charts(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    deficiency: Deficiency,
+) -> std::io::Result<()> {
+    return generate_cvd_charts_with_progress(dataset, colors, deficiency, &mut no_progress);
+}
+
+/// Like [`generate_cvd_charts`], reporting progress through the render
+/// stage, one unit per hue leaf.
+pub fn generate_cvd_charts_with_progress(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    deficiency: Deficiency,
+    progress: Progress,
+) -> std::io::Result<()> {
+    let dir = format!("doc/{}", deficiency.name());
+    let colors = simulate_all(colors, deficiency);
+
+    let font_data = load_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+
+    let total = dataset.hues().len();
+    report(progress, Stage::Render, 0, total);
+    for h in 0..total {
+        let plate = dataset.plate(h).unwrap();
+        draw_chart(
+            dataset,
+            &plate,
+            &colors,
+            &measure,
+            &chart_path(&dir, &plate),
+        )?;
+        report(progress, Stage::Render, h + 1, total);
+    }
+    return Ok(());
+}
+
+/// Render `doc/level<level>/page*.png` for every hue leaf, like
+/// `doc/level2/`, with the regions of the names of `level` 1 or 2, merged
+/// from the level 3 regions under them.
+///
+/// `colors` are the representative colors of the names of `level`, indexed
+/// by `id - 1`.
+pub fn generate_level_charts(
+    dataset: &Dataset,
+    level: u32,
+    colors: &[Srgb],
+) -> std::io::Result<()> {
+    return generate_level_charts_with_progress(dataset, level, colors, &mut no_progress);
+}
+
+/// Like [`generate_level_charts`], reporting progress through the render
+/// stage, one unit per hue leaf.
+pub fn generate_level_charts_with_progress(
+    dataset: &Dataset,
+    level: u32,
+    colors: &[Srgb],
+    progress: Progress,
+) -> std::io::Result<()> {
+    let dir = format!("doc/level{}", level);
+
+    let font_data = load_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+
+    let total = dataset.hues().len();
+    report(progress, Stage::Render, 0, total);
+    for h in 0..total {
+        let plate = dataset.plate_at_level(h, level).unwrap();
+        draw_chart(dataset, &plate, colors, &measure, &chart_path(&dir, &plate))?;
+        report(progress, Stage::Render, h + 1, total);
+    }
+    return Ok(());
+}
+
+/// Render `doc/page*.png` for the hue leaves whose blocks, names or colors
+/// changed since the last run, or whose file is missing, for a fast preview
+/// while editing; returns the number of charts rendered.
+///
+/// Changing how the charts are drawn, like their size or font, renders all
+/// of them again.
+pub fn generate_changed_charts(dataset: &Dataset, colors: &[Srgb]) -> std::io::Result<usize> {
+    return generate_changed_charts_with_progress(dataset, colors, &mut no_progress);
+}
+
+/// Like [`generate_changed_charts`], reporting progress through the render
+/// stage, one unit per changed hue leaf.
+pub fn generate_changed_charts_with_progress(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    progress: Progress,
+) -> std::io::Result<usize> {
+    return render_changed_charts(dataset, colors, ChartStyle::Modern, progress);
+}
+
+/// Like [`generate_changed_charts`], rendering `doc/nbs-1955/page*.png` in
+/// the style of the 1955 publication.
+pub fn generate_changed_nbs_1955_charts(
+    dataset: &Dataset,
+    colors: &[Srgb],
+) -> std::io::Result<usize> {
+    return render_changed_charts(dataset, colors, ChartStyle::Nbs1955, &mut no_progress);
+}
+
+/// Describe the settings charts are drawn with in `style`, for hashing: the
+/// version of this crate, the plot configuration, and the label font.
+fn chart_parameters(style: ChartStyle, font_data: &[u8]) -> String {
+    return format!(
+        "{} {:?} {:?} {:016x}",
+        env!("CARGO_PKG_VERSION"),
+        style,
+        config(),
+        fnv1a(font_data)
+    );
+}
+
+fn render_changed_charts(
+    dataset: &Dataset,
+    colors: &[Srgb],
+    style: ChartStyle,
+    progress: Progress,
+) -> std::io::Result<usize> {
+    let (dir, font_data) = match style {
+        ChartStyle::Modern => ("doc", load_font_data()),
+        ChartStyle::Nbs1955 => ("doc/nbs-1955", load_serif_font_data()),
+    };
+    std::fs::create_dir_all(dir)?;
+    let parameters = chart_parameters(style, &font_data);
+
+    let mut hashes = read_hashes(HASHES_PATH);
+    let changed = (0..dataset.hues().len())
+        .map(|h| dataset.plate(h).unwrap())
+        .map(|plate| (plate_hash_with(dataset, &plate, colors, &parameters), plate))
+        .filter(|(hash, plate)| {
+            let path = chart_path(dir, plate);
+            return hashes.get(&path) != Some(hash) || !Path::new(&path).exists();
+        })
+        .collect::<Vec<_>>();
+
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+
+    let total = changed.len();
+    report(progress, Stage::Render, 0, total);
+    for (i, (hash, plate)) in changed.iter().enumerate() {
+        let path = chart_path(dir, plate);
+        draw_chart_with_style(
+            dataset,
+            plate,
+            colors,
+            &measure,
+            style,
+            &ChartOverlays::default(),
+            &path,
+        )?;
+        hashes.insert(path, *hash);
+        report(progress, Stage::Render, i + 1, total);
+    }
+
+    write_hashes(HASHES_PATH, &hashes);
+    return Ok(total);
+}
+
+/// Render `doc/book/page*.png` for every page of a Munsell book.
+pub fn generate_book(dataset: &Dataset) -> std::io::Result<()> {
+    return generate_book_with_progress(dataset, &mut no_progress);
+}
+
+/// Like [`generate_book`], reporting progress through the render stage, one
+/// unit per page.
+pub fn generate_book_with_progress(dataset: &Dataset, progress: Progress) -> std::io::Result<()> {
+    let page_hues = book_page_hues();
+    let total = page_hues.len();
+    report(progress, Stage::Render, 0, total);
+    for (page, hue_name) in page_hues.iter().enumerate() {
+        let path = format!("doc/book/page{:02}_{}.png", page + 1, hue_name);
+
+        draw_book_page(dataset, hue_name, &path)?;
+        report(progress, Stage::Render, page + 1, total);
+    }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+    use ttf_word_wrap::TTFParserMeasure;
+
+    use crate::colors::get_mean_colors;
+    use crate::plot::load_font_data;
+    use crate::plot::png::{draw_chart, hatch_lines};
+    use crate::Dataset;
+
+    #[test]
+    fn chart_png() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let font_data = load_font_data();
+        let font_face = ttf_parser::Face::from_slice(&font_data, 0).unwrap();
+        let measure = TTFParserMeasure::new(&font_face);
+
+        // into a directory that doesn't exist yet
+        let dir = std::env::temp_dir().join("iscc-nbs-chart-test");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("charts").join("page.png");
+        let draw = |path: &std::path::Path| {
+            return draw_chart(
+                &dataset,
+                &dataset.plate(0).unwrap(),
+                &colors,
+                &measure,
+                path.to_str().unwrap(),
+            );
+        };
+        draw(&path).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        assert_eq!(&data[1..4], b"PNG");
+
+        // a file in the way of the directory
+        assert!(draw(&path.join("page.png")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn hatching() {
+        let square = [(0, 0), (10, 0), (10, 10), (0, 10), (0, 0)];
+
+        let lines = hatch_lines(&square, 0.0, 2.5);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], vec![(0, 3), (10, 3)]);
+
+        // a triangle is cut into shorter and shorter lines
+        let triangle = [(0, 0), (10, 0), (0, 10), (0, 0)];
+        let lengths = hatch_lines(&triangle, 0.0, 2.0)
+            .iter()
+            .map(|x| x[1].0 - x[0].0)
+            .collect::<Vec<i32>>();
+        assert_eq!(lengths, vec![10, 8, 6, 4, 2]);
+    }
+}
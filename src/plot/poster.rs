@@ -0,0 +1,356 @@
+//! A poster of every level 3 color: a grid of swatches in their
+//! representative colors, labelled with their ids, abbreviations and names,
+//! under headings of their level 1 names.
+//!
+//! The legend that accompanies the charts lists the same colors more
+//! compactly, one line each, for reading the ids of regions whose labels are
+//! cramped on the charts.
+//!
+//! Both layouts are computed here once, in pixels, and drawn by the PNG and
+//! SVG renderers.
+
+use palette::Srgb;
+
+use crate::naming::ColorNamingSystem;
+use crate::plot::label_text_color;
+use crate::plot::wheel::Anchor;
+
+const COLUMNS: usize = 10;
+const MARGIN: f64 = 40.0;
+const SWATCH_WIDTH: f64 = 180.0;
+const SWATCH_HEIGHT: f64 = 110.0;
+const GAP: f64 = 10.0;
+const HEADING_HEIGHT: f64 = 50.0;
+
+pub(crate) const TITLE_FONT_SIZE: f64 = 28.0;
+pub(crate) const HEADING_FONT_SIZE: f64 = 20.0;
+pub(crate) const ID_FONT_SIZE: f64 = 18.0;
+pub(crate) const LABEL_FONT_SIZE: f64 = 12.0;
+
+const LEGEND_COLUMNS: usize = 4;
+const LEGEND_COLUMN_WIDTH: f64 = 380.0;
+const LEGEND_ROW_HEIGHT: f64 = 20.0;
+const LEGEND_SWATCH_WIDTH: f64 = 28.0;
+const LEGEND_SWATCH_HEIGHT: f64 = 14.0;
+/// Where the id, the abbreviation and the name of a line of the legend are,
+/// from the left of its column; the id is aligned on its right.
+const LEGEND_ID_X: f64 = 64.0;
+const LEGEND_ABBR_X: f64 = 74.0;
+const LEGEND_NAME_X: f64 = 150.0;
+
+/// The longest line of a name, in characters, before it is broken.
+const NAME_LINE_LENGTH: usize = 24;
+
+pub(crate) struct PosterSwatch {
+    /// The top left corner.
+    pub at: (f64, f64),
+    pub size: (f64, f64),
+    pub fill: Srgb,
+}
+
+/// A single line of text, centered vertically on `at`.
+pub(crate) struct PosterText {
+    pub at: (f64, f64),
+    pub text: String,
+    pub anchor: Anchor,
+    pub size: f64,
+    pub bold: bool,
+    pub color: Srgb<u8>,
+}
+
+pub(crate) struct PosterDrawing {
+    pub width: f64,
+    pub height: f64,
+    pub swatches: Vec<PosterSwatch>,
+    pub texts: Vec<PosterText>,
+}
+
+/// Break a name into lines of at most `NAME_LINE_LENGTH` characters, at
+/// spaces.
+fn name_lines(name: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in name.split(' ') {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= NAME_LINE_LENGTH => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    return lines;
+}
+
+/// The ids of the finest names under each level 1 name, in order of id.
+fn level1_groups<S: ColorNamingSystem + ?Sized>(dataset: &S) -> Vec<(u32, Vec<u32>)> {
+    let mut level1_ids = dataset
+        .names_at(1)
+        .into_iter()
+        .flat_map(|names| names.keys().copied())
+        .collect::<Vec<u32>>();
+    level1_ids.sort();
+
+    let mut color_ids = dataset.finest_names().keys().copied().collect::<Vec<u32>>();
+    color_ids.sort();
+
+    return level1_ids
+        .into_iter()
+        .map(|level1_id| {
+            let members = color_ids
+                .iter()
+                .copied()
+                .filter(|id| dataset.ancestor_id(dataset.levels(), *id, 1) == Some(level1_id))
+                .collect();
+            (level1_id, members)
+        })
+        .collect();
+}
+
+/// Lay out the poster: a title, then for every level 1 name a heading and
+/// the swatches of its level 3 colors, filled with their representative
+/// colors (`colors`, indexed by `color_id - 1`), `COLUMNS` to a row.
+pub(crate) fn poster_drawing<S: ColorNamingSystem + ?Sized>(
+    dataset: &S,
+    colors: &[Srgb],
+) -> PosterDrawing {
+    let black = Srgb::new(0, 0, 0);
+    let cell_width = SWATCH_WIDTH + GAP;
+    let cell_height = SWATCH_HEIGHT + GAP;
+
+    let mut drawing = PosterDrawing {
+        width: 2.0 * MARGIN + (COLUMNS as f64) * cell_width - GAP,
+        height: 0.0,
+        swatches: Vec::new(),
+        texts: Vec::new(),
+    };
+
+    drawing.texts.push(PosterText {
+        at: (MARGIN, MARGIN),
+        text: format!(
+            "ISCC-NBS color names, iscc-nbs {}",
+            dataset.metadata().version
+        ),
+        anchor: Anchor::Start,
+        size: TITLE_FONT_SIZE,
+        bold: true,
+        color: black,
+    });
+    let mut y = MARGIN + TITLE_FONT_SIZE;
+
+    for (level1_id, members) in level1_groups(dataset) {
+        if members.is_empty() {
+            continue;
+        }
+        let level1 = &dataset.names_at(1).unwrap()[&level1_id];
+        drawing.texts.push(PosterText {
+            at: (MARGIN, y + HEADING_HEIGHT / 2.0),
+            text: format!("{} ({})", level1.name, level1.abbr),
+            anchor: Anchor::Start,
+            size: HEADING_FONT_SIZE,
+            bold: true,
+            color: black,
+        });
+        y += HEADING_HEIGHT;
+
+        for (i, color_id) in members.iter().enumerate() {
+            let x = MARGIN + ((i % COLUMNS) as f64) * cell_width;
+            let top = y + ((i / COLUMNS) as f64) * cell_height;
+            let fill = colors[(color_id - 1) as usize];
+            let color: Srgb<u8> = label_text_color(fill).parse().unwrap();
+            let name = &dataset.finest_names()[color_id];
+            let center = x + SWATCH_WIDTH / 2.0;
+
+            drawing.swatches.push(PosterSwatch {
+                at: (x, top),
+                size: (SWATCH_WIDTH, SWATCH_HEIGHT),
+                fill,
+            });
+
+            let mut line_y = top + 8.0 + ID_FONT_SIZE / 2.0;
+            drawing.texts.push(PosterText {
+                at: (center, line_y),
+                text: color_id.to_string(),
+                anchor: Anchor::Middle,
+                size: ID_FONT_SIZE,
+                bold: true,
+                color,
+            });
+            line_y += ID_FONT_SIZE;
+            drawing.texts.push(PosterText {
+                at: (center, line_y),
+                text: name.abbr.clone(),
+                anchor: Anchor::Middle,
+                size: LABEL_FONT_SIZE,
+                bold: false,
+                color,
+            });
+            line_y += LABEL_FONT_SIZE * 1.6;
+            for line in name_lines(&name.name) {
+                drawing.texts.push(PosterText {
+                    at: (center, line_y),
+                    text: line,
+                    anchor: Anchor::Middle,
+                    size: LABEL_FONT_SIZE,
+                    bold: false,
+                    color,
+                });
+                line_y += LABEL_FONT_SIZE * 1.2;
+            }
+        }
+
+        y += (members.len().div_ceil(COLUMNS) as f64) * cell_height;
+    }
+
+    drawing.height = y + MARGIN - GAP;
+    return drawing;
+}
+
+/// A line of the legend.
+enum LegendLine {
+    Heading(u32),
+    Color(u32),
+}
+
+/// Lay out the legend: a title, then for every level 1 name a heading and a
+/// line for each of its level 3 colors, with a swatch filled with its
+/// representative color (`colors`, indexed by `color_id - 1`), its id,
+/// abbreviation and name. The lines run down `LEGEND_COLUMNS` columns of
+/// about the same length, and a heading never ends a column.
+pub(crate) fn legend_drawing<S: ColorNamingSystem + ?Sized>(
+    dataset: &S,
+    colors: &[Srgb],
+) -> PosterDrawing {
+    let black = Srgb::new(0, 0, 0);
+
+    let mut lines = Vec::new();
+    for (level1_id, members) in level1_groups(dataset) {
+        if members.is_empty() {
+            continue;
+        }
+        lines.push(LegendLine::Heading(level1_id));
+        lines.extend(members.into_iter().map(LegendLine::Color));
+    }
+    let rows = lines.len().div_ceil(LEGEND_COLUMNS);
+
+    let mut drawing = PosterDrawing {
+        width: 0.0,
+        height: 0.0,
+        swatches: Vec::new(),
+        texts: Vec::new(),
+    };
+    drawing.texts.push(PosterText {
+        at: (MARGIN, MARGIN),
+        text: format!(
+            "ISCC-NBS color names, iscc-nbs {}",
+            dataset.metadata().version
+        ),
+        anchor: Anchor::Start,
+        size: TITLE_FONT_SIZE,
+        bold: true,
+        color: black,
+    });
+    let top = MARGIN + TITLE_FONT_SIZE;
+
+    let (mut column, mut row) = (0, 0);
+    let mut most_rows = 0;
+    for line in lines.iter() {
+        let last_row = matches!(line, LegendLine::Heading(_)) && row + 1 >= rows;
+        if row >= rows || (last_row && row > 0) {
+            column += 1;
+            row = 0;
+        }
+        let left = MARGIN + (column as f64) * LEGEND_COLUMN_WIDTH;
+        let y = top + (row as f64 + 0.5) * LEGEND_ROW_HEIGHT;
+
+        match *line {
+            LegendLine::Heading(level1_id) => {
+                let level1 = &dataset.names_at(1).unwrap()[&level1_id];
+                drawing.texts.push(PosterText {
+                    at: (left, y),
+                    text: format!("{} ({})", level1.name, level1.abbr),
+                    anchor: Anchor::Start,
+                    size: LABEL_FONT_SIZE,
+                    bold: true,
+                    color: black,
+                });
+            }
+            LegendLine::Color(color_id) => {
+                let name = &dataset.finest_names()[&color_id];
+                drawing.swatches.push(PosterSwatch {
+                    at: (left, y - LEGEND_SWATCH_HEIGHT / 2.0),
+                    size: (LEGEND_SWATCH_WIDTH, LEGEND_SWATCH_HEIGHT),
+                    fill: colors[(color_id - 1) as usize],
+                });
+                let columns = [
+                    (LEGEND_ID_X, color_id.to_string(), Anchor::End),
+                    (LEGEND_ABBR_X, name.abbr.clone(), Anchor::Start),
+                    (LEGEND_NAME_X, name.name.clone(), Anchor::Start),
+                ];
+                for (x, text, anchor) in columns {
+                    drawing.texts.push(PosterText {
+                        at: (left + x, y),
+                        text,
+                        anchor,
+                        size: LABEL_FONT_SIZE,
+                        bold: false,
+                        color: black,
+                    });
+                }
+            }
+        }
+
+        row += 1;
+        most_rows = most_rows.max(row);
+    }
+
+    drawing.width = 2.0 * MARGIN + ((column + 1) as f64) * LEGEND_COLUMN_WIDTH;
+    drawing.height = top + (most_rows as f64) * LEGEND_ROW_HEIGHT + MARGIN;
+    return drawing;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::plot::poster::{legend_drawing, level1_groups, name_lines, poster_drawing};
+    use crate::Dataset;
+
+    #[test]
+    fn poster_layout() {
+        let dataset = Dataset::bundled();
+        let groups = level1_groups(&dataset);
+        assert_eq!(groups.len(), 13);
+        assert_eq!(groups.iter().map(|x| x.1.len()).sum::<usize>(), 267);
+        assert_eq!(groups[0].1[0], 1);
+
+        let colors = get_mean_colors(&dataset);
+        let drawing = poster_drawing(&dataset, &colors);
+        assert_eq!(drawing.swatches.len(), 267);
+        assert!(drawing.texts.iter().any(|x| x.text == "Pink (Pk)"));
+        assert!(drawing.texts.iter().any(|x| x.text == "viv.Pk"));
+        assert!(drawing
+            .swatches
+            .iter()
+            .all(|x| x.at.1 + x.size.1 < drawing.height));
+
+        let legend = legend_drawing(&dataset, &colors);
+        assert_eq!(legend.swatches.len(), 267);
+        assert!(legend.texts.iter().any(|x| x.text == "Pink (Pk)"));
+        assert!(legend.texts.iter().any(|x| x.text == "Vivid pink"));
+        assert!(legend
+            .swatches
+            .iter()
+            .all(|x| x.at.0 + x.size.0 < legend.width && x.at.1 + x.size.1 < legend.height));
+        // no column ends with a heading
+        for heading in legend.texts.iter().filter(|x| x.bold).skip(1) {
+            assert!(legend
+                .swatches
+                .iter()
+                .any(|x| x.at.0 == heading.at.0 && x.at.1 > heading.at.1));
+        }
+
+        assert_eq!(
+            name_lines("Brilliant greenish yellow"),
+            vec!["Brilliant greenish", "yellow"]
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! Horizontal slices of the color solid: at a fixed Munsell value, the hue
+//! goes around the circle and the chroma out from the center, with every
+//! block cut by the slice filled in its representative color.
+//!
+//! Unlike the charts, which cut the solid along one hue leaf at a time,
+//! slices show the blocks that wrap around the hue circle, and the neutral
+//! core, in one piece. They are laid out like the hue circle of
+//! [`crate::plot::wheel`], and drawn by the same renderers.
+
+use palette::Srgb;
+
+use crate::dataset::Dataset;
+use crate::plot::wheel::{
+    angle, outside_text, polar, ring_sector, Anchor, WheelDrawing, WheelLine, WheelPolygon,
+    WheelText, CENTER, HUE_FONT_SIZE, NAME_FONT_SIZE,
+};
+
+/// The chroma at the edge of a slice; blocks reaching to `INF` are cut off
+/// here.
+const MAX_SLICE_CHROMA: f64 = 16.0;
+
+/// The radius of the edge of a slice, the hue labels, and the distance of
+/// the chroma scale below it, in pixels.
+const SLICE_RADIUS: f64 = 380.0;
+const HUE_LABEL: f64 = 402.0;
+const SCALE_OFFSET: f64 = 40.0;
+
+/// The value spans of a dataset, as (begin, end) pairs, with the last one
+/// ending at 10 rather than at `INF`.
+pub fn slice_values(dataset: &Dataset) -> Vec<(f32, f32)> {
+    let values = dataset
+        .values()
+        .iter()
+        .map(|x| x.parse::<f32>().unwrap().min(10.0))
+        .collect::<Vec<f32>>();
+    return values.windows(2).map(|x| (x[0], x[1])).collect();
+}
+
+fn radius(chroma: f64) -> f64 {
+    return SLICE_RADIUS * chroma.min(MAX_SLICE_CHROMA) / MAX_SLICE_CHROMA;
+}
+
+/// Lay out the slice of the solid at `value`: every block whose value span
+/// contains it, as a ring sector across its hues and chromas filled with its
+/// representative color (`colors`, indexed by `color_id - 1`), with the hue
+/// boundaries around the outside and a chroma scale below.
+pub(crate) fn slice_drawing(dataset: &Dataset, colors: &[Srgb], value: f32) -> WheelDrawing {
+    let (hues, chromas, values) = (dataset.hues(), dataset.chromas(), dataset.values());
+    let parse = |x: &[String], i: usize| x[i].parse::<f64>().unwrap();
+
+    let mut drawing = WheelDrawing {
+        polygons: Vec::new(),
+        lines: Vec::new(),
+        texts: Vec::new(),
+    };
+
+    for block in dataset.blocks() {
+        let value = value as f64;
+        if value < parse(values, block.values.start) || value >= parse(values, block.values.end) {
+            continue;
+        }
+        let begin = angle(&hues[block.hues.start], 0.0);
+        let mut end = angle(&hues[block.hues.end], begin);
+        // a block from a hue back to itself goes all the way around
+        if end == begin {
+            end = begin + 360.0;
+        }
+        drawing.polygons.push(WheelPolygon {
+            points: ring_sector(
+                radius(parse(chromas, block.chromas.start)),
+                radius(parse(chromas, block.chromas.end)),
+                begin,
+                end,
+            ),
+            fill: colors[(block.color_id - 1) as usize],
+        });
+    }
+
+    for hue in hues.iter() {
+        let degrees = angle(hue, 0.0);
+        drawing.lines.push(WheelLine {
+            from: polar(SLICE_RADIUS, degrees),
+            to: polar(SLICE_RADIUS + 8.0, degrees),
+            width: 1.0,
+        });
+        drawing
+            .texts
+            .push(outside_text(hue, HUE_LABEL, degrees, HUE_FONT_SIZE));
+    }
+
+    // the chroma scale runs right from below the center, under the slice
+    let y = CENTER + SLICE_RADIUS + SCALE_OFFSET;
+    drawing.lines.push(WheelLine {
+        from: (CENTER, y),
+        to: (CENTER + SLICE_RADIUS, y),
+        width: 1.0,
+    });
+    for chroma in (0..=MAX_SLICE_CHROMA as u32).step_by(2) {
+        let x = CENTER + radius(chroma as f64);
+        drawing.lines.push(WheelLine {
+            from: (x, y - 4.0),
+            to: (x, y + 4.0),
+            width: 1.0,
+        });
+        drawing.texts.push(WheelText {
+            at: (x, y + 14.0),
+            lines: vec![chroma.to_string()],
+            anchor: Anchor::Middle,
+            size: HUE_FONT_SIZE,
+        });
+    }
+    drawing.texts.push(WheelText {
+        at: (CENTER - 10.0, y),
+        lines: vec!["Chroma".to_string()],
+        anchor: Anchor::End,
+        size: HUE_FONT_SIZE,
+    });
+
+    drawing.texts.push(WheelText {
+        at: (20.0, 30.0),
+        lines: vec![format!("Value {}", value)],
+        anchor: Anchor::Start,
+        size: NAME_FONT_SIZE,
+    });
+
+    return drawing;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::plot::slice::{slice_drawing, slice_values};
+    use crate::Dataset;
+
+    #[test]
+    fn value_slices() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let spans = slice_values(&dataset);
+        assert_eq!(spans.len(), dataset.values().len() - 1);
+        assert_eq!(spans[0], (0.0, 1.5));
+        assert_eq!(spans.last(), Some(&(8.5, 10.0)));
+
+        // one sector per block the slice cuts, and a label per hue boundary
+        let drawing = slice_drawing(&dataset, &colors, 7.0);
+        let cut = dataset
+            .blocks()
+            .iter()
+            .filter(|x| {
+                let v = |i: usize| dataset.values()[i].parse::<f32>().unwrap();
+                v(x.values.start) <= 7.0 && 7.0 < v(x.values.end)
+            })
+            .count();
+        assert_eq!(drawing.polygons.len(), cut);
+        assert!(drawing
+            .texts
+            .iter()
+            .any(|x| x.lines == vec!["1R".to_string()]));
+
+        // the neutral core goes all the way around the center
+        let gray = colors[264 - 1];
+        let core = drawing
+            .polygons
+            .iter()
+            .filter(|x| x.fill == gray)
+            .flat_map(|x| x.points.iter())
+            .collect::<Vec<_>>();
+        assert!(core.iter().all(|p| (p.0 - 500.0).hypot(p.1 - 500.0) < 17.0));
+        assert!(core.iter().any(|p| p.0 < 490.0) && core.iter().any(|p| p.0 > 510.0));
+        assert!(core.iter().any(|p| p.1 < 490.0) && core.iter().any(|p| p.1 > 510.0));
+    }
+}
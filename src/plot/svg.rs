@@ -0,0 +1,685 @@
+//! The color name charts and the hue circle as SVG, drawn directly from the
+//! plate geometry without going through gnuplot.
+
+use std::fmt::Write as _;
+
+use geo_types::Polygon;
+use palette::Srgb;
+use ttf_word_wrap::TTFParserMeasure;
+
+use crate::dataset::Dataset;
+use crate::naming::ColorNamingSystem;
+use crate::plate::Plate;
+use crate::plot::config::config;
+use crate::plot::labels::place_labels;
+use crate::plot::load_font_data;
+use crate::plot::poster::{legend_drawing, poster_drawing, PosterDrawing};
+use crate::plot::slice::{slice_drawing, slice_values};
+use crate::plot::wheel::{wheel_drawing, Anchor, WheelDrawing, SIZE};
+use crate::progress::{no_progress, report, Progress, Stage};
+use crate::rgbspace::rgb_space;
+
+/// The size of the charts in user units; [`PlotConfig`] sets the size they
+/// are shown at.
+///
+/// [`PlotConfig`]: crate::plot::config::PlotConfig
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 800.0;
+
+/// The edges of the plotting area, in pixels.
+const LEFT: f64 = 60.0;
+const RIGHT: f64 = 580.0;
+const TOP: f64 = 40.0;
+const BOTTOM: f64 = 740.0;
+
+/// Font size in pixels for tick marks.
+const TICK_FONT_SIZE: f64 = 11.0;
+
+/// Maps chroma/value coordinates of a plate to pixels.
+struct View {
+    chroma_range: (f64, f64),
+    value_range: (f64, f64),
+}
+
+impl View {
+    fn new(plate: &Plate) -> Self {
+        return View {
+            chroma_range: plate.axes.chroma_range,
+            value_range: plate.axes.value_range,
+        };
+    }
+
+    fn x(&self, chroma: f64) -> f64 {
+        let (min, max) = self.chroma_range;
+        return LEFT + (chroma - min) / (max - min) * (RIGHT - LEFT);
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        let (min, max) = self.value_range;
+        return BOTTOM - (value - min) / (max - min) * (BOTTOM - TOP);
+    }
+}
+
+fn escape(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+fn polygon_points(view: &View, polygon: &Polygon<f64>) -> String {
+    return polygon
+        .exterior()
+        .points()
+        .map(|v| format!("{:.2},{:.2}", view.x(v.x()), view.y(v.y())))
+        .collect::<Vec<String>>()
+        .join(" ");
+}
+
+fn write_axes(out: &mut String, view: &View, plate: &Plate) {
+    let mut chroma_ticks: Vec<(f64, f64)> = (0..=16)
+        .step_by(2)
+        .map(|x| (x as f64, TICK_FONT_SIZE))
+        .collect();
+    chroma_ticks.push((1.0, TICK_FONT_SIZE));
+    for tick in plate.axes.fine_chroma_ticks.iter() {
+        chroma_ticks.push((*tick, TICK_FONT_SIZE * 0.75));
+    }
+
+    writeln!(
+        out,
+        "<g stroke=\"#000000\" stroke-opacity=\"0.2\" stroke-dasharray=\"2,3\">"
+    )
+    .unwrap();
+    for (tick, _) in chroma_ticks.iter() {
+        let x = view.x(*tick);
+        writeln!(
+            out,
+            "<line x1=\"{:.2}\" y1=\"{}\" x2=\"{:.2}\" y2=\"{}\" />",
+            x, TOP, x, BOTTOM
+        )
+        .unwrap();
+    }
+    for tick in 0..=10 {
+        let y = view.y(tick as f64);
+        writeln!(
+            out,
+            "<line x1=\"{}\" y1=\"{:.2}\" x2=\"{}\" y2=\"{:.2}\" />",
+            LEFT, y, RIGHT, y
+        )
+        .unwrap();
+    }
+    writeln!(out, "</g>").unwrap();
+
+    writeln!(
+        out,
+        "<path d=\"M{},{} V{} H{}\" fill=\"none\" stroke=\"#000000\" />",
+        LEFT, TOP, BOTTOM, RIGHT
+    )
+    .unwrap();
+
+    writeln!(out, "<g text-anchor=\"middle\">").unwrap();
+    for (tick, size) in chroma_ticks.iter() {
+        let x = view.x(*tick);
+        writeln!(
+            out,
+            "<line x1=\"{:.2}\" y1=\"{}\" x2=\"{:.2}\" y2=\"{}\" stroke=\"#000000\" />",
+            x,
+            BOTTOM,
+            x,
+            BOTTOM + 6.0
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "<text x=\"{:.2}\" y=\"{}\" font-size=\"{}\">{}</text>",
+            x,
+            BOTTOM + 8.0 + size,
+            size,
+            tick
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "<text x=\"{}\" y=\"{}\" font-size=\"{}\">Munsell Chroma</text>",
+        (LEFT + RIGHT) / 2.0,
+        HEIGHT - 20.0,
+        TICK_FONT_SIZE
+    )
+    .unwrap();
+    writeln!(out, "</g>").unwrap();
+
+    writeln!(out, "<g text-anchor=\"end\">").unwrap();
+    for tick in 0..=10 {
+        let y = view.y(tick as f64);
+        writeln!(
+            out,
+            "<line x1=\"{}\" y1=\"{:.2}\" x2=\"{}\" y2=\"{:.2}\" stroke=\"#000000\" />",
+            LEFT - 6.0,
+            y,
+            LEFT,
+            y
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "<text x=\"{}\" y=\"{:.2}\" font-size=\"{}\" dominant-baseline=\"middle\">{}</text>",
+            LEFT - 9.0,
+            y,
+            TICK_FONT_SIZE,
+            tick
+        )
+        .unwrap();
+    }
+    writeln!(out, "</g>").unwrap();
+    writeln!(
+        out,
+        "<text transform=\"translate(18,{}) rotate(-90)\" text-anchor=\"middle\" font-size=\"{}\">Munsell Value</text>",
+        (TOP + BOTTOM) / 2.0,
+        TICK_FONT_SIZE
+    )
+    .unwrap();
+}
+
+/// Render one plate of the color name charts as an SVG document.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn plate_to_svg(
+    dataset: &Dataset,
+    plate: &Plate,
+    colors: &[Srgb],
+    measure: &TTFParserMeasure,
+) -> String {
+    let size = (config().width as f64, config().height as f64);
+    return plate_svg(
+        dataset,
+        plate,
+        colors,
+        measure,
+        (0.0, 0.0, WIDTH, HEIGHT),
+        size,
+        None,
+    );
+}
+
+/// Render the part of a plate around the region of one color, outlined, as
+/// an SVG document at the scale of the whole chart, or `None` if the color
+/// is not on the plate.
+pub fn plate_excerpt_to_svg(
+    dataset: &Dataset,
+    plate: &Plate,
+    colors: &[Srgb],
+    measure: &TTFParserMeasure,
+    color_id: u32,
+) -> Option<String> {
+    const MARGIN: f64 = 30.0;
+
+    let region = plate.regions.iter().find(|x| x.color_id == color_id)?;
+    let view = View::new(plate);
+    // regions may reach past the visible range, out to the INF cutoffs
+    let left = view.x(region.bounds.min().x).max(LEFT) - MARGIN;
+    let right = view.x(region.bounds.max().x).min(RIGHT) + MARGIN;
+    let top = view.y(region.bounds.max().y).max(TOP) - MARGIN;
+    let bottom = view.y(region.bounds.min().y).min(BOTTOM) + MARGIN;
+    let (left, top) = (left.max(0.0).floor(), top.max(0.0).floor());
+    let (width, height) = (
+        right.min(WIDTH).ceil() - left,
+        bottom.min(HEIGHT).ceil() - top,
+    );
+
+    return Some(plate_svg(
+        dataset,
+        plate,
+        colors,
+        measure,
+        (left, top, width, height),
+        (width, height),
+        Some(color_id),
+    ));
+}
+
+/// Render a plate, showing the part of it in `view_box` at `size`, and
+/// outlining the region of the color `highlight`.
+fn plate_svg(
+    dataset: &Dataset,
+    plate: &Plate,
+    colors: &[Srgb],
+    measure: &TTFParserMeasure,
+    view_box: (f64, f64, f64, f64),
+    size: (f64, f64),
+    highlight: Option<u32>,
+) -> String {
+    let view = View::new(plate);
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\" font-family=\"{}\">",
+        size.0,
+        size.1,
+        view_box.0,
+        view_box.1,
+        view_box.2,
+        view_box.3,
+        config().font_name
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\" />"
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\" font-size=\"{}\">{}-{}</text>",
+        RIGHT,
+        TOP - 12.0,
+        TICK_FONT_SIZE,
+        escape(&plate.hue_begin),
+        escape(&plate.hue_end)
+    )
+    .unwrap();
+
+    // regions may extend past the visible range, out to the INF cutoffs; the
+    // id is unique per plate, so that several plates can share one page
+    writeln!(
+        out,
+        "<clipPath id=\"plot-area-{}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" /></clipPath>",
+        plate.hue_index,
+        LEFT,
+        TOP,
+        RIGHT - LEFT,
+        BOTTOM - TOP
+    )
+    .unwrap();
+    writeln!(out, "<g clip-path=\"url(#plot-area-{})\">", plate.hue_index).unwrap();
+
+    for region in plate.regions.iter() {
+        let id = region.color_id;
+        let color = colors[(id - 1) as usize];
+
+        writeln!(
+            out,
+            "<polygon data-color=\"{}\" points=\"{}\" fill=\"{}\" stroke=\"#000000\" />",
+            id,
+            polygon_points(&view, &region.polygon),
+            rgb_space().css_color(color)
+        )
+        .unwrap();
+    }
+    // over the other regions, so that none of their edges hide the outline
+    if let Some(region) = plate.regions.iter().find(|x| Some(x.color_id) == highlight) {
+        writeln!(
+            out,
+            "<polygon points=\"{}\" fill=\"none\" stroke=\"#000000\" stroke-width=\"4\" />",
+            polygon_points(&view, &region.polygon)
+        )
+        .unwrap();
+    }
+
+    for placement in place_labels(dataset, plate, colors, measure) {
+        let x = view.x(placement.position.0);
+        let y = view.y(placement.position.1);
+        let line_height = placement.font_size * 1.15;
+        let first_dy = -((placement.lines.len() - 1) as f64) * line_height / 2.0;
+        let rotate = if placement.rotation != 0.0 {
+            format!(
+                " transform=\"rotate({} {:.2} {:.2})\"",
+                -placement.rotation, x, y
+            )
+        } else {
+            String::new()
+        };
+
+        write!(
+            out,
+            "<text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"middle\" dominant-baseline=\"middle\" font-size=\"{}\" fill=\"#{:x}\"{}>",
+            x,
+            y,
+            placement.font_size,
+            placement.text_color,
+            rotate
+        )
+        .unwrap();
+        for (i, line) in placement.lines.iter().enumerate() {
+            let dy = if i == 0 { first_dy } else { line_height };
+            write!(out, "<tspan x=\"{:.2}\" dy=\"{:.2}\">", x, dy).unwrap();
+            // the id is in boldface
+            match line.split_once(':').filter(|_| i == 0) {
+                Some((prefix, suffix)) => write!(
+                    out,
+                    "<tspan font-weight=\"bold\">{}</tspan>:{}",
+                    escape(prefix),
+                    escape(suffix)
+                )
+                .unwrap(),
+                None => write!(out, "{}", escape(line)).unwrap(),
+            }
+            write!(out, "</tspan>").unwrap();
+        }
+        writeln!(out, "</text>").unwrap();
+    }
+
+    writeln!(out, "</g>").unwrap();
+    write_axes(&mut out, &view, plate);
+    writeln!(out, "</svg>").unwrap();
+
+    return out;
+}
+
+/// Write `doc/page*.svg` for every hue leaf, and `doc/legend.svg`.
+pub fn generate_svg(dataset: &Dataset, colors: &[Srgb]) {
+    generate_svg_with_progress(dataset, colors, &mut no_progress);
+}
+
+/// Like [`generate_svg`], reporting progress through the render stage, one
+/// unit per hue leaf.
+pub fn generate_svg_with_progress(dataset: &Dataset, colors: &[Srgb], progress: Progress) {
+    let font_data = load_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+
+    let total = dataset.hues().len();
+    report(progress, Stage::Render, 0, total);
+    for h in 0..total {
+        let plate = dataset.plate(h).unwrap();
+
+        let filename = format!(
+            "doc/page{}-{}_hues_{}-{}.svg",
+            plate.page, plate.side, plate.hue_begin, plate.hue_end
+        );
+        std::fs::write(filename, plate_to_svg(dataset, &plate, colors, &measure)).unwrap();
+
+        report(progress, Stage::Render, h + 1, total);
+    }
+    std::fs::write("doc/legend.svg", legend_to_svg(dataset, colors)).unwrap();
+}
+
+/// Write `doc/level<level>/page*.svg` for every hue leaf, like
+/// `doc/level2/`, with the regions of the names of `level` 1 or 2, merged
+/// from the level 3 regions under them.
+///
+/// `colors` are the representative colors of the names of `level`, indexed
+/// by `id - 1`.
+pub fn generate_level_svg(dataset: &Dataset, level: u32, colors: &[Srgb]) {
+    generate_level_svg_with_progress(dataset, level, colors, &mut no_progress);
+}
+
+/// Like [`generate_level_svg`], reporting progress through the render stage,
+/// one unit per hue leaf.
+pub fn generate_level_svg_with_progress(
+    dataset: &Dataset,
+    level: u32,
+    colors: &[Srgb],
+    progress: Progress,
+) {
+    let dir = format!("doc/level{}", level);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let font_data = load_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+
+    let total = dataset.hues().len();
+    report(progress, Stage::Render, 0, total);
+    for h in 0..total {
+        let plate = dataset.plate_at_level(h, level).unwrap();
+
+        let filename = format!(
+            "{}/page{}-{}_hues_{}-{}.svg",
+            dir, plate.page, plate.side, plate.hue_begin, plate.hue_end
+        );
+        std::fs::write(filename, plate_to_svg(dataset, &plate, colors, &measure)).unwrap();
+
+        report(progress, Stage::Render, h + 1, total);
+    }
+}
+
+/// Render the hue circle as an SVG document.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn wheel_to_svg(dataset: &Dataset, colors: &[Srgb]) -> String {
+    return wheel_drawing_to_svg(&wheel_drawing(dataset, colors));
+}
+
+/// Render the slice of the color solid at `value` as an SVG document.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn slice_to_svg(dataset: &Dataset, colors: &[Srgb], value: f32) -> String {
+    return wheel_drawing_to_svg(&slice_drawing(dataset, colors, value));
+}
+
+/// Render the layout of the hue circle, or of a slice of the solid, as an
+/// SVG document.
+fn wheel_drawing_to_svg(drawing: &WheelDrawing) -> String {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"{}\">",
+        SIZE, SIZE, SIZE, SIZE, config().font_name
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\" />"
+    )
+    .unwrap();
+
+    for polygon in drawing.polygons.iter() {
+        let points = polygon
+            .points
+            .iter()
+            .map(|(x, y)| format!("{:.2},{:.2}", x, y))
+            .collect::<Vec<String>>()
+            .join(" ");
+        writeln!(
+            out,
+            "<polygon points=\"{}\" fill=\"{}\" stroke=\"#000000\" />",
+            points,
+            rgb_space().css_color(polygon.fill)
+        )
+        .unwrap();
+    }
+
+    for line in drawing.lines.iter() {
+        writeln!(
+            out,
+            "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"#000000\" stroke-width=\"{}\" />",
+            line.from.0, line.from.1, line.to.0, line.to.1, line.width
+        )
+        .unwrap();
+    }
+
+    for text in drawing.texts.iter() {
+        let anchor = match text.anchor {
+            Anchor::Start => "start",
+            Anchor::Middle => "middle",
+            Anchor::End => "end",
+        };
+        let line_height = text.size * 1.15;
+        let first_dy = -((text.lines.len() - 1) as f64) * line_height / 2.0;
+
+        write!(
+            out,
+            "<text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"{}\" dominant-baseline=\"middle\" font-size=\"{}\">",
+            text.at.0, text.at.1, anchor, text.size
+        )
+        .unwrap();
+        for (i, line) in text.lines.iter().enumerate() {
+            let dy = if i == 0 { first_dy } else { line_height };
+            write!(
+                out,
+                "<tspan x=\"{:.2}\" dy=\"{:.2}\">{}</tspan>",
+                text.at.0,
+                dy,
+                escape(line)
+            )
+            .unwrap();
+        }
+        writeln!(out, "</text>").unwrap();
+    }
+
+    writeln!(out, "</svg>").unwrap();
+    return out;
+}
+
+/// Write `doc/hue_wheel.svg`.
+pub fn generate_wheel_svg(dataset: &Dataset, colors: &[Srgb]) {
+    std::fs::write("doc/hue_wheel.svg", wheel_to_svg(dataset, colors)).unwrap();
+}
+
+/// Write a slice through the middle of every value span into
+/// `doc/slices/`, like [`crate::plot::png::generate_slices`].
+pub fn generate_slices_svg(dataset: &Dataset, colors: &[Srgb]) {
+    std::fs::create_dir_all("doc/slices").unwrap();
+    for (begin, end) in slice_values(dataset) {
+        let path = format!("doc/slices/value_{}.svg", begin);
+        std::fs::write(path, slice_to_svg(dataset, colors, (begin + end) / 2.0)).unwrap();
+    }
+}
+
+/// Render the poster of every level 3 color as an SVG document.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn poster_to_svg<S: ColorNamingSystem + ?Sized>(dataset: &S, colors: &[Srgb]) -> String {
+    return swatch_drawing_to_svg(&poster_drawing(dataset, colors));
+}
+
+/// Render the legend of every level 3 color as an SVG document.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn legend_to_svg<S: ColorNamingSystem + ?Sized>(dataset: &S, colors: &[Srgb]) -> String {
+    return swatch_drawing_to_svg(&legend_drawing(dataset, colors));
+}
+
+fn swatch_drawing_to_svg(drawing: &PosterDrawing) -> String {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"{}\">",
+        drawing.width, drawing.height, drawing.width, drawing.height, config().font_name
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\" />"
+    )
+    .unwrap();
+
+    for swatch in drawing.swatches.iter() {
+        writeln!(
+            out,
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{}\" stroke=\"#000000\" />",
+            swatch.at.0,
+            swatch.at.1,
+            swatch.size.0,
+            swatch.size.1,
+            rgb_space().css_color(swatch.fill)
+        )
+        .unwrap();
+    }
+
+    for text in drawing.texts.iter() {
+        let anchor = match text.anchor {
+            Anchor::Start => "start",
+            Anchor::Middle => "middle",
+            Anchor::End => "end",
+        };
+        let weight = if text.bold {
+            " font-weight=\"bold\""
+        } else {
+            ""
+        };
+        writeln!(
+            out,
+            "<text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"{}\" dominant-baseline=\"middle\" font-size=\"{}\" fill=\"#{:x}\"{}>{}</text>",
+            text.at.0, text.at.1, anchor, text.size, text.color, weight, escape(&text.text)
+        )
+        .unwrap();
+    }
+
+    writeln!(out, "</svg>").unwrap();
+    return out;
+}
+
+/// Write `doc/poster.svg`.
+pub fn generate_poster_svg(dataset: &Dataset, colors: &[Srgb]) {
+    std::fs::write("doc/poster.svg", poster_to_svg(dataset, colors)).unwrap();
+}
+
+#[cfg(test)]
+mod test {
+    use ttf_word_wrap::TTFParserMeasure;
+
+    use crate::colors::get_mean_colors;
+    use crate::plot::load_font_data;
+    use crate::plot::svg::{
+        legend_to_svg, plate_excerpt_to_svg, plate_to_svg, poster_to_svg, slice_to_svg,
+        wheel_to_svg,
+    };
+    use crate::Dataset;
+
+    #[test]
+    fn plate_svg() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let font_data = load_font_data();
+        let font_face = ttf_parser::Face::from_slice(&font_data, 0).unwrap();
+        let measure = TTFParserMeasure::new(&font_face);
+
+        let plate = dataset.plate(0).unwrap();
+        let svg = plate_to_svg(&dataset, &plate, &colors, &measure);
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<polygon ").count(), plate.regions.len());
+        assert!(svg.contains("<tspan font-weight=\"bold\">1</tspan>:"));
+        assert!(svg.contains(">1R-4R</text>"));
+
+        let excerpt = plate_excerpt_to_svg(&dataset, &plate, &colors, &measure, 1).unwrap();
+        assert!(!excerpt.contains("viewBox=\"0 0 600 800\""));
+        assert!(excerpt.contains("stroke-width=\"4\""));
+        assert!(plate_excerpt_to_svg(&dataset, &plate, &colors, &measure, 100).is_none());
+    }
+
+    #[test]
+    fn wheel_svg() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let svg = wheel_to_svg(&dataset, &colors);
+
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(">Purplish blue</tspan>"));
+        assert!(svg.contains(">Yellow</tspan><tspan x="));
+        assert!(svg.contains(">9RP</tspan>"));
+
+        let slice = slice_to_svg(&dataset, &colors, 7.0);
+        assert!(slice.ends_with("</svg>\n"));
+        assert!(slice.contains(">Value 7</tspan>"));
+        assert!(slice.contains(">Chroma</tspan>"));
+    }
+
+    #[test]
+    fn poster_svg() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let svg = poster_to_svg(&dataset, &colors);
+
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches(" fill=\"#5964ce\" stroke=").count(), 1);
+        assert!(svg.contains(">Yellow green (YG)</text>"));
+
+        let legend = legend_to_svg(&dataset, &colors);
+        assert_eq!(legend.matches(" fill=\"#5964ce\" stroke=").count(), 1);
+        assert!(legend.contains(">Vivid pink</text>"));
+    }
+}
@@ -0,0 +1,291 @@
+//! The color name charts as standalone TikZ figures, for embedding in LaTeX
+//! documents at vector quality. Each compiles on its own with `pdflatex`,
+//! or can be `\input` into a document loading TikZ and `xcolor`.
+
+use std::fmt::Write as _;
+
+use geo_types::Polygon;
+use palette::Srgb;
+use ttf_word_wrap::TTFParserMeasure;
+
+use crate::dataset::Dataset;
+use crate::plate::Plate;
+use crate::plot::labels::place_labels;
+use crate::plot::load_font_data;
+use crate::progress::{no_progress, report, Progress, Stage};
+
+/// The size of the plotting area, in centimeters; the same shape as that of
+/// the 600×800 pixel PNG and SVG charts.
+const WIDTH_CM: f64 = 10.4;
+const HEIGHT_CM: f64 = 14.0;
+/// The width of the plotting area of the PNG and SVG charts, in pixels.
+const WIDTH_PX: f64 = 520.0;
+const PT_PER_CM: f64 = 72.27 / 2.54;
+
+/// Font size in points for tick marks.
+const TICK_FONT_SIZE: f64 = 7.0;
+
+/// Scale a font size in pixels of the PNG and SVG charts to points, so that
+/// labels wrap the same way.
+fn pixels_to_points(size: f64) -> f64 {
+    return size * WIDTH_CM / WIDTH_PX * PT_PER_CM;
+}
+
+/// Escape the characters TeX treats specially.
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\textbackslash{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    return out;
+}
+
+fn polygon_path(polygon: &Polygon<f64>) -> String {
+    let mut points = polygon
+        .exterior()
+        .points()
+        .map(|v| format!("({:.3},{:.3})", v.x(), v.y()))
+        .collect::<Vec<String>>();
+    // the exterior is closed; `cycle` closes the path instead
+    points.pop();
+    points.push("cycle".to_string());
+    return points.join(" -- ");
+}
+
+fn font(size: f64) -> String {
+    return format!(
+        "\\fontsize{{{:.1}}}{{{:.1}}}\\selectfont",
+        size,
+        size * 1.15
+    );
+}
+
+fn write_axes(out: &mut String, plate: &Plate) {
+    let (x0, x1) = plate.axes.chroma_range;
+    let (y0, y1) = plate.axes.value_range;
+
+    let mut chroma_ticks: Vec<(f64, f64)> = (0..=16)
+        .step_by(2)
+        .map(|x| (x as f64, TICK_FONT_SIZE))
+        .collect();
+    chroma_ticks.push((1.0, TICK_FONT_SIZE));
+    for tick in plate.axes.fine_chroma_ticks.iter() {
+        chroma_ticks.push((*tick, TICK_FONT_SIZE * 0.75));
+    }
+
+    for (tick, _) in chroma_ticks.iter() {
+        writeln!(out, "\\draw[grid] ({},{}) -- ({},{});", tick, y0, tick, y1).unwrap();
+    }
+    for tick in 0..=10 {
+        writeln!(out, "\\draw[grid] ({},{}) -- ({},{});", x0, tick, x1, tick).unwrap();
+    }
+    writeln!(
+        out,
+        "\\draw ({},{}) -- ({},{}) -- ({},{});",
+        x0, y1, x0, y0, x1, y0
+    )
+    .unwrap();
+
+    for (tick, size) in chroma_ticks.iter() {
+        writeln!(
+            out,
+            "\\draw ({},{}) -- ++(0,-3pt) node[below, font={}] {{{}}};",
+            tick,
+            y0,
+            font(*size),
+            tick
+        )
+        .unwrap();
+    }
+    for tick in 0..=10 {
+        writeln!(
+            out,
+            "\\draw ({},{}) -- ++(-3pt,0) node[left, font={}] {{{}}};",
+            x0,
+            tick,
+            font(TICK_FONT_SIZE),
+            tick
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "\\node[below=12pt, font={}] at ({:.2},{}) {{Munsell Chroma}};",
+        font(TICK_FONT_SIZE),
+        (x0 + x1) / 2.0,
+        y0
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "\\node[above=16pt, rotate=90, font={}] at ({},{:.2}) {{Munsell Value}};",
+        font(TICK_FONT_SIZE),
+        x0,
+        (y0 + y1) / 2.0
+    )
+    .unwrap();
+}
+
+/// Render one plate of the color name charts as a standalone LaTeX
+/// document with a TikZ picture.
+///
+/// `colors` are the representative colors of the level 3 names, indexed by
+/// `color_id - 1`.
+pub fn plate_to_tikz(
+    dataset: &Dataset,
+    plate: &Plate,
+    colors: &[Srgb],
+    measure: &TTFParserMeasure,
+) -> String {
+    let (x0, x1) = plate.axes.chroma_range;
+    let (y0, y1) = plate.axes.value_range;
+    let mut out = String::new();
+
+    writeln!(out, "\\documentclass[tikz, border=2mm]{{standalone}}").unwrap();
+    writeln!(out, "\\begin{{document}}").unwrap();
+    writeln!(
+        out,
+        "\\begin{{tikzpicture}}[x={:.4}cm, y={:.4}cm, grid/.style={{black!20, dashed}}]",
+        WIDTH_CM / (x1 - x0),
+        HEIGHT_CM / (y1 - y0)
+    )
+    .unwrap();
+
+    for region in plate.regions.iter() {
+        let c: Srgb<u8> = colors[(region.color_id - 1) as usize].into_format();
+        writeln!(
+            out,
+            "\\definecolor{{iscc{}}}{{RGB}}{{{},{},{}}}",
+            region.color_id, c.red, c.green, c.blue
+        )
+        .unwrap();
+    }
+
+    writeln!(
+        out,
+        "\\node[anchor=south east, font={}] at ({},{}) {{{}-{}}};",
+        font(TICK_FONT_SIZE),
+        x1,
+        y1,
+        escape(&plate.hue_begin),
+        escape(&plate.hue_end)
+    )
+    .unwrap();
+
+    // regions extend past the visible range, out to the INF cutoffs
+    writeln!(out, "\\begin{{scope}}").unwrap();
+    writeln!(out, "\\clip ({},{}) rectangle ({},{});", x0, y0, x1, y1).unwrap();
+    for region in plate.regions.iter() {
+        writeln!(
+            out,
+            "\\filldraw[fill=iscc{}, draw=black] {};",
+            region.color_id,
+            polygon_path(&region.polygon)
+        )
+        .unwrap();
+    }
+
+    for placement in place_labels(dataset, plate, colors, measure) {
+        let lines = placement
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| match line.split_once(':').filter(|_| i == 0) {
+                // the id is in boldface
+                Some((prefix, suffix)) => {
+                    format!("\\textbf{{{}}}:{}", escape(prefix), escape(suffix))
+                }
+                None => escape(line),
+            })
+            .collect::<Vec<String>>();
+        let c = placement.text_color;
+        writeln!(
+            out,
+            "\\node[align=center, rotate={}, inner sep=0, text={{rgb,255:red,{};green,{};blue,{}}}, font={}] at ({:.3},{:.3}) {{{}}};",
+            placement.rotation,
+            c.red,
+            c.green,
+            c.blue,
+            font(pixels_to_points(placement.font_size)),
+            placement.position.0,
+            placement.position.1,
+            lines.join("\\\\")
+        )
+        .unwrap();
+    }
+    writeln!(out, "\\end{{scope}}").unwrap();
+
+    write_axes(&mut out, plate);
+    writeln!(out, "\\end{{tikzpicture}}").unwrap();
+    writeln!(out, "\\end{{document}}").unwrap();
+    return out;
+}
+
+/// Write `doc/page*.tex` for every hue leaf.
+pub fn generate_tikz(dataset: &Dataset, colors: &[Srgb]) {
+    generate_tikz_with_progress(dataset, colors, &mut no_progress);
+}
+
+/// Like [`generate_tikz`], reporting progress through the render stage, one
+/// unit per hue leaf.
+pub fn generate_tikz_with_progress(dataset: &Dataset, colors: &[Srgb], progress: Progress) {
+    let font_data = load_font_data();
+    let font_face = ttf_parser::Face::from_slice(&font_data, 0).expect("TTF should be valid");
+    let measure = TTFParserMeasure::new(&font_face);
+
+    let total = dataset.hues().len();
+    report(progress, Stage::Render, 0, total);
+    for h in 0..total {
+        let plate = dataset.plate(h).unwrap();
+
+        let filename = format!(
+            "doc/page{}-{}_hues_{}-{}.tex",
+            plate.page, plate.side, plate.hue_begin, plate.hue_end
+        );
+        std::fs::write(filename, plate_to_tikz(dataset, &plate, colors, &measure)).unwrap();
+
+        report(progress, Stage::Render, h + 1, total);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ttf_word_wrap::TTFParserMeasure;
+
+    use crate::colors::get_mean_colors;
+    use crate::plot::load_font_data;
+    use crate::plot::tikz::{escape, plate_to_tikz};
+    use crate::Dataset;
+
+    #[test]
+    fn plate_tikz() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let font_data = load_font_data();
+        let font_face = ttf_parser::Face::from_slice(&font_data, 0).unwrap();
+        let measure = TTFParserMeasure::new(&font_face);
+
+        let plate = dataset.plate(0).unwrap();
+        let tikz = plate_to_tikz(&dataset, &plate, &colors, &measure);
+
+        assert!(tikz.starts_with("\\documentclass[tikz, border=2mm]{standalone}\n"));
+        assert!(tikz.ends_with("\\end{tikzpicture}\n\\end{document}\n"));
+        assert!(tikz.contains("\\definecolor{iscc1}{RGB}{255,194,197}\n"));
+        assert_eq!(tikz.matches("\\filldraw[").count(), plate.regions.len());
+        assert!(tikz.contains("{\\textbf{1}: Vivid"));
+        assert!(tikz.contains("{1R-4R};"));
+        // every brace is balanced
+        assert_eq!(tikz.matches('{').count(), tikz.matches('}').count());
+
+        assert_eq!(escape("50% & #1_{x}"), "50\\% \\& \\#1\\_\\{x\\}");
+    }
+}
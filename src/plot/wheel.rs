@@ -0,0 +1,280 @@
+//! The hue circle: the hue leaves around the wheel, with the level 1 and
+//! level 2 names of their most saturated colors in the sectors.
+//!
+//! The layout is computed here once, in pixels, and drawn by the PNG and SVG
+//! renderers.
+
+use palette::Srgb;
+
+use crate::dataset::{ColorBlock, Dataset};
+use crate::munsell::MunsellHue;
+
+pub(crate) const SIZE: f64 = 1000.0;
+pub(crate) const CENTER: f64 = SIZE / 2.0;
+
+/// The radii of the level 1 ring, the level 2 ring, the hue labels and the
+/// level 2 labels, in pixels.
+const LEVEL1_INNER: f64 = 120.0;
+const LEVEL1_OUTER: f64 = 250.0;
+const LEVEL2_OUTER: f64 = 340.0;
+const HUE_LABEL: f64 = 362.0;
+const LEVEL2_LABEL: f64 = 400.0;
+
+pub(crate) const HUE_FONT_SIZE: f64 = 10.0;
+pub(crate) const NAME_FONT_SIZE: f64 = 13.0;
+
+/// One hue leaf of the wheel, named after its most saturated color.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HueSector {
+    pub hue_begin: String,
+    pub hue_end: String,
+    /// The level 3 color with the highest chroma in the leaf, like "Vivid
+    /// red".
+    pub color_id: u32,
+    pub level2_id: u32,
+    pub level1_id: u32,
+}
+
+impl Dataset {
+    /// Name every hue leaf after its most saturated color: the block whose
+    /// chroma begins the highest, or the tallest of those.
+    pub fn hue_sectors(&self) -> Vec<HueSector> {
+        let hues = self.hues();
+        let chroma = |block: &ColorBlock| -> f32 {
+            return self.chromas()[block.chromas.start].parse().unwrap();
+        };
+
+        return (0..hues.len())
+            .map(|h| {
+                let block = self
+                    .blocks()
+                    .iter()
+                    .filter(|x| x.hues.start == h)
+                    .reduce(|best, x| {
+                        let (c, best_c) = (chroma(x), chroma(best));
+                        if c > best_c || (c == best_c && x.values.len() > best.values.len()) {
+                            x
+                        } else {
+                            best
+                        }
+                    })
+                    .unwrap();
+
+                let level2_id = self.names()[&block.color_id].parent.unwrap();
+                let level1_id = self.level2_names()[&level2_id].parent.unwrap();
+                HueSector {
+                    hue_begin: hues[h].clone(),
+                    hue_end: hues[(h + 1) % hues.len()].clone(),
+                    color_id: block.color_id,
+                    level2_id,
+                    level1_id,
+                }
+            })
+            .collect();
+    }
+}
+
+/// Group consecutive sectors with the same key, wrapping around the wheel,
+/// into (first sector, number of sectors) runs.
+fn runs(sectors: &[HueSector], key: fn(&HueSector) -> u32) -> Vec<(usize, usize)> {
+    let n = sectors.len();
+    // start at a boundary, so that no run is split at the wrap-around
+    let first = (0..n)
+        .find(|i| key(&sectors[*i]) != key(&sectors[(i + n - 1) % n]))
+        .unwrap_or(0);
+
+    let mut result: Vec<(usize, usize)> = Vec::new();
+    for i in (first..first + n).map(|i| i % n) {
+        match result.last_mut() {
+            Some((start, len)) if key(&sectors[*start]) == key(&sectors[i]) => *len += 1,
+            _ => result.push((i, 1)),
+        }
+    }
+    return result;
+}
+
+/// How text is aligned on its anchor point.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+pub(crate) struct WheelPolygon {
+    pub points: Vec<(f64, f64)>,
+    pub fill: Srgb,
+}
+
+pub(crate) struct WheelLine {
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+    pub width: f64,
+}
+
+/// Text centered vertically on `at`, one line per entry of `lines`.
+pub(crate) struct WheelText {
+    pub at: (f64, f64),
+    pub lines: Vec<String>,
+    pub anchor: Anchor,
+    pub size: f64,
+}
+
+pub(crate) struct WheelDrawing {
+    pub polygons: Vec<WheelPolygon>,
+    pub lines: Vec<WheelLine>,
+    pub texts: Vec<WheelText>,
+}
+
+/// The angle of a hue on the wheel, in degrees counterclockwise from the
+/// right, unwrapped so that it is not less than `after`.
+pub(crate) fn angle(hue: &str, after: f64) -> f64 {
+    let degrees = hue.parse::<MunsellHue>().unwrap().to_degrees() as f64;
+    return after + (degrees - after).rem_euclid(360.0);
+}
+
+pub(crate) fn polar(radius: f64, degrees: f64) -> (f64, f64) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    return (CENTER + radius * cos, CENTER - radius * sin);
+}
+
+/// The outline of a ring sector, with a point every degree along the arcs.
+pub(crate) fn ring_sector(inner: f64, outer: f64, begin: f64, end: f64) -> Vec<(f64, f64)> {
+    let steps = ((end - begin).ceil() as usize).max(1);
+    let arc = (0..=steps).map(|i| begin + (end - begin) * (i as f64) / (steps as f64));
+
+    let mut points = arc.clone().map(|a| polar(outer, a)).collect::<Vec<_>>();
+    points.extend(arc.rev().map(|a| polar(inner, a)));
+    return points;
+}
+
+/// Text placed outside of the wheel at `degrees`, aligned away from it.
+pub(crate) fn outside_text(text: &str, radius: f64, degrees: f64, size: f64) -> WheelText {
+    let cos = degrees.to_radians().cos();
+    let anchor = if cos > 0.3 {
+        Anchor::Start
+    } else if cos < -0.3 {
+        Anchor::End
+    } else {
+        Anchor::Middle
+    };
+    return WheelText {
+        at: polar(radius, degrees),
+        lines: vec![text.to_string()],
+        anchor,
+        size,
+    };
+}
+
+/// Lay out the hue circle: an inner ring of level 1 names, an outer ring of
+/// hue leaves filled with the representative colors of their most
+/// saturated colors (`colors`, indexed by `color_id - 1`), with the hue
+/// boundaries and level 2 names around the outside.
+pub(crate) fn wheel_drawing(dataset: &Dataset, colors: &[Srgb]) -> WheelDrawing {
+    let sectors = dataset.hue_sectors();
+    let span = |first: usize, len: usize| {
+        let begin = angle(&sectors[first].hue_begin, 0.0);
+        let end = angle(&sectors[(first + len - 1) % sectors.len()].hue_end, begin);
+        return (begin, end);
+    };
+
+    let mut drawing = WheelDrawing {
+        polygons: Vec::new(),
+        lines: Vec::new(),
+        texts: Vec::new(),
+    };
+
+    for (first, len) in runs(&sectors, |x| x.level1_id) {
+        let (begin, end) = span(first, len);
+        drawing.polygons.push(WheelPolygon {
+            points: ring_sector(LEVEL1_INNER, LEVEL1_OUTER, begin, end),
+            fill: Srgb::new(1.0, 1.0, 1.0),
+        });
+        drawing.lines.push(WheelLine {
+            from: polar(LEVEL1_INNER, begin),
+            to: polar(LEVEL2_OUTER, begin),
+            width: 2.0,
+        });
+
+        // "Yellow green" needs two lines to fit its narrow sector
+        let name = &dataset.level1_names()[&sectors[first].level1_id].name;
+        drawing.texts.push(WheelText {
+            at: polar((LEVEL1_INNER + LEVEL1_OUTER) / 2.0, (begin + end) / 2.0),
+            lines: name.split(' ').map(|x| x.to_string()).collect(),
+            anchor: Anchor::Middle,
+            size: NAME_FONT_SIZE,
+        });
+    }
+
+    for (h, sector) in sectors.iter().enumerate() {
+        let (begin, end) = span(h, 1);
+        drawing.polygons.push(WheelPolygon {
+            points: ring_sector(LEVEL1_OUTER, LEVEL2_OUTER, begin, end),
+            fill: colors[(sector.color_id - 1) as usize],
+        });
+        drawing.lines.push(WheelLine {
+            from: polar(LEVEL2_OUTER, begin),
+            to: polar(LEVEL2_OUTER + 8.0, begin),
+            width: 1.0,
+        });
+        drawing.texts.push(outside_text(
+            &sector.hue_begin,
+            HUE_LABEL,
+            begin,
+            HUE_FONT_SIZE,
+        ));
+    }
+
+    for (first, len) in runs(&sectors, |x| x.level2_id) {
+        let (begin, end) = span(first, len);
+        drawing.lines.push(WheelLine {
+            from: polar(LEVEL1_OUTER, begin),
+            to: polar(LEVEL2_OUTER, begin),
+            width: 2.0,
+        });
+
+        let name = &dataset.level2_names()[&sectors[first].level2_id].name;
+        drawing.texts.push(outside_text(
+            name,
+            LEVEL2_LABEL,
+            (begin + end) / 2.0,
+            NAME_FONT_SIZE,
+        ));
+    }
+
+    return drawing;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::plot::wheel::runs;
+    use crate::Dataset;
+
+    #[test]
+    fn sector_names() {
+        let dataset = Dataset::bundled();
+        let sectors = dataset.hue_sectors();
+        assert_eq!(sectors.len(), dataset.hues().len());
+
+        let level2 = |h: usize| &dataset.level2_names()[&sectors[h].level2_id].name;
+        assert_eq!(sectors[0].hue_begin, "1R");
+        assert_eq!(level2(0), "Red");
+        assert_eq!(level2(12), "Yellow");
+        assert_eq!(level2(25), "Purplish blue");
+        assert_eq!(level2(30), "Purplish red");
+    }
+
+    #[test]
+    fn runs_wrap_around() {
+        let dataset = Dataset::bundled();
+        let sectors = dataset.hue_sectors();
+
+        // red runs from 9RP across 1R to 7R
+        let level1 = runs(&sectors, |x| x.level1_id);
+        assert_eq!(level1.iter().map(|x| x.1).sum::<usize>(), sectors.len());
+        assert!(level1.contains(&(29, 5)));
+
+        let level2 = runs(&sectors, |x| x.level2_id);
+        assert_eq!(level2.len(), 17);
+    }
+}
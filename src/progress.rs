@@ -0,0 +1,79 @@
+//! Progress reporting for the long-running operations of this crate, for
+//! frontends that want to show how far along they are.
+//!
+//! Operations with a `_with_progress` variant call a [`Progress`] callback
+//! once with `done == 0` when a stage starts, and again each time a unit of
+//! work in it completes. To receive events on another thread, send them
+//! through a channel from the callback:
+//!
+//! ```
+//! use std::sync::mpsc;
+//! use iscc_nbs_validator::Dataset;
+//!
+//! let (sender, receiver) = mpsc::channel();
+//! let dataset = Dataset::bundled();
+//! dataset.srgb_coverage_with_progress(4, &mut |event| sender.send(event).unwrap());
+//! drop(sender);
+//!
+//! assert_eq!(receiver.iter().last().unwrap().done, 4);
+//! ```
+
+/// A stage of work that reports progress.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Parsing the XML document.
+    Parse,
+    /// Checking the names and blocks of the document.
+    Validate,
+    /// Writing and rendering figures, one unit per figure.
+    Render,
+    /// Classifying a batch of colors.
+    Classify,
+}
+
+/// A report that `done` out of `total` units of work in `stage` have
+/// completed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProgressEvent {
+    pub stage: Stage,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// A callback receiving progress events.
+pub type Progress<'a> = &'a mut dyn FnMut(ProgressEvent);
+
+/// A callback that ignores all events.
+pub(crate) fn no_progress(_: ProgressEvent) {}
+
+/// Report the state of a stage.
+pub(crate) fn report(progress: Progress, stage: Stage, done: usize, total: usize) {
+    progress(ProgressEvent { stage, done, total });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::progress::{ProgressEvent, Stage};
+    use crate::Dataset;
+
+    #[test]
+    fn parse_events() {
+        let text = std::fs::read_to_string("iscc-nbs.xml").unwrap();
+        let mut events: Vec<ProgressEvent> = Vec::new();
+        Dataset::parse_with_progress(&text, &mut |event| events.push(event)).unwrap();
+
+        let stages = events.iter().map(|x| (x.stage, x.done)).collect::<Vec<_>>();
+        assert_eq!(
+            stages,
+            vec![
+                (Stage::Parse, 0),
+                (Stage::Parse, 1),
+                (Stage::Validate, 0),
+                (Stage::Validate, 1),
+                (Stage::Validate, 2),
+                (Stage::Validate, 3),
+                (Stage::Validate, 4),
+            ]
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! A Python module, `pyiscc_nbs`, with the lookup table, the centroids and
+//! Munsell colors of the bundled dataset. Built with maturin; see
+//! `pyproject.toml`.
+
+use lazy_static::lazy_static;
+use palette::Srgb;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::colors::get_mean_munsell;
+use crate::dataset::Dataset;
+use crate::munsell::{MunsellColor, MunsellHue};
+
+lazy_static! {
+    static ref DATASET: Dataset = Dataset::bundled();
+    static ref CENTROIDS: Vec<MunsellColor> = get_mean_munsell(&*DATASET);
+}
+
+/// A color in Munsell notation, like `MunsellColor("7.5YR", 6.0, 8.0)`.
+#[pyclass(name = "MunsellColor", module = "pyiscc_nbs", frozen)]
+#[derive(Clone)]
+pub struct PyMunsellColor(MunsellColor);
+
+#[pymethods]
+impl PyMunsellColor {
+    #[new]
+    fn new(hue: &str, value: f32, chroma: f32) -> PyResult<Self> {
+        let hue = match hue.parse::<MunsellHue>() {
+            Ok(v) => v,
+            Err(e) => return Err(PyValueError::new_err(e.to_string())),
+        };
+        return Ok(PyMunsellColor(MunsellColor::new(hue, value, chroma)));
+    }
+
+    /// Parse a specification like "7.5YR 6/8", or return None.
+    #[staticmethod]
+    fn parse(spec: &str) -> Option<Self> {
+        return MunsellColor::parse(spec).map(PyMunsellColor);
+    }
+
+    #[getter]
+    fn hue(&self) -> String {
+        return self.0.hue.to_string();
+    }
+
+    #[getter]
+    fn value(&self) -> f32 {
+        return self.0.value;
+    }
+
+    #[getter]
+    fn chroma(&self) -> f32 {
+        return self.0.chroma;
+    }
+
+    /// The color in CIELAB, as an (L*, a*, b*) tuple.
+    fn to_lab(&self) -> (f32, f32, f32) {
+        let lab = self.0.to_lab();
+        return (lab.l, lab.a, lab.b);
+    }
+
+    fn __str__(&self) -> String {
+        return self.0.to_string();
+    }
+
+    fn __repr__(&self) -> String {
+        return format!(
+            "MunsellColor('{}', {}, {})",
+            self.0.hue, self.0.value, self.0.chroma
+        );
+    }
+}
+
+/// The id of the level 3 color a Munsell color falls into, or None.
+#[pyfunction]
+fn lookup(color: &PyMunsellColor) -> Option<u32> {
+    return DATASET.lookup_id(&color.0);
+}
+
+/// The id of the level 3 color an 8-bit sRGB color falls into, or None.
+#[pyfunction]
+fn lookup_rgb(r: u8, g: u8, b: u8) -> Option<u32> {
+    return DATASET.classify_srgb(Srgb::new(r, g, b).into_format());
+}
+
+/// The name of a level 3 color, like "Vivid pink", or None.
+#[pyfunction]
+fn name(color_id: u32) -> Option<String> {
+    return DATASET.names().get(&color_id).map(|x| x.name.clone());
+}
+
+/// The abbreviation of a level 3 color, like "viv.Pk", or None.
+#[pyfunction]
+fn abbr(color_id: u32) -> Option<String> {
+    return DATASET.names().get(&color_id).map(|x| x.abbr.clone());
+}
+
+/// Every block of the lookup table, as (color id, hue begin, hue end,
+/// chroma begin, chroma end, value begin, value end) tuples of boundaries
+/// like "7.5YR" and "6.0"; the ends are excluded, and "INF" is unbounded.
+#[pyfunction]
+#[allow(clippy::type_complexity)]
+fn blocks() -> Vec<(u32, String, String, String, String, String, String)> {
+    let (hues, chromas, values) = (DATASET.hues(), DATASET.chromas(), DATASET.values());
+    return DATASET
+        .blocks()
+        .iter()
+        .map(|x| {
+            (
+                x.color_id,
+                hues[x.hues.start].clone(),
+                hues[x.hues.end].clone(),
+                chromas[x.chromas.start].clone(),
+                chromas[x.chromas.end].clone(),
+                values[x.values.start].clone(),
+                values[x.values.end].clone(),
+            )
+        })
+        .collect();
+}
+
+/// The centroid of every level 3 color, indexed by color id - 1.
+#[pyfunction]
+fn centroids() -> Vec<PyMunsellColor> {
+    return CENTROIDS.iter().cloned().map(PyMunsellColor).collect();
+}
+
+#[pymodule]
+fn pyiscc_nbs(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyMunsellColor>()?;
+    module.add_function(wrap_pyfunction!(lookup, module)?)?;
+    module.add_function(wrap_pyfunction!(lookup_rgb, module)?)?;
+    module.add_function(wrap_pyfunction!(name, module)?)?;
+    module.add_function(wrap_pyfunction!(abbr, module)?)?;
+    module.add_function(wrap_pyfunction!(blocks, module)?)?;
+    module.add_function(wrap_pyfunction!(centroids, module)?)?;
+    return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+    use crate::python::{abbr, blocks, centroids, lookup, lookup_rgb, name, PyMunsellColor};
+
+    #[test]
+    fn module_functions() {
+        let color = PyMunsellColor::parse("2R 7/12").unwrap();
+        assert_eq!(color.hue(), "2.00R");
+        assert_eq!(lookup(&color), Some(1));
+        assert!(PyMunsellColor::new("2Q", 7.0, 12.0).is_err());
+        assert_eq!(
+            lookup(&PyMunsellColor::new("2R", 7.0, 12.0).unwrap()),
+            Some(1)
+        );
+
+        assert_eq!(lookup_rgb(0, 0, 0), Some(267));
+        assert_eq!(name(1).as_deref(), Some("Vivid pink"));
+        assert_eq!(abbr(1).as_deref(), Some("viv.Pk"));
+        assert_eq!(name(0), None);
+
+        assert_eq!(blocks().len(), 932);
+        assert_eq!(centroids().len(), 267);
+    }
+}
@@ -0,0 +1,62 @@
+//! A small pseudo-random number generator for sampling-based features, so
+//! that they don't need an external crate and give the same results on
+//! every platform.
+//!
+//! Every sampling-based feature takes an [`Rng`], or uses one seeded with
+//! [`DEFAULT_SEED`] if it doesn't, so that the same seed always gives the
+//! same results, across runs, platforms and releases of this crate.
+
+/// The seed of the features that aren't given an [`Rng`].
+pub const DEFAULT_SEED: u64 = 0;
+
+/// SplitMix64, which is fast, has a 64-bit state, and passes BigCrush.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create a generator whose numbers only depend on `seed`.
+    pub fn new(seed: u64) -> Self {
+        return Rng { state: seed };
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        return z ^ (z >> 31);
+    }
+
+    /// A uniformly distributed number in `(0, 1]`.
+    pub fn next_f64(&mut self) -> f64 {
+        return ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64;
+    }
+
+    /// A normally distributed number with mean 0 and standard deviation 1,
+    /// by the Box-Muller transform.
+    pub fn next_normal(&mut self) -> f64 {
+        let radius = (-2.0 * self.next_f64().ln()).sqrt();
+        let angle = 2.0 * std::f64::consts::PI * self.next_f64();
+        return radius * angle.cos();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::random::Rng;
+
+    #[test]
+    fn normal_distribution() {
+        let mut rng = Rng::new(1);
+        let samples = (0..10000).map(|_| rng.next_normal()).collect::<Vec<f64>>();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance =
+            samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+
+        assert!(mean.abs() < 0.05);
+        assert!((variance - 1.0).abs() < 0.05);
+        assert_eq!(Rng::new(7).next_u64(), Rng::new(7).next_u64());
+    }
+}
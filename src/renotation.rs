@@ -0,0 +1,247 @@
+//! Munsell to CIE xyY conversion, interpolated from the Munsell renotation
+//! data of Newhall, Nickerson and Judd (1943).
+//!
+//! The bundled table is the complete `all.dat` set published by the Munsell
+//! Color Science Laboratory at RIT, which includes the extrapolated colors
+//! beyond the MacAdam limits, so that every block of the color name charts
+//! can be converted. Chromaticities are relative to CIE Illuminant C.
+
+use std::collections::{BTreeMap, HashMap};
+
+use lazy_static::lazy_static;
+
+use crate::munsell::MunsellHue;
+
+const RENOTATION_DAT: &str = include_str!("../data/munsell-renotation-all.dat");
+
+/// Chromaticity of CIE Illuminant C, the renotation's neutral point.
+pub const ILLUMINANT_C_XY: (f64, f64) = (0.31006, 0.31616);
+
+/// The renotation table gives a hue page every 2.5 hue steps.
+const HUE_STEP: f32 = 2.5;
+const HUE_PAGES: usize = 40;
+
+/// ...and a chip every 2 chroma steps.
+const CHROMA_STEP: f32 = 2.0;
+
+struct Renotation {
+    /// (x, y) keyed by (hue page, value in tenths, chroma)
+    chromaticities: HashMap<(usize, u32, u32), (f64, f64)>,
+    /// The luminance factor Y (in percent) of each value, in tenths
+    luminances: BTreeMap<u32, f64>,
+}
+
+lazy_static! {
+    static ref RENOTATION: Renotation = Renotation::parse(RENOTATION_DAT);
+}
+
+fn hue_page(hue: MunsellHue) -> usize {
+    return ((hue.raw() / HUE_STEP).round() as usize) % HUE_PAGES;
+}
+
+impl Renotation {
+    fn parse(text: &str) -> Self {
+        let mut chromaticities = HashMap::new();
+        let mut luminances = BTreeMap::new();
+
+        // skip the " h V C x y Y" header
+        for line in text.lines().skip(1) {
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            let hue = fields[0].parse::<MunsellHue>().unwrap();
+            let value = (fields[1].parse::<f32>().unwrap() * 10.0).round() as u32;
+            let chroma = fields[2].parse::<u32>().unwrap();
+            let x = fields[3].parse::<f64>().unwrap();
+            let y = fields[4].parse::<f64>().unwrap();
+            let luminance = fields[5].parse::<f64>().unwrap();
+
+            chromaticities.insert((hue_page(hue), value, chroma), (x, y));
+            luminances.insert(value, luminance);
+        }
+
+        // value 0 is ideal black
+        luminances.insert(0, 0.0);
+
+        return Renotation {
+            chromaticities,
+            luminances,
+        };
+    }
+
+    /// The value steps (in tenths) surrounding `value`.
+    fn value_bounds(&self, value: f32) -> (u32, u32) {
+        let tenths = value * 10.0;
+        let lower = self
+            .luminances
+            .range(..=(tenths.floor() as u32))
+            .next_back()
+            .map(|(v, _)| *v)
+            .unwrap();
+        let upper = self
+            .luminances
+            .range((tenths.ceil() as u32)..)
+            .next()
+            .map(|(v, _)| *v)
+            .unwrap_or(lower);
+
+        return (lower, upper);
+    }
+
+    fn luminance(&self, value: f32) -> f64 {
+        let (lower, upper) = self.value_bounds(value);
+        if lower == upper {
+            return self.luminances[&lower];
+        }
+
+        let t = ((value * 10.0) - (lower as f32)) / ((upper - lower) as f32);
+
+        return lerp(self.luminances[&lower], self.luminances[&upper], t as f64);
+    }
+
+    /// Chromaticity of a chip in the table. Chips beyond the largest
+    /// tabulated chroma are extrapolated linearly from the last two.
+    fn chip(&self, page: usize, value: u32, chroma: u32) -> (f64, f64) {
+        if chroma == 0 {
+            return ILLUMINANT_C_XY;
+        }
+        if let Some(xy) = self.chromaticities.get(&(page, value, chroma)) {
+            return *xy;
+        }
+
+        let step = CHROMA_STEP as u32;
+        let max_chroma = (1..chroma / step)
+            .rev()
+            .map(|n| n * step)
+            .find(|c| self.chromaticities.contains_key(&(page, value, *c)));
+
+        return match max_chroma {
+            Some(max_chroma) => {
+                let last = self.chip(page, value, max_chroma);
+                let previous = self.chip(page, value, max_chroma - step);
+                let t = ((chroma - max_chroma) / step) as f64;
+
+                (
+                    last.0 + (last.0 - previous.0) * t,
+                    last.1 + (last.1 - previous.1) * t,
+                )
+            }
+            None => ILLUMINANT_C_XY,
+        };
+    }
+
+    /// Chromaticity at a tabulated hue page and value, interpolating
+    /// linearly between chroma steps.
+    fn on_page(&self, page: usize, value: u32, chroma: f32) -> (f64, f64) {
+        let lower = (chroma / CHROMA_STEP).floor() * CHROMA_STEP;
+        let t = ((chroma - lower) / CHROMA_STEP) as f64;
+
+        let a = self.chip(page, value, lower as u32);
+        let b = self.chip(page, value, (lower + CHROMA_STEP) as u32);
+
+        return (lerp(a.0, b.0, t), lerp(a.1, b.1, t));
+    }
+
+    /// Chromaticity at a tabulated value, interpolating between hue pages in
+    /// polar coordinates around the neutral point.
+    fn at_value(&self, hue: MunsellHue, value: u32, chroma: f32) -> (f64, f64) {
+        let pages = hue.raw() / HUE_STEP;
+        let lower = pages.floor() as usize % HUE_PAGES;
+        let t = (pages - pages.floor()) as f64;
+
+        let (r1, theta1) = to_polar(self.on_page(lower, value, chroma));
+        let (r2, theta2) = to_polar(self.on_page((lower + 1) % HUE_PAGES, value, chroma));
+
+        // go the short way around
+        let mut dtheta = theta2 - theta1;
+        if dtheta > std::f64::consts::PI {
+            dtheta -= 2.0 * std::f64::consts::PI;
+        } else if dtheta < -std::f64::consts::PI {
+            dtheta += 2.0 * std::f64::consts::PI;
+        }
+
+        return from_polar(lerp(r1, r2, t), theta1 + dtheta * t);
+    }
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    return a + (b - a) * t;
+}
+
+fn to_polar(xy: (f64, f64)) -> (f64, f64) {
+    let dx = xy.0 - ILLUMINANT_C_XY.0;
+    let dy = xy.1 - ILLUMINANT_C_XY.1;
+
+    return (dx.hypot(dy), dy.atan2(dx));
+}
+
+fn from_polar(r: f64, theta: f64) -> (f64, f64) {
+    return (
+        ILLUMINANT_C_XY.0 + r * theta.cos(),
+        ILLUMINANT_C_XY.1 + r * theta.sin(),
+    );
+}
+
+/// Convert a Munsell color into CIE xyY (Illuminant C), with Y in percent.
+///
+/// Value is clamped to `[0, 10]` and chroma to be non-negative.
+pub fn munsell_to_xyy(hue: MunsellHue, value: f32, chroma: f32) -> (f64, f64, f64) {
+    let renotation = &*RENOTATION;
+    let value = value.clamp(0.0, 10.0);
+    let chroma = chroma.max(0.0);
+
+    let luminance = renotation.luminance(value);
+
+    // below the darkest tabulated value, keep its chromaticity and only let
+    // the luminance go to zero
+    let (lower, upper) = renotation.value_bounds(value);
+    let (lower, upper) = if lower == 0 {
+        (upper, upper)
+    } else {
+        (lower, upper)
+    };
+
+    let a = renotation.at_value(hue, lower, chroma);
+    if lower == upper {
+        return (a.0, a.1, luminance);
+    }
+
+    // interpolate between values in proportion to luminance, as the
+    // chromaticities of a hue/chroma line vary more evenly with Y than V
+    let b = renotation.at_value(hue, upper, chroma);
+    let ya = renotation.luminances[&lower];
+    let yb = renotation.luminances[&upper];
+    let t = (luminance - ya) / (yb - ya);
+
+    return (lerp(a.0, b.0, t), lerp(a.1, b.1, t), luminance);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renotation::{munsell_to_xyy, ILLUMINANT_C_XY};
+    use crate::MunsellHue;
+
+    #[test]
+    fn tabulated_chips() {
+        let (x, y, luminance) = munsell_to_xyy("5R".parse::<MunsellHue>().unwrap(), 5.0, 10.0);
+        assert!((x - 0.4747).abs() < 1e-6);
+        assert!((y - 0.3227).abs() < 1e-6);
+        assert!((luminance - 19.77).abs() < 1e-6);
+    }
+
+    #[test]
+    fn neutral_axis() {
+        let (x, y, luminance) = munsell_to_xyy("5PB".parse::<MunsellHue>().unwrap(), 7.0, 0.0);
+        assert!((x - ILLUMINANT_C_XY.0).abs() < 1e-6);
+        assert!((y - ILLUMINANT_C_XY.1).abs() < 1e-6);
+        assert!(luminance > 42.0 && luminance < 44.0);
+
+        assert_eq!(munsell_to_xyy(MunsellHue::new(0.0), 0.0, 4.0).2, 0.0);
+    }
+
+    #[test]
+    fn interpolation_is_between_chips() {
+        let (x1, _, _) = munsell_to_xyy("5R".parse::<MunsellHue>().unwrap(), 5.0, 8.0);
+        let (x2, _, _) = munsell_to_xyy("5R".parse::<MunsellHue>().unwrap(), 5.0, 9.0);
+        let (x3, _, _) = munsell_to_xyy("5R".parse::<MunsellHue>().unwrap(), 5.0, 10.0);
+        assert!(x1 < x2 && x2 < x3);
+    }
+}
@@ -0,0 +1,349 @@
+//! The line editing of the `repl` command: a prompt with a history of the
+//! lines entered before, and completion of color names and abbreviations
+//! with the tab key.
+//!
+//! Keys are decoded from the bytes a terminal sends without line buffering,
+//! with [`read_key`]; switching the terminal to that mode, and drawing the
+//! line after every key, is left to the caller.
+
+use std::io::Read;
+
+use crate::dataset::Dataset;
+
+/// A key pressed at the prompt.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Key {
+    Char(char),
+    Backspace,
+    Left,
+    Right,
+    Home,
+    End,
+    Up,
+    Down,
+    Tab,
+    Enter,
+    /// Ctrl-C, which discards the line.
+    Interrupt,
+    /// Ctrl-D, which ends the session on an empty line.
+    Eof,
+}
+
+fn read_byte(input: &mut impl Read) -> std::io::Result<Option<u8>> {
+    let mut byte = [0];
+    return match input.read(&mut byte)? {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    };
+}
+
+/// Read the rest of an escape sequence, after the escape, like `[A` for the
+/// up arrow; `None` for sequences of keys without a meaning here.
+fn read_escape(input: &mut impl Read) -> std::io::Result<Option<Key>> {
+    if !matches!(read_byte(input)?, Some(b'[' | b'O')) {
+        return Ok(None);
+    }
+    let mut parameter = String::new();
+    while let Some(byte) = read_byte(input)? {
+        if byte.is_ascii_digit() || byte == b';' {
+            parameter.push(byte as char);
+            continue;
+        }
+        return Ok(match (byte, parameter.as_str()) {
+            (b'A', _) => Some(Key::Up),
+            (b'B', _) => Some(Key::Down),
+            (b'C', _) => Some(Key::Right),
+            (b'D', _) => Some(Key::Left),
+            (b'H', _) | (b'~', "1" | "7") => Some(Key::Home),
+            (b'F', _) | (b'~', "4" | "8") => Some(Key::End),
+            _ => None,
+        });
+    }
+    return Ok(None);
+}
+
+/// Read the rest of a character encoded in UTF-8, starting with `first`;
+/// `None` if it is not valid.
+fn read_char(input: &mut impl Read, first: u8) -> std::io::Result<Option<char>> {
+    let len = match first {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    };
+    let mut bytes = vec![first];
+    for _ in 1..len {
+        match read_byte(input)? {
+            Some(v) => bytes.push(v),
+            None => return Ok(None),
+        }
+    }
+    return Ok(std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|x| x.chars().next()));
+}
+
+/// Read the next key from a terminal that sends every key as it is pressed,
+/// skipping other control characters and escape sequences; `None` at the
+/// end of the input.
+pub fn read_key(input: &mut impl Read) -> std::io::Result<Option<Key>> {
+    while let Some(byte) = read_byte(input)? {
+        let key = match byte {
+            b'\t' => Some(Key::Tab),
+            b'\r' | b'\n' => Some(Key::Enter),
+            0x7f | 0x08 => Some(Key::Backspace),
+            0x01 => Some(Key::Home),
+            0x03 => Some(Key::Interrupt),
+            0x04 => Some(Key::Eof),
+            0x05 => Some(Key::End),
+            0x1b => read_escape(input)?,
+            0x00..=0x1f => None,
+            _ => read_char(input, byte)?.map(Key::Char),
+        };
+        if let Some(key) = key {
+            return Ok(Some(key));
+        }
+    }
+    return Ok(None);
+}
+
+/// The level 3 names and abbreviations starting with `prefix`, ignoring
+/// case: the names in order of id, then the abbreviations.
+pub fn completions(dataset: &Dataset, prefix: &str) -> Vec<String> {
+    let prefix = prefix.trim_start().to_lowercase();
+    if prefix.is_empty() {
+        return Vec::new();
+    }
+
+    let mut ids = dataset.names().keys().copied().collect::<Vec<u32>>();
+    ids.sort();
+    let names = ids.iter().map(|id| &dataset.names()[id].name);
+    let abbrs = ids.iter().map(|id| &dataset.names()[id].abbr);
+
+    let mut candidates: Vec<String> = Vec::new();
+    for candidate in names.chain(abbrs) {
+        if candidate.to_lowercase().starts_with(&prefix) && !candidates.contains(candidate) {
+            candidates.push(candidate.clone());
+        }
+    }
+    return candidates;
+}
+
+/// The longest start all `candidates` share, ignoring case, as the first of
+/// them writes it.
+fn common_prefix(candidates: &[String]) -> String {
+    let first = match candidates.first() {
+        Some(v) => v,
+        None => return String::new(),
+    };
+    let mut len = first.chars().count();
+    for candidate in candidates[1..].iter() {
+        len = first
+            .chars()
+            .zip(candidate.chars())
+            .take(len)
+            .take_while(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+            .count();
+    }
+    return first.chars().take(len).collect();
+}
+
+/// What to do after a key.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Edit {
+    /// Draw the line again.
+    Redraw,
+    /// The line was entered.
+    Submit(String),
+    /// Tab found several completions that share nothing more than the line:
+    /// list them, then draw the line again.
+    Candidates(Vec<String>),
+    /// End the session.
+    Exit,
+}
+
+/// The line being written at the prompt, and the lines entered before it.
+#[derive(Clone, Debug, Default)]
+pub struct LineEditor {
+    line: Vec<char>,
+    /// The position of the cursor, in characters.
+    cursor: usize,
+    history: Vec<String>,
+    /// The index of the line of the history being shown, and the line that
+    /// was being written before going back to it.
+    recalled: Option<(usize, String)>,
+}
+
+impl LineEditor {
+    pub fn new() -> LineEditor {
+        return LineEditor::default();
+    }
+
+    pub fn line(&self) -> String {
+        return self.line.iter().collect();
+    }
+
+    /// The position of the cursor in the line, in characters.
+    pub fn cursor(&self) -> usize {
+        return self.cursor;
+    }
+
+    /// The lines entered so far, oldest first, without blank lines and
+    /// repetitions of the line before.
+    pub fn history(&self) -> &[String] {
+        return &self.history;
+    }
+
+    fn set_line(&mut self, line: &str) {
+        self.line = line.chars().collect();
+        self.cursor = self.line.len();
+    }
+
+    /// Apply a key to the line, with `complete` giving the completions of a
+    /// line for the tab key, like [`completions`].
+    pub fn key(&mut self, key: Key, complete: impl Fn(&str) -> Vec<String>) -> Edit {
+        match key {
+            Key::Char(c) => {
+                self.line.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            Key::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.line.remove(self.cursor);
+            }
+            Key::Backspace => {}
+            Key::Left => self.cursor = self.cursor.saturating_sub(1),
+            Key::Right => self.cursor = (self.cursor + 1).min(self.line.len()),
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = self.line.len(),
+            Key::Up if !self.history.is_empty() => {
+                let index = match &self.recalled {
+                    Some((i, _)) => i.saturating_sub(1),
+                    None => self.history.len() - 1,
+                };
+                let draft = match self.recalled.take() {
+                    Some((_, draft)) => draft,
+                    None => self.line(),
+                };
+                self.set_line(&self.history[index].clone());
+                self.recalled = Some((index, draft));
+            }
+            Key::Up => {}
+            Key::Down => match self.recalled.take() {
+                Some((i, draft)) if i + 1 < self.history.len() => {
+                    self.set_line(&self.history[i + 1].clone());
+                    self.recalled = Some((i + 1, draft));
+                }
+                Some((_, draft)) => self.set_line(&draft),
+                None => {}
+            },
+            Key::Tab => {
+                let candidates = complete(&self.line());
+                let prefix = common_prefix(&candidates);
+                if prefix.chars().count() > self.line.len() {
+                    self.set_line(&prefix);
+                } else if candidates.len() > 1 {
+                    return Edit::Candidates(candidates);
+                }
+            }
+            Key::Enter => {
+                let line = self.line();
+                if !line.trim().is_empty() && self.history.last() != Some(&line) {
+                    self.history.push(line.clone());
+                }
+                self.set_line("");
+                self.recalled = None;
+                return Edit::Submit(line);
+            }
+            Key::Interrupt => {
+                self.set_line("");
+                self.recalled = None;
+            }
+            Key::Eof if self.line.is_empty() => return Edit::Exit,
+            Key::Eof => {}
+        }
+        return Edit::Redraw;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::repl::{completions, read_key, Edit, Key, LineEditor};
+    use crate::Dataset;
+
+    #[test]
+    fn line_editing() {
+        let dataset = Dataset::bundled();
+        let complete = |line: &str| completions(&dataset, line);
+
+        // arrows, a character of two bytes, and a skipped control character
+        let mut input: &[u8] = b"a\x1b[D\xc3\xa9\x07\x7f\t\r";
+        let mut keys = Vec::new();
+        while let Some(key) = read_key(&mut input).unwrap() {
+            keys.push(key);
+        }
+        assert_eq!(
+            keys,
+            vec![
+                Key::Char('a'),
+                Key::Left,
+                Key::Char('é'),
+                Key::Backspace,
+                Key::Tab,
+                Key::Enter
+            ]
+        );
+
+        let mut editor = LineEditor::new();
+        let type_text = |editor: &mut LineEditor, text: &str| {
+            for c in text.chars() {
+                editor.key(Key::Char(c), complete);
+            }
+        };
+
+        // completes as far as the names agree, then lists them
+        type_text(&mut editor, "vivid pu");
+        assert_eq!(editor.key(Key::Tab, complete), Edit::Redraw);
+        assert_eq!(editor.line(), "Vivid purpl");
+        match editor.key(Key::Tab, complete) {
+            Edit::Candidates(x) => assert_eq!(
+                x,
+                vec!["Vivid purplish blue", "Vivid purple", "Vivid purplish red"]
+            ),
+            x => panic!("{:?}", x),
+        }
+        type_text(&mut editor, "e");
+        editor.key(Key::Tab, complete);
+        assert_eq!(editor.line(), "Vivid purple");
+        assert_eq!(
+            editor.key(Key::Enter, complete),
+            Edit::Submit("Vivid purple".to_string())
+        );
+
+        // editing in the middle of the line
+        type_text(&mut editor, "5R 4/4");
+        editor.key(Key::Home, complete);
+        editor.key(Key::Right, complete);
+        type_text(&mut editor, ".5");
+        assert_eq!(editor.cursor(), 3);
+        editor.key(Key::Enter, complete);
+        editor.key(Key::Enter, complete);
+        assert_eq!(editor.history(), &["Vivid purple", "5.5R 4/4"]);
+
+        // going back through the history, and forward to the draft
+        type_text(&mut editor, "#ab");
+        editor.key(Key::Up, complete);
+        assert_eq!(editor.line(), "5.5R 4/4");
+        editor.key(Key::Up, complete);
+        editor.key(Key::Up, complete);
+        assert_eq!(editor.line(), "Vivid purple");
+        editor.key(Key::Down, complete);
+        editor.key(Key::Down, complete);
+        assert_eq!(editor.line(), "#ab");
+
+        assert_eq!(editor.key(Key::Eof, complete), Edit::Redraw);
+        editor.key(Key::Interrupt, complete);
+        assert_eq!(editor.line(), "");
+        assert_eq!(editor.key(Key::Eof, complete), Edit::Exit);
+    }
+}
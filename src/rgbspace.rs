@@ -0,0 +1,244 @@
+//! The RGB spaces representative colors can be given in: sRGB, or the wider
+//! gamuts of Display P3 and Rec. 2020, which show more of the saturated
+//! colors of the Munsell solid without reducing their chroma.
+//!
+//! Colors in every space are carried in palette's [`Srgb`] type, with the
+//! channels of that space; [`RgbSpace::css_color`] writes them so that
+//! browsers know which space they are in. The space used everywhere is set
+//! once with [`set_rgb_space`], like the adaptation.
+
+use std::sync::OnceLock;
+
+use palette::{LinSrgb, Srgb};
+
+use crate::export::srgb_hex;
+
+static RGB_SPACE: OnceLock<RgbSpace> = OnceLock::new();
+
+/// An RGB space relative to D65, with its primaries and transfer function.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RgbSpace {
+    #[default]
+    Srgb,
+    /// The primaries of DCI-P3 with the transfer function of sRGB, as on
+    /// Apple displays.
+    DisplayP3,
+    /// The primaries and transfer function of ITU-R BT.2020.
+    Rec2020,
+}
+
+type Matrix = [[f32; 3]; 3];
+
+const SRGB_TO_XYZ: Matrix = [
+    [0.4123908, 0.3575843, 0.1804808],
+    [0.212639, 0.7151687, 0.0721923],
+    [0.0193308, 0.1191948, 0.9505322],
+];
+
+const XYZ_TO_SRGB: Matrix = [
+    [3.24097, -1.5373832, -0.4986108],
+    [-0.9692436, 1.8759675, 0.0415551],
+    [0.0556301, -0.203977, 1.0569715],
+];
+
+const P3_TO_XYZ: Matrix = [
+    [0.4865709, 0.2656677, 0.1982173],
+    [0.2289746, 0.6917385, 0.0792869],
+    [0.0, 0.0451134, 1.0439444],
+];
+
+const XYZ_TO_P3: Matrix = [
+    [2.493497, -0.9313836, -0.4027108],
+    [-0.829489, 1.7626641, 0.0236247],
+    [0.0358458, -0.0761724, 0.9568845],
+];
+
+const REC2020_TO_XYZ: Matrix = [
+    [0.636958, 0.1446169, 0.168881],
+    [0.2627002, 0.6779981, 0.0593017],
+    [0.0, 0.0280727, 1.0609851],
+];
+
+const XYZ_TO_REC2020: Matrix = [
+    [1.7166512, -0.3556708, -0.2533663],
+    [-0.6666844, 1.6164812, 0.0157685],
+    [0.0176399, -0.0427706, 0.9421031],
+];
+
+/// The constants of the transfer function of BT.2020.
+const REC2020_ALPHA: f32 = 1.0992968;
+const REC2020_BETA: f32 = 0.018054;
+
+fn multiply(m: &Matrix, v: [f32; 3]) -> [f32; 3] {
+    return [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ];
+}
+
+/// Apply `f` to the magnitude of `x`, keeping its sign, so that colors
+/// outside the gamut round-trip.
+fn signed(x: f32, f: impl Fn(f32) -> f32) -> f32 {
+    return f(x.abs()).copysign(x);
+}
+
+fn rec2020_encode(x: f32) -> f32 {
+    if x < REC2020_BETA {
+        return 4.5 * x;
+    }
+    return REC2020_ALPHA * x.powf(0.45) - (REC2020_ALPHA - 1.0);
+}
+
+fn rec2020_decode(x: f32) -> f32 {
+    if x < 4.5 * REC2020_BETA {
+        return x / 4.5;
+    }
+    return ((x + REC2020_ALPHA - 1.0) / REC2020_ALPHA).powf(1.0 / 0.45);
+}
+
+impl RgbSpace {
+    /// The name of the space in CSS `color()`.
+    pub fn css_name(self) -> &'static str {
+        return match self {
+            RgbSpace::Srgb => "srgb",
+            RgbSpace::DisplayP3 => "display-p3",
+            RgbSpace::Rec2020 => "rec2020",
+        };
+    }
+
+    /// Encode a color given as linear sRGB, with channels that may be
+    /// outside of `[0, 1]`, in this space.
+    pub fn from_linear_srgb(self, color: LinSrgb) -> Srgb {
+        if self == RgbSpace::Srgb {
+            return Srgb::from_linear(color);
+        }
+        let xyz = multiply(&SRGB_TO_XYZ, [color.red, color.green, color.blue]);
+        let [r, g, b] = match self {
+            RgbSpace::DisplayP3 => multiply(&XYZ_TO_P3, xyz),
+            _ => multiply(&XYZ_TO_REC2020, xyz),
+        };
+        let encode = |x: f32| -> f32 {
+            return match self {
+                RgbSpace::DisplayP3 => Srgb::<f32>::from_linear(LinSrgb::new(x, x, x)).red,
+                _ => rec2020_encode(x),
+            };
+        };
+        return Srgb::new(signed(r, encode), signed(g, encode), signed(b, encode));
+    }
+
+    /// A color of sRGB in this space.
+    pub fn from_srgb(self, color: Srgb) -> Srgb {
+        if self == RgbSpace::Srgb {
+            return color;
+        }
+        return self.from_linear_srgb(color.into_linear());
+    }
+
+    /// The inverse of [`RgbSpace::from_linear_srgb`].
+    pub fn to_linear_srgb(self, color: Srgb) -> LinSrgb {
+        if self == RgbSpace::Srgb {
+            return color.into_linear();
+        }
+        let decode = |x: f32| -> f32 {
+            return match self {
+                RgbSpace::DisplayP3 => Srgb::new(x, x, x).into_linear().red,
+                _ => rec2020_decode(x),
+            };
+        };
+        let linear = [
+            signed(color.red, decode),
+            signed(color.green, decode),
+            signed(color.blue, decode),
+        ];
+        let xyz = match self {
+            RgbSpace::DisplayP3 => multiply(&P3_TO_XYZ, linear),
+            _ => multiply(&REC2020_TO_XYZ, linear),
+        };
+        let [r, g, b] = multiply(&XYZ_TO_SRGB, xyz);
+        return LinSrgb::new(r, g, b);
+    }
+
+    /// A color of this space as CSS: a hex code for sRGB, like `#ffc2c5`,
+    /// and `color()` for the others, like `color(display-p3 1 0.765 0.776)`.
+    pub fn css_color(self, color: Srgb) -> String {
+        if self == RgbSpace::Srgb {
+            return srgb_hex(color);
+        }
+        let channel = |x: f32| {
+            format!("{:.4}", x)
+                .trim_end_matches('0')
+                .trim_end_matches('.')
+                .to_string()
+        };
+        return format!(
+            "color({} {} {} {})",
+            self.css_name(),
+            channel(color.red),
+            channel(color.green),
+            channel(color.blue)
+        );
+    }
+}
+
+/// Give representative colors in `space` from now on. Only the first call
+/// has an effect, and only if no color has been computed yet; otherwise
+/// the space already in use is returned as the error.
+pub fn set_rgb_space(space: RgbSpace) -> Result<(), RgbSpace> {
+    return RGB_SPACE.set(space).map_err(|_| rgb_space());
+}
+
+/// The space representative colors are given in: the one given to
+/// [`set_rgb_space`], or sRGB.
+pub fn rgb_space() -> RgbSpace {
+    return *RGB_SPACE.get_or_init(RgbSpace::default);
+}
+
+#[cfg(test)]
+mod test {
+    use palette::{Clamp, LinSrgb, Srgb};
+
+    use crate::rgbspace::{rgb_space, set_rgb_space, RgbSpace};
+
+    #[test]
+    fn wide_gamuts() {
+        // every space has the same white, and round-trips
+        for space in [RgbSpace::Srgb, RgbSpace::DisplayP3, RgbSpace::Rec2020] {
+            let white = space.from_linear_srgb(LinSrgb::new(1.0, 1.0, 1.0));
+            for x in [white.red, white.green, white.blue] {
+                assert!((x - 1.0).abs() < 1e-3, "{:?}", space);
+            }
+            let color = LinSrgb::new(0.8, -0.05, 0.3);
+            let back = space.to_linear_srgb(space.from_linear_srgb(color));
+            assert!((back.red - color.red).abs() < 1e-4, "{:?}", space);
+            assert!((back.green - color.green).abs() < 1e-4, "{:?}", space);
+            assert!((back.blue - color.blue).abs() < 1e-4, "{:?}", space);
+        }
+
+        // the red of sRGB is inside the wider gamuts, and less saturated
+        let red = LinSrgb::new(1.0, 0.0, 0.0);
+        let p3 = RgbSpace::DisplayP3.from_linear_srgb(red);
+        assert!(p3.is_within_bounds());
+        assert!(p3.red < 1.0 && p3.green > 0.1);
+        // and the green of Display P3 is outside sRGB, but inside Rec. 2020
+        let green = RgbSpace::DisplayP3.to_linear_srgb(Srgb::new(0.0, 1.0, 0.0));
+        assert!(!Srgb::from_linear(green).is_within_bounds());
+        assert!(RgbSpace::Rec2020.from_linear_srgb(green).is_within_bounds());
+
+        assert_eq!(
+            RgbSpace::Srgb.css_color(Srgb::new(1.0, 0.0, 0.5)),
+            "#ff0080"
+        );
+        assert_eq!(
+            RgbSpace::DisplayP3.css_color(Srgb::new(1.0, 0.25, 0.0)),
+            "color(display-p3 1 0.25 0)"
+        );
+    }
+
+    #[test]
+    fn set_after_computing() {
+        // computing a color settles the space
+        let current = rgb_space();
+        assert_eq!(set_rgb_space(RgbSpace::Rec2020), Err(current));
+    }
+}
@@ -0,0 +1,131 @@
+//! Fuzzy search over the level 3 names and abbreviations, for finding a
+//! color from a half-remembered name like "viv prplsh blue".
+
+use crate::dataset::Dataset;
+
+/// A level 3 color matching a query, with how well it matches, from 0 to 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchMatch {
+    pub color_id: u32,
+    pub score: f32,
+}
+
+/// Whether the letters of `token` appear in `word` in order, starting with
+/// its first letter, like "prplsh" in "purplish".
+fn is_abbreviation(token: &str, word: &str) -> bool {
+    if token.chars().next() != word.chars().next() {
+        return false;
+    }
+    let mut letters = word.chars();
+    return token.chars().all(|c| letters.any(|x| x == c));
+}
+
+/// How well a lowercase query token matches a lowercase word: fully for the
+/// word itself, less for a prefix of it, and least for its letters in
+/// order, more so the more of the word they cover.
+fn word_score(token: &str, word: &str) -> f32 {
+    let coverage = (token.chars().count() as f32) / (word.chars().count() as f32);
+    if token == word {
+        return 1.0;
+    }
+    if word.starts_with(token) {
+        return 0.6 + 0.4 * coverage;
+    }
+    if is_abbreviation(token, word) {
+        return 0.5 * coverage.min(1.0);
+    }
+    return 0.0;
+}
+
+/// How well a query matches a name and its abbreviation, like "Vivid
+/// purplish blue" and "v.pB": every token scores by its best matching word
+/// or part of the abbreviation, and the total is shared out over the tokens
+/// or the words of the name, whichever are more, so that names with words
+/// left unmatched rank lower.
+fn match_score(tokens: &[String], name: &str, abbr: &str) -> f32 {
+    let words = name
+        .to_lowercase()
+        .split_whitespace()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>();
+    let abbr = abbr.to_lowercase();
+    let abbr_parts = abbr.split('.').collect::<Vec<&str>>();
+
+    let total: f32 = tokens
+        .iter()
+        .map(|token| {
+            if *token == abbr {
+                return 1.0;
+            }
+            let by_abbr = if abbr_parts.contains(&token.as_str()) {
+                0.8
+            } else {
+                0.0
+            };
+            return words
+                .iter()
+                .map(|word| word_score(token, word))
+                .fold(by_abbr, f32::max);
+        })
+        .sum();
+    return total / (tokens.len().max(words.len()) as f32);
+}
+
+impl Dataset {
+    /// Find the level 3 colors whose names or abbreviations best match a
+    /// query of whole or partial words, like "viv prplsh blue", best first,
+    /// at most `limit` of them.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchMatch> {
+        let tokens = query
+            .to_lowercase()
+            .split_whitespace()
+            .map(|x| x.to_string())
+            .collect::<Vec<String>>();
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches = self
+            .names()
+            .iter()
+            .map(|(id, name)| SearchMatch {
+                color_id: *id,
+                score: match_score(&tokens, &name.name, &name.abbr),
+            })
+            .filter(|x| x.score > 0.0)
+            .collect::<Vec<SearchMatch>>();
+        matches.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap()
+                .then(a.color_id.cmp(&b.color_id))
+        });
+        matches.truncate(limit);
+        return matches;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::search::word_score;
+    use crate::Dataset;
+
+    #[test]
+    fn fuzzy_search() {
+        assert_eq!(word_score("blue", "blue"), 1.0);
+        assert!(word_score("viv", "vivid") > word_score("prplsh", "purplish"));
+        assert!(word_score("prplsh", "purplish") > 0.0);
+        assert_eq!(word_score("rd", "green"), 0.0);
+
+        let dataset = Dataset::bundled();
+        let results = dataset.search("viv prplsh blue", 5);
+        assert_eq!(results[0].color_id, 194);
+        assert!(results.len() <= 5);
+        assert!(results.windows(2).all(|x| x[0].score >= x[1].score));
+
+        assert_eq!(dataset.search("viv.Pk", 1)[0].color_id, 1);
+        assert_eq!(dataset.search("blak", 1)[0].color_id, 267);
+        assert!(dataset.search("xyz", 10).is_empty());
+        assert!(dataset.search("  ", 10).is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+//! The ISCC-NBS color solid as a triangle mesh: every color block extruded
+//! into a wedge in cylindrical Munsell coordinates.
+//!
+//! Hue is the angle around the vertical axis, with 5R along +x and hues
+//! increasing counterclockwise when seen from above; chroma is the distance
+//! from the axis and value is the height (+y), all in Munsell units.
+
+use palette::Srgb;
+
+use crate::dataset::{ColorBlock, Dataset};
+use crate::munsell::MunsellHue;
+
+/// Where blocks that extend to `INF` are cut off.
+const MAX_CHROMA: f32 = 16.0;
+const MAX_VALUE: f32 = 10.0;
+
+/// The largest hue arc covered by a single segment of a curved face, in
+/// Munsell hue steps.
+const HUE_STEP: f32 = 1.0;
+
+/// The triangles of one level 3 color.
+#[derive(Clone, Debug)]
+pub struct SolidPart {
+    pub color_id: u32,
+    /// The representative color of the level 3 color.
+    pub color: Srgb,
+    pub positions: Vec<[f32; 3]>,
+    /// Indices into `positions`, wound counterclockwise when seen from
+    /// outside of the part.
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl SolidPart {
+    /// Add a quadrilateral whose corners are given counterclockwise when
+    /// seen from outside.
+    fn push_quad(&mut self, corners: [[f32; 3]; 4]) {
+        let base = self.positions.len() as u32;
+        self.positions.extend_from_slice(&corners);
+        self.triangles.push([base, base + 1, base + 2]);
+        self.triangles.push([base, base + 2, base + 3]);
+    }
+
+    fn push_block(&mut self, dataset: &Dataset, block: &ColorBlock) {
+        let hues = dataset.hues();
+        let chromas = dataset.chromas();
+        let values = dataset.values();
+
+        let hue_begin = hues[block.hues.start].parse::<MunsellHue>().unwrap().raw();
+        let hue_end = hues[block.hues.end].parse::<MunsellHue>().unwrap().raw();
+        let mut hue_width = (hue_end - hue_begin).rem_euclid(100.0);
+        if hue_width == 0.0 {
+            hue_width = 100.0;
+        }
+        let c0: f32 = chromas[block.chromas.start].parse().unwrap();
+        let c1 = chromas[block.chromas.end]
+            .parse::<f32>()
+            .unwrap()
+            .min(MAX_CHROMA);
+        let v0: f32 = values[block.values.start].parse().unwrap();
+        let v1 = values[block.values.end]
+            .parse::<f32>()
+            .unwrap()
+            .min(MAX_VALUE);
+
+        let point = |chroma: f32, angle: f32, value: f32| {
+            [chroma * angle.cos(), value, -chroma * angle.sin()]
+        };
+
+        let segments = (hue_width / HUE_STEP).ceil().max(1.0) as usize;
+        let angles = (0..=segments)
+            .map(|i| {
+                let hue = hue_begin + hue_width * (i as f32) / (segments as f32);
+                MunsellHue::new(hue.rem_euclid(100.0)).to_radians()
+            })
+            .collect::<Vec<f32>>();
+
+        for pair in angles.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+
+            // top and bottom
+            self.push_quad([
+                point(c0, a, v1),
+                point(c1, a, v1),
+                point(c1, b, v1),
+                point(c0, b, v1),
+            ]);
+            self.push_quad([
+                point(c0, a, v0),
+                point(c0, b, v0),
+                point(c1, b, v0),
+                point(c1, a, v0),
+            ]);
+
+            // outer and inner; the inner face vanishes on the neutral axis
+            self.push_quad([
+                point(c1, a, v0),
+                point(c1, b, v0),
+                point(c1, b, v1),
+                point(c1, a, v1),
+            ]);
+            if c0 > 0.0 {
+                self.push_quad([
+                    point(c0, a, v0),
+                    point(c0, a, v1),
+                    point(c0, b, v1),
+                    point(c0, b, v0),
+                ]);
+            }
+        }
+
+        // the sides, unless the block goes all the way around
+        if hue_width < 100.0 {
+            let (a, b) = (angles[0], angles[segments]);
+            self.push_quad([
+                point(c0, a, v0),
+                point(c1, a, v0),
+                point(c1, a, v1),
+                point(c0, a, v1),
+            ]);
+            self.push_quad([
+                point(c0, b, v0),
+                point(c0, b, v1),
+                point(c1, b, v1),
+                point(c1, b, v0),
+            ]);
+        }
+    }
+}
+
+impl Dataset {
+    /// Build a mesh of the color solid, with one part per level 3 color,
+    /// ordered by color id.
+    ///
+    /// `colors` are the representative colors of the level 3 names, indexed
+    /// by `color_id - 1`.
+    pub fn color_solid(&self, colors: &[Srgb]) -> Vec<SolidPart> {
+        let mut ids = self.names().keys().copied().collect::<Vec<u32>>();
+        ids.sort();
+
+        return ids
+            .into_iter()
+            .map(|id| {
+                let mut part = SolidPart {
+                    color_id: id,
+                    color: colors[(id - 1) as usize],
+                    positions: Vec::new(),
+                    triangles: Vec::new(),
+                };
+                for block in self.blocks().iter().filter(|x| x.color_id == id) {
+                    part.push_block(self, block);
+                }
+                part
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::colors::get_mean_colors;
+    use crate::Dataset;
+
+    #[test]
+    fn parts_are_closed_and_outward() {
+        let dataset = Dataset::bundled();
+        let colors = get_mean_colors(&dataset);
+        let solid = dataset.color_solid(&colors);
+        assert_eq!(solid.len(), 267);
+
+        // the signed volume of a closed mesh is positive when its triangles
+        // face outward; the polygonal wedges are slightly smaller than the
+        // round ones
+        let mut total = 0.0;
+        for part in solid.iter() {
+            let mut volume = 0.0;
+            for [a, b, c] in part.triangles.iter() {
+                let [a, b, c] = [a, b, c].map(|i| part.positions[*i as usize]);
+                volume += (a[0] * (b[1] * c[2] - b[2] * c[1])
+                    + a[1] * (b[2] * c[0] - b[0] * c[2])
+                    + a[2] * (b[0] * c[1] - b[1] * c[0]))
+                    / 6.0;
+            }
+            assert!(volume > 0.0, "{}", part.color_id);
+            total += volume;
+        }
+
+        let cylinder = std::f32::consts::PI * 16.0 * 16.0 * 10.0;
+        assert!(total > cylinder * 0.99 && total <= cylinder, "{}", total);
+    }
+}
@@ -0,0 +1,234 @@
+//! A compact form of the blocks for looking up colors: for every hue leaf
+//! and every span between two chroma boundaries, the runs of value spans
+//! of the same color, from black up.
+//!
+//! Instead of trying every block in turn, a [`SpanTable`] finds the leaf,
+//! the chroma span and the run by binary search, and holds one small entry
+//! per run instead of one per cell of the color solid like the
+//! [`crate::lookup::ColorLookupTable`] of [`Dataset::lookup_id`]. `cargo
+//! bench` compares the three.
+//!
+//! It needs only `alloc`, and [`crate::lite`] looks up colors with it
+//! without `std`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[cfg(feature = "std")]
+use crate::dataset::Dataset;
+#[cfg(feature = "std")]
+use crate::munsell::MunsellColor;
+
+/// A run of value spans of one color, up to where the next run begins.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Run {
+    /// The index of the lowest value boundary of the run.
+    value: u8,
+    /// The level 3 color, or 0 where no block covers the run.
+    color_id: u16,
+}
+
+/// The blocks of a dataset, run-length encoded along value.
+pub struct SpanTable {
+    /// The first hue of every leaf, normalized to `[0, 100)`, with the index
+    /// of the leaf, in increasing order of hue.
+    hue_starts: Vec<(f32, usize)>,
+    /// The chroma boundaries, ending in infinity.
+    chroma_points: Vec<f32>,
+    /// The value boundaries, ending in infinity.
+    value_points: Vec<f32>,
+    /// Where the runs of every leaf and chroma span begin in `runs`, by
+    /// `leaf * chroma spans + chroma span`, and where the last ones end.
+    columns: Vec<u32>,
+    runs: Vec<Run>,
+}
+
+/// A block as its color and its hue, chroma and value index ranges, like
+/// [`crate::ColorBlock`].
+pub(crate) type BlockRanges = (u32, Range<usize>, Range<usize>, Range<usize>);
+
+#[cfg(feature = "std")]
+fn parse_points(boundaries: &[String]) -> Vec<f32> {
+    return boundaries.iter().map(|x| x.parse().unwrap()).collect();
+}
+
+/// A hue in `[0, 100)`; `f32::rem_euclid` needs `std`.
+fn normalize_hue(hue: f32) -> f32 {
+    return (hue % 100.0 + 100.0) % 100.0;
+}
+
+impl SpanTable {
+    /// Encode the blocks of a dataset.
+    #[cfg(feature = "std")]
+    pub fn new(dataset: &Dataset) -> Self {
+        let hue_points = dataset
+            .leaves()
+            .iter()
+            .map(|x| x.begin.raw())
+            .collect::<Vec<f32>>();
+        let blocks = dataset
+            .blocks()
+            .iter()
+            .map(|x| {
+                (
+                    x.color_id,
+                    x.hues.clone(),
+                    x.chromas.clone(),
+                    x.values.clone(),
+                )
+            })
+            .collect::<Vec<BlockRanges>>();
+        return Self::from_blocks(
+            &hue_points,
+            parse_points(dataset.chromas()),
+            parse_points(dataset.values()),
+            &blocks,
+        );
+    }
+
+    /// Encode blocks between the given boundaries, with the hues as numbers
+    /// like [`crate::MunsellHue::raw`] and the last chroma and value
+    /// boundaries infinite.
+    pub(crate) fn from_blocks(
+        hue_points: &[f32],
+        chroma_points: Vec<f32>,
+        value_points: Vec<f32>,
+        blocks: &[BlockRanges],
+    ) -> Self {
+        let (chroma_spans, value_spans) = (chroma_points.len() - 1, value_points.len() - 1);
+        assert!(value_spans <= u8::MAX as usize + 1);
+
+        // fill in every cell first, then merge the runs
+        let leaves = hue_points.len();
+        let mut cells = vec![0u16; leaves * chroma_spans * value_spans];
+        for (color_id, hues, chromas, values) in blocks {
+            let mut h = hues.start;
+            while h != hues.end {
+                for c in chromas.clone() {
+                    for v in values.clone() {
+                        cells[(h * chroma_spans + c) * value_spans + v] = *color_id as u16;
+                    }
+                }
+                h = (h + 1) % leaves;
+            }
+        }
+
+        let mut columns = Vec::with_capacity(leaves * chroma_spans + 1);
+        let mut runs = Vec::new();
+        for column in cells.chunks(value_spans) {
+            columns.push(runs.len() as u32);
+            for (v, color_id) in column.iter().enumerate() {
+                if v == 0 || column[v - 1] != *color_id {
+                    runs.push(Run {
+                        value: v as u8,
+                        color_id: *color_id,
+                    });
+                }
+            }
+        }
+        columns.push(runs.len() as u32);
+
+        let mut hue_starts = hue_points
+            .iter()
+            .enumerate()
+            .map(|(h, x)| (normalize_hue(*x), h))
+            .collect::<Vec<(f32, usize)>>();
+        hue_starts.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        return SpanTable {
+            hue_starts,
+            chroma_points,
+            value_points,
+            columns,
+            runs,
+        };
+    }
+
+    /// The number of runs the table holds; the size of the table grows with
+    /// it, rather than with the number of cells between the boundaries.
+    pub fn run_count(&self) -> usize {
+        return self.runs.len();
+    }
+
+    /// Find the id of the level 3 color that a Munsell color falls into,
+    /// like [`Dataset::lookup_id`].
+    #[cfg(feature = "std")]
+    pub fn classify(&self, color: &MunsellColor) -> Option<u32> {
+        return self.classify_point(color.hue.raw(), color.value, color.chroma);
+    }
+
+    /// Find the id of the level 3 color at a hue, like
+    /// [`crate::MunsellHue::raw`], value and chroma.
+    pub fn classify_point(&self, hue: f32, value: f32, chroma: f32) -> Option<u32> {
+        // the leaf before the first hue is the one wrapping around from the
+        // last
+        let hue = normalize_hue(hue);
+        let leaf = match self.hue_starts.partition_point(|x| x.0 <= hue) {
+            0 => self.hue_starts[self.hue_starts.len() - 1].1,
+            i => self.hue_starts[i - 1].1,
+        };
+
+        let c = self.chroma_points.partition_point(|x| *x <= chroma);
+        if c == 0 || c == self.chroma_points.len() {
+            return None;
+        }
+        if value.is_nan() || value < self.value_points[0] {
+            return None;
+        }
+
+        let column = leaf * (self.chroma_points.len() - 1) + c - 1;
+        let runs = &self.runs[self.columns[column] as usize..self.columns[column + 1] as usize];
+        let r = runs.partition_point(|x| self.value_points[x.value as usize] <= value);
+        return match runs[r - 1].color_id {
+            0 => None,
+            id => Some(id as u32),
+        };
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::spans::SpanTable;
+    use crate::{Dataset, MunsellColor, MunsellHue};
+
+    #[test]
+    fn span_lookup() {
+        let dataset = Dataset::bundled();
+        let table = SpanTable::new(&dataset);
+        let cells =
+            dataset.hues().len() * (dataset.chromas().len() - 1) * (dataset.values().len() - 1);
+        assert!(table.run_count() < cells / 2);
+
+        // on and between the boundaries, and past the solid
+        let mut chromas = vec![-1.0, 0.0, 0.25, 40.0];
+        let mut values = vec![-0.5, 0.0, 0.25, 10.0, 10.5];
+        for x in dataset
+            .chromas()
+            .iter()
+            .filter_map(|x| x.parse::<f32>().ok())
+        {
+            chromas.extend([x, x + 0.1]);
+        }
+        for x in dataset
+            .values()
+            .iter()
+            .filter_map(|x| x.parse::<f32>().ok())
+        {
+            values.extend([x, x + 0.1]);
+        }
+        for hue in (0..400).map(|x| MunsellHue::new(x as f32 / 4.0)) {
+            for chroma in chromas.iter().filter(|x| x.is_finite()) {
+                for value in values.iter().filter(|x| x.is_finite()) {
+                    let color = MunsellColor::new(hue, *value, *chroma);
+                    assert_eq!(
+                        table.classify(&color),
+                        dataset.lookup_id(&color),
+                        "{}",
+                        color
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! A summary of a dataset, for reviewing it at a glance and for tracking it
+//! over releases: how its blocks are spread over the colors and the hue
+//! leaves, which boundaries they use, and how much of the Munsell solid each
+//! level 1 family covers.
+
+use serde_json::{json, Value};
+
+use crate::colors::{get_volumes, ColorVolume};
+use crate::dataset::{ColorBlock, Dataset};
+
+/// How many blocks cover a hue leaf.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LeafStats {
+    /// The leaf, like `1R-4R`.
+    pub leaf: String,
+    pub blocks: usize,
+}
+
+/// How many blocks begin and end at a hue boundary.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HueBoundaryStats {
+    pub hue: String,
+    pub begins: usize,
+    pub ends: usize,
+}
+
+/// How much of the Munsell solid the level 3 colors of a level 1 name
+/// cover together.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FamilyVolume {
+    pub level1_id: u32,
+    pub colors: usize,
+    pub volume: f32,
+    /// The share of the whole solid, from 0 to 1.
+    pub fraction: f32,
+}
+
+/// A summary of a dataset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DatasetStats {
+    /// The number of names of levels 1, 2 and 3.
+    pub names: [usize; 3],
+    pub blocks: usize,
+    /// The volume and block count of every level 3 color, indexed by
+    /// `color_id - 1`, like [`get_volumes`].
+    pub colors: Vec<ColorVolume>,
+    /// Every hue leaf, in the order of [`Dataset::hues`].
+    pub leaves: Vec<LeafStats>,
+    /// Every hue boundary, in the order of [`Dataset::hues`].
+    pub hue_boundaries: Vec<HueBoundaryStats>,
+    /// The lowest and highest chroma boundaries used by a block, leaving out
+    /// `INF`.
+    pub chroma_range: (f32, f32),
+    /// The lowest and highest value boundaries used by a block, leaving out
+    /// `INF`.
+    pub value_range: (f32, f32),
+    /// Every level 1 name, in order of id.
+    pub families: Vec<FamilyVolume>,
+}
+
+/// The lowest and highest finite numbers among `boundaries`.
+fn finite_range<'a>(boundaries: impl Iterator<Item = &'a String>) -> (f32, f32) {
+    return boundaries
+        .filter_map(|x| x.parse::<f32>().ok())
+        .filter(|x| x.is_finite())
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), x| {
+            (low.min(x), high.max(x))
+        });
+}
+
+/// Whether a block covers the hue leaf beginning at hue `h`, following its
+/// hue range around the circle.
+fn covers_leaf(block: &ColorBlock, h: usize) -> bool {
+    let (start, end) = (block.hues.start, block.hues.end);
+    if start < end {
+        return h >= start && h < end;
+    } else {
+        return h >= start || h < end;
+    }
+}
+
+impl Dataset {
+    /// Summarize this dataset.
+    pub fn stats(&self) -> DatasetStats {
+        let hues = self.hues();
+        let blocks = self.blocks();
+
+        let leaves = self
+            .leaves()
+            .iter()
+            .map(|leaf| LeafStats {
+                leaf: leaf.to_string(),
+                blocks: blocks.iter().filter(|x| covers_leaf(x, leaf.index)).count(),
+            })
+            .collect();
+
+        let hue_boundaries = hues
+            .iter()
+            .enumerate()
+            .map(|(h, hue)| HueBoundaryStats {
+                hue: hue.clone(),
+                begins: blocks.iter().filter(|x| x.hues.start == h).count(),
+                ends: blocks.iter().filter(|x| x.hues.end == h).count(),
+            })
+            .collect();
+
+        let chromas = self.chromas();
+        let values = self.values();
+        let chroma_range = finite_range(
+            blocks
+                .iter()
+                .flat_map(|x| [&chromas[x.chromas.start], &chromas[x.chromas.end]]),
+        );
+        let value_range = finite_range(
+            blocks
+                .iter()
+                .flat_map(|x| [&values[x.values.start], &values[x.values.end]]),
+        );
+
+        let colors = get_volumes(self);
+        let mut level1_ids = self.level1_names().keys().copied().collect::<Vec<u32>>();
+        level1_ids.sort();
+        let families = level1_ids
+            .into_iter()
+            .map(|level1_id| {
+                let members = self
+                    .names()
+                    .keys()
+                    .filter(|id| self.level_names(**id).unwrap().level2.parent == Some(level1_id))
+                    .map(|id| &colors[(id - 1) as usize])
+                    .collect::<Vec<&ColorVolume>>();
+                return FamilyVolume {
+                    level1_id,
+                    colors: members.len(),
+                    volume: members.iter().map(|x| x.volume).sum(),
+                    fraction: members.iter().map(|x| x.fraction).sum(),
+                };
+            })
+            .collect();
+
+        return DatasetStats {
+            names: [
+                self.level1_names().len(),
+                self.level2_names().len(),
+                self.names().len(),
+            ],
+            blocks: blocks.len(),
+            colors,
+            leaves,
+            hue_boundaries,
+            chroma_range,
+            value_range,
+            families,
+        };
+    }
+}
+
+impl DatasetStats {
+    pub fn to_json(&self) -> Value {
+        return json!({
+            "names": {
+                "level1": self.names[0],
+                "level2": self.names[1],
+                "level3": self.names[2],
+            },
+            "blocks": self.blocks,
+            "colors": self.colors.iter().enumerate().map(|(i, x)| json!({
+                "id": i + 1,
+                "blocks": x.blocks,
+                "volume": x.volume,
+                "fraction": x.fraction,
+            })).collect::<Vec<Value>>(),
+            "leaves": self.leaves.iter().map(|x| json!({
+                "leaf": x.leaf,
+                "blocks": x.blocks,
+            })).collect::<Vec<Value>>(),
+            "hue_boundaries": self.hue_boundaries.iter().map(|x| json!({
+                "hue": x.hue,
+                "begins": x.begins,
+                "ends": x.ends,
+            })).collect::<Vec<Value>>(),
+            "chroma_range": [self.chroma_range.0, self.chroma_range.1],
+            "value_range": [self.value_range.0, self.value_range.1],
+            "families": self.families.iter().map(|x| json!({
+                "level1_id": x.level1_id,
+                "colors": x.colors,
+                "volume": x.volume,
+                "fraction": x.fraction,
+            })).collect::<Vec<Value>>(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Dataset;
+
+    #[test]
+    fn dataset_stats() {
+        let dataset = Dataset::bundled();
+        let stats = dataset.stats();
+        assert_eq!(stats.names, [13, 29, 267]);
+        assert_eq!(stats.blocks, dataset.blocks().len());
+        assert_eq!(
+            stats.colors.iter().map(|x| x.blocks).sum::<usize>(),
+            stats.blocks
+        );
+
+        // every leaf is covered, and every block begins and ends somewhere
+        assert_eq!(stats.leaves.len(), dataset.hues().len());
+        assert!(stats.leaves.iter().all(|x| x.blocks > 0));
+        assert_eq!(stats.leaves[0].leaf, "1R-4R");
+        assert_eq!(
+            stats.hue_boundaries.iter().map(|x| x.begins).sum::<usize>(),
+            stats.blocks
+        );
+        assert_eq!(
+            stats.hue_boundaries.iter().map(|x| x.ends).sum::<usize>(),
+            stats.blocks
+        );
+
+        assert_eq!(stats.chroma_range, (0.0, 15.0));
+        assert_eq!(stats.value_range, (0.0, 8.5));
+        assert_eq!(stats.families.len(), 13);
+        let fraction: f32 = stats.families.iter().map(|x| x.fraction).sum();
+        assert!((fraction - 1.0).abs() < 1e-4);
+
+        let json = stats.to_json();
+        assert_eq!(json["names"]["level3"], 267);
+        assert_eq!(json["leaves"][0]["leaf"], "1R-4R");
+    }
+}
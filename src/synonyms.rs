@@ -0,0 +1,242 @@
+//! Synonyms from the NBS Dictionary of Color Names: the trade and common
+//! names of colors, like "maroon" or "peacock blue", with the level 3
+//! colors they correspond to.
+//!
+//! The dictionary is not included; it is read from a file with a name and
+//! its color ids per line, separated by a comma, like `Peacock blue,173
+//! 174`. A name may stand for several colors, and be given on several lines,
+//! as the dictionary lists it from several sources.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::dataset::Dataset;
+use crate::diagnostic::{Diagnostic, Diagnostics};
+
+/// A name from the dictionary.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Synonym {
+    /// The name as the file first writes it.
+    pub name: String,
+    /// The level 3 colors it stands for, in the order the file gives them.
+    pub ids: Vec<u32>,
+    /// The line of the file it is first given on, counting from 1.
+    pub line: u32,
+}
+
+/// Why a synonym file could not be read.
+#[derive(Debug)]
+pub enum SynonymError {
+    Read(std::io::Error),
+    /// A line that is not a name and color ids.
+    Invalid(String),
+}
+
+impl fmt::Display for SynonymError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            SynonymError::Read(e) => write!(f, "{}", e),
+            SynonymError::Invalid(e) => write!(f, "{}", e),
+        };
+    }
+}
+
+impl std::error::Error for SynonymError {}
+
+/// A name as it is looked up: in lowercase, with single spaces between the
+/// words.
+fn normalize(name: &str) -> String {
+    return name
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase();
+}
+
+/// The synonyms of a synonym file, in the order the file gives them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Synonyms {
+    entries: Vec<Synonym>,
+    /// The index of every entry, by its normalized name.
+    by_name: HashMap<String, usize>,
+}
+
+impl Synonyms {
+    /// Parse a synonym file, with one `name,ids` per line, the ids
+    /// separated by spaces. Blank lines, lines starting with `#` and a
+    /// header line are skipped.
+    pub fn parse(text: &str) -> Result<Self, SynonymError> {
+        let mut synonyms = Synonyms::default();
+        for (i, line) in text.lines().enumerate() {
+            let line_number = (i + 1) as u32;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // names may have commas, ids don't
+            let (name, ids) = match line.rsplit_once(',') {
+                Some((name, ids)) if !name.trim().is_empty() => (name.trim(), ids),
+                _ => {
+                    return Err(SynonymError::Invalid(format!(
+                        "line {} is not 'name,ids'",
+                        line_number
+                    )))
+                }
+            };
+            let ids = match ids
+                .split_whitespace()
+                .map(|x| x.parse::<u32>())
+                .collect::<Result<Vec<u32>, _>>()
+            {
+                Ok(v) if !v.is_empty() => v,
+                // a header
+                _ if i == 0 => continue,
+                _ => {
+                    return Err(SynonymError::Invalid(format!(
+                        "invalid color ids '{}' on line {}",
+                        ids.trim(),
+                        line_number
+                    )))
+                }
+            };
+            synonyms.insert(name, ids, line_number);
+        }
+        return Ok(synonyms);
+    }
+
+    /// Read a synonym file.
+    pub fn load(path: &Path) -> Result<Self, SynonymError> {
+        let text = std::fs::read_to_string(path).map_err(SynonymError::Read)?;
+        return Self::parse(&text);
+    }
+
+    /// Add the colors of a name, to those it already has if it was given
+    /// before.
+    fn insert(&mut self, name: &str, ids: Vec<u32>, line: u32) {
+        let key = normalize(name);
+        match self.by_name.get(&key) {
+            Some(i) => {
+                let entry = &mut self.entries[*i];
+                for id in ids {
+                    if !entry.ids.contains(&id) {
+                        entry.ids.push(id);
+                    }
+                }
+            }
+            None => {
+                self.by_name.insert(key, self.entries.len());
+                self.entries.push(Synonym {
+                    name: name.to_string(),
+                    ids,
+                    line,
+                });
+            }
+        }
+    }
+
+    /// Every synonym, in the order the file first gives them.
+    pub fn entries(&self) -> &[Synonym] {
+        return &self.entries;
+    }
+
+    /// Find a synonym, ignoring case and the whitespace between words.
+    pub fn lookup(&self, name: &str) -> Option<&Synonym> {
+        return self
+            .by_name
+            .get(&normalize(name))
+            .map(|i| &self.entries[*i]);
+    }
+
+    /// The synonyms of a level 3 color, in the order of the file.
+    pub fn of_color(&self, color_id: u32) -> Vec<&Synonym> {
+        return self
+            .entries
+            .iter()
+            .filter(|x| x.ids.contains(&color_id))
+            .collect();
+    }
+
+    /// Check the synonyms against `dataset`: color ids it doesn't have are
+    /// errors.
+    pub fn check(&self, dataset: &Dataset) -> Vec<Diagnostic> {
+        let mut diagnostics = Diagnostics::default();
+        for synonym in self.entries.iter() {
+            let unknown = synonym
+                .ids
+                .iter()
+                .copied()
+                .filter(|id| !dataset.names().contains_key(id))
+                .collect::<Vec<u32>>();
+            if unknown.is_empty() {
+                continue;
+            }
+            let list = unknown
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(", ");
+            let noun = if unknown.len() == 1 { "id" } else { "ids" };
+            let diagnostic = diagnostics
+                .error(
+                    "unknown-synonym-color",
+                    format!(
+                        "Synonym '{}' has level 3 {} {}, which the dataset doesn't have.",
+                        synonym.name, noun, list
+                    ),
+                )
+                .ids(3, &unknown);
+            diagnostic.line = Some(synonym.line);
+        }
+        return diagnostics.list;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::synonyms::Synonyms;
+    use crate::Dataset;
+
+    #[test]
+    fn synonym_lookup() {
+        let synonyms = Synonyms::parse(
+            "name,ids\n\
+             # from two sources\n\
+             Maroon,16 41\n\
+             Peacock  blue,173\n\
+             MAROON,16 44\n\
+             Red, dark,16\n",
+        )
+        .unwrap();
+        assert_eq!(synonyms.entries().len(), 3);
+
+        let maroon = synonyms.lookup("maroon").unwrap();
+        assert_eq!(maroon.name, "Maroon");
+        assert_eq!(maroon.ids, vec![16, 41, 44]);
+        assert_eq!(maroon.line, 3);
+        assert_eq!(synonyms.lookup(" peacock blue").unwrap().ids, vec![173]);
+        assert_eq!(synonyms.lookup("Red, dark").unwrap().ids, vec![16]);
+        assert!(synonyms.lookup("puce").is_none());
+        assert_eq!(
+            synonyms
+                .of_color(16)
+                .iter()
+                .map(|x| x.name.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["Maroon", "Red, dark"]
+        );
+
+        assert!(Synonyms::parse("Maroon,16\nPuce").is_err());
+        assert!(Synonyms::parse("Maroon,16\nPuce,x").is_err());
+        assert!(Synonyms::parse("Maroon,16\n,16").is_err());
+
+        let dataset = Dataset::bundled();
+        assert!(synonyms.check(&dataset).is_empty());
+        let unknown = Synonyms::parse("Maroon,16\nPuce,268 0").unwrap();
+        let diagnostics = unknown.check(&dataset);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].ids, vec![268, 0]);
+        assert_eq!(diagnostics[0].line, Some(2));
+    }
+}
@@ -0,0 +1,200 @@
+//! Color tolerances around the centroids of the level 3 colors: how far a
+//! color may stray from a category's centroid and still be sure to keep
+//! its name, and how far the farthest member of the category is.
+
+use palette::ColorDifference;
+
+use crate::colors::get_mean_munsell;
+use crate::dataset::{ColorBlock, Dataset};
+use crate::munsell::{MunsellColor, MunsellHue};
+
+/// How far past a face of a block to look to see whether it is on the
+/// boundary of its color, in hue, chroma and value steps.
+const NUDGE: f32 = 0.001;
+
+/// Spheres around the centroid of a level 3 color, with radii in CIEDE2000
+/// color difference units.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ToleranceSphere {
+    pub color_id: u32,
+    /// The centroid of the color, as computed by [`get_mean_munsell`].
+    pub centroid: MunsellColor,
+    /// The radius of the largest sphere around the centroid that lies
+    /// entirely within the color. Zero if the centroid falls outside of it.
+    pub inscribed: f32,
+    /// The radius of the smallest sphere around the centroid that contains
+    /// the whole color.
+    pub circumscribed: f32,
+}
+
+/// A point on the surface of a block, and the point just outside of it.
+struct FacePoint {
+    point: MunsellColor,
+    outside: MunsellColor,
+}
+
+/// Sample `samples` × `samples` points on each of the six faces of a block.
+///
+/// Boundaries at `INF` are cut off at chroma 16 and value 10, and faces on
+/// the neutral axis (chroma 0) are left out, since they are inside the
+/// Munsell solid.
+fn face_points(dataset: &Dataset, block: &ColorBlock, samples: usize) -> Vec<FacePoint> {
+    let hues = dataset.hues();
+    let chromas = dataset.chromas();
+    let values = dataset.values();
+
+    let hue_begin = hues[block.hues.start].parse::<MunsellHue>().unwrap().raw();
+    let hue_end = hues[block.hues.end].parse::<MunsellHue>().unwrap().raw();
+    let mut hue_width = (hue_end - hue_begin).rem_euclid(100.0);
+    if hue_width == 0.0 {
+        hue_width = 100.0;
+    }
+    let chroma_begin: f32 = chromas[block.chromas.start].parse().unwrap();
+    let chroma_end = chromas[block.chromas.end].parse::<f32>().unwrap().min(16.0);
+    let value_begin: f32 = values[block.values.start].parse().unwrap();
+    let value_end = values[block.values.end].parse::<f32>().unwrap().min(10.0);
+
+    let step = |i: usize| (i as f32) / ((samples - 1) as f32);
+    let color = |h: f32, v: f32, c: f32| {
+        MunsellColor::new(
+            MunsellHue::new((hue_begin + h * hue_width).rem_euclid(100.0)),
+            v,
+            c,
+        )
+    };
+    let nudge_hue = NUDGE / hue_width;
+    let mut points: Vec<FacePoint> = Vec::new();
+
+    for i in 0..samples {
+        for j in 0..samples {
+            let (a, b) = (step(i), step(j));
+            let value = value_begin + a * (value_end - value_begin);
+            let chroma = chroma_begin + b * (chroma_end - chroma_begin);
+            let hue = b;
+
+            // hue faces
+            for (h, outside_h) in [(0.0, -nudge_hue), (1.0, 1.0 + nudge_hue)] {
+                points.push(FacePoint {
+                    point: color(h, value, chroma),
+                    outside: color(outside_h, value, chroma),
+                });
+            }
+
+            // chroma faces
+            for (c, outside_c) in [
+                (chroma_begin, chroma_begin - NUDGE),
+                (chroma_end, chroma_end + NUDGE),
+            ] {
+                if c == 0.0 {
+                    continue;
+                }
+                points.push(FacePoint {
+                    point: color(hue, value, c),
+                    outside: color(hue, value, outside_c),
+                });
+            }
+
+            // value faces
+            for (v, outside_v) in [
+                (value_begin, value_begin - NUDGE),
+                (value_end, value_end + NUDGE),
+            ] {
+                points.push(FacePoint {
+                    point: color(hue, v, chroma),
+                    outside: color(hue, outside_v, chroma),
+                });
+            }
+        }
+    }
+
+    return points;
+}
+
+impl Dataset {
+    /// Compute the tolerance spheres of a level 3 color, by sampling
+    /// `samples` × `samples` points on each face of its blocks.
+    ///
+    /// Faces cut off at `INF` are not boundaries of the color, so colors
+    /// extending to `INF` have a circumscribed radius that only reaches out
+    /// to chroma 16 and value 10.
+    pub fn tolerance_sphere(&self, color_id: u32, samples: usize) -> ToleranceSphere {
+        let centroid = get_mean_munsell(self)[(color_id - 1) as usize].clone();
+        return self.tolerance_sphere_around(color_id, centroid, samples);
+    }
+
+    /// Like [`Dataset::tolerance_sphere`], for every level 3 color, ordered
+    /// by color id.
+    pub fn tolerance_spheres(&self, samples: usize) -> Vec<ToleranceSphere> {
+        let mut ids = self.names().keys().copied().collect::<Vec<u32>>();
+        ids.sort();
+
+        let centroids = get_mean_munsell(self);
+        return ids
+            .into_iter()
+            .map(|id| {
+                self.tolerance_sphere_around(id, centroids[(id - 1) as usize].clone(), samples)
+            })
+            .collect();
+    }
+
+    fn tolerance_sphere_around(
+        &self,
+        color_id: u32,
+        centroid: MunsellColor,
+        samples: usize,
+    ) -> ToleranceSphere {
+        let center = centroid.to_lab();
+        let centroid_inside = self.lookup_id(&centroid) == Some(color_id);
+
+        let mut inscribed = f32::INFINITY;
+        let mut circumscribed: f32 = 0.0;
+
+        for block in self.blocks().iter().filter(|x| x.color_id == color_id) {
+            for face in face_points(self, block, samples.max(2)) {
+                let distance = center.get_color_difference(&face.point.to_lab());
+                circumscribed = circumscribed.max(distance);
+
+                if self.lookup_id(&face.outside) != Some(color_id) {
+                    inscribed = inscribed.min(distance);
+                }
+            }
+        }
+
+        if !centroid_inside || !inscribed.is_finite() {
+            inscribed = 0.0;
+        }
+
+        return ToleranceSphere {
+            color_id,
+            centroid,
+            inscribed,
+            circumscribed,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Dataset;
+
+    #[test]
+    fn spheres_are_nested() {
+        let dataset = Dataset::bundled();
+
+        for id in [1, 13, 34, 115, 185, 265] {
+            let sphere = dataset.tolerance_sphere(id, 5);
+            assert!(sphere.inscribed > 0.0, "{}", id);
+            assert!(sphere.inscribed < sphere.circumscribed, "{}", id);
+        }
+    }
+
+    #[test]
+    fn more_samples_find_closer_boundaries() {
+        let dataset = Dataset::bundled();
+        let coarse = dataset.tolerance_sphere(34, 3);
+        let fine = dataset.tolerance_sphere(34, 9);
+
+        assert!(fine.inscribed <= coarse.inscribed + 1e-4);
+        assert!(fine.circumscribed >= coarse.circumscribed - 1e-4);
+    }
+}
@@ -0,0 +1,323 @@
+//! Supplementary name files, giving the names of a dataset in another
+//! language, keyed by color id, like `names.de.xml`:
+//!
+//! ```xml
+//! <translation lang="de">
+//!     <name level="1" color="1" name="Rosa" />
+//!     <name color="1" name="Lebhaftes Rosa" />
+//! </translation>
+//! ```
+//!
+//! or `names.fr.json`:
+//!
+//! ```json
+//! { "lang": "fr", "level1": { "1": "Rose" }, "level3": { "1": "Rose vif" } }
+//! ```
+//!
+//! Names without a `level` are level 3 names. The abbreviations are those of
+//! the dataset, in every language.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::dataset::{ColorName, Dataset};
+use crate::diagnostic::{Diagnostic, Diagnostics};
+
+/// The names of a dataset in one language, by level and color id.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Translation {
+    /// The language, as a tag like `de` or `pt-BR`.
+    pub lang: String,
+    pub level1_names: HashMap<u32, String>,
+    pub level2_names: HashMap<u32, String>,
+    pub names: HashMap<u32, String>,
+}
+
+/// Why a name file could not be read.
+#[derive(Debug)]
+pub enum TranslationError {
+    Read(std::io::Error),
+    Xml(roxmltree::Error),
+    Json(serde_json::Error),
+    /// A file that parses but isn't a name file, like one with a name
+    /// without an id.
+    Invalid(String),
+}
+
+impl fmt::Display for TranslationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            TranslationError::Read(e) => write!(f, "{}", e),
+            TranslationError::Xml(e) => write!(f, "{}", e),
+            TranslationError::Json(e) => write!(f, "{}", e),
+            TranslationError::Invalid(e) => write!(f, "{}", e),
+        };
+    }
+}
+
+impl std::error::Error for TranslationError {}
+
+/// Parse a color id, and a level from 1 to 3.
+fn parse_id(level: &str, id: &str) -> Result<(u32, u32), TranslationError> {
+    let level = match level.parse::<u32>() {
+        Ok(v @ 1..=3) => v,
+        _ => {
+            return Err(TranslationError::Invalid(format!(
+                "'{}' is not a level from 1 to 3",
+                level
+            )))
+        }
+    };
+    return match id.parse::<u32>() {
+        Ok(v) => Ok((level, v)),
+        Err(_) => Err(TranslationError::Invalid(format!(
+            "'{}' is not a color id",
+            id
+        ))),
+    };
+}
+
+impl Translation {
+    fn level_mut(&mut self, level: u32) -> &mut HashMap<u32, String> {
+        return match level {
+            1 => &mut self.level1_names,
+            2 => &mut self.level2_names,
+            _ => &mut self.names,
+        };
+    }
+
+    /// Add a name, failing if its id already has one.
+    fn insert(&mut self, level: u32, id: u32, name: &str) -> Result<(), TranslationError> {
+        if self.level_mut(level).insert(id, name.to_string()).is_some() {
+            return Err(TranslationError::Invalid(format!(
+                "level {} id {} is named twice",
+                level, id
+            )));
+        }
+        return Ok(());
+    }
+
+    /// Parse a name file in XML.
+    pub fn parse_xml(text: &str) -> Result<Self, TranslationError> {
+        let doc = roxmltree::Document::parse(text).map_err(TranslationError::Xml)?;
+        let root = doc.root_element();
+        if !root.has_tag_name("translation") {
+            return Err(TranslationError::Invalid(
+                "the root element is not <translation>".into(),
+            ));
+        }
+
+        let mut translation = Translation {
+            lang: root.attribute("lang").unwrap_or_default().to_string(),
+            ..Default::default()
+        };
+        for node in root.children().filter(|n| n.has_tag_name("name")) {
+            let line = doc.text_pos_at(node.range().start).row;
+            let (id, name) = match (node.attribute("color"), node.attribute("name")) {
+                (Some(id), Some(name)) => (id, name),
+                _ => {
+                    return Err(TranslationError::Invalid(format!(
+                        "the <name> on line {} needs a color and a name",
+                        line
+                    )))
+                }
+            };
+            let (level, id) = parse_id(node.attribute("level").unwrap_or("3"), id)?;
+            translation.insert(level, id, name)?;
+        }
+        return Ok(translation);
+    }
+
+    /// Parse a name file in JSON.
+    pub fn parse_json(text: &str) -> Result<Self, TranslationError> {
+        let json: Value = serde_json::from_str(text).map_err(TranslationError::Json)?;
+        let mut translation = Translation {
+            lang: json["lang"].as_str().unwrap_or_default().to_string(),
+            ..Default::default()
+        };
+        for level in 1..=3 {
+            let key = format!("level{}", level);
+            let names = match json.get(&key) {
+                Some(Value::Object(v)) => v,
+                Some(_) => {
+                    return Err(TranslationError::Invalid(format!(
+                        "\"{}\" is not an object",
+                        key
+                    )))
+                }
+                None => continue,
+            };
+            for (id, name) in names {
+                let (level, id) = parse_id(&level.to_string(), id)?;
+                let name = match name.as_str() {
+                    Some(v) => v,
+                    None => {
+                        return Err(TranslationError::Invalid(format!(
+                            "the name of level {} id {} is not a string",
+                            level, id
+                        )))
+                    }
+                };
+                translation.insert(level, id, name)?;
+            }
+        }
+        return Ok(translation);
+    }
+
+    /// Read a name file, in JSON if its name ends in `.json` and in XML
+    /// otherwise.
+    pub fn load(path: &Path) -> Result<Self, TranslationError> {
+        let text = std::fs::read_to_string(path).map_err(TranslationError::Read)?;
+        return match path.extension().and_then(|x| x.to_str()) {
+            Some("json") => Self::parse_json(&text),
+            _ => Self::parse_xml(&text),
+        };
+    }
+
+    /// Check the names against those of `dataset`: names of ids the dataset
+    /// doesn't have, and empty names, are errors; names left untranslated,
+    /// and names given to more than one id of a level, are warnings.
+    pub fn check(&self, dataset: &Dataset) -> Vec<Diagnostic> {
+        let mut diagnostics = Diagnostics::default();
+        for (level, names, canonical) in [
+            (1, &self.level1_names, dataset.level1_names()),
+            (2, &self.level2_names, dataset.level2_names()),
+            (3, &self.names, dataset.names()),
+        ] {
+            let mut ids = names.keys().copied().collect::<Vec<u32>>();
+            ids.sort();
+
+            let mut seen: HashMap<&str, u32> = HashMap::new();
+            for id in ids.iter() {
+                let name = names[id].as_str();
+                if !canonical.contains_key(id) {
+                    diagnostics
+                        .error(
+                            "unknown-translation",
+                            format!(
+                                "Translated name '{}' has level {} id {}, which the dataset doesn't have.",
+                                name, level, id
+                            ),
+                        )
+                        .ids(level, &[*id]);
+                } else if name.trim().is_empty() {
+                    diagnostics
+                        .error(
+                            "empty-translation",
+                            format!("Level {} id {} has an empty translated name.", level, id),
+                        )
+                        .ids(level, &[*id]);
+                } else if let Some(other) = seen.insert(name, *id) {
+                    diagnostics
+                        .warning(
+                            "duplicate-translation",
+                            format!(
+                                "Translated name '{}' is used for both level {} id {} and {}.",
+                                name, level, other, id
+                            ),
+                        )
+                        .ids(level, &[other, *id]);
+                }
+            }
+
+            let mut missing = canonical
+                .keys()
+                .filter(|id| !names.contains_key(id))
+                .copied()
+                .collect::<Vec<u32>>();
+            missing.sort();
+            if !missing.is_empty() {
+                diagnostics
+                    .warning(
+                        "missing-translation",
+                        format!(
+                            "{} of {} level {} names have no '{}' translation, and are shown in English.",
+                            missing.len(),
+                            canonical.len(),
+                            level,
+                            self.lang
+                        ),
+                    )
+                    .ids(level, &missing);
+            }
+        }
+        return diagnostics.list;
+    }
+}
+
+/// Replace the names of a level with their translations, or, where there
+/// is none, with the English name in parentheses.
+fn translate_level(names: &mut HashMap<u32, ColorName>, translated: &HashMap<u32, String>) {
+    for (id, name) in names.iter_mut() {
+        name.name = match translated.get(id) {
+            Some(v) => v.clone(),
+            None => format!("({})", name.name),
+        };
+    }
+}
+
+impl Dataset {
+    /// Use the names of `translation` instead of the English names, at
+    /// every level; names it has no translation for are kept in English,
+    /// in parentheses, so that they stand out from the translated ones.
+    ///
+    /// The abbreviations are kept as they are.
+    pub fn translate(&mut self, translation: &Translation) {
+        translate_level(&mut self.level1_names, &translation.level1_names);
+        translate_level(&mut self.level2_names, &translation.level2_names);
+        translate_level(&mut self.names, &translation.names);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::translation::Translation;
+    use crate::{Dataset, Severity};
+
+    #[test]
+    fn translated_names() {
+        let xml = Translation::parse_xml(
+            "<translation lang=\"de\">\n\
+             <name level=\"1\" color=\"1\" name=\"Rosa\" />\n\
+             <name color=\"1\" name=\"Lebhaftes Rosa\" />\n\
+             </translation>",
+        )
+        .unwrap();
+        let json = Translation::parse_json(
+            r#"{ "lang": "de", "level1": { "1": "Rosa" }, "level3": { "1": "Lebhaftes Rosa" } }"#,
+        )
+        .unwrap();
+        assert_eq!(xml, json);
+        assert_eq!(xml.names[&1], "Lebhaftes Rosa");
+
+        assert!(Translation::parse_xml("<translation><name color=\"1\" /></translation>").is_err());
+        assert!(Translation::parse_json(r#"{ "level3": ["a"] }"#).is_err());
+        assert!(Translation::parse_json(r#"{ "level3": { "x": "a" } }"#).is_err());
+
+        let mut dataset = Dataset::bundled();
+        let diagnostics = xml.check(&dataset);
+        assert!(diagnostics.iter().all(|x| x.severity == Severity::Warning));
+        let missing = diagnostics
+            .iter()
+            .find(|x| x.code == "missing-translation" && x.level == Some(3))
+            .unwrap();
+        assert_eq!(missing.ids.len(), 266);
+
+        let mut unknown = xml.clone();
+        unknown.names.insert(268, "Unbekannt".into());
+        assert!(unknown
+            .check(&dataset)
+            .iter()
+            .any(|x| x.code == "unknown-translation" && x.severity == Severity::Error));
+
+        dataset.translate(&xml);
+        assert_eq!(dataset.names()[&1].name, "Lebhaftes Rosa");
+        assert_eq!(dataset.names()[&1].abbr, "viv.Pk");
+        assert_eq!(dataset.names()[&2].name, "(Strong pink)");
+        assert_eq!(dataset.level1_names()[&1].name, "Rosa");
+        assert_eq!(dataset.lookup_abbr("viv.Pk"), Some(1));
+    }
+}
@@ -0,0 +1,194 @@
+//! A check of the blocks by random sampling: points drawn throughout the
+//! part of the Munsell solid that sRGB can show should each fall into
+//! exactly one block, and classify into that block's color.
+//!
+//! Validation checks every cell between the boundaries; sampling checks
+//! the blocks and the lookup table between them, where mistakes in the
+//! boundary rules or in wrapping around the hue circle would show.
+
+use palette::convert::FromColorUnclamped;
+use palette::{Clamp, Srgb};
+
+use crate::dataset::Dataset;
+use crate::gamut::MAX_CHROMA;
+use crate::munsell::{MunsellColor, MunsellHue};
+use crate::naming::ColorNamingSystem;
+use crate::progress::{no_progress, report, Progress, Stage};
+use crate::random::{Rng, DEFAULT_SEED};
+
+/// What is wrong at a sampled point.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SampleFault {
+    /// No block covers the point.
+    Gap,
+    /// Blocks of more than one color cover the point; these, by id.
+    Overlap(Vec<u32>),
+    /// One block covers the point, but the lookup table has another color
+    /// there, or none.
+    Mismatch {
+        expected: u32,
+        classified: Option<u32>,
+    },
+}
+
+/// A sampled point that doesn't have exactly one color.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaultySample {
+    pub color: MunsellColor,
+    pub fault: SampleFault,
+}
+
+/// The outcome of [`Dataset::sample_coverage`] and [`check_samples`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SampleCoverage {
+    pub samples: usize,
+    /// Every faulty point, in the order they were drawn.
+    pub faults: Vec<FaultySample>,
+}
+
+/// Draw a point uniformly from the part of the Munsell solid within the
+/// sRGB gamut, by drawing from the cylinder up to [`MAX_CHROMA`] until one
+/// fits.
+fn sample_in_gamut(rng: &mut Rng) -> MunsellColor {
+    loop {
+        let hue = MunsellHue::new((100.0 * rng.next_f64()) as f32);
+        let value = (10.0 * rng.next_f64()) as f32;
+        // uniform over the disk, not over the radius
+        let chroma = (MAX_CHROMA as f64 * rng.next_f64().sqrt()) as f32;
+        let color = MunsellColor::new(hue, value, chroma);
+        if Srgb::from_color_unclamped(color.to_display_lch()).is_within_bounds() {
+            return color;
+        }
+    }
+}
+
+/// Check `samples` random points within the sRGB gamut against the blocks
+/// of any naming system, drawing from `rng` and reporting progress through
+/// the classify stage, one unit per hundredth of the samples. A point is a
+/// mismatch where [`ColorNamingSystem::classify`] disagrees with the one
+/// block covering it.
+pub fn check_samples<S: ColorNamingSystem + ?Sized>(
+    system: &S,
+    samples: usize,
+    rng: &mut Rng,
+    progress: Progress,
+) -> SampleCoverage {
+    const UNITS: usize = 100;
+
+    let mut faults = Vec::new();
+    report(progress, Stage::Classify, 0, UNITS);
+    for i in 0..samples {
+        let color = sample_in_gamut(rng);
+        let mut ids = system
+            .blocks()
+            .iter()
+            .filter(|x| system.block_contains(x, &color))
+            .map(|x| x.color_id)
+            .collect::<Vec<u32>>();
+        ids.sort();
+        ids.dedup();
+
+        let fault = match ids[..] {
+            [] => Some(SampleFault::Gap),
+            [id] => match system.classify(&color) {
+                Some(x) if x == id => None,
+                classified => Some(SampleFault::Mismatch {
+                    expected: id,
+                    classified,
+                }),
+            },
+            _ => Some(SampleFault::Overlap(ids)),
+        };
+        if let Some(fault) = fault {
+            faults.push(FaultySample { color, fault });
+        }
+
+        if (i + 1) * UNITS / samples > i * UNITS / samples {
+            report(progress, Stage::Classify, (i + 1) * UNITS / samples, UNITS);
+        }
+    }
+
+    return SampleCoverage { samples, faults };
+}
+
+impl Dataset {
+    /// Check `samples` random points within the sRGB gamut, drawn from an
+    /// [`Rng`] seeded with [`DEFAULT_SEED`].
+    pub fn sample_coverage(&self, samples: usize) -> SampleCoverage {
+        return self.sample_coverage_with_progress(
+            samples,
+            &mut Rng::new(DEFAULT_SEED),
+            &mut no_progress,
+        );
+    }
+
+    /// Like [`Dataset::sample_coverage`], drawing from `rng` and reporting
+    /// progress through the classify stage, one unit per hundredth of the
+    /// samples.
+    pub fn sample_coverage_with_progress(
+        &self,
+        samples: usize,
+        rng: &mut Rng,
+        progress: Progress,
+    ) -> SampleCoverage {
+        return check_samples(self, samples, rng, progress);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dataset::ColorBlock;
+    use crate::random::Rng;
+    use crate::verify::{sample_in_gamut, SampleFault};
+    use crate::Dataset;
+
+    #[test]
+    fn sample_coverage() {
+        let dataset = Dataset::bundled();
+        let coverage = dataset.sample_coverage(500);
+        assert_eq!(coverage.samples, 500);
+        assert!(coverage.faults.is_empty(), "{:?}", coverage.faults);
+
+        // the samples are spread over the hue circle and the values
+        let mut rng = Rng::new(3);
+        let colors = (0..200)
+            .map(|_| sample_in_gamut(&mut rng))
+            .collect::<Vec<_>>();
+        assert!(colors.iter().any(|x| x.hue.raw() < 10.0));
+        assert!(colors.iter().any(|x| x.hue.raw() > 90.0));
+        assert!(colors.iter().any(|x| x.value < 2.0 && x.chroma > 0.0));
+
+        // without every other block there are gaps, and with a copy of every
+        // block in another color, overlaps
+        let with_blocks = |blocks: Vec<ColorBlock>| {
+            return Dataset::from_parts(
+                dataset.metadata().clone(),
+                (
+                    dataset.level1_names().clone(),
+                    dataset.level2_names().clone(),
+                    dataset.names().clone(),
+                ),
+                dataset.hues().to_vec(),
+                dataset.chromas().to_vec(),
+                dataset.values().to_vec(),
+                blocks,
+            );
+        };
+        let holed = with_blocks(dataset.blocks().iter().step_by(2).cloned().collect());
+        let faults = holed.sample_coverage(200).faults;
+        assert!(faults.iter().any(|x| x.fault == SampleFault::Gap));
+        assert!(faults.iter().all(|x| x.fault == SampleFault::Gap));
+
+        let mut blocks = dataset.blocks().to_vec();
+        for block in dataset.blocks() {
+            let mut copy = block.clone();
+            copy.color_id = block.color_id % 267 + 1;
+            blocks.push(copy);
+        }
+        let faults = with_blocks(blocks).sample_coverage(20).faults;
+        assert_eq!(faults.len(), 20);
+        assert!(faults
+            .iter()
+            .all(|x| matches!(&x.fault, SampleFault::Overlap(ids) if ids.len() == 2)));
+    }
+}
@@ -0,0 +1,48 @@
+//! Name lookup for JavaScript, through `wasm-bindgen`, so that web apps
+//! like color pickers can name colors without asking a server.
+
+use lazy_static::lazy_static;
+use palette::Srgb;
+use wasm_bindgen::prelude::*;
+
+use crate::classify::srgb_to_munsell;
+use crate::dataset::Dataset;
+use crate::munsell::MunsellColor;
+
+lazy_static! {
+    static ref DATASET: Dataset = Dataset::bundled();
+}
+
+/// The level 3 name of an 8-bit sRGB color, or `undefined` if it falls
+/// outside the blocks of the dataset.
+#[wasm_bindgen(js_name = nameForRgb)]
+pub fn name_for_rgb(r: u8, g: u8, b: u8) -> Option<String> {
+    let color: Srgb = Srgb::new(r, g, b).into_format();
+    let munsell = srgb_to_munsell(color);
+    return DATASET.lookup(&munsell).map(|x| x.name.clone());
+}
+
+/// The level 3 name of a Munsell color like "7.5YR 6/8", or `undefined` if
+/// it is not a Munsell specification or falls outside the blocks of the
+/// dataset.
+#[wasm_bindgen(js_name = nameForMunsell)]
+pub fn name_for_munsell(spec: &str) -> Option<String> {
+    let color = MunsellColor::parse(spec)?;
+    return DATASET.lookup(&color).map(|x| x.name.clone());
+}
+
+#[cfg(test)]
+mod test {
+    use crate::wasm::{name_for_munsell, name_for_rgb};
+
+    #[test]
+    fn names() {
+        assert_eq!(name_for_rgb(255, 255, 255).as_deref(), Some("White"));
+        assert_eq!(name_for_rgb(0, 0, 0).as_deref(), Some("Black"));
+        assert_eq!(
+            name_for_munsell("7.5YR 6/8").as_deref(),
+            Some("Dark orange yellow")
+        );
+        assert_eq!(name_for_munsell("blue"), None);
+    }
+}